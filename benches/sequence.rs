@@ -0,0 +1,70 @@
+//! Compares reading documents through a `FileSequence` and a `MmapSequence`.
+//!
+//! Run with `cargo bench --features mmap --bench sequence [PATH...]`, the bundled documents
+//! are used without paths. Each measurement is the median of several runs.
+
+use pdf_rs::document::PDFDocument;
+use pdf_rs::error::Result;
+use pdf_rs::options::ParseOptions;
+use pdf_rs::sequence::FileSequence;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// The number of runs of each measurement.
+const RUNS: usize = 7;
+
+/// Opens a document through one of the sequences.
+type Open = fn(&Path) -> Result<PDFDocument>;
+
+fn open_file(path: &Path) -> Result<PDFDocument> {
+    PDFDocument::new_with_options(FileSequence::new(File::open(path)?), ParseOptions::lenient())
+}
+
+fn open_mmap(path: &Path) -> Result<PDFDocument> {
+    // SAFETY: the benchmarked files aren't modified while they are read
+    unsafe { PDFDocument::open_mmap_with_options(path.to_path_buf(), ParseOptions::lenient()) }
+}
+
+/// Reads every object of the xref table, returning the number of objects read.
+fn read_all(document: &mut PDFDocument) -> usize {
+    (0..document.get_xref_slice().len()).filter(|index| matches!(document.read_object(*index), Ok(Some(_)))).count()
+}
+
+/// Returns the median duration of the runs of the closure.
+fn median(mut run: impl FnMut()) -> Duration {
+    let mut durations = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .collect::<Vec<_>>();
+    durations.sort();
+    durations[RUNS / 2]
+}
+
+fn main() {
+    let mut paths = std::env::args().skip(1).filter(|it| !it.starts_with('-')).map(PathBuf::from).collect::<Vec<_>>();
+    if paths.is_empty() {
+        paths = vec![PathBuf::from("document/pdfreference1.0.pdf"), PathBuf::from("document/PDF32000_2008.pdf")];
+    }
+    println!("{:<32} {:<6} {:>12} {:>12} {:>8}", "document", "source", "open", "objects", "count");
+    for path in &paths {
+        let name = path.file_name().map_or(String::new(), |it| it.to_string_lossy().into_owned());
+        for (source, open) in [("file", open_file as Open), ("mmap", open_mmap as Open)] {
+            if let Err(e) = open(path) {
+                println!("{:<32} {:<6} {}", name, source, e);
+                continue;
+            }
+            let open_time = median(|| {
+                open(path).unwrap();
+            });
+            let mut count = 0;
+            let objects_time = median(|| {
+                count = read_all(&mut open(path).unwrap());
+            });
+            println!("{:<32} {:<6} {:>12?} {:>12?} {:>8}", name, source, open_time, objects_time, count);
+        }
+    }
+}
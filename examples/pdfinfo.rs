@@ -0,0 +1,158 @@
+//! Prints the basic information of a PDF document, e.g.
+//!
+//! ```plaintext
+//! cargo run --example pdfinfo -- document/pdfreference1.0.pdf
+//! ```
+//!
+//! With `--xref` the cross-reference table is printed as well.
+use pdf_rs::date::Date;
+use pdf_rs::document::PDFDocument;
+use pdf_rs::error::Result;
+use pdf_rs::objects::PDFObject;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// The number of pages whose size is printed.
+const PAGE_SIZE_LIMIT: usize = 5;
+/// The number of top-level outline titles printed.
+const OUTLINE_LIMIT: usize = 20;
+
+fn main() -> ExitCode {
+    let mut xref = false;
+    let mut path = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--xref" => xref = true,
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => path = None,
+        }
+    }
+    let path = match path {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: pdfinfo [--xref] <file>");
+            return ExitCode::from(2);
+        }
+    };
+    let mut out = std::io::stdout().lock();
+    match run(path.clone(), xref, &mut out) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("pdfinfo: {}: {}", path.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Writes the information of the document at `path` to `out`, followed by
+/// its cross-reference table if `xref` is set.
+pub fn run(path: PathBuf, xref: bool, out: &mut impl Write) -> Result<()> {
+    let mut document = PDFDocument::open(path)?;
+    write_info(&mut document, out)?;
+    if xref {
+        write_xref(&document, out)?;
+    }
+    Ok(())
+}
+
+/// Writes the information of the document to `out`.
+fn write_info(document: &mut PDFDocument, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "PDF version:    {}", document.get_version())?;
+    writeln!(out, "Pages:          {}", document.get_page_num())?;
+    if let Some(describe) = document.get_describe() {
+        let fields = [
+            ("Title", describe.get_title()),
+            ("Author", describe.get_author()),
+            ("Creator", describe.get_creator()),
+            ("Producer", describe.get_producer()),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                writeln!(out, "{:<16}{}", format!("{}:", name), value)?;
+            }
+        }
+        if let Some(date) = describe.get_creation_date() {
+            writeln!(out, "CreationDate:   {}", format_date(date))?;
+        }
+        if let Some(date) = describe.get_mod_date() {
+            writeln!(out, "ModDate:        {}", format_date(date))?;
+        }
+    }
+    let encrypted = if document.get_encrypt_ref().is_some() { "yes" } else { "no" };
+    writeln!(out, "Encrypted:      {}", encrypted)?;
+
+    for (index, page_id) in document.get_page_ids().into_iter().take(PAGE_SIZE_LIMIT).enumerate() {
+        let media_box = document
+            .get_page_tree()
+            .get_inherited_attr(page_id, "MediaBox")
+            .and_then(|it| it.as_array())
+            .map(|values| values.iter().filter_map(|it| it.as_number()).map(|it| it.as_f64()).collect::<Vec<_>>());
+        match media_box {
+            Some(values) if values.len() == 4 => writeln!(
+                out,
+                "Page {} size:    {} x {} pts",
+                index + 1,
+                values[2] - values[0],
+                values[3] - values[1]
+            )?,
+            _ => writeln!(out, "Page {} size:    unknown", index + 1)?,
+        }
+    }
+
+    if let Some(outline) = document.get_outline() {
+        writeln!(out, "Outline:")?;
+        let mut titles = Vec::new();
+        let mut item = outline.get_root_node().and_then(|root| root.get_first_id());
+        while let Some(node) = item.and_then(|id| outline.get_node(id)) {
+            titles.push(node.get_title().unwrap_or(""));
+            item = node.get_next_id();
+        }
+        for title in titles.iter().take(OUTLINE_LIMIT) {
+            writeln!(out, "  {}", title)?;
+        }
+        if titles.len() > OUTLINE_LIMIT {
+            writeln!(out, "  ... ({} more)", titles.len() - OUTLINE_LIMIT)?;
+        }
+    }
+
+    writeln!(out, "Fonts:")?;
+    for font in list_fonts(document)? {
+        writeln!(out, "  {}", font)?;
+    }
+    Ok(())
+}
+
+/// Writes the cross-reference table of the document to `out`.
+fn write_xref(document: &PDFDocument, out: &mut impl Write) -> Result<()> {
+    let mut dump = String::new();
+    // Writing into a string can't fail
+    let _ = document.dump_xref(&mut dump);
+    write!(out, "{}", dump)?;
+    Ok(())
+}
+
+/// Lists the base font names of the font dictionaries of the document.
+fn list_fonts(document: &mut PDFDocument) -> Result<BTreeSet<String>> {
+    let mut fonts = BTreeSet::new();
+    for index in 0..document.get_xref_slice().len() {
+        let object = match document.read_object(index) {
+            Ok(Some(PDFObject::IndirectObject(_, _, object))) => object,
+            // Skip the objects which can't be read
+            _ => continue,
+        };
+        if let Some(dict) = object.as_dict()
+            && dict.named_value_was("Type", "Font")
+            && let Some(name) = dict.get_named_value("BaseFont")
+        {
+            fonts.insert(name.to_string());
+        }
+    }
+    Ok(fonts)
+}
+
+/// Formats a date as milliseconds since the Unix epoch with its time zone.
+fn format_date(date: &Date) -> String {
+    format!("{} ms since epoch (UTC{:+})", date.get_millisecond(), date.get_time_zero())
+}
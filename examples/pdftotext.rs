@@ -0,0 +1,98 @@
+//! Extracts the text of a PDF document page by page, e.g.
+//!
+//! ```plaintext
+//! cargo run --example pdftotext -- --pages 2-5,8 --layout document/pdfreference1.0.pdf out.txt
+//! ```
+//!
+//! Without an output path the text is written to stdout. Pages are terminated by a form feed.
+use pdf_rs::document::PDFDocument;
+use pdf_rs::error::Result;
+use pdf_rs::helper::parse_page_ranges;
+use pdf_rs::page::Page;
+use pdf_rs::text::TextExtractionOptions;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: pdftotext [--pages <ranges>] [--layout] <file> [<output>]";
+
+/// The parsed command line arguments.
+pub struct Args {
+    input: PathBuf,
+    output: Option<PathBuf>,
+    /// The page ranges, e.g. `2-5,8`, all pages if absent.
+    pages: Option<String>,
+    layout: bool,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Some(args) => args,
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let mut err = std::io::stderr().lock();
+    let result = match &args.output {
+        Some(output) => File::create(output)
+            .map_err(Into::into)
+            .and_then(|file| run(&args, &mut BufWriter::new(file), &mut err)),
+        None => run(&args, &mut std::io::stdout().lock(), &mut err),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("pdftotext: {}: {}", args.input.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parses the arguments following the program name, returns `None` if they are malformed.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Option<Args> {
+    let mut input = None;
+    let mut output = None;
+    let mut pages = None;
+    let mut layout = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--layout" => layout = true,
+            "--pages" => pages = Some(args.next()?),
+            arg if arg.starts_with("--") => return None,
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ if output.is_none() => output = Some(PathBuf::from(arg)),
+            _ => return None,
+        }
+    }
+    Some(Args { input: input?, output, pages, layout })
+}
+
+/// Writes the text of the selected pages to `out`, one page at a time.
+///
+/// A page whose text can't be extracted is reported to `err` and skipped.
+pub fn run(args: &Args, out: &mut impl Write, err: &mut impl Write) -> Result<()> {
+    let mut document = PDFDocument::open(args.input.clone())?;
+    let page_ids = document.get_page_ids();
+    let indexes = match &args.pages {
+        Some(pages) => parse_page_ranges(pages, page_ids.len())?,
+        None => (0..page_ids.len()).collect(),
+    };
+    let options = TextExtractionOptions::new().with_layout(args.layout);
+    for index in indexes {
+        match Page::new(page_ids[index]).text_with(&mut document, &options) {
+            Ok(text) => {
+                write!(out, "{}", text)?;
+                if !text.is_empty() && !text.ends_with('\n') {
+                    writeln!(out)?;
+                }
+            }
+            Err(e) => writeln!(err, "pdftotext: page {}: {}", index + 1, e)?,
+        }
+        write!(out, "\x0c")?;
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,12 @@
+//! Feeds arbitrary bytes to [`pdf_rs::parse_object`], which must return instead of panicking.
+//!
+//! ```plaintext
+//! cargo +nightly fuzz run parse_object fuzz/regressions/parse_object
+//! ```
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = pdf_rs::parse_object(data);
+});
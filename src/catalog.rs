@@ -1,31 +1,60 @@
-use crate::constants::{CONTENTS, COUNT, FIRST, KIDS, LAST, NEXT, OUTLINES, PAGES, PREV, TITLE, TYPE};
+use crate::constants::{
+    CONTENTS, COUNT, CROP_BOX, DEST, FIRST, KIDS, LANG, LAST, MARK_INFO, MARKED, MEDIA_BOX, NEXT, OUTLINES,
+    PAGE, PAGES, PREV, RESOURCES, ROTATE, SUSPECTS, TITLE, TYPE, USER_PROPERTIES,
+};
 use crate::encoding::PreDefinedEncoding;
-use crate::error::PDFError::{ObjectAttrMiss, PDFParseError, XrefEntryNotFound};
+use crate::error::PDFError::{CircularReference, MissingNode, ObjectAttrMiss, PDFParseError, XrefEntryNotFound};
 use crate::error::Result;
-use crate::objects::{Dictionary, ObjRefTuple, PDFNumber, PDFObject, XEntry};
-use crate::parser::parse_with_offset;
+use crate::objects::{Dictionary, ObjRefTuple, ObjectId, PDFNumber, PDFObject, XrefTable};
+use crate::options::{PROGRESS_PAGES, Phase, WarningCode};
+use crate::parser::parse_entry;
 use crate::pstr::convert_glyph_text;
 use crate::tokenizer::Tokenizer;
-use crate::utils::xrefs_search;
-use std::collections::HashMap;
+use crate::trace::span;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 
+/// The page attributes a page inherits from its ancestors if it doesn't define them.
+const INHERITABLE_ATTRS: [&str; 4] = [RESOURCES, MEDIA_BOX, CROP_BOX, ROTATE];
+
 macro_rules! mixture_node_id {
     ($obj_num:expr,$gen_num:expr) => {{
-        let node_id = ($obj_num as u64) << 16 | $gen_num as u64;
-        node_id
+        ObjectId::new($obj_num, $gen_num)
     }};
 }
 macro_rules! extract_node_id {
     ($node_id:expr) => {{
-        let obj_num = ($node_id >> 16) as u32;
-        let gen_num = ($node_id & 0xFFFF) as u16;
-        (obj_num, gen_num)
+        $node_id.to_tuple()
     }};
 }
 
-/// Type alias for node identifiers in the page tree.
-pub type NodeId = u64;
+/// Type alias for node identifiers in the page and outline trees, the identifier of the
+/// object defining the node.
+pub type NodeId = ObjectId;
+
+/// Represents the document catalog, the root of the object hierarchy.
+#[derive(Default)]
+pub struct Catalog {
+    /// The attributes of the catalog.
+    attrs: Dictionary,
+    /// The natural language of the document, e.g. `en-US`.
+    lang: Option<String>,
+    /// The tagged PDF flags of the document.
+    mark_info: MarkInfo,
+}
+
+/// The `/MarkInfo` flags of a tagged PDF document.
+///
+/// Absent keys take the defaults defined by the specification, which are all false.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct MarkInfo {
+    /// The document conforms to the tagged PDF conventions.
+    marked: bool,
+    /// The document contains tag suspects, so the structure tree may be unreliable.
+    suspects: bool,
+    /// The structure tree contains user properties.
+    user_properties: bool,
+}
 
 /// Represents a tree structure for organizing pages in a PDF document.
 ///
@@ -37,6 +66,8 @@ pub struct PageTreeArean {
     root_id: NodeId,
     /// A collection of all nodes in the page tree, indexed by their IDs.
     nodes: HashMap<NodeId, PageNode>,
+    /// The IDs of the pages in page order.
+    page_ids: Vec<NodeId>,
 }
 
 /// Represents a node in the page tree structure.
@@ -59,13 +90,15 @@ pub struct PageNode {
     /// Optional ID of the parent node.
     /// This is None for the root node.
     parent_id: Option<NodeId>,
+    /// True if the node is a placeholder for an object which can't be parsed.
+    unreadable: bool,
 }
 
 /// Represents the outline (bookmarks) structure of a PDF document.
 ///
 /// The outline provides a hierarchical navigation structure for the document,
 /// typically displayed in the PDF viewer's sidebar.
-pub(crate) struct OutlineTreeArean {
+pub struct OutlineTreeArean {
     /// The ID of the root node in the outline tree.
     root_id: NodeId,
     /// A collection of all nodes in the outline tree, indexed by their IDs.
@@ -76,7 +109,10 @@ pub(crate) struct OutlineTreeArean {
 ///
 /// Each outline node corresponds to a bookmark entry in the PDF document.
 pub struct OutlineNode {
+    /// The declared `/Count`.
     count: i64,
+    /// The `/Count` recomputed from the items below the node.
+    computed_count: i64,
     /// The title of the bookmark.
     title: Option<String>,
     /// Optional ID of the previous sibling node.
@@ -88,7 +124,11 @@ pub struct OutlineNode {
     /// Optional ID of the last child node.
     last_id: Option<NodeId>,
     /// Optional ID of the parent node.
-    parent_id: Option<NodeId>
+    parent_id: Option<NodeId>,
+    /// The IDs of the children in document order, from `/First` along the `/Next` links.
+    children: Vec<NodeId>,
+    /// The destination of the bookmark, `/Dest`.
+    dest: Option<PDFObject>,
 }
 
 /// Creates a page tree arena from the PDF catalog.
@@ -104,27 +144,36 @@ pub struct OutlineNode {
 ///
 /// # Returns
 ///
-/// A `Result` containing a tuple with the constructed `PageTreeArean` and an optional `Outline`,
-/// or an error if the page catalog cannot be found
+/// A `Result` containing a tuple with the catalog, the constructed `PageTreeArean` and an
+/// optional `Outline`, or an error if the page catalog cannot be found
 pub(crate) fn decode_catalog_data(
     tokenizer: &mut Tokenizer,
     catalog: (u32, u16),
-    xrefs: &[XEntry],
-) -> Result<(PageTreeArean, Option<OutlineTreeArean>)> {
-    let entry = xrefs_search(xrefs, catalog)?;
-    let obj = parse_with_offset(tokenizer, entry.value)?;
-    let catalog_attr = match obj {
-        PDFObject::IndirectObject(_, _, value) => value.to_dict(),
-        _ => return Err(ObjectAttrMiss("PDF catalog not found.")),
+    xrefs: &XrefTable,
+) -> Result<(Catalog, PageTreeArean, Option<OutlineTreeArean>)> {
+    let entry = xrefs.search(catalog)?;
+    let obj = parse_entry(tokenizer, entry)?;
+    let catalog_attr = match obj.into_indirect() {
+        Some((_, _, value)) => value.into_dict(),
+        None => return Err(ObjectAttrMiss("PDF catalog not found.")),
     };
     match catalog_attr {
         Some(dict) => {
+            tokenizer.get_options().report(Phase::Catalog, 1, 1)?;
             let page_tree_arean;
             if let Some(PDFObject::ObjectRef(obj_num, gen_num)) = dict.get(PAGES) {
                 let mut nodes = HashMap::new();
                 let obj_num = *obj_num;
                 let gen_num = *gen_num;
-                build_page_tree(tokenizer, xrefs, (obj_num, gen_num), None, &mut nodes)?;
+                let mut progress = PageTreeProgress { pages: 0, total: None };
+                let span = span!("pdf.page_tree", pages, nodes);
+                let _entered = span.enter();
+                let mut visited = HashSet::from([mixture_node_id!(obj_num, gen_num)]);
+                build_page_tree(tokenizer, xrefs, (obj_num, gen_num), None, &mut nodes, &mut visited, &mut progress)?;
+                span.record("pages", progress.pages);
+                span.record("nodes", nodes.len() as u64);
+                let total = progress.total.unwrap_or(progress.pages);
+                tokenizer.get_options().report(Phase::PageTree, progress.pages, total)?;
                 page_tree_arean = PageTreeArean::new(mixture_node_id!(obj_num, gen_num), nodes);
             } else {
                 return Err(ObjectAttrMiss("Catalog attribute not contain pages attr."));
@@ -134,15 +183,87 @@ pub(crate) fn decode_catalog_data(
                 let mut map = HashMap::<NodeId, OutlineNode>::new();
                 let obj_num = *obj_num;
                 let gen_num = *gen_num;
-                build_outline_tree(tokenizer, xrefs, obj_num, gen_num, None, &mut map)?;
-                outline = Some(OutlineTreeArean::new(mixture_node_id!(obj_num, gen_num), map));
+                let span = span!("pdf.outline", items);
+                let _entered = span.enter();
+                let mut visited = HashSet::new();
+                match build_outline_tree(tokenizer, xrefs, obj_num, gen_num, None, &mut map, &mut visited) {
+                    Ok(_) => {
+                        span.record("items", map.len() as u64);
+                        tokenizer.get_options().report(Phase::Outline, map.len() as u64, 0)?;
+                        let tree = OutlineTreeArean::new(mixture_node_id!(obj_num, gen_num), map);
+                        if tokenizer.get_options().is_check_outline_counts() {
+                            warn_outline_counts(tokenizer, xrefs, &tree);
+                        }
+                        outline = Some(tree);
+                    }
+                    // The outline is optional, drop it instead of the whole document
+                    Err(e) if tokenizer.get_options().is_lenient() && !e.is_abort() => {
+                        let offset = xrefs.search((obj_num, gen_num)).map_or(0, |entry| entry.value);
+                        tokenizer.warn(offset, format!("Outline is unreadable: {}", e));
+                    }
+                    Err(e) => return Err(e),
+                }
             }
-            Ok((page_tree_arean, outline))
+            let lang = match dict.get(LANG) {
+                Some(PDFObject::String(pstr)) => Some(convert_glyph_text(pstr, &PreDefinedEncoding::PDFDoc)),
+                _ => None,
+            };
+            let mark_info = match dict.get(MARK_INFO) {
+                Some(PDFObject::Dict(mark_info)) => MarkInfo::new(mark_info),
+                Some(PDFObject::ObjectRef(obj_num, gen_num)) => {
+                    let reference = (*obj_num, *gen_num);
+                    match xrefs.search(reference).and_then(|entry| parse_entry(tokenizer, entry)) {
+                        Ok(PDFObject::IndirectObject(_, _, value)) => {
+                            value.as_dict().map(MarkInfo::new).unwrap_or_default()
+                        }
+                        Ok(_) => MarkInfo::default(),
+                        Err(e) if e.is_abort() => return Err(e),
+                        // The flags only describe the document, read it as untagged instead of failing
+                        Err(e) => {
+                            let offset = xrefs.search(reference).map_or(0, |entry| entry.value);
+                            tokenizer.warn(offset, format!("Mark information is unreadable: {}", e));
+                            MarkInfo::default()
+                        }
+                    }
+                }
+                _ => MarkInfo::default(),
+            };
+            let catalog = Catalog { attrs: dict, lang, mark_info };
+            Ok((catalog, page_tree_arean, outline))
         }
         _ => Err(ObjectAttrMiss("Catalog attribute not found or not a dict.")),
     }
 }
 
+/// Counts the pages built so far for [`Phase::PageTree`] progress reports.
+struct PageTreeProgress {
+    pages: u64,
+    /// The `/Count` of the root node.
+    total: Option<u64>,
+}
+
+/// Decides whether a page tree node is an intermediate node or a page.
+///
+/// Some writers omit `/Type`, so the structure decides: a node with `/Kids` is an
+/// intermediate node and a node without is a page. A `/Type /Pages` node without `/Kids`
+/// stays an intermediate node, e.g. the root of an empty document, unless it has `/Contents`.
+/// When `/Type` disagrees with the structure, the structure wins so no pages are dropped.
+///
+/// # Returns
+///
+/// True for an intermediate node, and a description of the conflict if `/Type` disagrees
+pub(crate) fn classify_page_node(attrs: &Dictionary) -> (bool, Option<&'static str>) {
+    let has_kids = attrs.get_array_value(KIDS).is_some();
+    match attrs.get_named_value(TYPE) {
+        Some(PAGE) if has_kids => (true, Some("has /Type /Page but /Kids, treated as /Pages")),
+        Some(PAGES) if !has_kids && attrs.contain(CONTENTS) => {
+            (false, Some("has /Type /Pages but /Contents and no /Kids, treated as /Page"))
+        }
+        Some(PAGES) => (true, None),
+        _ => (has_kids, None),
+    }
+}
+
 /// Recursively builds the page tree structure from PDF objects.
 ///
 /// This function traverses the PDF page tree hierarchy, creating nodes for both
@@ -156,19 +277,23 @@ pub(crate) fn decode_catalog_data(
 /// * `obj_ref` - A tuple containing the object number and generation number of the current node
 /// * `parent` - An optional parent node ID
 /// * `nodes` - A mutable reference to the HashMap storing all page nodes
+/// * `visited` - The IDs of the nodes listed so far, the current node included
+/// * `progress` - The pages built so far
 ///
 /// # Returns
 ///
 /// A `Result` indicating success or an error if parsing fails
 fn build_page_tree(
     tokenizer: &mut Tokenizer,
-    xrefs: &[XEntry],
+    xrefs: &XrefTable,
     obj_ref: (u32, u16),
     parent_id: Option<NodeId>,
     nodes: &mut HashMap<NodeId, PageNode>,
+    visited: &mut HashSet<NodeId>,
+    progress: &mut PageTreeProgress,
 ) -> Result<()> {
-    let entry = xrefs_search(xrefs, obj_ref)?;
-    let obj = match parse_with_offset(tokenizer, entry.value)? {
+    let entry = xrefs.search(obj_ref)?;
+    let obj = match parse_entry(tokenizer, entry)? {
         PDFObject::IndirectObject(_, _, value) => *value,
         _ => return Err(XrefEntryNotFound(obj_ref.0, obj_ref.1)),
     };
@@ -176,7 +301,10 @@ fn build_page_tree(
         PDFObject::Dict(dict) => dict,
         _ => return Err(PDFParseError("Page attributes is not a dict")),
     };
-    let is_page_tree = attrs.named_value_was(TYPE, PAGES);
+    let (is_page_tree, conflict) = classify_page_node(&attrs);
+    if let Some(conflict) = conflict {
+        tokenizer.warn(entry.value, format!("Page tree node {} {} {}", obj_ref.0, obj_ref.1, conflict));
+    }
     // If it is not a page tree, then it is a page
     if !is_page_tree {
         let node_id = mixture_node_id!(obj_ref.0, obj_ref.1);
@@ -188,14 +316,27 @@ fn build_page_tree(
             parent_id,
         );
         nodes.insert(node_id, leaf_node);
+        progress.pages += 1;
+        if progress.pages.is_multiple_of(PROGRESS_PAGES) {
+            let total = progress.total.unwrap_or(0);
+            tokenizer.get_options().report(Phase::PageTree, progress.pages, total)?;
+        }
         return Ok(());
     }
-    let count = match attrs.get_u64_num(COUNT) {
-        Some(count) => count,
-        _ => return Err(PDFParseError("Page count not exist or not a number")),
-    };
-    let mut kids = None;
-    if count > 0 {
+    let lenient = tokenizer.get_options().is_lenient();
+    // A missing or non-integer /Count is unknown in lenient mode, it's computed from the kids
+    let declared = attrs.get_u64_num(COUNT);
+    if declared.is_none() && !lenient {
+        return Err(PDFParseError("Page count not exist or not a number"));
+    }
+    if parent_id.is_none() {
+        progress.total = declared;
+    }
+    // An intermediate node without pages, e.g. the root of a document carrying only
+    // attachments, has no kids rather than being a page
+    let mut kids = Some(Vec::new());
+    let has_kids = attrs.get_array_value(KIDS).is_some();
+    if declared.is_none_or(|count| count > 0) || (lenient && has_kids) {
         let arr = match attrs.get_array_value(KIDS) {
             Some(kids) => kids,
             _ => return Err(PDFParseError("Page kids not exist or not an array")),
@@ -204,8 +345,30 @@ fn build_page_tree(
         let tmp = mixture_node_id!(obj_ref.0, obj_ref.1);
         for kid in arr {
             if let PDFObject::ObjectRef(obj_num, gen_num) = kid {
-                children.push(mixture_node_id!(*obj_num, *gen_num));
-                build_page_tree(tokenizer, xrefs, (*obj_num, *gen_num), Some(tmp), nodes)?;
+                let kid_id = mixture_node_id!(*obj_num, *gen_num);
+                // A kid listed before closes a cycle or is shared with another parent,
+                // following it again could recurse forever
+                if !visited.insert(kid_id) {
+                    if !lenient {
+                        return Err(CircularReference { object: ObjectId::new(*obj_num, *gen_num) });
+                    }
+                    let offset = xrefs.search((*obj_num, *gen_num)).map_or(0, |entry| entry.value);
+                    tokenizer.warn(offset, format!(
+                        "Page tree node {} {} is listed more than once, ignored", obj_num, gen_num
+                    ));
+                    continue;
+                }
+                children.push(kid_id);
+                let result = build_page_tree(tokenizer, xrefs, (*obj_num, *gen_num), Some(tmp), nodes, visited, progress);
+                // Substitute a placeholder so the sibling pages still load
+                if let Err(e) = result {
+                    if !lenient || e.is_abort() {
+                        return Err(e);
+                    }
+                    let offset = xrefs.search((*obj_num, *gen_num)).map_or(0, |entry| entry.value);
+                    tokenizer.warn(offset, format!("Page tree node {} {} is unreadable: {}", obj_num, gen_num, e));
+                    nodes.insert(kid_id, PageNode::placeholder(kid_id, Some(tmp)));
+                }
             } else {
                 return Err(PDFParseError(
                     "Page kids not exist or not an object reference",
@@ -214,6 +377,30 @@ fn build_page_tree(
         }
         kids = Some(children)
     };
+    let count = match declared {
+        Some(count) if !lenient => count,
+        _ => {
+            let computed = kids
+                .iter()
+                .flatten()
+                .filter_map(|kid| nodes.get(kid))
+                .map(|kid| if kid.kids.is_some() { kid.count } else { 1 })
+                .sum();
+            match declared {
+                Some(count) if count == computed => {}
+                Some(count) => tokenizer.warn(entry.value, format!(
+                    "Page tree node {} {} declares /Count {} but has {} pages", obj_ref.0, obj_ref.1, count, computed
+                )),
+                None => tokenizer.warn(entry.value, format!(
+                    "Page tree node {} {} has no valid /Count, computed {}", obj_ref.0, obj_ref.1, computed
+                )),
+            }
+            if parent_id.is_none() {
+                progress.total = Some(computed);
+            }
+            computed
+        }
+    };
     let node_id = mixture_node_id!(obj_ref.0, obj_ref.1);
     let page_node = PageNode::new(
         node_id,
@@ -226,16 +413,51 @@ fn build_page_tree(
     Ok(())
 }
 
+/// Warns about every outline node whose declared `/Count` differs from the recomputed one.
+fn warn_outline_counts(tokenizer: &mut Tokenizer, xrefs: &XrefTable, tree: &OutlineTreeArean) {
+    for (node_id, node) in tree.count_mismatches() {
+        let offset = xrefs.search(node_id.to_tuple()).map_or(0, |entry| entry.value);
+        tokenizer.warn_with(offset, WarningCode::OutlineCount, node.count_mismatch_message());
+    }
+}
+
+/// Builds the outline nodes of a sibling chain and their children, returning the IDs of the
+/// siblings in document order.
 fn build_outline_tree(
     tokenizer: &mut Tokenizer,
-    xrefs: &[XEntry],
+    xrefs: &XrefTable,
     obj_num: u32,
     gen_num: u16,
     parent_id: Option<NodeId>,
     map: &mut HashMap<NodeId, OutlineNode>,
-) -> Result<()> {
-    let entry = xrefs_search(xrefs, (obj_num, gen_num))?;
-    let object = parse_with_offset(tokenizer, entry.value)?;
+    visited: &mut HashSet<NodeId>,
+) -> Result<Vec<NodeId>> {
+    // Siblings are walked in a loop, only children recurse, so long bookmark lists don't
+    // exhaust the stack. A `/First` or `/Next` link to a node already seen would loop forever
+    // and ends the chain instead.
+    let mut siblings = Vec::new();
+    let mut next = Some((obj_num, gen_num));
+    while let Some((obj_num, gen_num)) = next
+        && visited.insert(mixture_node_id!(obj_num, gen_num))
+    {
+        siblings.push(mixture_node_id!(obj_num, gen_num));
+        next = build_outline_node(tokenizer, xrefs, obj_num, gen_num, parent_id, map, visited)?;
+    }
+    Ok(siblings)
+}
+
+/// Builds an outline node and its children, returning the reference of the next sibling.
+fn build_outline_node(
+    tokenizer: &mut Tokenizer,
+    xrefs: &XrefTable,
+    obj_num: u32,
+    gen_num: u16,
+    parent_id: Option<NodeId>,
+    map: &mut HashMap<NodeId, OutlineNode>,
+    visited: &mut HashSet<NodeId>,
+) -> Result<Option<(u32, u16)>> {
+    let entry = xrefs.search((obj_num, gen_num))?;
+    let object = parse_entry(tokenizer, entry)?;
     let (_, _, attrs) = match object.as_indirect_object() {
         Some((obj_num, gen_num, obj)) => match obj.as_dict() {
             Some(dict) => (obj_num, gen_num, dict),
@@ -248,13 +470,15 @@ fn build_outline_tree(
     let mut next_id = None;
     let mut first_id = None;
     let mut last_id = None;
+    let mut next = None;
     let node_id = mixture_node_id!(obj_num, gen_num);
     if let Some(PDFObject::ObjectRef(obj_num, gen_num)) = attrs.get(PREV) {
         prev_id = Some(mixture_node_id!(*obj_num, *gen_num));
     }
+    let mut children = Vec::new();
     if let Some(PDFObject::ObjectRef(obj_num, gen_num)) = attrs.get(FIRST) {
         first_id = Some(mixture_node_id!(*obj_num, *gen_num));
-        build_outline_tree(tokenizer, xrefs, *obj_num, *gen_num, Some(node_id), map)?;
+        children = build_outline_tree(tokenizer, xrefs, *obj_num, *gen_num, Some(node_id), map, visited)?;
     }
     if let Some(PDFObject::ObjectRef(obj_num, gen_num)) = attrs.get(LAST) {
         last_id = Some(mixture_node_id!(*obj_num, *gen_num));
@@ -262,10 +486,20 @@ fn build_outline_tree(
 
     if let Some(PDFObject::ObjectRef(obj_num, gen_num)) = attrs.get(NEXT) {
         next_id = Some(mixture_node_id!(*obj_num, *gen_num));
-        build_outline_tree(tokenizer, xrefs, *obj_num, *gen_num, Some(node_id), map)?;
+        next = Some((*obj_num, *gen_num));
     }
 
-    if let Some(PDFObject::String(pstr)) = attrs.get(TITLE){
+    // Titles are text strings, PDFDocEncoding or UTF-16BE with a byte order mark, and may be
+    // indirect; an unreadable title leaves the node untitled
+    let title_object = match attrs.get(TITLE) {
+        Some(PDFObject::ObjectRef(obj_num, gen_num)) => xrefs
+            .search((*obj_num, *gen_num))
+            .and_then(|entry| parse_entry(tokenizer, entry))
+            .ok()
+            .and_then(|it| it.into_indirect().map(|(_, _, obj)| obj)),
+        title_object => title_object.cloned(),
+    };
+    if let Some(PDFObject::String(pstr)) = &title_object {
         title = Some(convert_glyph_text(pstr, &PreDefinedEncoding::PDFDoc));
     }
 
@@ -276,15 +510,61 @@ fn build_outline_tree(
     };
     let outline_node = OutlineNode {
         count,
+        computed_count: 0,
         title,
         prev_id,
         next_id,
         first_id,
         last_id,
         parent_id,
+        children,
+        dest: attrs.get(DEST).cloned(),
     };
     map.insert(node_id, outline_node);
-    Ok(())
+    Ok(next)
+}
+
+impl Catalog {
+    /// Returns the attributes of the catalog.
+    pub fn get_attrs(&self) -> &Dictionary {
+        &self.attrs
+    }
+
+    /// Returns the natural language of the document declared by `/Lang`, e.g. `en-US`.
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    /// Returns the tagged PDF flags declared by `/MarkInfo`.
+    pub fn mark_info(&self) -> MarkInfo {
+        self.mark_info
+    }
+}
+
+impl MarkInfo {
+    fn new(dict: &Dictionary) -> Self {
+        let flag = |key| dict.get(key).and_then(|it| it.as_bool()).unwrap_or(false);
+        Self {
+            marked: flag(MARKED),
+            suspects: flag(SUSPECTS),
+            user_properties: flag(USER_PROPERTIES),
+        }
+    }
+
+    /// Returns true if the document conforms to the tagged PDF conventions.
+    pub fn is_marked(&self) -> bool {
+        self.marked
+    }
+
+    /// Returns true if the document contains tag suspects.
+    pub fn has_suspects(&self) -> bool {
+        self.suspects
+    }
+
+    /// Returns true if the structure tree contains user properties.
+    pub fn has_user_properties(&self) -> bool {
+        self.user_properties
+    }
 }
 
 impl PageTreeArean {
@@ -299,50 +579,173 @@ impl PageTreeArean {
     ///
     /// A new `PageTreeArean` instance
     pub(crate) fn new(root_id: NodeId, nodes: HashMap<NodeId, PageNode>) -> Self {
-        Self { nodes, root_id }
+        let mut page_ids = Vec::new();
+        fetch_kid_page(&nodes, &mut page_ids, root_id);
+        Self { nodes, root_id, page_ids }
+    }
+
+    /// Returns the ID of the root node, which the `/Pages` entry of the catalog refers to.
+    pub fn get_root_id(&self) -> NodeId {
+        self.root_id
     }
 
     /// Returns a reference to the root node of the page tree.
     ///
     /// # Returns
     ///
-    /// A reference to the root `PageNode`
-    pub fn get_root_node(&self) -> Option<&PageNode> {
-        self.nodes.get(&self.root_id)
+    /// A `Result` containing the root `PageNode`, or `MissingNode` if the root wasn't built,
+    /// e.g. the catalog of a partially opened document is unreadable
+    pub fn get_root_node(&self) -> Result<&PageNode> {
+        self.node(self.root_id)
+    }
+
+    /// Returns the node with the given ID, a page or an intermediate `/Pages` node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the node
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the node, or `MissingNode` if the tree has no such node
+    pub fn node(&self, node_id: NodeId) -> Result<&PageNode> {
+        self.nodes.get(&node_id).ok_or(MissingNode(node_id))
+    }
+
+    /// Returns the parent of a node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the node
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parent, `None` for the root, or `MissingNode` if the node
+    /// or its parent isn't in the tree
+    pub fn parent_of(&self, node_id: NodeId) -> Result<Option<&PageNode>> {
+        self.node(node_id)?.parent_id.map(|parent_id| self.node(parent_id)).transpose()
+    }
+
+    /// Returns the children of a node in `/Kids` order.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the node
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the children, empty for a page, or `MissingNode` if the node or
+    /// one of its kids isn't in the tree
+    pub fn children_of(&self, node_id: NodeId) -> Result<Vec<&PageNode>> {
+        match &self.node(node_id)?.kids {
+            Some(kids) => kids.iter().map(|kid| self.node(*kid)).collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the IDs of the pages in page order.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the page IDs, or `MissingNode` if the root isn't in the tree
+    pub fn leaf_ids(&self) -> Result<Vec<NodeId>> {
+        self.get_root_node()?;
+        Ok(self.page_ids.clone())
     }
 
     /// Gets the total number of pages in the document.
     ///
-    /// This method counts all leaf nodes in the tree (nodes with count == 0),
-    /// which represent actual pages rather than intermediate page tree nodes.
+    /// This method counts the leaf nodes reachable from the root, which represent actual
+    /// pages rather than intermediate page tree nodes. Intermediate nodes without kids
+    /// aren't pages, so a document whose root has none has 0 pages.
     ///
     /// # Returns
     ///
     /// The total number of pages in the document
     pub(crate) fn get_page_num(&self) -> usize {
-        self.nodes.values().filter(|node| node.count == 0).count()
+        self.page_ids.len()
     }
 
-    pub(crate) fn get_leaf_page_ids(&self) -> Vec<NodeId> {
-        let root_id = self.root_id;
-        let mut page_node_ids = Vec::new();
-        self.fetch_kid_page(&mut page_node_ids, root_id);
-        page_node_ids
+    /// Returns the IDs of the pages in page order without copying them.
+    pub(crate) fn get_page_id_slice(&self) -> &[NodeId] {
+        &self.page_ids
     }
 
-    pub(crate) fn get_page_node(&self, node_id: NodeId) -> Option<&PageNode> {
-        self.nodes.get(&node_id)
+    /// Looks up an attribute of a node, climbing the ancestors while the node doesn't define it.
+    ///
+    /// Attributes like `/MediaBox` or `/Resources` are often defined once on an ancestor
+    /// `/Pages` node and inherited by the pages. Only the inheritable attributes `/Resources`,
+    /// `/MediaBox`, `/CropBox` and `/Rotate` are looked up on the ancestors, others only on
+    /// the node itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the node to start from
+    /// * `key` - The attribute key
+    ///
+    /// # Returns
+    ///
+    /// The value defined by the nearest node, or `None` if no node on the path defines it
+    pub fn get_inherited_attr(&self, node_id: NodeId, key: &str) -> Option<&PDFObject> {
+        let mut node = self.nodes.get(&node_id);
+        if !INHERITABLE_ATTRS.contains(&key) {
+            return node?.attrs.get(key);
+        }
+        // A node is visited at most once even if the parent links form a cycle
+        for _ in 0..self.nodes.len() {
+            let page_node = node?;
+            if let Some(value) = page_node.attrs.get(key) {
+                return Some(value);
+            }
+            node = page_node.parent_id.and_then(|id| self.nodes.get(&id));
+        }
+        None
     }
 
-    fn fetch_kid_page(&self, page_node_ids: &mut Vec<NodeId>, node_id: NodeId) {
-        if let Some(page_node) = self.nodes.get(&node_id) {
-            if page_node.count == 0 {
-                page_node_ids.push(node_id);
-            } else if let Some(kids) = page_node.kids.as_ref() {
-                for kid_id in kids {
-                    self.fetch_kid_page(page_node_ids, *kid_id);
-                }
+    /// Returns the attributes of a node merged with the inheritable attributes it inherits
+    /// from its ancestors, see [`PageTreeArean::get_inherited_attr`].
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the node
+    ///
+    /// # Returns
+    ///
+    /// The merged attributes, or `None` if the tree has no such node
+    pub fn get_effective_attrs(&self, node_id: NodeId) -> Option<Dictionary> {
+        let mut attrs = self.nodes.get(&node_id)?.attrs.clone();
+        for key in INHERITABLE_ATTRS {
+            if !attrs.contain(key)
+                && let Some(value) = self.get_inherited_attr(node_id, key)
+            {
+                attrs.insert(key, value.clone());
+            }
+        }
+        Some(attrs)
+    }
+
+    /// Counts the leaf pages under a node.
+    pub(crate) fn count_leaf_pages(&self, node_id: NodeId) -> u64 {
+        match self.nodes.get(&node_id) {
+            Some(PageNode { kids: Some(kids), .. }) => {
+                kids.iter().map(|kid| self.count_leaf_pages(*kid)).sum()
+            }
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+}
+
+/// Collects the pages below a node depth-first in `/Kids` order, which is the page order
+/// of viewers.
+fn fetch_kid_page(nodes: &HashMap<NodeId, PageNode>, page_node_ids: &mut Vec<NodeId>, node_id: NodeId) {
+    if let Some(page_node) = nodes.get(&node_id) {
+        if let Some(kids) = page_node.kids.as_ref() {
+            for kid_id in kids {
+                fetch_kid_page(nodes, page_node_ids, *kid_id);
             }
+        } else {
+            page_node_ids.push(node_id);
         }
     }
 }
@@ -391,7 +794,7 @@ fn fmt_page_node(
         if let Some(kids) = page_node.kids.as_ref() {
             let total = kids.len();
             for (i, kid_id) in kids.iter().enumerate() {
-                let is_kid_last = (i == total - 1);
+                let is_kid_last = i == total - 1;
                 fmt_page_node(page_tree_arean, kid_id, f, indent + 1, is_kid_last)?;
             }
         }
@@ -402,7 +805,7 @@ fn fmt_page_node(
 impl Display for PageTreeArean {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let root_id = &self.root_id;
-        if let Some(page_node) = self.nodes.get(&root_id) {
+        if self.nodes.contains_key(root_id) {
             fmt_page_node(self, root_id, f, 0,false)?;
         }
         Ok(())
@@ -413,13 +816,159 @@ impl Display for PageTreeArean {
 
 impl OutlineTreeArean {
     pub(crate) fn new(root_id: NodeId, nodes: HashMap<NodeId, OutlineNode>) -> Self {
-        Self { root_id, nodes }
+        let mut tree = Self { root_id, nodes };
+        tree.reconcile_counts();
+        tree
+    }
+
+    /// Recomputes the `/Count` of every node from the items below it.
+    ///
+    /// An item is open if its declared `/Count` is positive, the root is always open. An
+    /// open node counts its visible descendants, a closed one the negated number of
+    /// descendants visible once it is opened. The tree is walked without recursion, the
+    /// children lists hold every node once.
+    fn reconcile_counts(&mut self) {
+        let root_id = self.root_id;
+        let mut visible = HashMap::<NodeId, i64>::new();
+        let mut pending = vec![(root_id, false)];
+        while let Some((node_id, expanded)) = pending.pop() {
+            let Some(node) = self.nodes.get(&node_id) else {
+                continue;
+            };
+            if !expanded {
+                pending.push((node_id, true));
+                pending.extend(node.children.iter().map(|it| (*it, false)));
+                continue;
+            }
+            let count = node
+                .children
+                .iter()
+                .filter_map(|kid| self.nodes.get(kid).map(|it| (kid, it)))
+                .map(|(kid, it)| 1 + if it.count > 0 { visible[kid] } else { 0 })
+                .sum();
+            visible.insert(node_id, count);
+        }
+        for (node_id, node) in self.nodes.iter_mut() {
+            let count = visible.get(node_id).copied().unwrap_or(0);
+            node.computed_count = if *node_id == root_id || node.count > 0 { count } else { -count };
+        }
+    }
+
+    /// Returns the nodes whose declared `/Count` differs from the recomputed one, sorted
+    /// by their IDs.
+    pub fn count_mismatches(&self) -> Vec<(NodeId, &OutlineNode)> {
+        let mut nodes = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.count != node.computed_count)
+            .map(|(node_id, node)| (*node_id, node))
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|it| it.0);
+        nodes
+    }
+
+    /// Returns the root node of the outline, i.e. the `/Outlines` dictionary.
+    pub fn get_root_node(&self) -> Option<&OutlineNode> {
+        self.nodes.get(&self.root_id)
+    }
+
+    /// Returns the outline node with the given ID.
+    pub fn get_node(&self, node_id: NodeId) -> Option<&OutlineNode> {
+        self.nodes.get(&node_id)
+    }
+
+    /// Returns the IDs of the top-level bookmarks in document order.
+    pub fn root_items(&self) -> &[NodeId] {
+        self.get_root_node().map_or(&[], |it| it.children())
+    }
+}
+
+impl OutlineNode {
+    /// Returns the title of the bookmark.
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the `/Count` of the node, negative if the node is closed.
+    pub fn get_count(&self) -> i64 {
+        self.count
+    }
+
+    /// Returns the `/Count` the node should declare according to the items below it, see
+    /// [`OutlineTreeArean::count_mismatches`].
+    pub fn get_computed_count(&self) -> i64 {
+        self.computed_count
+    }
+
+    /// Describes the node for messages, by its title or as the outline root.
+    pub(crate) fn label(&self) -> String {
+        match &self.title {
+            Some(title) => format!("Outline item \"{}\"", title),
+            None if self.parent_id.is_none() => "Outline root".to_string(),
+            None => "Untitled outline item".to_string(),
+        }
+    }
+
+    /// Describes a `/Count` disagreeing with the computed count, for warnings and findings.
+    pub(crate) fn count_mismatch_message(&self) -> String {
+        format!("{} declares /Count {} but {} is computed", self.label(), self.count, self.computed_count)
+    }
+
+    /// Returns the ID of the previous sibling.
+    pub fn get_prev_id(&self) -> Option<NodeId> {
+        self.prev_id
+    }
+
+    /// Returns the ID of the next sibling.
+    pub fn get_next_id(&self) -> Option<NodeId> {
+        self.next_id
+    }
+
+    /// Returns the IDs of the children in document order, the items reached from `/First`
+    /// along the `/Next` links. An item reached before, through a cyclic link, is left out.
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// Returns the ID of the first child.
+    pub fn get_first_id(&self) -> Option<NodeId> {
+        self.first_id
+    }
+
+    /// Returns the ID of the last child.
+    pub fn get_last_id(&self) -> Option<NodeId> {
+        self.last_id
+    }
+
+    /// Returns the ID of the parent node.
+    pub fn get_parent_id(&self) -> Option<NodeId> {
+        self.parent_id
+    }
+
+    /// Returns the destination of the bookmark, an explicit destination array like
+    /// `[3 0 R /Fit]` or a named destination.
+    pub fn get_dest(&self) -> Option<&PDFObject> {
+        self.dest.as_ref()
     }
 }
 
 impl PageNode {
     pub(crate) fn new(node_id: NodeId, attrs: Dictionary, kids: Option<Vec<NodeId>>, count: u64, parent_id: Option<NodeId>) -> Self {
-        Self { node_id, attrs, kids, count, parent_id }
+        Self { node_id, attrs, kids, count, parent_id, unreadable: false }
+    }
+
+    /// Creates a page placeholder for a page tree node which can't be parsed.
+    pub(crate) fn placeholder(node_id: NodeId, parent_id: Option<NodeId>) -> Self {
+        let attrs = Dictionary::new(HashMap::new());
+        Self { node_id, attrs, kids: None, count: 0, parent_id, unreadable: true }
+    }
+
+    /// Returns true if the node is a placeholder for an object which can't be parsed.
+    ///
+    /// Placeholders only appear in lenient mode, they have no attributes and count as a page
+    /// so the indexes of the sibling pages are preserved.
+    pub fn is_unreadable(&self) -> bool {
+        self.unreadable
     }
     
     pub fn get_page_obj_ref(&self) -> ObjRefTuple {
@@ -454,17 +1003,8 @@ impl PageNode {
         self.count
     }
 
-    pub(crate) fn get_contents(&self)->Vec<ObjRefTuple> {
-        match self.attrs.get(CONTENTS) {
-            Some(PDFObject::ObjectRef(obj_num, gen_num)) => vec![(*obj_num, *gen_num)],
-            Some(PDFObject::Array(arr)) => arr.iter().filter_map(|obj| {
-                if let PDFObject::ObjectRef(obj_num, gen_num) = obj {
-                    Some((*obj_num, *gen_num))
-                } else {
-                    None
-                }
-            }).collect(),
-            _ => vec![]
-        }
+    /// Returns the unresolved `/Contents` of the page.
+    pub(crate) fn get_contents(&self) -> Option<&PDFObject> {
+        self.attrs.get(CONTENTS)
     }
 }
@@ -1,3 +1,9 @@
+// The keys of the public `names` module are defined there once
+pub(crate) use crate::names::{
+    ANNOTS, CONTENTS, COUNT, CROP_BOX, DESTS, ENCRYPT, FILTER, FONT, ID, INFO, KIDS, LENGTH, MEDIA_BOX, NAMES,
+    PAGES, PARENT, RESOURCES, ROOT, ROTATE, SUBTYPE, TYPE, XOBJECT,
+};
+
 /// Macro to define PDF key constants and a utility function for key checking.
 ///
 /// This macro generates a module containing string constants for common PDF keys
@@ -50,38 +56,83 @@ pdf_key!(
     (END_STREAM,"endstream")
 );
 
-/// Key for page tree nodes.
-pub(crate) const KIDS: &str = "Kids";
-/// Key for object type.
-pub(crate) const TYPE: &str = "Type";
 /// Key for previous cross-reference section.
 pub(crate) const PREV: &str = "Prev";
 /// Key for cross-reference table size.
 pub(crate) const SIZE: &str = "Size";
-/// Key for document catalog.
-pub(crate) const ROOT: &str = "Root";
-/// Key for count of pages or objects.
-pub(crate) const COUNT: &str = "Count";
-/// Key for pages object type.
-pub(crate) const PAGES: &str = "Pages";
-/// Key for catalog object type.
-pub(crate) const CATALOG: &str = "Catalog";
+/// Key for the cross-reference stream offset of a hybrid-reference file.
+pub(crate) const XREF_STM: &str = "XRefStm";
+/// Key for the field widths of a cross-reference stream.
+pub(crate) const W: &str = "W";
+/// Key for the subsections of a cross-reference stream.
+pub(crate) const INDEX: &str = "Index";
+pub(crate) const PREDICTOR: &str = "Predictor";
+pub(crate) const COLUMNS: &str = "Columns";
+/// Key for page object type.
+pub(crate) const PAGE: &str = "Page";
 /// Key for outlines.
 pub(crate) const OUTLINES: &str = "Outlines";
-/// Key for stream length.
-pub(crate) const LENGTH: &str = "Length";
 
 pub(crate) const FIRST: &str = "First";
 pub(crate) const LAST: &str = "Last";
 
 pub(crate) const NEXT: &str = "Next";
+pub(crate) const DEST: &str = "Dest";
 
-pub(crate) const INFO: &str = "Info";
+pub(crate) const CF: &str = "CF";
+pub(crate) const CFM: &str = "CFM";
+pub(crate) const STM_F: &str = "StmF";
+pub(crate) const V: &str = "V";
+pub(crate) const STANDARD: &str = "Standard";
 pub(crate) const PRODUCER: &str = "Producer";
 pub(crate) const CREATOR: &str = "Creator";
 pub(crate) const CREATION_DATE: &str = "CreationDate";
 pub(crate) const AUTHOR: &str = "Author";
 pub(crate) const TITLE: &str = "Title";
+pub(crate) const SUBJECT: &str = "Subject";
+pub(crate) const KEYWORDS: &str = "Keywords";
 pub(crate) const MOD_DATE:&str = "ModDate";
-pub(crate) const CONTENTS:&str = "Contents";
-pub(crate) const FILTER:&str = "Filter";
\ No newline at end of file
+pub(crate) const TRAPPED:&str = "Trapped";
+pub(crate) const LANG:&str = "Lang";
+pub(crate) const MARK_INFO:&str = "MarkInfo";
+pub(crate) const MARKED:&str = "Marked";
+pub(crate) const SUSPECTS:&str = "Suspects";
+pub(crate) const USER_PROPERTIES:&str = "UserProperties";
+pub(crate) const IMAGE:&str = "Image";
+pub(crate) const ENCODING:&str = "Encoding";
+pub(crate) const BASE_ENCODING:&str = "BaseEncoding";
+pub(crate) const DIFFERENCES:&str = "Differences";
+pub(crate) const WIDTHS:&str = "Widths";
+pub(crate) const FIRST_CHAR:&str = "FirstChar";
+pub(crate) const TYPE0:&str = "Type0";
+pub(crate) const DECODE_PARMS:&str = "DecodeParms";
+pub(crate) const FORM:&str = "Form";
+pub(crate) const MATRIX:&str = "Matrix";
+pub(crate) const BBOX:&str = "BBox";
+pub(crate) const AP:&str = "AP";
+pub(crate) const AS:&str = "AS";
+pub(crate) const METADATA:&str = "Metadata";
+pub(crate) const OBJ_STM:&str = "ObjStm";
+pub(crate) const XREF_STREAM:&str = "XRef";
+pub(crate) const N:&str = "N";
+pub(crate) const RECT:&str = "Rect";
+pub(crate) const QUAD_POINTS:&str = "QuadPoints";
+pub(crate) const BASE_FONT:&str = "BaseFont";
+pub(crate) const FONT_DESCRIPTOR:&str = "FontDescriptor";
+pub(crate) const FLAGS:&str = "Flags";
+pub(crate) const DESCENDANT_FONTS:&str = "DescendantFonts";
+pub(crate) const FONT_FILES:[&str; 3] = ["FontFile", "FontFile2", "FontFile3"];
+pub(crate) const TYPE1:&str = "Type1";
+pub(crate) const TYPE3:&str = "Type3";
+pub(crate) const WIDTH:&str = "Width";
+pub(crate) const HEIGHT:&str = "Height";
+pub(crate) const COLOR_SPACE:&str = "ColorSpace";
+pub(crate) const PAGE_LABELS:&str = "PageLabels";
+pub(crate) const NUMS:&str = "Nums";
+pub(crate) const S:&str = "S";
+pub(crate) const P:&str = "P";
+pub(crate) const ST:&str = "St";
+pub(crate) const LIMITS:&str = "Limits";
+pub(crate) const STRUCT_TREE_ROOT:&str = "StructTreeRoot";
+pub(crate) const PARENT_TREE:&str = "ParentTree";
+pub(crate) const VERSION:&str = "Version";
@@ -0,0 +1,469 @@
+use crate::catalog::NodeId;
+use crate::constants::{MATRIX, RESOURCES, XOBJECT};
+use crate::document::PDFDocument;
+use crate::error::PDFError::{ContentParseError, EOFError, FormXObjectCycle, FormXObjectTooDeep, PDFParseError};
+use crate::error::Result;
+use crate::helper::read_page_content;
+use crate::name::Name;
+use crate::objects::{Dictionary, ObjRefTuple, PDFObject, Stream};
+use crate::options::{ParseOptions, WarningCode};
+use crate::parser::parser0;
+use crate::sequence::MemorySequence;
+use crate::tokenizer::Token::{Delimiter, Eof, Id, Number};
+use crate::tokenizer::{Token, Tokenizer};
+use std::collections::HashMap;
+
+/// Operator beginning an inline image.
+const BEGIN_INLINE_IMAGE: &str = "BI";
+/// Operator beginning the data of an inline image.
+const INLINE_IMAGE_DATA: &str = "ID";
+
+/// Represents a single operation of a content stream.
+///
+/// A content stream is a sequence of operations, each consisting of zero or more
+/// operands followed by an operator, e.g. `/F1 12 Tf` or `(Hello) Tj`.
+pub struct Operation {
+    /// The operator of the operation, e.g. `Tf`.
+    operator: Name,
+    /// The operands preceding the operator.
+    operands: Vec<PDFObject>,
+}
+
+/// The matrix of a form XObject without `/Matrix`.
+const IDENTITY: [f64; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// A visitor receiving the operations of a content stream in order.
+///
+/// The operations of a form XObject follow the `Do` operation painting it, enclosed by
+/// calls of [`ContentVisitor::begin_form`] and [`ContentVisitor::end_form`].
+pub(crate) trait ContentVisitor {
+    /// Visits a single operation.
+    fn visit(&mut self, operation: &Operation) -> Result<()>;
+
+    /// Enters a form XObject, `matrix` is its `/Matrix` and `resources` its `/Resources`,
+    /// the resources of the enclosing content apply if it has none.
+    fn begin_form(&mut self, _document: &mut PDFDocument, _matrix: [f64; 6], _resources: Option<&PDFObject>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Leaves the form XObject entered last.
+    fn end_form(&mut self) {}
+}
+
+/// Reads operations one by one from decoded content stream bytes.
+pub(crate) struct ContentReader {
+    tokenizer: Tokenizer,
+    /// The index of the page the content belongs to, if known.
+    page: Option<usize>,
+    /// The index of the next operation.
+    operation: usize,
+    /// The number of `q` operations without a matching `Q`.
+    depth: usize,
+    /// The index and offset of the `BT` operation of the open text object.
+    text_object: Option<(usize, u64)>,
+}
+
+impl Operation {
+    /// Returns the operator of the operation.
+    pub fn get_operator(&self) -> &str {
+        &self.operator
+    }
+
+    /// Returns the operands of the operation.
+    pub fn get_operands(&self) -> &[PDFObject] {
+        &self.operands
+    }
+
+    /// Returns the operand at the given index as a name.
+    pub(crate) fn get_name_operand(&self, index: usize) -> Option<&str> {
+        self.operands.get(index).and_then(|it| it.as_name())
+    }
+}
+
+impl ContentReader {
+    /// Creates a new reader over the given decoded content stream bytes.
+    pub(crate) fn new(buf: Vec<u8>) -> Self {
+        Self::with_options(buf, ParseOptions::default(), None)
+    }
+
+    /// Creates a new reader over the content of the page with the given index.
+    pub(crate) fn with_options(mut buf: Vec<u8>, options: ParseOptions, page: Option<usize>) -> Self {
+        // Make sure the last operator is terminated by a white-space
+        buf.push(b'\n');
+        let tokenizer = Tokenizer::with_options(MemorySequence::new(buf), options);
+        Self { tokenizer, page, operation: 0, depth: 0, text_object: None }
+    }
+
+    /// Reads the next operation, returns `None` once the content is exhausted.
+    ///
+    /// A malformed operation fails with a `ContentParseError` locating it, in lenient mode
+    /// it's skipped with a warning and reading resumes after the next operator. Unbalanced
+    /// `q`/`Q` and `BT`/`ET` operations are passed on with a warning.
+    pub(crate) fn next_operation(&mut self) -> Result<Option<Operation>> {
+        loop {
+            let mut offset = 0;
+            let message = match self.read_operation(&mut offset) {
+                Ok(Some(operation)) => {
+                    self.check_balance(&operation, offset);
+                    self.operation += 1;
+                    return Ok(Some(operation));
+                }
+                Ok(None) => {
+                    if let Some((operation, offset)) = self.text_object.take() {
+                        let message = format!(
+                            "{}: text object isn't closed by ET, closed at the end of the content",
+                            self.location(operation, offset)
+                        );
+                        self.tokenizer.warn_with(offset, WarningCode::UnterminatedTextObject, message);
+                    }
+                    return Ok(None);
+                }
+                Err(e) => e.to_string(),
+            };
+            let error = ContentParseError { page: self.page, operation: self.operation, offset, message };
+            if !self.tokenizer.get_options().is_lenient() {
+                return Err(error);
+            }
+            self.tokenizer.warn_with(offset, WarningCode::MalformedOperation, format!("{} (skipped)", error));
+            self.operation += 1;
+            self.skip_to_operator();
+        }
+    }
+
+    /// Tracks the graphics state stack and the text object, warning about a `Q` on an
+    /// empty stack and a `BT` inside a text object.
+    fn check_balance(&mut self, operation: &Operation, offset: u64) {
+        match operation.get_operator() {
+            "q" => self.depth += 1,
+            "Q" if self.depth == 0 => {
+                let message = format!("{}: Q without a matching q, ignored", self.location(self.operation, offset));
+                self.tokenizer.warn_with(offset, WarningCode::GraphicsStackUnderflow, message);
+            }
+            "Q" => self.depth -= 1,
+            "BT" => {
+                if self.text_object.is_some() {
+                    let message = format!("{}: BT inside a text object, treated as ET BT", self.location(self.operation, offset));
+                    self.tokenizer.warn_with(offset, WarningCode::NestedTextObject, message);
+                }
+                self.text_object = Some((self.operation, offset));
+            }
+            "ET" => self.text_object = None,
+            _ => {}
+        }
+    }
+
+    /// Describes the location of an operation like a `ContentParseError` does.
+    fn location(&self, operation: usize, offset: u64) -> String {
+        let page = self.page.map_or(String::new(), |it| format!("Page {}, ", it + 1));
+        format!("{}operation #{} near byte {}", page, operation + 1, offset)
+    }
+
+    /// Drops the tokens up to and including the next operator.
+    fn skip_to_operator(&mut self) {
+        loop {
+            // Every attempt consumes input, so tokens which can't be read are dropped as well
+            match self.tokenizer.next_token() {
+                Ok(Id(_)) | Ok(Eof) | Err(EOFError) => return,
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads the next operation, `offset` is set to the offset of each token before it's read.
+    fn read_operation(&mut self, offset: &mut u64) -> Result<Option<Operation>> {
+        let mut operands = Vec::new();
+        loop {
+            *offset = self.tokenizer.next_token_offset()?;
+            let token = self.tokenizer.next_token()?;
+            match token {
+                Eof => {
+                    if operands.is_empty() {
+                        return Ok(None);
+                    }
+                    return Err(PDFParseError("Content stream operands without operator."));
+                }
+                Id(operator) => {
+                    if operator == BEGIN_INLINE_IMAGE {
+                        let image = self.parse_inline_image()?;
+                        operands.push(image);
+                    }
+                    return Ok(Some(Operation { operator, operands }));
+                }
+                token => operands.push(self.parse_operand(token)?),
+            }
+        }
+    }
+
+    /// Content streams can't contain indirect references, so numbers are taken as they
+    /// are instead of looking ahead for `R`.
+    fn parse_operand(&mut self, token: Token) -> Result<PDFObject> {
+        match token {
+            Number(number) => Ok(PDFObject::Number(number)),
+            Delimiter("[") => {
+                self.tokenizer.enter_nesting()?;
+                let array = self.parse_array();
+                self.tokenizer.leave_nesting();
+                array
+            }
+            token => parser0(&mut self.tokenizer, token),
+        }
+    }
+
+    /// Parses the elements of an array operand up to the closing `]`.
+    fn parse_array(&mut self) -> Result<PDFObject> {
+        let mut elements = Vec::new();
+        loop {
+            let token = self.tokenizer.next_token()?;
+            match token {
+                Delimiter("]") => {
+                    return Ok(PDFObject::Array(elements));
+                }
+                Eof => return Err(EOFError),
+                token => elements.push(self.parse_operand(token)?),
+            }
+        }
+    }
+
+    /// Parses the key-value pairs and the data of an inline image (`BI ... ID ... EI`).
+    fn parse_inline_image(&mut self) -> Result<PDFObject> {
+        let mut entries = HashMap::new();
+        loop {
+            let token = self.tokenizer.next_token()?;
+            match token {
+                Id(ref operator) if operator == INLINE_IMAGE_DATA => break,
+                Eof => return Err(EOFError),
+                token => match self.parse_operand(token)? {
+                    PDFObject::Named(key) => {
+                        let token = self.tokenizer.next_token()?;
+                        let value = self.parse_operand(token)?;
+                        entries.insert(key, value);
+                    }
+                    _ => return Err(PDFParseError("Inline image key must be a name.")),
+                },
+            }
+        }
+        // The image data ends with white-space followed by `EI` and another white-space
+        let mut tail = [b'x'; 3];
+        let limit = self.tokenizer.get_options().get_max_string_len();
+        let data_start = self.tokenizer.position();
+        let range = self.tokenizer.loop_util_limited(&[], limit, data_start, |chr| {
+            let matched = tail[0].is_ascii_whitespace()
+                && tail[1] == b'E'
+                && tail[2] == b'I'
+                && chr.is_ascii_whitespace();
+            tail = [tail[1], tail[2], chr as u8];
+            Ok(matched)
+        })?;
+        let mut buf = self.tokenizer.drain_from_buf(range);
+        // Remove the white-space after `ID` and the trailing ` EI`
+        let end = buf.len().saturating_sub(3);
+        buf.truncate(end);
+        if !buf.is_empty() {
+            buf.remove(0);
+        }
+        Ok(PDFObject::Stream(Stream::new(Dictionary::new(entries), buf)))
+    }
+}
+
+/// Parses decoded content stream bytes into their operations.
+///
+/// # Arguments
+///
+/// * `buf` - The decoded content stream bytes
+///
+/// # Returns
+///
+/// A `Result` containing the operations in order, or an error if the content is malformed
+pub fn parse_content(buf: Vec<u8>) -> Result<Vec<Operation>> {
+    let mut reader = ContentReader::new(buf);
+    let mut operations = Vec::new();
+    while let Some(operation) = reader.next_operation()? {
+        operations.push(operation);
+    }
+    Ok(operations)
+}
+
+/// Feeds every operation of the content of a page to `func`.
+///
+/// Parse errors name the page, the operation and the offset within the decoded content,
+/// the warnings of operations skipped in lenient mode are recorded on the document.
+///
+/// # Arguments
+///
+/// * `document` - The document the page belongs to
+/// * `page_id` - The ID of the page
+/// * `func` - The function receiving the operations
+///
+/// # Returns
+///
+/// A `Result` indicating success or the first error raised by reading, parsing or by `func`
+pub(crate) fn for_each_page_operation<F>(document: &mut PDFDocument, page_id: NodeId, func: F) -> Result<()>
+where
+    F: FnMut(&mut PDFDocument, Operation) -> Result<()>,
+{
+    let content = read_page_content(document, page_id)?;
+    let page = document.get_page_ids().iter().position(|it| *it == page_id);
+    let reader = ContentReader::with_options(content, document.get_options().clone(), page);
+    let obj_ref = document.get_page_node(page_id).map(|it| it.get_page_obj_ref());
+    read_operations(document, reader, obj_ref, func)
+}
+
+/// Feeds the operations of a reader to `func`, then records the warnings of the reader
+/// on the document as warnings about the object the content belongs to.
+fn read_operations<F>(
+    document: &mut PDFDocument,
+    mut reader: ContentReader,
+    obj_ref: Option<ObjRefTuple>,
+    mut func: F,
+) -> Result<()>
+where
+    F: FnMut(&mut PDFDocument, Operation) -> Result<()>,
+{
+    let mut read = || {
+        while let Some(operation) = reader.next_operation()? {
+            func(document, operation)?;
+        }
+        Ok(())
+    };
+    let result = read();
+    if let Some(obj_ref) = obj_ref {
+        for warning in reader.tokenizer.get_warnings() {
+            document.warn_about_with(obj_ref, warning.get_code(), warning.get_message().to_string());
+        }
+    }
+    result
+}
+
+/// Feeds every operation of the content of a page to the visitor, see
+/// [`for_each_page_operation`], following the form XObjects painted by `Do`.
+pub(crate) fn visit_page_content(
+    document: &mut PDFDocument,
+    page_id: NodeId,
+    visitor: &mut impl ContentVisitor,
+) -> Result<()> {
+    let xobjects = document.read_page_resource_refs(page_id, XOBJECT)?;
+    let mut forms = Vec::new();
+    for_each_page_operation(document, page_id, |document, operation| {
+        visitor.visit(&operation)?;
+        visit_form(document, &operation, &xobjects, visitor, &mut forms)
+    })
+}
+
+/// Feeds the operations of the form XObject painted by a `Do` operation to the visitor.
+///
+/// A form painting itself, directly or through nested forms, fails with a
+/// `FormXObjectCycle` error and forms nested deeper than the limit of the parse options
+/// with a `FormXObjectTooDeep` error. In lenient mode such a form is skipped with a warning.
+///
+/// # Arguments
+///
+/// * `document` - The document the content belongs to
+/// * `operation` - The operation, anything but `Do` is ignored
+/// * `xobjects` - The XObject resources of the enclosing content by name
+/// * `visitor` - The visitor
+/// * `forms` - The references of the enclosing forms
+///
+/// # Returns
+///
+/// A `Result` indicating success or the first error raised by reading, parsing or the visitor
+fn visit_form(
+    document: &mut PDFDocument,
+    operation: &Operation,
+    xobjects: &HashMap<String, ObjRefTuple>,
+    visitor: &mut impl ContentVisitor,
+    forms: &mut Vec<ObjRefTuple>,
+) -> Result<()> {
+    if operation.get_operator() != "Do" {
+        return Ok(());
+    }
+    match operation.get_name_operand(0).and_then(|name| xobjects.get(name)) {
+        Some(obj_ref) => visit_form_xobject(document, *obj_ref, xobjects, visitor, forms),
+        None => Ok(()),
+    }
+}
+
+/// Feeds the operations of a form XObject to the visitor, see [`visit_form`], anything but
+/// a form XObject is ignored.
+///
+/// # Arguments
+///
+/// * `document` - The document the form belongs to
+/// * `obj_ref` - The reference of the XObject
+/// * `xobjects` - The XObject resources of the enclosing content by name
+/// * `visitor` - The visitor
+/// * `forms` - The references of the enclosing forms
+///
+/// # Returns
+///
+/// A `Result` indicating success or the first error raised by reading, parsing or the visitor
+pub(crate) fn visit_form_xobject(
+    document: &mut PDFDocument,
+    obj_ref: ObjRefTuple,
+    xobjects: &HashMap<String, ObjRefTuple>,
+    visitor: &mut impl ContentVisitor,
+    forms: &mut Vec<ObjRefTuple>,
+) -> Result<()> {
+    let limit = document.get_options().get_max_form_depth();
+    let error = if forms.contains(&obj_ref) {
+        Some(FormXObjectCycle(obj_ref))
+    } else if forms.len() >= limit {
+        Some(FormXObjectTooDeep(limit))
+    } else {
+        None
+    };
+    let stream = match read_form_xobject(document, obj_ref)? {
+        Some(stream) => stream,
+        None => return Ok(()),
+    };
+    if let Some(error) = error {
+        if !document.get_options().is_lenient() {
+            return Err(error);
+        }
+        document.warn_about_with(obj_ref, WarningCode::FormRecursion, format!("{} (skipped)", error));
+        return Ok(());
+    }
+    let dict = stream.get_metadata();
+    let matrix = form_matrix(dict);
+    let resources = dict.get(RESOURCES).cloned();
+    let own_xobjects = match &resources {
+        Some(resources) => Some(document.read_resource_refs(Some(resources), XOBJECT)?),
+        None => None,
+    };
+    let xobjects = own_xobjects.as_ref().unwrap_or(xobjects);
+    let reader = ContentReader::with_options(document.decode_stream(&stream)?, document.get_options().clone(), None);
+    visitor.begin_form(document, matrix, resources.as_ref())?;
+    forms.push(obj_ref);
+    let result = read_operations(document, reader, Some(obj_ref), |document, operation| {
+        visitor.visit(&operation)?;
+        visit_form(document, &operation, xobjects, visitor, forms)
+    });
+    forms.pop();
+    visitor.end_form();
+    result
+}
+
+/// Returns the `/Matrix` of a form XObject dictionary, the identity if it is missing or malformed.
+pub(crate) fn form_matrix(dict: &Dictionary) -> [f64; 6] {
+    let mut matrix = IDENTITY;
+    if let Some(values) = dict.get_array_value(MATRIX)
+        && values.len() == 6
+    {
+        for (value, item) in matrix.iter_mut().zip(values) {
+            *value = item.as_number().map_or(0.0, |it| it.as_f64());
+        }
+    }
+    matrix
+}
+
+/// Reads an XObject if it is a form XObject, without reading the data of other XObjects.
+pub(crate) fn read_form_xobject(document: &mut PDFDocument, obj_ref: ObjRefTuple) -> Result<Option<Stream>> {
+    let object = document.with_skipped_stream_data(|document| document.read_object_with_ref(obj_ref));
+    match object? {
+        Some(PDFObject::IndirectObject(_, _, object)) if object.as_stream().is_some_and(|it| it.is_form_xobject()) => {}
+        _ => return Ok(None),
+    }
+    match document.read_object_with_ref(obj_ref)? {
+        Some(PDFObject::IndirectObject(_, _, object)) => Ok(object.into_stream()),
+        _ => Ok(None),
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::ops::Range;
 use std::str::FromStr;
 use crate::error::PDFError;
@@ -6,6 +7,8 @@ use crate::error::PDFError;
 ///
 /// This struct stores time information with millisecond precision,
 /// following the PDF specification for date/time representation.
+/// It is displayed in the PDF date format, e.g. `D:20240131120000Z`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Date {
     /// Time zone offset from UTC in hours.
     pub(crate) time_zero: i8,
@@ -32,6 +35,7 @@ impl Date {
     /// # Returns
     ///
     /// A new Date instance with the calculated Unix timestamp in milliseconds
+    #[allow(clippy::too_many_arguments)]
     fn new(year: i32, month: u8, day: u8, hour: u8, minute: u8, second: u8, time_zero: i8, utm: u8) -> Self {
         let millisecond = Self::calculate_unix_timestamp_millis(year, month, day, hour, minute, second, time_zero, utm);
 
@@ -61,6 +65,7 @@ impl Date {
     /// # Returns
     ///
     /// The Unix timestamp in milliseconds
+    #[allow(clippy::too_many_arguments)]
     fn calculate_unix_timestamp_millis(year: i32, month: u8, day: u8, hour: u8, minute: u8, second: u8, time_zero: i8, utm: u8) -> u64 {
         // Days in each month for non-leap years
        static DAYS_IN_MONTH: [u64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
@@ -119,6 +124,20 @@ impl Date {
         (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
     }
 
+    /// Creates a date from a Unix timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `millisecond` - The milliseconds since 1970-01-01 00:00:00 UTC
+    /// * `time_zero` - The time zone offset from UTC in hours the date is displayed in
+    ///
+    /// # Returns
+    ///
+    /// The date
+    pub fn from_millisecond(millisecond: u64, time_zero: i8) -> Self {
+        Date { time_zero, millisecond }
+    }
+
     /// Returns the millisecond component of the date and time.
     ///
     /// This represents the number of milliseconds elapsed since the Unix epoch
@@ -144,6 +163,33 @@ impl Date {
     }
 }
 
+/// Converts days since 1970-01-01 into a `(year, month, day)` civil date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let seconds = (self.millisecond / 1000) as i64 + self.time_zero as i64 * 3600;
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let time = seconds.rem_euclid(86_400);
+        write!(f, "D:{:04}{:02}{:02}{:02}{:02}{:02}", year, month, day, time / 3600, time % 3600 / 60, time % 60)?;
+        match self.time_zero {
+            0 => f.write_str("Z"),
+            tz => write!(f, "{}{:02}'00'", if tz < 0 { '-' } else { '+' }, tz.unsigned_abs()),
+        }
+    }
+}
+
 fn parse_part(text: &str, range: Range<usize>) -> u8 {
     text.get(range)
         .and_then(|s| s.parse::<u8>().ok())
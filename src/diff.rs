@@ -0,0 +1,343 @@
+use crate::constants::{INFO, ROOT};
+use crate::date::Date;
+use crate::document::{PDFDescribe, PDFDocument};
+use crate::objects::{Dictionary, ObjRefTuple, PDFObject};
+use crate::page::Page;
+use crate::text::TextExtractionOptions;
+use crate::writer;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// The number of characters of a value shown by the `Display` form of a difference.
+const MAX_SHOWN_CHARS: usize = 64;
+
+/// An entry of the information dictionary and its getter.
+type InfoField<T> = (&'static str, fn(&PDFDescribe) -> Option<&T>);
+
+/// How an item differs between the two documents of a [`diff`].
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DiffKind {
+    /// The item only exists in the second document.
+    Added,
+    /// The item only exists in the first document.
+    Removed,
+    /// The item exists in both documents with different values.
+    Changed,
+}
+
+/// A single difference found by [`diff`].
+///
+/// The path names the item, e.g. `PageCount`, `Page[3]/Text`, `Info/Title`,
+/// `Outline[0][2]` or `Root/Pages/Kids[0]/MediaBox` for objects reachable from the catalog.
+#[derive(Clone, Debug)]
+pub struct Difference {
+    kind: DiffKind,
+    path: String,
+    /// The value in the first document, if it has the item.
+    before: Option<String>,
+    /// The value in the second document, if it has the item.
+    after: Option<String>,
+}
+
+/// The result of comparing two documents, see [`diff`].
+#[derive(Clone, Debug, Default)]
+pub struct DiffReport {
+    differences: Vec<Difference>,
+}
+
+impl Difference {
+    fn new(path: String, before: Option<String>, after: Option<String>) -> Self {
+        let kind = match (&before, &after) {
+            (None, _) => DiffKind::Added,
+            (_, None) => DiffKind::Removed,
+            _ => DiffKind::Changed,
+        };
+        Self { kind, path, before, after }
+    }
+
+    /// Returns how the item differs.
+    pub fn get_kind(&self) -> DiffKind {
+        self.kind
+    }
+
+    /// Returns the path of the item.
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the value in the first document, or `None` if the item was added.
+    pub fn get_before(&self) -> Option<&str> {
+        self.before.as_deref()
+    }
+
+    /// Returns the value in the second document, or `None` if the item was removed.
+    pub fn get_after(&self) -> Option<&str> {
+        self.after.as_deref()
+    }
+}
+
+impl DiffReport {
+    /// Returns all differences in the order they were found.
+    pub fn get_differences(&self) -> &[Difference] {
+        &self.differences
+    }
+
+    /// Returns true if the documents don't differ.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Returns the difference of the item at the path, if any.
+    pub fn find(&self, path: &str) -> Option<&Difference> {
+        self.differences.iter().find(|it| it.path == path)
+    }
+
+    fn compare(&mut self, path: impl Into<String>, before: Option<String>, after: Option<String>) {
+        if before != after {
+            self.differences.push(Difference::new(path.into(), before, after));
+        }
+    }
+
+    /// Compares two lists of `(path, value)` items by path.
+    fn compare_lists(&mut self, prefix: &str, before: Vec<(String, String)>, after: Vec<(String, String)>) {
+        let paths = before.iter().chain(&after).map(|(path, _)| path.clone()).collect::<BTreeSet<_>>();
+        let value = |items: &[(String, String)], path: &str| items.iter().find(|it| it.0 == path).map(|it| it.1.clone());
+        for path in paths {
+            self.compare(format!("{}{}", prefix, path), value(&before, &path), value(&after, &path));
+        }
+    }
+}
+
+/// Shortens a value to [`MAX_SHOWN_CHARS`] characters.
+fn shorten(value: &str) -> String {
+    match value.char_indices().nth(MAX_SHOWN_CHARS) {
+        Some((index, _)) => format!("{}...", &value[..index]),
+        None => value.to_string(),
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => {
+                write!(f, "~ {}: {:?} -> {:?}", self.path, shorten(before), shorten(after))
+            }
+            (Some(before), None) => write!(f, "- {}: {:?}", self.path, shorten(before)),
+            (None, Some(after)) => write!(f, "+ {}: {:?}", self.path, shorten(after)),
+            (None, None) => write!(f, "~ {}", self.path),
+        }
+    }
+}
+
+impl Display for DiffReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for difference in &self.differences {
+            writeln!(f, "{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two documents structurally, e.g. to check that re-saving a document changed
+/// nothing meaningful.
+///
+/// The page counts, the text of each page, the information dictionaries, the outline
+/// titles and the objects reachable from the catalogs are compared. Objects are paired by
+/// their path from the catalog rather than by their numbers, so renumbered objects compare
+/// equal, and streams compare equal if their dictionaries and decoded data are equal. An
+/// item which can't be read is compared by its error message.
+///
+/// # Arguments
+///
+/// * `a` - A mutable reference to the first document
+/// * `b` - A mutable reference to the second document
+///
+/// # Returns
+///
+/// The differences, values are reported as `before` from `a` and `after` from `b`
+pub fn diff(a: &mut PDFDocument, b: &mut PDFDocument) -> DiffReport {
+    let mut report = DiffReport::default();
+    let (a_pages, b_pages) = (a.get_page_num(), b.get_page_num());
+    report.compare("PageCount", Some(a_pages.to_string()), Some(b_pages.to_string()));
+    diff_text(&mut report, a, b);
+    diff_info(&mut report, a.get_describe(), b.get_describe());
+    report.compare_lists("Outline", outline_titles(a), outline_titles(b));
+    diff_objects(&mut report, a, b);
+    report
+}
+
+/// Compares the text of the pages both documents have.
+fn diff_text(report: &mut DiffReport, a: &mut PDFDocument, b: &mut PDFDocument) {
+    let options = TextExtractionOptions::default();
+    let a_ids = a.get_page_ids();
+    let b_ids = b.get_page_ids();
+    let text = |document: &mut PDFDocument, page_id| match Page::new(page_id).text_with(document, &options) {
+        Ok(text) => text,
+        Err(e) => format!("error: {}", e),
+    };
+    for (index, (a_id, b_id)) in a_ids.into_iter().zip(b_ids).enumerate() {
+        let before = text(a, a_id);
+        let after = text(b, b_id);
+        report.compare(format!("Page[{}]/Text", index), Some(before), Some(after));
+    }
+}
+
+/// Compares the entries of the information dictionaries.
+fn diff_info(report: &mut DiffReport, a: Option<&PDFDescribe>, b: Option<&PDFDescribe>) {
+    let strings: [InfoField<str>; 6] = [
+        ("Title", PDFDescribe::get_title),
+        ("Author", PDFDescribe::get_author),
+        ("Subject", PDFDescribe::get_subject),
+        ("Keywords", PDFDescribe::get_keywords),
+        ("Creator", PDFDescribe::get_creator),
+        ("Producer", PDFDescribe::get_producer),
+    ];
+    for (key, get) in strings {
+        let value = |describe: Option<&PDFDescribe>| describe.and_then(get).map(str::to_string);
+        report.compare(format!("Info/{}", key), value(a), value(b));
+    }
+    let dates: [InfoField<Date>; 2] =
+        [("CreationDate", PDFDescribe::get_creation_date), ("ModDate", PDFDescribe::get_mod_date)];
+    for (key, get) in dates {
+        let value = |describe: Option<&PDFDescribe>| describe.and_then(get).map(Date::to_string);
+        report.compare(format!("Info/{}", key), value(a), value(b));
+    }
+    let trapped = |describe: Option<&PDFDescribe>| describe.and_then(|it| it.get_trapped()).map(|it| format!("{:?}", it));
+    report.compare("Info/Trapped", trapped(a), trapped(b));
+    let custom = |describe: Option<&PDFDescribe>| {
+        let entries = describe.map(|it| it.get_custom().map(|(key, value)| (format!("/{}", key), show(value))).collect());
+        entries.unwrap_or_default()
+    };
+    report.compare_lists(INFO, custom(a), custom(b));
+}
+
+/// Lists the outline items as their position path, e.g. `[0][2]` for the third child of the
+/// first top-level item, and their title.
+fn outline_titles(document: &PDFDocument) -> Vec<(String, String)> {
+    let mut titles = Vec::new();
+    let Some(outline) = document.get_outline() else {
+        return titles;
+    };
+    let Some(root) = outline.get_root_node() else {
+        return titles;
+    };
+    let mut pending = VecDeque::from([(String::new(), root.get_first_id())]);
+    let mut visited = HashSet::new();
+    while let Some((prefix, first)) = pending.pop_front() {
+        let mut next = first;
+        let mut index = 0;
+        while let Some(node_id) = next
+            && visited.insert(node_id)
+            && let Some(node) = outline.get_node(node_id)
+        {
+            let path = format!("{}[{}]", prefix, index);
+            titles.push((path.clone(), node.get_title().unwrap_or_default().to_string()));
+            pending.push_back((path, node.get_first_id()));
+            next = node.get_next_id();
+            index += 1;
+        }
+    }
+    titles
+}
+
+/// Returns the object and generation number of a reference.
+fn obj_ref(object: &PDFObject) -> Option<ObjRefTuple> {
+    match object {
+        PDFObject::ObjectRef(obj_num, gen_num) => Some((*obj_num, *gen_num)),
+        _ => None,
+    }
+}
+
+/// Reads the object a reference points to, a direct object is returned unchanged.
+fn resolve(document: &mut PDFDocument, object: PDFObject) -> PDFObject {
+    let PDFObject::ObjectRef(obj_num, gen_num) = object else {
+        return object;
+    };
+    match document.read_object_with_ref((obj_num, gen_num)) {
+        Ok(Some(PDFObject::IndirectObject(_, _, object))) => *object,
+        Ok(_) => PDFObject::Null,
+        Err(e) => PDFObject::String(writer::encode_text(&format!("error: {}", e))),
+    }
+}
+
+/// Returns an object written in PDF syntax, a stream is summarized by its dictionary.
+fn show(object: &PDFObject) -> String {
+    match object {
+        PDFObject::Stream(stream) => {
+            let dict = PDFObject::Dict(stream.get_metadata().clone());
+            format!("{} stream", String::from_utf8_lossy(&writer::to_bytes(&dict)))
+        }
+        _ => String::from_utf8_lossy(&writer::to_bytes(object)).into_owned(),
+    }
+}
+
+/// Compares the objects reachable from the catalogs, walking both graphs in parallel.
+///
+/// A pair of references is followed once, which ends cycles like the `/Parent` of pages.
+fn diff_objects(report: &mut DiffReport, a: &mut PDFDocument, b: &mut PDFDocument) {
+    let (a_root, b_root) = (a.get_catalog_ref(), b.get_catalog_ref());
+    let root = |catalog: Option<ObjRefTuple>| catalog.map_or(PDFObject::Null, |(obj_num, gen_num)| PDFObject::ObjectRef(obj_num, gen_num));
+    let mut pending = VecDeque::from([(ROOT.to_string(), root(a_root), root(b_root))]);
+    let mut visited = HashSet::<(Option<ObjRefTuple>, Option<ObjRefTuple>)>::new();
+    while let Some((path, before, after)) = pending.pop_front() {
+        let refs = (obj_ref(&before), obj_ref(&after));
+        if refs != (None, None) && !visited.insert(refs) {
+            continue;
+        }
+        let before = resolve(a, before);
+        let after = resolve(b, after);
+        match (&before, &after) {
+            (PDFObject::Dict(a_dict), PDFObject::Dict(b_dict)) => {
+                compare_dicts(report, &mut pending, &path, a_dict, b_dict);
+            }
+            (PDFObject::Stream(a_stream), PDFObject::Stream(b_stream)) => {
+                compare_dicts(report, &mut pending, &path, a_stream.get_metadata(), b_stream.get_metadata());
+                if a_stream.as_slice() != b_stream.as_slice() {
+                    let a_data = a.decode_stream(a_stream).unwrap_or_else(|_| a_stream.as_slice().to_vec());
+                    let b_data = b.decode_stream(b_stream).unwrap_or_else(|_| b_stream.as_slice().to_vec());
+                    if a_data != b_data {
+                        report.compare(format!("{}/(data)", path), Some(summarize(&a_data)), Some(summarize(&b_data)));
+                    }
+                }
+            }
+            (PDFObject::Array(a_elements), PDFObject::Array(b_elements)) => {
+                for index in 0..a_elements.len().max(b_elements.len()) {
+                    let element_path = format!("{}[{}]", path, index);
+                    match (a_elements.get(index), b_elements.get(index)) {
+                        (Some(before), Some(after)) => pending.push_back((element_path, before.clone(), after.clone())),
+                        (before, after) => report.compare(element_path, before.map(show), after.map(show)),
+                    }
+                }
+            }
+            _ if before != after => report.compare(path, Some(show(&before)), Some(show(&after))),
+            _ => {}
+        }
+    }
+}
+
+/// Summarizes stream data by its length and hash.
+fn summarize(data: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    format!("{} bytes, hash {:016x}", data.len(), hasher.finish())
+}
+
+/// Compares two dictionaries key by key in sorted key order, the values both have are
+/// queued for comparison.
+fn compare_dicts(
+    report: &mut DiffReport,
+    pending: &mut VecDeque<(String, PDFObject, PDFObject)>,
+    path: &str,
+    a: &Dictionary,
+    b: &Dictionary,
+) {
+    let keys = a.iter().chain(b.iter()).map(|(key, _)| key).collect::<BTreeSet<_>>();
+    for key in keys {
+        let key_path = format!("{}/{}", path, key);
+        match (a.get(key), b.get(key)) {
+            (Some(before), Some(after)) => pending.push_back((key_path, before.clone(), after.clone())),
+            (before, after) => report.compare(key_path, before.map(show), after.map(show)),
+        }
+    }
+}
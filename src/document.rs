@@ -1,25 +1,68 @@
-use crate::catalog::{NodeId, OutlineTreeArean, PageTreeArean, decode_catalog_data, PageNode};
+use crate::catalog::{Catalog, NodeId, OutlineTreeArean, PageTreeArean, decode_catalog_data, PageNode};
 use crate::constants::pdf_key::{START_XREF, XREF};
 use crate::constants::{
-    AUTHOR, CREATION_DATE, CREATOR, INFO, MOD_DATE, PREV, PRODUCER, ROOT, TITLE,
+    ANNOTS, AP, AS, AUTHOR, CREATION_DATE, CREATOR, ENCRYPT, FONT, ID, INFO, KEYWORDS, MOD_DATE, NAMES, OUTLINES, PARENT_TREE, PREV,
+    PRODUCER, N, QUAD_POINTS, RECT, RESOURCES, ROOT, SIZE, STRUCT_TREE_ROOT, SUBJECT, TITLE, TRAPPED, XOBJECT,
+    XREF_STM,
 };
+use crate::content::{ContentVisitor, Operation, for_each_page_operation, visit_page_content};
 use crate::convert_glyph_from_dict;
 use crate::date::Date;
-use crate::encoding::PreDefinedEncoding;
+use crate::encoding::{Encoding, PreDefinedEncoding};
+use crate::features::{Feature, collect_required_features, encryption_flavor};
+use crate::duplicates::{DuplicateDefinition, collect_duplicate_definitions};
 use crate::error::PDFError::{
-    InvalidPDFDocument, ObjectAttrMiss, PDFParseError, XrefTableNotFound,
+    CircularReference, EncryptedUpdate, GenerationMismatch, InvalidPDFDocument, ObjectAttrMiss, OffsetOutOfRange, PDFParseError, PDFParseError0, PageNotFound,
+    PathSegmentMismatch, XrefTableNotFound,
 };
-use crate::error::Result;
-use crate::objects::{Dictionary, ObjRefTuple, PDFNumber, PDFObject, XEntry};
-use crate::parser::{parse, parse_text_xref, parse_with_offset};
+use crate::error::{PDFError, Result};
+use crate::objects::{DictBuilder, Dictionary, ObjRefTuple, ObjectId, PDFNumber, PDFObject, PDFString, Stream, XEntry, XEntryKind, XrefTable};
+use crate::helper::read_page_content;
+use crate::path::{PathSeg, parse_path};
+use crate::name::Name;
+use crate::options::{ParseOptions, Phase, Warning, WarningCode};
+use crate::parser::{find_next_object_header, parse, parse_entry, parse_text_xref, parse_with_offset, parse_xref_stream};
 use crate::pstr::convert_glyph_text;
-use crate::sequence::{FileSequence, Sequence};
+use crate::sequence::{FileSequence, MemorySequence, OffsetSequence, Sequence};
+#[cfg(feature = "mmap")]
+use crate::sequence::MmapSequence;
+use crate::font::{FontPreflight, font_preflight};
+use crate::image::{ImageInventory, collect_images};
+use crate::labels::{PageLabelRange, collect_page_labels, label_of};
+use crate::name_tree::NameTree;
+use crate::number_tree::NumberTree;
+use crate::page_count::{CountResult, count_pages_up_to};
+use crate::revisions::{RevisionChanges, assign_revisions};
+use crate::import::ObjectImporter;
+use crate::objstm::{ObjectStream, collect_object_streams, read_object_stream_objects};
+use crate::stats::{DocumentStats, collect_stats};
+use crate::stream_map::{StreamMap, collect_stream_map};
+use crate::report::{TextReport, collect_text_report};
+use crate::resources::{ResourceUsageReport, UnusedResources, collect_resource_usage, collect_unused_resources};
+use crate::operators::{HistogramOptions, collect_operator_histogram, collect_page_operator_histogram};
+use crate::page::{Page, PageRef};
+use crate::partial::{Facet, PartialDocument};
+use crate::text::{TextExtractionOptions, extract_fragments, form_text, text_in_quads};
 use crate::tokenizer::Tokenizer;
-use crate::utils::{count_leading_line_endings, line_ending, literal_to_u64, xrefs_search};
+use crate::trace::span;
+use crate::utils::{is_white_space, literal_to_u64};
+use crate::verify::{ValidationReport, VerifyOptions, verify_document};
 use crate::vpdf::PDFVersion;
+use crate::writer::{self, DocumentBuilder, OutlineBuilder, WriteOptions, encode_text};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+/// The keys of the information dictionary defined by the specification.
+const STANDARD_INFO_KEYS: [&str; 9] =
+    [TITLE, AUTHOR, SUBJECT, KEYWORDS, CREATOR, PRODUCER, CREATION_DATE, MOD_DATE, TRAPPED];
+
+/// The document information dictionary (`/Info`).
+///
+/// A changed copy is written back by [`PDFDocument::set_describe`] and
+/// [`PDFDocument::save_incremental`], or into a new file by `DocumentBuilder::set_info`.
+#[derive(Clone)]
 pub struct PDFDescribe {
     /// (Optional) The name of the application that converted the document from its native format to
     /// PDF.
@@ -39,6 +82,56 @@ pub struct PDFDescribe {
     /// otherwise optional; PDF 1.1) The date and time the document was
     /// most recently modified, in human-readable form (see 7.9.4, “Dates”).
     mod_date: Option<Date>,
+    /// (Optional; PDF 1.1) The subject of the document.
+    subject: Option<String>,
+    /// (Optional; PDF 1.1) Keywords associated with the document.
+    keywords: Option<String>,
+    /// (Optional; PDF 1.3) Whether the document has been modified to include trapping
+    /// information.
+    trapped: Option<Trapped>,
+    /// The entries which aren't defined by the specification, e.g. `/SourceModified` or
+    /// company-specific tags.
+    custom: Dictionary,
+    /// Whether `/Producer` is replaced by the name and version of this library when written.
+    producer_stamp: bool,
+}
+
+/// The `/Trapped` entry of the document information dictionary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trapped {
+    /// The document has been fully trapped.
+    True,
+    /// The document has not yet been trapped.
+    False,
+    /// Either it is unknown whether the document has been trapped or it has been partly
+    /// but not yet fully trapped.
+    Unknown,
+}
+
+impl Trapped {
+    /// Parses the value of `/Trapped`, which is a name or a boolean in older files.
+    fn from_object(object: &PDFObject) -> Option<Trapped> {
+        match object {
+            PDFObject::Bool(true) => Some(Trapped::True),
+            PDFObject::Bool(false) => Some(Trapped::False),
+            PDFObject::Named(name) => match name.as_str() {
+                "True" => Some(Trapped::True),
+                "False" => Some(Trapped::False),
+                "Unknown" => Some(Trapped::Unknown),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the name the value is written as.
+    fn as_name(&self) -> &'static str {
+        match self {
+            Trapped::True => "True",
+            Trapped::False => "False",
+            Trapped::Unknown => "Unknown",
+        }
+    }
 }
 
 /// Represents a PDF document with all its components and functionality.
@@ -46,8 +139,14 @@ pub struct PDFDescribe {
 /// This struct encapsulates a parsed PDF document, providing access to its cross-reference
 /// table, version information, tokenizer, and page structure.
 pub struct PDFDocument {
-    /// Cross-reference table containing references to all objects in the PDF.
-    xrefs: Vec<XEntry>,
+    /// Cross-reference table containing references to all objects in the PDF, with the
+    /// overrides applied. Shared with the tokenizer, which resolves indirect stream lengths
+    /// through it.
+    xrefs: XrefTable,
+    /// The cross-reference table merged from the file.
+    merged_xrefs: XrefTable,
+    /// The entries replacing those of the merged table, keyed by object number.
+    xref_overrides: BTreeMap<u32, XEntry>,
     /// PDF version information.
     version: PDFVersion,
     /// Tokenizer for parsing the PDF content.
@@ -58,6 +157,117 @@ pub struct PDFDocument {
     outline_tree_arean: Option<OutlineTreeArean>,
     /// Document info
     describe: Option<PDFDescribe>,
+    /// Whether `describe` was changed by [`PDFDocument::set_describe`].
+    describe_changed: bool,
+    /// The outline staged by [`PDFDocument::set_outline`].
+    staged_outline: Option<OutlineBuilder>,
+    /// The document information dictionary reference declared by the trailer.
+    info: Option<ObjRefTuple>,
+    /// The offset of the newest cross-reference section.
+    startxref: u64,
+    /// Cache of the object references used by each page, keyed by page id.
+    page_refs: HashMap<NodeId, HashSet<ObjRefTuple>>,
+    /// The document catalog reference, `None` if a partially opened document's trailer has
+    /// no `/Root`.
+    catalog: Option<ObjRefTuple>,
+    /// The document catalog.
+    catalog_data: Catalog,
+    /// The xref table size declared by the trailer.
+    trailer_size: Option<u64>,
+    /// Objects which failed to parse in lenient mode.
+    unreadable: HashSet<ObjRefTuple>,
+    /// The encryption dictionary reference declared by the trailer.
+    encrypt: Option<ObjRefTuple>,
+    /// The file identifier declared by the trailer.
+    id: Option<Vec<PDFObject>>,
+    /// The encryption named by the encryption dictionary.
+    encryption: Option<Feature>,
+    /// The cross-reference mechanism found while opening.
+    xref_kind: XrefKind,
+    /// The number of cross-reference sections merged while opening.
+    xref_sections: usize,
+    /// The objects each revision added, changed or deleted, the original document first.
+    revisions: Vec<RevisionChanges>,
+    /// The object streams with their parsed headers, collected on first use.
+    object_streams: Option<Vec<ObjectStream>>,
+    /// The page label ranges, collected on first use.
+    page_labels: Option<Vec<PageLabelRange>>,
+    /// The encodings replacing those of fonts, keyed by font reference.
+    font_encodings: HashMap<ObjRefTuple, Encoding>,
+    /// The offset of the `%PDF-` header in the file, which all other offsets are relative to.
+    header_offset: u64,
+}
+
+impl Default for Trailer {
+    fn default() -> Self {
+        Trailer {
+            catalog: None,
+            info: None,
+            size: None,
+            size_offset: 0,
+            encrypt: None,
+            id: None,
+            xref_kind: XrefKind::Classic,
+            xref_sections: 0,
+            sections: Vec::new(),
+            revisions: Vec::new(),
+        }
+    }
+}
+
+/// The maximum number of references followed to resolve one path hop.
+const MAX_REF_CHAIN: usize = 32;
+/// The number of bytes searched for the `%PDF-` header, bytes before it are ignored.
+const HEADER_SCAN_LEN: usize = 1024;
+/// The number of bytes at the end of a file first searched for `startxref`.
+const STARTXREF_WINDOW: usize = 1024;
+/// The highest object number readers are required to support, see Annex C of ISO 32000-1.
+const MAX_OBJ_NUM: u32 = 8_388_607;
+
+/// Entries of the trailer dictionaries collected while merging the xref tables.
+struct Trailer {
+    /// The document catalog reference.
+    catalog: Option<ObjRefTuple>,
+    /// The document information dictionary reference.
+    info: Option<ObjRefTuple>,
+    /// The declared number of entries of the xref table.
+    size: Option<u64>,
+    /// The offset of the trailer declaring `size`.
+    size_offset: u64,
+    /// The encryption dictionary reference.
+    encrypt: Option<ObjRefTuple>,
+    /// The file identifier.
+    id: Option<Vec<PDFObject>>,
+    /// The mechanism of the cross-reference sections.
+    xref_kind: XrefKind,
+    /// The number of cross-reference sections merged.
+    xref_sections: usize,
+    /// The entries of each section merged so far, the newest first.
+    sections: Vec<Vec<XEntry>>,
+    /// The changes of each revision, the original document first.
+    revisions: Vec<RevisionChanges>,
+}
+
+/// The cross-reference mechanism a document uses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XrefKind {
+    /// Only cross-reference tables (`xref` keyword).
+    Classic,
+    /// Cross-reference tables whose trailers point at a supplementary cross-reference
+    /// stream via `/XRefStm`, readable by pre-1.5 readers.
+    Hybrid,
+    /// Cross-reference streams (`/Type /XRef`) holding the trailer entries in their
+    /// dictionary, in at least one section.
+    Stream,
+}
+
+/// Collects the references of the fonts selected by `Tf` and the XObjects painted by `Do`.
+struct ResourceUsageVisitor {
+    /// The fonts of the page followed by those of the entered form XObjects.
+    fonts: Vec<HashMap<String, ObjRefTuple>>,
+    /// The XObjects of the page followed by those of the entered form XObjects.
+    xobjects: Vec<HashMap<String, ObjRefTuple>>,
+    refs: HashSet<ObjRefTuple>,
 }
 
 impl PDFDocument {
@@ -74,9 +284,75 @@ impl PDFDocument {
     /// A `Result` containing the parsed `PDFDocument` or an error if the file cannot be opened
     /// or parsed correctly
     pub fn open(path: PathBuf) -> Result<PDFDocument> {
+        Self::open_with_options(path, ParseOptions::default())
+    }
+
+    /// Opens a PDF document from a file path with the given parse options.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the PDF file to open
+    /// * `options` - The options controlling how malformations are handled
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `PDFDocument` or an error if the file cannot be opened
+    /// or parsed correctly
+    pub fn open_with_options(path: PathBuf, options: ParseOptions) -> Result<PDFDocument> {
         let file = std::fs::File::open(path)?;
         let sequence = FileSequence::new(file);
-        Self::new(sequence)
+        Self::new_with_options(sequence, options)
+    }
+
+    /// Opens a PDF document by mapping the file into memory, which suits large files.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified as long as the document exists, see
+    /// [`MmapSequence::new`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the PDF file to open
+    /// * `options` - The options controlling how malformations are handled
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `PDFDocument` or an error if the file cannot be
+    /// mapped or parsed correctly
+    #[cfg(feature = "mmap")]
+    pub unsafe fn open_mmap_with_options(path: PathBuf, options: ParseOptions) -> Result<PDFDocument> {
+        // SAFETY: the caller keeps the file unmodified
+        let sequence = unsafe { MmapSequence::open(path)? };
+        Self::new_with_options(sequence, options)
+    }
+
+    /// Creates a PDF document from the bytes of a PDF file held in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes of the PDF file
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `PDFDocument` or an error if parsing fails
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<PDFDocument> {
+        Self::new(MemorySequence::new(bytes))
+    }
+
+    /// Creates a PDF document from the bytes of a PDF file held in memory with the given
+    /// parse options.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes of the PDF file
+    /// * `options` - The options controlling how malformations are handled
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `PDFDocument` or an error if parsing fails
+    pub fn from_bytes_with_options(bytes: Vec<u8>, options: ParseOptions) -> Result<PDFDocument> {
+        Self::new_with_options(MemorySequence::new(bytes), options)
     }
 
     /// Creates a PDF document from a sequence of bytes.
@@ -91,38 +367,165 @@ impl PDFDocument {
     /// # Returns
     ///
     /// A `Result` containing the parsed `PDFDocument` or an error if parsing fails
-    pub fn new(mut sequence: impl Sequence + 'static) -> Result<PDFDocument> {
-        let version = parse_version(&mut sequence)?;
-        let offset = cal_xref_table_offset(&mut sequence)?;
-        let mut tokenizer = Tokenizer::new(sequence);
+    pub fn new(sequence: impl Sequence + 'static) -> Result<PDFDocument> {
+        Self::new_with_options(sequence, ParseOptions::default())
+    }
+
+    /// Creates a PDF document from a sequence of bytes with the given parse options.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - A sequence implementation providing access to the PDF bytes
+    /// * `options` - The options controlling how malformations are handled
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `PDFDocument` or an error if parsing fails
+    pub fn new_with_options(
+        mut sequence: impl Sequence + 'static,
+        options: ParseOptions,
+    ) -> Result<PDFDocument> {
+        let (version, header_offset) = {
+            let span = span!("pdf.header", file_size, header_offset);
+            let _entered = span.enter();
+            span.record("file_size", sequence.size()?);
+            let header = parse_version(&mut sequence)?;
+            span.record("header_offset", header.1);
+            header
+        };
+        options.report(Phase::Header, 1, 1)?;
+        let mut sequence = OffsetSequence::new(sequence, header_offset);
+        let offset = cal_xref_table_offset(&mut sequence, options.get_max_startxref_scan())?;
+        let mut tokenizer = Tokenizer::with_options(sequence, options);
+        warn_header_offset(&mut tokenizer, header_offset);
         tokenizer.seek(offset)?;
         // Merge all xref table
-        let (xrefs, catalog, info) = merge_xref_table(&mut tokenizer)?;
-        let (page_tree_arena, outline_tree_arean) = match catalog {
-            Some(catalog) => decode_catalog_data(&mut tokenizer, catalog, &xrefs)?,
+        let (xrefs, trailer) = merge_xref_table(&mut tokenizer)?;
+        let xrefs = XrefTable::new(xrefs);
+        tokenizer.set_xrefs(xrefs.clone());
+        check_declared_size(&mut tokenizer, &xrefs, &trailer);
+        let encryption = trailer.encrypt.map(|encrypt| check_encryption(&mut tokenizer, &xrefs, encrypt));
+        let catalog = match trailer.catalog {
+            Some(catalog) => catalog,
             None => return Err(ObjectAttrMiss("Trailer can't found catalog attr.")),
         };
-        let mut describe = None;
-        // Parse document info
-        if let Some(obj) = info {
-            let entry = xrefs_search(&xrefs, obj)?;
-            if let PDFObject::IndirectObject(_, _, value) =
-                parse_with_offset(&mut tokenizer, entry.value)?
-            {
-                if let PDFObject::Dict(dict) = *value {
-                    describe = Some(PDFDescribe::new(dict));
-                }
+        let parts = read_catalog(&mut tokenizer, catalog, &xrefs)?;
+        Ok(assemble((version, header_offset), tokenizer, xrefs, trailer, offset, encryption, parts))
+    }
+
+    /// Opens a PDF document from a file path, keeping whatever can be read instead of
+    /// failing as a whole, see [`PDFDocument::new_partial`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the PDF file to open
+    ///
+    /// # Returns
+    ///
+    /// The partially read document and the errors which made facets unavailable, the
+    /// first error is the I/O error if the file can't be opened
+    pub fn open_partial(path: PathBuf) -> (PartialDocument, Vec<PDFError>) {
+        Self::open_partial_with_options(path, ParseOptions::default())
+    }
+
+    /// Opens a PDF document from a file path like [`PDFDocument::open_partial`] with the
+    /// given parse options.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the PDF file to open
+    /// * `options` - The options controlling how malformations are handled
+    ///
+    /// # Returns
+    ///
+    /// The partially read document and the errors which made facets unavailable
+    pub fn open_partial_with_options(path: PathBuf, options: ParseOptions) -> (PartialDocument, Vec<PDFError>) {
+        match std::fs::File::open(path) {
+            Ok(file) => Self::new_partial(FileSequence::new(file), options),
+            Err(e) => {
+                let (document, mut errors) = Self::new_partial(MemorySequence::new(Vec::new()), options);
+                errors.insert(0, e.into());
+                (document, errors)
             }
         }
-        let document = PDFDocument {
-            xrefs,
-            version,
-            tokenizer,
-            page_tree_arena,
-            outline_tree_arean,
-            describe,
+    }
+
+    /// Creates a PDF document from a sequence of bytes, reading as much as possible.
+    ///
+    /// Opening continues past failures which [`PDFDocument::new_with_options`] returns:
+    ///
+    /// * An unknown header version is replaced by 1.0.
+    /// * Unreadable cross-reference data is rebuilt by scanning the file for object
+    ///   definitions like [`PDFDocument::scan_objects`], the newest definition of an
+    ///   object wins. The trailer is lost, so the catalog and Info are unavailable.
+    /// * An unreadable catalog, page tree or outline leaves the document without pages
+    ///   and outline, objects can still be read by reference.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - A sequence implementation providing access to the PDF bytes
+    /// * `options` - The options controlling how malformations are handled
+    ///
+    /// # Returns
+    ///
+    /// The partially read document and the errors which made facets unavailable, in the
+    /// order they occurred
+    pub fn new_partial(mut sequence: impl Sequence + 'static, options: ParseOptions) -> (PartialDocument, Vec<PDFError>) {
+        let mut errors = Vec::new();
+        let mut unavailable = Vec::new();
+        let (version, header_offset) = match parse_version(&mut sequence) {
+            Ok(header) => header,
+            Err(e) => {
+                errors.push(e);
+                unavailable.push(Facet::Header);
+                (PDFVersion::V1_0, 0)
+            }
+        };
+        let mut sequence = OffsetSequence::new(sequence, header_offset);
+        let offset = cal_xref_table_offset(&mut sequence, options.get_max_startxref_scan());
+        let mut tokenizer = Tokenizer::with_options(sequence, options);
+        warn_header_offset(&mut tokenizer, header_offset);
+        let merged = offset.and_then(|offset| {
+            tokenizer.seek(offset)?;
+            Ok((offset, merge_xref_table(&mut tokenizer)?))
+        });
+        let (offset, xrefs, trailer) = match merged {
+            Ok((offset, (xrefs, trailer))) => (offset, xrefs, trailer),
+            Err(e) => {
+                errors.push(e);
+                unavailable.push(Facet::Xref);
+                let xrefs = match scan_object_headers(&mut tokenizer) {
+                    Ok(objects) => rebuild_xref(&mut tokenizer, objects),
+                    Err(e) => {
+                        errors.push(e);
+                        Vec::new()
+                    }
+                };
+                (0, xrefs, Trailer::default())
+            }
         };
-        Ok(document)
+        let xrefs = XrefTable::new(xrefs);
+        tokenizer.set_xrefs(xrefs.clone());
+        check_declared_size(&mut tokenizer, &xrefs, &trailer);
+        let encryption = trailer.encrypt.map(|encrypt| check_encryption(&mut tokenizer, &xrefs, encrypt));
+        let parts = match trailer.catalog {
+            Some(catalog) => read_catalog(&mut tokenizer, catalog, &xrefs),
+            None => Err(ObjectAttrMiss("Trailer can't found catalog attr.")),
+        };
+        let parts = match parts {
+            Ok(parts) => parts,
+            Err(e) => {
+                errors.push(e);
+                unavailable.push(Facet::Catalog);
+                (Catalog::default(), PageTreeArean::new(NodeId::default(), HashMap::new()), None)
+            }
+        };
+        let info = trailer.info;
+        let document = assemble((version, header_offset), tokenizer, xrefs, trailer, offset, encryption, parts);
+        if info.is_some() && document.describe.is_none() {
+            unavailable.push(Facet::Info);
+        }
+        (PartialDocument::new(document, unavailable), errors)
     }
 
     /// Gets a reference to the cross-reference table slice.
@@ -134,6 +537,59 @@ impl PDFDocument {
         &self.xrefs
     }
 
+    /// Gets the document catalog.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the catalog of the newest revision
+    pub fn get_catalog(&self) -> &Catalog {
+        &self.catalog_data
+    }
+
+    /// Gets the reference of the encryption dictionary declared by the newest trailer.
+    ///
+    /// # Returns
+    ///
+    /// The object number and generation number, or `None` if the document isn't encrypted
+    pub fn get_encrypt_ref(&self) -> Option<ObjRefTuple> {
+        self.encrypt
+    }
+
+    /// Gets the encryption of the document, named by the crypt filter method like `AESV3`.
+    ///
+    /// Strings are left encrypted. Decoding stream data, e.g. for text extraction, fails
+    /// with `Unsupported` in strict mode while lenient mode decodes the encrypted bytes.
+    ///
+    /// # Returns
+    ///
+    /// The encryption feature, or `None` if the document isn't encrypted
+    pub fn get_encryption(&self) -> Option<&Feature> {
+        self.encryption.as_ref()
+    }
+
+    /// Gets the file identifier declared by the newest trailer.
+    ///
+    /// # Returns
+    ///
+    /// The two strings of the `/ID` array, or `None` if no trailer defines it
+    pub fn get_id(&self) -> Option<&[PDFObject]> {
+        self.id.as_deref()
+    }
+
+    /// Gets the malformations tolerated so far.
+    ///
+    /// Most warnings are recoveries made in lenient mode, a few are recorded in every mode
+    /// because they don't prevent reading, e.g. an xref entry pointing to an object with
+    /// other numbers. Warnings are recorded while objects are read, so reading more objects
+    /// may add more.
+    ///
+    /// # Returns
+    ///
+    /// A slice of the warnings in the order they were recorded
+    pub fn get_warnings(&self) -> &[Warning] {
+        self.tokenizer.get_warnings()
+    }
+
     /// Finds the index of a cross-reference entry that matches a condition.
     ///
     /// # Arguments
@@ -150,6 +606,73 @@ impl PDFDocument {
         self.xrefs.iter().position(visit)
     }
 
+    /// Gets the xref entry of an object through the index of the cross-reference table.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - The object number and generation number of the object
+    ///
+    /// # Returns
+    ///
+    /// The entry, or `None` if the object number is unknown or its entry has another generation
+    pub fn get_xref_entry(&self, obj_ref: impl Into<ObjectId>) -> Option<&XEntry> {
+        self.xrefs.search(obj_ref.into().to_tuple()).ok()
+    }
+
+    /// Replaces the xref entry of an object for this session, e.g. to point it at the offset
+    /// the object is really defined at when the xref is off.
+    ///
+    /// The override takes precedence over the merged table in every lookup that follows and
+    /// replaces an earlier override of the object number. The file is left as it is, only
+    /// saving the document writes the objects read through the override. The page tree and
+    /// outline read while opening aren't read again.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - The object number and generation number of the object, which the entry
+    ///   keeps for every kind
+    /// * `kind` - Where the object is stored, or that it is free
+    pub fn override_xref_entry(&mut self, obj_ref: impl Into<ObjectId>, kind: XEntryKind) {
+        let (obj_num, gen_num) = obj_ref.into().to_tuple();
+        self.xref_overrides.insert(obj_num, XEntry::of_kind(obj_num, gen_num, kind));
+        self.apply_xref_overrides(obj_num);
+    }
+
+    /// Removes the override of an object, restoring the entry of the merged table.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - The object number and generation number of the object
+    ///
+    /// # Returns
+    ///
+    /// True if the object had an override of the same generation
+    pub fn remove_xref_override(&mut self, obj_ref: impl Into<ObjectId>) -> bool {
+        let (obj_num, gen_num) = obj_ref.into().to_tuple();
+        if self.xref_overrides.get(&obj_num).is_none_or(|it| it.gen_num != gen_num) {
+            return false;
+        }
+        self.xref_overrides.remove(&obj_num);
+        self.apply_xref_overrides(obj_num);
+        true
+    }
+
+    /// Returns the entries replacing those of the merged table, ordered by object number.
+    pub fn get_xref_overrides(&self) -> impl Iterator<Item = &XEntry> {
+        self.xref_overrides.values()
+    }
+
+    /// Rebuilds the table from the merged table and the overrides after the entry of an
+    /// object number changed, and forgets what was learned through the old entry.
+    fn apply_xref_overrides(&mut self, obj_num: u32) {
+        self.xrefs = self.merged_xrefs.with_overrides(&self.xref_overrides);
+        self.tokenizer.set_xrefs(self.xrefs.clone());
+        self.unreadable.retain(|it| it.0 != obj_num);
+        self.page_refs.clear();
+        self.object_streams = None;
+        self.page_labels = None;
+    }
+
     /// Gets the PDF version information.
     ///
     /// # Returns
@@ -159,6 +682,19 @@ impl PDFDocument {
         &self.version
     }
 
+    /// Gets the offset of the `%PDF-` header in the file.
+    ///
+    /// Bytes before the header, e.g. left by an e-mail extraction, are ignored. All other
+    /// offsets, e.g. of xref entries and warnings, are relative to the header, and saving
+    /// writes the file from the header on.
+    ///
+    /// # Returns
+    ///
+    /// The offset, 0 unless the file has bytes before the header
+    pub fn get_header_offset(&self) -> u64 {
+        self.header_offset
+    }
+
     /// Reads an object from the PDF document by its index.
     ///
     /// # Arguments
@@ -170,24 +706,140 @@ impl PDFDocument {
     /// A `Result` containing an optional PDFObject (None if the index is out of bounds
     /// or the object is freed) or an error if reading/parsing fails
     pub fn read_object(&mut self, index: usize) -> Result<Option<PDFObject>> {
+        Ok(self.read_entry(index)?.map(|(_, object)| object))
+    }
+
+    /// Reads an object from the PDF document by its index along with its xref entry.
+    ///
+    /// A warning is recorded when the numbers of the parsed indirect object differ from
+    /// the entry, which usually means the xref offsets are shifted.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the object to read from the cross-reference table
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an optional copy of the entry and the PDFObject (None if the
+    /// index is out of bounds or the object is freed) or an error if reading/parsing fails
+    pub fn read_entry(&mut self, index: usize) -> Result<Option<(XEntry, PDFObject)>> {
         if index >= self.xrefs.len() {
             return Ok(None);
         }
-        let entry = &self.xrefs[index];
+        let entry = self.xrefs[index].clone();
         if entry.is_freed() {
             return Ok(None);
         }
-        self.tokenizer.seek(entry.get_value())?;
-        let object = parse(&mut self.tokenizer)?;
-        Ok(Some(object))
+        let obj_ref = (entry.get_obj_num(), entry.get_gen_num());
+        let offset = entry.get_value();
+        let object = match parse_entry(&mut self.tokenizer, &entry) {
+            Ok(object) => object,
+            Err(e) => {
+                // Mark the object so the warning is recorded once
+                if self.tokenizer.get_options().is_lenient() && self.unreadable.insert(obj_ref) {
+                    self.tokenizer.warn(
+                        offset,
+                        format!("Object {} {} is unreadable: {}", obj_ref.0, obj_ref.1, e),
+                    );
+                }
+                return Err(e);
+            }
+        };
+        if let PDFObject::IndirectObject(obj_num, gen_num, _) = object
+            && (obj_num, gen_num) != obj_ref
+        {
+            self.tokenizer.warn(
+                offset,
+                format!(
+                    "Xref entry {} {} points to object {} {}",
+                    obj_ref.0, obj_ref.1, obj_num, gen_num
+                ),
+            );
+        }
+        Ok(Some((entry, object)))
+    }
+
+    /// Checks whether an object failed to parse in lenient mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - The object number and generation number of the object
+    ///
+    /// # Returns
+    ///
+    /// True if the object was found unreadable so far
+    pub fn is_unreadable(&self, obj_ref: impl Into<ObjectId>) -> bool {
+        self.unreadable.contains(&obj_ref.into().to_tuple())
+    }
+
+    /// Parses the object at a byte offset without consulting the xref table.
+    ///
+    /// White-space and comments before the object are skipped. An `N G obj` header at the
+    /// offset yields the indirect object, anything else the bare object.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The byte offset in the file
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the object and the number of bytes from `offset` to its end,
+    /// an `OffsetOutOfRange` error if the offset is not inside the file, or an error if
+    /// no object can be parsed there
+    pub fn parse_at_offset(&mut self, offset: u64) -> Result<(PDFObject, u64)> {
+        parse_at(&mut self.tokenizer, offset)
+    }
+
+    /// Scans the whole file for object definitions in file order, ignoring the xref table.
+    ///
+    /// An object which can't be parsed is reported as a warning and skipped by searching
+    /// forward for the next `N G obj` header. The scan stops when no header is found within
+    /// the resync limit of the parse options.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the reference and offset of every parsed object, or an error
+    /// if the file can't be read
+    pub fn scan_objects(&mut self) -> Result<Vec<(ObjRefTuple, u64)>> {
+        scan_object_headers(&mut self.tokenizer)
+    }
+
+    /// Returns the number of bytes read by recovery scans so far, i.e. the part of the
+    /// recovery budget of the parse options which is used up.
+    pub fn get_recovery_scanned(&self) -> usize {
+        self.tokenizer.get_recovery_scanned()
     }
 
-    pub fn read_object_with_ref(&mut self, tuple: ObjRefTuple) -> Result<Option<PDFObject>> {
-        self.xrefs
-            .iter()
-            .position(|entry| entry.obj_num == tuple.0 && entry.gen_num == tuple.1)
-            .map(|index| self.read_object(index))
-            .unwrap_or(Ok(None))
+    /// Reads an object from the PDF document by its reference.
+    ///
+    /// # Arguments
+    ///
+    /// * `tuple` - The object number and generation number of the object
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an optional PDFObject (None if the object number is unknown
+    /// or the object is freed), a `GenerationMismatch` error if the object slot is in use
+    /// by another generation, or an error if reading/parsing fails
+    pub fn read_object_with_ref(&mut self, tuple: impl Into<ObjectId>) -> Result<Option<PDFObject>> {
+        let tuple = tuple.into().to_tuple();
+        let index = match self.xrefs.position(tuple.0) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let entry = &self.xrefs[index];
+        // A reference to a freed object is a null reference, whatever generation the
+        // free entry carries
+        if entry.is_freed() {
+            return Ok(None);
+        }
+        if entry.gen_num != tuple.1 {
+            return Err(GenerationMismatch {
+                requested: tuple,
+                found: (entry.obj_num, entry.gen_num),
+            });
+        }
+        self.read_object(index)
     }
 
     pub fn get_page_num(&self) -> usize {
@@ -195,168 +847,1913 @@ impl PDFDocument {
     }
 
     pub fn get_page_ids(&self) -> Vec<NodeId> {
-        self.page_tree_arena.get_leaf_page_ids()
+        self.page_tree_arena.get_page_id_slice().to_vec()
     }
 
-    pub fn get_page(&self, node_id: NodeId) -> Option<&PageNode> {
-        self.page_tree_arena.get_page_node(node_id)
+    /// Returns the page at an index in page order, the order viewers display the pages in.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the page
+    ///
+    /// # Returns
+    ///
+    /// The page, or `None` if the index is out of range
+    pub fn get_page(&self, index: usize) -> Option<Page> {
+        self.page_tree_arena.get_page_id_slice().get(index).map(|it| Page::new(*it))
     }
-}
 
-/// Parses the PDF version from the beginning of the document.
-///
-/// This function reads the first few bytes of a PDF document to extract and validate
-/// the PDF version information.
-///
-/// # Arguments
-///
-/// * `sequence` - A mutable reference to a sequence implementation for reading bytes
-///
-/// # Returns
-///
-/// A `Result` containing the parsed PDFVersion or an error if the version cannot be
-/// parsed or is invalid
-fn parse_version(sequence: &mut impl Sequence) -> Result<PDFVersion> {
-    let mut buf = [0u8; 1024];
-    let n = sequence.read(&mut buf)?;
-    if n < 8 {
-        return Err(InvalidPDFDocument);
+    /// Iterates the pages in page order with their index and attributes.
+    pub fn pages(&self) -> impl Iterator<Item = PageRef<'_>> {
+        self.page_tree_arena.get_page_id_slice().iter().enumerate().filter_map(|(index, page_id)| {
+            let node = self.page_tree_arena.node(*page_id).ok()?;
+            Some(PageRef::new(index, node))
+        })
     }
-    if buf.len() < 8 || !buf.starts_with(b"%PDF-") {
-        return Err(InvalidPDFDocument);
+
+    /// Returns the page tree node with the given ID, a page or an intermediate `/Pages` node,
+    /// or `None` where [`PageTreeArean::node`] fails with `MissingNode`.
+    pub fn get_page_node(&self, node_id: NodeId) -> Option<&PageNode> {
+        self.page_tree_arena.node(node_id).ok()
     }
-    let version = String::from_utf8(buf[5..8].to_vec())?;
-    Ok(version.try_into()?)
-}
 
-/// Merges cross-reference tables from a PDF document.
-///
-/// This function parses and merges multiple cross-reference tables that may exist
-/// in a PDF document, handling cases where there are previous xref tables referenced
-/// in the document trailer.
-///
-/// # Arguments
-///
-/// * `tokenizer` - A mutable reference to the tokenizer for parsing PDF content
-///
-/// # Returns
+    /// Checks the structure of the document without modifying it.
+    ///
+    /// The xref offsets and the object headers found there, the objects defined more than
+    /// once, see [`PDFDocument::duplicate_definitions`], the trailer `/Size`, the page
+    /// tree and outline counts, the required keys and every object reachable from the
+    /// catalog are checked, each problem is reported as a finding instead of failing the
+    /// whole validation.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the validation report, or an error if the file can't be read
+    pub fn verify(&mut self) -> Result<ValidationReport> {
+        verify_document(self, &VerifyOptions::default())
+    }
+
+    /// Checks the structure of the document like [`PDFDocument::verify`] and runs the
+    /// optional checks selected by the options, e.g. the unknown keys check.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The optional checks to run
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the validation report, or an error if the file can't be read
+    pub fn verify_with(&mut self, options: &VerifyOptions) -> Result<ValidationReport> {
+        verify_document(self, options)
+    }
+
+    /// Counts the objects of the document by type, filter and purpose.
+    ///
+    /// The counts are computed in one pass over the xref table without reading the stream
+    /// data. Objects which can't be parsed are counted as unreadable.
+    ///
+    /// # Returns
+    ///
+    /// The statistics of the document
+    pub fn stats(&mut self) -> DocumentStats {
+        collect_stats(self)
+    }
+
+    /// Looks up a nested object by a path of dotted keys and bracketed array indices,
+    /// e.g. `Pages.Kids[0].MediaBox` starting at the catalog.
+    ///
+    /// Indirect references are resolved at every hop, including the root and the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The object the path starts at
+    /// * `path` - The path, see [`parse_path`]
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the object, `None` if a key is missing or an index is out of
+    /// bounds, or an error if the path is malformed or a segment doesn't fit the object it
+    /// is applied to
+    pub fn get_path(&mut self, root: &PDFObject, path: &str) -> Result<Option<PDFObject>> {
+        let segs = parse_path(path)?;
+        self.get_path_segs(root, &segs)
+    }
+
+    /// Looks up a nested object by pre-parsed path segments, see [`PDFDocument::get_path`].
+    pub fn get_path_segs(&mut self, root: &PDFObject, segs: &[PathSeg]) -> Result<Option<PDFObject>> {
+        let mut current = match self.resolve_path_hop(root.clone())? {
+            Some(object) => object,
+            None => return Ok(None),
+        };
+        for (index, seg) in segs.iter().enumerate() {
+            let next = match (seg, &current) {
+                (PathSeg::Key(key), PDFObject::Dict(dict)) => dict.get(key),
+                (PathSeg::Key(key), PDFObject::Stream(stream)) => stream.get_metadata().get(key),
+                (PathSeg::Index(i), PDFObject::Array(elements)) => elements.get(*i),
+                (_, object) => {
+                    let segment = segs[..=index].iter().fold(String::new(), |mut path, seg| {
+                        if !path.is_empty() && matches!(seg, PathSeg::Key(_)) {
+                            path.push('.');
+                        }
+                        path.push_str(&seg.to_string());
+                        path
+                    });
+                    return Err(PathSegmentMismatch { segment, found: object.type_name().to_string() });
+                }
+            };
+            current = match next {
+                Some(next) => match self.resolve_path_hop(next.clone())? {
+                    Some(object) => object,
+                    None => return Ok(None),
+                },
+                None => return Ok(None),
+            };
+        }
+        Ok(Some(current))
+    }
+
+    /// Returns the value of an object, following references like a path hop. A reference to
+    /// a missing object resolves to `None`.
+    pub(crate) fn resolve(&mut self, object: PDFObject) -> Result<Option<PDFObject>> {
+        self.resolve_path_hop(object)
+    }
+
+    /// Resolves a reference, following chains of references up to a fixed depth.
+    ///
+    /// A chain returning to a reference it already followed fails with `CircularReference`.
+    fn resolve_path_hop(&mut self, mut object: PDFObject) -> Result<Option<PDFObject>> {
+        let mut followed = Vec::new();
+        for _ in 0..MAX_REF_CHAIN {
+            object = match object {
+                PDFObject::ObjectRef(obj_num, gen_num) if followed.contains(&(obj_num, gen_num)) => {
+                    return Err(CircularReference { object: (obj_num, gen_num).into() });
+                }
+                PDFObject::ObjectRef(obj_num, gen_num) => {
+                    followed.push((obj_num, gen_num));
+                    match self.read_object_with_ref((obj_num, gen_num))? {
+                        Some(PDFObject::IndirectObject(_, _, value)) => *value,
+                        _ => return Ok(None),
+                    }
+                }
+                PDFObject::IndirectObject(_, _, value) => *value,
+                object => return Ok(Some(object)),
+            };
+        }
+        Err(PDFParseError("Reference chain is too long"))
+    }
+
+    /// Writes the merged cross-reference table in the familiar `0000000017 00000 n` form,
+    /// grouped into subsections of consecutive object numbers.
+    ///
+    /// Entries which failed validation so far are annotated with a `%` comment, e.g.
+    /// objects found unreadable or offsets beyond the end of the file. Compressed entries
+    /// show the index of the object and name its object stream in a comment.
+    ///
+    /// # Arguments
+    ///
+    /// * `w` - The destination
+    ///
+    /// # Returns
+    ///
+    /// A `fmt::Result` indicating whether writing succeeded
+    pub fn dump_xref(&self, w: &mut impl std::fmt::Write) -> std::fmt::Result {
+        let file_size = self.get_file_size().ok();
+        let mut entries = self.xrefs.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|it| it.obj_num);
+        writeln!(w, "xref")?;
+        let mut index = 0;
+        while index < entries.len() {
+            let first = entries[index].obj_num;
+            let len = entries[index..]
+                .iter()
+                .enumerate()
+                .take_while(|(i, entry)| entry.obj_num as u64 == first as u64 + *i as u64)
+                .count();
+            writeln!(w, "{} {}", first, len)?;
+            for entry in &entries[index..index + len] {
+                write!(w, "{}", entry)?;
+                if entry.using && self.unreadable.contains(&(entry.obj_num, entry.gen_num)) {
+                    write!(w, " % unreadable")?;
+                } else if let Some(object_stream) = entry.object_stream {
+                    write!(w, " % in object stream {}", object_stream)?;
+                } else if entry.using && file_size.is_some_and(|size| entry.value >= size) {
+                    write!(w, " % offset beyond end of file")?;
+                }
+                writeln!(w)?;
+            }
+            index += len;
+        }
+        Ok(())
+    }
+
+    /// Returns the cross-reference mechanism of the document.
+    pub fn xref_kind(&self) -> XrefKind {
+        self.xref_kind
+    }
+
+    /// Returns the number of cross-reference sections merged while opening, i.e. the
+    /// original section plus one per incremental update.
+    pub fn xref_sections(&self) -> usize {
+        self.xref_sections
+    }
+
+    /// Lists the objects each revision added, changed or deleted, revision 0 being the
+    /// original document and each incremental update appending one, e.g. to see what
+    /// changed after the revision a signature covers.
+    ///
+    /// # Returns
+    ///
+    /// The changes of each revision in file order, empty if the cross-reference data was
+    /// rebuilt by scanning the file
+    pub fn revision_changes(&self) -> &[RevisionChanges] {
+        &self.revisions
+    }
+
+    /// Returns the revision which introduced the current definition of an object.
+    ///
+    /// The overrides of [`PDFDocument::override_xref_entry`] don't count, this is the history
+    /// of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - The object number and generation number of the object
+    ///
+    /// # Returns
+    ///
+    /// The index of the revision, 0 for the original document, or `None` if the merged
+    /// table has no entry in use of the object
+    pub fn object_revision(&self, obj_ref: impl Into<ObjectId>) -> Option<usize> {
+        let entry = self.merged_xrefs.search(obj_ref.into().to_tuple()).ok()?;
+        entry.is_using().then_some(entry.revision)
+    }
+
+    /// Returns true if the cross-reference data refers to objects stored in object streams.
+    pub fn uses_object_streams(&self) -> bool {
+        self.xrefs.iter().any(XEntry::is_compressed)
+    }
+
+    /// Lists the features the document requires, whether the crate supports them or not,
+    /// see [`Feature::is_supported`].
+    ///
+    /// The encryption and the cross-reference form are detected while opening, the filters
+    /// are collected from all streams.
+    ///
+    /// # Returns
+    ///
+    /// The features in sorted order
+    pub fn required_features(&mut self) -> Vec<Feature> {
+        collect_required_features(self)
+    }
+
+    /// Lists the objects defined more than once in the file, scanning it as a whole.
+    ///
+    /// Reads always follow the xref, a definition at another offset is never read. Only a
+    /// document whose xref is rebuilt by a scan, see [`PDFDocument::open_partial`], uses the
+    /// last definition in file order like an incremental update would, recording a warning
+    /// with the offsets of both definitions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the duplicates ordered by object number, or an error if the
+    /// file can't be read
+    pub fn duplicate_definitions(&mut self) -> Result<Vec<DuplicateDefinition>> {
+        collect_duplicate_definitions(self)
+    }
+
+    /// Lists the object streams (`/Type /ObjStm`) and the objects each one contains.
+    ///
+    /// Only the headers are parsed, not the contained objects. The result is cached, so
+    /// later calls don't scan the document again.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the object streams in xref order,
+    /// or an error if the header of an object stream is malformed
+    pub fn object_streams(&mut self) -> Result<&[ObjectStream]> {
+        if self.object_streams.is_none() {
+            self.object_streams = Some(collect_object_streams(self)?);
+        }
+        Ok(self.object_streams.as_deref().unwrap_or_default())
+    }
+
+    /// Gets a name tree of the catalog `/Names` dictionary.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the tree in the `/Names` dictionary, e.g. `Dests`,
+    ///   `EmbeddedFiles` or `JavaScript`
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the tree, `None` if the catalog has no such tree, or an error if
+    /// the `/Names` dictionary can't be read
+    pub fn get_name_tree(&mut self, key: &str) -> Result<Option<NameTree>> {
+        let Some(names) = self.catalog_data.get_attrs().get(NAMES).cloned() else {
+            return Ok(None);
+        };
+        let root = match self.resolve(names)? {
+            Some(PDFObject::Dict(dict)) => dict.get(key).cloned(),
+            _ => None,
+        };
+        Ok(root.map(NameTree::new))
+    }
+
+    /// Gets the `/ParentTree` number tree of the structure tree root, which maps the
+    /// `/StructParents` of pages and annotations to their structure elements.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the tree, `None` if the document has no structure tree or it
+    /// has no parent tree, or an error if the structure tree root can't be read
+    pub fn get_parent_tree(&mut self) -> Result<Option<NumberTree>> {
+        let Some(struct_tree_root) = self.catalog_data.get_attrs().get(STRUCT_TREE_ROOT).cloned() else {
+            return Ok(None);
+        };
+        let root = match self.resolve(struct_tree_root)? {
+            Some(PDFObject::Dict(dict)) => dict.get(PARENT_TREE).cloned(),
+            _ => None,
+        };
+        Ok(root.map(NumberTree::new))
+    }
+
+    /// Counts the pages up to a limit, e.g. to tell whether a document has more than N
+    /// pages.
+    ///
+    /// The page tree objects are read again instead of using the tree built while
+    /// opening. The root `/Count` is trusted when the kids of the root add up to it. The
+    /// kids are only read until `limit` of them are pages, and the walk after them stops as
+    /// soon as `limit` pages are confirmed. The number of nodes read is thus bounded by the
+    /// limit and the depth of the tree rather than the size of the document.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The number of pages after which counting stops
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `Exact` with the number of pages if it is below the limit,
+    /// `AtLeast(limit)` otherwise, or an error if a page tree node can't be read
+    pub fn count_pages_up_to(&mut self, limit: usize) -> Result<CountResult> {
+        count_pages_up_to(self, limit)
+    }
+
+    /// Reads the page label ranges of the `/PageLabels` number tree of the catalog, e.g. a
+    /// lowercase roman range for the front matter followed by a decimal range for the body.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the ranges ordered by their first page, empty if the document
+    /// has no page labels, or an error if the tree can't be read
+    pub fn page_labels(&mut self) -> Result<&[PageLabelRange]> {
+        if self.page_labels.is_none() {
+            self.page_labels = Some(collect_page_labels(self)?);
+        }
+        Ok(self.page_labels.as_deref().unwrap_or_default())
+    }
+
+    /// Returns the label of a page, like `iv` or `A-3`, which viewers display instead of
+    /// the page number.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the page in page order
+    ///
+    /// # Returns
+    ///
+    /// The label, or `None` if the index is out of range, the document has no page labels
+    /// or they can't be read
+    pub fn page_label(&mut self, index: usize) -> Option<String> {
+        if index >= self.page_tree_arena.get_page_id_slice().len() {
+            return None;
+        }
+        label_of(self.page_labels().ok()?, index)
+    }
+
+    /// Checks whether the fonts used by the pages are embedded, as required by print shops.
+    ///
+    /// Each font in the `/Font` resources of a page, of the form XObjects they declare and of
+    /// the appearance streams of the page annotations is reported once with whether its font
+    /// program is embedded, whether it is a standard 14 font which may be used without
+    /// embedding and whether its name marks a subset. Type0 fonts are checked through their
+    /// descendant font. Fonts written inline in a `/Font` dictionary are included, and a font
+    /// which can't be read is reported as not embedded.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the preflight result, or an error if a page, its resources or a
+    /// form XObject can't be read
+    pub fn font_preflight(&mut self) -> Result<FontPreflight> {
+        font_preflight(self)
+    }
+
+    /// Decodes the text of a simple font with the given encoding for this session, in place
+    /// of its `/Encoding` or the fallback for fonts without one, e.g. for a Type3 font whose
+    /// glyph names are known from elsewhere. Composite fonts aren't affected.
+    ///
+    /// # Arguments
+    ///
+    /// * `font` - The object number and generation number of the font dictionary
+    /// * `encoding` - The encoding, replacing an earlier one of the font
+    pub fn set_font_encoding(&mut self, font: impl Into<ObjectId>, encoding: Encoding) {
+        self.font_encodings.insert(font.into().to_tuple(), encoding);
+    }
+
+    /// Removes the encoding set for a font, its own encoding is used again.
+    ///
+    /// # Arguments
+    ///
+    /// * `font` - The object number and generation number of the font dictionary
+    ///
+    /// # Returns
+    ///
+    /// The removed encoding, or `None` if none was set for the font
+    pub fn remove_font_encoding(&mut self, font: impl Into<ObjectId>) -> Option<Encoding> {
+        self.font_encodings.remove(&font.into().to_tuple())
+    }
+
+    /// Returns the encoding set for a font by [`PDFDocument::set_font_encoding`].
+    pub fn get_font_encoding(&self, font: impl Into<ObjectId>) -> Option<&Encoding> {
+        self.font_encodings.get(&font.into().to_tuple())
+    }
+
+    /// Finds the fonts and XObjects a page declares in its resources but never uses.
+    ///
+    /// A font is used when the content selects it by `Tf` and an XObject when the content
+    /// paints it by `Do`, also from inside form XObjects which inherit the page resources.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page in page order
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the unused resource names, or an error if the page is not
+    /// found or its content can't be parsed
+    pub fn unused_resources(&mut self, page_index: usize) -> Result<UnusedResources> {
+        collect_unused_resources(self, page_index)
+    }
+
+    /// Finds the unused resources of every page, see [`PDFDocument::unused_resources`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the report, or an error if the content of a page can't be parsed
+    pub fn resource_usage(&mut self) -> Result<ResourceUsageReport> {
+        collect_resource_usage(self)
+    }
+
+    /// Counts the operators of a page content by name, including the operators of the
+    /// form XObjects it paints, see [`PDFDocument::page_operator_histogram_with`].
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page in page order
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the count of each operator, or an error if the page is not
+    /// found or its content can't be parsed
+    pub fn page_operator_histogram(&mut self, page_index: usize) -> Result<HashMap<String, u32>> {
+        self.page_operator_histogram_with(page_index, &HistogramOptions::new())
+    }
+
+    /// Counts the operators of a page content by name with the given options.
+    ///
+    /// A form XObject counts each time it is painted, as its operators run again, and forms
+    /// nested in forms are followed. A form painting itself is handled like by text
+    /// extraction: an error in strict mode, skipped with a warning in lenient mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page in page order
+    /// * `options` - Whether the operators of forms are merged into the page counts
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the count of each operator, or an error if the page is not
+    /// found or its content can't be parsed
+    pub fn page_operator_histogram_with(&mut self, page_index: usize, options: &HistogramOptions) -> Result<HashMap<String, u32>> {
+        collect_page_operator_histogram(self, page_index, options)
+    }
+
+    /// Sums the operator counts of every page, a form painted by several pages counts for
+    /// each of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Whether the operators of forms are merged into the page counts
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the count of each operator, or an error if the content of a
+    /// page can't be parsed
+    pub fn operator_histogram(&mut self, options: &HistogramOptions) -> Result<HashMap<String, u32>> {
+        collect_operator_histogram(self, options)
+    }
+
+    /// Lists every image XObject of the document with its dimensions, filters, color space,
+    /// declared length and the pages painting it.
+    ///
+    /// Images are found by scanning all objects, so images which no page paints are listed
+    /// with no pages. Duplicates are found on request by [`ImageInventory::duplicates`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the inventory, or an error if a page content can't be read
+    pub fn image_inventory(&mut self) -> Result<ImageInventory> {
+        collect_images(self)
+    }
+
+    /// Lists the bytes the data of every stream object occupies in the file.
+    ///
+    /// The ranges allow carving or comparing regions without serializing the streams again,
+    /// overlapping ranges are reported as they indicate corruption.
+    ///
+    /// # Returns
+    ///
+    /// The stream map, objects which can't be parsed are left out
+    pub fn stream_map(&mut self) -> StreamMap {
+        collect_stream_map(self)
+    }
+
+    /// Reads the objects contained in an object stream.
+    ///
+    /// Entries whose offset is out of order or beyond the decoded data, see
+    /// [`ObjectStream::get_skipped`], and objects which can't be parsed are skipped and
+    /// recorded as warnings, the other objects are still returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - The reference of the object stream
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the object numbers and objects in header order, or an error if
+    /// the object isn't an object stream or `/N` disagrees with the header
+    pub fn object_stream_objects(&mut self, obj_ref: impl Into<ObjectId>) -> Result<Vec<(u32, PDFObject)>> {
+        read_object_stream_objects(self, obj_ref.into().to_tuple())
+    }
+
+    /// Returns the options the document was opened with.
+    pub(crate) fn get_options(&self) -> &ParseOptions {
+        self.tokenizer.get_options()
+    }
+
+    /// Records a warning at the offset of the given object.
+    pub(crate) fn warn_about(&mut self, obj_ref: ObjRefTuple, message: String) {
+        self.warn_about_with(obj_ref, WarningCode::Other, message);
+    }
+
+    /// Records a warning of the given kind at the offset of the given object.
+    pub(crate) fn warn_about_with(&mut self, obj_ref: ObjRefTuple, code: WarningCode, message: String) {
+        let offset = self.xrefs.find(obj_ref.0).map_or(0, |entry| entry.value);
+        self.tokenizer.warn_with(offset, code, message);
+    }
+
+    /// Decodes a stream, counting the decoded bytes against the limit of the parse options.
+    pub(crate) fn decode_stream(&mut self, stream: &Stream) -> Result<Vec<u8>> {
+        self.tokenizer.decode_stream(stream)
+    }
+
+    /// Fails if the time budget of the parse options is used up.
+    pub(crate) fn check_deadline(&self) -> Result<()> {
+        self.tokenizer.check_deadline()
+    }
+
+    /// Runs a closure reading objects without the data of their streams, e.g. to look at the
+    /// dictionaries of all objects, and restores the previous setting afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure reading the objects
+    ///
+    /// # Returns
+    ///
+    /// The result of the closure
+    pub(crate) fn with_skipped_stream_data<T>(&mut self, f: impl FnOnce(&mut PDFDocument) -> T) -> T {
+        let skip_stream_data = self.tokenizer.is_skip_stream_data();
+        self.tokenizer.set_skip_stream_data(true);
+        let result = f(self);
+        self.tokenizer.set_skip_stream_data(skip_stream_data);
+        result
+    }
+
+    /// Runs a closure, then drops the warnings it recorded and refunds the recovery budget
+    /// it used, for passes reporting their problems as findings.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure reading the objects
+    ///
+    /// # Returns
+    ///
+    /// The result of the closure
+    pub(crate) fn with_restored_diagnostics<T>(&mut self, f: impl FnOnce(&mut PDFDocument) -> T) -> T {
+        let warnings = self.tokenizer.get_warnings().len();
+        let recovery_scanned = self.tokenizer.get_recovery_scanned();
+        let result = f(self);
+        self.tokenizer.restore_diagnostics(warnings, recovery_scanned);
+        result
+    }
+
+    /// Gets the page tree of the document.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the page tree arena
+    pub fn get_page_tree(&self) -> &PageTreeArean {
+        &self.page_tree_arena
+    }
+
+    /// Gets the outline (bookmarks) of the document.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the outline tree arena, or `None` if the document has no outline
+    pub fn get_outline(&self) -> Option<&OutlineTreeArean> {
+        self.outline_tree_arean.as_ref()
+    }
+
+    /// Gets the document information dictionary.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the document info, or `None` if the trailer declares no `/Info`
+    pub fn get_describe(&self) -> Option<&PDFDescribe> {
+        self.describe.as_ref()
+    }
+
+    /// Stages a changed document information dictionary, written by
+    /// [`PDFDocument::save_incremental`]. [`PDFDocument::get_describe`] returns it from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `describe` - The information, entries which aren't set keep their current value
+    pub fn set_describe(&mut self, describe: PDFDescribe) {
+        self.describe = Some(describe);
+        self.describe_changed = true;
+    }
+
+    /// Stages an outline replacing the `/Outlines` of the catalog, written by
+    /// [`PDFDocument::save_incremental`]. [`PDFDocument::get_outline`] keeps returning the
+    /// outline read from the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `outline` - The outline, the destinations of its items are page indexes
+    pub fn set_outline(&mut self, outline: OutlineBuilder) {
+        self.staged_outline = Some(outline);
+    }
+
+    /// Writes the document with the staged changes appended as an incremental update.
+    ///
+    /// The original bytes, including any before the `%PDF-` header, are copied unchanged,
+    /// followed by the changed objects, a classic cross-reference section and a trailer whose
+    /// `/Prev` points at the previous section. The information dictionary keeps its object
+    /// number and the entries which weren't changed. A staged outline is appended under fresh
+    /// object numbers along with the catalog, which keeps its object number and points at the
+    /// new outline, the objects of the old outline stay in the file. Without staged changes
+    /// only the original bytes are written.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The destination
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, or an error if the document can't be read or written,
+    /// is encrypted, has no readable catalog or has an object number beyond the supported
+    /// ones, or a `PageNotFound` error if an item of the staged outline refers to a page out
+    /// of range
+    pub fn save_incremental(&mut self, out: &mut impl Write) -> Result<()> {
+        let describe = match &self.describe {
+            Some(describe) if self.describe_changed => Some(describe.clone()),
+            _ => None,
+        };
+        let outline = self.staged_outline.clone();
+        if (describe.is_some() || outline.is_some()) && self.encrypt.is_some() {
+            return Err(EncryptedUpdate);
+        }
+        // Bytes before the header are copied as well, the offsets stay relative to the header
+        let size = self.tokenizer.size()?;
+        let mut original = self.tokenizer.read_prefix()?;
+        let prefix_len = original.len();
+        original.extend(self.tokenizer.read_bytes(size as usize)?);
+        if describe.is_none() && outline.is_none() {
+            out.write_all(&original)?;
+            return Ok(());
+        }
+        let catalog_ref = self.require_catalog()?;
+        // A /Size beyond the supported object numbers is corrupt, numbering after it would
+        // overflow
+        let declared = match self.trailer_size {
+            Some(size) => match u32::try_from(size) {
+                Ok(size) if size <= MAX_OBJ_NUM + 1 => size,
+                _ => {
+                    self.tokenizer.warn(self.startxref, format!("trailer /Size {} is implausible, ignored", size));
+                    0
+                }
+            },
+            None => 0,
+        };
+        let highest = self.actual_max_object().unwrap_or(0);
+        if highest > MAX_OBJ_NUM {
+            return Err(PDFParseError0(format!("object number {} leaves no room for new objects", highest)));
+        }
+        let mut next_obj_num = (highest + 1).max(declared);
+        let mut objects = Vec::new();
+        let mut info = self.info;
+        if let Some(describe) = describe {
+            let (info_ref, mut dict) = match self.info {
+                Some(info) => (info, self.read_dict_with_ref(info)?.unwrap_or_default()),
+                None => {
+                    next_obj_num += 1;
+                    ((next_obj_num - 1, 0), Dictionary::default())
+                }
+            };
+            describe.write_into(&mut dict);
+            info = Some(info_ref);
+            objects.push((info_ref, PDFObject::Dict(dict)));
+        }
+        if let Some(outline) = outline {
+            let pages = self
+                .get_page_ids()
+                .into_iter()
+                .filter_map(|id| self.page_tree_arena.node(id).ok())
+                .map(|node| node.get_page_obj_ref())
+                .collect::<Vec<_>>();
+            let mut builder = DocumentBuilder::starting_at(next_obj_num);
+            let root = builder.set_outline(&outline, &pages)?;
+            objects.extend(builder.iter().map(|(obj_ref, object)| (obj_ref, object.clone())));
+            let mut catalog = self.read_dict_with_ref(catalog_ref)?.ok_or(ObjectAttrMiss("PDF catalog not found."))?;
+            catalog.insert(OUTLINES, PDFObject::ObjectRef(root.0, root.1));
+            objects.push((catalog_ref, PDFObject::Dict(catalog)));
+        }
+        objects.sort_by_key(|(obj_ref, _)| *obj_ref);
+        let mut buf = Vec::new();
+        if !original.ends_with(b"\n") && !original.ends_with(b"\r") {
+            buf.push(b'\n');
+        }
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (obj_ref, object) in &objects {
+            offsets.push((*obj_ref, original.len() - prefix_len + buf.len()));
+            writeln!(buf, "{} {} obj", obj_ref.0, obj_ref.1)?;
+            writer::write_object(object, &mut buf)?;
+            buf.extend_from_slice(b"\nendobj\n");
+        }
+        let xref_offset = original.len() - prefix_len + buf.len();
+        buf.extend_from_slice(b"xref\n");
+        // Consecutive object numbers share a subsection
+        for run in offsets.chunk_by(|a, b| a.0.0 + 1 == b.0.0) {
+            writeln!(buf, "{} {}", run[0].0.0, run.len())?;
+            for ((_, gen_num), offset) in run {
+                write!(buf, "{:010} {:05} n\r\n", offset, gen_num)?;
+            }
+        }
+        buf.extend_from_slice(b"trailer\n");
+        let max_obj_num = objects.iter().map(|((obj_num, _), _)| *obj_num).max().unwrap_or(0);
+        let mut trailer = DictBuilder::new()
+            .entry(SIZE, (next_obj_num as u64).max(max_obj_num as u64 + 1))
+            .reference(ROOT, catalog_ref);
+        if let Some(info) = info {
+            trailer = trailer.reference(INFO, info);
+        }
+        trailer = trailer.entry(PREV, self.startxref);
+        if let Some(id) = &self.id {
+            trailer = trailer.entry(ID, id.clone());
+        }
+        writer::write_object(&PDFObject::Dict(trailer.build()), &mut buf)?;
+        write!(buf, "\nstartxref\n{}\n%%EOF\n", xref_offset)?;
+        out.write_all(&original)?;
+        out.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Writes the document as a new file with a single cross-reference table.
+    ///
+    /// With [`WriteOptions::with_preserve_numbering`] every object keeps its number and
+    /// generation and the objects are written in the order of the source file, so unchanged
+    /// objects produce the same bytes as in the previous save. Objects stored in object
+    /// streams are written on their own, the object streams and cross-reference streams
+    /// become free entries. Otherwise the objects
+    /// reachable from the catalog are renumbered. The information dictionary is copied
+    /// unchanged in both cases, apart from the entries changed by
+    /// [`PDFDocument::set_describe`]. A stream which
+    /// [`WriteOptions::with_compress_streams`] can't decode is written unchanged and recorded
+    /// as a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The destination
+    /// * `options` - The options
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating whether writing succeeded, or an error if an object can't be
+    /// read, the trailer has no `/Root` or the document is encrypted
+    pub fn save(&mut self, out: &mut impl Write, options: &WriteOptions) -> Result<()> {
+        if self.encrypt.is_some() {
+            return Err(EncryptedUpdate);
+        }
+        let catalog_ref = self.require_catalog()?;
+        if !options.is_preserve_numbering() {
+            let mut builder = DocumentBuilder::new();
+            let mut mapping = HashMap::new();
+            let root = ObjectImporter::import_into(&mut builder, self, catalog_ref, &mut mapping)?;
+            builder.set_root(root);
+            // The information is copied unchanged, the staged changes are written into it
+            let describe = self.describe.clone().filter(|_| self.describe_changed);
+            match (self.info, describe) {
+                (Some(info), describe) => {
+                    let info = ObjectImporter::import_into(&mut builder, self, info, &mut mapping)?;
+                    if let Some(describe) = describe {
+                        let mut dict = builder.get(info).and_then(|it| it.as_dict()).cloned().unwrap_or_default();
+                        describe.write_into(&mut dict);
+                        builder.set(info, dict);
+                    }
+                    builder.set_info_ref(info);
+                }
+                (None, Some(describe)) => {
+                    builder.set_info(&describe);
+                }
+                (None, None) => {}
+            }
+            // Warnings refer to the source objects
+            let sources = mapping.into_iter().map(|(source, copy)| (copy, source)).collect::<HashMap<_, _>>();
+            let mut failed = Vec::new();
+            builder.write_reporting(out, options, &mut |obj_ref, e| {
+                failed.push((sources.get(&obj_ref).copied().unwrap_or(obj_ref), e))
+            })?;
+            self.warn_undecodable(failed);
+            return Ok(());
+        }
+        // The offsets give the order the objects were written in, compressed objects follow
+        // their object stream
+        let offsets = self.xrefs.iter().filter(|it| !it.is_compressed()).map(|it| (it.obj_num, it.value)).collect::<HashMap<_, _>>();
+        let position = |entry: &XEntry| match entry.object_stream {
+            Some(object_stream) => (offsets.get(&object_stream).copied().unwrap_or(0), entry.value + 1),
+            None => (entry.value, 0),
+        };
+        let mut entries = self.xrefs.iter().filter(|it| !it.is_freed()).map(|it| (position(it), (it.obj_num, it.gen_num))).collect::<Vec<_>>();
+        entries.sort();
+        let mut objects = Vec::with_capacity(entries.len());
+        for (_, obj_ref) in entries {
+            let object = match self.read_object_with_ref(obj_ref)? {
+                Some(PDFObject::IndirectObject(_, _, object)) => *object,
+                Some(object) => object,
+                None => PDFObject::Null,
+            };
+            // The compressed objects are written on their own and the table replaces the
+            // cross-reference streams, so the streams holding them would define them twice
+            if let PDFObject::Stream(stream) = &object
+                && (stream.is_object_stream() || stream.is_xref_stream())
+            {
+                continue;
+            }
+            objects.push((obj_ref, object));
+        }
+        let mut info = self.info;
+        if let Some(describe) = self.describe.as_ref().filter(|_| self.describe_changed) {
+            let next_obj_num = self.actual_max_object().map_or(1, |it| it + 1);
+            let info_ref = *info.get_or_insert((next_obj_num, 0));
+            match objects.iter_mut().find(|(obj_ref, _)| *obj_ref == info_ref) {
+                Some((_, PDFObject::Dict(dict))) => describe.write_into(dict),
+                Some((_, object)) => {
+                    let mut dict = Dictionary::default();
+                    describe.write_into(&mut dict);
+                    *object = PDFObject::Dict(dict);
+                }
+                None => {
+                    let mut dict = Dictionary::default();
+                    describe.write_into(&mut dict);
+                    objects.push((info_ref, PDFObject::Dict(dict)));
+                }
+            }
+        }
+        let free = self.xrefs.iter().filter(|it| it.is_freed()).map(|it| (it.obj_num, it.gen_num)).collect();
+        let mut trailer = DictBuilder::new().reference(ROOT, catalog_ref);
+        if let Some(info) = info {
+            trailer = trailer.reference(INFO, info);
+        }
+        if let Some(id) = &self.id {
+            trailer = trailer.entry(ID, id.clone());
+        }
+        let version = self.version.to_string();
+        let mut failed = Vec::new();
+        writer::write_file(&version, &objects, &free, trailer.build(), options, &mut |obj_ref, e| failed.push((obj_ref, e)), out)?;
+        self.warn_undecodable(failed);
+        Ok(())
+    }
+
+    /// Records a warning for each stream which was written unchanged by [`PDFDocument::save`]
+    /// because it can't be decoded.
+    fn warn_undecodable(&mut self, failed: Vec<(ObjRefTuple, PDFError)>) {
+        for ((obj_num, gen_num), e) in failed {
+            self.warn_about((obj_num, gen_num), format!(
+                "Stream {} {} can't be decoded, written unchanged: {}", obj_num, gen_num, e
+            ));
+        }
+    }
+
+    pub(crate) fn get_catalog_ref(&self) -> Option<ObjRefTuple> {
+        self.catalog
+    }
+
+    /// Returns the catalog reference, or an error if the trailer has no `/Root`.
+    fn require_catalog(&self) -> Result<ObjRefTuple> {
+        self.catalog.ok_or(ObjectAttrMiss("Trailer can't found catalog attr."))
+    }
+
+    /// Returns the `/Size` declared by the newest trailer.
+    ///
+    /// It should be one greater than the highest object number, see
+    /// [`PDFDocument::actual_max_object`].
+    pub fn declared_size(&self) -> Option<u64> {
+        self.trailer_size
+    }
+
+    /// Returns the highest object number of the merged xref tables.
+    pub fn actual_max_object(&self) -> Option<u32> {
+        self.xrefs.iter().map(|it| it.obj_num).max()
+    }
+
+    pub(crate) fn get_file_size(&self) -> Result<u64> {
+        self.tokenizer.size()
+    }
+
+    /// Returns the decoded content of a page.
+    ///
+    /// All content streams of the page are decoded through their filters and concatenated,
+    /// separated by a line feed so that operators of adjacent streams are not merged.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_id` - The ID of the page
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the content bytes, empty if the page has no contents,
+    /// or an error if the page is not found or a content stream can't be decoded
+    pub fn page_content_bytes(&mut self, page_id: NodeId) -> Result<Vec<u8>> {
+        read_page_content(self, page_id)
+    }
+
+    /// Returns the operations of the content of a page, see [`PDFDocument::page_content_bytes`].
+    ///
+    /// A malformed operation fails with a `ContentParseError` naming the page, the operation
+    /// and the offset within the content bytes. In lenient mode it's skipped with a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_id` - The ID of the page
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the operations in order, or an error if the page is not
+    /// found or its content can't be decoded or parsed
+    pub fn page_content_operations(&mut self, page_id: NodeId) -> Result<Vec<Operation>> {
+        let mut operations = Vec::new();
+        for_each_page_operation(self, page_id, |_, operation| {
+            operations.push(operation);
+            Ok(())
+        })?;
+        Ok(operations)
+    }
+
+    /// Extracts the text of every page in page order, see [`Page::text_with`].
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The extraction options
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the text of each page, or an error if the content of a page
+    /// can't be parsed
+    pub fn pages_text(&mut self, options: &TextExtractionOptions) -> Result<Vec<String>> {
+        self.get_page_ids().into_iter().map(|page_id| Page::new(page_id).text_with(self, options)).collect()
+    }
+
+    /// Extracts the text of every page like [`PDFDocument::pages_text`], but records the
+    /// outcome, number of characters and time of each page instead of stopping at the first
+    /// page which fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The extraction options
+    ///
+    /// # Returns
+    ///
+    /// The text of all pages separated by form feeds and the result of each page
+    pub fn extract_text_report(&mut self, options: &TextExtractionOptions) -> TextReport {
+        collect_text_report(self, options)
+    }
+
+    /// Returns the text lying under an annotation, e.g. a Highlight or Square annotation.
+    ///
+    /// The area of a markup annotation is given by its `/QuadPoints`, which may describe
+    /// several quadrilaterals, other annotations and markup annotations without valid
+    /// quadrilaterals use their `/Rect`. A word is covered when the middle of its box lies
+    /// inside the area.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page in page order
+    /// * `annot_index` - The index of the annotation in the `/Annots` array of the page
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the covered text in reading order, or an error if the page or
+    /// annotation is not found, the annotation has no area or the page content can't be parsed
+    pub fn text_under_annotation(&mut self, page_index: usize, annot_index: usize) -> Result<String> {
+        let (page_id, annot) = self.read_annotation(page_index, annot_index)?;
+        let mut numbers = |key: &str| -> Result<Vec<f64>> {
+            let values = self.get_path_segs(&annot, &[PathSeg::Key(key.to_string())])?;
+            Ok(values
+                .and_then(|it| it.into_array())
+                .map(|values| values.iter().filter_map(|it| it.as_number()).map(|it| it.as_f64()).collect())
+                .unwrap_or_default())
+        };
+        let points = numbers(QUAD_POINTS)?;
+        let quads = if !points.is_empty() && points.len().is_multiple_of(8) {
+            points
+                .chunks(8)
+                .map(|it| [(it[0], it[1]), (it[2], it[3]), (it[4], it[5]), (it[6], it[7])])
+                .collect::<Vec<_>>()
+        } else {
+            match numbers(RECT)?.as_slice() {
+                &[x0, y0, x1, y1] => vec![[(x0, y0), (x1, y0), (x1, y1), (x0, y1)]],
+                _ => return Err(ObjectAttrMiss("Annotation /Rect not found or malformed.")),
+            }
+        };
+        let fragments = extract_fragments(self, page_id)?;
+        Ok(text_in_quads(&fragments, &quads))
+    }
+
+    /// Returns the text shown by the normal appearance of an annotation.
+    ///
+    /// Tools flattening a form move the field values into the appearance streams of the
+    /// widget annotations and remove `/V`, so the appearance is the only place left holding
+    /// the value. A normal appearance with several states, e.g. of a check box, is selected
+    /// by `/AS`. Only words inside the `/BBox` of the appearance are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page in page order
+    /// * `annot_index` - The index of the annotation in the `/Annots` array of the page
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the text in reading order, `None` if the annotation has no
+    /// normal appearance stream, or an error if the page or annotation is not found or the
+    /// appearance can't be parsed
+    pub fn annotation_appearance_text(&mut self, page_index: usize, annot_index: usize) -> Result<Option<String>> {
+        let (_, annot) = self.read_annotation(page_index, annot_index)?;
+        let state = self.get_path_segs(&annot, &[PathSeg::Key(AS.to_string())])?;
+        let appearance = self.get_path_segs(&annot, &[PathSeg::Key(AP.to_string())])?;
+        let obj_ref = match appearance.as_ref().and_then(|it| it.as_dict()).and_then(|it| it.get(N)) {
+            Some(PDFObject::ObjectRef(obj_num, gen_num)) => (*obj_num, *gen_num),
+            Some(PDFObject::Dict(states)) => {
+                let state = state.as_ref().and_then(|it| it.as_name());
+                match state.and_then(|it| states.get(it)).and_then(|it| it.as_object_ref()) {
+                    Some(obj_ref) => obj_ref,
+                    None => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+        form_text(self, obj_ref)
+    }
+
+    /// Reads an annotation of a page by its index in the `/Annots` array.
+    fn read_annotation(&mut self, page_index: usize, annot_index: usize) -> Result<(NodeId, PDFObject)> {
+        let page_id = match self.get_page_ids().get(page_index) {
+            Some(page_id) => *page_id,
+            None => return Err(PageNotFound(format!("Page not found:{}", page_index))),
+        };
+        let page = PDFObject::Dict(self.get_page_node(page_id).map(|it| it.get_attrs().clone()).unwrap_or_default());
+        match self.get_path_segs(&page, &[PathSeg::Key(ANNOTS.to_string()), PathSeg::Index(annot_index)])? {
+            Some(annot) => Ok((page_id, annot)),
+            None => Err(ObjectAttrMiss("Page annotation not found.")),
+        }
+    }
+
+    /// Finds the pages using the given object as a font or an XObject.
+    ///
+    /// A page uses an object when its content stream selects a font (`Tf`) or paints
+    /// an XObject (`Do`) whose resource entry refers to the object. The references used
+    /// by each page are cached, so repeated queries don't parse the content again.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - The object number and generation number of the object
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the indexes of the pages using the object in page order,
+    /// or an error if a page content can't be read
+    pub fn pages_referencing(&mut self, obj_ref: impl Into<ObjectId>) -> Result<Vec<usize>> {
+        let obj_ref = obj_ref.into().to_tuple();
+        let mut indexes = Vec::new();
+        for (index, page_id) in self.get_page_ids().into_iter().enumerate() {
+            if !self.page_refs.contains_key(&page_id) {
+                let refs = self.collect_page_refs(page_id)?;
+                self.page_refs.insert(page_id, refs);
+            }
+            if self.page_refs[&page_id].contains(&obj_ref) {
+                indexes.push(index);
+            }
+        }
+        Ok(indexes)
+    }
+
+    /// Collects the references of the fonts and XObjects the page content uses.
+    fn collect_page_refs(&mut self, page_id: NodeId) -> Result<HashSet<ObjRefTuple>> {
+        let fonts = self.read_page_resource_refs(page_id, FONT)?;
+        let xobjects = self.read_page_resource_refs(page_id, XOBJECT)?;
+        let mut visitor = ResourceUsageVisitor {
+            fonts: vec![fonts],
+            xobjects: vec![xobjects],
+            refs: HashSet::new(),
+        };
+        visit_page_content(self, page_id, &mut visitor)?;
+        Ok(visitor.refs)
+    }
+
+    /// Reads the names and references of a resource category (e.g. `/Font`) of a page,
+    /// whose `/Resources` may be inherited from an ancestor page tree node.
+    ///
+    /// Both the `/Resources` dictionary and the category dictionary may be indirect objects.
+    pub(crate) fn read_page_resource_refs(
+        &mut self,
+        page_id: NodeId,
+        category: &str,
+    ) -> Result<HashMap<String, ObjRefTuple>> {
+        Ok(named_refs(&self.read_page_resource_dict(page_id, category)?))
+    }
+
+    /// Reads the dictionary of a resource category (e.g. `/Font`) of a page like
+    /// [`PDFDocument::read_page_resource_refs`], including the entries which aren't references.
+    pub(crate) fn read_page_resource_dict(&mut self, page_id: NodeId, category: &str) -> Result<Dictionary> {
+        if self.get_page_node(page_id).is_none() {
+            return Err(PageNotFound(format!("Page not found:{}", page_id)));
+        }
+        let resources = self.page_tree_arena.get_inherited_attr(page_id, RESOURCES).cloned();
+        self.read_resource_dict(resources.as_ref(), category)
+    }
+
+    /// Reads the names and references of a resource category (e.g. `/Font`) of a
+    /// `/Resources` value, which may be a dictionary or a reference to one.
+    ///
+    /// The category dictionary may be an indirect object as well.
+    pub(crate) fn read_resource_refs(
+        &mut self,
+        resources: Option<&PDFObject>,
+        category: &str,
+    ) -> Result<HashMap<String, ObjRefTuple>> {
+        Ok(named_refs(&self.read_resource_dict(resources, category)?))
+    }
+
+    /// Reads the dictionary of a resource category (e.g. `/Font`) of a `/Resources` value
+    /// like [`PDFDocument::read_resource_refs`], an empty dictionary if it is missing.
+    fn read_resource_dict(&mut self, resources: Option<&PDFObject>, category: &str) -> Result<Dictionary> {
+        let (obj_ref, is_resources) = match resources {
+            Some(PDFObject::Dict(resources)) => match resources.get(category) {
+                Some(PDFObject::Dict(dict)) => return Ok(dict.clone()),
+                Some(PDFObject::ObjectRef(obj_num, gen_num)) => ((*obj_num, *gen_num), false),
+                _ => return Ok(Dictionary::default()),
+            },
+            Some(PDFObject::ObjectRef(obj_num, gen_num)) => ((*obj_num, *gen_num), true),
+            _ => return Ok(Dictionary::default()),
+        };
+        let mut dict = match self.read_dict_with_ref(obj_ref)? {
+            Some(dict) => dict,
+            None => return Ok(Dictionary::default()),
+        };
+        if is_resources {
+            dict = match dict.remove(category) {
+                Some(PDFObject::Dict(dict)) => dict,
+                Some(PDFObject::ObjectRef(obj_num, gen_num)) => {
+                    self.read_dict_with_ref((obj_num, gen_num))?.unwrap_or_default()
+                }
+                _ => Dictionary::default(),
+            };
+        }
+        Ok(dict)
+    }
+
+    /// Reads an indirect object and returns its value if it is a dictionary.
+    fn read_dict_with_ref(&mut self, tuple: ObjRefTuple) -> Result<Option<Dictionary>> {
+        match self.read_object_with_ref(tuple)? {
+            Some(PDFObject::IndirectObject(_, _, value)) => Ok(value.into_dict()),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl ContentVisitor for ResourceUsageVisitor {
+    fn visit(&mut self, operation: &Operation) -> Result<()> {
+        let resources = match operation.get_operator() {
+            "Tf" => &self.fonts,
+            "Do" => &self.xobjects,
+            _ => return Ok(()),
+        };
+        let obj_ref = operation
+            .get_name_operand(0)
+            .and_then(|name| resources.iter().rev().find_map(|it| it.get(name)));
+        if let Some(obj_ref) = obj_ref {
+            self.refs.insert(*obj_ref);
+        }
+        Ok(())
+    }
+
+    fn begin_form(&mut self, document: &mut PDFDocument, _matrix: [f64; 6], resources: Option<&PDFObject>) -> Result<()> {
+        self.fonts.push(document.read_resource_refs(resources, FONT)?);
+        self.xobjects.push(document.read_resource_refs(resources, XOBJECT)?);
+        Ok(())
+    }
+
+    fn end_form(&mut self) {
+        self.fonts.pop();
+        self.xobjects.pop();
+    }
+}
+
+/// Collects the entries of a dictionary whose values are object references.
+fn named_refs(dict: &Dictionary) -> HashMap<String, ObjRefTuple> {
+    dict.iter()
+        .filter_map(|(name, value)| value.as_object_ref().map(|obj_ref| (name.to_string(), obj_ref)))
+        .collect()
+}
+
+/// Parses the PDF version from the beginning of the document.
+///
+/// The `%PDF-` header is searched in the first [`HEADER_SCAN_LEN`] bytes like Acrobat
+/// does, files extracted from e-mails or downloads may have bytes before it.
+///
+/// # Arguments
+///
+/// * `sequence` - A mutable reference to a sequence implementation for reading bytes
+///
+/// # Returns
+///
+/// A `Result` containing the parsed PDFVersion and the offset of the header, or an error if
+/// the header isn't found or the version is invalid
+fn parse_version(sequence: &mut impl Sequence) -> Result<(PDFVersion, u64)> {
+    let mut buf = [0u8; HEADER_SCAN_LEN + 8];
+    let mut n = 0;
+    while n < buf.len() {
+        match sequence.read(&mut buf[n..])? {
+            0 => break,
+            read => n += read,
+        }
+    }
+    let offset = buf[..n].windows(5).position(|it| it == b"%PDF-").filter(|it| *it <= HEADER_SCAN_LEN);
+    let Some(offset) = offset.filter(|it| it + 8 <= n) else {
+        return Err(InvalidPDFDocument);
+    };
+    let version = String::from_utf8(buf[offset + 5..offset + 8].to_vec())?;
+    Ok((version.try_into()?, offset as u64))
+}
+
+/// Parses the object at a byte offset, see [`PDFDocument::parse_at_offset`].
+fn parse_at(tokenizer: &mut Tokenizer, offset: u64) -> Result<(PDFObject, u64)> {
+    let size = tokenizer.size()?;
+    if offset >= size {
+        return Err(OffsetOutOfRange { offset, size });
+    }
+    tokenizer.seek(offset)?;
+    let object = parse(tokenizer)?;
+    Ok((object, tokenizer.position() - offset))
+}
+
+/// Scans the whole file for object definitions, see [`PDFDocument::scan_objects`].
+fn scan_object_headers(tokenizer: &mut Tokenizer) -> Result<Vec<(ObjRefTuple, u64)>> {
+    let limit = tokenizer.get_options().get_max_resync_scan();
+    let mut objects = Vec::new();
+    let mut offset = 0;
+    loop {
+        tokenizer.seek(offset)?;
+        let (start, keyword_end) = match find_next_object_header(tokenizer, limit)? {
+            Some(header) => header,
+            None => return Ok(objects),
+        };
+        // Resuming within the header would find `12 0 obj` again as `2 0 obj`
+        offset = match parse_at(tokenizer, start) {
+            Ok((PDFObject::IndirectObject(obj_num, gen_num, _), len)) => {
+                objects.push(((obj_num, gen_num), start));
+                start + len
+            }
+            Ok(_) => keyword_end,
+            Err(e) => {
+                tokenizer.warn(start, format!("Object at offset {} is unreadable: {}", start, e));
+                keyword_end
+            }
+        };
+    }
+}
+
+/// Builds xref entries from the objects found by a scan in file order, a later definition
+/// of an object replaces an earlier one like an incremental update does, which is recorded
+/// as a warning.
+fn rebuild_xref(tokenizer: &mut Tokenizer, objects: Vec<(ObjRefTuple, u64)>) -> Vec<XEntry> {
+    let mut newest = HashMap::<u32, XEntry>::new();
+    for ((obj_num, gen_num), offset) in objects {
+        if let Some(previous) = newest.get(&obj_num) {
+            tokenizer.warn(offset, format!(
+                "Object {} {} is defined at offsets {} and {}, the later one is used",
+                obj_num, gen_num, previous.value, offset
+            ));
+        }
+        newest.insert(obj_num, XEntry::new(obj_num, gen_num, offset, true));
+    }
+    let mut xrefs = newest.into_values().collect::<Vec<_>>();
+    xrefs.sort_by_key(|it| it.obj_num);
+    xrefs
+}
+
+/// Reads the catalog with the page tree and outline it refers to.
+fn read_catalog(
+    tokenizer: &mut Tokenizer,
+    catalog: ObjRefTuple,
+    xrefs: &XrefTable,
+) -> Result<(Catalog, PageTreeArean, Option<OutlineTreeArean>)> {
+    let span = span!("pdf.catalog", obj_num);
+    let _entered = span.enter();
+    span.record("obj_num", catalog.0 as u64);
+    decode_catalog_data(tokenizer, catalog, xrefs)
+}
+
+/// Records a warning about bytes before the `%PDF-` header, which are ignored.
+fn warn_header_offset(tokenizer: &mut Tokenizer, header_offset: u64) {
+    if header_offset > 0 {
+        tokenizer.warn(0, format!("{} bytes before the %PDF- header are ignored", header_offset));
+    }
+}
+
+/// Puts a document together from the parts read while opening, reading the Info
+/// dictionary the trailer declares.
+fn assemble(
+    (version, header_offset): (PDFVersion, u64),
+    mut tokenizer: Tokenizer,
+    xrefs: XrefTable,
+    trailer: Trailer,
+    startxref: u64,
+    encryption: Option<Feature>,
+    (catalog_data, page_tree_arena, outline_tree_arean): (Catalog, PageTreeArean, Option<OutlineTreeArean>),
+) -> PDFDocument {
+    let describe = trailer.info.and_then(|info| read_describe(&mut tokenizer, &xrefs, info));
+    // Page tree nodes replaced by placeholders
+    let unreadable = page_tree_arena
+        .get_page_id_slice()
+        .iter()
+        .filter_map(|id| page_tree_arena.node(*id).ok())
+        .filter(|node| node.is_unreadable())
+        .map(|node| node.get_page_obj_ref())
+        .collect();
+    PDFDocument {
+        merged_xrefs: xrefs.clone(),
+        xrefs,
+        xref_overrides: BTreeMap::new(),
+        version,
+        tokenizer,
+        page_tree_arena,
+        outline_tree_arean,
+        describe,
+        describe_changed: false,
+        staged_outline: None,
+        info: trailer.info,
+        startxref,
+        page_refs: HashMap::new(),
+        catalog: trailer.catalog,
+        catalog_data,
+        trailer_size: trailer.size,
+        unreadable,
+        encrypt: trailer.encrypt,
+        id: trailer.id,
+        encryption,
+        xref_kind: trailer.xref_kind,
+        xref_sections: trailer.xref_sections,
+        revisions: trailer.revisions,
+        object_streams: None,
+        page_labels: None,
+        font_encodings: HashMap::new(),
+        header_offset,
+    }
+}
+
+/// Merges cross-reference tables from a PDF document.
+///
+/// This function parses and merges multiple cross-reference tables that may exist
+/// in a PDF document, handling cases where there are previous xref tables referenced
+/// in the document trailer.
+///
+/// # Arguments
+///
+/// * `tokenizer` - A mutable reference to the tokenizer for parsing PDF content
+///
+/// # Returns
 ///
-/// A `Result` containing a tuple with the merged vector of XEntry objects and
-/// a tuple of the catalog object number and generation number, or an error if
-/// parsing fails
-fn merge_xref_table(
-    mut tokenizer: &mut Tokenizer,
-) -> Result<(Vec<XEntry>, Option<(u32, u16)>, Option<(u32, u16)>)> {
+/// A `Result` containing a tuple with the merged vector of XEntry objects, tagged with the
+/// revision which last touched their object, and the entries collected from the trailers,
+/// or an error if parsing fails
+fn merge_xref_table(tokenizer: &mut Tokenizer) -> Result<(Vec<XEntry>, Trailer)> {
+    let (mut xrefs, mut trailer) = merge_xref_sections(tokenizer)?;
+    let mut sections = std::mem::take(&mut trailer.sections);
+    sections.reverse();
+    trailer.revisions = assign_revisions(&mut xrefs, &sections);
+    Ok((xrefs, trailer))
+}
+
+/// Merges the cross-reference sections following the `/Prev` chain, newest first, and
+/// keeps the entries of each section in the trailer.
+fn merge_xref_sections(tokenizer: &mut Tokenizer) -> Result<(Vec<XEntry>, Trailer)> {
     let mut xrefs = Vec::<XEntry>::new();
-    let mut info = None;
-    let mut catalog = None;
+    // The object numbers of the entries merged so far
+    let mut merged = HashSet::new();
+    let mut trailer = Trailer::default();
+    let file_size = tokenizer.size()?;
+    // The offsets of the xref tables read so far, a /Prev must not point back at one of them
+    let mut visited = HashSet::new();
+    let mut prev = None;
+    let merge_span = span!("pdf.xref", sections, entries);
+    let _merging = merge_span.enter();
     loop {
+        let span = span!("pdf.xref_section", offset, entries);
+        let _entered = span.enter();
+        span.record("offset", tokenizer.position());
+        let position = tokenizer.position();
+        visited.insert(position);
         let is_xref = tokenizer.check_next_token0(false, |token| token.key_was(XREF))?;
-        if !is_xref {
-            return Err(XrefTableNotFound);
-        }
-        let entries = parse_text_xref(tokenizer)?;
-        if xrefs.is_empty() {
-            xrefs.extend_from_slice(&entries);
+        // A section is an xref table followed by its trailer, or a cross-reference stream
+        // whose dictionary holds the trailer entries
+        let (mut entries, trailer_offset, mut dictionary) = if is_xref {
+            let entries = parse_text_xref(tokenizer)?;
+            let trailer_offset = tokenizer.position();
+            match parse(tokenizer)? {
+                PDFObject::Dict(dictionary) => (entries, trailer_offset, dictionary),
+                _ => return Err(PDFParseError("Xref table broken.")),
+            }
         } else {
-            for entry in entries {
-                if let None = xrefs.iter().find(|it| it.obj_num == entry.obj_num) {
-                    xrefs.push(entry);
+            match (read_xref_stream(tokenizer, position), prev) {
+                (Some(Ok((entries, dictionary))), _) => {
+                    trailer.xref_kind = XrefKind::Stream;
+                    (entries, position, dictionary)
+                }
+                (Some(Err(e)), Some(prev)) if !e.is_abort() => {
+                    return broken_prev(tokenizer, xrefs, trailer, prev, format!(
+                        "trailer /Prev {} points at an unreadable cross-reference stream: {}", prev, e
+                    ));
                 }
+                (Some(Err(e)), _) => return Err(e),
+                (None, Some(prev)) => {
+                    return broken_prev(tokenizer, xrefs, trailer, prev, format!(
+                        "trailer /Prev {} doesn't point at an xref table", prev
+                    ));
+                }
+                (None, None) => return Err(XrefTableNotFound),
+            }
+        };
+        span.record("entries", entries.len() as u64);
+        trailer.xref_sections += 1;
+        tokenizer.get_options().report(Phase::Xref, trailer.xref_sections as u64, 0)?;
+        // /Size is advisory, the newest one is checked against the merged tables once
+        // they are all read
+        let size = dictionary.get_u64_num(SIZE);
+        // The newest trailer comes first, so older trailers never overwrite an entry
+        if trailer.size.is_none() {
+            trailer.size = size;
+            trailer.size_offset = trailer_offset;
+        }
+        if trailer.catalog.is_none() {
+            trailer.catalog = dictionary.get(ROOT).and_then(|it| it.as_object_ref());
+        }
+        if trailer.info.is_none() {
+            trailer.info = dictionary.get(INFO).and_then(|it| it.as_object_ref());
+        }
+        if trailer.encrypt.is_none() {
+            trailer.encrypt = dictionary.get(ENCRYPT).and_then(|it| it.as_object_ref());
+        }
+        if dictionary.contain(XREF_STM) && trailer.xref_kind != XrefKind::Stream {
+            trailer.xref_kind = XrefKind::Hybrid;
+        }
+        if let Some(offset) = dictionary.get_u64_num(XREF_STM) {
+            merge_hidden_entries(tokenizer, &mut entries, offset)?;
+        }
+        trailer.sections.push(entries.clone());
+        // The entries of newer sections shadow those of older ones
+        for entry in entries {
+            if merged.insert(entry.obj_num) {
+                xrefs.push(entry);
             }
         }
-        if let PDFObject::Dict(dictionary) = parse(&mut tokenizer)? {
-            if let Some(PDFObject::ObjectRef(obj_num, gen_num)) = dictionary.get(ROOT) {
-                catalog = Some((*obj_num, *gen_num));
-                if let Some(PDFObject::ObjectRef(obj_num, gen_num)) = dictionary.get(INFO) {
-                    info = Some((*obj_num, *gen_num));
-                }
+        if trailer.id.is_none()
+            && let Some(PDFObject::Array(id)) = dictionary.remove(ID)
+        {
+            trailer.id = Some(id);
+        }
+        // Recursive previous xref
+        if let Some(PDFObject::Number(PDFNumber::Unsigned(offset))) = dictionary.get(PREV) {
+            let offset = *offset;
+            if offset >= file_size {
+                return broken_prev(tokenizer, xrefs, trailer, offset, format!(
+                    "trailer /Prev {} exceeds file size {}", offset, file_size
+                ));
             }
-            // Recursive previous xref
-            if let Some(PDFObject::Number(PDFNumber::Unsigned(prev))) = dictionary.get(PREV) {
-                tokenizer.seek(*prev)?;
-                continue;
+            if visited.contains(&offset) {
+                return broken_prev(tokenizer, xrefs, trailer, offset, format!(
+                    "trailer /Prev {} points back at an xref table already read", offset
+                ));
             }
-            return Ok((xrefs, catalog, info));
+            tokenizer.seek(offset)?;
+            prev = Some(offset);
+            continue;
+        }
+        merge_span.record("sections", trailer.xref_sections as u64);
+        merge_span.record("entries", xrefs.len() as u64);
+        return Ok((xrefs, trailer));
+    }
+}
+
+/// Adds the entries of the cross-reference stream a hybrid-reference trailer points at by
+/// `/XRefStm` to the entries of its xref table.
+///
+/// The xref table takes precedence, so only objects the table doesn't list as in use are
+/// taken from the stream, typically the objects stored in object streams. Readers
+/// predating cross-reference streams ignore the stream, so one which can't be read is
+/// recorded as a warning and the table is used alone, unless reading it crossed a resource
+/// limit.
+fn merge_hidden_entries(tokenizer: &mut Tokenizer, entries: &mut Vec<XEntry>, offset: u64) -> Result<()> {
+    let hidden = match read_xref_stream(tokenizer, offset) {
+        Some(hidden) => hidden.map(|(entries, _)| entries),
+        None => Err(PDFParseError0("no cross-reference stream found there".to_string())),
+    };
+    let hidden = match hidden {
+        Ok(hidden) => hidden,
+        Err(e) if e.is_abort() => return Err(e),
+        Err(e) => {
+            tokenizer.warn(offset, format!("trailer /XRefStm {} can't be read, ignored: {}", offset, e));
+            return Ok(());
+        }
+    };
+    // The objects in use, a stream listing an object twice gives its first entry
+    let mut listed = entries.iter().filter(|it| it.is_using()).map(|it| it.obj_num).collect::<HashSet<_>>();
+    let hidden = hidden.into_iter().filter(|it| it.is_using() && listed.insert(it.obj_num)).collect::<Vec<_>>();
+    let replaced = hidden.iter().map(|it| it.obj_num).collect::<HashSet<_>>();
+    entries.retain(|it| !replaced.contains(&it.obj_num));
+    entries.extend(hidden);
+    Ok(())
+}
+
+/// Warns when the `/Size` of the newest trailer isn't one greater than the highest object
+/// number of the merged xref tables, a frequent symptom of botched incremental updates.
+fn check_declared_size(tokenizer: &mut Tokenizer, xrefs: &[XEntry], trailer: &Trailer) {
+    let max_obj_num = xrefs.iter().map(|it| it.obj_num).max();
+    if let (Some(size), Some(max_obj_num)) = (trailer.size, max_obj_num)
+        && size != max_obj_num as u64 + 1
+    {
+        tokenizer.warn(trailer.size_offset, format!(
+            "trailer /Size {} doesn't match the highest object number {}", size, max_obj_num
+        ));
+    }
+}
+
+/// Reads the document information dictionary.
+///
+/// The information is optional, so a missing, unreadable or malformed `/Info` object is
+/// recorded as a warning instead of failing to open the document.
+fn read_describe(tokenizer: &mut Tokenizer, xrefs: &XrefTable, info: ObjRefTuple) -> Option<PDFDescribe> {
+    let (obj_num, gen_num) = info;
+    let entry = match xrefs.search(info) {
+        Ok(entry) => entry,
+        Err(_) => {
+            tokenizer.warn(0, format!("Info object {} {} is missing from the xref table, ignored", obj_num, gen_num));
+            return None;
+        }
+    };
+    let object = match parse_entry(tokenizer, entry) {
+        Ok(PDFObject::IndirectObject(_, _, value)) => *value,
+        Ok(object) => object,
+        Err(e) => {
+            tokenizer.warn(entry.value, format!("Info object {} {} is unreadable, ignored: {}", obj_num, gen_num, e));
+            return None;
         }
-        return Err(PDFParseError("Xref table broken."));
+    };
+    match object {
+        PDFObject::Dict(dict) => Some(PDFDescribe::from_dict(dict)),
+        _ => {
+            tokenizer.warn(entry.value, format!("Info object {} {} is not a dictionary, ignored", obj_num, gen_num));
+            None
+        }
+    }
+}
+
+/// Reads the cross-reference stream at the offset.
+///
+/// # Returns
+///
+/// The entries of the stream and its dictionary, which holds the trailer entries, `None`
+/// if there is no cross-reference stream at the offset, or an error if the stream can't
+/// be decoded or its data is malformed
+fn read_xref_stream(tokenizer: &mut Tokenizer, offset: u64) -> Option<Result<(Vec<XEntry>, Dictionary)>> {
+    match parse_with_offset(tokenizer, offset) {
+        Ok(PDFObject::IndirectObject(_, _, object)) => match object.into_stream() {
+            Some(stream) if stream.is_xref_stream() => {
+                let entries = tokenizer.decode_stream(&stream).and_then(|data| parse_xref_stream(&stream, data));
+                Some(entries.map(|entries| (entries, stream.get_metadata().clone())))
+            }
+            _ => None,
+        },
+        Err(e) if e.is_abort() => Some(Err(e)),
+        _ => None,
     }
 }
 
+/// Names the encryption of the document, which can't be decrypted, and records a warning.
+///
+/// The document still opens, the tokenizer fails to decode stream data in strict mode.
+fn check_encryption(tokenizer: &mut Tokenizer, xrefs: &XrefTable, encrypt: ObjRefTuple) -> Feature {
+    let (obj_num, gen_num) = encrypt;
+    let (offset, flavor) = match xrefs.search(encrypt) {
+        Ok(entry) => match parse_entry(tokenizer, entry) {
+            Ok(PDFObject::IndirectObject(_, _, value)) => (entry.value, value.as_dict().map(encryption_flavor)),
+            _ => (entry.value, None),
+        },
+        Err(_) => (0, None),
+    };
+    let feature = Feature::Encryption(flavor.unwrap_or_else(|| "unknown".to_string()));
+    let detail = format!("encryption dictionary {} {} can't be decrypted", obj_num, gen_num);
+    tokenizer.warn_with(offset, WarningCode::Unsupported, format!("Unsupported {}: {}", feature, detail));
+    tokenizer.set_encryption(Some(feature.clone()));
+    feature
+}
+
+/// Handles a /Prev which doesn't lead to another xref table.
+///
+/// In lenient mode the older xref tables are ignored and a warning is recorded, otherwise
+/// the message is returned as error.
+fn broken_prev(
+    tokenizer: &mut Tokenizer,
+    xrefs: Vec<XEntry>,
+    trailer: Trailer,
+    prev: u64,
+    message: String,
+) -> Result<(Vec<XEntry>, Trailer)> {
+    if !tokenizer.get_options().is_lenient() {
+        return Err(PDFParseError0(message));
+    }
+    tokenizer.warn(prev, message);
+    Ok((xrefs, trailer))
+}
+
 /// Calculates the offset of the cross-reference table in the PDF document.
 ///
 /// This function searches for the "startxref" keyword near the end of the document
 /// and extracts the offset value that points to the beginning of the cross-reference table.
+/// The search starts with the last [`STARTXREF_WINDOW`] bytes and widens eightfold up to
+/// `max_scan` bytes, each window is searched as a whole so the keyword can't straddle
+/// two reads. Of several keywords the last one followed by an offset is used.
 ///
 /// # Arguments
 ///
 /// * `sequence` - A mutable reference to a sequence implementation for reading bytes
+/// * `max_scan` - The maximum number of bytes searched, at least one window is searched
 ///
 /// # Returns
 ///
 /// A `Result` containing the calculated offset as a u64 value, or an error if the
 /// startxref keyword cannot be found or the offset cannot be parsed
-fn cal_xref_table_offset(sequence: &mut impl Sequence) -> Result<u64> {
+fn cal_xref_table_offset(sequence: &mut impl Sequence, max_scan: usize) -> Result<u64> {
     let size = sequence.size()?;
-    let pos = if size > 1024 { size - 1024 } else { 0 };
-    let mut buf = [0u8; 1024];
-    sequence.seek(pos)?;
-    let n = sequence.read(&mut buf)?;
-    let chars = START_XREF.as_bytes();
-    let mut tx = chars.len();
-    let mut index = n;
-    for i in (0..n).rev() {
-        let b = buf[i];
-        if chars[tx - 1] == b {
-            tx -= 1;
-            if tx == 0 {
-                index = i;
-                break;
+    let limit = (max_scan.max(STARTXREF_WINDOW) as u64).min(size);
+    let mut window = STARTXREF_WINDOW as u64;
+    loop {
+        let len = window.min(limit);
+        sequence.seek(size - len)?;
+        let mut buf = vec![0u8; len as usize];
+        let mut n = 0;
+        while n < buf.len() {
+            match sequence.read(&mut buf[n..])? {
+                0 => break,
+                read => n += read,
             }
         }
+        let buf = &buf[..n];
+        let keyword = START_XREF.as_bytes();
+        let offset = buf
+            .windows(keyword.len())
+            .enumerate()
+            .rev()
+            .filter(|(_, it)| *it == keyword)
+            .find_map(|(index, _)| {
+                let tail = &buf[index + keyword.len()..];
+                let start = tail.iter().position(|b| !is_white_space(*b))?;
+                let digits = tail[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+                literal_to_u64(&tail[start..start + digits])
+            });
+        if let Some(offset) = offset {
+            return Ok(offset);
+        }
+        if len == limit {
+            return Err(InvalidPDFDocument);
+        }
+        window = window.saturating_mul(8);
     }
-    // Can't find start xref
-    if index == n {
-        return Err(InvalidPDFDocument);
+}
+
+impl PDFDescribe {
+    /// Returns the name of the application that converted the document to PDF.
+    pub fn get_producer(&self) -> Option<&str> {
+        self.producer.as_deref()
     }
-    index = index + chars.len();
-    let crlf_num = count_leading_line_endings(&buf[index..n]);
-    let start = index + (crlf_num as usize);
-    let mut end = 0usize;
-    for i in start..n {
-        if line_ending(buf[i]) {
-            end = i;
-            break;
+
+    /// Returns the name of the application that created the original document.
+    pub fn get_creator(&self) -> Option<&str> {
+        self.creator.as_deref()
+    }
+
+    /// Returns the date the document was created.
+    pub fn get_creation_date(&self) -> Option<&Date> {
+        self.creation_date.as_ref()
+    }
+
+    /// Returns the name of the person who created the document.
+    pub fn get_author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// Returns the title of the document.
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the date the document was most recently modified.
+    pub fn get_mod_date(&self) -> Option<&Date> {
+        self.mod_date.as_ref()
+    }
+
+    /// Returns the subject of the document.
+    pub fn get_subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    /// Returns the keywords associated with the document.
+    pub fn get_keywords(&self) -> Option<&str> {
+        self.keywords.as_deref()
+    }
+
+    /// Returns whether the document has been trapped, or `None` if `/Trapped` is absent or
+    /// has an invalid value.
+    pub fn get_trapped(&self) -> Option<Trapped> {
+        self.trapped
+    }
+
+    /// Returns the entries which aren't defined by the specification in unspecified order.
+    pub fn get_custom(&self) -> impl Iterator<Item = (&str, &PDFObject)> {
+        self.custom.iter()
+    }
+
+    /// Returns true if `/Producer` is replaced by the name and version of this library
+    /// when written.
+    pub fn is_producer_stamp(&self) -> bool {
+        self.producer_stamp
+    }
+
+    /// Creates an empty information dictionary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the title of the document.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the name of the person who created the document.
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Sets the subject of the document.
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Sets the keywords associated with the document.
+    pub fn with_keywords(mut self, keywords: impl Into<String>) -> Self {
+        self.keywords = Some(keywords.into());
+        self
+    }
+
+    /// Sets the name of the application that created the original document.
+    pub fn with_creator(mut self, creator: impl Into<String>) -> Self {
+        self.creator = Some(creator.into());
+        self
+    }
+
+    /// Sets the name of the application that converted the document to PDF, it is only
+    /// written if the producer stamp is turned off.
+    pub fn with_producer(mut self, producer: impl Into<String>) -> Self {
+        self.producer = Some(producer.into());
+        self
+    }
+
+    /// Sets whether `/Producer` is replaced by the name and version of this library when
+    /// written, which is the default.
+    pub fn with_producer_stamp(mut self, producer_stamp: bool) -> Self {
+        self.producer_stamp = producer_stamp;
+        self
+    }
+
+    /// Sets the date the document was created.
+    pub fn with_creation_date(mut self, creation_date: Date) -> Self {
+        self.creation_date = Some(creation_date);
+        self
+    }
+
+    /// Sets the date the document was most recently modified.
+    pub fn with_mod_date(mut self, mod_date: Date) -> Self {
+        self.mod_date = Some(mod_date);
+        self
+    }
+
+    /// Sets whether the document has been trapped.
+    pub fn with_trapped(mut self, trapped: Trapped) -> Self {
+        self.trapped = Some(trapped);
+        self
+    }
+
+    /// Sets an entry which isn't defined by the specification, a standard key like
+    /// `Title` is ignored as it is set by its own method. References aren't written.
+    pub fn with_custom(mut self, key: impl Into<Name>, value: impl Into<PDFObject>) -> Self {
+        let key = key.into();
+        if !STANDARD_INFO_KEYS.contains(&key.as_str()) {
+            self.custom.insert(key, value);
         }
+        self
     }
-    if end == 0 || start == end {
-        return Err(InvalidPDFDocument);
+
+    /// Writes the entries into an information dictionary, keeping the entries which are
+    /// not set. Text is written as UTF-16BE when it isn't ASCII.
+    ///
+    /// Custom entries referring to other objects aren't written, the references are only
+    /// valid in the document they were read from, whose dictionary already has them.
+    pub(crate) fn write_into(&self, dict: &mut Dictionary) {
+        let producer = match self.producer_stamp {
+            true => Some(format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))),
+            false => self.producer.clone(),
+        };
+        let texts = [
+            (TITLE, self.title.as_deref()),
+            (AUTHOR, self.author.as_deref()),
+            (SUBJECT, self.subject.as_deref()),
+            (KEYWORDS, self.keywords.as_deref()),
+            (CREATOR, self.creator.as_deref()),
+            (PRODUCER, producer.as_deref()),
+        ];
+        for (key, text) in texts {
+            if let Some(text) = text {
+                dict.insert(key, encode_text(text));
+            }
+        }
+        for (key, date) in [(CREATION_DATE, self.creation_date), (MOD_DATE, self.mod_date)] {
+            if let Some(date) = date {
+                dict.insert(key, PDFString::literal(date.to_string()));
+            }
+        }
+        if let Some(trapped) = self.trapped {
+            dict.insert(TRAPPED, PDFObject::Named(Name::from(trapped.as_name())));
+        }
+        for (key, value) in self.custom.iter() {
+            if !matches!(value, PDFObject::ObjectRef(..)) {
+                dict.insert(key, value.clone());
+            }
+        }
     }
-    let offset = literal_to_u64(&buf[start..end]);
-    Ok(offset)
-}
 
-impl PDFDescribe {
-    pub(crate) fn new(dictionary: Dictionary) -> PDFDescribe {
+    pub(crate) fn from_dict(dictionary: Dictionary) -> PDFDescribe {
         let encoding = PreDefinedEncoding::PDFDoc;
         let producer = convert_glyph_from_dict!(dictionary, PRODUCER, &encoding);
         let creator = convert_glyph_from_dict!(dictionary, CREATOR, &encoding);
         let creation_date =
             convert_glyph_from_dict!(dictionary, CREATION_DATE, &encoding).map_or(None, |text| {
-                match Date::from_str(text.as_str()) {
-                    Ok(date) => Some(date),
-                    Err(_) => None,
-                }
+                Date::from_str(text.as_str()).ok()
             });
         let mod_date =
             convert_glyph_from_dict!(dictionary, MOD_DATE, &encoding).map_or(None, |text| {
-                match Date::from_str(text.as_str()) {
-                    Ok(date) => Some(date),
-                    Err(_) => None,
-                }
+                Date::from_str(text.as_str()).ok()
             });
         let author = convert_glyph_from_dict!(dictionary, AUTHOR, &encoding);
         let title = convert_glyph_from_dict!(dictionary, TITLE, &encoding);
+        let subject = convert_glyph_from_dict!(dictionary, SUBJECT, &encoding);
+        let keywords = convert_glyph_from_dict!(dictionary, KEYWORDS, &encoding);
+        let trapped = dictionary.get(TRAPPED).and_then(Trapped::from_object);
+        let mut custom = dictionary;
+        for key in STANDARD_INFO_KEYS {
+            custom.remove(key);
+        }
         PDFDescribe {
             producer,
             creator,
@@ -364,6 +2761,29 @@ impl PDFDescribe {
             author,
             title,
             mod_date,
+            subject,
+            keywords,
+            trapped,
+            custom,
+            producer_stamp: true,
+        }
+    }
+}
+
+impl Default for PDFDescribe {
+    fn default() -> Self {
+        PDFDescribe {
+            producer: None,
+            creator: None,
+            creation_date: None,
+            author: None,
+            title: None,
+            mod_date: None,
+            subject: None,
+            keywords: None,
+            trapped: None,
+            custom: Dictionary::default(),
+            producer_stamp: true,
         }
     }
 }
@@ -0,0 +1,78 @@
+use crate::document::PDFDocument;
+use crate::error::Result;
+use crate::objects::ObjRefTuple;
+use std::collections::BTreeMap;
+
+/// An object defined more than once in the file, see [`PDFDocument::duplicate_definitions`].
+///
+/// Incremental updates legitimately redefine objects, the xref of the newest revision then
+/// designates the last definition. A definition the xref skips or never reaches can hide
+/// content from some readers only, the pattern of shadow attacks and polyglot files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateDefinition {
+    obj_ref: ObjRefTuple,
+    /// The offsets of the definitions in file order.
+    offsets: Vec<u64>,
+    /// The offset the xref designates, if it is one of the definitions.
+    designated: Option<u64>,
+}
+
+impl DuplicateDefinition {
+    /// Returns the object number and generation number of the object.
+    pub fn get_object(&self) -> ObjRefTuple {
+        self.obj_ref
+    }
+
+    /// Returns the offsets of all definitions in file order.
+    pub fn get_offsets(&self) -> &[u64] {
+        &self.offsets
+    }
+
+    /// Returns the offset of the definition the xref designates, which reads follow, or
+    /// `None` if the xref designates none of them.
+    pub fn get_designated_offset(&self) -> Option<u64> {
+        self.designated
+    }
+
+    /// Returns the offsets of the definitions reads never see.
+    pub fn get_shadowed_offsets(&self) -> Vec<u64> {
+        self.offsets.iter().copied().filter(|it| Some(*it) != self.designated).collect()
+    }
+
+    /// Returns true if the xref designates the last definition in file order, like an
+    /// incremental update does.
+    pub fn is_last_designated(&self) -> bool {
+        self.designated.is_some() && self.designated == self.offsets.last().copied()
+    }
+}
+
+/// Lists the objects defined more than once by scanning the whole file.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// A `Result` containing the duplicates ordered by object number, or an error if the file
+/// can't be read
+pub(crate) fn collect_duplicate_definitions(document: &mut PDFDocument) -> Result<Vec<DuplicateDefinition>> {
+    let objects = document.with_skipped_stream_data(PDFDocument::scan_objects);
+    let mut definitions = BTreeMap::<ObjRefTuple, Vec<u64>>::new();
+    for (obj_ref, offset) in objects? {
+        definitions.entry(obj_ref).or_default().push(offset);
+    }
+    let duplicates = definitions
+        .into_iter()
+        .filter(|(_, offsets)| offsets.len() > 1)
+        .map(|(obj_ref, offsets)| {
+            let designated = document
+                .get_xref_entry(obj_ref)
+                .filter(|it| it.is_using() && !it.is_compressed())
+                .map(|it| it.get_value())
+                .filter(|it| offsets.contains(it));
+            DuplicateDefinition { obj_ref, offsets, designated }
+        })
+        .collect();
+    Ok(duplicates)
+}
@@ -1,11 +1,26 @@
-/// Enum for pdf predefined encodings
-pub(crate) enum PreDefinedEncoding {
+use crate::objects::PDFObject;
+use std::sync::LazyLock;
+
+/// A predefined encoding of the PDF specification, see [`Encoding::predefined`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreDefinedEncoding {
     MacRoman,
     Standard,
     WinAnsi,
     PDFDoc,
     MacExpert,
 }
+
+/// A single-byte encoding, mapping each character code to the character of its glyph.
+///
+/// The tables of the predefined encodings are shared, see [`Encoding::predefined`], a custom
+/// table is built from its codes with [`Encoding::from_pairs`] and used for a font with
+/// [`crate::document::PDFDocument::set_font_encoding`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    codes: [Option<char>; 256],
+}
+
 type EncodingEntry = (u8, &'static str, Option<char>);
 
 include!("../encoding/MacRoman");
@@ -14,24 +29,145 @@ include!("../encoding/WinAnsi");
 include!("../encoding/PDFDoc");
 include!("../encoding/MacExpert");
 
+/// The tables of the predefined encodings in the order of [`PreDefinedEncoding`].
+static PREDEFINED: LazyLock<[Encoding; 5]> = LazyLock::new(|| {
+    let table = |entries: &[EncodingEntry]| {
+        Encoding::from_pairs(entries.iter().filter_map(|(code, _, chr)| Some((*code, (*chr)?))))
+    };
+    [
+        table(&MAC_ROMAN_ENCODING),
+        table(&STANDARD_ENCODING),
+        table(&WIN_ANSI_ENCODING),
+        Encoding { codes: PDF_DOC_ENCODING },
+        table(&MAC_EXPERT_ENCODING),
+    ]
+});
 
-pub(crate) fn mapper_chr_from_u8(bytes: u8, encoding: &PreDefinedEncoding) -> Option<char> {
-    match encoding {
-        PreDefinedEncoding::PDFDoc => {
-            PDF_DOC_ENCODING[bytes as usize]
+impl PreDefinedEncoding {
+    /// Returns the encoding of a name as used by a font `/Encoding` or `/BaseEncoding`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the encoding, e.g. `WinAnsiEncoding`
+    ///
+    /// # Returns
+    ///
+    /// The encoding, or `None` if no predefined encoding has the name
+    pub fn from_name(name: &str) -> Option<PreDefinedEncoding> {
+        match name {
+            "MacRomanEncoding" => Some(PreDefinedEncoding::MacRoman),
+            "StandardEncoding" => Some(PreDefinedEncoding::Standard),
+            "WinAnsiEncoding" => Some(PreDefinedEncoding::WinAnsi),
+            "PDFDocEncoding" => Some(PreDefinedEncoding::PDFDoc),
+            "MacExpertEncoding" => Some(PreDefinedEncoding::MacExpert),
+            _ => None,
         }
-        _ => {
-            let look_table = match encoding {
-                PreDefinedEncoding::MacRoman => MAC_ROMAN_ENCODING,
-                PreDefinedEncoding::Standard => STANDARD_ENCODING,
-                PreDefinedEncoding::WinAnsi => WIN_ANSI_ENCODING,
-                PreDefinedEncoding::MacExpert => MAC_EXPERT_ENCODING,
-                _ => return None
-            };
-            look_table.iter()
-                .filter(|e| e.0 == bytes)
-                .map(|e| e.2)
-                .next()?
+    }
+
+    /// Returns the name of the encoding, e.g. `WinAnsiEncoding`.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            PreDefinedEncoding::MacRoman => "MacRomanEncoding",
+            PreDefinedEncoding::Standard => "StandardEncoding",
+            PreDefinedEncoding::WinAnsi => "WinAnsiEncoding",
+            PreDefinedEncoding::PDFDoc => "PDFDocEncoding",
+            PreDefinedEncoding::MacExpert => "MacExpertEncoding",
         }
     }
-}
\ No newline at end of file
+
+    /// Returns the table of the encoding.
+    pub fn get_encoding(&self) -> &'static Encoding {
+        &PREDEFINED[*self as usize]
+    }
+}
+
+impl Encoding {
+    /// Returns the table of a predefined encoding, see [`PreDefinedEncoding::from_name`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the encoding, e.g. `WinAnsiEncoding`
+    ///
+    /// # Returns
+    ///
+    /// The table, or `None` if no predefined encoding has the name
+    pub fn predefined(name: &str) -> Option<&'static Encoding> {
+        PreDefinedEncoding::from_name(name).map(|it| it.get_encoding())
+    }
+
+    /// Builds an encoding from the character of each code, codes which aren't listed have
+    /// no character. Of pairs with the same code the last one is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `pairs` - The codes with their characters
+    ///
+    /// # Returns
+    ///
+    /// The encoding
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (u8, char)>) -> Encoding {
+        let mut codes = [None; 256];
+        for (code, chr) in pairs {
+            codes[code as usize] = Some(chr);
+        }
+        Encoding { codes }
+    }
+
+    /// Returns the character of a code, or `None` if the code has none.
+    pub fn lookup(&self, code: u8) -> Option<char> {
+        self.codes[code as usize]
+    }
+
+    /// Sets the character of a code, `None` leaves the code without character.
+    pub(crate) fn set(&mut self, code: u8, chr: Option<char>) {
+        self.codes[code as usize] = chr;
+    }
+
+    /// Applies a `/Differences` array, e.g. `[39 /quotesingle 96 /grave]`.
+    ///
+    /// Each number sets the code of the glyph name following it, the names after it take
+    /// the next codes. Glyph names are looked up like by [`mapper_chr_from_glyph_name`],
+    /// codes past 255 and other items are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `differences` - The items of the array
+    pub fn apply_differences(&mut self, differences: &[PDFObject]) {
+        let mut code = 0usize;
+        for item in differences {
+            match item {
+                PDFObject::Number(number) => code = number.as_f64() as usize,
+                PDFObject::Named(name) => {
+                    if let Ok(code) = u8::try_from(code) {
+                        self.set(code, mapper_chr_from_glyph_name(name));
+                    }
+                    code += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Looks up the character of a glyph name, e.g. from an encoding `/Differences` array.
+///
+/// Names of the predefined encodings are resolved first, then names of the form `uniXXXX`.
+pub(crate) fn mapper_chr_from_glyph_name(name: &str) -> Option<char> {
+    let tables = [
+        STANDARD_ENCODING,
+        WIN_ANSI_ENCODING,
+        MAC_ROMAN_ENCODING,
+        MAC_EXPERT_ENCODING,
+    ];
+    let found = tables.iter()
+        .flat_map(|table| table.iter())
+        .find(|e| e.1 == name)
+        .and_then(|e| e.2);
+    if found.is_some() {
+        return found;
+    }
+    name.strip_prefix("uni")
+        .filter(|hex| hex.len() == 4)
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .and_then(char::from_u32)
+}
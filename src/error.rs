@@ -1,3 +1,6 @@
+use crate::features::Feature;
+use crate::options::Resource;
+use crate::objects::{ObjRefTuple, ObjectId};
 use std::num::{ParseFloatError, ParseIntError};
 use std::string::FromUtf8Error;
 use thiserror::Error;
@@ -39,10 +42,71 @@ pub enum PDFError {
     IllegalDateFormat(String),
     #[error("Page not found:{0}")]
     PageNotFound(String),
-    #[error("Content stream type error")]
-    ContentStreamTypeError,
+    #[error("Page tree node {0} is missing")]
+    MissingNode(ObjectId),
+    /// Never returned, an unsupported filter is reported as `Unsupported` with a
+    /// [`Feature::Filter`].
+    #[deprecated(note = "use `Unsupported` with `Feature::Filter`")]
     #[error("Not support filter:{0}")]
     NotSupportFilter(String),
     #[error("Invalid stream byte sequence:{0}")]
     InvalidStreamByteSequence(String),
+    #[error("Stream length {0} is not followed by endstream")]
+    StreamLengthMismatch(u64),
+    #[error("Object {requested:?} not found, the slot holds generation {found:?}")]
+    GenerationMismatch {
+        requested: ObjRefTuple,
+        found: ObjRefTuple,
+    },
+    #[error("Invalid page range:{0}")]
+    InvalidPageRange(String),
+    #[error("Cancelled by the progress callback")]
+    Cancelled,
+    /// A limit of the parse options was crossed, see [`crate::options::ParseOptions::with_max_objects`].
+    #[error("Limit of {limit} {which} exceeded")]
+    ResourceLimitExceeded { which: Resource, limit: u64 },
+    #[error(
+        "Page {page:?} /Contents element {index}{} is a {found} instead of a stream",
+        .object.map_or(String::new(), |it| format!(" (object {} {})", it.0, it.1))
+    )]
+    ContentNotStream { page: ObjRefTuple, index: usize, object: Option<ObjRefTuple>, found: &'static str },
+    #[error("Invalid object id:{0}")]
+    InvalidObjectId(String),
+    #[error("Invalid object path:{0}")]
+    InvalidPath(String),
+    #[error("Object path segment {segment} can't be applied to {found}")]
+    PathSegmentMismatch { segment: String, found: String },
+    #[error("Token at offset {at_offset} exceeds the maximum length {limit}")]
+    TokenTooLong { limit: usize, at_offset: u64 },
+    /// A malformed content stream operation, `page` and `operation` are zero-based indexes
+    /// and `offset` is the byte offset within the decoded content.
+    #[error("{}operation #{} near byte {offset}: {message}", .page.map_or(String::new(), |it| format!("Page {}, ", it + 1)), .operation + 1)]
+    ContentParseError { page: Option<usize>, operation: usize, offset: u64, message: String },
+    #[error("Form XObject {0:?} paints itself")]
+    FormXObjectCycle(ObjRefTuple),
+    #[error("Form XObjects are nested deeper than {0}")]
+    FormXObjectTooDeep(usize),
+    #[error("Offset {offset} is beyond the end of the file of {size} bytes")]
+    OffsetOutOfRange { offset: u64, size: u64 },
+    /// The document requires a feature the crate doesn't implement, `detail` explains
+    /// where it was found.
+    #[error("Unsupported {feature}: {detail}")]
+    Unsupported { feature: Feature, detail: String },
+    /// An object refers back to itself while being resolved, e.g. a stream whose `/Length`
+    /// is the stream itself or a chain of references ending where it started.
+    #[error("Object {object} refers to itself")]
+    CircularReference { object: ObjectId },
+    #[error("Can't write an encrypted document")]
+    EncryptedUpdate,
+    #[cfg(feature = "tokio")]
+    #[error("Blocking task failed:{0}")]
+    BlockingTaskFailed(#[from] tokio::task::JoinError),
+}
+
+impl PDFError {
+    /// Returns true if the error aborts the whole operation, also where lenient mode
+    /// recovers from other errors: a cancellation or an exceeded resource limit.
+    pub fn is_abort(&self) -> bool {
+        matches!(self, PDFError::Cancelled | PDFError::ResourceLimitExceeded { .. })
+    }
 }
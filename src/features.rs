@@ -0,0 +1,113 @@
+use crate::constants::{CF, CFM, FILTER, STANDARD, STM_F, V};
+use crate::document::{PDFDocument, XrefKind};
+use crate::filter::SUPPORTED_FILTERS;
+use crate::objects::{Dictionary, PDFObject};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// A capability a document requires from its reader, see [`PDFDocument::required_features`]
+/// and [`crate::error::PDFError::Unsupported`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature {
+    /// The document is encrypted, named by the crypt filter method like `AESV3`, or by the
+    /// security handler if it isn't the standard one.
+    Encryption(String),
+    /// Streams are encoded by the filter, e.g. `JBIG2Decode`.
+    Filter(String),
+    /// The cross-reference data is stored in cross-reference streams (`/Type /XRef`).
+    XrefStream,
+}
+
+impl Feature {
+    /// Returns true if the crate implements the feature.
+    ///
+    /// Decryption isn't implemented. A filter is supported if streams encoded by it can
+    /// be decoded.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Feature::Filter(name) => SUPPORTED_FILTERS.contains(&name.as_str()),
+            Feature::XrefStream => true,
+            Feature::Encryption(_) => false,
+        }
+    }
+}
+
+impl Display for Feature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Feature::Encryption(flavor) => write!(f, "encryption {}", flavor),
+            Feature::Filter(name) => write!(f, "filter {}", name),
+            Feature::XrefStream => write!(f, "cross-reference streams"),
+        }
+    }
+}
+
+/// Names the encryption of an encryption dictionary.
+///
+/// The crypt filter method of the stream filter, e.g. `AESV2` or `AESV3`, is preferred,
+/// otherwise the name is derived from `/V`. A security handler other than `Standard`, e.g.
+/// `Adobe.PubSec`, is named instead.
+///
+/// # Arguments
+///
+/// * `dict` - The encryption dictionary
+///
+/// # Returns
+///
+/// The name of the encryption
+pub(crate) fn encryption_flavor(dict: &Dictionary) -> String {
+    if let Some(filter) = dict.get_named_value(FILTER)
+        && filter != STANDARD
+    {
+        return filter.to_string();
+    }
+    let method = dict
+        .get_named_value(STM_F)
+        .and_then(|stm_f| dict.get(CF)?.as_dict()?.get(stm_f)?.as_dict())
+        .and_then(|crypt_filter| crypt_filter.get_named_value(CFM));
+    if let Some(method) = method {
+        return method.to_string();
+    }
+    match dict.get_u64_num(V) {
+        Some(1 | 2) => "RC4".to_string(),
+        Some(5) => "AESV3".to_string(),
+        Some(v) => format!("V{}", v),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Lists the features the document requires: its encryption, cross-reference streams if
+/// it has any, also those of hybrid-reference files, and the filters of all streams.
+///
+/// Only the stream dictionaries are read for their `/Filter`. An object which can't be parsed
+/// can't declare a filter either and is passed over.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// The features in sorted order
+pub(crate) fn collect_required_features(document: &mut PDFDocument) -> Vec<Feature> {
+    let mut features = BTreeSet::new();
+    if let Some(encryption) = document.get_encryption() {
+        features.insert(encryption.clone());
+    }
+    if document.xref_kind() != XrefKind::Classic {
+        features.insert(Feature::XrefStream);
+    }
+    document.with_skipped_stream_data(|document| {
+        for index in 0..document.get_xref_slice().len() {
+            if document.get_xref_slice()[index].is_freed() {
+                continue;
+            }
+            if let Ok(Some(PDFObject::IndirectObject(_, _, object))) = document.read_object(index)
+                && let Some(stream) = object.as_stream()
+            {
+                features.extend(stream.filters().into_iter().map(Feature::Filter));
+            }
+        }
+    });
+    features.into_iter().collect()
+}
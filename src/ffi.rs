@@ -0,0 +1,190 @@
+//! A minimal C API, enabled by the `ffi` feature, the build script generates the header
+//! `pdf_rs.h` into its `OUT_DIR`, e.g. `target/release/build/pdf-rs-*/out`.
+//!
+//! All strings are UTF-8 and NUL-terminated. A document returned by [`pdf_open`] is owned
+//! by the caller until it is passed to [`pdf_close`]. Functions returning a number report
+//! failures as one of the negative `PDF_ERR_*` codes, functions returning a pointer report
+//! them as null, and the message of the last failure on the calling thread is available
+//! from [`pdf_last_error_message`].
+//!
+//! A static library for linking is built by
+//! `cargo rustc --release --features ffi --crate-type staticlib`.
+
+use crate::document::PDFDocument;
+use crate::error::PDFError;
+use crate::page::Page;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::PathBuf;
+use std::ptr;
+
+/// A required pointer argument is null.
+pub const PDF_ERR_NULL_ARGUMENT: i64 = -1;
+/// A string argument is not valid UTF-8.
+pub const PDF_ERR_INVALID_UTF8: i64 = -2;
+/// The file can't be read.
+pub const PDF_ERR_IO: i64 = -3;
+/// The document is malformed.
+pub const PDF_ERR_PARSE: i64 = -4;
+/// The page index is out of range.
+pub const PDF_ERR_PAGE_NOT_FOUND: i64 = -5;
+/// The library panicked, the document must not be used any more.
+pub const PDF_ERR_PANIC: i64 = -6;
+
+/// An opaque handle of an open document.
+pub struct PdfDoc {
+    document: PDFDocument,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records the message of a failure and returns its code.
+fn fail(code: i64, message: String) -> i64 {
+    // Messages never contain NUL, but don't lose the failure if one does
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|it| *it.borrow_mut() = Some(message));
+    code
+}
+
+/// Maps an error onto its code.
+fn error_code(error: &PDFError) -> i64 {
+    match error {
+        PDFError::IOError(_) => PDF_ERR_IO,
+        PDFError::PageNotFound(_) => PDF_ERR_PAGE_NOT_FOUND,
+        _ => PDF_ERR_PARSE,
+    }
+}
+
+/// Runs `func`, turning a panic into `PDF_ERR_PANIC`.
+fn guard(func: impl FnOnce() -> i64) -> i64 {
+    catch_unwind(AssertUnwindSafe(func)).unwrap_or_else(|_| fail(PDF_ERR_PANIC, "pdf-rs panicked".to_string()))
+}
+
+/// Opens a PDF document.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file
+///
+/// # Returns
+///
+/// The document, to be released by [`pdf_close`], or null on failure
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_open(path: *const c_char) -> *mut PdfDoc {
+    let mut doc = ptr::null_mut();
+    guard(|| {
+        if path.is_null() {
+            return fail(PDF_ERR_NULL_ARGUMENT, "path is null".to_string());
+        }
+        // SAFETY: the caller passes a NUL-terminated string
+        let path = match unsafe { CStr::from_ptr(path) }.to_str() {
+            Ok(path) => PathBuf::from(path),
+            Err(e) => return fail(PDF_ERR_INVALID_UTF8, format!("path is not UTF-8: {}", e)),
+        };
+        match PDFDocument::open(path) {
+            Ok(document) => {
+                doc = Box::into_raw(Box::new(PdfDoc { document }));
+                0
+            }
+            Err(e) => fail(error_code(&e), e.to_string()),
+        }
+    });
+    doc
+}
+
+/// Returns the number of pages of a document.
+///
+/// # Returns
+///
+/// The number of pages, or a negative error code
+///
+/// # Safety
+///
+/// `doc` must be null or a document returned by [`pdf_open`] which isn't closed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_page_count(doc: *const PdfDoc) -> i64 {
+    // SAFETY: the caller passes a live document
+    match unsafe { doc.as_ref() } {
+        Some(doc) => doc.document.get_page_num() as i64,
+        None => fail(PDF_ERR_NULL_ARGUMENT, "doc is null".to_string()),
+    }
+}
+
+/// Extracts the text of a page.
+///
+/// The text and a terminating NUL are written to `out_buf` only if they fit into
+/// `out_len` bytes, so call again with a buffer of the returned length plus one if the
+/// result is not smaller than `out_len`. `out_buf` may be null to query the length.
+///
+/// # Arguments
+///
+/// * `doc` - The document
+/// * `page` - The index of the page in page order
+/// * `out_buf` - The destination of the UTF-8 text
+/// * `out_len` - The size of `out_buf` in bytes
+///
+/// # Returns
+///
+/// The length of the text in bytes without the NUL, or a negative error code
+///
+/// # Safety
+///
+/// `doc` must be null or a document returned by [`pdf_open`] which isn't closed, and
+/// `out_buf` must be null or writable for `out_len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_extract_text(doc: *mut PdfDoc, page: usize, out_buf: *mut c_char, out_len: usize) -> i64 {
+    // SAFETY: the caller passes a live document which isn't used concurrently
+    let doc = match unsafe { doc.as_mut() } {
+        Some(doc) => doc,
+        None => return fail(PDF_ERR_NULL_ARGUMENT, "doc is null".to_string()),
+    };
+    guard(|| {
+        let document = &mut doc.document;
+        let page_id = match document.get_page_ids().get(page) {
+            Some(page_id) => *page_id,
+            None => return fail(PDF_ERR_PAGE_NOT_FOUND, format!("Page not found:{}", page)),
+        };
+        let text = match Page::new(page_id).text(document) {
+            Ok(text) => text,
+            Err(e) => return fail(error_code(&e), e.to_string()),
+        };
+        if !out_buf.is_null() && text.len() < out_len {
+            // SAFETY: the buffer holds `out_len` bytes, which is more than the text
+            unsafe {
+                ptr::copy_nonoverlapping(text.as_ptr(), out_buf.cast::<u8>(), text.len());
+                *out_buf.add(text.len()) = 0;
+            }
+        }
+        text.len() as i64
+    })
+}
+
+/// Returns the message of the last failure on the calling thread.
+///
+/// # Returns
+///
+/// The message, valid until the next failure on the same thread, or null if nothing failed
+#[unsafe(no_mangle)]
+pub extern "C" fn pdf_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|it| it.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Closes a document and releases its memory.
+///
+/// # Safety
+///
+/// `doc` must be null or a document returned by [`pdf_open`] which isn't closed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_close(doc: *mut PdfDoc) {
+    if !doc.is_null() {
+        // SAFETY: the document was boxed by `pdf_open` and is released once
+        drop(unsafe { Box::from_raw(doc) });
+    }
+}
@@ -1,9 +1,17 @@
 use crate::error::{PDFError, Result};
+use crate::features::Feature;
 use crate::objects::Stream;
+use crate::trace::span;
 use crate::utils::hex2bytes;
 use flate2::read::ZlibDecoder;
 use std::io::Read;
 
+/// The filters [`decode_stream`] can decode.
+pub(crate) const SUPPORTED_FILTERS: [&str; 3] = ["FlateDecode", "ASCIIHexDecode", "ASCII85Decode"];
+
+/// The filters encoding binary data as ASCII text, which only inflate the data.
+pub(crate) const ASCII_FILTERS: [&str; 2] = ["ASCIIHexDecode", "ASCII85Decode"];
+
 /// Decodes ASCII85 encoded data.
 ///
 /// ASCII85 (also known as Base85) is an encoding scheme that converts binary data
@@ -36,8 +44,8 @@ fn ascii_85_decode(buf: &[u8]) -> Result<Vec<u8>> {
             continue;
         }
         let mut e = false;
-        if b < b'!' || b > b'u' {
-            e = (b == b'~' && i < l - 1 && buf[i + 1] == b'>');
+        if !(b'!'..=b'u').contains(&b) {
+            e = b == b'~' && i < l - 1 && buf[i + 1] == b'>';
             if !e {
                 return Err(PDFError::InvalidStreamByteSequence(format!("ASCII85Decode must be between '!' and 'u' but it is '{}'", b as char)));
             }
@@ -52,7 +60,7 @@ fn ascii_85_decode(buf: &[u8]) -> Result<Vec<u8>> {
         if w == 5 || i == l - 1 || e {
             let mut value = 0u32;
             for (i, v) in t.iter_mut().enumerate() {
-                value = value + (*v as u32) * 85u32.pow((i) as u32);
+                value += (*v as u32) * 85u32.pow((i) as u32);
             }
             let k = value.to_be_bytes();
             bytes.extend_from_slice(&k[0..ASCII_85_LOOKUP[w - 1] as usize]);
@@ -82,8 +90,8 @@ fn ascii_85_decode(buf: &[u8]) -> Result<Vec<u8>> {
 ///
 /// # Errors
 ///
-/// Returns an error if the filter is not supported
-fn decode_stream_xx_decode(filter: &str, buf: &[u8]) -> Result<Vec<u8>> {
+/// Returns [`PDFError::Unsupported`] if the filter is not supported
+pub(crate) fn decode_stream_xx_decode(filter: &str, buf: &[u8]) -> Result<Vec<u8>> {
     let bytes = match filter {
         "FlateDecode" => {
             let mut zlib_decoder = ZlibDecoder::new(buf);
@@ -91,13 +99,78 @@ fn decode_stream_xx_decode(filter: &str, buf: &[u8]) -> Result<Vec<u8>> {
             zlib_decoder.read_to_end(&mut flate_bytes)?;
             flate_bytes
         }
-        "ASCIIHexDecode" => hex2bytes(buf),
+        "ASCIIHexDecode" => {
+            // The data ends at the `>` end-of-data marker
+            let end = buf.iter().position(|b| *b == b'>').unwrap_or(buf.len());
+            hex2bytes(&buf[..end])?
+        }
         "ASCII85Decode" => ascii_85_decode(buf)?,
-        _ => return Err(PDFError::NotSupportFilter(filter.to_string()))
+        _ => return Err(PDFError::Unsupported {
+            feature: Feature::Filter(filter.to_string()),
+            detail: "streams encoded by this filter can't be decoded".to_string(),
+        })
     };
     Ok(bytes)
 }
 
+/// Reverses the PNG predictors (`/Predictor` 10 to 15) of decoded data with one byte per
+/// pixel, as used by cross-reference streams. Each row of `columns` bytes is preceded by
+/// a byte naming the predictor of the row.
+///
+/// # Arguments
+///
+/// * `buf` - The decoded, still predicted data
+/// * `columns` - The number of bytes per row
+///
+/// # Returns
+///
+/// A `Result` containing the rows without their predictor bytes, or an error if a row is
+/// incomplete or names an unknown predictor
+pub(crate) fn undo_png_predictor(buf: &[u8], columns: usize) -> Result<Vec<u8>> {
+    if columns == 0 || !buf.len().is_multiple_of(columns + 1) {
+        return Err(PDFError::InvalidStreamByteSequence(format!(
+            "PNG predicted data of {} bytes doesn't hold rows of {} columns", buf.len(), columns
+        )));
+    }
+    let mut bytes = Vec::with_capacity(buf.len() / (columns + 1) * columns);
+    let mut prior = vec![0u8; columns];
+    for row in buf.chunks(columns + 1) {
+        let mut current = row[1..].to_vec();
+        for i in 0..columns {
+            let left = if i > 0 { current[i - 1] } else { 0 };
+            let up = prior[i];
+            let up_left = if i > 0 { prior[i - 1] } else { 0 };
+            let predicted = match row[0] {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                predictor => return Err(PDFError::InvalidStreamByteSequence(format!(
+                    "Unknown PNG predictor {}", predictor
+                ))),
+            };
+            current[i] = current[i].wrapping_add(predicted);
+        }
+        bytes.extend_from_slice(&current);
+        prior = current;
+    }
+    Ok(bytes)
+}
+
+/// Predicts a byte from its left, upper and upper left neighbours like PNG does.
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let (a, b, c) = ((estimate - left as i16).abs(), (estimate - up as i16).abs(), (estimate - up_left as i16).abs());
+    if a <= b && a <= c {
+        left
+    } else if b <= c {
+        up
+    } else {
+        up_left
+    }
+}
+
 /// Decodes a PDF stream by applying all its filters in reverse order.
 ///
 /// PDF streams can have multiple filters applied in sequence. This function
@@ -116,17 +189,15 @@ fn decode_stream_xx_decode(filter: &str, buf: &[u8]) -> Result<Vec<u8>> {
 ///
 /// Returns an error if any filter fails to decode the data
 pub(crate) fn decode_stream(stream: &Stream) -> Result<Vec<u8>> {
-    let filters = stream.get_filters();
-    let len = filters.len();
-    let mut bytes = Vec::new();
-    for i in (0..len).rev() {
-        let filter = &filters[i];
-        let slice = if bytes.is_empty() {
-            stream.as_slice()
-        } else {
-            bytes.as_slice()
-        };
-        bytes = decode_stream_xx_decode(filter, &slice)?;
+    let filters = stream.filters();
+    let mut bytes = stream.as_slice().to_vec();
+    for filter in filters.iter().rev() {
+        let span = span!("pdf.stream_decode", filter, input_bytes, output_bytes);
+        let _entered = span.enter();
+        span.record_str("filter", filter);
+        span.record("input_bytes", bytes.len() as u64);
+        bytes = decode_stream_xx_decode(filter, &bytes)?;
+        span.record("output_bytes", bytes.len() as u64);
     }
     Ok(bytes)
 }
@@ -156,7 +227,18 @@ mod tests {
         let bytes = ascii_85_decode(b"87cURDn~>")?;
         assert_eq!(bytes, b"Hello");
         let result = ascii_85_decode(b"87cURDnv\n~>");
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    /// Tests reversing the PNG Up and Sub predictors of two rows.
+    #[test]
+    fn test_undo_png_predictor() -> Result<()> {
+        let bytes = undo_png_predictor(&[2, 1, 2, 3, 2, 1, 1, 1], 3)?;
+        assert_eq!(bytes, [1, 2, 3, 2, 3, 4]);
+        let bytes = undo_png_predictor(&[1, 5, 1, 1], 3)?;
+        assert_eq!(bytes, [5, 6, 7]);
+        assert!(undo_png_predictor(&[2, 1, 2], 3).is_err());
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,379 @@
+use crate::constants::{
+    ANNOTS, AP, BASE_FONT, DESCENDANT_FONTS, ENCODING, FLAGS, FONT, FONT_DESCRIPTOR, FONT_FILES, RESOURCES, SUBTYPE,
+    TYPE0, TYPE1, TYPE3, XOBJECT,
+};
+use crate::document::PDFDocument;
+use crate::error::PDFError::PageNotFound;
+use crate::error::Result;
+use crate::objects::{Dictionary, ObjRefTuple, PDFObject};
+use crate::path::PathSeg;
+use std::collections::BTreeSet;
+
+/// The fonts every conforming reader provides, they may be used without embedding.
+const STANDARD_14: [&str; 14] = [
+    "Times-Roman",
+    "Times-Bold",
+    "Times-Italic",
+    "Times-BoldItalic",
+    "Helvetica",
+    "Helvetica-Bold",
+    "Helvetica-Oblique",
+    "Helvetica-BoldOblique",
+    "Courier",
+    "Courier-Bold",
+    "Courier-Oblique",
+    "Courier-BoldOblique",
+    "Symbol",
+    "ZapfDingbats",
+];
+
+/// The font descriptor flag of fonts using glyphs outside the standard Latin set.
+const SYMBOLIC: u64 = 1 << 2;
+/// The font descriptor flag of fonts using only glyphs of the standard Latin set.
+const NONSYMBOLIC: u64 = 1 << 5;
+
+/// How reliable decoded text is, ordered from the least to the most reliable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Confidence {
+    /// The text was guessed, e.g. bytes read as Latin-1, and may be garbled.
+    Low,
+    /// The text was decoded by the default the font implies.
+    Medium,
+    /// The text was decoded by the encoding the font declares.
+    High,
+}
+
+/// How the character codes of a font are turned into text, see [`FontStatus::get_decoding`].
+///
+/// `/ToUnicode` maps aren't read. Fonts without `/Encoding` decode each code by a fallback
+/// chain: the default the font implies, then cp1252, then Latin-1. The cp1252 step is the
+/// WinAnsi table and follows only a `StandardEncoding` default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextDecoding {
+    /// The `/Encoding` of the font, including its `/Differences`.
+    Encoding,
+    /// A Type0 font, whose multi-byte codes can't be decoded without `/ToUnicode`.
+    Composite,
+    /// The default of a font without `/Encoding`, `StandardEncoding` for Type1 fonts and
+    /// WinAnsi for other fonts flagged nonsymbolic.
+    Builtin,
+    /// Nothing is known about the codes, they are read as cp1252.
+    Cp1252,
+    /// A symbolic font without `/Encoding`, the codes are read as Latin-1.
+    Latin1,
+}
+
+impl TextDecoding {
+    /// Returns the confidence in text decoded this way, the codes a [`TextDecoding::Builtin`]
+    /// default doesn't cover have `Low` confidence.
+    pub fn confidence(&self) -> Confidence {
+        match self {
+            TextDecoding::Encoding => Confidence::High,
+            TextDecoding::Builtin => Confidence::Medium,
+            TextDecoding::Composite | TextDecoding::Cp1252 | TextDecoding::Latin1 => Confidence::Low,
+        }
+    }
+}
+
+/// Chooses how the codes of a font dictionary are decoded, driven by the `/Flags` of its
+/// font descriptor when it has no `/Encoding`. An unreadable font descriptor counts as
+/// having no flags.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `dict` - The font dictionary
+///
+/// # Returns
+///
+/// The decoding of the font
+pub(crate) fn text_decoding(document: &mut PDFDocument, dict: &Dictionary) -> TextDecoding {
+    if dict.named_value_was(SUBTYPE, TYPE0) {
+        return TextDecoding::Composite;
+    }
+    if dict.contain(ENCODING) {
+        return TextDecoding::Encoding;
+    }
+    let path = [PathSeg::Key(FONT_DESCRIPTOR.to_string()), PathSeg::Key(FLAGS.to_string())];
+    let flags = match document.get_path_segs(&PDFObject::Dict(dict.clone()), &path) {
+        Ok(Some(PDFObject::Number(flags))) => Some(flags.as_f64() as u64),
+        _ => None,
+    };
+    match flags {
+        Some(flags) if flags & SYMBOLIC != 0 => TextDecoding::Latin1,
+        Some(flags) if flags & NONSYMBOLIC != 0 => TextDecoding::Builtin,
+        _ if dict.named_value_was(SUBTYPE, TYPE1) => TextDecoding::Builtin,
+        _ => TextDecoding::Cp1252,
+    }
+}
+
+/// The embedding status of a font, see [`PDFDocument::font_preflight`].
+#[derive(Clone, Debug)]
+pub struct FontStatus {
+    /// The reference of the font dictionary, `None` for a font written inline in a `/Font` dictionary.
+    obj_ref: Option<ObjRefTuple>,
+    base_font: Option<String>,
+    subtype: Option<String>,
+    /// Whether a font program is embedded, for Type0 fonts in the descendant font.
+    embedded: bool,
+    standard_14: bool,
+    subset: bool,
+    /// How the text shown with the font is decoded.
+    decoding: TextDecoding,
+}
+
+impl FontStatus {
+    /// Returns the reference of the font dictionary, `None` if the font is written inline
+    /// in a `/Font` resource dictionary.
+    pub fn get_obj_ref(&self) -> Option<ObjRefTuple> {
+        self.obj_ref
+    }
+
+    /// Returns the `/BaseFont` of the font.
+    pub fn get_base_font(&self) -> Option<&str> {
+        self.base_font.as_deref()
+    }
+
+    /// Returns the `/Subtype` of the font, e.g. `Type1` or `Type0`.
+    pub fn get_subtype(&self) -> Option<&str> {
+        self.subtype.as_deref()
+    }
+
+    /// Returns how the text shown with the font is decoded, see
+    /// [`TextDecoding::confidence`] for how reliable it is.
+    pub fn get_decoding(&self) -> TextDecoding {
+        self.decoding
+    }
+
+    /// Returns true if a font program (`/FontFile`, `/FontFile2` or `/FontFile3`) is embedded.
+    ///
+    /// Type0 fonts carry the font program in their descendant font, Type3 fonts define
+    /// their glyphs in the document and always count as embedded. A font whose dictionary,
+    /// descendant font or font descriptor can't be read counts as not embedded.
+    pub fn is_embedded(&self) -> bool {
+        self.embedded
+    }
+
+    /// Returns true if the font is one of the standard 14 fonts.
+    pub fn is_standard_14(&self) -> bool {
+        self.standard_14
+    }
+
+    /// Returns true if the name has a subset tag, e.g. `ABCDEF+Minion`.
+    pub fn is_subset(&self) -> bool {
+        self.subset
+    }
+
+    /// Returns true if the font is embedded or exempt from embedding as a standard 14 font.
+    pub fn is_acceptable(&self) -> bool {
+        self.embedded || self.standard_14
+    }
+}
+
+/// The embedding status of the fonts used by the pages of a document.
+#[derive(Clone, Debug, Default)]
+pub struct FontPreflight {
+    fonts: Vec<FontStatus>,
+}
+
+impl FontPreflight {
+    /// Returns the status of each font, the referenced fonts in object number order followed
+    /// by the inline fonts in the order they were found.
+    pub fn get_fonts(&self) -> &[FontStatus] {
+        &self.fonts
+    }
+
+    /// Returns true if every font is embedded or exempt as a standard 14 font.
+    pub fn all_fonts_embedded(&self) -> bool {
+        self.fonts.iter().all(FontStatus::is_acceptable)
+    }
+
+    /// Returns the fonts which are neither embedded nor exempt.
+    pub fn missing(&self) -> impl Iterator<Item = &FontStatus> {
+        self.fonts.iter().filter(|it| !it.is_acceptable())
+    }
+}
+
+/// Returns true if the name starts with a subset tag of six upper case letters and a plus sign.
+fn is_subset_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    bytes.len() > 7 && bytes[6] == b'+' && bytes[..6].iter().all(u8::is_ascii_uppercase)
+}
+
+/// Reads a value of a font, an unreadable value counts as missing unless the error aborts.
+fn read_font_path(document: &mut PDFDocument, font: &PDFObject, path: &[PathSeg]) -> Result<Option<PDFObject>> {
+    match document.get_path_segs(font, path) {
+        Err(e) if !e.is_abort() => Ok(None),
+        result => result,
+    }
+}
+
+/// Returns true if the font dictionary has a font descriptor with an embedded font program.
+fn has_font_file(document: &mut PDFDocument, font: &PDFObject) -> Result<bool> {
+    for font_file in FONT_FILES {
+        let path = [PathSeg::Key(FONT_DESCRIPTOR.to_string()), PathSeg::Key(font_file.to_string())];
+        if read_font_path(document, font, &path)?.is_some_and(|it| it.as_stream().is_some()) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Determines the embedding status of a font dictionary, a font which can't be read as a
+/// dictionary is reported as not embedded.
+fn font_status(document: &mut PDFDocument, obj_ref: Option<ObjRefTuple>, dict: Option<Dictionary>) -> Result<FontStatus> {
+    let dict = match dict {
+        Some(dict) => dict,
+        None => {
+            return Ok(FontStatus {
+                obj_ref,
+                base_font: None,
+                subtype: None,
+                embedded: false,
+                standard_14: false,
+                subset: false,
+                decoding: TextDecoding::Cp1252,
+            });
+        }
+    };
+    let decoding = text_decoding(document, &dict);
+    let base_font = dict.get_named_value(BASE_FONT).map(|it| it.to_string());
+    let subtype = dict.get_named_value(SUBTYPE).map(|it| it.to_string());
+    let font = PDFObject::Dict(dict);
+    let embedded = match subtype.as_deref() {
+        Some(TYPE3) => true,
+        Some(TYPE0) => {
+            let path = [PathSeg::Key(DESCENDANT_FONTS.to_string()), PathSeg::Index(0)];
+            match read_font_path(document, &font, &path)? {
+                Some(descendant) => has_font_file(document, &descendant)?,
+                None => false,
+            }
+        }
+        _ => has_font_file(document, &font)?,
+    };
+    // Composite fonts always need their font program
+    let standard_14 = subtype.as_deref() != Some(TYPE0)
+        && base_font.as_deref().is_some_and(|name| STANDARD_14.contains(&name));
+    let subset = base_font.as_deref().is_some_and(is_subset_name);
+    Ok(FontStatus { obj_ref, base_font, subtype, embedded, standard_14, subset, decoding })
+}
+
+/// Resolves a value which should be a dictionary, an empty dictionary if it isn't one.
+fn resolve_dict(document: &mut PDFDocument, value: Option<&PDFObject>) -> Result<Dictionary> {
+    let value = match value {
+        Some(value) => document.resolve(value.clone())?,
+        None => None,
+    };
+    Ok(value.and_then(PDFObject::into_dict).unwrap_or_default())
+}
+
+/// The fonts in the resources of the pages, of the form XObjects they declare and of the
+/// appearance streams of their annotations.
+#[derive(Default)]
+struct FontCollector {
+    refs: BTreeSet<ObjRefTuple>,
+    /// The font dictionaries written inline in a `/Font` dictionary.
+    inline: Vec<Dictionary>,
+    /// The form XObjects and appearance streams already walked.
+    streams: BTreeSet<ObjRefTuple>,
+}
+
+impl FontCollector {
+    /// Collects the fonts of a `/Resources` value and walks the form XObjects it declares.
+    fn collect_resources(&mut self, document: &mut PDFDocument, resources: Option<&PDFObject>) -> Result<()> {
+        let resources = resolve_dict(document, resources)?;
+        for (_, font) in resolve_dict(document, resources.get(FONT))?.iter() {
+            match font {
+                PDFObject::ObjectRef(obj_num, gen_num) => {
+                    self.refs.insert((*obj_num, *gen_num));
+                }
+                PDFObject::Dict(dict) => self.inline.push(dict.clone()),
+                _ => {}
+            }
+        }
+        for (_, xobject) in resolve_dict(document, resources.get(XOBJECT))?.iter() {
+            if let Some(obj_ref) = xobject.as_object_ref() {
+                self.collect_stream(document, obj_ref, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Collects the fonts of the resources of a stream once, of a form XObject if `form_only`
+    /// is set or of any stream, since appearance streams often lack the form `/Subtype`.
+    fn collect_stream(&mut self, document: &mut PDFDocument, obj_ref: ObjRefTuple, form_only: bool) -> Result<()> {
+        if !self.streams.insert(obj_ref) {
+            return Ok(());
+        }
+        let object = document.with_skipped_stream_data(|document| document.read_object_with_ref(obj_ref));
+        let resources = match object? {
+            Some(PDFObject::IndirectObject(_, _, object)) => match object.into_stream() {
+                Some(stream) if !form_only || stream.is_form_xobject() => stream.get_metadata().get(RESOURCES).cloned(),
+                _ => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        self.collect_resources(document, resources.as_ref())
+    }
+
+    /// Collects the fonts of the appearance streams of the annotations of a page, each entry
+    /// of `/AP` being a stream or a dictionary of streams by appearance state.
+    fn collect_annotations(&mut self, document: &mut PDFDocument, annots: Option<&PDFObject>) -> Result<()> {
+        let annots = match annots {
+            Some(annots) => document.resolve(annots.clone())?.and_then(PDFObject::into_array).unwrap_or_default(),
+            None => return Ok(()),
+        };
+        for annot in annots {
+            let annot = resolve_dict(document, Some(&annot))?;
+            for (_, appearance) in resolve_dict(document, annot.get(AP))?.iter() {
+                match appearance {
+                    PDFObject::ObjectRef(obj_num, gen_num) => self.collect_stream(document, (*obj_num, *gen_num), false)?,
+                    PDFObject::Dict(states) => {
+                        for obj_ref in states.iter().filter_map(|(_, state)| state.as_object_ref()) {
+                            self.collect_stream(document, obj_ref, false)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks the embedding status of the fonts used by all pages.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// A `Result` containing the status of each font, or an error if a page, its resources or
+/// a form XObject can't be read
+pub(crate) fn font_preflight(document: &mut PDFDocument) -> Result<FontPreflight> {
+    let mut collector = FontCollector::default();
+    for page_id in document.get_page_ids() {
+        let page = match document.get_page_node(page_id) {
+            Some(page) => page,
+            None => return Err(PageNotFound(format!("Page not found:{}", page_id))),
+        };
+        let annots = page.get_attrs().get(ANNOTS).cloned();
+        let resources = document.get_page_tree().get_inherited_attr(page_id, RESOURCES).cloned();
+        collector.collect_resources(document, resources.as_ref())?;
+        collector.collect_annotations(document, annots.as_ref())?;
+    }
+    let mut fonts = Vec::with_capacity(collector.refs.len() + collector.inline.len());
+    for obj_ref in collector.refs {
+        let dict = match document.resolve(PDFObject::ObjectRef(obj_ref.0, obj_ref.1)) {
+            Ok(font) => font.and_then(PDFObject::into_dict),
+            Err(e) if e.is_abort() => return Err(e),
+            Err(_) => None,
+        };
+        fonts.push(font_status(document, Some(obj_ref), dict)?);
+    }
+    for dict in collector.inline {
+        fonts.push(font_status(document, None, Some(dict))?);
+    }
+    Ok(FontPreflight { fonts })
+}
@@ -1,15 +1,21 @@
 use crate::catalog::NodeId;
 use crate::document::PDFDocument;
-use crate::error::PDFError::{ContentStreamTypeError, PageNotFound};
+use crate::error::PDFError::{ContentNotStream, InvalidPageRange, PageNotFound};
 use crate::error::Result;
-use crate::filter::decode_stream;
 use crate::objects::{PDFObject, Stream};
+use crate::page::Page;
+use crate::text::TextExtractionOptions;
 
 /// Extracts content streams from a specific page in the PDF document.
 ///
 /// This function retrieves all content streams associated with a page,
 /// which contain the graphical and textual content to be rendered.
 ///
+/// `/Contents` may be a stream or an array of streams, each of them direct or indirect,
+/// and the array itself may be indirect. An indirect object wrapping another one, as some
+/// generators write, is unwrapped. `null` contents or elements, e.g. left by editors, are
+/// skipped with a warning.
+///
 /// # Arguments
 ///
 /// * `document` - A mutable reference to the PDF document
@@ -17,31 +23,64 @@ use crate::objects::{PDFObject, Stream};
 ///
 /// # Returns
 ///
-/// A `Result` containing a vector of `Stream` objects representing the page's content,
-/// or an error if the page is not found or the content stream type is invalid
-fn extract_page_content_stream(document: &mut PDFDocument, page_id: NodeId) -> Result<Vec<Stream>> {
-    let page = match document.get_page(page_id) {
+/// A `Result` containing a vector of `Stream` objects representing the page's content
+/// in order, or a `ContentNotStream` error naming the page, the element and its object if
+/// an element is not a stream
+pub(crate) fn extract_page_content_stream(document: &mut PDFDocument, page_id: NodeId) -> Result<Vec<Stream>> {
+    let page = match document.get_page_node(page_id) {
         Some(page) => page,
         None => return Err(PageNotFound(format!("Page not found:{}", page_id))),
     };
-    let contents = page.get_contents();
-    let mut streams = Vec::new();
-    for tuple in contents {
-        match document.read_object_with_ref(tuple)? {
-            Some(PDFObject::IndirectObject(_, _, obj)) => match *obj {
-                PDFObject::Stream(stream) => streams.push(stream),
-                _ => return Err(ContentStreamTypeError)
-            }
-            _ => return Err(ContentStreamTypeError)
+    let page_ref = page.get_page_obj_ref();
+    let contents = match page.get_contents() {
+        Some(contents) => contents.clone(),
+        None => return Ok(Vec::new()),
+    };
+    let contents_ref = contents.as_object_ref();
+    let elements = match resolve_content(document, contents)? {
+        PDFObject::Array(elements) => elements.into_iter().map(|it| (it.as_object_ref(), it)).collect(),
+        PDFObject::Null => {
+            document.warn_about(page_ref, format!("Page {} {} /Contents is null, skipped", page_ref.0, page_ref.1));
+            Vec::new()
+        }
+        contents => vec![(contents_ref, contents)],
+    };
+    let mut streams = Vec::with_capacity(elements.len());
+    for (index, (object, element)) in elements.into_iter().enumerate() {
+        match resolve_content(document, element)? {
+            PDFObject::Stream(stream) => streams.push(stream),
+            PDFObject::Null => document.warn_about(
+                page_ref,
+                format!("Page {} {} /Contents element {} is null, skipped", page_ref.0, page_ref.1, index),
+            ),
+            found => return Err(ContentNotStream { page: page_ref, index, object, found: found.type_name() }),
         }
     }
     Ok(streams)
 }
 
+/// Resolves a `/Contents` value or element, a missing object resolves to `null`.
+fn resolve_content(document: &mut PDFDocument, object: PDFObject) -> Result<PDFObject> {
+    let mut object = match object {
+        PDFObject::ObjectRef(obj_num, gen_num) => {
+            document.read_object_with_ref((obj_num, gen_num))?.unwrap_or(PDFObject::Null)
+        }
+        object => object,
+    };
+    // The object read is an indirect object, some generators wrap the value in one more
+    for _ in 0..2 {
+        match object {
+            PDFObject::IndirectObject(_, _, inner) => object = *inner,
+            _ => break,
+        }
+    }
+    Ok(object)
+}
+
 /// Extracts text content from a specific page in the PDF document.
 ///
-/// This function retrieves and processes the text content from a page's content streams.
-/// Currently returns an empty string as a placeholder for future text extraction implementation.
+/// The text is extracted in content stream order, see [`extract_page_text_with`] for
+/// the positioned layout mode.
 ///
 /// # Arguments
 ///
@@ -52,10 +91,95 @@ fn extract_page_content_stream(document: &mut PDFDocument, page_id: NodeId) -> R
 ///
 /// A `Result` containing an optional string with the extracted text,
 /// or an error if the page cannot be accessed
+#[deprecated(note = "use `Page::text`")]
 pub fn extract_page_text(document: &mut PDFDocument, page_id: NodeId) -> Result<Option<String>> {
+    Page::new(page_id).text(document).map(Some)
+}
+
+/// Extracts text content from a specific page in the PDF document with the given options.
+///
+/// Simple fonts are decoded through their `/Encoding`, composite fonts without a
+/// `/ToUnicode` map yield no text.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `page_id` - The ID of the page to extract text from
+/// * `options` - The extraction options
+///
+/// # Returns
+///
+/// A `Result` containing an optional string with the extracted text,
+/// or an error if the page cannot be accessed or its content can't be parsed
+#[deprecated(note = "use `Page::text_with`")]
+pub fn extract_page_text_with(
+    document: &mut PDFDocument,
+    page_id: NodeId,
+    options: &TextExtractionOptions,
+) -> Result<Option<String>> {
+    Page::new(page_id).text_with(document, options).map(Some)
+}
+
+/// Parses a page range specification such as `2-5,8` into page indexes.
+///
+/// Page numbers start at 1, a range may be open-ended (`3-` or `-4`) and the indexes are
+/// returned in the order given, starting at 0.
+///
+/// # Arguments
+///
+/// * `spec` - The comma separated page numbers and ranges
+/// * `page_num` - The number of pages of the document
+///
+/// # Returns
+///
+/// A `Result` containing the page indexes,
+/// or an error if the specification is malformed or exceeds the page count
+pub fn parse_page_ranges(spec: &str, page_num: usize) -> Result<Vec<usize>> {
+    let invalid = || InvalidPageRange(spec.to_string());
+    let parse = |value: &str, default: usize| -> Result<usize> {
+        match value.trim() {
+            "" => Ok(default),
+            value => value.parse::<usize>().map_err(|_| invalid()),
+        }
+    };
+    let mut indexes = Vec::new();
+    for part in spec.split(',') {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (parse(start, 1)?, parse(end, page_num)?),
+            None => {
+                let page = parse(part, 0)?;
+                (page, page)
+            }
+        };
+        if start == 0 || start > end || end > page_num {
+            return Err(invalid());
+        }
+        indexes.extend(start - 1..end);
+    }
+    Ok(indexes)
+}
+
+/// Reads the decoded content of a specific page in the PDF document.
+///
+/// All content streams of the page are decoded and concatenated, separated by
+/// a line feed so that operators of adjacent streams are not merged.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `page_id` - The ID of the page to read the content from
+///
+/// # Returns
+///
+/// A `Result` containing the decoded content bytes,
+/// or an error if the page is not found or a content stream can't be decoded
+pub(crate) fn read_page_content(document: &mut PDFDocument, page_id: NodeId) -> Result<Vec<u8>> {
+    document.check_deadline()?;
     let streams = extract_page_content_stream(document, page_id)?;
+    let mut content = Vec::new();
     for stream in streams {
-        let text = decode_stream(&stream)?;
+        content.extend_from_slice(&document.decode_stream(&stream)?);
+        content.push(b'\n');
     }
-    Ok(Some(String::new()))
+    Ok(content)
 }
\ No newline at end of file
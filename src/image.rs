@@ -0,0 +1,187 @@
+use crate::constants::{COLOR_SPACE, HEIGHT, WIDTH};
+use crate::document::PDFDocument;
+use crate::error::Result;
+use crate::objects::{ObjRefTuple, PDFObject};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// An image XObject of a document, see [`PDFDocument::image_inventory`].
+#[derive(Clone, Debug)]
+pub struct ImageInfo {
+    obj_ref: ObjRefTuple,
+    width: Option<u64>,
+    height: Option<u64>,
+    filters: Vec<String>,
+    color_space: Option<String>,
+    declared_length: Option<u64>,
+    /// The indexes of the pages painting the image.
+    pages: Vec<usize>,
+    /// The hash of the encoded data, computed by [`ImageInventory::duplicates`].
+    hash: Option<u64>,
+}
+
+impl ImageInfo {
+    /// Returns the reference of the image stream.
+    pub fn get_obj_ref(&self) -> ObjRefTuple {
+        self.obj_ref
+    }
+
+    /// Returns the `/Width` in samples.
+    pub fn get_width(&self) -> Option<u64> {
+        self.width
+    }
+
+    /// Returns the `/Height` in samples.
+    pub fn get_height(&self) -> Option<u64> {
+        self.height
+    }
+
+    /// Returns the filters in decoding order.
+    pub fn get_filters(&self) -> &[String] {
+        &self.filters
+    }
+
+    /// Returns the color space name, the family for array color spaces, e.g. `Indexed`.
+    pub fn get_color_space(&self) -> Option<&str> {
+        self.color_space.as_deref()
+    }
+
+    /// Returns the `/Length` of the encoded data.
+    pub fn get_declared_length(&self) -> Option<u64> {
+        self.declared_length
+    }
+
+    /// Returns the indexes of the pages painting the image, in page order.
+    pub fn get_pages(&self) -> &[usize] {
+        &self.pages
+    }
+
+    /// Returns the hash of the encoded data, once [`ImageInventory::duplicates`] computed it.
+    pub fn get_hash(&self) -> Option<u64> {
+        self.hash
+    }
+}
+
+/// The image XObjects of a document in object number order.
+#[derive(Clone, Debug, Default)]
+pub struct ImageInventory {
+    images: Vec<ImageInfo>,
+}
+
+impl ImageInventory {
+    /// Returns the images in object number order.
+    pub fn get_images(&self) -> &[ImageInfo] {
+        &self.images
+    }
+
+    /// Finds separate image objects with identical encoded data.
+    ///
+    /// The data is hashed on the first call only, images whose hashes match are compared
+    /// byte by byte so a hash collision isn't reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the inventory was taken from
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the groups of two or more references with identical data,
+    /// or an error if an image can't be read
+    pub fn duplicates(&mut self, document: &mut PDFDocument) -> Result<Vec<Vec<ObjRefTuple>>> {
+        let mut by_hash: HashMap<u64, Vec<ObjRefTuple>> = HashMap::new();
+        for image in &mut self.images {
+            let hash = match image.hash {
+                Some(hash) => hash,
+                None => {
+                    let mut hasher = DefaultHasher::new();
+                    read_image_data(document, image.obj_ref)?.hash(&mut hasher);
+                    *image.hash.insert(hasher.finish())
+                }
+            };
+            by_hash.entry(hash).or_default().push(image.obj_ref);
+        }
+        let mut groups = Vec::new();
+        for candidates in by_hash.into_values().filter(|it| it.len() > 1) {
+            let mut pending = Vec::with_capacity(candidates.len());
+            for obj_ref in candidates {
+                pending.push((obj_ref, read_image_data(document, obj_ref)?));
+            }
+            while let Some((obj_ref, data)) = pending.pop() {
+                let mut group = vec![obj_ref];
+                pending.retain(|(other, other_data)| {
+                    let equal = *other_data == data;
+                    if equal {
+                        group.push(*other);
+                    }
+                    !equal
+                });
+                if group.len() > 1 {
+                    group.sort();
+                    groups.push(group);
+                }
+            }
+        }
+        groups.sort();
+        Ok(groups)
+    }
+}
+
+/// Reads the encoded data of an image stream.
+fn read_image_data(document: &mut PDFDocument, obj_ref: ObjRefTuple) -> Result<Vec<u8>> {
+    match document.read_object_with_ref(obj_ref)? {
+        Some(PDFObject::IndirectObject(_, _, object)) => {
+            Ok(object.into_stream().map(|it| it.as_slice().to_vec()).unwrap_or_default())
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Lists the image XObjects of the xref table and the pages painting them.
+///
+/// Images are recognized by their stream dictionary without reading the samples. An object
+/// which can't be parsed isn't listed, as it can't be told to be an image.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// A `Result` containing the inventory, or an error if a page content can't be read
+pub(crate) fn collect_images(document: &mut PDFDocument) -> Result<ImageInventory> {
+    let mut streams = Vec::new();
+    document.with_skipped_stream_data(|document| {
+        for index in 0..document.get_xref_slice().len() {
+            if document.get_xref_slice()[index].is_freed() {
+                continue;
+            }
+            if let Ok(Some((entry, PDFObject::IndirectObject(_, _, object)))) = document.read_entry(index)
+                && let PDFObject::Stream(stream) = *object
+                && stream.is_image()
+            {
+                streams.push(((entry.get_obj_num(), entry.get_gen_num()), stream));
+            }
+        }
+    });
+    streams.sort_by_key(|it| it.0);
+    let mut images = Vec::with_capacity(streams.len());
+    for (obj_ref, stream) in streams {
+        let dict = stream.get_metadata();
+        let color_space = match dict.get(COLOR_SPACE) {
+            Some(PDFObject::Named(name)) => Some(name.to_string()),
+            Some(PDFObject::Array(elements)) => elements.first().and_then(|it| it.as_name()).map(|it| it.to_string()),
+            _ => None,
+        };
+        images.push(ImageInfo {
+            obj_ref,
+            width: dict.get_u64_num(WIDTH),
+            height: dict.get_u64_num(HEIGHT),
+            filters: stream.filters(),
+            color_space,
+            declared_length: stream.declared_length_in(document)?,
+            pages: document.pages_referencing(obj_ref)?,
+            hash: None,
+        });
+    }
+    Ok(ImageInventory { images })
+}
@@ -0,0 +1,135 @@
+use crate::constants::PARENT;
+use crate::document::PDFDocument;
+use crate::error::Result;
+use crate::objects::{Dictionary, ObjRefTuple, PDFObject};
+use crate::writer::DocumentBuilder;
+use std::collections::{HashMap, VecDeque};
+
+/// Copies object graphs from a document into a [`DocumentBuilder`].
+pub struct ObjectImporter;
+
+impl ObjectImporter {
+    /// Deep-copies the objects reachable from `root` into `dest`.
+    ///
+    /// Every copied object gets a fresh object number and every reference inside the copies
+    /// is rewritten to the new numbers. References to objects which don't exist in `src` are
+    /// copied as `null` objects, as the specification treats them like `null`. Other cycles
+    /// are followed once because an object is only copied the first time it is reached.
+    ///
+    /// A `/Parent` link isn't followed, so importing a page doesn't copy the whole page tree.
+    /// It's kept if the parent is copied anyway, e.g. for the kids of an imported `/Pages`
+    /// node, and left out otherwise for the caller to set.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest` - The builder receiving the copies
+    /// * `src` - The document to copy from
+    /// * `root` - The reference of the first object to copy
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new reference of `root` and the mapping from source to new
+    /// references of all copied objects, or an error if a source object can't be read
+    pub fn import(
+        dest: &mut DocumentBuilder,
+        src: &mut PDFDocument,
+        root: ObjRefTuple,
+    ) -> Result<(ObjRefTuple, HashMap<ObjRefTuple, ObjRefTuple>)> {
+        let mut mapping = HashMap::new();
+        let new_root = Self::import_into(dest, src, root, &mut mapping)?;
+        Ok((new_root, mapping))
+    }
+
+    /// Deep-copies the objects reachable from `root` like [`ObjectImporter::import`], the
+    /// objects already in `mapping` aren't copied again so several roots share them.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new reference of `root`, or an error if a source object
+    /// can't be read
+    pub(crate) fn import_into(
+        dest: &mut DocumentBuilder,
+        src: &mut PDFDocument,
+        root: ObjRefTuple,
+        mapping: &mut HashMap<ObjRefTuple, ObjRefTuple>,
+    ) -> Result<ObjRefTuple> {
+        if let Some(copy) = mapping.get(&root) {
+            return Ok(*copy);
+        }
+        let mut queue = VecDeque::new();
+        let new_root = dest.reserve();
+        mapping.insert(root, new_root);
+        queue.push_back(root);
+        let mut copies = Vec::new();
+        while let Some(obj_ref) = queue.pop_front() {
+            let mut object = match src.read_object_with_ref(obj_ref)? {
+                Some(PDFObject::IndirectObject(_, _, object)) => *object,
+                Some(object) => object,
+                None => PDFObject::Null,
+            };
+            let parent = take_parent(&mut object);
+            rewrite_refs(&mut object, &mut |source| {
+                *mapping.entry(source).or_insert_with(|| {
+                    queue.push_back(source);
+                    dest.reserve()
+                })
+            });
+            copies.push((mapping[&obj_ref], object, parent));
+        }
+        // The parents are known once everything reachable is copied
+        for (copy, mut object, parent) in copies {
+            if let Some((obj_num, gen_num)) = parent.and_then(|it| mapping.get(&it))
+                && let Some(dict) = dict_mut(&mut object)
+            {
+                dict.insert(PARENT, PDFObject::ObjectRef(*obj_num, *gen_num));
+            }
+            dest.set(copy, object);
+        }
+        Ok(new_root)
+    }
+}
+
+/// Returns the dictionary of a dictionary or stream object.
+fn dict_mut(object: &mut PDFObject) -> Option<&mut Dictionary> {
+    match object {
+        PDFObject::Dict(dict) => Some(dict),
+        PDFObject::Stream(stream) => Some(stream.get_metadata_mut()),
+        _ => None,
+    }
+}
+
+/// Removes the `/Parent` reference of a dictionary or stream object and returns it.
+fn take_parent(object: &mut PDFObject) -> Option<ObjRefTuple> {
+    let dict = dict_mut(object)?;
+    let parent = dict.get(PARENT).and_then(|it| it.as_object_ref())?;
+    dict.remove(PARENT);
+    Some(parent)
+}
+
+/// Replaces every reference nested in `object` by the reference returned from `map`.
+fn rewrite_refs(object: &mut PDFObject, map: &mut impl FnMut(ObjRefTuple) -> ObjRefTuple) {
+    match object {
+        PDFObject::ObjectRef(obj_num, gen_num) => {
+            (*obj_num, *gen_num) = map((*obj_num, *gen_num));
+        }
+        PDFObject::Array(elements) => {
+            for element in elements {
+                rewrite_refs(element, map);
+            }
+        }
+        PDFObject::Dict(dict) => rewrite_dict_refs(dict, map),
+        PDFObject::Stream(stream) => rewrite_dict_refs(stream.get_metadata_mut(), map),
+        PDFObject::IndirectObject(_, _, value) => rewrite_refs(value, map),
+        _ => {}
+    }
+}
+
+/// Rewrites the references of a dictionary in key order, so the new object numbers
+/// don't depend on the hash order.
+fn rewrite_dict_refs(dict: &mut Dictionary, map: &mut impl FnMut(ObjRefTuple) -> ObjRefTuple) {
+    let mut entries = dict.iter_mut().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    for (_, value) in entries {
+        rewrite_refs(value, map);
+    }
+}
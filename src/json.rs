@@ -0,0 +1,132 @@
+//! The JSON output of [`ValidationReport::to_json`] and [`DocumentStats::to_json`], enabled by
+//! the `serde` feature.
+//!
+//! Both documents are objects starting with `schema_version`, currently
+//! [`SCHEMA_VERSION`]. The version is increased when a field is removed, renamed or changes
+//! its type; new fields may be added without increasing it.
+//!
+//! A validation report looks like:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "findings": [
+//!     {
+//!       "code": "dangling_reference",
+//!       "severity": "warning",
+//!       "message": "Reference 9 0 R points to no object",
+//!       "object": { "number": 3, "generation": 0 },
+//!       "offset": null
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! * `code` - The category of the finding, see [`Category::code`]
+//! * `severity` - `info`, `warning` or `error`
+//! * `message` - The human-readable description, not meant for matching
+//! * `object` - The object the finding is about, or `null`
+//! * `offset` - The byte offset the finding is about, or `null`
+//!
+//! Statistics have one integer field per counter of [`DocumentStats`], named like its getter
+//! without `get_`, e.g. `dictionaries` or `free_entries`, and a `filters` object mapping the
+//! filter names to their number of streams, in name order.
+
+use crate::stats::DocumentStats;
+use crate::verify::{Finding, ValidationReport};
+use serde::Serialize;
+use std::collections::BTreeMap;
+#[cfg(doc)]
+use crate::verify::Category;
+
+/// The version of the JSON schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct ReportJson<'a> {
+    schema_version: u32,
+    findings: Vec<FindingJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct FindingJson<'a> {
+    code: &'static str,
+    severity: &'static str,
+    message: &'a str,
+    object: Option<ObjectJson>,
+    offset: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct ObjectJson {
+    number: u32,
+    generation: u16,
+}
+
+#[derive(Serialize)]
+struct StatsJson<'a> {
+    schema_version: u32,
+    dictionaries: u64,
+    streams: u64,
+    arrays: u64,
+    numbers: u64,
+    strings: u64,
+    names: u64,
+    booleans: u64,
+    nulls: u64,
+    unreadable: u64,
+    filters: BTreeMap<&'a str, u64>,
+    stream_bytes: u64,
+    pages: u64,
+    fonts: u64,
+    images: u64,
+    in_use_entries: u64,
+    free_entries: u64,
+}
+
+impl<'a> From<&'a Finding> for FindingJson<'a> {
+    fn from(finding: &'a Finding) -> Self {
+        FindingJson {
+            code: finding.get_category().code(),
+            severity: finding.get_severity().code(),
+            message: finding.get_message(),
+            object: finding.get_object().map(|(number, generation)| ObjectJson { number, generation }),
+            offset: finding.get_offset(),
+        }
+    }
+}
+
+/// Serializes a value of the schema, which has no maps with non-string keys and therefore
+/// can't fail.
+fn to_string(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("the JSON schema only has string keys")
+}
+
+pub(crate) fn report_to_json(report: &ValidationReport) -> String {
+    to_string(&ReportJson {
+        schema_version: SCHEMA_VERSION,
+        findings: report.get_findings().iter().map(FindingJson::from).collect(),
+    })
+}
+
+pub(crate) fn stats_to_json(stats: &DocumentStats) -> String {
+    to_string(&StatsJson {
+        schema_version: SCHEMA_VERSION,
+        dictionaries: stats.get_dictionaries(),
+        streams: stats.get_streams(),
+        arrays: stats.get_arrays(),
+        numbers: stats.get_numbers(),
+        strings: stats.get_strings(),
+        names: stats.get_names(),
+        booleans: stats.get_booleans(),
+        nulls: stats.get_nulls(),
+        unreadable: stats.get_unreadable(),
+        filters: stats.get_filters().iter().map(|(name, count)| (name.as_str(), *count)).collect(),
+        stream_bytes: stats.get_stream_bytes(),
+        pages: stats.get_pages(),
+        fonts: stats.get_fonts(),
+        images: stats.get_images(),
+        in_use_entries: stats.get_in_use_entries(),
+        free_entries: stats.get_free_entries(),
+    })
+}
@@ -0,0 +1,247 @@
+use crate::constants::{
+    ANNOTS, CONTENTS, COUNT, CROP_BOX, DESTS, ID, KIDS, MEDIA_BOX, NAMES, PAGES, PARENT, RESOURCES, ROTATE, SUBTYPE,
+    TYPE,
+};
+use crate::vpdf::PDFVersion;
+use crate::vpdf::PDFVersion::{V1_0, V1_1, V1_2, V1_3, V1_4, V1_5, V1_6, V1_7, V2_0};
+use std::fmt::{Display, Formatter};
+
+/// The kind of a dictionary whose keys are checked against the specification, see
+/// [`crate::verify::VerifyOptions::with_unknown_keys`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum KeyContext {
+    /// The document catalog.
+    Catalog,
+    /// An intermediate node of the page tree.
+    Pages,
+    /// A page object.
+    Page,
+    /// A font dictionary of any font type.
+    Font,
+    /// An annotation dictionary of any annotation type.
+    Annot,
+}
+
+/// The keys of the catalog and the version defining them (ISO 32000-2, table 29).
+const CATALOG_KEYS: &[(&str, PDFVersion)] = &[
+    (TYPE, V1_0),
+    ("Version", V1_4),
+    ("Extensions", V1_7),
+    (PAGES, V1_0),
+    ("PageLabels", V1_3),
+    (NAMES, V1_2),
+    (DESTS, V1_1),
+    ("ViewerPreferences", V1_2),
+    ("PageLayout", V1_0),
+    ("PageMode", V1_0),
+    ("Outlines", V1_0),
+    ("Threads", V1_1),
+    ("OpenAction", V1_1),
+    ("AA", V1_4),
+    ("URI", V1_1),
+    ("AcroForm", V1_2),
+    ("Metadata", V1_4),
+    ("StructTreeRoot", V1_3),
+    ("MarkInfo", V1_4),
+    ("Lang", V1_4),
+    ("SpiderInfo", V1_3),
+    ("OutputIntents", V1_4),
+    ("PieceInfo", V1_4),
+    ("OCProperties", V1_5),
+    ("Perms", V1_5),
+    ("Legal", V1_5),
+    ("Requirements", V1_7),
+    ("Collection", V1_7),
+    ("NeedsRendering", V1_7),
+    ("DSS", V2_0),
+    ("AF", V2_0),
+    ("DPartRoot", V2_0),
+];
+
+/// The keys of an intermediate page tree node, including the inheritable page attributes
+/// (table 30).
+const PAGES_KEYS: &[(&str, PDFVersion)] = &[
+    (TYPE, V1_0),
+    (PARENT, V1_0),
+    (KIDS, V1_0),
+    (COUNT, V1_0),
+    (RESOURCES, V1_0),
+    (MEDIA_BOX, V1_0),
+    (CROP_BOX, V1_0),
+    (ROTATE, V1_0),
+];
+
+/// The keys of a page object (table 31).
+const PAGE_KEYS: &[(&str, PDFVersion)] = &[
+    (TYPE, V1_0),
+    (PARENT, V1_0),
+    ("LastModified", V1_3),
+    (RESOURCES, V1_0),
+    (MEDIA_BOX, V1_0),
+    (CROP_BOX, V1_0),
+    ("BleedBox", V1_3),
+    ("TrimBox", V1_3),
+    ("ArtBox", V1_3),
+    ("BoxColorInfo", V1_4),
+    (CONTENTS, V1_0),
+    (ROTATE, V1_0),
+    ("Group", V1_4),
+    ("Thumb", V1_0),
+    ("B", V1_1),
+    ("Dur", V1_1),
+    ("Trans", V1_1),
+    (ANNOTS, V1_0),
+    ("AA", V1_2),
+    ("Metadata", V1_4),
+    ("PieceInfo", V1_3),
+    ("StructParents", V1_3),
+    (ID, V1_3),
+    ("PZ", V1_3),
+    ("SeparationInfo", V1_3),
+    ("Tabs", V1_5),
+    ("TemplateInstantiated", V1_5),
+    ("PresSteps", V1_5),
+    ("UserUnit", V1_6),
+    ("VP", V1_6),
+    ("AF", V2_0),
+    ("OutputIntents", V2_0),
+    ("DPart", V2_0),
+];
+
+/// The keys of the simple, Type 3 and composite fonts and of CIDFonts (tables 109 to 121).
+const FONT_KEYS: &[(&str, PDFVersion)] = &[
+    (TYPE, V1_0),
+    (SUBTYPE, V1_0),
+    ("Name", V1_0),
+    ("BaseFont", V1_0),
+    ("FirstChar", V1_0),
+    ("LastChar", V1_0),
+    ("Widths", V1_0),
+    ("FontDescriptor", V1_0),
+    ("Encoding", V1_0),
+    ("FontBBox", V1_0),
+    ("FontMatrix", V1_0),
+    ("CharProcs", V1_0),
+    ("ToUnicode", V1_2),
+    (RESOURCES, V1_2),
+    ("DescendantFonts", V1_2),
+    ("CIDSystemInfo", V1_2),
+    ("DW", V1_2),
+    ("W", V1_2),
+    ("DW2", V1_2),
+    ("W2", V1_2),
+    ("CIDToGIDMap", V1_2),
+];
+
+/// The keys common to all annotations (table 166) and those of the annotation types,
+/// including the field keys of merged widget annotations.
+const ANNOT_KEYS: &[(&str, PDFVersion)] = &[
+    (TYPE, V1_0),
+    (SUBTYPE, V1_0),
+    ("Rect", V1_0),
+    (CONTENTS, V1_0),
+    ("Border", V1_0),
+    ("Dest", V1_0),
+    ("Open", V1_0),
+    ("Name", V1_0),
+    ("M", V1_1),
+    ("F", V1_1),
+    ("C", V1_1),
+    ("A", V1_1),
+    ("T", V1_1),
+    ("AP", V1_2),
+    ("AS", V1_2),
+    ("H", V1_2),
+    ("AA", V1_2),
+    ("FS", V1_2),
+    ("Sound", V1_2),
+    ("Movie", V1_2),
+    (PARENT, V1_2),
+    ("FT", V1_2),
+    (KIDS, V1_2),
+    ("TU", V1_2),
+    ("TM", V1_3),
+    ("Ff", V1_2),
+    ("V", V1_2),
+    ("DV", V1_2),
+    ("DA", V1_2),
+    ("Q", V1_2),
+    ("MaxLen", V1_2),
+    ("Opt", V1_2),
+    ("TI", V1_2),
+    ("I", V1_2),
+    ("P", V1_3),
+    ("StructParent", V1_3),
+    ("Popup", V1_3),
+    ("CA", V1_4),
+    ("NM", V1_4),
+    ("QuadPoints", V1_6),
+    ("PA", V1_3),
+    ("MK", V1_2),
+    ("BS", V1_2),
+    ("IC", V1_4),
+    ("L", V1_3),
+    ("LE", V1_4),
+    ("Vertices", V1_5),
+    ("InkList", V1_3),
+    ("RC", V1_5),
+    ("CreationDate", V1_5),
+    ("IRT", V1_5),
+    ("Subj", V1_5),
+    ("RT", V1_6),
+    ("IT", V1_6),
+    ("ExData", V1_7),
+    ("State", V1_5),
+    ("StateModel", V1_5),
+    ("DS", V1_5),
+    ("RV", V1_5),
+    ("BE", V1_5),
+    ("RD", V1_5),
+    ("LL", V1_6),
+    ("LLE", V1_6),
+    ("Cap", V1_6),
+    ("LLO", V1_7),
+    ("CP", V1_7),
+    ("CO", V1_7),
+    ("Measure", V1_7),
+    ("OC", V1_5),
+    ("Lock", V1_5),
+    ("SV", V1_5),
+    ("SM", V2_0),
+    ("AF", V2_0),
+    ("ca", V2_0),
+    ("BM", V2_0),
+    ("Lang", V2_0),
+];
+
+impl KeyContext {
+    /// Returns the keys the specification defines for the dictionary kind and the version
+    /// which added each of them.
+    pub(crate) fn known_keys(&self) -> &'static [(&'static str, PDFVersion)] {
+        match self {
+            KeyContext::Catalog => CATALOG_KEYS,
+            KeyContext::Pages => PAGES_KEYS,
+            KeyContext::Page => PAGE_KEYS,
+            KeyContext::Font => FONT_KEYS,
+            KeyContext::Annot => ANNOT_KEYS,
+        }
+    }
+
+    /// Returns the version which added a key, `None` if the specification doesn't define it.
+    pub(crate) fn defined_since(&self, key: &str) -> Option<PDFVersion> {
+        self.known_keys().iter().find(|(known, _)| *known == key).map(|(_, version)| *version)
+    }
+}
+
+impl Display for KeyContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            KeyContext::Catalog => "Catalog",
+            KeyContext::Pages => "Pages",
+            KeyContext::Page => "Page",
+            KeyContext::Font => "Font",
+            KeyContext::Annot => "Annot",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,203 @@
+use crate::constants::{P, PAGE_LABELS, S, ST};
+use crate::document::PDFDocument;
+use crate::encoding::PreDefinedEncoding;
+use crate::error::Result;
+use crate::number_tree::NumberTree;
+use crate::objects::PDFObject;
+use crate::pstr::convert_glyph_text;
+
+/// The largest number formatted as roman numerals or letters, larger numbers would repeat
+/// `M` or a letter thousands of times and are formatted as decimal numerals instead.
+const MAX_REPEATED_NUMBER: u64 = 100_000;
+
+/// The numbering style of a page label range (`/S`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelStyle {
+    /// Decimal arabic numerals (`D`).
+    Decimal,
+    /// Uppercase roman numerals (`R`).
+    UpperRoman,
+    /// Lowercase roman numerals (`r`).
+    LowerRoman,
+    /// Uppercase letters (`A`), `A` to `Z`, then `AA` to `ZZ` and so on.
+    UpperAlpha,
+    /// Lowercase letters (`a`), `a` to `z`, then `aa` to `zz` and so on.
+    LowerAlpha,
+}
+
+/// A range of pages labelled alike, starting at a page and ending before the next range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageLabelRange {
+    /// The index of the first page of the range.
+    start: usize,
+    /// The numbering style, `None` if the labels are only the prefix.
+    style: Option<LabelStyle>,
+    prefix: String,
+    /// The number of the first page of the range (`/St`).
+    first: u64,
+}
+
+impl LabelStyle {
+    fn from_name(name: &str) -> Option<LabelStyle> {
+        match name {
+            "D" => Some(LabelStyle::Decimal),
+            "R" => Some(LabelStyle::UpperRoman),
+            "r" => Some(LabelStyle::LowerRoman),
+            "A" => Some(LabelStyle::UpperAlpha),
+            "a" => Some(LabelStyle::LowerAlpha),
+            _ => None,
+        }
+    }
+
+    /// Formats a page number in the style.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The page number, starting at 1
+    ///
+    /// # Returns
+    ///
+    /// The formatted number, empty for 0 which no style can represent
+    pub fn format(&self, number: u64) -> String {
+        if number == 0 {
+            return String::new();
+        }
+        match self {
+            _ if number > MAX_REPEATED_NUMBER => number.to_string(),
+            LabelStyle::Decimal => number.to_string(),
+            LabelStyle::UpperRoman => roman(number),
+            LabelStyle::LowerRoman => roman(number).to_lowercase(),
+            LabelStyle::UpperAlpha => alpha(number),
+            LabelStyle::LowerAlpha => alpha(number).to_lowercase(),
+        }
+    }
+}
+
+/// Formats a number as uppercase roman numerals, thousands are repeated `M`s.
+fn roman(mut number: u64) -> String {
+    const NUMERALS: [(u64, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut text = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            text.push_str(numeral);
+            number -= value;
+        }
+    }
+    text
+}
+
+/// Formats a number as uppercase letters, 27 is `AA` and 53 is `AAA`.
+fn alpha(number: u64) -> String {
+    let letter = (b'A' + ((number - 1) % 26) as u8) as char;
+    std::iter::repeat_n(letter, ((number - 1) / 26 + 1) as usize).collect()
+}
+
+impl PageLabelRange {
+    /// Returns the index of the first page of the range.
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the numbering style, or `None` if the labels consist of the prefix only.
+    pub fn get_style(&self) -> Option<LabelStyle> {
+        self.style
+    }
+
+    /// Returns the prefix of the labels.
+    pub fn get_prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the number of the first page of the range.
+    pub fn get_first_number(&self) -> u64 {
+        self.first
+    }
+
+    /// Returns the label of a page of the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the page in the document, at least the start of the range
+    ///
+    /// # Returns
+    ///
+    /// The prefix followed by the page number in the style of the range
+    pub fn label(&self, index: usize) -> String {
+        let number = self.first.saturating_add(index.saturating_sub(self.start) as u64);
+        match self.style {
+            Some(style) => format!("{}{}", self.prefix, style.format(number)),
+            None => self.prefix.clone(),
+        }
+    }
+}
+
+/// Reads the page label ranges from the `/PageLabels` number tree of the catalog.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// A `Result` containing the ranges ordered by their first page, empty if the document has
+/// no page labels, or an error if the tree can't be read
+pub(crate) fn collect_page_labels(document: &mut PDFDocument) -> Result<Vec<PageLabelRange>> {
+    let Some(root) = document.get_catalog().get_attrs().get(PAGE_LABELS).cloned() else {
+        return Ok(Vec::new());
+    };
+    let mut entries = NumberTree::new(root).range_iter(document, 0..).collect::<Result<Vec<_>>>()?;
+    // A malformed tree may list its entries out of order
+    entries.sort_by_key(|(start, _)| *start);
+    let mut ranges = Vec::new();
+    for (start, value) in entries {
+        let Ok(start) = usize::try_from(start) else {
+            continue;
+        };
+        let PDFObject::Dict(dict) = value else {
+            continue;
+        };
+        let style = dict.get_named_value(S).and_then(LabelStyle::from_name);
+        let prefix = match dict.get(P) {
+            Some(prefix) => document.resolve(prefix.clone())?,
+            None => None,
+        };
+        let prefix = match prefix {
+            Some(PDFObject::String(pstr)) => convert_glyph_text(&pstr, &PreDefinedEncoding::PDFDoc),
+            _ => String::new(),
+        };
+        let first = dict.get_u64_num(ST).filter(|it| *it > 0).unwrap_or(1);
+        ranges.push(PageLabelRange { start, style, prefix, first });
+    }
+    // Of ranges starting at the same page the first one in the tree is kept
+    ranges.dedup_by_key(|it| it.start);
+    Ok(ranges)
+}
+
+/// Returns the label of a page.
+///
+/// # Arguments
+///
+/// * `ranges` - The page label ranges ordered by their first page
+/// * `index` - The index of the page
+///
+/// # Returns
+///
+/// The label, or `None` if no range covers the page
+pub(crate) fn label_of(ranges: &[PageLabelRange], index: usize) -> Option<String> {
+    let range = ranges.iter().rev().find(|it| it.start <= index)?;
+    Some(range.label(index))
+}
@@ -2,14 +2,52 @@ pub(crate) mod utils;
 pub mod vpdf;
 pub mod error;
 pub mod objects;
+pub mod name;
+pub mod names;
+pub mod name_tree;
+pub mod number_tree;
 pub mod document;
 pub mod sequence;
 pub(crate) mod parser;
 pub(crate) mod constants;
 pub(crate) mod tokenizer;
-pub(crate) mod catalog;
-pub(crate) mod encoding;
+pub(crate) mod trace;
+pub mod catalog;
+pub mod encoding;
 mod pstr;
 pub mod date;
 pub mod helper;
-mod filter;
\ No newline at end of file
+mod filter;
+pub mod content;
+pub mod verify;
+pub(crate) mod known_keys;
+pub mod options;
+pub mod stats;
+pub mod text;
+pub mod writer;
+pub mod objstm;
+pub mod import;
+pub mod path;
+pub mod page;
+pub mod font;
+pub mod image;
+pub mod stream_map;
+pub mod resources;
+pub mod report;
+pub mod features;
+pub mod operators;
+pub mod partial;
+pub mod duplicates;
+pub mod diff;
+pub mod labels;
+pub mod revisions;
+pub mod page_count;
+#[cfg(feature = "tokio")]
+mod nonblocking;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "serde")]
+pub mod json;
+
+pub use diff::diff;
+pub use parser::{ObjectReader, parse_object};
@@ -0,0 +1,323 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::string::FromUtf8Error;
+
+/// The longest name stored inline, so a `Name` is no larger than a `String`.
+const INLINE_CAP: usize = 22;
+
+/// Names and keywords which are shared instead of allocated, sorted for binary search.
+const COMMON_NAMES: [&str; 77] = [
+    "ASCII85Decode",
+    "ASCIIHexDecode",
+    "Annots",
+    "BaseFont",
+    "BitsPerComponent",
+    "Catalog",
+    "ColorSpace",
+    "Contents",
+    "Count",
+    "Creator",
+    "CropBox",
+    "DCTDecode",
+    "DecodeParms",
+    "DeviceCMYK",
+    "DeviceGray",
+    "DeviceRGB",
+    "Encoding",
+    "ExtGState",
+    "Filter",
+    "First",
+    "FirstChar",
+    "FlateDecode",
+    "Font",
+    "FontDescriptor",
+    "Form",
+    "Height",
+    "Image",
+    "ImageB",
+    "ImageC",
+    "Index",
+    "Info",
+    "Kids",
+    "LZWDecode",
+    "Last",
+    "LastChar",
+    "Length",
+    "MediaBox",
+    "N",
+    "Next",
+    "ObjStm",
+    "Outlines",
+    "PDF",
+    "Page",
+    "Pages",
+    "Parent",
+    "Prev",
+    "ProcSet",
+    "Producer",
+    "Rect",
+    "Resources",
+    "Root",
+    "Rotate",
+    "RunLengthDecode",
+    "Size",
+    "Subtype",
+    "Text",
+    "Title",
+    "TrueType",
+    "Type",
+    "Type0",
+    "Type1",
+    "W",
+    "Width",
+    "Widths",
+    "WinAnsiEncoding",
+    "XObject",
+    "XRef",
+    "endobj",
+    "endstream",
+    "false",
+    "null",
+    "obj",
+    "startxref",
+    "stream",
+    "trailer",
+    "true",
+    "xref",
+];
+
+/// A PDF name or keyword, e.g. `Type` for `/Type`, without the leading slash.
+///
+/// Common names are shared static strings and short names are stored inline, so most
+/// names are created without allocating. A name dereferences to `str` and compares,
+/// hashes and orders like its text.
+///
+/// # Examples
+///
+/// ```
+/// use pdf_rs::name::Name;
+///
+/// let name = Name::new("MediaBox");
+/// assert_eq!(name, "MediaBox");
+/// assert!(name.starts_with("Media"));
+/// assert_eq!(name.to_string(), "MediaBox");
+/// ```
+#[derive(Clone)]
+pub struct Name(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Static(&'static str),
+    Inline(u8, [u8; INLINE_CAP]),
+    Heap(Box<str>),
+}
+
+impl Name {
+    /// Creates a name, sharing a common name or storing a short one inline.
+    pub fn new(text: &str) -> Self {
+        if let Some(name) = intern(text) {
+            return Name(Repr::Static(name));
+        }
+        if text.len() <= INLINE_CAP {
+            let mut buf = [0; INLINE_CAP];
+            buf[..text.len()].copy_from_slice(text.as_bytes());
+            return Name(Repr::Inline(text.len() as u8, buf));
+        }
+        Name(Repr::Heap(text.into()))
+    }
+
+    /// Creates a name from a first byte followed by the remaining bytes, as the tokenizer
+    /// reads them, without an intermediate buffer for short names.
+    pub(crate) fn from_parts(head: u8, tail: &[u8]) -> std::result::Result<Self, FromUtf8Error> {
+        let len = tail.len() + 1;
+        if len <= INLINE_CAP {
+            let mut buf = [0; INLINE_CAP];
+            buf[0] = head;
+            buf[1..len].copy_from_slice(tail);
+            return match std::str::from_utf8(&buf[..len]) {
+                Ok(text) => Ok(Name::new(text)),
+                // Let `String` build the error, the bytes are short
+                Err(_) => String::from_utf8(buf[..len].to_vec()).map(Name::from),
+            };
+        }
+        let mut bytes = Vec::with_capacity(len);
+        bytes.push(head);
+        bytes.extend_from_slice(tail);
+        String::from_utf8(bytes).map(Name::from)
+    }
+
+    /// Returns the text of the name.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Static(text) => text,
+            // The bytes were copied from a `str` and never change
+            Repr::Inline(len, buf) => std::str::from_utf8(&buf[..*len as usize]).unwrap_or_default(),
+            Repr::Heap(text) => text,
+        }
+    }
+
+    /// Returns true if the name doesn't own heap memory.
+    pub fn is_inline(&self) -> bool {
+        !matches!(self.0, Repr::Heap(_))
+    }
+}
+
+/// Returns the shared static string equal to `text`, if it is a common name.
+fn intern(text: &str) -> Option<&'static str> {
+    COMMON_NAMES.binary_search(&text).ok().map(|index| COMMON_NAMES[index])
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Default for Name {
+    fn default() -> Self {
+        Name(Repr::Static(""))
+    }
+}
+
+impl From<&str> for Name {
+    fn from(text: &str) -> Self {
+        Name::new(text)
+    }
+}
+
+impl From<String> for Name {
+    fn from(text: String) -> Self {
+        if intern(&text).is_some() || text.len() <= INLINE_CAP {
+            return Name::new(&text);
+        }
+        Name(Repr::Heap(text.into_boxed_str()))
+    }
+}
+
+impl From<&String> for Name {
+    fn from(text: &String) -> Self {
+        Name::new(text)
+    }
+}
+
+impl From<Name> for String {
+    fn from(name: Name) -> Self {
+        match name.0 {
+            Repr::Heap(text) => text.into_string(),
+            _ => name.as_str().to_string(),
+        }
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Name {}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<Name> for str {
+    fn eq(&self, other: &Name) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<Name> for &str {
+    fn eq(&self, other: &Name) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<Name> for String {
+    fn eq(&self, other: &Name) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Name {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Must hash like `str` for lookups through `Borrow<str>`
+        self.as_str().hash(state)
+    }
+}
+
+impl Display for Name {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Debug for Name {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_names_sorted() {
+        assert!(COMMON_NAMES.windows(2).all(|it| it[0] < it[1]));
+    }
+
+    #[test]
+    fn test_representations() {
+        assert!(matches!(Name::new("Type").0, Repr::Static(_)));
+        assert!(matches!(Name::new("Helvetica").0, Repr::Inline(..)));
+        let long = "A".repeat(INLINE_CAP + 1);
+        assert!(!Name::new(&long).is_inline());
+        assert_eq!(Name::from_parts(b'A', &long.as_bytes()[1..]).unwrap(), long);
+        assert_eq!(Name::from_parts(b'K', b"ids").unwrap(), "Kids");
+        assert!(Name::from_parts(b'A', &[0xFF]).is_err());
+    }
+}
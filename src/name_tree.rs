@@ -0,0 +1,176 @@
+use crate::constants::{KIDS, LIMITS, NAMES};
+use crate::document::PDFDocument;
+use crate::error::Result;
+use crate::objects::{Dictionary, ObjRefTuple, PDFObject};
+use std::collections::{HashSet, VecDeque};
+
+/// A name tree, e.g. the named destinations `/Dests` of the catalog `/Names` dictionary,
+/// see [`PDFDocument::get_name_tree`].
+///
+/// The tree maps byte string keys to values, either flat in the `/Names` array of the root
+/// or spread over leaves below `/Kids`. Nodes are read from the document when a lookup or an
+/// iteration reaches them. Values are returned as written, so a value stored in its own
+/// object is a reference.
+#[derive(Clone)]
+pub struct NameTree {
+    /// The root node, a dictionary or a reference to one.
+    root: PDFObject,
+}
+
+/// Iterates over the entries of a [`NameTree`] in tree order, see [`NameTree::iter`].
+pub struct NameTreeIter<'a> {
+    document: &'a mut PDFDocument,
+    /// The nodes left to read, the next one last.
+    pending: Vec<PDFObject>,
+    /// The entries of the leaf read last.
+    entries: VecDeque<(Vec<u8>, PDFObject)>,
+    visited: HashSet<ObjRefTuple>,
+}
+
+impl NameTree {
+    /// Creates a name tree of its root node.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root node, a dictionary or a reference to one
+    pub fn new(root: PDFObject) -> Self {
+        NameTree { root }
+    }
+
+    /// Returns the root node as given on creation.
+    pub fn get_root(&self) -> &PDFObject {
+        &self.root
+    }
+
+    /// Looks up the value of a key.
+    ///
+    /// Kids whose `/Limits` exclude the key are skipped without reading their descendants.
+    /// Kids with missing or malformed `/Limits` are searched, and leaves are searched
+    /// entirely since their keys aren't always sorted. A node reached twice is skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A mutable reference to the PDF document holding the tree
+    /// * `key` - The bytes of the key
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value, `None` if no entry has the key, or an error if a
+    /// node can't be read
+    pub fn lookup(&self, document: &mut PDFDocument, key: &[u8]) -> Result<Option<PDFObject>> {
+        let mut visited = HashSet::new();
+        let mut pending = vec![(self.root.clone(), false)];
+        while let Some((node, is_kid)) = pending.pop() {
+            let Some(dict) = read_node(document, node, &mut visited)? else {
+                continue;
+            };
+            // The root has no limits, a stray entry there is ignored
+            if is_kid && excludes(&dict, key) {
+                continue;
+            }
+            for (entry_key, value) in leaf_entries(document, &dict)? {
+                if entry_key == key {
+                    return Ok(Some(value));
+                }
+            }
+            pending.extend(kids(document, &dict)?.into_iter().rev().map(|kid| (kid, true)));
+        }
+        Ok(None)
+    }
+
+    /// Returns an iterator over the key and value pairs of the tree in tree order.
+    ///
+    /// Nodes are read as the iteration reaches them, a node which can't be read yields an
+    /// error and the iteration goes on with the next one. Entries with a key which isn't a
+    /// string are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A mutable reference to the PDF document holding the tree
+    pub fn iter<'a>(&self, document: &'a mut PDFDocument) -> NameTreeIter<'a> {
+        NameTreeIter {
+            document,
+            pending: vec![self.root.clone()],
+            entries: VecDeque::new(),
+            visited: HashSet::new(),
+        }
+    }
+}
+
+impl Iterator for NameTreeIter<'_> {
+    type Item = Result<(Vec<u8>, PDFObject)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.entries.pop_front() {
+                return Some(Ok(entry));
+            }
+            let node = self.pending.pop()?;
+            let expanded = read_node(self.document, node, &mut self.visited).and_then(|dict| match dict {
+                Some(dict) => Ok((leaf_entries(self.document, &dict)?, kids(self.document, &dict)?)),
+                None => Ok(Default::default()),
+            });
+            match expanded {
+                Ok((entries, kids)) => {
+                    self.entries.extend(entries);
+                    self.pending.extend(kids.into_iter().rev());
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Reads a node of a tree, `None` if it isn't a dictionary or a reference reached before.
+pub(crate) fn read_node(
+    document: &mut PDFDocument,
+    node: PDFObject,
+    visited: &mut HashSet<ObjRefTuple>,
+) -> Result<Option<Dictionary>> {
+    if let PDFObject::ObjectRef(obj_num, gen_num) = node
+        && !visited.insert((obj_num, gen_num))
+    {
+        return Ok(None);
+    }
+    match document.resolve(node)? {
+        Some(PDFObject::Dict(dict)) => Ok(Some(dict)),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the `/Kids` of a node, empty for a leaf.
+pub(crate) fn kids(document: &mut PDFDocument, dict: &Dictionary) -> Result<Vec<PDFObject>> {
+    match dict.get(KIDS) {
+        Some(kids) => Ok(document.resolve(kids.clone())?.and_then(PDFObject::into_array).unwrap_or_default()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Returns the key and value pairs of the `/Names` of a leaf, skipping keys which aren't
+/// strings.
+fn leaf_entries(document: &mut PDFDocument, dict: &Dictionary) -> Result<Vec<(Vec<u8>, PDFObject)>> {
+    let Some(names) = dict.get(NAMES) else {
+        return Ok(Vec::new());
+    };
+    let names = document.resolve(names.clone())?.and_then(PDFObject::into_array).unwrap_or_default();
+    let entries = names
+        .chunks_exact(2)
+        .filter_map(|pair| Some((pair[0].as_string()?.as_bytes().to_vec(), pair[1].clone())))
+        .collect();
+    Ok(entries)
+}
+
+/// Checks whether the `/Limits` of a node exclude a key. Limits which aren't two strings in
+/// order exclude nothing.
+fn excludes(dict: &Dictionary, key: &[u8]) -> bool {
+    let limits = dict.get_array_value(LIMITS).unwrap_or_default();
+    match limits {
+        [low, high] => match (low.as_string(), high.as_string()) {
+            (Some(low), Some(high)) if low.as_bytes() <= high.as_bytes() => {
+                key < low.as_bytes() || key > high.as_bytes()
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,60 @@
+//! Well-known dictionary keys, shared with the crate's own lookups.
+//!
+//! The constants are plain strings, so they work with every [`Dictionary`] getter, e.g.
+//!
+//! ```no_run
+//! use pdf_rs::document::PDFDocument;
+//! use pdf_rs::names;
+//!
+//! let document = PDFDocument::open("document/pdfreference1.0.pdf".into()).unwrap();
+//! let catalog = document.get_catalog().get_attrs();
+//! assert!(catalog.named_value_was(names::TYPE, "Catalog"));
+//! assert!(catalog.contain(names::PAGES));
+//! ```
+//!
+//! [`Dictionary`]: crate::objects::Dictionary
+
+/// Key for the object type.
+pub const TYPE: &str = "Type";
+/// Key for the object subtype.
+pub const SUBTYPE: &str = "Subtype";
+/// Key for the page tree root of the catalog.
+pub const PAGES: &str = "Pages";
+/// Key for the children of a page tree node.
+pub const KIDS: &str = "Kids";
+/// Key for the parent of a page tree node or outline item.
+pub const PARENT: &str = "Parent";
+/// Key for the number of pages below a page tree node or of visible outline items.
+pub const COUNT: &str = "Count";
+/// Key for the content streams of a page.
+pub const CONTENTS: &str = "Contents";
+/// Key for the resource dictionary of a page or form.
+pub const RESOURCES: &str = "Resources";
+/// Key for the media box of a page.
+pub const MEDIA_BOX: &str = "MediaBox";
+/// Key for the crop box of a page.
+pub const CROP_BOX: &str = "CropBox";
+/// Key for the rotation of a page.
+pub const ROTATE: &str = "Rotate";
+/// Key for the fonts of a resource dictionary.
+pub const FONT: &str = "Font";
+/// Key for the external objects of a resource dictionary.
+pub const XOBJECT: &str = "XObject";
+/// Key for the filters of a stream.
+pub const FILTER: &str = "Filter";
+/// Key for the length of a stream.
+pub const LENGTH: &str = "Length";
+/// Key for the catalog of the trailer.
+pub const ROOT: &str = "Root";
+/// Key for the document information dictionary of the trailer.
+pub const INFO: &str = "Info";
+/// Key for the encryption dictionary of the trailer.
+pub const ENCRYPT: &str = "Encrypt";
+/// Key for the file identifier of the trailer.
+pub const ID: &str = "ID";
+/// Key for the annotations of a page.
+pub const ANNOTS: &str = "Annots";
+/// Key for the named destinations of the catalog.
+pub const DESTS: &str = "Dests";
+/// Key for the name dictionary of the catalog.
+pub const NAMES: &str = "Names";
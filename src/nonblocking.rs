@@ -0,0 +1,74 @@
+//! Async wrappers which run the blocking parsing on tokio's blocking thread pool, so
+//! request handlers don't stall the executor. Enabled by the `tokio` feature.
+
+use crate::document::PDFDocument;
+use crate::error::Result;
+use crate::text::TextExtractionOptions;
+use crate::verify::ValidationReport;
+use std::path::PathBuf;
+use tokio::task::spawn_blocking;
+
+impl PDFDocument {
+    /// Opens a PDF document like [`PDFDocument::open`] on a blocking thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the PDF file to open
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `PDFDocument` or an error if the file cannot be opened
+    /// or parsed correctly
+    pub async fn open_async(path: PathBuf) -> Result<PDFDocument> {
+        spawn_blocking(move || PDFDocument::open(path)).await?
+    }
+
+    /// Creates a PDF document like [`PDFDocument::from_bytes`] on a blocking thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes of the PDF file
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `PDFDocument` or an error if parsing fails
+    pub async fn from_bytes_async(bytes: Vec<u8>) -> Result<PDFDocument> {
+        spawn_blocking(move || PDFDocument::from_bytes(bytes)).await?
+    }
+
+    /// Checks the document like [`PDFDocument::verify`] on a blocking thread.
+    ///
+    /// The document is moved to the blocking thread and handed back with the report.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the document and the validation report, or an error if the
+    /// file can't be read
+    pub async fn verify_async(mut self) -> Result<(PDFDocument, ValidationReport)> {
+        spawn_blocking(move || {
+            let report = self.verify()?;
+            Ok((self, report))
+        })
+        .await?
+    }
+
+    /// Extracts the text of every page like [`PDFDocument::pages_text`] on a blocking thread.
+    ///
+    /// The document is moved to the blocking thread and handed back with the text.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The extraction options
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the document and the text of each page, or an error if the
+    /// content of a page can't be parsed
+    pub async fn pages_text_async(mut self, options: TextExtractionOptions) -> Result<(PDFDocument, Vec<String>)> {
+        spawn_blocking(move || {
+            let text = self.pages_text(&options)?;
+            Ok((self, text))
+        })
+        .await?
+    }
+}
@@ -0,0 +1,169 @@
+use crate::constants::{LIMITS, NUMS};
+use crate::document::PDFDocument;
+use crate::error::Result;
+use crate::name_tree::{kids, read_node};
+use crate::objects::{Dictionary, ObjRefTuple, PDFNumber, PDFObject};
+use std::collections::{HashSet, VecDeque};
+use std::ops::{Bound, RangeBounds};
+
+/// A number tree, e.g. the `/PageLabels` of the catalog or the `/ParentTree` of the
+/// structure tree root, see [`PDFDocument::get_parent_tree`].
+///
+/// The tree maps integer keys to values, either flat in the `/Nums` array of the root or
+/// spread over leaves below `/Kids`. Nodes are read from the document when a lookup or an
+/// iteration reaches them, and values stored in their own object are resolved.
+#[derive(Clone)]
+pub struct NumberTree {
+    /// The root node, a dictionary or a reference to one.
+    root: PDFObject,
+}
+
+/// Iterates over the entries of a [`NumberTree`] in tree order, see [`NumberTree::range_iter`].
+pub struct NumberTreeIter<'a> {
+    document: &'a mut PDFDocument,
+    /// The nodes left to read, the next one last, and whether they are kids.
+    pending: Vec<(PDFObject, bool)>,
+    /// The entries in range of the leaf read last, with their values still unresolved.
+    entries: VecDeque<(i64, PDFObject)>,
+    visited: HashSet<ObjRefTuple>,
+    range: (Bound<i64>, Bound<i64>),
+}
+
+impl NumberTree {
+    /// Creates a number tree of its root node.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root node, a dictionary or a reference to one
+    pub fn new(root: PDFObject) -> Self {
+        NumberTree { root }
+    }
+
+    /// Returns the root node as given on creation.
+    pub fn get_root(&self) -> &PDFObject {
+        &self.root
+    }
+
+    /// Looks up the value of a key.
+    ///
+    /// Kids whose `/Limits` exclude the key are skipped without reading their descendants,
+    /// every other kid is searched, so kids with overlapping limits are all searched. Kids
+    /// with missing or malformed `/Limits` are searched too.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A mutable reference to the PDF document holding the tree
+    /// * `key` - The key
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the resolved value, `None` if no entry has the key or its
+    /// value is a reference to a missing object, or an error if a node can't be read
+    pub fn lookup(&self, document: &mut PDFDocument, key: i64) -> Result<Option<PDFObject>> {
+        match self.range_iter(document, key..=key).next() {
+            Some(entry) => entry.map(|(_, value)| Some(value)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over the key and value pairs of the tree whose keys are in a
+    /// range, e.g. `..` for all entries.
+    ///
+    /// Entries come in tree order, which is key order unless the tree is malformed. Kids
+    /// whose `/Limits` don't overlap the range are skipped. A node which can't be read
+    /// yields an error and the iteration goes on with the next one, an entry whose value is
+    /// a reference to a missing object is skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A mutable reference to the PDF document holding the tree
+    /// * `range` - The range of the keys
+    pub fn range_iter<'a>(&self, document: &'a mut PDFDocument, range: impl RangeBounds<i64>) -> NumberTreeIter<'a> {
+        NumberTreeIter {
+            document,
+            pending: vec![(self.root.clone(), false)],
+            entries: VecDeque::new(),
+            visited: HashSet::new(),
+            range: (range.start_bound().cloned(), range.end_bound().cloned()),
+        }
+    }
+}
+
+impl NumberTreeIter<'_> {
+    /// Reads the next node, queueing its entries in range and its kids.
+    fn expand(&mut self, node: PDFObject, is_kid: bool) -> Result<()> {
+        let Some(dict) = read_node(self.document, node, &mut self.visited)? else {
+            return Ok(());
+        };
+        // The root has no limits, a stray entry there is ignored
+        if is_kid && !self.limits_overlap(&dict) {
+            return Ok(());
+        }
+        if let Some(nums) = dict.get(NUMS) {
+            let nums = self.document.resolve(nums.clone())?.and_then(PDFObject::into_array).unwrap_or_default();
+            for pair in nums.chunks_exact(2) {
+                if let Some(key) = as_key(&pair[0])
+                    && self.range.contains(&key)
+                {
+                    self.entries.push_back((key, pair[1].clone()));
+                }
+            }
+        }
+        let kids = kids(self.document, &dict)?;
+        self.pending.extend(kids.into_iter().rev().map(|kid| (kid, true)));
+        Ok(())
+    }
+
+    /// Checks whether the `/Limits` of a node overlap the range. Limits which aren't two
+    /// integers in order overlap every range.
+    fn limits_overlap(&self, dict: &Dictionary) -> bool {
+        let limits = dict.get_array_value(LIMITS).unwrap_or_default();
+        let (low, high) = match limits {
+            [low, high] => match (as_key(low), as_key(high)) {
+                (Some(low), Some(high)) if low <= high => (low, high),
+                _ => return true,
+            },
+            _ => return true,
+        };
+        let below = match self.range.1 {
+            Bound::Included(end) => end < low,
+            Bound::Excluded(end) => end <= low,
+            Bound::Unbounded => false,
+        };
+        let above = match self.range.0 {
+            Bound::Included(start) => start > high,
+            Bound::Excluded(start) => start >= high,
+            Bound::Unbounded => false,
+        };
+        !below && !above
+    }
+}
+
+impl Iterator for NumberTreeIter<'_> {
+    type Item = Result<(i64, PDFObject)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, value)) = self.entries.pop_front() {
+                match self.document.resolve(value) {
+                    Ok(Some(value)) => return Some(Ok((key, value))),
+                    Ok(None) => continue,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            let (node, is_kid) = self.pending.pop()?;
+            if let Err(e) = self.expand(node, is_kid) {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Returns the integer of a number tree key, `None` for other objects.
+fn as_key(object: &PDFObject) -> Option<i64> {
+    match object.as_number()? {
+        PDFNumber::Signed(value) => Some(*value),
+        PDFNumber::Unsigned(value) => i64::try_from(*value).ok(),
+        PDFNumber::Real(_) => None,
+    }
+}
@@ -1,12 +1,124 @@
-use std::collections::HashMap;
-use crate::constants::FILTER;
+use std::collections::{BTreeMap, HashMap};
+use std::collections::hash_map::Entry;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+use std::str::FromStr;
+use crate::constants::{
+    DECODE_PARMS, FILTER, FORM, IMAGE, LENGTH, METADATA, OBJ_STM, SUBTYPE, TYPE, XREF_STREAM,
+};
+use crate::document::PDFDocument;
+use crate::name::Name;
+use crate::error::PDFError::InvalidObjectId;
+use crate::error::{PDFError, Result};
 
 /// Type alias for an object reference tuple containing object number and generation number.
 pub type ObjRefTuple = (u32, u16);
 
+/// Identifies an indirect object by its object number and generation number.
+///
+/// Pages and outline items are identified by the object they are defined by. An
+/// `ObjectId` converts from and to an [`ObjRefTuple`], so APIs taking
+/// `impl Into<ObjectId>` accept both, and it compares equal to the tuple with the same
+/// numbers. It displays as a reference and parses from one.
+///
+/// # Examples
+///
+/// ```
+/// use pdf_rs::objects::ObjectId;
+///
+/// let id: ObjectId = "12 0 R".parse().unwrap();
+/// assert_eq!(id, ObjectId::new(12, 0));
+/// assert_eq!(id, (12, 0));
+/// assert_eq!(id.to_string(), "12 0 R");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectId {
+    /// The object number.
+    number: u32,
+    /// The generation number.
+    generation: u16,
+}
+
+impl ObjectId {
+    /// Creates an object identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The object number
+    /// * `generation` - The generation number
+    pub const fn new(number: u32, generation: u16) -> Self {
+        Self { number, generation }
+    }
+
+    /// Returns the object number.
+    pub fn get_number(&self) -> u32 {
+        self.number
+    }
+
+    /// Returns the generation number.
+    pub fn get_generation(&self) -> u16 {
+        self.generation
+    }
+
+    /// Returns the object number and generation number as a tuple.
+    pub fn to_tuple(self) -> ObjRefTuple {
+        (self.number, self.generation)
+    }
+}
+
+impl From<ObjRefTuple> for ObjectId {
+    fn from((number, generation): ObjRefTuple) -> Self {
+        Self::new(number, generation)
+    }
+}
+
+impl From<ObjectId> for ObjRefTuple {
+    fn from(id: ObjectId) -> Self {
+        id.to_tuple()
+    }
+}
+
+impl PartialEq<ObjRefTuple> for ObjectId {
+    fn eq(&self, other: &ObjRefTuple) -> bool {
+        self.to_tuple() == *other
+    }
+}
+
+impl PartialEq<ObjectId> for ObjRefTuple {
+    fn eq(&self, other: &ObjectId) -> bool {
+        *self == other.to_tuple()
+    }
+}
+
+impl Display for ObjectId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} R", self.number, self.generation)
+    }
+}
+
+impl FromStr for ObjectId {
+    type Err = PDFError;
+
+    /// Parses a reference like `12 0 R`, the `R` may be omitted.
+    fn from_str(text: &str) -> Result<Self> {
+        let mut parts = text.split_ascii_whitespace();
+        let number = parts.next().and_then(|it| it.parse().ok());
+        let generation = parts.next().and_then(|it| it.parse().ok());
+        let rest = parts.next();
+        match (number, generation, rest, parts.next()) {
+            (Some(number), Some(generation), None | Some("R"), None) => Ok(Self::new(number, generation)),
+            _ => Err(InvalidObjectId(text.to_string())),
+        }
+    }
+}
+
 /// Represents a numeric value in a PDF document.
 ///
 /// PDF supports three types of numbers: signed integers, unsigned integers, and real numbers.
+///
+/// Equality is exact on both the variant and the value, so `Unsigned(1)`, `Signed(1)` and
+/// `Real(1.0)` are all different, use [`PDFNumber::as_f64`] to compare numerically.
 #[derive(PartialEq, Clone)]
 pub enum PDFNumber {
     /// A signed integer value.
@@ -17,10 +129,24 @@ pub enum PDFNumber {
     Real(f64),
 }
 
+/// A rectangle like a page boundary, written as the array `[llx lly urx ury]`.
+///
+/// The corners are ordered on creation, so the lower left corner is the minimum of the two
+/// corners of the array in either order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    llx: f64,
+    lly: f64,
+    urx: f64,
+    ury: f64,
+}
+
 /// Represents a cross-reference table entry.
 ///
 /// XRef entries map object numbers to their file positions and track whether objects are in use.
-#[derive(Clone)]
+///
+/// The `Display` form is the entry line of a cross-reference table, e.g. `0000000017 00000 n`.
+#[derive(Clone, Debug)]
 pub struct XEntry {
     /// The value of the entry.
     pub(crate) value: u64,
@@ -30,26 +156,61 @@ pub struct XEntry {
     pub(crate) obj_num: u32,
     /// The generation number of the entry.
     pub(crate) gen_num: u16,
+    /// The number of the object stream holding the object of a compressed entry, whose
+    /// value is then the index of the object within the stream.
+    pub(crate) object_stream: Option<u32>,
+    /// The revision which last added, changed or deleted the object, 0 for the original
+    /// document.
+    pub(crate) revision: usize,
+}
+
+/// What a cross-reference entry designates, see [`PDFDocument::override_xref_entry`].
+///
+/// [`PDFDocument::override_xref_entry`]: crate::document::PDFDocument::override_xref_entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XEntryKind {
+    /// The object is free.
+    Free,
+    /// The object is defined at a byte offset of the file.
+    Offset(u64),
+    /// The object is stored at an index of an object stream.
+    Compressed { object_stream: u32, index: u64 },
 }
 
 /// Represents a PDF dictionary object.
 ///
 /// Dictionaries are associative tables containing key-value pairs where keys are names
 /// and values can be any PDF object type.
+///
+/// Two dictionaries are equal if they have the same keys with equal values, regardless of order.
+/// The iteration order is unspecified, the [`writer`](crate::writer) emits keys sorted.
+#[derive(PartialEq, Clone, Default)]
 pub struct Dictionary {
-    entries: HashMap<String, PDFObject>,
+    entries: HashMap<Name, PDFObject>,
 }
 
 /// Represents a PDF stream object.
 ///
 /// Streams contain large amounts of data (like images or page content) with associated metadata.
+///
+/// Two streams are equal if their dictionaries and their raw, still encoded, data are equal.
+/// The data is always held in memory, so cloning a stream copies its bytes.
+#[derive(Clone)]
 pub struct Stream {
     buf: Vec<u8>,
     metadata: Dictionary,
+    /// The bytes the data occupies in the parsed sequence.
+    raw_range: Option<Range<u64>>,
+}
+
+impl PartialEq for Stream {
+    fn eq(&self, other: &Self) -> bool {
+        self.buf == other.buf && self.metadata == other.metadata
+    }
 }
 
 /// Represents the kind of PDF string encoding.
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub(crate) enum PDFStrKind {
     /// Literal string enclosed in parentheses.
     Literal,
@@ -60,11 +221,26 @@ pub(crate) enum PDFStrKind {
 /// Represents a PDF string object.
 ///
 /// Strings can be either literal or hexadecimal encoded.
+///
+/// Two strings are equal if their bytes are equal, e.g. `(AB)` equals `<4142>`.
+#[derive(Clone)]
 pub struct PDFString {
     kind: PDFStrKind,
     buf: Vec<u8>,
 }
 
+impl PartialEq for PDFString {
+    fn eq(&self, other: &Self) -> bool {
+        self.buf == other.buf
+    }
+}
+
+/// Represents any PDF object.
+///
+/// Equality is structural: arrays compare element-wise, dictionaries as unordered maps and
+/// references by their object and generation numbers, without resolving them. Cloning is
+/// deep, the clone shares nothing with the original.
+#[derive(PartialEq, Clone)]
 pub enum PDFObject {
     /// The keywords true and false represent boolean objects with values true and false.
     Bool(bool),
@@ -84,7 +260,10 @@ pub enum PDFObject {
     ///  /ASomewhatLongerName2
     ///  /A;Name_With-various***characters?.
     /// ```
-    Named(String),
+    /// The name is stored without the slash as a [`Name`], which avoids allocating for
+    /// common and short names. It is built from a `String` or `&str` by `into()`, the
+    /// accessors like [`PDFObject::as_name`] return it as `&str`.
+    Named(Name),
     String(PDFString),
     /// ## Arrays
     /// An array is a sequence of PDF objects. An array may contain a mixture of object
@@ -203,10 +382,7 @@ pub enum PDFObject {
 impl PDFObject {
     /// Returns true if the object is a boolean.
     pub fn is_bool(&self) -> bool {
-        match self {
-            PDFObject::Bool(_) => true,
-            _ => false,
-        }
+        matches!(self, PDFObject::Bool(_))
     }
     /// Returns the boolean value of the object if it is a boolean.
     pub fn as_bool(&self) -> Option<bool> {
@@ -218,10 +394,7 @@ impl PDFObject {
 
     /// Returns true if the object is a number.
     pub fn is_number(&self) -> bool {
-        match self {
-            PDFObject::Number(_) => true,
-            _ => false,
-        }
+        matches!(self, PDFObject::Number(_))
     }
     /// Returns the number value of the object if it is a number.
     pub fn as_number(&self) -> Option<&PDFNumber> {
@@ -232,10 +405,7 @@ impl PDFObject {
     }
     /// Returns true if the object is a string.
     pub fn is_string(&self) -> bool {
-        match self {
-            PDFObject::String(_) => true,
-            _ => false,
-        }
+        matches!(self, PDFObject::String(_))
     }
 
     /// Returns the string value of the object if it is a string.
@@ -246,13 +416,18 @@ impl PDFObject {
         }
     }
 
-    /// Returns the string value of the object if it is a string.
-    pub fn is_array(&self) -> bool {
+    /// Takes the bytes of the string if it is one.
+    pub fn into_string_bytes(self) -> Option<Vec<u8>> {
         match self {
-            PDFObject::Array(_) => true,
-            _ => false,
+            PDFObject::String(s) => Some(s.buf),
+            _ => None,
         }
     }
+
+    /// Returns true if the object is an array.
+    pub fn is_array(&self) -> bool {
+        matches!(self, PDFObject::Array(_))
+    }
     /// Returns the array of objects if it is an array.
     pub fn as_array(&self) -> Option<&[PDFObject]> {
         match self {
@@ -260,13 +435,42 @@ impl PDFObject {
             _ => None,
         }
     }
-    /// Returns true if the object is a dictionary.
-    pub fn is_dict(&self) -> bool {
+    /// Takes the array of objects if it is an array.
+    pub fn into_array(self) -> Option<Vec<PDFObject>> {
         match self {
-            PDFObject::Dict(_) => true,
-            _ => false,
+            PDFObject::Array(a) => Some(a),
+            _ => None,
         }
     }
+    /// Returns the rectangle if the object is an array of four numbers.
+    pub fn as_rect(&self) -> Option<Rect> {
+        match self.as_array()? {
+            [x0, y0, x1, y1] => {
+                let value = |object: &PDFObject| object.as_number().map(PDFNumber::as_f64);
+                Some(Rect::new(value(x0)?, value(y0)?, value(x1)?, value(y1)?))
+            }
+            _ => None,
+        }
+    }
+    /// Returns the name of the object type for messages, e.g. `dictionary`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            PDFObject::Bool(_) => "boolean",
+            PDFObject::Number(_) => "number",
+            PDFObject::Named(_) => "name",
+            PDFObject::String(_) => "string",
+            PDFObject::Array(_) => "array",
+            PDFObject::Dict(_) => "dictionary",
+            PDFObject::Null => "null",
+            PDFObject::ObjectRef(..) => "reference",
+            PDFObject::IndirectObject(..) => "indirect object",
+            PDFObject::Stream(_) => "stream",
+        }
+    }
+    /// Returns true if the object is a dictionary.
+    pub fn is_dict(&self) -> bool {
+        matches!(self, PDFObject::Dict(_))
+    }
     /// Returns the dictionary if it is one.
     pub fn as_dict(&self) -> Option<&Dictionary> {
         match self {
@@ -274,19 +478,21 @@ impl PDFObject {
             _ => None,
         }
     }
-    /// Returns the dictionary if it is one.
-    pub fn to_dict(self) -> Option<Dictionary> {
+    /// Takes the dictionary if it is one.
+    pub fn into_dict(self) -> Option<Dictionary> {
         match self {
             PDFObject::Dict(d) => Some(d),
             _ => None,
         }
     }
+    /// Takes the dictionary if it is one, same as [`PDFObject::into_dict`].
+    #[deprecated(note = "use `into_dict`")]
+    pub fn to_dict(self) -> Option<Dictionary> {
+        self.into_dict()
+    }
     /// Returns true if the object is an indirect object.
     pub fn is_object_ref(&self) -> bool {
-        match self {
-            PDFObject::ObjectRef(_, ..) => true,
-            _ => false,
-        }
+        matches!(self, PDFObject::ObjectRef(..))
     }
     /// Returns the object reference if it is one.
     pub fn as_object_ref(&self) -> Option<(u32, u16)> {
@@ -298,10 +504,7 @@ impl PDFObject {
 
     /// Returns true if the object is an indirect object.
     pub fn is_indirect_object(&self) -> bool {
-        match self {
-            PDFObject::IndirectObject(_, _, _) => true,
-            _ => false,
-        }
+        matches!(self, PDFObject::IndirectObject(..))
     }
     /// Returns the indirect object if it is one.
     pub fn as_indirect_object(&self) -> Option<(u32, u16, &PDFObject)> {
@@ -310,20 +513,21 @@ impl PDFObject {
             _ => None,
         }
     }
+    /// Takes the object number, generation number and value if it is an indirect object.
+    pub fn into_indirect(self) -> Option<(u32, u16, PDFObject)> {
+        match self {
+            PDFObject::IndirectObject(n, g, data) => Some((n, g, *data)),
+            _ => None,
+        }
+    }
 
     /// Returns true if the object is null.
     pub fn is_null(&self) -> bool {
-        match self {
-            PDFObject::Null => true,
-            _ => false,
-        }
+        matches!(self, PDFObject::Null)
     }
     /// Returns true if the object is a stream.
     pub fn is_stream(&self)->bool{
-        match self {
-            PDFObject::Stream(_) => true,
-            _ => false,
-        }
+        matches!(self, PDFObject::Stream(_))
     }
 
     /// Returns the stream if it is one.
@@ -333,17 +537,21 @@ impl PDFObject {
             _ => None,
         }
     }
-    /// Returns true if the object is a name.
-    pub fn is_name(&self)->bool{
+    /// Takes the stream if it is one.
+    pub fn into_stream(self) -> Option<Stream> {
         match self {
-            PDFObject::Named(_) => true,
-            _ => false,
+            PDFObject::Stream(s) => Some(s),
+            _ => None,
         }
     }
+    /// Returns true if the object is a name.
+    pub fn is_name(&self)->bool{
+        matches!(self, PDFObject::Named(_))
+    }
     /// Returns the name if it is one.
-    pub fn as_name(&self)->Option<&String>{
+    pub fn as_name(&self)->Option<&str>{
         match self {
-            PDFObject::Named(s) => Some(s),
+            PDFObject::Named(s) => Some(s.as_str()),
             _ => None,
         }
     }
@@ -352,38 +560,83 @@ impl PDFObject {
 
 impl Dictionary {
     /// Creates a new dictionary with the given entries.
-    pub(crate) fn new(entries: HashMap<String, PDFObject>) -> Self {
+    pub(crate) fn new(entries: HashMap<Name, PDFObject>) -> Self {
         Dictionary { entries }
     }
-    /// Returns the value of the entry with the given key.
-    pub fn get(&self, key: &str)-> Option<&PDFObject> {
-        self.entries.get(key)
+    /// Returns the value of the entry with the given key, e.g. one of [`crate::names`], a
+    /// `String` or a [`Name`].
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&PDFObject> {
+        self.entries.get(key.as_ref())
+    }
+
+    /// Returns a mutable reference to the value of the entry with the given key.
+    pub fn get_mut(&mut self, key: impl AsRef<str>) -> Option<&mut PDFObject> {
+        self.entries.get_mut(key.as_ref())
+    }
+
+    /// Inserts an entry, returning the value previously stored under the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key without the leading slash, e.g. `Type`
+    /// * `value` - The value of the entry
+    ///
+    /// # Returns
+    ///
+    /// The replaced value, if any
+    pub fn insert(&mut self, key: impl Into<Name>, value: impl Into<PDFObject>) -> Option<PDFObject> {
+        self.entries.insert(key.into(), value.into())
+    }
+
+    /// Returns the entry of the given key for in-place manipulation.
+    pub fn entry(&mut self, key: impl Into<Name>) -> Entry<'_, Name, PDFObject> {
+        self.entries.entry(key.into())
     }
 
     /// Removes the entry with the given key.
-    pub fn remove(&mut self,key:&str)->Option<PDFObject>{
-        self.entries.remove(key)
+    pub fn remove(&mut self, key: impl AsRef<str>) -> Option<PDFObject> {
+        self.entries.remove(key.as_ref())
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the dictionary has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Returns an iterator over the entries of the dictionary.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PDFObject)> {
+        self.entries.iter().map(|(key, value)| (key.as_str(), value))
     }
+
+    /// Returns an iterator over the entries of the dictionary with mutable values.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut PDFObject)> {
+        self.entries.iter_mut().map(|(key, value)| (key.as_str(), value))
+    }
+
     /// Returns true if the dictionary contains the given key.
-    pub fn contain(&self, key: &str)->bool{
-        self.entries.contains_key(key)
+    pub fn contain(&self, key: impl AsRef<str>) -> bool {
+        self.entries.contains_key(key.as_ref())
     }
 
     /// Returns the value of the entry with the given key as a name.
-    pub fn get_named_value(&self, key: &str) -> Option<&String> {
+    pub fn get_named_value(&self, key: impl AsRef<str>) -> Option<&str> {
         self.get(key).and_then(|it| it.as_name())
     }
 
 
     /// Returns the value of the entry with the given key as a u64.
-    pub fn get_u64_num(&self, key: &str) -> Option<u64> {
+    pub fn get_u64_num(&self, key: impl AsRef<str>) -> Option<u64> {
         self.get(key)
             .and_then(|it| it.as_number())
             .and_then(|it| if let PDFNumber::Unsigned(num) = it { Some(*num) } else { None })
     }
 
     /// Returns true if the value of the entry with the given key is the given name.
-    pub fn named_value_was(&self, keys: &str,except:&str) -> bool {
+    pub fn named_value_was(&self, keys: impl AsRef<str>, except: &str) -> bool {
         if let Some(value) = self.get_named_value(keys) {
             value == except
         } else {
@@ -392,11 +645,81 @@ impl Dictionary {
     }
 
     /// Returns the value of the entry with the given key as an array.
-    pub fn get_array_value(&self, key: &str) -> Option<&[PDFObject]> {
+    pub fn get_array_value(&self, key: impl AsRef<str>) -> Option<&[PDFObject]> {
         self.get(key).and_then(|it| it.as_array())
     }
 }
 
+impl PDFNumber {
+    /// Returns the number as a floating-point value.
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            PDFNumber::Signed(value) => *value as f64,
+            PDFNumber::Unsigned(value) => *value as f64,
+            PDFNumber::Real(value) => *value,
+        }
+    }
+}
+
+impl Rect {
+    /// Creates a rectangle from two opposite corners in any order.
+    pub fn new(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+        Rect { llx: x0.min(x1), lly: y0.min(y1), urx: x0.max(x1), ury: y0.max(y1) }
+    }
+
+    /// Returns the x coordinate of the lower left corner.
+    pub fn get_llx(&self) -> f64 {
+        self.llx
+    }
+
+    /// Returns the y coordinate of the lower left corner.
+    pub fn get_lly(&self) -> f64 {
+        self.lly
+    }
+
+    /// Returns the x coordinate of the upper right corner.
+    pub fn get_urx(&self) -> f64 {
+        self.urx
+    }
+
+    /// Returns the y coordinate of the upper right corner.
+    pub fn get_ury(&self) -> f64 {
+        self.ury
+    }
+
+    /// Returns the width of the rectangle.
+    pub fn get_width(&self) -> f64 {
+        self.urx - self.llx
+    }
+
+    /// Returns the height of the rectangle.
+    pub fn get_height(&self) -> f64 {
+        self.ury - self.lly
+    }
+
+    /// Returns the rectangle as `[llx lly urx ury]`.
+    pub fn to_array(&self) -> [f64; 4] {
+        [self.llx, self.lly, self.urx, self.ury]
+    }
+
+    /// Returns the overlap with another rectangle, or `None` if they don't overlap.
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect {
+            llx: self.llx.max(other.llx),
+            lly: self.lly.max(other.lly),
+            urx: self.urx.min(other.urx),
+            ury: self.ury.min(other.ury),
+        };
+        (rect.llx <= rect.urx && rect.lly <= rect.ury).then_some(rect)
+    }
+}
+
+impl From<Rect> for PDFObject {
+    fn from(value: Rect) -> Self {
+        PDFObject::Array(value.to_array().into_iter().map(PDFObject::from).collect())
+    }
+}
+
 impl XEntry {
     pub(crate) fn new(obj_num: u32, gen_num: u16, value: u64, using: bool) -> Self {
         XEntry {
@@ -404,6 +727,32 @@ impl XEntry {
             gen_num,
             using,
             value,
+            object_stream: None,
+            revision: 0,
+        }
+    }
+
+    /// Creates an entry of an object of the given kind.
+    pub(crate) fn of_kind(obj_num: u32, gen_num: u16, kind: XEntryKind) -> Self {
+        match kind {
+            XEntryKind::Free => XEntry::new(obj_num, gen_num, 0, false),
+            XEntryKind::Offset(offset) => XEntry::new(obj_num, gen_num, offset, true),
+            XEntryKind::Compressed { object_stream, index } => {
+                XEntry { gen_num, ..XEntry::compressed(obj_num, object_stream, index) }
+            }
+        }
+    }
+
+    /// Creates an entry of an object stored at `index` in the object stream `object_stream`,
+    /// such objects always have generation 0.
+    pub(crate) fn compressed(obj_num: u32, object_stream: u32, index: u64) -> Self {
+        XEntry {
+            obj_num,
+            gen_num: 0,
+            using: true,
+            value: index,
+            object_stream: Some(object_stream),
+            revision: 0,
         }
     }
     /// Returns the object number of the entry.
@@ -423,10 +772,127 @@ impl XEntry {
     pub fn is_freed(&self)->bool{
         !self.using
     }
-    /// Returns the value of the entry.
+    /// Returns the value of the entry, the byte offset of an in-use object or the index
+    /// within its object stream of a compressed object.
     pub fn get_value(&self)->u64{
         self.value
     }
+
+    /// Returns the number of the object stream holding the object, if the entry is compressed.
+    pub fn get_object_stream(&self) -> Option<u32> {
+        self.object_stream
+    }
+
+    /// Returns true if the object is stored in an object stream instead of at a byte offset.
+    pub fn is_compressed(&self) -> bool {
+        self.object_stream.is_some()
+    }
+
+    /// Returns the index of the revision which last added, changed or deleted the object, 0
+    /// for the original document or a document whose cross-reference data was rebuilt.
+    pub fn get_revision(&self) -> usize {
+        self.revision
+    }
+}
+
+impl Display for XEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.using { 'n' } else { 'f' };
+        write!(f, "{:010} {:05} {}", self.value, self.gen_num, kind)
+    }
+}
+
+/// The merged cross-reference entries of a document, indexed by object number so
+/// resolving a reference doesn't scan the entries.
+///
+/// The table dereferences to the entries in xref order. Cloning shares the entries and the
+/// index.
+#[derive(Clone, Default)]
+pub(crate) struct XrefTable {
+    entries: Arc<[XEntry]>,
+    /// The position of the entry of each object number, the first one if the number is
+    /// listed twice.
+    index: Arc<HashMap<u32, usize>>,
+}
+
+impl XrefTable {
+    /// Creates a table of the given entries and indexes them.
+    pub(crate) fn new(entries: Vec<XEntry>) -> Self {
+        let mut index = HashMap::with_capacity(entries.len());
+        for (position, entry) in entries.iter().enumerate() {
+            index.entry(entry.obj_num).or_insert(position);
+        }
+        XrefTable { entries: entries.into(), index: Arc::new(index) }
+    }
+
+    /// Creates a table of the entries with the entries of some object numbers replaced, an
+    /// object number the table doesn't list is added in object number order.
+    pub(crate) fn with_overrides(&self, overrides: &BTreeMap<u32, XEntry>) -> Self {
+        if overrides.is_empty() {
+            return self.clone();
+        }
+        let mut entries = self
+            .entries
+            .iter()
+            .map(|it| match overrides.get(&it.obj_num) {
+                // The override doesn't change the history of the object
+                Some(entry) => XEntry { revision: it.revision, ..entry.clone() },
+                None => it.clone(),
+            })
+            .collect::<Vec<_>>();
+        // The merged entries follow the sections, the insertion points need them sorted
+        entries.sort_by_key(|it| it.obj_num);
+        for entry in overrides.values().filter(|it| self.position(it.obj_num).is_none()) {
+            let position = entries.partition_point(|it| it.obj_num < entry.obj_num);
+            entries.insert(position, entry.clone());
+        }
+        XrefTable::new(entries)
+    }
+
+    /// Returns the position of the entry of an object number.
+    pub(crate) fn position(&self, obj_num: u32) -> Option<usize> {
+        self.index.get(&obj_num).copied()
+    }
+
+    /// Returns the entry of an object number, whatever its generation.
+    pub(crate) fn find(&self, obj_num: u32) -> Option<&XEntry> {
+        self.position(obj_num).map(|position| &self.entries[position])
+    }
+
+    /// Searches for the entry of an object reference.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - A tuple containing the object number and generation number to search for
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&XEntry)` - A reference to the matching XRef entry if found
+    /// * `Err(Error)` - An error if no matching entry is found
+    ///
+    /// # Errors
+    ///
+    /// Returns an XrefEntryNotFound error if no XRef entry has the object number, or a
+    /// GenerationMismatch error if the entry of the object number has another generation.
+    pub(crate) fn search(&self, obj_ref: ObjRefTuple) -> Result<&XEntry> {
+        match self.find(obj_ref.0) {
+            Some(entry) if entry.gen_num == obj_ref.1 => Ok(entry),
+            // The slot was reused by a newer generation
+            Some(entry) => Err(PDFError::GenerationMismatch {
+                requested: obj_ref,
+                found: (entry.obj_num, entry.gen_num),
+            }),
+            None => Err(PDFError::XrefEntryNotFound(obj_ref.0, obj_ref.1)),
+        }
+    }
+}
+
+impl Deref for XrefTable {
+    type Target = [XEntry];
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
 }
 
 impl Stream {
@@ -435,13 +901,28 @@ impl Stream {
     /// # Arguments
     ///
     /// * `metadata` - A dictionary containing stream metadata
-    /// * `buf` - The byte buffer containing the stream data
+    /// * `buf` - The byte buffer containing the raw, still encoded, stream data
     ///
     /// # Returns
     ///
     /// A new `Stream` instance
-    pub(crate) fn new(metadata: Dictionary,buf:Vec<u8>) -> Self {
-        Stream { buf, metadata }
+    pub fn new(metadata: Dictionary,buf:Vec<u8>) -> Self {
+        Stream { buf, metadata, raw_range: None }
+    }
+
+    /// Records the bytes the data occupies in the parsed sequence.
+    pub(crate) fn with_raw_range(mut self, raw_range: Range<u64>) -> Self {
+        self.raw_range = Some(raw_range);
+        self
+    }
+
+    /// Returns the bytes the data occupies in the file, between `stream` and `endstream`.
+    ///
+    /// The range is known for streams parsed from a document, also when the data was
+    /// skipped, and `None` for streams built in memory. Streams parsed by an
+    /// [`ObjectReader`](crate::ObjectReader) report the range within its bytes.
+    pub fn raw_range(&self) -> Option<Range<u64>> {
+        self.raw_range.clone()
     }
 
     /// Returns a slice reference to the stream's byte buffer.
@@ -449,25 +930,109 @@ impl Stream {
     /// # Returns
     ///
     /// A slice reference to the internal byte buffer
-    pub(crate) fn as_slice(&self) -> &[u8] {
+    pub fn as_slice(&self) -> &[u8] {
         &self.buf
     }
 
+    /// Returns the stream dictionary.
+    pub fn get_metadata(&self) -> &Dictionary {
+        &self.metadata
+    }
+
+    /// Returns a mutable reference to the stream dictionary.
+    pub fn get_metadata_mut(&mut self) -> &mut Dictionary {
+        &mut self.metadata
+    }
+
+    /// Returns the stream dictionary, same as [`Stream::get_metadata`].
+    pub fn dict(&self) -> &Dictionary {
+        &self.metadata
+    }
+
+    /// Returns the `/Length` of the stream if it is a direct number.
+    pub fn declared_length(&self) -> Option<u64> {
+        self.metadata.get_u64_num(LENGTH)
+    }
 
-    pub(crate) fn get_filters(&self) -> Vec<String> {
+    /// Returns the `/Length` of the stream, reading it from the document if it is indirect.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the stream belongs to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the length, `None` if it is missing or not an unsigned number,
+    /// or an error if the referenced object can't be read
+    pub fn declared_length_in(&self, document: &mut PDFDocument) -> Result<Option<u64>> {
+        match self.metadata.get(LENGTH) {
+            Some(PDFObject::ObjectRef(obj_num, gen_num)) => {
+                match document.read_object_with_ref((*obj_num, *gen_num))? {
+                    Some(PDFObject::IndirectObject(_, _, value)) => match *value {
+                        PDFObject::Number(PDFNumber::Unsigned(length)) => Ok(Some(length)),
+                        _ => Ok(None),
+                    },
+                    _ => Ok(None),
+                }
+            }
+            _ => Ok(self.declared_length()),
+        }
+    }
+
+    /// Returns the names of the filters in the order of the `/Filter` array, a single
+    /// `/Filter` name becomes a one-element vector.
+    pub fn filters(&self) -> Vec<String> {
         match self.metadata.get(FILTER){
             Some(PDFObject::Array(arr)) => {
                 arr.iter()
-                    .filter_map(|it| it.as_name())
-                    .map(|it| it.clone())
+                    .filter_map(|it| it.as_name()).map(|it| it.to_string())
                     .collect()
             }
             Some(PDFObject::Named(name)) => {
-                vec![name.clone()]
+                vec![name.to_string()]
             }
             _ => vec![]
         }
-    } 
+    }
+
+    /// Returns the decode parameters of each filter, aligned with [`Stream::filters`].
+    ///
+    /// Filters without parameters, whether omitted or `null`, get `None`.
+    pub fn decode_parms(&self) -> Vec<Option<&Dictionary>> {
+        let count = self.filters().len();
+        let mut parms = match self.metadata.get(DECODE_PARMS) {
+            Some(PDFObject::Array(arr)) => arr.iter().map(|it| it.as_dict()).collect(),
+            Some(PDFObject::Dict(dict)) => vec![Some(dict)],
+            _ => vec![],
+        };
+        parms.resize(count, None);
+        parms
+    }
+
+    /// Returns true if the stream is an image XObject (`/Subtype /Image`).
+    pub fn is_image(&self) -> bool {
+        self.metadata.named_value_was(SUBTYPE, IMAGE)
+    }
+
+    /// Returns true if the stream is a form XObject (`/Subtype /Form`).
+    pub fn is_form_xobject(&self) -> bool {
+        self.metadata.named_value_was(SUBTYPE, FORM)
+    }
+
+    /// Returns true if the stream is a metadata stream (`/Type /Metadata`).
+    pub fn is_metadata(&self) -> bool {
+        self.metadata.named_value_was(TYPE, METADATA)
+    }
+
+    /// Returns true if the stream is an object stream (`/Type /ObjStm`).
+    pub fn is_object_stream(&self) -> bool {
+        self.metadata.named_value_was(TYPE, OBJ_STM)
+    }
+
+    /// Returns true if the stream is a cross-reference stream (`/Type /XRef`).
+    pub fn is_xref_stream(&self) -> bool {
+        self.metadata.named_value_was(TYPE, XREF_STREAM)
+    }
 }
 
 impl PDFString {
@@ -485,6 +1050,26 @@ impl PDFString {
         PDFString { kind, buf }
     }
 
+    /// Creates a literal string, written as `(...)`.
+    pub fn literal(buf: impl Into<Vec<u8>>) -> Self {
+        Self::new(PDFStrKind::Literal, buf.into())
+    }
+
+    /// Creates a hexadecimal string, written as `<...>`.
+    pub fn hexadecimal(buf: impl Into<Vec<u8>>) -> Self {
+        Self::new(PDFStrKind::Hexadecimal, buf.into())
+    }
+
+    /// Returns the bytes of the string.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns true if the string is written in hexadecimal form.
+    pub fn is_hexadecimal(&self) -> bool {
+        self.kind == PDFStrKind::Hexadecimal
+    }
+
     /// Returns a reference to the string's byte buffer.
     ///
     /// # Returns
@@ -494,27 +1079,163 @@ impl PDFString {
         &self.buf
     }
 
-    /// Returns the encoding kind of the string.
-    ///
-    /// # Returns
-    ///
-    /// A reference to the `PDFStrKind` indicating the encoding type
-    pub(crate) fn get_kind(&self) -> &PDFStrKind {
-        &self.kind
-    }
-
     /// Returns true if the string is in UTF-16BE encoding.
     ///
-    /// This checks if the string is hexadecimal encoded and starts with the
-    /// UTF-16BE byte order mark (BOM) 0xFE 0xFF.
+    /// This checks if the string starts with the UTF-16BE byte order mark (BOM) 0xFE 0xFF,
+    /// literal strings carry it as well, e.g. `(\376\377...)`.
     ///
     /// # Returns
     ///
     /// True if the string is UTF-16BE encoded, false otherwise
     pub(crate) fn is_utf16be(&self) -> bool {
-        if self.kind == PDFStrKind::Literal {
-            return false;
-        }
         self.buf.starts_with(b"\xFE\xFF")
     }
-}
\ No newline at end of file
+}
+/// Builds a [`Dictionary`] entry by entry, e.g.
+///
+/// ```
+/// use pdf_rs::objects::DictBuilder;
+///
+/// let info = DictBuilder::new()
+///     .string("Title", "Report")
+///     .name("Trapped", "False")
+///     .build();
+/// assert_eq!(info.len(), 2);
+/// ```
+#[derive(Default)]
+pub struct DictBuilder {
+    dict: Dictionary,
+}
+
+/// Builds an array element by element, see [`DictBuilder`].
+#[derive(Default)]
+pub struct ArrayBuilder {
+    elements: Vec<PDFObject>,
+}
+
+impl DictBuilder {
+    /// Creates a builder of an empty dictionary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entry, replacing an earlier entry with the same key.
+    pub fn entry(mut self, key: impl Into<Name>, value: impl Into<PDFObject>) -> Self {
+        self.dict.insert(key, value);
+        self
+    }
+
+    /// Adds an entry whose value is a name.
+    pub fn name(self, key: impl Into<Name>, name: impl Into<Name>) -> Self {
+        self.entry(key, PDFObject::Named(name.into()))
+    }
+
+    /// Adds an entry whose value is a literal string.
+    pub fn string(self, key: impl Into<Name>, value: impl Into<Vec<u8>>) -> Self {
+        self.entry(key, PDFString::literal(value))
+    }
+
+    /// Adds an entry whose value is a reference to an indirect object.
+    pub fn reference(self, key: impl Into<Name>, obj_ref: impl Into<ObjectId>) -> Self {
+        let obj_ref = obj_ref.into();
+        self.entry(key, PDFObject::ObjectRef(obj_ref.number, obj_ref.generation))
+    }
+
+    /// Returns the built dictionary.
+    pub fn build(self) -> Dictionary {
+        self.dict
+    }
+}
+
+impl ArrayBuilder {
+    /// Creates a builder of an empty array.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an element.
+    pub fn push(mut self, value: impl Into<PDFObject>) -> Self {
+        self.elements.push(value.into());
+        self
+    }
+
+    /// Appends a name.
+    pub fn name(self, name: impl Into<Name>) -> Self {
+        self.push(PDFObject::Named(name.into()))
+    }
+
+    /// Appends a reference to an indirect object.
+    pub fn reference(self, obj_ref: impl Into<ObjectId>) -> Self {
+        let obj_ref = obj_ref.into();
+        self.push(PDFObject::ObjectRef(obj_ref.number, obj_ref.generation))
+    }
+
+    /// Returns the built array.
+    pub fn build(self) -> PDFObject {
+        PDFObject::Array(self.elements)
+    }
+}
+
+impl From<bool> for PDFObject {
+    fn from(value: bool) -> Self {
+        PDFObject::Bool(value)
+    }
+}
+
+/// Non-negative integers become [`PDFNumber::Unsigned`] like parsed ones.
+impl From<i64> for PDFObject {
+    fn from(value: i64) -> Self {
+        match u64::try_from(value) {
+            Ok(value) => PDFObject::Number(PDFNumber::Unsigned(value)),
+            Err(_) => PDFObject::Number(PDFNumber::Signed(value)),
+        }
+    }
+}
+
+impl From<i32> for PDFObject {
+    fn from(value: i32) -> Self {
+        PDFObject::from(value as i64)
+    }
+}
+
+impl From<u64> for PDFObject {
+    fn from(value: u64) -> Self {
+        PDFObject::Number(PDFNumber::Unsigned(value))
+    }
+}
+
+impl From<f64> for PDFObject {
+    fn from(value: f64) -> Self {
+        PDFObject::Number(PDFNumber::Real(value))
+    }
+}
+
+impl From<PDFNumber> for PDFObject {
+    fn from(value: PDFNumber) -> Self {
+        PDFObject::Number(value)
+    }
+}
+
+impl From<PDFString> for PDFObject {
+    fn from(value: PDFString) -> Self {
+        PDFObject::String(value)
+    }
+}
+
+impl From<Vec<PDFObject>> for PDFObject {
+    fn from(value: Vec<PDFObject>) -> Self {
+        PDFObject::Array(value)
+    }
+}
+
+impl From<Dictionary> for PDFObject {
+    fn from(value: Dictionary) -> Self {
+        PDFObject::Dict(value)
+    }
+}
+
+impl From<Stream> for PDFObject {
+    fn from(value: Stream) -> Self {
+        PDFObject::Stream(value)
+    }
+}
@@ -0,0 +1,311 @@
+use crate::constants::{FIRST, N};
+use crate::document::PDFDocument;
+use crate::error::PDFError::{CircularReference, PDFParseError0, XrefEntryNotFound};
+use crate::error::Result;
+use crate::objects::{ObjRefTuple, PDFNumber, PDFObject, Stream};
+use crate::parser::{ObjectReader, parse_with_offset};
+use crate::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+/// An object stream (`/Type /ObjStm`) and the objects it contains, as listed by its header.
+#[derive(Clone, Debug)]
+pub struct ObjectStream {
+    obj_ref: ObjRefTuple,
+    /// The declared number of objects.
+    n: u32,
+    /// The offset of the first object within the decoded data.
+    first: u64,
+    /// The object number and offset, relative to `first`, of each contained object.
+    entries: Vec<(u32, u64)>,
+    /// The indexes of the entries whose offset is out of order or beyond the data.
+    skipped: Vec<usize>,
+}
+
+impl ObjectStream {
+    /// Returns the reference of the object stream itself.
+    pub fn get_obj_ref(&self) -> ObjRefTuple {
+        self.obj_ref
+    }
+
+    /// Returns the declared number of contained objects (`/N`).
+    pub fn get_n(&self) -> u32 {
+        self.n
+    }
+
+    /// Returns the offset of the first object within the decoded data (`/First`).
+    pub fn get_first(&self) -> u64 {
+        self.first
+    }
+
+    /// Returns the object number and offset, relative to `/First`, of each contained object
+    /// in header order.
+    pub fn get_entries(&self) -> &[(u32, u64)] {
+        &self.entries
+    }
+
+    /// Returns the index of an object within the stream, as used by type 2 xref entries.
+    pub fn index_of(&self, obj_num: u32) -> Option<usize> {
+        self.entries.iter().position(|it| it.0 == obj_num)
+    }
+
+    /// Returns the indexes of the entries which are skipped because their offset is
+    /// smaller than the offset of a preceding entry or lies beyond the decoded data.
+    pub fn get_skipped(&self) -> &[usize] {
+        &self.skipped
+    }
+
+    /// Returns the warnings describing the skipped entries.
+    fn skipped_messages(&self) -> impl Iterator<Item = String> + '_ {
+        self.skipped.iter().map(|index| {
+            let (obj_num, offset) = self.entries[*index];
+            format!(
+                "Object stream {} {} entry {} (object {}) has invalid offset {}, skipped",
+                self.obj_ref.0, self.obj_ref.1, index, obj_num, offset
+            )
+        })
+    }
+
+    /// Returns the valid entries with the end of their data, i.e. the offset of the next
+    /// valid entry or the end of the decoded data.
+    fn valid_ranges(&self, data_len: u64) -> Vec<(u32, u64, u64)> {
+        (0..self.entries.len())
+            .filter_map(|index| {
+                let (start, end) = self.range_of(index, data_len)?;
+                Some((self.entries[index].0, start, end))
+            })
+            .collect()
+    }
+
+    /// Returns the start and end of the data of an entry within the decoded data, None
+    /// if the entry doesn't exist or is skipped.
+    fn range_of(&self, index: usize, data_len: u64) -> Option<(u64, u64)> {
+        if self.skipped.contains(&index) {
+            return None;
+        }
+        let (_, offset) = *self.entries.get(index)?;
+        let end = (index + 1..self.entries.len())
+            .find(|next| !self.skipped.contains(next))
+            .map_or(data_len, |next| self.first + self.entries[next].1);
+        Some((self.first + offset, end))
+    }
+}
+
+/// Parses the header of an object stream, i.e. the `/N` pairs of object number and offset
+/// preceding the first object.
+///
+/// # Arguments
+///
+/// * `obj_ref` - The reference of the object stream
+/// * `stream` - The object stream
+/// * `data` - The decoded data of the stream
+///
+/// # Returns
+///
+/// A `Result` containing the parsed header and the decoded data, or an error if `/N` or
+/// `/First` is missing or the header doesn't hold exactly `/N` pairs
+pub(crate) fn parse_object_stream_header(obj_ref: ObjRefTuple, stream: &Stream, data: Vec<u8>) -> Result<(ObjectStream, Vec<u8>)> {
+    let dict = stream.get_metadata();
+    let missing = |key: &str| PDFParseError0(format!("Object stream {} {} has no /{}", obj_ref.0, obj_ref.1, key));
+    let n = dict.get_u64_num(N).ok_or_else(|| missing(N))?;
+    let first = dict.get_u64_num(FIRST).ok_or_else(|| missing(FIRST))?;
+    let n = u32::try_from(n).map_err(|_| PDFParseError0(format!("Object stream /N {} is out of range", n)))?;
+    let header = &data[..(first as usize).min(data.len())];
+    let mut reader = ObjectReader::from_bytes(header);
+    let mut next_u64 = || match reader.next_object()? {
+        Some(PDFObject::Number(PDFNumber::Unsigned(value))) => Ok(Some(value)),
+        None => Ok(None),
+        _ => Err(PDFParseError0(format!("Object stream {} {} has a malformed header", obj_ref.0, obj_ref.1))),
+    };
+    let mut numbers = Vec::new();
+    while let Some(number) = next_u64()? {
+        numbers.push(number);
+    }
+    if numbers.len() != n as usize * 2 {
+        return Err(PDFParseError0(format!(
+            "Object stream {} {} declares /N {} but its header holds {} pairs",
+            obj_ref.0, obj_ref.1, n, numbers.len() / 2
+        )));
+    }
+    let mut entries = Vec::with_capacity(n as usize);
+    let mut skipped = Vec::new();
+    let mut last_offset = None;
+    for (index, pair) in numbers.chunks(2).enumerate() {
+        let obj_num = u32::try_from(pair[0])
+            .map_err(|_| PDFParseError0(format!("Object number out of range:{}", pair[0])))?;
+        let offset = pair[1];
+        // Offsets must ascend, otherwise the data of an object can't be delimited
+        let in_order = last_offset.is_none_or(|last| offset > last);
+        if in_order && first.saturating_add(offset) < data.len() as u64 {
+            last_offset = Some(offset);
+        } else {
+            skipped.push(index);
+        }
+        entries.push((obj_num, offset));
+    }
+    Ok((ObjectStream { obj_ref, n, first, entries, skipped }, data))
+}
+
+/// Reads the object stream with the given number from the xref table of the tokenizer and
+/// parses its header.
+///
+/// The stream data is read even while the tokenizer skips stream data. Entries with an
+/// invalid offset are recorded as warnings.
+fn load_object_stream(tokenizer: &mut Tokenizer, obj_num: u32) -> Result<(ObjectStream, Vec<u8>)> {
+    let entry = match tokenizer.get_xrefs().find(obj_num) {
+        Some(entry) if entry.is_using() => entry.clone(),
+        _ => return Err(XrefEntryNotFound(obj_num, 0)),
+    };
+    let obj_ref = (obj_num, entry.gen_num);
+    if entry.is_compressed() {
+        return Err(PDFParseError0(format!("Object stream {} {} is itself stored in an object stream", obj_num, entry.gen_num)));
+    }
+    // A compressed /Length of the object stream itself
+    if tokenizer.is_in_flight(obj_ref) {
+        return Err(CircularReference { object: obj_ref.into() });
+    }
+    let skip_stream_data = tokenizer.is_skip_stream_data();
+    tokenizer.set_skip_stream_data(false);
+    let object = parse_with_offset(tokenizer, entry.value);
+    tokenizer.set_skip_stream_data(skip_stream_data);
+    let stream = match object? {
+        PDFObject::IndirectObject(_, _, object) => object.into_stream(),
+        _ => None,
+    };
+    let stream = match stream {
+        Some(stream) if stream.is_object_stream() => stream,
+        _ => return Err(PDFParseError0(format!("Object {} {} is not an object stream", obj_ref.0, obj_ref.1))),
+    };
+    let data = tokenizer.decode_stream(&stream)?;
+    let (header, data) = parse_object_stream_header(obj_ref, &stream, data)?;
+    for message in header.skipped_messages() {
+        tokenizer.warn(entry.value, message);
+    }
+    Ok((header, data))
+}
+
+/// Reads an object stored in an object stream, as listed by a compressed xref entry.
+///
+/// The object stream is decoded on first use and kept by the tokenizer, so reading more
+/// of its objects doesn't decode it again.
+///
+/// # Arguments
+///
+/// * `tokenizer` - The tokenizer holding the xref table
+/// * `object_stream` - The number of the object stream
+/// * `index` - The index of the object within the stream
+/// * `obj_num` - The object number the xref entry lists
+///
+/// # Returns
+///
+/// A `Result` containing the object as an indirect object of generation 0, or an error if
+/// the object stream can't be read or doesn't hold the object at the index
+pub(crate) fn read_compressed_object(tokenizer: &mut Tokenizer, object_stream: u32, index: u64, obj_num: u32) -> Result<PDFObject> {
+    tokenizer.charge_object()?;
+    let decoded = match tokenizer.get_object_stream(object_stream) {
+        Some(decoded) => decoded,
+        None => {
+            let decoded = Arc::new(load_object_stream(tokenizer, object_stream)?);
+            tokenizer.cache_object_stream(object_stream, decoded.clone());
+            decoded
+        }
+    };
+    let (header, data) = &*decoded;
+    let missing = || PDFParseError0(format!("Object stream {} holds no object {} at index {}", object_stream, obj_num, index));
+    let index = usize::try_from(index).map_err(|_| missing())?;
+    let range = match header.entries.get(index) {
+        Some((listed, _)) if *listed == obj_num => header.range_of(index, data.len() as u64),
+        _ => None,
+    };
+    let (start, end) = range.ok_or_else(missing)?;
+    let object = ObjectReader::from_bytes(&data[start as usize..end as usize]).next_object()?.ok_or_else(missing)?;
+    Ok(PDFObject::IndirectObject(obj_num, 0, Box::new(object)))
+}
+
+/// Finds the object streams of the xref table and parses their headers.
+///
+/// Object streams are found by the `/Type /ObjStm` of their dictionary before their data is
+/// read. An unparsable object doesn't count as an object stream, while an object stream
+/// entry with an invalid offset is recorded as a warning.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// A `Result` containing the object streams in xref order,
+/// or an error if the header of an object stream is malformed
+pub(crate) fn collect_object_streams(document: &mut PDFDocument) -> Result<Vec<ObjectStream>> {
+    let mut refs = Vec::new();
+    document.with_skipped_stream_data(|document| {
+        for index in 0..document.get_xref_slice().len() {
+            if document.get_xref_slice()[index].is_freed() {
+                continue;
+            }
+            if let Ok(Some((entry, PDFObject::IndirectObject(_, _, object)))) = document.read_entry(index)
+                && object.as_stream().is_some_and(|it| it.is_object_stream())
+            {
+                refs.push((entry.get_obj_num(), entry.get_gen_num()));
+            }
+        }
+    });
+    let mut streams = Vec::with_capacity(refs.len());
+    for obj_ref in refs {
+        if let Some(PDFObject::IndirectObject(_, _, object)) = document.read_object_with_ref(obj_ref)?
+            && let PDFObject::Stream(stream) = *object
+        {
+            let data = document.decode_stream(&stream)?;
+            let (header, _) = parse_object_stream_header(obj_ref, &stream, data)?;
+            for message in header.skipped_messages() {
+                document.warn_about(obj_ref, message);
+            }
+            streams.push(header);
+        }
+    }
+    Ok(streams)
+}
+
+/// Reads the objects contained in an object stream.
+///
+/// Entries with an invalid offset and objects which can't be parsed are skipped with a
+/// warning, so one corrupt object doesn't hide the others. Each object is parsed from its
+/// offset up to the offset of the next valid entry.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `obj_ref` - The reference of the object stream
+///
+/// # Returns
+///
+/// A `Result` containing the object numbers and objects in header order, or an error if
+/// the object isn't an object stream or its header is malformed
+pub(crate) fn read_object_stream_objects(
+    document: &mut PDFDocument,
+    obj_ref: ObjRefTuple,
+) -> Result<Vec<(u32, PDFObject)>> {
+    let stream = match document.read_object_with_ref(obj_ref)? {
+        Some(PDFObject::IndirectObject(_, _, object)) => object.into_stream(),
+        _ => None,
+    };
+    let stream = match stream {
+        Some(stream) if stream.is_object_stream() => stream,
+        _ => return Err(PDFParseError0(format!("Object {} {} is not an object stream", obj_ref.0, obj_ref.1))),
+    };
+    let data = document.decode_stream(&stream)?;
+    let (header, data) = parse_object_stream_header(obj_ref, &stream, data)?;
+    for message in header.skipped_messages() {
+        document.warn_about(obj_ref, message);
+    }
+    let mut objects = Vec::new();
+    for (obj_num, start, end) in header.valid_ranges(data.len() as u64) {
+        let bytes = &data[start as usize..end as usize];
+        match ObjectReader::from_bytes(bytes).next_object() {
+            Ok(Some(object)) => objects.push((obj_num, object)),
+            Ok(None) | Err(_) => document.warn_about(obj_ref, format!(
+                "Object stream {} {} object {} can't be parsed, skipped", obj_ref.0, obj_ref.1, obj_num
+            )),
+        }
+    }
+    Ok(objects)
+}
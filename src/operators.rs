@@ -0,0 +1,132 @@
+use crate::catalog::NodeId;
+use crate::content::{ContentVisitor, Operation, visit_page_content};
+use crate::document::PDFDocument;
+use crate::error::PDFError::PageNotFound;
+use crate::error::Result;
+use crate::objects::PDFObject;
+use std::collections::HashMap;
+
+/// The prefix of the operators counted inside form XObjects when they aren't merged, see
+/// [`HistogramOptions::with_merge_forms`].
+const FORM_PREFIX: &str = "Form/";
+
+/// Options controlling how operators are counted, see [`PDFDocument::page_operator_histogram_with`].
+#[derive(Clone, Debug)]
+pub struct HistogramOptions {
+    /// Whether operators inside form XObjects count like those of the page content.
+    merge_forms: bool,
+}
+
+impl Default for HistogramOptions {
+    fn default() -> Self {
+        Self { merge_forms: true }
+    }
+}
+
+impl HistogramOptions {
+    /// Creates the default options, merging the operators of form XObjects into the page counts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the operators inside form XObjects are merged into the page counts.
+    ///
+    /// Unmerged operators are counted under their name prefixed by `Form/`, e.g. `Form/Tj`,
+    /// which no operator name can collide with as `/` delimits names.
+    ///
+    /// # Arguments
+    ///
+    /// * `merge_forms` - True to merge the operators of forms
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_merge_forms(mut self, merge_forms: bool) -> Self {
+        self.merge_forms = merge_forms;
+        self
+    }
+
+    /// Returns true if the operators of form XObjects are merged into the page counts.
+    pub fn is_merge_forms(&self) -> bool {
+        self.merge_forms
+    }
+}
+
+/// Counts the operators of a page content and of the forms it paints.
+struct HistogramVisitor<'a> {
+    options: &'a HistogramOptions,
+    counts: HashMap<String, u32>,
+    /// The number of enclosing forms.
+    depth: usize,
+}
+
+impl ContentVisitor for HistogramVisitor<'_> {
+    fn visit(&mut self, operation: &Operation) -> Result<()> {
+        let operator = match self.depth > 0 && !self.options.merge_forms {
+            true => format!("{}{}", FORM_PREFIX, operation.get_operator()),
+            false => operation.get_operator().to_string(),
+        };
+        *self.counts.entry(operator).or_default() += 1;
+        Ok(())
+    }
+
+    fn begin_form(&mut self, _document: &mut PDFDocument, _matrix: [f64; 6], _resources: Option<&PDFObject>) -> Result<()> {
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn end_form(&mut self) {
+        self.depth -= 1;
+    }
+}
+
+/// Counts the operators of a page, a form painted several times counts each time.
+fn page_histogram(document: &mut PDFDocument, page_id: NodeId, options: &HistogramOptions) -> Result<HashMap<String, u32>> {
+    let mut visitor = HistogramVisitor { options, counts: HashMap::new(), depth: 0 };
+    visit_page_content(document, page_id, &mut visitor)?;
+    Ok(visitor.counts)
+}
+
+/// Counts the operators of a page by name.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `page_index` - The index of the page in page order
+/// * `options` - The counting options
+///
+/// # Returns
+///
+/// A `Result` containing the count of each operator, or an error if the page is not found
+/// or its content can't be parsed
+pub(crate) fn collect_page_operator_histogram(
+    document: &mut PDFDocument,
+    page_index: usize,
+    options: &HistogramOptions,
+) -> Result<HashMap<String, u32>> {
+    match document.get_page_ids().get(page_index) {
+        Some(page_id) => page_histogram(document, *page_id, options),
+        None => Err(PageNotFound(format!("Page not found:{}", page_index))),
+    }
+}
+
+/// Sums the operator counts of every page.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `options` - The counting options
+///
+/// # Returns
+///
+/// A `Result` containing the count of each operator, or an error if the content of a page
+/// can't be parsed
+pub(crate) fn collect_operator_histogram(document: &mut PDFDocument, options: &HistogramOptions) -> Result<HashMap<String, u32>> {
+    let mut counts = HashMap::new();
+    for page_id in document.get_page_ids() {
+        for (operator, count) in page_histogram(document, page_id, options)? {
+            *counts.entry(operator).or_default() += count;
+        }
+    }
+    Ok(counts)
+}
@@ -0,0 +1,481 @@
+use crate::error::PDFError::Cancelled;
+use crate::error::Result;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default number of bytes scanned for `endstream` when a stream `/Length` is wrong.
+const DEFAULT_MAX_STREAM_SCAN: usize = 64 * 1024 * 1024;
+/// Default number of bytes scanned for the next object header after a corrupt object.
+const DEFAULT_MAX_RESYNC_SCAN: usize = 16 * 1024 * 1024;
+/// Default number of bytes at the end of a file searched for `startxref`.
+const DEFAULT_MAX_STARTXREF_SCAN: usize = 1024 * 1024;
+/// Default number of bytes all recovery scans of a document may read together.
+const DEFAULT_MAX_RECOVERY_SCAN: usize = 256 * 1024 * 1024;
+/// Default maximum length of a name, number or keyword token.
+const DEFAULT_MAX_TOKEN_LEN: usize = 4 * 1024 * 1024;
+/// Default maximum length of a string or inline image, which are legitimately large.
+const DEFAULT_MAX_STRING_LEN: usize = 256 * 1024 * 1024;
+/// Default maximum nesting of form XObjects followed while reading page content.
+const DEFAULT_MAX_FORM_DEPTH: usize = 32;
+
+/// A callback receiving the progress of opening a document, see [`ParseOptions::with_progress`].
+pub type ProgressCallback = dyn Fn(Phase, u64, u64) -> ControlFlow<()> + Send + Sync;
+
+/// The milestones reported while opening a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// The header was read, reported as `(1, 1)`.
+    Header,
+    /// A cross-reference section was merged, reported as `(sections merged, 0)` because
+    /// the number of sections is unknown up front.
+    Xref,
+    /// The catalog was read, reported as `(1, 1)`.
+    Catalog,
+    /// Pages of the page tree were built, reported as `(pages built, root /Count)` every
+    /// [`PROGRESS_PAGES`] pages and once the tree is complete.
+    PageTree,
+    /// The outline was built, reported as `(outline items, 0)`.
+    Outline,
+}
+
+/// A resource whose use is capped by the parse options, see
+/// [`crate::error::PDFError::ResourceLimitExceeded`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resource {
+    /// The indirect objects parsed, see [`ParseOptions::with_max_objects`].
+    Objects,
+    /// The bytes of all decoded streams, see [`ParseOptions::with_max_total_decoded_bytes`].
+    DecodedBytes,
+    /// The wall-clock time in milliseconds, see [`ParseOptions::with_time_budget`].
+    Time,
+}
+
+impl Display for Resource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Resource::Objects => write!(f, "objects"),
+            Resource::DecodedBytes => write!(f, "decoded bytes"),
+            Resource::Time => write!(f, "milliseconds"),
+        }
+    }
+}
+
+/// The number of pages built between two [`Phase::PageTree`] reports.
+pub const PROGRESS_PAGES: u64 = 64;
+
+/// Options controlling how tolerant the parser is to malformed documents.
+///
+/// In strict mode (the default) every malformation is an error. In lenient mode the
+/// parser tries to recover and records a [`Warning`] instead.
+#[derive(Clone)]
+pub struct ParseOptions {
+    /// Whether the parser recovers from malformations.
+    lenient: bool,
+    /// The maximum number of bytes scanned for `endstream` while recovering a stream.
+    max_stream_scan: usize,
+    /// The maximum number of bytes scanned for the next object header after a corrupt object.
+    max_resync_scan: usize,
+    /// The maximum number of bytes at the end of a file searched for `startxref`.
+    max_startxref_scan: usize,
+    /// The number of bytes all recovery scans may read together.
+    max_recovery_scan: usize,
+    /// The maximum length of a name, number or keyword token.
+    max_token_len: usize,
+    /// The maximum length of a string or inline image.
+    max_string_len: usize,
+    /// The maximum nesting of form XObjects followed while reading page content.
+    max_form_depth: usize,
+    /// Whether outline `/Count` values are checked against the outline tree.
+    check_outline_counts: bool,
+    /// The maximum number of indirect objects parsed for a document.
+    max_objects: Option<u64>,
+    /// The maximum number of bytes decoded from all streams of a document.
+    max_total_decoded_bytes: Option<u64>,
+    /// The wall-clock time a document may take from being opened.
+    time_budget: Option<Duration>,
+    /// The callback receiving progress while opening a document.
+    on_progress: Option<Arc<ProgressCallback>>,
+}
+
+/// The kind of a [`Warning`], for callers which react to specific malformations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WarningCode {
+    /// A malformation without a dedicated code, described by the message.
+    #[default]
+    Other,
+    /// A content stream operation which can't be parsed was skipped.
+    MalformedOperation,
+    /// A `Q` without a matching `q` was ignored.
+    GraphicsStackUnderflow,
+    /// A `BT` inside a text object was treated as `ET BT`.
+    NestedTextObject,
+    /// A text object without `ET` was closed at the end of the content.
+    UnterminatedTextObject,
+    /// A form XObject painting itself or nested too deeply was skipped.
+    FormRecursion,
+    /// An outline item declares a `/Count` different from its visible descendants.
+    OutlineCount,
+    /// A feature the crate doesn't implement, e.g. decryption, was ignored.
+    Unsupported,
+}
+
+/// A malformation the parser tolerated, mostly by recovering from it in lenient mode.
+#[derive(Clone, Debug)]
+pub struct Warning {
+    /// The byte offset the warning is about.
+    offset: u64,
+    /// The kind of the warning.
+    code: WarningCode,
+    /// The human-readable description of the warning.
+    message: String,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+impl ParseOptions {
+    /// Creates options failing on every malformation.
+    pub fn strict() -> Self {
+        Self {
+            lenient: false,
+            max_stream_scan: DEFAULT_MAX_STREAM_SCAN,
+            max_resync_scan: DEFAULT_MAX_RESYNC_SCAN,
+            max_startxref_scan: DEFAULT_MAX_STARTXREF_SCAN,
+            max_recovery_scan: DEFAULT_MAX_RECOVERY_SCAN,
+            max_token_len: DEFAULT_MAX_TOKEN_LEN,
+            max_string_len: DEFAULT_MAX_STRING_LEN,
+            max_form_depth: DEFAULT_MAX_FORM_DEPTH,
+            check_outline_counts: false,
+            max_objects: None,
+            max_total_decoded_bytes: None,
+            time_budget: None,
+            on_progress: None,
+        }
+    }
+
+    /// Creates options recovering from malformations where possible.
+    pub fn lenient() -> Self {
+        Self {
+            lenient: true,
+            ..Self::strict()
+        }
+    }
+
+    /// Sets the maximum number of bytes scanned for `endstream` while recovering a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_stream_scan` - The maximum number of bytes
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_stream_scan(mut self, max_stream_scan: usize) -> Self {
+        self.max_stream_scan = max_stream_scan;
+        self
+    }
+
+    /// Sets the maximum number of bytes scanned for the next object header after a corrupt object.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_resync_scan` - The maximum number of bytes
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_resync_scan(mut self, max_resync_scan: usize) -> Self {
+        self.max_resync_scan = max_resync_scan;
+        self
+    }
+
+    /// Sets the maximum number of bytes at the end of a file searched for `startxref`.
+    ///
+    /// The search starts with the last kilobyte and widens eightfold until the keyword is
+    /// found, so trailing comments or data appended after `%%EOF` don't keep a file from
+    /// opening. At least the last kilobyte is always searched.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_startxref_scan` - The maximum number of bytes
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_startxref_scan(mut self, max_startxref_scan: usize) -> Self {
+        self.max_startxref_scan = max_startxref_scan;
+        self
+    }
+
+    /// Sets the number of bytes all recovery scans may read together.
+    ///
+    /// The `endstream` and object header scans are charged against this budget, once it
+    /// is exhausted recovery stops and the original error is returned. This keeps the work
+    /// on pathological files linear in the budget.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_recovery_scan` - The maximum number of bytes
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_recovery_scan(mut self, max_recovery_scan: usize) -> Self {
+        self.max_recovery_scan = max_recovery_scan;
+        self
+    }
+
+    /// Sets the maximum length of a name, number or keyword token.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_token_len` - The maximum number of bytes
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_token_len(mut self, max_token_len: usize) -> Self {
+        self.max_token_len = max_token_len;
+        self
+    }
+
+    /// Sets the maximum length of a string or inline image.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_string_len` - The maximum number of bytes
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = max_string_len;
+        self
+    }
+
+    /// Sets the maximum nesting of form XObjects followed while reading page content.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_form_depth` - The maximum number of nested forms
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_form_depth(mut self, max_form_depth: usize) -> Self {
+        self.max_form_depth = max_form_depth;
+        self
+    }
+
+    /// Sets whether a warning is recorded for every outline item whose `/Count` differs
+    /// from the one computed from the outline tree, see
+    /// [`crate::catalog::OutlineTreeArean::count_mismatches`].
+    ///
+    /// # Arguments
+    ///
+    /// * `check_outline_counts` - True to check the counts while opening
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_check_outline_counts(mut self, check_outline_counts: bool) -> Self {
+        self.check_outline_counts = check_outline_counts;
+        self
+    }
+
+    /// Sets the maximum number of indirect objects parsed for a document, unlimited by
+    /// default.
+    ///
+    /// Every object read counts, also when it is read again. Opening and every later
+    /// operation on the document share the limit, crossing it fails with
+    /// `ResourceLimitExceeded`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_objects` - The maximum number of objects
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_objects(mut self, max_objects: u64) -> Self {
+        self.max_objects = Some(max_objects);
+        self
+    }
+
+    /// Sets the maximum number of bytes decoded from all streams of a document together,
+    /// unlimited by default.
+    ///
+    /// Every decoding counts, also when a stream is decoded again. Crossing the limit fails
+    /// with `ResourceLimitExceeded`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_total_decoded_bytes` - The maximum number of bytes
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_max_total_decoded_bytes(mut self, max_total_decoded_bytes: u64) -> Self {
+        self.max_total_decoded_bytes = Some(max_total_decoded_bytes);
+        self
+    }
+
+    /// Sets the wall-clock time a document may take, unlimited by default.
+    ///
+    /// The deadline starts when the document is opened and is checked per object parsed,
+    /// per stream decoded and per page whose content is read, so a single long step can
+    /// overrun it. Passing the deadline fails with `ResourceLimitExceeded`.
+    ///
+    /// # Arguments
+    ///
+    /// * `time_budget` - The time from opening the document to the deadline
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+
+    /// Sets a callback receiving the progress of opening a document.
+    ///
+    /// The callback is invoked at coarse milestones with the [`Phase`] and a
+    /// `(done, total)` pair, `total` is 0 where it isn't known. Returning
+    /// `ControlFlow::Break` aborts opening with a `Cancelled` error. A panic in the callback
+    /// is not caught and unwinds through the caller of `open`.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_progress` - The callback
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_progress(
+        mut self,
+        on_progress: impl Fn(Phase, u64, u64) -> ControlFlow<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Reports progress to the callback, if any.
+    ///
+    /// # Returns
+    ///
+    /// A `Cancelled` error if the callback asks to stop
+    pub(crate) fn report(&self, phase: Phase, done: u64, total: u64) -> Result<()> {
+        match &self.on_progress {
+            Some(on_progress) if on_progress(phase, done, total).is_break() => Err(Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns true if the parser recovers from malformations.
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Returns the maximum number of bytes scanned for `endstream` while recovering a stream.
+    pub fn get_max_stream_scan(&self) -> usize {
+        self.max_stream_scan
+    }
+
+    /// Returns the maximum number of bytes scanned for the next object header after a corrupt object.
+    pub fn get_max_resync_scan(&self) -> usize {
+        self.max_resync_scan
+    }
+
+    /// Returns the maximum number of bytes at the end of a file searched for `startxref`.
+    pub fn get_max_startxref_scan(&self) -> usize {
+        self.max_startxref_scan
+    }
+
+    /// Returns the number of bytes all recovery scans may read together.
+    pub fn get_max_recovery_scan(&self) -> usize {
+        self.max_recovery_scan
+    }
+
+    /// Returns the maximum length of a name, number or keyword token.
+    pub fn get_max_token_len(&self) -> usize {
+        self.max_token_len
+    }
+
+    /// Returns the maximum length of a string or inline image.
+    pub fn get_max_string_len(&self) -> usize {
+        self.max_string_len
+    }
+
+    /// Returns the maximum nesting of form XObjects followed while reading page content.
+    pub fn get_max_form_depth(&self) -> usize {
+        self.max_form_depth
+    }
+
+    /// Returns true if outline `/Count` values are checked while opening a document.
+    pub fn is_check_outline_counts(&self) -> bool {
+        self.check_outline_counts
+    }
+
+    /// Returns the maximum number of indirect objects parsed for a document, if limited.
+    pub fn get_max_objects(&self) -> Option<u64> {
+        self.max_objects
+    }
+
+    /// Returns the maximum number of bytes decoded from all streams of a document, if limited.
+    pub fn get_max_total_decoded_bytes(&self) -> Option<u64> {
+        self.max_total_decoded_bytes
+    }
+
+    /// Returns the wall-clock time a document may take from being opened, if limited.
+    pub fn get_time_budget(&self) -> Option<Duration> {
+        self.time_budget
+    }
+}
+
+impl Debug for ParseOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("lenient", &self.lenient)
+            .field("max_stream_scan", &self.max_stream_scan)
+            .field("max_resync_scan", &self.max_resync_scan)
+            .field("max_startxref_scan", &self.max_startxref_scan)
+            .field("max_recovery_scan", &self.max_recovery_scan)
+            .field("max_token_len", &self.max_token_len)
+            .field("max_string_len", &self.max_string_len)
+            .field("max_form_depth", &self.max_form_depth)
+            .field("check_outline_counts", &self.check_outline_counts)
+            .field("max_objects", &self.max_objects)
+            .field("max_total_decoded_bytes", &self.max_total_decoded_bytes)
+            .field("time_budget", &self.time_budget)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl Warning {
+    pub(crate) fn new(offset: u64, code: WarningCode, message: String) -> Self {
+        Self { offset, code, message }
+    }
+
+    /// Returns the byte offset the warning is about.
+    pub fn get_offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the kind of the warning.
+    pub fn get_code(&self) -> WarningCode {
+        self.code
+    }
+
+    /// Returns the human-readable description of the warning.
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
@@ -0,0 +1,368 @@
+use crate::catalog::{NodeId, PageNode};
+use crate::constants::{CROP_BOX, MEDIA_BOX, RESOURCES, ROTATE};
+use crate::content::Operation;
+use crate::objects::{Dictionary, PDFObject, Rect};
+use crate::document::PDFDocument;
+use crate::error::PDFError::{ObjectAttrMiss, PageNotFound};
+use crate::error::Result;
+use crate::text::{
+    CoordinateSpace, TextExtractionOptions, TextFragment, apply_bidi, apply_invisible_text, extract_fragments, fragments_to_text,
+};
+use crate::trace::span;
+
+/// A page of a document, a lightweight handle which is passed the document to read from.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pdf_rs::document::PDFDocument;
+/// use pdf_rs::page::Page;
+///
+/// let mut document = PDFDocument::open("document/pdfreference1.0.pdf".into())?;
+/// let page = Page::new(document.get_page_ids()[0]);
+/// println!("{}", page.text(&mut document)?);
+/// # Ok::<(), pdf_rs::error::PDFError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Page {
+    page_id: NodeId,
+}
+
+/// A page seen while iterating [`PDFDocument::pages`], with its index in page order and
+/// its page tree node.
+#[derive(Clone, Copy)]
+pub struct PageRef<'a> {
+    index: usize,
+    node: &'a PageNode,
+}
+
+/// Converts points between user space and the spaces derived from the MediaBox of a page.
+///
+/// Points are `(x, y)` pairs. The MediaBox may have a non-zero origin, e.g. `[20 20 620 812]`,
+/// so user space and MediaBox-relative coordinates differ by its lower left corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PageTransform {
+    /// The MediaBox as `[llx lly urx ury]` with the corners ordered.
+    media_box: [f64; 4],
+}
+
+impl PageTransform {
+    /// Creates a transform for the given MediaBox, the corners may be in any order.
+    pub fn new(media_box: [f64; 4]) -> Self {
+        let [x0, y0, x1, y1] = media_box;
+        PageTransform { media_box: [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)] }
+    }
+
+    /// Returns the MediaBox as `[llx lly urx ury]`.
+    pub fn get_media_box(&self) -> [f64; 4] {
+        self.media_box
+    }
+
+    /// Returns the width of the MediaBox.
+    pub fn get_width(&self) -> f64 {
+        self.media_box[2] - self.media_box[0]
+    }
+
+    /// Returns the height of the MediaBox.
+    pub fn get_height(&self) -> f64 {
+        self.media_box[3] - self.media_box[1]
+    }
+
+    /// Converts a user space point into MediaBox-relative coordinates.
+    pub fn to_media_relative(&self, point: (f64, f64)) -> (f64, f64) {
+        (point.0 - self.media_box[0], point.1 - self.media_box[1])
+    }
+
+    /// Converts a MediaBox-relative point into user space.
+    pub fn from_media_relative(&self, point: (f64, f64)) -> (f64, f64) {
+        (point.0 + self.media_box[0], point.1 + self.media_box[1])
+    }
+
+    /// Converts a user space point into fractions of the MediaBox size.
+    ///
+    /// A degenerate MediaBox without width or height yields non-finite values.
+    pub fn to_normalized(&self, point: (f64, f64)) -> (f64, f64) {
+        let (x, y) = self.to_media_relative(point);
+        (x / self.get_width(), y / self.get_height())
+    }
+
+    /// Converts fractions of the MediaBox size into a user space point.
+    pub fn from_normalized(&self, point: (f64, f64)) -> (f64, f64) {
+        self.from_media_relative((point.0 * self.get_width(), point.1 * self.get_height()))
+    }
+
+    /// Converts a user space point into the given space.
+    pub fn convert(&self, point: (f64, f64), space: CoordinateSpace) -> (f64, f64) {
+        match space {
+            CoordinateSpace::User => point,
+            CoordinateSpace::MediaRelative => self.to_media_relative(point),
+            CoordinateSpace::Normalized => self.to_normalized(point),
+        }
+    }
+}
+
+impl<'a> PageRef<'a> {
+    pub(crate) fn new(index: usize, node: &'a PageNode) -> Self {
+        PageRef { index, node }
+    }
+
+    /// Returns the zero-based index of the page in page order.
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the ID of the page.
+    pub fn get_page_id(&self) -> NodeId {
+        self.node.get_page_id()
+    }
+
+    /// Returns a handle of the page, which reads its content.
+    pub fn get_page(&self) -> Page {
+        Page::new(self.node.get_page_id())
+    }
+
+    /// Returns the attributes the page defines itself, inherited attributes are not included.
+    pub fn get_attrs(&self) -> &'a Dictionary {
+        self.node.get_attrs()
+    }
+}
+
+impl Page {
+    /// Creates a handle for the page with the given ID, see [`PDFDocument::get_page_ids`].
+    pub fn new(page_id: NodeId) -> Self {
+        Page { page_id }
+    }
+
+    /// Returns the ID of the page.
+    pub fn get_page_id(&self) -> NodeId {
+        self.page_id
+    }
+
+    /// Returns the page tree node of the page, giving read-only access to its attributes.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the node, or `MissingNode` if the page isn't in the page tree
+    pub fn node<'a>(&self, document: &'a PDFDocument) -> Result<&'a PageNode> {
+        document.get_page_tree().node(self.page_id)
+    }
+
+    /// Returns the intermediate `/Pages` node the page is a kid of, see
+    /// [`crate::catalog::PageTreeArean::parent_of`].
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parent, `None` if the page is the root, or `MissingNode`
+    /// if the page or its parent isn't in the page tree
+    pub fn parent<'a>(&self, document: &'a PDFDocument) -> Result<Option<&'a PageNode>> {
+        document.get_page_tree().parent_of(self.page_id)
+    }
+
+    /// Extracts the text of the page in content stream order.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the text, or an error if the page is not found or its
+    /// content can't be parsed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_rs::document::PDFDocument;
+    /// use pdf_rs::page::Page;
+    ///
+    /// let mut document = PDFDocument::open("document/pdfreference1.0.pdf".into())?;
+    /// let page = Page::new(document.get_page_ids()[1]);
+    /// assert!(page.text(&mut document)?.contains("Library of Congress"));
+    /// # Ok::<(), pdf_rs::error::PDFError>(())
+    /// ```
+    pub fn text(&self, document: &mut PDFDocument) -> Result<String> {
+        self.text_with(document, &TextExtractionOptions::default())
+    }
+
+    /// Extracts the text of the page with the given options.
+    ///
+    /// Simple fonts are decoded through their `/Encoding`, composite fonts without a
+    /// `/ToUnicode` map yield no text.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    /// * `options` - The extraction options
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the text, or an error if the page is not found or its
+    /// content can't be parsed
+    pub fn text_with(&self, document: &mut PDFDocument, options: &TextExtractionOptions) -> Result<String> {
+        let span = span!("pdf.page_text", obj_num, fragments, chars);
+        let _entered = span.enter();
+        span.record("obj_num", self.page_id.get_number() as u64);
+        let mut fragments = extract_fragments(document, self.page_id)?;
+        apply_invisible_text(&mut fragments, options);
+        let text = fragments_to_text(&fragments, options);
+        span.record("fragments", fragments.len() as u64);
+        span.record("chars", text.chars().count() as u64);
+        Ok(text)
+    }
+
+    /// Returns the transform derived from the MediaBox of the page, which may be
+    /// inherited from an ancestor page tree node.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the transform, or an error if the page is not found or has no
+    /// MediaBox of four numbers
+    pub fn transform(&self, document: &PDFDocument) -> Result<PageTransform> {
+        if document.get_page_node(self.page_id).is_none() {
+            return Err(PageNotFound(format!("Page not found:{}", self.page_id)));
+        }
+        let values = document
+            .get_page_tree()
+            .get_inherited_attr(self.page_id, MEDIA_BOX)
+            .and_then(|it| it.as_array())
+            .map(|values| values.iter().filter_map(|it| it.as_number()).map(|it| it.as_f64()).collect::<Vec<_>>());
+        match values.as_deref() {
+            Some(&[x0, y0, x1, y1]) => Ok(PageTransform::new([x0, y0, x1, y1])),
+            _ => Err(ObjectAttrMiss("Page MediaBox not found or malformed.")),
+        }
+    }
+
+    /// Returns the MediaBox of the page, the boundaries of the physical medium, which may be
+    /// inherited from an ancestor page tree node.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the rectangle, or an error if the page is not found or has no
+    /// MediaBox of four numbers
+    pub fn media_box(&self, document: &mut PDFDocument) -> Result<Rect> {
+        self.resolved_rect(document, MEDIA_BOX)?.ok_or(ObjectAttrMiss("Page MediaBox not found or malformed."))
+    }
+
+    /// Returns the CropBox of the page, the region displayed or printed, which may be
+    /// inherited from an ancestor page tree node.
+    ///
+    /// The CropBox is clipped to the MediaBox and defaults to it if it is missing, malformed
+    /// or outside of the MediaBox.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the rectangle, or an error if the page is not found or has no
+    /// MediaBox of four numbers
+    pub fn crop_box(&self, document: &mut PDFDocument) -> Result<Rect> {
+        let media_box = self.media_box(document)?;
+        let crop_box = self.resolved_rect(document, CROP_BOX)?;
+        Ok(crop_box.and_then(|it| it.intersect(&media_box)).unwrap_or(media_box))
+    }
+
+    /// Returns the clockwise rotation of the page when displayed, which may be inherited
+    /// from an ancestor page tree node.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the rotation in degrees from 0 to 359, 0 if `/Rotate` is missing
+    /// or not a number, or an error if the page is not found
+    pub fn rotate(&self, document: &mut PDFDocument) -> Result<i32> {
+        let rotate = self.resolved_attr(document, ROTATE)?;
+        let degrees = rotate.as_ref().and_then(PDFObject::as_number).map_or(0, |it| it.as_f64() as i64);
+        Ok(degrees.rem_euclid(360) as i32)
+    }
+
+    /// Returns the resources of the page, which may be inherited from an ancestor page
+    /// tree node.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the resource dictionary, `None` if it is missing or not a
+    /// dictionary, or an error if the page is not found
+    pub fn resources(&self, document: &mut PDFDocument) -> Result<Option<Dictionary>> {
+        Ok(self.resolved_attr(document, RESOURCES)?.and_then(PDFObject::into_dict))
+    }
+
+    /// Looks up an attribute of the page or its ancestors and resolves it if it is a reference.
+    fn resolved_attr(&self, document: &mut PDFDocument, key: &str) -> Result<Option<PDFObject>> {
+        if document.get_page_node(self.page_id).is_none() {
+            return Err(PageNotFound(format!("Page not found:{}", self.page_id)));
+        }
+        match document.get_page_tree().get_inherited_attr(self.page_id, key).cloned() {
+            Some(value) => document.resolve(value),
+            None => Ok(None),
+        }
+    }
+
+    /// Looks up a rectangle attribute like [`Page::resolved_attr`], the numbers of the
+    /// array may be references too.
+    fn resolved_rect(&self, document: &mut PDFDocument, key: &str) -> Result<Option<Rect>> {
+        let Some(PDFObject::Array(values)) = self.resolved_attr(document, key)? else {
+            return Ok(None);
+        };
+        let mut resolved = Vec::with_capacity(values.len());
+        for value in values {
+            resolved.push(document.resolve(value)?.unwrap_or(PDFObject::Null));
+        }
+        Ok(PDFObject::Array(resolved).as_rect())
+    }
+
+    /// Returns the text fragments of the page in content stream order, or in logical order
+    /// with [`TextExtractionOptions::with_bidi`].
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document the page belongs to
+    /// * `options` - The extraction options, selecting the coordinate space of the positions,
+    ///   the handling of invisible text and the order of right-to-left text
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the fragments, or an error if the page is not found, its
+    /// content can't be parsed or a MediaBox-derived space is requested without a MediaBox
+    pub fn fragments_with(&self, document: &mut PDFDocument, options: &TextExtractionOptions) -> Result<Vec<TextFragment>> {
+        let mut fragments = extract_fragments(document, self.page_id)?;
+        apply_invisible_text(&mut fragments, options);
+        apply_bidi(&mut fragments, options);
+        let space = options.get_coordinate_space();
+        if space != CoordinateSpace::User {
+            let transform = self.transform(document)?;
+            fragments.iter_mut().for_each(|it| it.convert(&transform, space));
+        }
+        Ok(fragments)
+    }
+
+    /// Returns the decoded content of the page, see [`PDFDocument::page_content_bytes`].
+    pub fn content_bytes(&self, document: &mut PDFDocument) -> Result<Vec<u8>> {
+        document.page_content_bytes(self.page_id)
+    }
+
+    /// Returns the operations of the page content, see [`PDFDocument::page_content_operations`].
+    pub fn content_operations(&self, document: &mut PDFDocument) -> Result<Vec<Operation>> {
+        document.page_content_operations(self.page_id)
+    }
+}
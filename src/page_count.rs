@@ -0,0 +1,153 @@
+use crate::catalog::classify_page_node;
+use crate::constants::{COUNT, KIDS, PAGES};
+use crate::document::PDFDocument;
+use crate::error::Result;
+use crate::objects::{Dictionary, ObjRefTuple, PDFObject};
+use crate::trace::span;
+use std::collections::HashSet;
+
+/// The number of pages found by [`PDFDocument::count_pages_up_to`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountResult {
+    /// The document has exactly this many pages.
+    Exact(usize),
+    /// The document has at least this many pages, the limit of the count.
+    AtLeast(usize),
+}
+
+impl CountResult {
+    /// Returns the number of pages known to exist.
+    pub fn get_pages(&self) -> usize {
+        match self {
+            CountResult::Exact(pages) | CountResult::AtLeast(pages) => *pages,
+        }
+    }
+
+    /// Returns true if the count is exact.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, CountResult::Exact(_))
+    }
+}
+
+/// The nodes read so far and the pages confirmed among them.
+struct Walk {
+    visited: HashSet<ObjRefTuple>,
+    /// The number of page tree nodes read from the document.
+    nodes: u64,
+}
+
+impl Walk {
+    /// Reads a page tree node, `None` if it was read before or isn't a dictionary. Only
+    /// references count as read nodes, a node passed as a dictionary was read already.
+    fn read(&mut self, document: &mut PDFDocument, node: PDFObject) -> Result<Option<Dictionary>> {
+        if let PDFObject::ObjectRef(obj_num, gen_num) = node {
+            if !self.visited.insert((obj_num, gen_num)) {
+                return Ok(None);
+            }
+            self.nodes += 1;
+        }
+        match document.resolve(node)? {
+            Some(PDFObject::Dict(dict)) => Ok(Some(dict)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Returns the `/Kids` of an intermediate node, `None` for a page.
+fn kids(dict: &Dictionary) -> Option<Vec<PDFObject>> {
+    let (is_page_tree, _) = classify_page_node(dict);
+    is_page_tree.then(|| dict.get_array_value(KIDS).map(<[PDFObject]>::to_vec).unwrap_or_default())
+}
+
+/// Counts the pages of the document up to a limit by walking the page tree objects.
+///
+/// The root `/Count` is trusted if the kids of the root agree with it, a kid counting as
+/// one page or as its own `/Count`. The kids are sampled until `limit` of them are pages,
+/// so a flat tree costs `limit` reads instead of one per page. Otherwise the pages are
+/// walked depth-first, the walk ends once `limit` pages are confirmed. Nodes which aren't
+/// dictionaries or were read before don't count.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `limit` - The number of pages after which counting stops
+///
+/// # Returns
+///
+/// A `Result` containing the exact number of pages if it is below the limit, or an error if
+/// a node can't be read
+pub(crate) fn count_pages_up_to(document: &mut PDFDocument, limit: usize) -> Result<CountResult> {
+    let span = span!("pdf.count_pages", limit, nodes, pages);
+    let _entered = span.enter();
+    span.record("limit", limit as u64);
+    let mut walk = Walk { visited: HashSet::new(), nodes: 0 };
+    let result = count(document, limit, &mut walk);
+    span.record("nodes", walk.nodes);
+    if let Ok(result) = &result {
+        span.record("pages", result.get_pages() as u64);
+    }
+    result
+}
+
+fn count(document: &mut PDFDocument, limit: usize, walk: &mut Walk) -> Result<CountResult> {
+    let bounded = |pages: usize| {
+        if pages >= limit { CountResult::AtLeast(limit) } else { CountResult::Exact(pages) }
+    };
+    if limit == 0 {
+        return Ok(CountResult::AtLeast(0));
+    }
+    let Some(root) = document.get_catalog().get_attrs().get(PAGES).cloned() else {
+        return Ok(CountResult::Exact(0));
+    };
+    let Some(root) = walk.read(document, root)? else {
+        return Ok(CountResult::Exact(0));
+    };
+    let Some(root_kids) = kids(&root) else {
+        return Ok(CountResult::Exact(1));
+    };
+    // The shallow sample, kept for the walk
+    let mut sample = Vec::with_capacity(root_kids.len());
+    let mut sampled_count = Some(0u64);
+    let mut sampled_pages = 0;
+    for kid in root_kids {
+        let Some(dict) = walk.read(document, kid)? else {
+            sampled_count = None;
+            continue;
+        };
+        let kid_count = match kids(&dict) {
+            Some(_) => dict.get_u64_num(COUNT),
+            None => {
+                // The pages among the kids confirm the limit, the rest can't change the result
+                sampled_pages += 1;
+                if sampled_pages >= limit {
+                    return Ok(CountResult::AtLeast(limit));
+                }
+                Some(1)
+            }
+        };
+        sampled_count = sampled_count.zip(kid_count).map(|(sum, count)| sum + count);
+        sample.push(dict);
+    }
+    if let Some(declared) = root.get_u64_num(COUNT)
+        && sampled_count == Some(declared)
+    {
+        return Ok(bounded(declared as usize));
+    }
+    let mut pages = 0;
+    let mut pending = sample.into_iter().rev().map(PDFObject::Dict).collect::<Vec<_>>();
+    while let Some(node) = pending.pop() {
+        let Some(dict) = walk.read(document, node)? else {
+            continue;
+        };
+        match kids(&dict) {
+            Some(kids) => pending.extend(kids.into_iter().rev()),
+            None => {
+                pages += 1;
+                if pages >= limit {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(bounded(pages))
+}
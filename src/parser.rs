@@ -1,39 +1,130 @@
 use crate::constants::pdf_key::{END_OBJ, END_STREAM, OBJ, R, STREAM};
 use crate::constants::*;
-use crate::error::{PDFError, Result};
+use crate::error::Result;
+use crate::sequence::MemorySequence;
+use crate::name::Name;
 use crate::objects::{Dictionary, PDFNumber, PDFObject, PDFStrKind, PDFString, Stream, XEntry};
 use crate::tokenizer::Token::{Delimiter, Id, Key, Number};
 use crate::tokenizer::{Token, Tokenizer};
 use std::collections::HashMap;
-use crate::error::PDFError::{EOFError, PDFParseError, PDFParseError0};
-use crate::utils::hex2bytes;
+use crate::error::PDFError::{
+    CircularReference, EOFError, PDFParseError, PDFParseError0, StreamLengthMismatch, TokenTooLong,
+};
+use crate::filter::undo_png_predictor;
+use crate::objstm::read_compressed_object;
+use crate::utils::{hex2bytes, unescape_literal};
+
+/// Parses exactly one object from bytes, e.g. `<< /Type /Page >>`, `[1 2 R]` or
+/// `12 0 obj (text) endobj`.
+///
+/// Arbitrary input never panics, malformed objects, excessive nesting and trailing
+/// data are reported as errors.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the object, surrounding white-space is allowed
+///
+/// # Returns
+///
+/// A `Result` containing the parsed object, or an error if the bytes are not a single object
+pub fn parse_object(bytes: &[u8]) -> Result<PDFObject> {
+    let mut reader = ObjectReader::from_bytes(bytes);
+    let object = parse(&mut reader.tokenizer)?;
+    if !reader.is_eof()? {
+        return Err(PDFParseError("Trailing data after object."));
+    }
+    Ok(object)
+}
+
+/// Reads sequential objects from bytes which aren't part of the document sequence, e.g.
+/// the decoded data of an object stream.
+///
+/// # Examples
+///
+/// ```
+/// use pdf_rs::ObjectReader;
+///
+/// let mut reader = ObjectReader::from_bytes(b"<< /A 1 >> [2 0 R]");
+/// assert!(reader.next_object()?.is_some_and(|it| it.is_dict()));
+/// assert!(reader.next_object()?.is_some_and(|it| it.is_array()));
+/// assert!(reader.next_object()?.is_none());
+/// # Ok::<(), pdf_rs::error::PDFError>(())
+/// ```
+pub struct ObjectReader {
+    tokenizer: Tokenizer,
+}
+
+impl ObjectReader {
+    /// Creates a reader over a copy of the bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        // Make sure a trailing number or keyword is terminated by a white-space
+        let mut buf = bytes.to_vec();
+        buf.push(b'\n');
+        ObjectReader { tokenizer: Tokenizer::new(MemorySequence::new(buf)) }
+    }
+
+    /// Reads the next object.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the object, `None` once only white-space is left,
+    /// or an error if the next object is malformed
+    pub fn next_object(&mut self) -> Result<Option<PDFObject>> {
+        if self.is_eof()? {
+            return Ok(None);
+        }
+        parse(&mut self.tokenizer).map(Some)
+    }
+
+    fn is_eof(&mut self) -> Result<bool> {
+        Ok(*self.tokenizer.peek_token(0)? == Token::Eof)
+    }
+}
 
 pub(crate) fn parse_with_offset(tokenizer: &mut Tokenizer, offset: u64) -> Result<PDFObject> {
     tokenizer.seek(offset)?;
     parse(tokenizer)
 }
 
-pub(crate) fn parse(mut tokenizer: &mut Tokenizer) -> Result<PDFObject>
+/// Parses the object of an in-use xref entry, at its byte offset or inside its object
+/// stream, see [`read_compressed_object`].
+pub(crate) fn parse_entry(tokenizer: &mut Tokenizer, entry: &XEntry) -> Result<PDFObject> {
+    match entry.get_object_stream() {
+        Some(object_stream) => read_compressed_object(tokenizer, object_stream, entry.value, entry.obj_num),
+        None => parse_with_offset(tokenizer, entry.value),
+    }
+}
+
+pub(crate) fn parse(tokenizer: &mut Tokenizer) -> Result<PDFObject>
 {
     let token = tokenizer.next_token()?;
-    let object = parser0(&mut tokenizer, token)?;
+    let object = parser0(tokenizer, token)?;
     Ok(object)
 }
 
-fn parser0(tokenizer: &mut Tokenizer, token: Token) -> Result<PDFObject> {
+pub(crate) fn parser0(tokenizer: &mut Tokenizer, token: Token) -> Result<PDFObject> {
     match token {
-        Delimiter(delimiter) => match delimiter.as_str() {
+        Delimiter(delimiter) => match delimiter {
             "<<" => {
-                let dict = parse_dict(tokenizer)?;
+                tokenizer.enter_nesting()?;
+                let dict = parse_dict(tokenizer);
+                tokenizer.leave_nesting();
+                let dict = dict?;
                 // If the next token is stream, then it is a stream
                 if tokenizer.check_next_token0(false, |token| token.key_was(STREAM))? {
                     return parse_stream(tokenizer, dict);
                 }
                 Ok(PDFObject::Dict(dict))
             }
-            "[" => parse_array(tokenizer),
+            "[" => {
+                tokenizer.enter_nesting()?;
+                let array = parse_array(tokenizer);
+                tokenizer.leave_nesting();
+                array
+            }
             "/" => parse_named(tokenizer),
-            "<" | "(" => parse_string(tokenizer, delimiter == "("),
+            "(" => parse_literal_string(tokenizer),
+            "<" => parse_hex_string(tokenizer),
             _ => Err(PDFParseError0(format!("Delimiter '{}' not implemented", delimiter))),
         },
         Key(key) => match key.as_str() {
@@ -48,27 +139,53 @@ fn parser0(tokenizer: &mut Tokenizer, token: Token) -> Result<PDFObject> {
         }
         Number(number) => match number {
             PDFNumber::Unsigned(value) => {
-                let is_num = tokenizer.check_next_token(|token| token.is_u64())?;
-                if !is_num {
+                // `1 2 R` and `1 2 obj` need two tokens of lookahead, which stay buffered in order
+                if !tokenizer.peek_token(0)?.is_u64() {
                     return Ok(PDFObject::Number(number));
                 }
-                let is_obj = tokenizer.check_next_token(|token| token.key_was(R) || token.key_was(OBJ))?;
-                if is_obj {
-                    return parse_obj(tokenizer, Some(value as u32));
+                let next = tokenizer.peek_token(1)?;
+                if next.key_was(R) || next.key_was(OBJ) {
+                    let obj_num = u32::try_from(value)
+                        .map_err(|_| PDFParseError0(format!("Object number out of range:{}", value)))?;
+                    return parse_obj(tokenizer, Some(obj_num));
                 }
                 Ok(PDFObject::Number(number))
             }
             _ => Ok(PDFObject::Number(number))
         },
         Token::Eof => Err(EOFError),
-        _ => Err(PDFParseError0(format!("Illegal token:{}", token.to_string())))
+        _ => Err(PDFParseError0(format!("Illegal token:{}", token)))
     }
 }
 
+/// Parses the entries of a cross-reference table following the `xref` keyword.
+///
+/// A table consists of subsections, each starting with the number of its first object and
+/// the number of its entries, e.g. `0 1` followed by `17 12`. The subsections end where
+/// the next token isn't such a header, usually at the `trailer` keyword.
+///
+/// # Arguments
+///
+/// * `tokenizer` - The tokenizer positioned after the `xref` keyword
+///
+/// # Returns
+///
+/// A `Result` containing the entries of all subsections in file order, or an error if an
+/// entry is malformed
 pub(crate) fn parse_text_xref(tokenizer: &mut Tokenizer) -> Result<Vec<XEntry>> {
+    let mut entries = Vec::<XEntry>::new();
+    loop {
+        parse_xref_subsection(tokenizer, &mut entries)?;
+        if !tokenizer.peek_token(0)?.is_u64() || !tokenizer.peek_token(1)?.is_u64() {
+            return Ok(entries);
+        }
+    }
+}
+
+/// Parses one subsection of a cross-reference table, appending its entries.
+fn parse_xref_subsection(tokenizer: &mut Tokenizer, entries: &mut Vec<XEntry>) -> Result<()> {
     let obj_num = tokenizer.next_token()?.as_u32()?;
     let length = tokenizer.next_token()?.as_u32()?;
-    let mut entries = Vec::<XEntry>::new();
     for i in 0..length {
         let value = tokenizer.next_token()?.as_u64()?;
         let gen_num = tokenizer.next_token()?.as_u16()?;
@@ -78,7 +195,9 @@ pub(crate) fn parse_text_xref(tokenizer: &mut Tokenizer) -> Result<Vec<XEntry>>
             "f" => false,
             _ => return Err(PDFParseError0(format!("Except a token with 'f' or 'n' but it is '{}'", state)))
         };
-        let obj_num = obj_num + i;
+        let obj_num = obj_num
+            .checked_add(i)
+            .ok_or_else(|| PDFParseError0(format!("Xref subsection exceeds object number {}", u32::MAX)))?;
         let entry = XEntry::new(
             obj_num,
             gen_num,
@@ -87,9 +206,84 @@ pub(crate) fn parse_text_xref(tokenizer: &mut Tokenizer) -> Result<Vec<XEntry>>
         );
         entries.push(entry);
     }
+    Ok(())
+}
+
+/// Parses the entries of a cross-reference stream (`/Type /XRef`).
+///
+/// Each row holds the fields whose byte widths `/W` lists: the type, defaulting to 1 for
+/// a zero width, then two values. Type 0 rows are free entries, type 1 rows objects at a
+/// byte offset and type 2 rows objects in an object stream. Rows of other types are
+/// skipped as the spec demands.
+///
+/// # Arguments
+///
+/// * `stream` - The cross-reference stream
+/// * `data` - The decoded data of the stream
+///
+/// # Returns
+///
+/// A `Result` containing the entries of all subsections listed by `/Index`, or an error
+/// if `/W` is malformed or the data holds fewer rows than listed
+pub(crate) fn parse_xref_stream(stream: &Stream, mut data: Vec<u8>) -> Result<Vec<XEntry>> {
+    let dict = stream.get_metadata();
+    let widths = dict
+        .get_array_value(W)
+        .map(|items| items.iter().map(unsigned).collect::<Option<Vec<_>>>())
+        .unwrap_or_default()
+        .filter(|widths| widths.len() == 3 && widths.iter().all(|it| *it <= 8))
+        .ok_or_else(|| PDFParseError0("Cross-reference stream has a malformed /W".to_string()))?;
+    let index = match dict.get_array_value(INDEX) {
+        Some(items) => items.iter().map(unsigned).collect::<Option<Vec<_>>>(),
+        None => dict.get_u64_num(SIZE).map(|size| vec![0, size]),
+    };
+    let index = index
+        .filter(|index| index.len() % 2 == 0)
+        .ok_or_else(|| PDFParseError0("Cross-reference stream has a malformed /Index".to_string()))?;
+    // The predictor belongs to the last filter, e.g. `FlateDecode` below `ASCIIHexDecode`
+    let parms = stream.decode_parms().last().copied().flatten();
+    match parms.and_then(|it| it.get_u64_num(PREDICTOR)).unwrap_or(1) {
+        1 => {}
+        10..=15 => {
+            let columns = parms.and_then(|it| it.get_u64_num(COLUMNS)).unwrap_or(1);
+            data = undo_png_predictor(&data, columns as usize)?;
+        }
+        predictor => return Err(PDFParseError0(format!("Cross-reference stream predictor {} isn't supported", predictor))),
+    }
+    let row = widths.iter().sum::<u64>() as usize;
+    let mut rows = data.chunks_exact(row.max(1));
+    let mut entries = Vec::new();
+    for subsection in index.chunks(2) {
+        for i in 0..subsection[1] {
+            let row = rows
+                .next()
+                .ok_or_else(|| PDFParseError0("Cross-reference stream data ends early".to_string()))?;
+            let (kind, rest) = row.split_at(widths[0] as usize);
+            let (first, second) = rest.split_at(widths[1] as usize);
+            let field = |bytes: &[u8]| bytes.iter().fold(0u64, |value, b| (value << 8) | *b as u64);
+            let kind = if kind.is_empty() { 1 } else { field(kind) };
+            let obj_num = u32::try_from(subsection[0] + i)
+                .map_err(|_| PDFParseError0(format!("Object number out of range:{}", subsection[0] + i)))?;
+            let entry = match kind {
+                0 => XEntry::new(obj_num, field(second) as u16, field(first), false),
+                1 => XEntry::new(obj_num, field(second) as u16, field(first), true),
+                2 => XEntry::compressed(obj_num, field(first) as u32, field(second)),
+                _ => continue,
+            };
+            entries.push(entry);
+        }
+    }
     Ok(entries)
 }
 
+/// Returns the value of an unsigned number object.
+fn unsigned(object: &PDFObject) -> Option<u64> {
+    match object {
+        PDFObject::Number(PDFNumber::Unsigned(value)) => Some(*value),
+        _ => None,
+    }
+}
+
 fn parse_obj(tokenizer: &mut Tokenizer, option: Option<u32>) -> Result<PDFObject> {
     let obj_num = match option {
         Some(num) => num,
@@ -101,8 +295,12 @@ fn parse_obj(tokenizer: &mut Tokenizer, option: Option<u32>) -> Result<PDFObject
     if let Key(ref key) = type_token {
         let object = match key.as_str() {
             OBJ => {
+                tokenizer.charge_object()?;
                 let token = tokenizer.next_token()?;
-                let value = parser0(tokenizer, token)?;
+                tokenizer.enter_object((obj_num, gen_num));
+                let value = parser0(tokenizer, token);
+                tokenizer.leave_object();
+                let value = value?;
                 // Except a token with 'endobj'
                 tokenizer.next_token()?.except(|token| token.key_was(END_OBJ))?;
                 return Ok(PDFObject::IndirectObject(obj_num, gen_num, Box::new(value)));
@@ -115,19 +313,17 @@ fn parse_obj(tokenizer: &mut Tokenizer, option: Option<u32>) -> Result<PDFObject
     }
     Err(PDFParseError("Except a token with R or obj"))
 }
-fn parse_dict(mut tokenizer: &mut Tokenizer) -> Result<Dictionary> {
-    let mut entries = HashMap::<String, PDFObject>::new();
+fn parse_dict(tokenizer: &mut Tokenizer) -> Result<Dictionary> {
+    let mut entries = HashMap::<Name, PDFObject>::new();
     loop {
         let token = tokenizer.next_token()?;
-        if let Delimiter(ref delimiter) = token {
-            if delimiter == ">>" {
-                break;
-            }
+        if let Delimiter(">>") = token {
+            break;
         }
-        let object = parser0(&mut tokenizer, token)?;
+        let object = parser0(tokenizer, token)?;
         if let PDFObject::Named(named) = object {
             let token = tokenizer.next_token()?;
-            let value = parser0(&mut tokenizer, token)?;
+            let value = parser0(tokenizer, token)?;
             entries.insert(named, value);
         } else {
             return Err(PDFParseError("Except a named token."));
@@ -137,58 +333,84 @@ fn parse_dict(mut tokenizer: &mut Tokenizer) -> Result<Dictionary> {
 }
 
 fn parse_named(tokenizer: &mut Tokenizer) -> Result<PDFObject> {
-    let token = tokenizer.next_token()?;
-    if let Id(name) = token {
-        return Ok(PDFObject::Named(name));
+    let token = tokenizer.next_name()?;
+    // Names like `/R` or `/obj` are tokenized as keywords
+    match token {
+        Id(name) | Key(name) => Ok(PDFObject::Named(name)),
+        _ => Err(PDFParseError("Except a identifier token.")),
     }
-    Err(PDFParseError("Except a identifier token."))
 }
 
 fn parse_array(tokenizer: &mut Tokenizer) -> Result<PDFObject> {
     let mut elements = Vec::<PDFObject>::new();
     loop {
         let token = tokenizer.next_token()?;
-        if let Delimiter(ref delimiter) = token {
-            if delimiter == "]" {
-                return Ok(PDFObject::Array(elements));
-            }
+        if let Delimiter("]") = token {
+            return Ok(PDFObject::Array(elements));
         }
         let object = parser0(tokenizer, token)?;
         elements.push(object);
     }
 }
 
-fn parse_string(tokenizer: &mut Tokenizer, literal_str: bool) -> Result<PDFObject> {
-    let end_chr = if literal_str { ')' } else { '>' };
+/// Parses a literal string after its `(`, see [`unescape_literal`] for the escapes.
+fn parse_literal_string(tokenizer: &mut Tokenizer) -> Result<PDFObject> {
     let mut is_escape = false;
-    let result = tokenizer.loop_util(&[], |chr| {
-        let m = if chr == '\\' {
-            is_escape = !is_escape;
-            false
-        } else {
-            let mut end = chr == end_chr;
-            if is_escape {
-                end = false;
+    // Balanced parentheses are allowed unescaped inside literal strings
+    let mut depth = 0;
+    let limit = tokenizer.get_options().get_max_string_len();
+    // The string starts at the `(` read before
+    let token_start = tokenizer.position().saturating_sub(1);
+    let result = tokenizer.loop_util_limited(&[], limit, token_start, |chr| {
+        let end = match chr {
+            '\\' => {
+                is_escape = !is_escape;
+                return Ok(false);
+            }
+            _ if is_escape => false,
+            '(' => {
+                depth += 1;
+                false
             }
-            is_escape = false;
-            end
+            ')' if depth > 0 => {
+                depth -= 1;
+                false
+            }
+            ')' => true,
+            _ => false,
         };
-        Ok(m)
+        is_escape = false;
+        Ok(end)
     });
     match result {
+        Ok(range) => {
+            let buf = unescape_literal(&tokenizer.drain_from_buf(range));
+            // Remove ')'
+            tokenizer.remove_buf_len(1);
+            Ok(PDFObject::String(PDFString::new(PDFStrKind::Literal, buf)))
+        }
+        Err(e @ TokenTooLong { .. }) => Err(e),
+        Err(_e) => Err(PDFParseError("String did not close properly")),
+    }
+}
+
+/// Parses a hexadecimal string after its `<`.
+///
+/// Hexadecimal strings have no escapes, white-space between the digits is ignored and an
+/// odd last digit is followed by an implied `0`, e.g. `<4 1 7>` is `Ap`. Any other character
+/// before the closing `>` is an error.
+fn parse_hex_string(tokenizer: &mut Tokenizer) -> Result<PDFObject> {
+    let limit = tokenizer.get_options().get_max_string_len();
+    let offset = tokenizer.position();
+    match tokenizer.loop_util_limited(&['>'], limit, offset.saturating_sub(1), |_| Ok(false)) {
         Ok(range) => {
             let buf = tokenizer.drain_from_buf(range);
-            let buf = if literal_str {
-                buf
-            } else {
-                hex2bytes(&buf)
-            };
-            // Remove '>' or ')'
+            // Remove '>'
             tokenizer.remove_buf_len(1);
-            let kind = if literal_str { PDFStrKind::Literal } else { PDFStrKind::Hexadecimal };
-            let value = PDFString::new(kind, buf);
-            Ok(PDFObject::String(value))
+            let buf = hex2bytes(&buf).map_err(|e| PDFParseError0(format!("Hexadecimal string at offset {}: {}", offset, e)))?;
+            Ok(PDFObject::String(PDFString::new(PDFStrKind::Hexadecimal, buf)))
         }
+        Err(e @ TokenTooLong { .. }) => Err(e),
         Err(_e) => Err(PDFParseError("String did not close properly")),
     }
 }
@@ -197,19 +419,203 @@ fn parse_string(tokenizer: &mut Tokenizer, literal_str: bool) -> Result<PDFObjec
 /// between the `stream` and `endstream` keywords. This length does not include
 /// the `stream` or `endstream` keywords themselves, nor the required
 /// end-of-line marker (CRLF or LF) immediately following `stream`.
+///
+/// An indirect `/Length` is read through the cross-reference table of the tokenizer.
 pub(crate) fn parse_stream(tokenizer: &mut Tokenizer, metadata: Dictionary) -> Result<PDFObject> {
-    if let Some(PDFObject::Number(PDFNumber::Unsigned(length))) = metadata.get(LENGTH) {
+    let length = match metadata.get(LENGTH) {
+        Some(PDFObject::Number(PDFNumber::Unsigned(length))) => Some(*length),
+        Some(PDFObject::ObjectRef(obj_num, gen_num)) => read_indirect_length(tokenizer, (*obj_num, *gen_num))?,
+        _ => None,
+    };
+    if let Some(length) = length {
         // Skip CRLF
         tokenizer.skip_crlf()?;
-        let length = *length as usize;
+        let length = length as usize;
+        let start = tokenizer.position();
+        if tokenizer.is_skip_stream_data() {
+            let end = start.saturating_add(length as u64);
+            if skip_stream_data(tokenizer, end) {
+                return Ok(PDFObject::Stream(Stream::new(metadata, Vec::new()).with_raw_range(start..end)));
+            }
+            // The length is wrong, read the data as usual
+            tokenizer.seek(start)?;
+        }
+        if tokenizer.get_options().is_lenient() {
+            let buf = read_stream_data_lenient(tokenizer, length)?;
+            let end = start + buf.len() as u64;
+            return Ok(PDFObject::Stream(Stream::new(metadata, buf).with_raw_range(start..end)));
+        }
         let buf = tokenizer.read_bytes(length)?;
         if buf.len() != length {
             return Err(PDFParseError0(format!("Require Stream length is {} but it is {}", length, buf.len())));
         }
-        let stream = Stream::new(metadata, buf);
+        let stream = Stream::new(metadata, buf).with_raw_range(start..start + length as u64);
         // Except next token is `endstream`
-        tokenizer.next_token()?.except(|token| token.key_was(END_STREAM))?;
+        if !tokenizer.next_token()?.key_was(END_STREAM) {
+            return Err(StreamLengthMismatch(length as u64));
+        }
         return Ok(PDFObject::Stream(stream));
     }
     Err(PDFParseError("Stream length is not found"))
-}
\ No newline at end of file
+}
+
+/// Reads an indirect `/Length` and returns to the stream data.
+///
+/// # Returns
+///
+/// A `Result` containing the length, `None` if the object isn't in the cross-reference
+/// table or isn't an unsigned number, or a `CircularReference` error if the object is being
+/// parsed, e.g. the stream itself
+fn read_indirect_length(tokenizer: &mut Tokenizer, obj_ref: (u32, u16)) -> Result<Option<u64>> {
+    if tokenizer.is_in_flight(obj_ref) {
+        return Err(CircularReference { object: obj_ref.into() });
+    }
+    let entry = match tokenizer.get_xrefs().search(obj_ref) {
+        Ok(entry) if !entry.is_freed() => entry.clone(),
+        _ => return Ok(None),
+    };
+    let position = tokenizer.position();
+    let object = parse_entry(tokenizer, &entry);
+    tokenizer.seek(position)?;
+    match object? {
+        PDFObject::IndirectObject(_, _, value) => match *value {
+            PDFObject::Number(PDFNumber::Unsigned(length)) => Ok(Some(length)),
+            _ => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Seeks over stream data ending at `end`, returns true if `endstream` follows.
+fn skip_stream_data(tokenizer: &mut Tokenizer, end: u64) -> bool {
+    tokenizer.seek(end).is_ok()
+        && tokenizer
+            .next_token()
+            .is_ok_and(|token| token.key_was(END_STREAM))
+}
+
+/// Reads the data of a stream whose declared length may be wrong.
+///
+/// The declared length is used when it is followed by `endstream`, otherwise the data is
+/// scanned for `endstream` and ends at the line ending before it.
+///
+/// # Arguments
+///
+/// * `tokenizer` - A mutable reference to the tokenizer positioned at the stream data
+/// * `length` - The declared length of the stream
+///
+/// # Returns
+///
+/// A `Result` containing the stream data, or an error if `endstream` can't be found
+/// within the scan limit or the recovery budget
+fn read_stream_data_lenient(tokenizer: &mut Tokenizer, length: usize) -> Result<Vec<u8>> {
+    let offset = tokenizer.position();
+    let keyword = END_STREAM.as_bytes();
+    let available = tokenizer.fill_buf(length.saturating_add(keyword.len() + 32))?;
+    if available >= length && tokenizer.peek_buf()[length..].trim_ascii_start().starts_with(keyword) {
+        let buf = tokenizer.read_bytes(length)?;
+        tokenizer.next_token()?;
+        return Ok(buf);
+    }
+    let budget = tokenizer.recovery_budget();
+    let max_scan = tokenizer.get_options().get_max_stream_scan().min(budget);
+    let mut from = 0;
+    let index = loop {
+        let want = (from + 64 * 1024).min(max_scan);
+        let available = tokenizer.fill_buf(want)?.min(max_scan);
+        let found = tokenizer.peek_buf()[from..available]
+            .windows(keyword.len())
+            .position(|window| window == keyword);
+        if let Some(index) = found {
+            tokenizer.charge_recovery(offset, from + index + keyword.len(), false);
+            break from + index;
+        }
+        if available < want || available >= max_scan {
+            let exhausted = available >= max_scan && max_scan == budget;
+            tokenizer.charge_recovery(offset, available, exhausted);
+            return Err(StreamLengthMismatch(length as u64));
+        }
+        from = (available + 1).saturating_sub(keyword.len());
+    };
+    // The line ending before `endstream` isn't part of the data
+    let buf = tokenizer.peek_buf();
+    let mut end = index;
+    if end > 0 && buf[end - 1] == b'\n' {
+        end -= 1;
+    }
+    if end > 0 && buf[end - 1] == b'\r' {
+        end -= 1;
+    }
+    let data = tokenizer.read_bytes(end)?;
+    tokenizer.remove_buf_len(index - end + keyword.len());
+    tokenizer.warn(
+        offset,
+        format!("Stream /Length {} is wrong, endstream found after {} bytes", length, end),
+    );
+    Ok(data)
+}
+
+/// Scans forward from the current position for the next `N G obj` header.
+///
+/// # Arguments
+///
+/// * `tokenizer` - A mutable reference to the tokenizer
+/// * `limit` - The maximum number of bytes to scan
+///
+/// # Returns
+///
+/// A `Result` containing the offsets of the header and of the end of its `obj` keyword, or
+/// `None` if no header is found within the limit or the recovery budget
+pub(crate) fn find_next_object_header(tokenizer: &mut Tokenizer, limit: usize) -> Result<Option<(u64, u64)>> {
+    let start = tokenizer.position();
+    let budget = tokenizer.recovery_budget();
+    let limit = limit.min(budget);
+    let keyword = OBJ.as_bytes();
+    let mut from = 0;
+    loop {
+        let want = (from + 64 * 1024).min(limit);
+        let available = tokenizer.fill_buf(want)?.min(limit);
+        let buf = &tokenizer.peek_buf()[..available];
+        let mut index = from;
+        while let Some(i) = buf[index..].windows(keyword.len()).position(|window| window == keyword) {
+            let at = index + i;
+            if let Some(header) = object_header_start(buf, at) {
+                tokenizer.charge_recovery(start, at + keyword.len(), false);
+                return Ok(Some((start + header as u64, start + (at + keyword.len()) as u64)));
+            }
+            index = at + 1;
+        }
+        if available < want || available >= limit {
+            let exhausted = available >= limit && limit == budget;
+            tokenizer.charge_recovery(start, available, exhausted);
+            return Ok(None);
+        }
+        from = (available + 1).saturating_sub(keyword.len());
+    }
+}
+
+/// Returns the start of the `N G` preceding the `obj` keyword at `at`, if it is a header.
+fn object_header_start(buf: &[u8], at: usize) -> Option<usize> {
+    // `obj` must not be a part of a longer word, e.g. `endobj`
+    if buf.get(at + OBJ.len()).is_some_and(|b| b.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let mut cursor = at;
+    for _ in 0..2 {
+        let spaces = buf[..cursor].iter().rev().take_while(|b| b.is_ascii_whitespace()).count();
+        if spaces == 0 {
+            return None;
+        }
+        cursor -= spaces;
+        let digits = buf[..cursor].iter().rev().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        cursor -= digits;
+    }
+    // The object number must not be a part of a longer word
+    if cursor > 0 && buf[cursor - 1].is_ascii_alphanumeric() {
+        return None;
+    }
+    Some(cursor)
+}
@@ -0,0 +1,63 @@
+use crate::document::PDFDocument;
+
+/// A part of a document which [`PDFDocument::open_partial`] may fail to read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Facet {
+    /// The `%PDF-` header, the version is assumed to be 1.0.
+    Header,
+    /// The cross-reference tables and trailers, the xref entries are rebuilt by scanning
+    /// the file for object definitions.
+    Xref,
+    /// The catalog with the page tree and outline, the document has no pages.
+    Catalog,
+    /// The document information dictionary the trailer declares.
+    Info,
+}
+
+/// A document opened by [`PDFDocument::open_partial`] along with the facets which
+/// couldn't be read.
+///
+/// The document behaves like a fully opened one, with unavailable facets left empty: no
+/// pages without the catalog, no describe without the Info dictionary. Objects can be read
+/// by reference as long as the xref entries, read or rebuilt, list them.
+pub struct PartialDocument {
+    document: PDFDocument,
+    /// The facets which failed to read, in reading order.
+    unavailable: Vec<Facet>,
+}
+
+impl PartialDocument {
+    pub(crate) fn new(document: PDFDocument, unavailable: Vec<Facet>) -> Self {
+        Self { document, unavailable }
+    }
+
+    /// Returns the facets which failed to read, in reading order.
+    pub fn get_unavailable(&self) -> &[Facet] {
+        &self.unavailable
+    }
+
+    /// Returns true if the facet was read.
+    pub fn is_available(&self, facet: Facet) -> bool {
+        !self.unavailable.contains(&facet)
+    }
+
+    /// Returns true if every facet was read, i.e. the document opens as a whole.
+    pub fn is_complete(&self) -> bool {
+        self.unavailable.is_empty()
+    }
+
+    /// Returns the document with its unavailable facets left empty.
+    pub fn get_document(&self) -> &PDFDocument {
+        &self.document
+    }
+
+    /// Returns the document for reading objects, which needs mutable access.
+    pub fn get_document_mut(&mut self) -> &mut PDFDocument {
+        &mut self.document
+    }
+
+    /// Returns the document, dropping the record of unavailable facets.
+    pub fn into_document(self) -> PDFDocument {
+        self.document
+    }
+}
@@ -0,0 +1,64 @@
+use crate::error::PDFError::InvalidPath;
+use crate::error::Result;
+use std::fmt::{Display, Formatter};
+
+/// A segment of an object path, see [`parse_path`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSeg {
+    /// A dictionary key without the leading slash, e.g. `Pages`.
+    Key(String),
+    /// An array index.
+    Index(usize),
+}
+
+impl Display for PathSeg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathSeg::Key(key) => write!(f, "{}", key),
+            PathSeg::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+/// Parses an object path of dotted keys and bracketed array indices.
+///
+/// # Arguments
+///
+/// * `path` - The path, e.g. `Pages.Kids[0].MediaBox`
+///
+/// # Returns
+///
+/// A `Result` containing the segments, or an `InvalidPath` error if the path is empty,
+/// has an empty key or a malformed index
+///
+/// # Examples
+///
+/// ```
+/// use pdf_rs::path::{PathSeg, parse_path};
+///
+/// let segs = parse_path("Kids[0].MediaBox").unwrap();
+/// assert_eq!(segs, [PathSeg::Key("Kids".to_string()), PathSeg::Index(0), PathSeg::Key("MediaBox".to_string())]);
+/// ```
+pub fn parse_path(path: &str) -> Result<Vec<PathSeg>> {
+    let invalid = || InvalidPath(path.to_string());
+    let mut segs = Vec::new();
+    for part in path.split('.') {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !key.is_empty() {
+            segs.push(PathSeg::Key(key.to_string()));
+        } else if !segs.is_empty() || rest.is_empty() {
+            // Only the path may start with an index, e.g. `[0].Type`
+            return Err(invalid());
+        }
+        while !rest.is_empty() {
+            let end = rest.find(']').ok_or_else(invalid)?;
+            let index = rest[1..end].parse::<usize>().map_err(|_| invalid())?;
+            segs.push(PathSeg::Index(index));
+            rest = &rest[end + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(invalid());
+            }
+        }
+    }
+    Ok(segs)
+}
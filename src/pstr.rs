@@ -1,4 +1,4 @@
-use crate::encoding::{PreDefinedEncoding, mapper_chr_from_u8};
+use crate::encoding::PreDefinedEncoding;
 use crate::objects::PDFString;
 
 #[macro_export] macro_rules! convert_glyph_from_dict {
@@ -16,9 +16,10 @@ pub(crate) fn convert_glyph_text(str: &PDFString, encoding: &PreDefinedEncoding)
         return to_utf16be_text(str);
     }
     let buf = str.get_buf();
+    let table = encoding.get_encoding();
     let mut chr_buf = Vec::<char>::new();
     for b in buf {
-        let t = mapper_chr_from_u8(*b, encoding);
+        let t = table.lookup(*b);
         if let Some(chr) = t {
             chr_buf.push(chr);
         }
@@ -30,15 +31,13 @@ pub(crate) fn convert_glyph_text(str: &PDFString, encoding: &PreDefinedEncoding)
 fn to_utf16be_text(str: &PDFString) -> String {
     let buf = str.get_buf();
     // UTF-16BE strings in PDF start with a BOM (0xFE 0xFF)
-    // Skip the BOM and convert the remaining bytes to UTF-16
-    if buf.len() >= 2 && buf.len() % 2 == 0 {
-        let utf16_data: Vec<u16> = buf[2..]
-            .chunks_exact(2)
-            .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
-            .collect();
-        String::from_utf16(&utf16_data).unwrap_or_default()
-    } else {
-        // todo Convert byte to hex string?
-        String::new()
-    }
+    // Skip the BOM and convert the remaining bytes to UTF-16, a trailing odd byte is dropped
+    // and unpaired surrogates are replaced instead of losing the whole string
+    let utf16_data: Vec<u16> = buf
+        .get(2..)
+        .unwrap_or_default()
+        .chunks_exact(2)
+        .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+        .collect();
+    String::from_utf16_lossy(&utf16_data)
 }
\ No newline at end of file
@@ -0,0 +1,166 @@
+use crate::catalog::NodeId;
+use crate::document::PDFDocument;
+use crate::error::PDFError;
+use crate::error::PDFError::PDFParseError0;
+use crate::page::Page;
+use crate::text::TextExtractionOptions;
+use std::time::{Duration, Instant};
+
+/// The separator between the text of two pages in [`TextReport::get_text`], a form feed
+/// like `pdftotext` writes.
+pub const PAGE_SEPARATOR: char = '\x0c';
+
+/// The outcome of extracting the text of a page.
+#[derive(Debug)]
+pub enum PageStatus {
+    /// The text was extracted without warnings.
+    Ok,
+    /// The text was extracted, recording the given number of warnings, see
+    /// [`PDFDocument::get_warnings`].
+    Warnings(usize),
+    /// The text can't be extracted.
+    Failed(PDFError),
+}
+
+/// The result of extracting the text of a single page, see [`PDFDocument::extract_text_report`].
+#[derive(Debug)]
+pub struct PageResult {
+    /// The index of the page in page order.
+    index: usize,
+    status: PageStatus,
+    /// The number of characters of the extracted text.
+    chars: usize,
+    /// The time spent extracting the text.
+    elapsed: Duration,
+}
+
+/// The text of a document along with how the extraction of each page went.
+#[derive(Debug, Default)]
+pub struct TextReport {
+    /// The text of all pages, separated by [`PAGE_SEPARATOR`].
+    text: String,
+    pages: Vec<PageResult>,
+}
+
+impl PageStatus {
+    /// Returns true if the text of the page was extracted, possibly with warnings.
+    pub fn is_extracted(&self) -> bool {
+        !matches!(self, PageStatus::Failed(_))
+    }
+}
+
+impl PageResult {
+    /// Returns the index of the page in page order.
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the outcome of the extraction.
+    pub fn get_status(&self) -> &PageStatus {
+        &self.status
+    }
+
+    /// Returns the number of characters of the extracted text, 0 if the extraction failed.
+    pub fn get_chars(&self) -> usize {
+        self.chars
+    }
+
+    /// Returns the time spent extracting the text.
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl TextReport {
+    /// Returns the text of all pages separated by [`PAGE_SEPARATOR`], a failed page
+    /// contributes no text.
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the result of each page in page order.
+    pub fn get_pages(&self) -> &[PageResult] {
+        &self.pages
+    }
+
+    /// Returns the number of pages extracted without warnings.
+    pub fn ok_pages(&self) -> usize {
+        self.pages.iter().filter(|it| matches!(it.status, PageStatus::Ok)).count()
+    }
+
+    /// Returns the number of pages extracted with warnings.
+    pub fn warning_pages(&self) -> usize {
+        self.pages.iter().filter(|it| matches!(it.status, PageStatus::Warnings(_))).count()
+    }
+
+    /// Returns the number of pages whose extraction failed.
+    pub fn failed_pages(&self) -> usize {
+        self.pages.iter().filter(|it| !it.status.is_extracted()).count()
+    }
+
+    /// Returns the number of characters extracted from all pages.
+    pub fn total_chars(&self) -> usize {
+        self.pages.iter().map(|it| it.chars).sum()
+    }
+
+    /// Returns the time spent extracting all pages.
+    pub fn total_elapsed(&self) -> Duration {
+        self.pages.iter().map(|it| it.elapsed).sum()
+    }
+}
+
+/// Returns the error of a page object which was replaced by a placeholder.
+fn unreadable_page(document: &mut PDFDocument, page_id: NodeId) -> PDFError {
+    match document.read_object_with_ref(page_id) {
+        Err(e) => e,
+        _ => PDFParseError0(format!("Page object {} is unreadable", page_id)),
+    }
+}
+
+/// Extracts the text of every page, recording the outcome of each page instead of
+/// stopping at the first failure. A page whose object is unreadable, which lenient mode
+/// replaces by an empty placeholder, is reported as failed. A crossed resource limit stops
+/// the extraction, the failed page is the last one reported.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `options` - The extraction options
+///
+/// # Returns
+///
+/// The text of the pages and the result of each page
+pub(crate) fn collect_text_report(document: &mut PDFDocument, options: &TextExtractionOptions) -> TextReport {
+    let mut report = TextReport::default();
+    for (index, page_id) in document.get_page_ids().into_iter().enumerate() {
+        if index > 0 {
+            report.text.push(PAGE_SEPARATOR);
+        }
+        let warnings = document.get_warnings().len();
+        let start = Instant::now();
+        // A placeholder of an unreadable page has no text but is no success either
+        let result = match document.get_page_node(page_id).is_some_and(|it| it.is_unreadable()) {
+            true => Err(unreadable_page(document, page_id)),
+            false => Page::new(page_id).text_with(document, options),
+        };
+        let elapsed = start.elapsed();
+        let (status, chars) = match result {
+            Ok(text) => {
+                report.text.push_str(&text);
+                let status = match document.get_warnings().len() - warnings {
+                    0 => PageStatus::Ok,
+                    count => PageStatus::Warnings(count),
+                };
+                (status, text.chars().count())
+            }
+            Err(e) => (PageStatus::Failed(e), 0),
+        };
+        let abort = matches!(&status, PageStatus::Failed(e) if e.is_abort());
+        report.pages.push(PageResult { index, status, chars, elapsed });
+        // The remaining pages would fail the same way
+        if abort {
+            break;
+        }
+    }
+    report
+}
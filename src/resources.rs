@@ -0,0 +1,162 @@
+use crate::catalog::NodeId;
+use crate::constants::{FONT, XOBJECT};
+use crate::content::{ContentVisitor, Operation, visit_page_content};
+use crate::document::PDFDocument;
+use crate::error::PDFError::PageNotFound;
+use crate::error::Result;
+use crate::objects::{Dictionary, PDFObject};
+use std::collections::BTreeSet;
+
+/// The resources a page declares but its content never uses, see
+/// [`PDFDocument::unused_resources`].
+#[derive(Clone, Debug, Default)]
+pub struct UnusedResources {
+    /// The index of the page in page order.
+    page: usize,
+    /// The `/Font` names never selected by `Tf`, sorted.
+    fonts: Vec<String>,
+    /// The `/XObject` names never painted by `Do`, sorted.
+    xobjects: Vec<String>,
+}
+
+/// The unused resources of every page, see [`PDFDocument::resource_usage`].
+#[derive(Clone, Debug, Default)]
+pub struct ResourceUsageReport {
+    pages: Vec<UnusedResources>,
+}
+
+impl UnusedResources {
+    /// Returns the index of the page in page order.
+    pub fn get_page(&self) -> usize {
+        self.page
+    }
+
+    /// Returns the names of the unused fonts in sorted order.
+    pub fn get_fonts(&self) -> &[String] {
+        &self.fonts
+    }
+
+    /// Returns the names of the unused XObjects in sorted order.
+    pub fn get_xobjects(&self) -> &[String] {
+        &self.xobjects
+    }
+
+    /// Returns true if the page uses all resources it declares.
+    pub fn is_empty(&self) -> bool {
+        self.fonts.is_empty() && self.xobjects.is_empty()
+    }
+}
+
+impl ResourceUsageReport {
+    /// Returns the unused resources of each page in page order.
+    pub fn get_pages(&self) -> &[UnusedResources] {
+        &self.pages
+    }
+
+    /// Returns the number of unused font entries of all pages.
+    pub fn unused_fonts(&self) -> usize {
+        self.pages.iter().map(|it| it.fonts.len()).sum()
+    }
+
+    /// Returns the number of unused XObject entries of all pages.
+    pub fn unused_xobjects(&self) -> usize {
+        self.pages.iter().map(|it| it.xobjects.len()).sum()
+    }
+}
+
+/// Collects the resource names a page content selects by `Tf` and paints by `Do`.
+///
+/// Names inside a form XObject refer to the resources of the form, unless the form has no
+/// `/Resources` and inherits those of the page.
+#[derive(Default)]
+struct NameUsageVisitor {
+    fonts: BTreeSet<String>,
+    xobjects: BTreeSet<String>,
+    /// Whether each entered form has resources of its own.
+    forms: Vec<bool>,
+}
+
+impl ContentVisitor for NameUsageVisitor {
+    fn visit(&mut self, operation: &Operation) -> Result<()> {
+        if self.forms.iter().any(|it| *it) {
+            return Ok(());
+        }
+        let names = match operation.get_operator() {
+            "Tf" => &mut self.fonts,
+            "Do" => &mut self.xobjects,
+            _ => return Ok(()),
+        };
+        if let Some(name) = operation.get_name_operand(0) {
+            names.insert(name.to_string());
+        }
+        Ok(())
+    }
+
+    fn begin_form(&mut self, _document: &mut PDFDocument, _matrix: [f64; 6], resources: Option<&PDFObject>) -> Result<()> {
+        self.forms.push(resources.is_some());
+        Ok(())
+    }
+
+    fn end_form(&mut self) {
+        self.forms.pop();
+    }
+}
+
+/// Compares the fonts and XObjects a page declares with those its content uses.
+fn page_unused_resources(document: &mut PDFDocument, page: usize, page_id: NodeId) -> Result<UnusedResources> {
+    // Fonts may be written inline, so every name counts and not only the references
+    let fonts = document.read_page_resource_dict(page_id, FONT)?;
+    let xobjects = document.read_page_resource_dict(page_id, XOBJECT)?;
+    let mut visitor = NameUsageVisitor::default();
+    visit_page_content(document, page_id, &mut visitor)?;
+    let unused = |declared: &Dictionary, used: &BTreeSet<String>| {
+        let mut unused = declared
+            .iter()
+            .map(|(name, _)| name)
+            .filter(|it| !used.contains(*it))
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        unused.sort();
+        unused
+    };
+    Ok(UnusedResources {
+        page,
+        fonts: unused(&fonts, &visitor.fonts),
+        xobjects: unused(&xobjects, &visitor.xobjects),
+    })
+}
+
+/// Finds the fonts and XObjects a page declares in its resources but never uses.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `page_index` - The index of the page in page order
+///
+/// # Returns
+///
+/// A `Result` containing the unused resources, or an error if the page is not found or
+/// its content can't be parsed
+pub(crate) fn collect_unused_resources(document: &mut PDFDocument, page_index: usize) -> Result<UnusedResources> {
+    match document.get_page_ids().get(page_index) {
+        Some(page_id) => page_unused_resources(document, page_index, *page_id),
+        None => Err(PageNotFound(format!("Page not found:{}", page_index))),
+    }
+}
+
+/// Finds the unused resources of every page.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// A `Result` containing the report, or an error if the content of a page can't be parsed
+pub(crate) fn collect_resource_usage(document: &mut PDFDocument) -> Result<ResourceUsageReport> {
+    let mut pages = Vec::new();
+    for (index, page_id) in document.get_page_ids().into_iter().enumerate() {
+        pages.push(page_unused_resources(document, index, page_id)?);
+    }
+    Ok(ResourceUsageReport { pages })
+}
@@ -0,0 +1,100 @@
+use crate::objects::{ObjRefTuple, XEntry};
+use std::collections::HashMap;
+
+/// The objects a revision of a document added, changed or deleted, see
+/// [`PDFDocument::revision_changes`].
+///
+/// Revision 0 is the original document, each incremental update appends a revision with its
+/// own cross-reference section. An update listing an object at the place an older revision
+/// already listed it doesn't change the object.
+///
+/// [`PDFDocument::revision_changes`]: crate::document::PDFDocument::revision_changes
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RevisionChanges {
+    revision: usize,
+    /// The objects in use no older revision had in use, by object number.
+    added: Vec<ObjRefTuple>,
+    /// The objects in use an older revision had elsewhere, by object number.
+    changed: Vec<ObjRefTuple>,
+    /// The objects an older revision had in use and this one frees, by object number.
+    deleted: Vec<ObjRefTuple>,
+}
+
+impl RevisionChanges {
+    /// Returns the index of the revision, 0 for the original document.
+    pub fn get_revision(&self) -> usize {
+        self.revision
+    }
+
+    /// Returns the objects the revision added.
+    pub fn get_added(&self) -> &[ObjRefTuple] {
+        &self.added
+    }
+
+    /// Returns the objects the revision redefined, including objects given a new generation.
+    pub fn get_changed(&self) -> &[ObjRefTuple] {
+        &self.changed
+    }
+
+    /// Returns the objects the revision freed, with the generation they had in use.
+    pub fn get_deleted(&self) -> &[ObjRefTuple] {
+        &self.deleted
+    }
+
+    /// Returns true if the revision neither added, changed nor deleted an object.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// Returns true if two in-use entries designate the same object at the same place.
+fn same_location(a: &XEntry, b: &XEntry) -> bool {
+    a.gen_num == b.gen_num && a.value == b.value && a.object_stream == b.object_stream
+}
+
+/// Compares the cross-reference sections of the revisions and sets the revision of each
+/// merged entry to the revision which last added, changed or deleted its object.
+///
+/// # Arguments
+///
+/// * `xrefs` - The merged entries
+/// * `sections` - The entries of each section, the original document first
+///
+/// # Returns
+///
+/// The changes of each revision, the original document first
+pub(crate) fn assign_revisions(xrefs: &mut [XEntry], sections: &[Vec<XEntry>]) -> Vec<RevisionChanges> {
+    // The newest entry in use of each object number
+    let mut current = HashMap::<u32, &XEntry>::new();
+    // The revision which last touched each object number
+    let mut touched = HashMap::<u32, usize>::new();
+    let mut revisions = Vec::with_capacity(sections.len());
+    for (revision, entries) in sections.iter().enumerate() {
+        let mut changes = RevisionChanges { revision, ..Default::default() };
+        for entry in entries.iter().filter(|it| it.obj_num != 0) {
+            let obj_ref = (entry.obj_num, entry.gen_num);
+            match current.get(&entry.obj_num) {
+                Some(previous) if !entry.using => {
+                    changes.deleted.push((previous.obj_num, previous.gen_num));
+                    current.remove(&entry.obj_num);
+                }
+                None if !entry.using => continue,
+                Some(previous) if same_location(previous, entry) => continue,
+                Some(_) => changes.changed.push(obj_ref),
+                None => changes.added.push(obj_ref),
+            }
+            if entry.using {
+                current.insert(entry.obj_num, entry);
+            }
+            touched.insert(entry.obj_num, revision);
+        }
+        changes.added.sort_unstable();
+        changes.changed.sort_unstable();
+        changes.deleted.sort_unstable();
+        revisions.push(changes);
+    }
+    for entry in xrefs.iter_mut() {
+        entry.revision = touched.get(&entry.obj_num).copied().unwrap_or(0);
+    }
+    revisions
+}
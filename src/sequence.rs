@@ -4,7 +4,8 @@ use crate::error::{PDFError, Result};
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 
-pub trait Sequence {
+/// A source of document bytes, it must be `Send` so documents can move between threads.
+pub trait Sequence: Send {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
     /// Read a line data until encounter line delimiter
     fn read_line(&mut self) -> Result<Vec<u8>>;
@@ -12,6 +13,16 @@ pub trait Sequence {
     fn read_line_str(&mut self) -> Result<String>;
     fn seek(&mut self, pos: u64) -> Result<u64>;
     fn size(&self) -> Result<u64>;
+    /// Returns all bytes of the sequence if they are in memory, so ranges can be copied
+    /// directly instead of being read through a buffer.
+    fn as_slice(&self) -> Option<&[u8]> {
+        None
+    }
+    /// Reads the bytes before the start of the sequence, e.g. those preceding the `%PDF-`
+    /// header, the position is undefined until the next seek.
+    fn read_prefix(&mut self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
 }
 
 pub struct FileSequence {
@@ -56,11 +67,9 @@ impl Sequence for FileSequence {
         let mut tmp = 0;
         loop {
             let len = buf.len();
-            for i in tmp..len {
-                if line_ending(buf[i]) {
-                    let line_data = self.split_line_data(i);
-                    return Ok(line_data);
-                }
+            if let Some(i) = buf[tmp..len].iter().position(|b| line_ending(*b)) {
+                let line_data = self.split_line_data(tmp + i);
+                return Ok(line_data);
             }
             tmp = len;
             let n = self.file.read(&mut bytes)?;
@@ -98,3 +107,224 @@ impl Sequence for FileSequence {
         Ok(n)
     }
 }
+
+/// A sequence backed by an in-memory byte buffer, for documents which are already in
+/// memory, e.g. downloaded or embedded, see [`crate::document::PDFDocument::from_bytes`].
+///
+/// It behaves like a [`FileSequence`] over the same bytes, seeking past the end fails with
+/// [`PDFError::SeekExceedError`].
+pub struct MemorySequence {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl MemorySequence {
+    /// Creates a sequence reading the given bytes from the start.
+    pub fn new(buf: Vec<u8>) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Returns the bytes of the sequence.
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl From<Vec<u8>> for MemorySequence {
+    fn from(buf: Vec<u8>) -> Self {
+        Self::new(buf)
+    }
+}
+
+/// Copies bytes of the data from the position on and advances the position.
+fn read_slice(data: &[u8], pos: &mut usize, buf: &mut [u8]) -> usize {
+    let n = min(data.len() - *pos, buf.len());
+    buf[0..n].copy_from_slice(&data[*pos..*pos + n]);
+    *pos += n;
+    n
+}
+
+/// Reads the line at the position like [`FileSequence`] does, skipping the line endings
+/// around it, and advances the position.
+fn read_line_slice(data: &[u8], pos: &mut usize) -> Result<Vec<u8>> {
+    let start = *pos + count_leading_line_endings(&data[*pos..]) as usize;
+    let end = match data[start..].iter().position(|b| line_ending(*b)) {
+        Some(index) => start + index,
+        None => return Err(PDFError::EOFError),
+    };
+    *pos = end + count_leading_line_endings(&data[end..]) as usize;
+    Ok(data[start..end].to_vec())
+}
+
+/// Moves the position, failing beyond the end of the data like [`FileSequence`] does.
+fn seek_slice(data: &[u8], pos: &mut usize, to: u64) -> Result<u64> {
+    if (data.len() as u64) < to {
+        return Err(PDFError::SeekExceedError);
+    }
+    *pos = to as usize;
+    Ok(to)
+}
+
+impl Sequence for MemorySequence {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Ok(read_slice(&self.buf, &mut self.pos, buf))
+    }
+
+    fn read_line(&mut self) -> Result<Vec<u8>> {
+        read_line_slice(&self.buf, &mut self.pos)
+    }
+
+    fn read_line_str(&mut self) -> Result<String> {
+        let buf = self.read_line()?;
+        let text = String::from_utf8(buf)?;
+        Ok(text)
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<u64> {
+        seek_slice(&self.buf, &mut self.pos, pos)
+    }
+
+    fn size(&self) -> Result<u64> {
+        Ok(self.buf.len() as u64)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(&self.buf)
+    }
+}
+
+/// A sequence over a memory-mapped file, for large documents.
+///
+/// Reads copy from the mapping without system calls and seeking is O(1), the operating
+/// system pages the file in as it is read. Creating one is unsafe, as the mapping is only
+/// valid while no one truncates or modifies the file.
+#[cfg(feature = "mmap")]
+pub struct MmapSequence {
+    map: memmap2::Mmap,
+    pos: usize,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapSequence {
+    /// Maps the given file.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified, by this or another process, as long as
+    /// the sequence exists. Otherwise reads are undefined behavior, they may see the changes
+    /// or fail with a bus error.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The file
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the sequence, or an IO error if the file can't be mapped
+    pub unsafe fn new(file: &File) -> Result<Self> {
+        // SAFETY: the caller keeps the file unmodified
+        let map = unsafe { memmap2::Mmap::map(file)? };
+        Ok(Self { map, pos: 0 })
+    }
+
+    /// Opens and maps the file at the given path, see [`MmapSequence::new`].
+    ///
+    /// # Safety
+    ///
+    /// The file must stay unmodified as long as the sequence exists, see
+    /// [`MmapSequence::new`].
+    pub unsafe fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        // SAFETY: the caller keeps the file unmodified
+        unsafe { Self::new(&File::open(path)?) }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Sequence for MmapSequence {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Ok(read_slice(&self.map, &mut self.pos, buf))
+    }
+
+    fn read_line(&mut self) -> Result<Vec<u8>> {
+        read_line_slice(&self.map, &mut self.pos)
+    }
+
+    fn read_line_str(&mut self) -> Result<String> {
+        let buf = self.read_line()?;
+        let text = String::from_utf8(buf)?;
+        Ok(text)
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<u64> {
+        seek_slice(&self.map, &mut self.pos, pos)
+    }
+
+    fn size(&self) -> Result<u64> {
+        Ok(self.map.len() as u64)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(&self.map)
+    }
+}
+
+/// A sequence starting at an offset of another sequence, e.g. at the `%PDF-` header of a
+/// file with bytes before it, whose xref offsets are relative to the header.
+///
+/// Positions and the size are relative to the offset, the bytes before it are only read by
+/// [`Sequence::read_prefix`] once the sequence was seeked.
+pub(crate) struct OffsetSequence<S> {
+    inner: S,
+    offset: u64,
+}
+
+impl<S: Sequence> OffsetSequence<S> {
+    /// Creates a sequence starting at the offset of the inner sequence, reads continue at
+    /// the current position of the inner sequence until the first seek.
+    pub(crate) fn new(inner: S, offset: u64) -> Self {
+        Self { inner, offset }
+    }
+}
+
+impl<S: Sequence> Sequence for OffsetSequence<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.inner.read(buf)
+    }
+
+    fn read_line(&mut self) -> Result<Vec<u8>> {
+        self.inner.read_line()
+    }
+
+    fn read_line_str(&mut self) -> Result<String> {
+        self.inner.read_line_str()
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<u64> {
+        let pos = pos.checked_add(self.offset).ok_or(PDFError::SeekExceedError)?;
+        Ok(self.inner.seek(pos)? - self.offset)
+    }
+
+    fn size(&self) -> Result<u64> {
+        Ok(self.inner.size()?.saturating_sub(self.offset))
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        let data = self.inner.as_slice()?;
+        data.get(self.offset as usize..)
+    }
+
+    fn read_prefix(&mut self) -> Result<Vec<u8>> {
+        self.inner.seek(0)?;
+        let mut prefix = vec![0u8; self.offset as usize];
+        let mut filled = 0;
+        while filled < prefix.len() {
+            let n = self.inner.read(&mut prefix[filled..])?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        prefix.truncate(filled);
+        Ok(prefix)
+    }
+}
@@ -0,0 +1,178 @@
+use crate::constants::{FONT, TYPE};
+use crate::document::PDFDocument;
+use crate::objects::PDFObject;
+use std::collections::HashMap;
+
+/// Statistics about the objects of a document, see [`PDFDocument::stats`].
+#[derive(Default, Debug)]
+pub struct DocumentStats {
+    /// The number of indirect objects by the type of their value.
+    dictionaries: u64,
+    streams: u64,
+    arrays: u64,
+    numbers: u64,
+    strings: u64,
+    names: u64,
+    booleans: u64,
+    nulls: u64,
+    /// The number of in-use objects which can't be parsed.
+    unreadable: u64,
+    /// The number of streams by filter name, a stream counts once per filter.
+    filters: HashMap<String, u64>,
+    /// The sum of the declared stream lengths.
+    stream_bytes: u64,
+    pages: u64,
+    fonts: u64,
+    images: u64,
+    /// The number of in-use and free xref entries.
+    in_use_entries: u64,
+    free_entries: u64,
+}
+
+impl DocumentStats {
+    /// Returns the number of objects whose value is a dictionary.
+    pub fn get_dictionaries(&self) -> u64 {
+        self.dictionaries
+    }
+
+    /// Returns the number of objects whose value is a stream.
+    pub fn get_streams(&self) -> u64 {
+        self.streams
+    }
+
+    /// Returns the number of objects whose value is an array.
+    pub fn get_arrays(&self) -> u64 {
+        self.arrays
+    }
+
+    /// Returns the number of objects whose value is a number.
+    pub fn get_numbers(&self) -> u64 {
+        self.numbers
+    }
+
+    /// Returns the number of objects whose value is a string.
+    pub fn get_strings(&self) -> u64 {
+        self.strings
+    }
+
+    /// Returns the number of objects whose value is a name.
+    pub fn get_names(&self) -> u64 {
+        self.names
+    }
+
+    /// Returns the number of objects whose value is a boolean.
+    pub fn get_booleans(&self) -> u64 {
+        self.booleans
+    }
+
+    /// Returns the number of objects whose value is null.
+    pub fn get_nulls(&self) -> u64 {
+        self.nulls
+    }
+
+    /// Returns the number of in-use objects which can't be parsed.
+    pub fn get_unreadable(&self) -> u64 {
+        self.unreadable
+    }
+
+    /// Returns the number of streams by filter name.
+    pub fn get_filters(&self) -> &HashMap<String, u64> {
+        &self.filters
+    }
+
+    /// Returns the sum of the declared stream lengths.
+    pub fn get_stream_bytes(&self) -> u64 {
+        self.stream_bytes
+    }
+
+    /// Returns the number of pages.
+    pub fn get_pages(&self) -> u64 {
+        self.pages
+    }
+
+    /// Returns the number of font dictionaries.
+    pub fn get_fonts(&self) -> u64 {
+        self.fonts
+    }
+
+    /// Returns the number of image XObjects.
+    pub fn get_images(&self) -> u64 {
+        self.images
+    }
+
+    /// Returns the number of in-use xref entries.
+    pub fn get_in_use_entries(&self) -> u64 {
+        self.in_use_entries
+    }
+
+    /// Returns the number of free xref entries.
+    pub fn get_free_entries(&self) -> u64 {
+        self.free_entries
+    }
+
+    /// Serializes the statistics as JSON in the schema described by [`crate::json`].
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        crate::json::stats_to_json(self)
+    }
+
+    fn count_object(&mut self, object: &PDFObject) {
+        match object {
+            PDFObject::Dict(dict) => {
+                self.dictionaries += 1;
+                if dict.named_value_was(TYPE, FONT) {
+                    self.fonts += 1;
+                }
+            }
+            PDFObject::Stream(stream) => {
+                self.streams += 1;
+                self.stream_bytes += stream.declared_length().unwrap_or(0);
+                for filter in stream.filters() {
+                    *self.filters.entry(filter).or_default() += 1;
+                }
+                if stream.is_image() {
+                    self.images += 1;
+                }
+            }
+            PDFObject::Array(_) => self.arrays += 1,
+            PDFObject::Number(_) => self.numbers += 1,
+            PDFObject::String(_) => self.strings += 1,
+            PDFObject::Named(_) => self.names += 1,
+            PDFObject::Bool(_) => self.booleans += 1,
+            PDFObject::Null => self.nulls += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Collects the statistics in one pass over the xref table, skipping the stream data.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// The statistics; objects which fail to parse are counted as unreadable instead of
+/// failing the pass
+pub(crate) fn collect_stats(document: &mut PDFDocument) -> DocumentStats {
+    let mut stats = DocumentStats {
+        pages: document.get_page_num() as u64,
+        ..Default::default()
+    };
+    document.with_skipped_stream_data(|document| {
+        for index in 0..document.get_xref_slice().len() {
+            if document.get_xref_slice()[index].is_freed() {
+                stats.free_entries += 1;
+                continue;
+            }
+            stats.in_use_entries += 1;
+            match document.read_entry(index) {
+                Ok(Some((_, PDFObject::IndirectObject(_, _, object)))) => stats.count_object(&object),
+                Ok(_) => {}
+                Err(_) => stats.unreadable += 1,
+            }
+        }
+    });
+    stats
+}
@@ -0,0 +1,73 @@
+use crate::document::PDFDocument;
+use crate::objects::{ObjRefTuple, PDFObject};
+use std::ops::Range;
+
+/// The bytes occupied by the stream data of a document, see [`PDFDocument::stream_map`].
+#[derive(Clone, Debug, Default)]
+pub struct StreamMap {
+    /// The stream objects and their data ranges in ascending start order.
+    ranges: Vec<(ObjRefTuple, Range<u64>)>,
+    /// The pairs of streams whose data ranges overlap.
+    overlaps: Vec<(ObjRefTuple, ObjRefTuple)>,
+}
+
+impl StreamMap {
+    /// Returns the stream objects and their data ranges in ascending start order.
+    pub fn get_ranges(&self) -> &[(ObjRefTuple, Range<u64>)] {
+        &self.ranges
+    }
+
+    /// Returns the pairs of streams whose data ranges overlap, a strong sign of corruption.
+    pub fn get_overlaps(&self) -> &[(ObjRefTuple, ObjRefTuple)] {
+        &self.overlaps
+    }
+
+    /// Returns the bytes between the data of consecutive streams, which hold the other
+    /// objects and the stream dictionaries.
+    pub fn gaps(&self) -> Vec<Range<u64>> {
+        self.ranges
+            .windows(2)
+            .filter(|pair| pair[0].1.end < pair[1].1.start)
+            .map(|pair| pair[0].1.end..pair[1].1.start)
+            .collect()
+    }
+}
+
+/// Collects the data ranges of the stream objects of the xref table.
+///
+/// The ranges come from the stream dictionaries and `/Length`, the data itself isn't read. An
+/// object which can't be parsed has no known range and is left out of the map.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+///
+/// # Returns
+///
+/// The stream map
+pub(crate) fn collect_stream_map(document: &mut PDFDocument) -> StreamMap {
+    let mut ranges = Vec::new();
+    document.with_skipped_stream_data(|document| {
+        for index in 0..document.get_xref_slice().len() {
+            if document.get_xref_slice()[index].is_freed() {
+                continue;
+            }
+            if let Ok(Some((entry, PDFObject::IndirectObject(_, _, object)))) = document.read_entry(index)
+                && let Some(range) = object.as_stream().and_then(|it| it.raw_range())
+            {
+                ranges.push(((entry.get_obj_num(), entry.get_gen_num()), range));
+            }
+        }
+    });
+    ranges.sort_by_key(|it| (it.1.start, it.1.end));
+    let mut overlaps = Vec::new();
+    for (index, (obj_ref, range)) in ranges.iter().enumerate() {
+        for (other, other_range) in &ranges[index + 1..] {
+            if other_range.start >= range.end {
+                break;
+            }
+            overlaps.push((*obj_ref, *other));
+        }
+    }
+    StreamMap { ranges, overlaps }
+}
@@ -0,0 +1,1143 @@
+use crate::catalog::NodeId;
+use crate::constants::{
+    BASE_ENCODING, BBOX, DIFFERENCES, ENCODING, FIRST_CHAR, FONT, SUBTYPE, TYPE1, WIDTHS,
+};
+use crate::content::{
+    ContentVisitor, Operation, form_matrix, read_form_xobject, visit_form_xobject, visit_page_content,
+};
+use crate::document::PDFDocument;
+use crate::encoding::{Encoding, PreDefinedEncoding};
+use crate::error::Result;
+use crate::font::{Confidence, TextDecoding, text_decoding};
+use crate::name::Name;
+use crate::objects::{Dictionary, ObjRefTuple, PDFObject};
+use crate::page::PageTransform;
+use std::collections::HashMap;
+
+/// The glyph width in thousandths of text space used when a font declares none.
+const DEFAULT_GLYPH_WIDTH: f64 = 500.0;
+/// The horizontal gap, relative to the font size, from which fragments are separated by a space.
+const WORD_GAP: f64 = 0.15;
+/// The vertical distance, relative to the font size, within which fragments share a line.
+const LINE_TOLERANCE: f64 = 0.5;
+/// The width of a layout column relative to the font size.
+const COLUMN_WIDTH: f64 = 0.5;
+/// The height of the middle of a word box above the baseline, relative to the font size.
+const WORD_MIDDLE: f64 = 0.3;
+
+/// The coordinate space the positions of text fragments are reported in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoordinateSpace {
+    /// Raw PDF user space.
+    #[default]
+    User,
+    /// User space moved so the lower left corner of the MediaBox is the origin.
+    MediaRelative,
+    /// Fractions of the MediaBox width and height, 0 to 1 inside the box.
+    Normalized,
+}
+
+/// How text drawn in an invisible rendering mode (`3 Tr`), e.g. the OCR layer of a scanned
+/// page behind its image, is extracted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvisibleText {
+    /// Extracted like visible text.
+    #[default]
+    Include,
+    /// Left out, e.g. when a document hides junk text.
+    Exclude,
+    /// Extracted, its fragments are flagged by [`TextFragment::is_invisible`].
+    Flag,
+}
+
+/// Options controlling how the text of a page is extracted.
+#[derive(Clone, Debug, Default)]
+pub struct TextExtractionOptions {
+    /// Whether the text is arranged by position instead of in content stream order.
+    layout: bool,
+    /// The coordinate space of the reported fragment positions.
+    coordinate_space: CoordinateSpace,
+    /// Whether right-to-left runs are reordered from visual into logical order.
+    bidi: bool,
+    /// How text in an invisible rendering mode is extracted.
+    invisible_text: InvisibleText,
+}
+
+/// A piece of text shown by a single text-showing operation.
+///
+/// Positions are in user space unless another [`CoordinateSpace`] was requested.
+#[derive(Clone, Debug)]
+pub struct TextFragment {
+    text: String,
+    /// The origin of the first glyph, raised by the text rise.
+    x: f64,
+    y: f64,
+    /// The vertical position of the baseline without the text rise, which groups lines.
+    line_y: f64,
+    /// The advance of all glyphs along the baseline.
+    width: f64,
+    /// The offset along the baseline of the glyph of each character of `text`.
+    offsets: Vec<f64>,
+    /// The font size scaled by the text and transformation matrices.
+    font_size: f64,
+    /// The lowest confidence of the decoded characters.
+    confidence: Confidence,
+    /// The text rendering mode (`Tr`), 0 to 7.
+    rendering_mode: u8,
+    /// Whether the fragment is invisible and the options asked to flag such text.
+    invisible: bool,
+}
+
+impl TextExtractionOptions {
+    /// Creates the default options, extracting text in content stream order.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the text is arranged by position instead of in content stream order.
+    ///
+    /// In layout mode fragments are grouped into lines by their baseline and placed in
+    /// columns by their horizontal position, which keeps tables and multi-column text readable.
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - True for the positioned mode
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_layout(mut self, layout: bool) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Returns true if the text is arranged by position.
+    pub fn is_layout(&self) -> bool {
+        self.layout
+    }
+
+    /// Sets the coordinate space of the fragment positions, the text is unaffected.
+    ///
+    /// # Arguments
+    ///
+    /// * `coordinate_space` - The coordinate space
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_coordinate_space(mut self, coordinate_space: CoordinateSpace) -> Self {
+        self.coordinate_space = coordinate_space;
+        self
+    }
+
+    /// Returns the coordinate space of the fragment positions.
+    pub fn get_coordinate_space(&self) -> CoordinateSpace {
+        self.coordinate_space
+    }
+
+    /// Sets whether right-to-left text, e.g. Hebrew or Arabic, is put into logical order.
+    ///
+    /// Such documents usually show the glyphs of a line from left to right, so the plain
+    /// text has each right-to-left word reversed. This pass reverses the right-to-left runs
+    /// of each line and orders the runs by the direction of the line, which is right-to-left
+    /// if most of its letters are. The fragments of [`Page::fragments_with`] are reordered
+    /// too, the characters within each fragment and the fragments of right-to-left lines.
+    /// This is a simplified form of the Unicode bidirectional algorithm without explicit
+    /// embeddings or mirroring. Off by default.
+    ///
+    /// [`Page::fragments_with`]: crate::page::Page::fragments_with
+    ///
+    /// # Arguments
+    ///
+    /// * `bidi` - True to reorder right-to-left text
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_bidi(mut self, bidi: bool) -> Self {
+        self.bidi = bidi;
+        self
+    }
+
+    /// Returns true if right-to-left text is put into logical order.
+    pub fn is_bidi(&self) -> bool {
+        self.bidi
+    }
+
+    /// Sets how text in an invisible rendering mode is extracted, by default it is
+    /// included so the OCR layers of scanned pages yield their text.
+    ///
+    /// # Arguments
+    ///
+    /// * `invisible_text` - The handling of invisible text
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_invisible_text(mut self, invisible_text: InvisibleText) -> Self {
+        self.invisible_text = invisible_text;
+        self
+    }
+
+    /// Returns how text in an invisible rendering mode is extracted.
+    pub fn get_invisible_text(&self) -> InvisibleText {
+        self.invisible_text
+    }
+}
+
+impl TextFragment {
+    /// Returns the decoded text of the fragment.
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the horizontal position of the fragment origin.
+    pub fn get_x(&self) -> f64 {
+        self.x
+    }
+
+    /// Returns the vertical position of the fragment baseline, raised by the text rise
+    /// (`Ts`) of superscripts and subscripts.
+    pub fn get_y(&self) -> f64 {
+        self.y
+    }
+
+    /// Returns the advance of the fragment along the baseline.
+    pub fn get_width(&self) -> f64 {
+        self.width
+    }
+
+    /// Returns the effective font size of the fragment.
+    pub fn get_font_size(&self) -> f64 {
+        self.font_size
+    }
+
+    /// Returns how reliable the text is, the lowest confidence of its characters. A fragment
+    /// without decoded characters has the confidence of its font.
+    pub fn get_confidence(&self) -> Confidence {
+        self.confidence
+    }
+
+    /// Returns the text rendering mode (`Tr`) the fragment is drawn with, e.g. 0 for
+    /// filled and 3 for invisible glyphs.
+    pub fn get_rendering_mode(&self) -> u8 {
+        self.rendering_mode
+    }
+
+    /// Returns true if the fragment is drawn invisibly and the options flag such text,
+    /// see [`InvisibleText::Flag`].
+    pub fn is_invisible(&self) -> bool {
+        self.invisible
+    }
+
+    /// Returns true if the glyphs are painted, mode 3 neither fills nor strokes them and
+    /// mode 7 only adds them to the clipping path.
+    fn is_painted(&self) -> bool {
+        !matches!(self.rendering_mode, 3 | 7)
+    }
+
+    /// Moves the fragment from user space into the given space, the font size is kept
+    /// in user space units.
+    pub(crate) fn convert(&mut self, transform: &PageTransform, space: CoordinateSpace) {
+        self.line_y = transform.convert((self.x, self.line_y), space).1;
+        (self.x, self.y) = transform.convert((self.x, self.y), space);
+        if space == CoordinateSpace::Normalized {
+            self.width /= transform.get_width();
+            self.offsets.iter_mut().for_each(|it| *it /= transform.get_width());
+        }
+    }
+}
+
+/// A simple font reduced to what is needed to decode and measure text.
+struct Font {
+    /// The text of each single-byte character code.
+    encoding: Encoding,
+    /// The confidence in the text of each single-byte character code.
+    confidences: Vec<Confidence>,
+    /// The confidence of the font as a whole, see [`TextDecoding::confidence`].
+    confidence: Confidence,
+    first_char: usize,
+    /// The glyph widths in thousandths of text space starting at `first_char`.
+    widths: Vec<f64>,
+    /// Composite fonts use two-byte codes which can't be decoded without a `/ToUnicode` map.
+    two_byte: bool,
+}
+
+impl Font {
+    /// A font decoding with the standard encoding, used for missing or unreadable fonts.
+    /// Nothing is known about the font, so its text has low confidence.
+    fn fallback() -> Self {
+        let mut font = Self::with_encoding(PreDefinedEncoding::Standard.get_encoding().clone());
+        font.confidences.fill(Confidence::Low);
+        font.confidence = Confidence::Low;
+        font
+    }
+
+    fn with_encoding(encoding: Encoding) -> Self {
+        Self {
+            encoding,
+            confidences: vec![Confidence::High; 256],
+            confidence: Confidence::High,
+            first_char: 0,
+            widths: Vec::new(),
+            two_byte: false,
+        }
+    }
+
+    /// Builds a font without `/Encoding`, decoding each code by the first step of the chain
+    /// which has text for it: the builtin default, cp1252 and Latin-1. The steps before
+    /// `decoding` are skipped, e.g. a symbolic font is read as Latin-1 right away.
+    ///
+    /// The cp1252 step reads the code by the WinAnsi table, so it only adds text after the
+    /// `StandardEncoding` default of Type1 fonts and is left out after a WinAnsi default.
+    fn with_fallback_chain(decoding: TextDecoding, type1: bool) -> Self {
+        let builtin = match type1 {
+            true => PreDefinedEncoding::Standard,
+            false => PreDefinedEncoding::WinAnsi,
+        };
+        let cp1252 = match decoding {
+            TextDecoding::Builtin if builtin == PreDefinedEncoding::WinAnsi => None,
+            TextDecoding::Latin1 => None,
+            _ => Some(PreDefinedEncoding::WinAnsi.get_encoding()),
+        };
+        let mut font = Self::with_encoding(builtin.get_encoding().clone());
+        font.confidence = decoding.confidence();
+        for code in 0..=255u8 {
+            let index = code as usize;
+            let (chr, confidence) = match font.encoding.lookup(code).filter(|_| decoding == TextDecoding::Builtin) {
+                Some(chr) => (Some(chr), Confidence::Medium),
+                None => {
+                    let chr = cp1252.and_then(|it| it.lookup(code)).or_else(|| latin1(code));
+                    (chr, Confidence::Low)
+                }
+            };
+            font.encoding.set(code, chr);
+            font.confidences[index] = confidence;
+        }
+        font
+    }
+
+    /// Builds the font from its dictionary, resolving indirect `/Encoding` and `/Widths`.
+    ///
+    /// An encoding set by [`PDFDocument::set_font_encoding`] replaces the `/Encoding`.
+    fn load(document: &mut PDFDocument, obj_ref: ObjRefTuple, dict: &Dictionary) -> Result<Self> {
+        let decoding = text_decoding(document, dict);
+        if decoding == TextDecoding::Composite {
+            return Ok(Self { two_byte: true, ..Self::fallback() });
+        }
+        let mut font = if let Some(encoding) = document.get_font_encoding(obj_ref) {
+            Self::with_encoding(encoding.clone())
+        } else {
+            let encoding = match dict.get(ENCODING) {
+                Some(encoding) => resolve(document, encoding)?,
+                None => None,
+            };
+            match encoding {
+                Some(encoding) => Self::with_encoding_object(&encoding),
+                None => Self::with_fallback_chain(decoding, dict.named_value_was(SUBTYPE, TYPE1)),
+            }
+        };
+        font.first_char = dict.get_u64_num(FIRST_CHAR).unwrap_or(0) as usize;
+        let widths = match dict.get(WIDTHS) {
+            Some(widths) => resolve(document, widths)?,
+            None => None,
+        };
+        if let Some(PDFObject::Array(widths)) = widths {
+            font.widths = widths.iter().map(|it| it.as_number().map_or(0.0, |n| n.as_f64())).collect();
+        }
+        Ok(font)
+    }
+
+    /// Builds the font from an `/Encoding` value, either a name or an encoding dictionary.
+    fn with_encoding_object(encoding: &PDFObject) -> Self {
+        match encoding {
+            PDFObject::Named(name) => Self::with_encoding(predefined_encoding(name).clone()),
+            PDFObject::Dict(encoding) => {
+                let base = encoding.get_named_value(BASE_ENCODING).unwrap_or("");
+                let mut table = predefined_encoding(base).clone();
+                if let Some(differences) = encoding.get_array_value(DIFFERENCES) {
+                    table.apply_differences(differences);
+                }
+                Self::with_encoding(table)
+            }
+            _ => Self::fallback(),
+        }
+    }
+
+    /// Splits a string into character codes.
+    fn codes(&self, buf: &[u8]) -> Vec<u32> {
+        if self.two_byte {
+            buf.chunks(2)
+                .map(|it| it.iter().fold(0u32, |code, b| (code << 8) | *b as u32))
+                .collect()
+        } else {
+            buf.iter().map(|b| *b as u32).collect()
+        }
+    }
+
+    /// Returns the text of a code along with the confidence in it.
+    fn decode(&self, code: u32) -> Option<(char, Confidence)> {
+        if self.two_byte {
+            return None;
+        }
+        let chr = self.encoding.lookup(u8::try_from(code).ok()?)?;
+        Some((chr, self.confidences[code as usize]))
+    }
+
+    /// Returns the glyph width of a code in thousandths of text space.
+    fn width(&self, code: u32) -> f64 {
+        (code as usize)
+            .checked_sub(self.first_char)
+            .and_then(|index| self.widths.get(index))
+            .copied()
+            .unwrap_or(DEFAULT_GLYPH_WIDTH)
+    }
+}
+
+/// Reads a byte as Latin-1, control characters have no text.
+fn latin1(code: u8) -> Option<char> {
+    match code {
+        0x20..=0x7E | 0xA0..=0xFF => Some(code as char),
+        _ => None,
+    }
+}
+
+/// Returns the table of a predefined encoding, unknown names fall back to the standard encoding.
+fn predefined_encoding(name: &str) -> &'static Encoding {
+    Encoding::predefined(name).unwrap_or(PreDefinedEncoding::Standard.get_encoding())
+}
+
+/// Returns the value of an object, reading it from the document if it is a reference.
+fn resolve(document: &mut PDFDocument, object: &PDFObject) -> Result<Option<PDFObject>> {
+    match object {
+        PDFObject::ObjectRef(obj_num, gen_num) => {
+            match document.read_object_with_ref((*obj_num, *gen_num))? {
+                Some(PDFObject::IndirectObject(_, _, value)) => Ok(Some(*value)),
+                _ => Ok(None),
+            }
+        }
+        object => Ok(Some(object.clone())),
+    }
+}
+
+/// An affine transformation `[a b c d e f]` as used by the PDF graphics state.
+#[derive(Clone, Copy)]
+struct Matrix([f64; 6]);
+
+impl Matrix {
+    const IDENTITY: Matrix = Matrix([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn translate(tx: f64, ty: f64) -> Self {
+        Matrix([1.0, 0.0, 0.0, 1.0, tx, ty])
+    }
+
+    /// Returns `self × other`, i.e. `self` applied first.
+    fn multiply(&self, other: &Matrix) -> Matrix {
+        let [a, b, c, d, e, f] = self.0;
+        let [oa, ob, oc, od, oe, of] = other.0;
+        Matrix([
+            a * oa + b * oc,
+            a * ob + b * od,
+            c * oa + d * oc,
+            c * ob + d * od,
+            e * oa + f * oc + oe,
+            e * ob + f * od + of,
+        ])
+    }
+
+    /// Maps a point through the matrix.
+    fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// Returns the scale the matrix applies to vertical distances.
+    fn vertical_scale(&self) -> f64 {
+        (self.0[2] * self.0[2] + self.0[3] * self.0[3]).sqrt()
+    }
+
+    /// Returns the scale the matrix applies to horizontal distances.
+    fn horizontal_scale(&self) -> f64 {
+        (self.0[0] * self.0[0] + self.0[1] * self.0[1]).sqrt()
+    }
+}
+
+/// The text state parameters which change the advance, position and painting of glyphs.
+#[derive(Clone, Copy)]
+struct TextSpacing {
+    /// The extra advance after each glyph (`Tc`) in unscaled text space units.
+    char_spacing: f64,
+    /// The extra advance after each single-byte space (`Tw`) in unscaled text space units.
+    word_spacing: f64,
+    /// The horizontal scaling (`Tz`) as a factor, 1 by default.
+    scaling: f64,
+    /// The distance the baseline is raised by (`Ts`) in unscaled text space units.
+    rise: f64,
+    /// The text rendering mode (`Tr`).
+    rendering_mode: u8,
+}
+
+impl Default for TextSpacing {
+    fn default() -> Self {
+        Self { char_spacing: 0.0, word_spacing: 0.0, scaling: 1.0, rise: 0.0, rendering_mode: 0 }
+    }
+}
+
+/// Collects the text fragments of a content stream while tracking the text state.
+struct TextVisitor {
+    /// The fonts of the page followed by those of the entered form XObjects.
+    fonts: Vec<HashMap<String, Font>>,
+    fallback: Font,
+    font: Option<Name>,
+    font_size: f64,
+    leading: f64,
+    spacing: TextSpacing,
+    ctm: Matrix,
+    /// The states saved by `q`, the spacing is part of the graphics state.
+    stack: Vec<(Matrix, TextSpacing)>,
+    text_matrix: Matrix,
+    line_matrix: Matrix,
+    /// The state to restore when leaving each entered form XObject.
+    forms: Vec<FormState>,
+    fragments: Vec<TextFragment>,
+}
+
+/// The graphics state a form XObject is entered with.
+struct FormState {
+    ctm: Matrix,
+    /// The length of the `q` stack.
+    depth: usize,
+    font: Option<Name>,
+    font_size: f64,
+    spacing: TextSpacing,
+}
+
+impl TextVisitor {
+    /// Creates a visitor with the fonts of the content by resource name.
+    fn new(fonts: HashMap<String, Font>) -> Self {
+        Self {
+            fonts: vec![fonts],
+            fallback: Font::fallback(),
+            font: None,
+            font_size: 0.0,
+            leading: 0.0,
+            spacing: TextSpacing::default(),
+            ctm: Matrix::IDENTITY,
+            stack: Vec::new(),
+            text_matrix: Matrix::IDENTITY,
+            line_matrix: Matrix::IDENTITY,
+            forms: Vec::new(),
+            fragments: Vec::new(),
+        }
+    }
+
+    fn move_line(&mut self, tx: f64, ty: f64) {
+        self.line_matrix = Matrix::translate(tx, ty).multiply(&self.line_matrix);
+        self.text_matrix = self.line_matrix;
+    }
+
+    fn next_line(&mut self) {
+        self.move_line(0.0, -self.leading);
+    }
+
+    /// Decodes and positions a string, advancing the text matrix past its glyphs.
+    ///
+    /// Each glyph advances by its width plus the character spacing, and a single-byte
+    /// space additionally by the word spacing, all scaled horizontally. The rise moves the
+    /// glyphs off the baseline without advancing.
+    fn show(&mut self, operand: Option<&PDFObject>) {
+        let buf = match operand.and_then(|it| it.as_string()) {
+            Some(pstr) => pstr.get_buf(),
+            None => return,
+        };
+        let font = self
+            .font
+            .as_ref()
+            .and_then(|name| self.fonts.iter().rev().find_map(|fonts| fonts.get(name.as_str())))
+            .unwrap_or(&self.fallback);
+        let matrix = self.text_matrix.multiply(&self.ctm);
+        let scale = matrix.horizontal_scale();
+        let TextSpacing { char_spacing, word_spacing, scaling, rise, rendering_mode } = self.spacing;
+        let mut text = String::new();
+        let mut offsets = Vec::new();
+        let mut advance = 0.0;
+        let mut confidence = None::<Confidence>;
+        for code in font.codes(buf) {
+            if let Some((chr, chr_confidence)) = font.decode(code) {
+                push_char(&mut text, chr);
+                offsets.resize(text.chars().count(), advance * scale);
+                confidence = Some(confidence.map_or(chr_confidence, |it| it.min(chr_confidence)));
+            }
+            let spacing = match !font.two_byte && code == 32 {
+                true => char_spacing + word_spacing,
+                false => char_spacing,
+            };
+            advance += (font.width(code) / 1000.0 * self.font_size + spacing) * scaling;
+        }
+        let (x, y) = matrix.apply((0.0, rise));
+        self.fragments.push(TextFragment {
+            text,
+            x,
+            y,
+            line_y: matrix.0[5],
+            width: advance * scale,
+            offsets,
+            font_size: self.font_size * matrix.vertical_scale(),
+            confidence: confidence.unwrap_or(font.confidence),
+            rendering_mode,
+            invisible: false,
+        });
+        self.text_matrix = Matrix::translate(advance, 0.0).multiply(&self.text_matrix);
+    }
+}
+
+impl ContentVisitor for TextVisitor {
+    fn visit(&mut self, operation: &Operation) -> Result<()> {
+        let operands = operation.get_operands();
+        let number = |index: usize| operands.get(index).and_then(|it| it.as_number()).map_or(0.0, |it| it.as_f64());
+        match operation.get_operator() {
+            "q" => self.stack.push((self.ctm, self.spacing)),
+            "Q" => {
+                // A Q without a matching q is ignored like by the content reader
+                if let Some(state) = self.stack.pop() {
+                    (self.ctm, self.spacing) = state;
+                }
+            }
+            "cm" => {
+                let matrix = Matrix([number(0), number(1), number(2), number(3), number(4), number(5)]);
+                self.ctm = matrix.multiply(&self.ctm);
+            }
+            "BT" => {
+                self.text_matrix = Matrix::IDENTITY;
+                self.line_matrix = Matrix::IDENTITY;
+            }
+            "Tf" => {
+                self.font = operation.get_name_operand(0).map(Name::new);
+                self.font_size = number(1);
+            }
+            "TL" => self.leading = number(0),
+            "Tc" => self.spacing.char_spacing = number(0),
+            "Tw" => self.spacing.word_spacing = number(0),
+            "Tz" => self.spacing.scaling = number(0) / 100.0,
+            "Ts" => self.spacing.rise = number(0),
+            "Tr" => {
+                // Out of range modes are ignored like other malformed operands
+                if let Ok(mode @ 0..=7) = u8::try_from(number(0) as i64) {
+                    self.spacing.rendering_mode = mode;
+                }
+            }
+            "Td" => self.move_line(number(0), number(1)),
+            "TD" => {
+                self.leading = -number(1);
+                self.move_line(number(0), number(1));
+            }
+            "Tm" => {
+                self.line_matrix = Matrix([number(0), number(1), number(2), number(3), number(4), number(5)]);
+                self.text_matrix = self.line_matrix;
+            }
+            "T*" => self.next_line(),
+            "Tj" => self.show(operands.first()),
+            "'" => {
+                self.next_line();
+                self.show(operands.first());
+            }
+            "\"" => {
+                self.spacing.word_spacing = number(0);
+                self.spacing.char_spacing = number(1);
+                self.next_line();
+                self.show(operands.get(2));
+            }
+            "TJ" => {
+                for item in operands.first().and_then(|it| it.as_array()).unwrap_or_default() {
+                    match item {
+                        PDFObject::Number(adjustment) => {
+                            let tx = -adjustment.as_f64() / 1000.0 * self.font_size * self.spacing.scaling;
+                            self.text_matrix = Matrix::translate(tx, 0.0).multiply(&self.text_matrix);
+                        }
+                        item => self.show(Some(item)),
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn begin_form(&mut self, document: &mut PDFDocument, matrix: [f64; 6], resources: Option<&PDFObject>) -> Result<()> {
+        let refs = document.read_resource_refs(resources, FONT)?;
+        self.fonts.push(load_fonts(document, refs));
+        self.forms.push(FormState {
+            ctm: self.ctm,
+            depth: self.stack.len(),
+            font: self.font.clone(),
+            font_size: self.font_size,
+            spacing: self.spacing,
+        });
+        self.ctm = Matrix(matrix).multiply(&self.ctm);
+        Ok(())
+    }
+
+    fn end_form(&mut self) {
+        self.fonts.pop();
+        if let Some(state) = self.forms.pop() {
+            self.ctm = state.ctm;
+            self.stack.truncate(state.depth);
+            self.font = state.font;
+            self.font_size = state.font_size;
+            self.spacing = state.spacing;
+        }
+    }
+}
+
+/// Appends a character, spelling out ligatures as their letters.
+fn push_char(text: &mut String, chr: char) {
+    match chr {
+        '\u{FB00}' => text.push_str("ff"),
+        '\u{FB01}' => text.push_str("fi"),
+        '\u{FB02}' => text.push_str("fl"),
+        '\u{FB03}' => text.push_str("ffi"),
+        '\u{FB04}' => text.push_str("ffl"),
+        chr => text.push(chr),
+    }
+}
+
+/// Loads fonts by resource name.
+///
+/// A font which can't be read is left out, its text is then decoded with the standard encoding.
+fn load_fonts(document: &mut PDFDocument, refs: HashMap<String, ObjRefTuple>) -> HashMap<String, Font> {
+    let mut fonts = HashMap::new();
+    for (name, obj_ref) in refs {
+        let dict = match document.read_object_with_ref(obj_ref) {
+            Ok(Some(PDFObject::IndirectObject(_, _, value))) => value.into_dict(),
+            _ => None,
+        };
+        if let Some(dict) = dict
+            && let Ok(font) = Font::load(document, obj_ref, &dict)
+        {
+            fonts.insert(name, font);
+        }
+    }
+    fonts
+}
+
+/// Extracts the positioned text fragments of a page in content stream order.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `page_id` - The ID of the page to extract the fragments from
+///
+/// # Returns
+///
+/// A `Result` containing the fragments,
+/// or an error if the page is not found or its content can't be parsed
+pub(crate) fn extract_fragments(document: &mut PDFDocument, page_id: NodeId) -> Result<Vec<TextFragment>> {
+    let refs = document.read_page_resource_refs(page_id, FONT)?;
+    let mut visitor = TextVisitor::new(load_fonts(document, refs));
+    visit_page_content(document, page_id, &mut visitor)?;
+    Ok(visitor.fragments)
+}
+
+/// Extracts the text of a form XObject, e.g. an annotation appearance, inside its `/BBox`.
+///
+/// Words whose middle lies outside the box, like borders or labels drawn around it, are
+/// dropped and the rest is joined in reading order, see [`text_in_quads`].
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document
+/// * `obj_ref` - The reference of the form XObject
+///
+/// # Returns
+///
+/// A `Result` containing the text, `None` if the object isn't a form XObject,
+/// or an error if its content can't be parsed
+pub(crate) fn form_text(document: &mut PDFDocument, obj_ref: ObjRefTuple) -> Result<Option<String>> {
+    let stream = match read_form_xobject(document, obj_ref)? {
+        Some(stream) => stream,
+        None => return Ok(None),
+    };
+    let dict = stream.get_metadata();
+    let bbox = dict
+        .get_array_value(BBOX)
+        .map(|values| values.iter().filter_map(|it| it.as_number()).map(|it| it.as_f64()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let matrix = Matrix(form_matrix(dict));
+    let mut visitor = TextVisitor::new(HashMap::new());
+    visit_form_xobject(document, obj_ref, &HashMap::new(), &mut visitor, &mut Vec::new())?;
+    let text = match bbox.as_slice() {
+        &[x0, y0, x1, y1] => {
+            let quad = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)].map(|point| matrix.apply(point));
+            text_in_quads(&visitor.fragments, &[quad])
+        }
+        _ => stream_text(&visitor.fragments),
+    };
+    Ok(Some(text))
+}
+
+/// Drops or flags the fragments drawn invisibly according to the options.
+///
+/// # Arguments
+///
+/// * `fragments` - The fragments of a page
+/// * `options` - The extraction options
+pub(crate) fn apply_invisible_text(fragments: &mut Vec<TextFragment>, options: &TextExtractionOptions) {
+    match options.get_invisible_text() {
+        InvisibleText::Include => {}
+        InvisibleText::Exclude => fragments.retain(|it| it.is_painted()),
+        InvisibleText::Flag => fragments.iter_mut().for_each(|it| it.invisible = !it.is_painted()),
+    }
+}
+
+/// Joins fragments into text according to the options.
+///
+/// # Arguments
+///
+/// * `fragments` - The fragments in content stream order
+/// * `options` - The extraction options
+///
+/// # Returns
+///
+/// The text, lines are separated by a line feed
+pub(crate) fn fragments_to_text(fragments: &[TextFragment], options: &TextExtractionOptions) -> String {
+    let text = if options.is_layout() {
+        layout_text(fragments)
+    } else {
+        stream_text(fragments)
+    };
+    if options.is_bidi() {
+        text.split('\n').map(logical_order).collect::<Vec<_>>().join("\n")
+    } else {
+        text
+    }
+}
+
+/// The direction class of a character in the simplified bidirectional algorithm.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    LeftToRight,
+    RightToLeft,
+    Neutral,
+}
+
+/// Classifies a character, letters of the Hebrew, Arabic and related scripts are
+/// right-to-left, other letters and all digits left-to-right, everything else neutral.
+fn direction(chr: char) -> Direction {
+    match chr {
+        // Arabic-Indic digits keep their left-to-right order like other numbers
+        '\u{0660}'..='\u{0669}' | '\u{06F0}'..='\u{06F9}' => Direction::LeftToRight,
+        '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}' => {
+            if chr.is_alphabetic() { Direction::RightToLeft } else { Direction::Neutral }
+        }
+        chr if chr.is_alphanumeric() => Direction::LeftToRight,
+        _ => Direction::Neutral,
+    }
+}
+
+/// Returns the direction of a line, right-to-left if most of its letters are, or `None` if
+/// it has no right-to-left letters and needs no reordering.
+fn line_direction(chars: impl Iterator<Item = char>) -> Option<Direction> {
+    let (mut rtl, mut ltr) = (0, 0);
+    for chr in chars {
+        match direction(chr) {
+            Direction::RightToLeft => rtl += 1,
+            Direction::LeftToRight => ltr += 1,
+            Direction::Neutral => {}
+        }
+    }
+    match rtl {
+        0 => None,
+        _ if rtl > ltr => Some(Direction::RightToLeft),
+        _ => Some(Direction::LeftToRight),
+    }
+}
+
+/// Puts a line of text shown from left to right into logical order.
+///
+/// White-space at the start and end of the line stays in place, the characters between
+/// are reordered by [`logical_indices`].
+///
+/// # Arguments
+///
+/// * `line` - The line in visual order
+///
+/// # Returns
+///
+/// The line in logical order, unchanged if it has no right-to-left letters
+fn logical_order(line: &str) -> String {
+    let start = line.len() - line.trim_start().len();
+    let end = line.trim_end().len().max(start);
+    let chars = line[start..end].chars().collect::<Vec<_>>();
+    let Some(line_direction) = line_direction(chars.iter().copied()) else {
+        return line.to_string();
+    };
+    let mut text = String::with_capacity(line.len());
+    text.push_str(&line[..start]);
+    text.extend(logical_indices(&chars, line_direction).into_iter().map(|it| chars[it]));
+    text.push_str(&line[end..]);
+    text
+}
+
+/// Returns the logical order of characters shown from left to right, as indices into
+/// `chars`.
+///
+/// This is a simplified form of the Unicode bidirectional algorithm without explicit
+/// embeddings, mirroring or number classes:
+///
+/// * Neutral characters between two runs of the same direction take it, all other
+///   neutrals take the direction of the line.
+/// * Each right-to-left run is reversed. In a right-to-left line the order of the runs is
+///   reversed as well, so left-to-right runs, e.g. numbers, keep their own order.
+///
+/// # Arguments
+///
+/// * `chars` - The characters in visual order
+/// * `line_direction` - The direction of the line they belong to, see [`line_direction`]
+fn logical_indices(chars: &[char], line_direction: Direction) -> Vec<usize> {
+    let mut directions = chars.iter().map(|it| direction(*it)).collect::<Vec<_>>();
+    let mut index = 0;
+    while index < directions.len() {
+        if directions[index] != Direction::Neutral {
+            index += 1;
+            continue;
+        }
+        let run_end = (index..directions.len()).find(|it| directions[*it] != Direction::Neutral).unwrap_or(directions.len());
+        let before = index.checked_sub(1).map(|it| directions[it]);
+        let after = directions.get(run_end).copied();
+        let resolved = match before == after {
+            true => before.unwrap_or(line_direction),
+            false => line_direction,
+        };
+        directions[index..run_end].fill(resolved);
+        index = run_end;
+    }
+    let resolved = directions.into_iter().enumerate().collect::<Vec<_>>();
+    let mut runs = resolved
+        .chunk_by(|a, b| a.1 == b.1)
+        .map(|run| match run[0].1 {
+            Direction::RightToLeft => run.iter().rev().map(|it| it.0).collect::<Vec<_>>(),
+            _ => run.iter().map(|it| it.0).collect(),
+        })
+        .collect::<Vec<_>>();
+    if line_direction == Direction::RightToLeft {
+        runs.reverse();
+    }
+    runs.into_iter().flatten().collect()
+}
+
+/// Puts the fragments of each line into logical order if the options ask for it, see
+/// [`TextExtractionOptions::with_bidi`].
+///
+/// Lines are found like in content stream order. The characters of each fragment are
+/// reordered along with their offsets by the direction of the whole line, and the fragments
+/// of a right-to-left line are reversed. The positions stay those of the glyphs.
+///
+/// # Arguments
+///
+/// * `fragments` - The fragments of a page in content stream order
+/// * `options` - The extraction options
+pub(crate) fn apply_bidi(fragments: &mut [TextFragment], options: &TextExtractionOptions) {
+    if !options.is_bidi() {
+        return;
+    }
+    let mut start = 0;
+    while start < fragments.len() {
+        let same_line = fragments[start..]
+            .windows(2)
+            .take_while(|pair| {
+                let size = pair[0].font_size.max(pair[1].font_size);
+                (pair[1].line_y - pair[0].line_y).abs() <= size * LINE_TOLERANCE
+            })
+            .count();
+        let line = &mut fragments[start..start + same_line + 1];
+        start += line.len();
+        let Some(line_direction) = line_direction(line.iter().flat_map(|it| it.text.chars())) else {
+            continue;
+        };
+        for fragment in line.iter_mut() {
+            let chars = fragment.text.chars().collect::<Vec<_>>();
+            let indices = logical_indices(&chars, line_direction);
+            fragment.text = indices.iter().map(|it| chars[*it]).collect();
+            if fragment.offsets.len() == chars.len() {
+                fragment.offsets = indices.iter().map(|it| fragment.offsets[*it]).collect();
+            }
+        }
+        if line_direction == Direction::RightToLeft {
+            line.reverse();
+        }
+    }
+}
+
+/// Joins fragments in stream order, starting a new line when the baseline moves.
+fn stream_text(fragments: &[TextFragment]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&TextFragment> = None;
+    for fragment in fragments.iter().filter(|it| !it.text.is_empty()) {
+        if let Some(previous) = previous {
+            let size = previous.font_size.max(fragment.font_size);
+            if (fragment.line_y - previous.line_y).abs() > size * LINE_TOLERANCE {
+                text.push('\n');
+            } else if fragment.x - (previous.x + previous.width) > size * WORD_GAP {
+                push_separator(&mut text, &fragment.text);
+            }
+        }
+        text.push_str(&fragment.text);
+        previous = Some(fragment);
+    }
+    text
+}
+
+/// Groups fragments into lines from top to bottom and places them in columns by position.
+fn layout_text(fragments: &[TextFragment]) -> String {
+    let mut sorted: Vec<&TextFragment> = fragments.iter().filter(|it| !it.text.trim().is_empty()).collect();
+    sorted.sort_by(|a, b| b.line_y.total_cmp(&a.line_y));
+    let mut lines: Vec<Vec<&TextFragment>> = Vec::new();
+    for fragment in sorted {
+        match lines.last_mut() {
+            Some(line) if (line[0].line_y - fragment.line_y).abs() <= line[0].font_size.max(fragment.font_size) * LINE_TOLERANCE => {
+                line.push(fragment)
+            }
+            _ => lines.push(vec![fragment]),
+        }
+    }
+    let left = fragments.iter().map(|it| it.x).fold(f64::INFINITY, f64::min);
+    let mut text = String::new();
+    for mut line in lines {
+        line.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let mut row = String::new();
+        let mut end: Option<f64> = None;
+        for fragment in line {
+            let column = ((fragment.x - left) / (fragment.font_size * COLUMN_WIDTH)).round().max(0.0) as usize;
+            let len = row.chars().count();
+            // Glyphs of one word may be split over several fragments
+            let adjacent = end.is_some_and(|end| fragment.x - end <= fragment.font_size * WORD_GAP);
+            if !adjacent {
+                if column > len {
+                    row.extend(std::iter::repeat_n(' ', column - len));
+                } else if end.is_some() {
+                    push_separator(&mut row, &fragment.text);
+                }
+            }
+            row.push_str(&fragment.text);
+            end = Some(fragment.x + fragment.width);
+        }
+        text.push_str(row.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Separates two words by a space unless one of them already provides it.
+fn push_separator(text: &mut String, next: &str) {
+    if !text.ends_with(' ') && !next.starts_with(' ') {
+        text.push(' ');
+    }
+}
+
+/// A run of non white-space characters of a fragment and the extent of its glyphs.
+struct Word<'a> {
+    text: String,
+    fragment: &'a TextFragment,
+    start: f64,
+    end: f64,
+}
+
+impl Word<'_> {
+    /// Returns the middle of the word box, used to decide whether the word is covered.
+    fn middle(&self) -> (f64, f64) {
+        let fragment = self.fragment;
+        (fragment.x + (self.start + self.end) / 2.0, fragment.y + fragment.font_size * WORD_MIDDLE)
+    }
+}
+
+/// Splits a fragment at white-space into words.
+fn fragment_words(fragment: &TextFragment) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut current: Option<Word> = None;
+    for (index, chr) in fragment.text.chars().enumerate() {
+        let offset = fragment.offsets.get(index).copied().unwrap_or(fragment.width);
+        if chr.is_whitespace() {
+            if let Some(mut word) = current.take() {
+                word.end = offset;
+                words.push(word);
+            }
+            continue;
+        }
+        current
+            .get_or_insert_with(|| Word { text: String::new(), fragment, start: offset, end: fragment.width })
+            .text
+            .push(chr);
+    }
+    words.extend(current);
+    words
+}
+
+/// Orders the corners of a quadrilateral around its centre.
+///
+/// The specification lists `/QuadPoints` counter-clockwise while Acrobat writes the upper
+/// edge first and then the lower edge in the same direction, which crosses the diagonals.
+/// Sorting by angle yields a convex polygon for either order.
+fn order_quad(quad: &[(f64, f64); 4]) -> [(f64, f64); 4] {
+    let cx = quad.iter().map(|it| it.0).sum::<f64>() / 4.0;
+    let cy = quad.iter().map(|it| it.1).sum::<f64>() / 4.0;
+    let mut ordered = *quad;
+    ordered.sort_by(|a, b| (a.1 - cy).atan2(a.0 - cx).total_cmp(&(b.1 - cy).atan2(b.0 - cx)));
+    ordered
+}
+
+/// Returns true if the point lies inside the counter-clockwise ordered quadrilateral.
+fn quad_contains(quad: &[(f64, f64); 4], point: (f64, f64)) -> bool {
+    (0..4).all(|index| {
+        let (ax, ay) = quad[index];
+        let (bx, by) = quad[(index + 1) % 4];
+        (bx - ax) * (point.1 - ay) - (by - ay) * (point.0 - ax) >= 0.0
+    })
+}
+
+/// Returns the text of the words whose middle lies inside one of the quadrilaterals.
+///
+/// The words are joined in reading order, i.e. lines from top to bottom and words from
+/// left to right, glyphs of one word split over several fragments are joined without a space.
+///
+/// # Arguments
+///
+/// * `fragments` - The fragments of the page in user space
+/// * `quads` - The quadrilaterals in user space, the corners may be in any order
+///
+/// # Returns
+///
+/// The covered text, empty if no word is covered
+pub(crate) fn text_in_quads(fragments: &[TextFragment], quads: &[[(f64, f64); 4]]) -> String {
+    let quads = quads.iter().map(order_quad).collect::<Vec<_>>();
+    let mut words = fragments
+        .iter()
+        .flat_map(fragment_words)
+        .filter(|word| quads.iter().any(|quad| quad_contains(quad, word.middle())))
+        .collect::<Vec<_>>();
+    let same_line = |a: &Word, b: &Word| {
+        (a.fragment.line_y - b.fragment.line_y).abs() <= a.fragment.font_size.max(b.fragment.font_size) * LINE_TOLERANCE
+    };
+    words.sort_by(|a, b| b.fragment.line_y.total_cmp(&a.fragment.line_y));
+    let mut lines: Vec<Vec<Word>> = Vec::new();
+    for word in words {
+        match lines.last_mut() {
+            Some(line) if same_line(&line[0], &word) => line.push(word),
+            _ => lines.push(vec![word]),
+        }
+    }
+    let mut text = String::new();
+    for mut line in lines {
+        line.sort_by(|a, b| (a.fragment.x + a.start).total_cmp(&(b.fragment.x + b.start)));
+        let mut end: Option<f64> = None;
+        for word in line {
+            let x = word.fragment.x + word.start;
+            let adjacent = end.is_some_and(|end| x - end <= word.fragment.font_size * WORD_GAP);
+            if !text.is_empty() && !adjacent {
+                text.push(' ');
+            }
+            text.push_str(&word.text);
+            end = Some(word.fragment.x + word.end);
+        }
+    }
+    text
+}
@@ -1,15 +1,31 @@
 use crate::constants::is_key;
 use crate::error::PDFError;
-use crate::error::PDFError::{PDFParseError0};
+use crate::error::PDFError::{PDFParseError0, ResourceLimitExceeded, Unsupported};
 use crate::error::Result;
-use crate::objects::PDFNumber;
+use crate::features::Feature;
+use crate::filter::decode_stream;
+use crate::name::Name;
+use crate::objects::{ObjRefTuple, PDFNumber, Stream, XrefTable};
+use crate::objstm::ObjectStream;
+use crate::options::{ParseOptions, Resource, Warning, WarningCode};
 use crate::sequence::Sequence;
-use crate::tokenizer::Token::{Bool, Delimiter, Eof, Id, Key, Number};
-use crate::utils::{hexdump, line_ending};
+use crate::tokenizer::Token::{Delimiter, Eof, Id, Key, Number};
+use crate::utils::line_ending;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::ops::Range;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The maximum nesting depth of arrays and dictionaries, deeper objects are rejected
+/// instead of overflowing the stack.
+const MAX_NESTING: usize = 256;
+/// The maximum number of consecutive reads holding nothing but white-space, a safety net
+/// against sequences which never run out of white-space.
+const MAX_BLANK_READS: usize = 1 << 20;
 
 /// Common end characters
-const COMMON_END_CHARS: [char; 11] = [
+const COMMON_END_CHARS: [char; 12] = [
     '<',
     '>',
     '(',
@@ -21,76 +37,77 @@ const COMMON_END_CHARS: [char; 11] = [
     '/',
     '[',
     ']',
+    '%',
 ];
 
 pub(crate) struct Tokenizer {
     buf: Vec<u8>,
     token_buf: Vec<Token>,
     sequence: Box<dyn Sequence>,
+    /// The offset of the sequence, i.e. the offset right after the buffered bytes.
+    sequence_pos: u64,
+    options: ParseOptions,
+    /// Malformations recovered from in lenient mode.
+    warnings: Vec<Warning>,
+    /// Whether stream data is skipped instead of read, leaving streams empty.
+    skip_stream_data: bool,
+    /// The number of arrays and dictionaries currently being parsed.
+    depth: usize,
+    /// The number of bytes read by recovery scans so far.
+    recovery_scanned: usize,
+    /// The cross-reference table, used to resolve indirect stream lengths.
+    xrefs: XrefTable,
+    /// The indirect objects currently being parsed, the innermost last.
+    in_flight: Vec<ObjRefTuple>,
+    /// The parsed header and decoded data of the object streams read so far by number.
+    object_streams: HashMap<u32, Arc<(ObjectStream, Vec<u8>)>>,
+    /// The number of indirect objects parsed so far.
+    objects_parsed: u64,
+    /// The number of bytes decoded from streams so far.
+    decoded_bytes: u64,
+    /// The end of the time budget of the parse options.
+    deadline: Option<Instant>,
+    /// The encryption of the document, whose stream data strict mode refuses to decode.
+    encryption: Option<Feature>,
 }
 
 #[derive(PartialEq, Clone)]
 pub(crate) enum Token {
-    Id(String),
-    Bool(bool),
-    Key(String),
+    Id(Name),
+    Key(Name),
     Number(PDFNumber),
-    Delimiter(String),
+    Delimiter(&'static str),
     Eof,
 }
 
 impl Token {
-    pub(crate) fn is_numer(&self) -> bool {
-        if let Number(_) = self {
-            return true;
-        }
-        false
-    }
-
     pub(crate) fn is_u64(&self) -> bool {
-        if let Number(PDFNumber::Unsigned(_)) = self {
-            true
-        } else {
-            false
-        }
-    }
-
-    pub(crate) fn is_id(&self) -> bool {
-        if let Id(_) = self { true } else { false }
-    }
-
-    pub(crate) fn to_string(&self) -> String {
-        match self {
-            Id(id) => id.clone(),
-            Key(key) => key.clone(),
-            Delimiter(delimiter) => delimiter.clone(),
-            Number(PDFNumber::Unsigned(num)) => num.to_string(),
-            Number(PDFNumber::Signed(num)) => num.to_string(),
-            Number(PDFNumber::Real(num)) => num.to_string(),
-            Bool(bool) => bool.to_string(),
-            Eof => "_eof".to_string(),
-        }
+        matches!(self, Number(PDFNumber::Unsigned(_)))
     }
 
     pub(crate) fn as_u64(&self) -> Result<u64> {
         if let Number(PDFNumber::Unsigned(num)) = self {
             return Ok(*num);
         }
-        Err(PDFParseError0(format!("Token can't convert to u64:'{}'",self.to_string())))
+        Err(PDFParseError0(format!("Token can't convert to u64:'{}'",self)))
     }
 
     pub(crate) fn as_u32(&self) -> Result<u32> {
-        if let Number(PDFNumber::Unsigned(num)) = self {
-            return Ok(*num as u32);
+        if let Number(PDFNumber::Unsigned(num)) = self
+            && let Ok(num) = u32::try_from(*num)
+        {
+            return Ok(num);
         }
-        Err(PDFParseError0(format!("Token can't convert to u32:'{}'", self.to_string())))
+        Err(PDFParseError0(format!("Token can't convert to u32:'{}'", self)))
     }
-    
+
     pub(crate) fn as_u16(&self) -> Result<u16> {
-        if let Number(PDFNumber::Unsigned(num)) = self {
-            return Ok(*num as u16);
+        if let Number(PDFNumber::Unsigned(num)) = self
+            && let Ok(num) = u16::try_from(*num)
+        {
+            return Ok(num);
         }
-        Err(PDFParseError0(format!("Token can't convert to u16:'{}'", self.to_string())))
+        Err(PDFParseError0(format!("Token can't convert to u16:'{}'", self)))
     }
 
     pub(crate) fn except<F>(self, func: F) -> Result<Self>
@@ -110,49 +127,261 @@ impl Token {
         }
         false
     }
-    
-    /// Returns true if the token is a delimiter.
-    pub(crate) fn is_delimiter(&self) -> bool {
-        if let Delimiter(_) = self { true } else { false }
-    }
+}
 
-    /// Returns true if the token is a delimiter and the delimiter is the specified string.
-    pub(crate) fn delimiter_was(&self, str: &str) -> bool {
-        if let Delimiter(delimiter) = self {
-            return delimiter == str;
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Id(id) => write!(f, "{}", id),
+            Key(key) => write!(f, "{}", key),
+            Delimiter(delimiter) => write!(f, "{}", delimiter),
+            Number(PDFNumber::Unsigned(num)) => write!(f, "{}", num),
+            Number(PDFNumber::Signed(num)) => write!(f, "{}", num),
+            Number(PDFNumber::Real(num)) => write!(f, "{}", num),
+            Eof => write!(f, "_eof"),
         }
-        false
     }
 }
 
 impl Tokenizer {
     pub(crate) fn new(sequence: impl Sequence + 'static) -> Self {
+        Self::with_options(sequence, ParseOptions::default())
+    }
+
+    pub(crate) fn with_options(sequence: impl Sequence + 'static, options: ParseOptions) -> Self {
+        let deadline = options.get_time_budget().map(|budget| Instant::now() + budget);
         Self {
             sequence: Box::new(sequence),
             buf: Vec::new(),
             token_buf: Vec::new(),
+            sequence_pos: 0,
+            options,
+            warnings: Vec::new(),
+            skip_stream_data: false,
+            depth: 0,
+            recovery_scanned: 0,
+            xrefs: XrefTable::default(),
+            in_flight: Vec::new(),
+            object_streams: HashMap::new(),
+            objects_parsed: 0,
+            decoded_bytes: 0,
+            deadline,
+            encryption: None,
         }
     }
 
-    pub(crate) fn check_next_token<F>(&mut self, func: F) -> Result<bool>
-    where
-        F: FnMut(&Token) -> bool,
-    {
-        self.check_next_token0(true, func)
+    /// Sets the cross-reference table used to resolve indirect stream lengths and
+    /// compressed objects, dropping the object streams decoded for the previous table.
+    pub(crate) fn set_xrefs(&mut self, xrefs: XrefTable) {
+        self.xrefs = xrefs;
+        self.object_streams.clear();
+    }
+
+    /// Returns an object stream decoded before, see [`Tokenizer::cache_object_stream`].
+    pub(crate) fn get_object_stream(&self, obj_num: u32) -> Option<Arc<(ObjectStream, Vec<u8>)>> {
+        self.object_streams.get(&obj_num).cloned()
+    }
+
+    /// Keeps a decoded object stream, so reading several of its objects decodes it once.
+    pub(crate) fn cache_object_stream(&mut self, obj_num: u32, decoded: Arc<(ObjectStream, Vec<u8>)>) {
+        self.object_streams.insert(obj_num, decoded);
+    }
+
+    pub(crate) fn get_xrefs(&self) -> &XrefTable {
+        &self.xrefs
+    }
+
+    /// Marks an indirect object as being parsed until [`Tokenizer::leave_object`].
+    pub(crate) fn enter_object(&mut self, obj_ref: ObjRefTuple) {
+        self.in_flight.push(obj_ref);
+    }
+
+    pub(crate) fn leave_object(&mut self) {
+        self.in_flight.pop();
+    }
+
+    /// Returns true if the indirect object is being parsed, so resolving a reference to it
+    /// would never end.
+    pub(crate) fn is_in_flight(&self, obj_ref: ObjRefTuple) -> bool {
+        self.in_flight.contains(&obj_ref)
+    }
+
+    pub(crate) fn get_options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    pub(crate) fn get_warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub(crate) fn is_skip_stream_data(&self) -> bool {
+        self.skip_stream_data
     }
 
-    pub(crate) fn check_next_token0<F>(&mut self, cache: bool,mut func: F) -> Result<bool>
+    /// Sets whether stream data is skipped instead of read, for passes only needing the
+    /// stream dictionaries.
+    pub(crate) fn set_skip_stream_data(&mut self, skip_stream_data: bool) {
+        self.skip_stream_data = skip_stream_data;
+    }
+
+    /// Fails if the time budget of the parse options is used up.
+    pub(crate) fn check_deadline(&self) -> Result<()> {
+        match (self.deadline, self.options.get_time_budget()) {
+            (Some(deadline), Some(budget)) if Instant::now() > deadline => Err(ResourceLimitExceeded {
+                which: Resource::Time,
+                limit: budget.as_millis() as u64,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts a parsed indirect object against the limit of the parse options.
+    pub(crate) fn charge_object(&mut self) -> Result<()> {
+        self.objects_parsed += 1;
+        if let Some(limit) = self.options.get_max_objects()
+            && self.objects_parsed > limit
+        {
+            return Err(ResourceLimitExceeded { which: Resource::Objects, limit });
+        }
+        self.check_deadline()
+    }
+
+    /// Sets the encryption of the document, see [`Tokenizer::decode_stream`].
+    pub(crate) fn set_encryption(&mut self, encryption: Option<Feature>) {
+        self.encryption = encryption;
+    }
+
+    /// Decodes a stream, counting the decoded bytes against the limit of the parse options.
+    ///
+    /// The data of an encrypted document can't be decrypted, strict mode fails with
+    /// `Unsupported` while lenient mode decodes the encrypted bytes.
+    pub(crate) fn decode_stream(&mut self, stream: &Stream) -> Result<Vec<u8>> {
+        self.check_deadline()?;
+        if let Some(encryption) = &self.encryption
+            && !self.options.is_lenient()
+        {
+            let detail = "stream data can't be decrypted".to_string();
+            return Err(Unsupported { feature: encryption.clone(), detail });
+        }
+        let bytes = decode_stream(stream)?;
+        self.decoded_bytes = self.decoded_bytes.saturating_add(bytes.len() as u64);
+        if let Some(limit) = self.options.get_max_total_decoded_bytes()
+            && self.decoded_bytes > limit
+        {
+            return Err(ResourceLimitExceeded { which: Resource::DecodedBytes, limit });
+        }
+        Ok(bytes)
+    }
+
+    /// Returns the number of bytes read by recovery scans so far.
+    pub(crate) fn get_recovery_scanned(&self) -> usize {
+        self.recovery_scanned
+    }
+
+    /// Drops the warnings recorded after the first `warnings` and resets the bytes read by
+    /// recovery scans, undoing the diagnostics of a pass reporting its problems otherwise.
+    pub(crate) fn restore_diagnostics(&mut self, warnings: usize, recovery_scanned: usize) {
+        self.warnings.truncate(warnings);
+        self.recovery_scanned = recovery_scanned;
+    }
+
+    /// Returns the number of bytes recovery scans may still read.
+    pub(crate) fn recovery_budget(&self) -> usize {
+        self.options.get_max_recovery_scan().saturating_sub(self.recovery_scanned)
+    }
+
+    /// Charges bytes read by a recovery scan against the budget.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset the scan started at, used for the warning
+    /// * `scanned` - The number of bytes read
+    /// * `exhausted` - Whether the scan stopped because the budget ran out
+    pub(crate) fn charge_recovery(&mut self, offset: u64, scanned: usize, exhausted: bool) {
+        self.recovery_scanned = self.recovery_scanned.saturating_add(scanned);
+        if exhausted {
+            let limit = self.options.get_max_recovery_scan();
+            self.warn(offset, format!("Recovery budget of {} bytes is exhausted", limit));
+        }
+    }
+
+    /// Records a warning at the given offset, unless the same warning was recorded before.
+    pub(crate) fn warn(&mut self, offset: u64, message: String) {
+        self.warn_with(offset, WarningCode::Other, message);
+    }
+
+    /// Records a warning of the given kind, see [`Tokenizer::warn`].
+    pub(crate) fn warn_with(&mut self, offset: u64, code: WarningCode, message: String) {
+        let recorded = self
+            .warnings
+            .iter()
+            .any(|it| it.get_offset() == offset && it.get_message() == message);
+        if !recorded {
+            self.warnings.push(Warning::new(offset, code, message));
+        }
+    }
+
+    /// Returns the offset of the next unread byte, ignoring peeked tokens.
+    pub(crate) fn position(&self) -> u64 {
+        self.sequence_pos - self.buf.len() as u64
+    }
+
+    /// Skips white-space and comments and returns the offset of the next token.
+    ///
+    /// With peeked tokens this is the offset right after them.
+    pub(crate) fn next_token_offset(&mut self) -> Result<u64> {
+        if self.token_buf.is_empty() {
+            self.next_chr0(|_| false)?;
+        }
+        Ok(self.position())
+    }
+
+    /// Reads from the sequence into the given buffer, keeping track of the sequence offset.
+    fn read_sequence(&mut self, bytes: &mut [u8]) -> Result<usize> {
+        let n = self.sequence.read(bytes)?;
+        self.sequence_pos += n as u64;
+        Ok(n)
+    }
+
+    /// Enters a nested array or dictionary.
+    ///
+    /// Fails once the nesting exceeds [`MAX_NESTING`], every successful call must be
+    /// paired with [`Tokenizer::leave_nesting`].
+    pub(crate) fn enter_nesting(&mut self) -> Result<()> {
+        if self.depth >= MAX_NESTING {
+            return Err(PDFParseError0(format!("Objects nested deeper than {}", MAX_NESTING)));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Leaves a nested array or dictionary.
+    pub(crate) fn leave_nesting(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Returns the token `index` positions ahead without consuming it.
+    ///
+    /// Peeked tokens are returned by [`Tokenizer::next_token`] in their original order.
+    pub(crate) fn peek_token(&mut self, index: usize) -> Result<&Token> {
+        while self.token_buf.len() <= index {
+            let token = match self.next_chr()? {
+                Some(chr) => self.chr2token(chr)?,
+                None => Eof,
+            };
+            self.token_buf.push(token);
+        }
+        Ok(&self.token_buf[index])
+    }
+
+    /// Checks the next token, it is consumed if it matches and `cache` is false.
+    pub(crate) fn check_next_token0<F>(&mut self, cache: bool, func: F) -> Result<bool>
     where
-        F: FnMut(&Token) -> bool,
+        F: FnOnce(&Token) -> bool,
     {
-        let token = if let Some(chr) = self.next_chr()? {
-            self.chr2token(chr)?
-        } else {
-            Eof
-        };
-        let m = func(&token);
-        if !m || cache {
-            self.token_buf.push(token);
+        let m = func(self.peek_token(0)?);
+        if m && !cache {
+            self.token_buf.remove(0);
         }
         Ok(m)
     }
@@ -169,30 +398,51 @@ impl Tokenizer {
         self.chr2token(option.unwrap())
     }
 
+    /// Reads the name following a `/` delimiter.
+    ///
+    /// The name is read up to the next delimiter, so names starting like a number are read
+    /// as names, e.g. the `/1.7` of a catalog `/Version`. A `/` followed by white-space is
+    /// read like before, as the next token.
+    pub(crate) fn next_name(&mut self) -> Result<Token> {
+        if self.token_buf.is_empty() {
+            let range = self.loop_util(&COMMON_END_CHARS, |_c| Ok(false))?;
+            if !range.is_empty() {
+                let text = Name::from_parts(self.buf[0], &self.buf[1..range.end])?;
+                self.buf.drain(range);
+                return Ok(Id(text));
+            }
+        }
+        self.next_token()
+    }
+
     fn chr2token(&mut self, chr: char) -> Result<Token> {
         let token = match chr {
             '<' => match self.next_chr_was('<') {
-                true => Delimiter(String::from("<<")),
-                false => Delimiter(String::from('<')),
+                true => Delimiter("<<"),
+                false => Delimiter("<"),
             },
             '>' => match self.next_chr_was('>') {
-                true => Delimiter(String::from(">>")),
-                false => Delimiter(String::from(">")),
+                true => Delimiter(">>"),
+                false => Delimiter(">"),
             },
-            '/' | '(' | ')' | '[' | ']' => Delimiter(chr.into()),
+            '/' => Delimiter("/"),
+            '(' => Delimiter("("),
+            ')' => Delimiter(")"),
+            '[' => Delimiter("["),
+            ']' => Delimiter("]"),
             '+' | '-' | '.' => self.num_deco(chr)?,
             chr => {
                 // If the character is a digit, then we need to read the number
-                if chr.is_digit(10) {
+                if chr.is_ascii_digit() {
                     self.num_deco(chr)?
                 }
                 // Identifier
                 else {
                     let range = self.loop_util(&COMMON_END_CHARS, |_c| Ok(false))?;
-                    let mut buf = self.buf.drain(range).collect::<Vec<u8>>();
-                    buf.insert(0, chr as u8);
-                    let text = String::from_utf8(buf)?;
-                    if is_key(text.as_str()) {
+                    // Built from the buffer in place, common and short names don't allocate
+                    let text = Name::from_parts(chr as u8, &self.buf[range.clone()])?;
+                    self.buf.drain(range);
+                    if is_key(&text) {
                         return Ok(Key(text));
                     }
                     Id(text)
@@ -213,16 +463,27 @@ impl Tokenizer {
                 }
                 is_real = true;
             } else {
-                let is_digit = c.is_digit(10);
+                let is_digit = c.is_ascii_digit();
                 if !is_digit {
                     return Err(PDFError::PDFParseError0(format!("Invalid number character: {:0x}", c as u8)));
                 }
             }
-            return Ok(false);
+            Ok(false)
         })?;
-        let mut bytes = self.buf.drain(range).collect::<Vec<u8>>();
-        bytes.insert(0, chr as u8);
-        let text = String::from_utf8(bytes)?;
+        // Assemble the number on the stack unless it's unusually long
+        let tail = &self.buf[range.clone()];
+        let mut stack = [0u8; 32];
+        let heap;
+        let bytes = if tail.len() < stack.len() {
+            stack[0] = chr as u8;
+            stack[1..=tail.len()].copy_from_slice(tail);
+            &stack[..=tail.len()]
+        } else {
+            heap = [&[chr as u8], tail].concat();
+            &heap[..]
+        };
+        // Only signs, digits and dots were accepted, which are ASCII
+        let text = std::str::from_utf8(bytes).unwrap_or_default();
         let value = if is_real {
             PDFNumber::Real(text.parse::<f64>()?)
         } else {
@@ -233,28 +494,52 @@ impl Tokenizer {
                 PDFNumber::Unsigned(text.parse::<u64>()?)
             }
         };
+        self.buf.drain(range);
         Ok(Number(value))
     }
 
-    pub(crate) fn loop_util<F>(&mut self, end_chars: &[char], mut func: F) -> Result<Range<usize>>
+    /// Scans the rest of a token whose first character was already read.
+    pub(crate) fn loop_util<F>(&mut self, end_chars: &[char], func: F) -> Result<Range<usize>>
+    where
+        F: FnMut(char) -> Result<bool>,
+    {
+        let limit = self.options.get_max_token_len();
+        let token_start = self.position().saturating_sub(1);
+        self.loop_util_limited(end_chars, limit, token_start, func)
+    }
+
+    /// Same as [`Tokenizer::loop_util`] for tokens which may be longer than the maximum
+    /// token length, e.g. strings.
+    ///
+    /// Fails with `TokenTooLong` reporting `token_start` once more than `limit` bytes were
+    /// scanned.
+    pub(crate) fn loop_util_limited<F>(
+        &mut self,
+        end_chars: &[char],
+        limit: usize,
+        token_start: u64,
+        mut func: F,
+    ) -> Result<Range<usize>>
     where
         F: FnMut(char) -> Result<bool>,
     {
         let mut index = 0usize;
-        let buf = &mut self.buf;
         'ext: loop {
+            if index > limit {
+                return Err(PDFError::TokenTooLong { limit, at_offset: token_start });
+            }
             // If index is equal to buffer length, then we need to read more data
-            if index == buf.len() {
+            if index == self.buf.len() {
                 let mut bytes = [0u8; 1024];
-                let n = self.sequence.read(&mut bytes)?;
+                let n = self.read_sequence(&mut bytes)?;
                 if n == 0 {
                     return Err(PDFError::EOFError);
                 }
-                buf.extend_from_slice(&bytes[0..n]);
+                self.buf.extend_from_slice(&bytes[0..n]);
             }
-            let len = buf.len();
-            for i in index..len {
-                let chr = char::from(buf[i]);
+            let len = self.buf.len();
+            for (i, b) in self.buf.iter().enumerate().skip(index) {
+                let chr = char::from(*b);
                 if end_chars.contains(&chr) || func(chr)? {
                     index = i;
                     break 'ext;
@@ -266,10 +551,7 @@ impl Tokenizer {
     }
 
     fn next_chr(&mut self) -> Result<Option<char>> {
-        let option = match self.next_chr0(|_| true)? {
-            None => None,
-            Some((_, chr)) => Some(chr),
-        };
+        let option = self.next_chr0(|_| true)?.map(|(_, chr)| chr);
         Ok(option)
     }
 
@@ -280,37 +562,58 @@ impl Tokenizer {
         }
     }
 
-    /// Read next byte
+    /// Read next byte, skipping white-space and comments
+    ///
+    /// A comment runs from `%` to the end of the line and counts as white-space, this
+    /// includes the `%%EOF` marker, the end of a file is located by
+    /// [`PDFDocument`](crate::document::PDFDocument) from the `startxref` keyword instead.
+    ///
+    /// Returns `None` once the sequence is exhausted, also in the middle of white-space.
     fn next_chr0<F>(&mut self, func: F) -> Result<Option<(bool, char)>>
     where
         F: Fn(char) -> bool,
     {
-        let buf = &mut self.buf;
         let mut bytes = [0u8; 1024];
-        if buf.is_empty() {
-            let n = self.sequence.read(&mut bytes)?;
-            if n == 0 {
-                return Ok(None);
+        let mut blank_reads = 0;
+        let mut in_comment = false;
+        loop {
+            if self.buf.is_empty() {
+                if blank_reads >= MAX_BLANK_READS {
+                    return Err(PDFParseError0(format!(
+                        "White-space run at offset {} spans more than {} reads", self.position(), MAX_BLANK_READS
+                    )));
+                }
+                let n = self.read_sequence(&mut bytes)?;
+                if n == 0 {
+                    return Ok(None);
+                }
+                blank_reads += 1;
+                self.buf.extend_from_slice(&bytes[0..n]);
             }
-            buf.extend_from_slice(&bytes[0..n]);
-        }
-        let len = buf.len();
-        let mut skip_cunt = 0;
-        for i in 0..len {
-            let b = buf[i];
-            if line_ending(b) || b == b' ' {
-                skip_cunt += 1;
-            } else {
-                break;
+            if in_comment {
+                match self.buf.iter().position(|b| line_ending(*b)) {
+                    Some(end) => {
+                        self.buf.drain(0..end);
+                        in_comment = false;
+                    }
+                    None => {
+                        self.buf.clear();
+                        continue;
+                    }
+                }
+            }
+            let skip_cunt = self.buf.iter().take_while(|b| line_ending(**b) || **b == b' ').count();
+            if skip_cunt > 0 {
+                self.buf.drain(0..skip_cunt);
+            }
+            // A comment is skipped up to its line ending, an empty buffer needs more data
+            match self.buf.first() {
+                Some(b'%') => in_comment = true,
+                Some(_) => break,
+                None => {}
             }
         }
-        if skip_cunt > 0 {
-            buf.drain(0..skip_cunt);
-        }
-        // If buffer is empty, then we need to read more data
-        if buf.is_empty() {
-            return self.next_chr0(func);
-        }
+        let buf = &mut self.buf;
         let b = buf[0];
         let chr = char::from(b);
         let equal = func(chr);
@@ -322,35 +625,67 @@ impl Tokenizer {
 
     pub(crate) fn seek(&mut self, offset: u64) -> Result<u64> {
         let n = self.sequence.seek(offset)?;
+        self.sequence_pos = n;
         self.token_buf.clear();
         self.buf.clear();
         Ok(n)
     }
 
+    /// Returns the size of the underlying sequence.
+    pub(crate) fn size(&self) -> Result<u64> {
+        self.sequence.size()
+    }
+
+    /// Reads the bytes before the start of the document, e.g. junk preceding the `%PDF-`
+    /// header, and seeks back to the start.
+    pub(crate) fn read_prefix(&mut self) -> Result<Vec<u8>> {
+        let prefix = self.sequence.read_prefix()?;
+        self.seek(0)?;
+        Ok(prefix)
+    }
+
     pub(crate) fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
-        let buf_len = self.buf.len();
-        let buf = if buf_len >= len {
-            self.buf.drain(0..len).collect::<Vec<u8>>()
-        } else {
-            let diff = len - buf_len;
-            let mut bytes = vec![0u8; diff];
-            let n = self.sequence.read(&mut bytes)?;
-            let mut buf = Vec::<u8>::new();
-            buf.extend_from_slice(&self.buf);
-            buf.extend_from_slice(&bytes[0..n]);
-            // Should clear buffer
-            self.buf.clear();
-            buf
-        };
-        // #[cfg(debug_assertions)]
-        // {
-        //     hexdump(&buf);
-        // }
+        // A sequence in memory is copied from once instead of through the buffer
+        if self.buf.len() < len
+            && let Some(data) = self.sequence.as_slice()
+        {
+            let start = (self.sequence_pos as usize).min(data.len());
+            let end = start.saturating_add(len - self.buf.len()).min(data.len());
+            let mut bytes = Vec::with_capacity(self.buf.len() + end - start);
+            bytes.append(&mut self.buf);
+            bytes.extend_from_slice(&data[start..end]);
+            self.sequence_pos = self.sequence.seek(end as u64)?;
+            self.token_buf.clear();
+            return Ok(bytes);
+        }
+        let n = self.fill_buf(len)?.min(len);
+        let buf = self.buf.drain(0..n).collect::<Vec<u8>>();
         // Clear token buffer
         self.token_buf.clear();
         Ok(buf)
     }
 
+    /// Reads from the sequence until at least `len` bytes are buffered or the end is reached.
+    ///
+    /// Return the number of buffered bytes
+    pub(crate) fn fill_buf(&mut self, len: usize) -> Result<usize> {
+        while self.buf.len() < len {
+            // Read in bounded chunks, a bogus length must not allocate up front
+            let mut bytes = vec![0u8; (len - self.buf.len()).clamp(1024, 1024 * 1024)];
+            let n = self.read_sequence(&mut bytes)?;
+            if n == 0 {
+                break;
+            }
+            self.buf.extend_from_slice(&bytes[0..n]);
+        }
+        Ok(self.buf.len())
+    }
+
+    /// Returns the buffered bytes which are not consumed yet.
+    pub(crate) fn peek_buf(&self) -> &[u8] {
+        &self.buf
+    }
+
     pub(crate) fn drain_from_buf(&mut self, range: Range<usize>) -> Vec<u8> {
         self.buf.drain(range).collect()
     }
@@ -372,3 +707,63 @@ impl Tokenizer {
         Ok(count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::MemorySequence;
+
+    /// Yields the given number of spaces one byte per read, then EOF.
+    struct TrickleSequence {
+        remaining: usize,
+    }
+
+    impl Sequence for TrickleSequence {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            if self.remaining == 0 || buf.is_empty() {
+                return Ok(0);
+            }
+            self.remaining -= 1;
+            buf[0] = b' ';
+            Ok(1)
+        }
+
+        fn read_line(&mut self) -> Result<Vec<u8>> {
+            Err(PDFError::EOFError)
+        }
+
+        fn read_line_str(&mut self) -> Result<String> {
+            Err(PDFError::EOFError)
+        }
+
+        fn seek(&mut self, pos: u64) -> Result<u64> {
+            Ok(pos)
+        }
+
+        fn size(&self) -> Result<u64> {
+            Ok(10_000)
+        }
+    }
+
+    /// Tests that a white-space tail delivered in tiny chunks ends in `None` without recursion.
+    #[test]
+    fn test_whitespace_tail() {
+        let mut tokenizer = Tokenizer::new(TrickleSequence { remaining: 10_000 });
+        assert_eq!(tokenizer.next_chr().unwrap(), None);
+        assert_eq!(tokenizer.position(), 10_000);
+        let mut tokenizer = Tokenizer::new(TrickleSequence { remaining: 10_000 });
+        assert!(tokenizer.next_token().unwrap() == Token::Eof);
+    }
+
+    /// Tests that comments end tokens and are skipped like white-space, also at the end.
+    #[test]
+    fn test_comments() {
+        let bytes = b"1%one\n%two\r\n/Name%three\n[%four\n]\n%%EOF";
+        let mut tokenizer = Tokenizer::new(MemorySequence::new(bytes.to_vec()));
+        let tokens = std::iter::from_fn(|| Some(tokenizer.next_token().unwrap()).filter(|it| *it != Token::Eof))
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["1", "/", "Name", "[", "]"]);
+        assert_eq!(tokenizer.position(), bytes.len() as u64);
+    }
+}
@@ -0,0 +1,71 @@
+//! Spans around the parse phases, emitted through `tracing` when the `tracing` feature is
+//! enabled and compiled to nothing otherwise.
+//!
+//! Every span is at the `INFO` level and declares its fields up front, they are recorded
+//! once the numbers are known. Durations are measured by the subscriber from entering and
+//! leaving the span.
+
+/// Creates a [`Span`] with the given name and fields, e.g.
+/// `span!("pdf.xref_section", entries)`.
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($name:literal $(, $field:ident)*) => {
+        $crate::trace::Span(tracing::info_span!($name $(, $field = tracing::field::Empty)*))
+    };
+}
+
+/// Creates a [`Span`] with the given name and fields, e.g.
+/// `span!("pdf.xref_section", entries)`.
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($name:literal $(, $field:ident)*) => {
+        $crate::trace::Span
+    };
+}
+
+pub(crate) use span;
+
+/// A span around a parse phase, see [`span`].
+#[cfg(feature = "tracing")]
+pub(crate) struct Span(pub(crate) tracing::Span);
+
+/// A span around a parse phase, see [`span`].
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+/// The guard returned by [`Span::enter`], the span is left when it is dropped.
+#[cfg(feature = "tracing")]
+pub(crate) type Entered<'a> = tracing::span::Entered<'a>;
+
+/// The guard returned by [`Span::enter`], the span is left when it is dropped.
+#[cfg(not(feature = "tracing"))]
+pub(crate) type Entered<'a> = std::marker::PhantomData<&'a ()>;
+
+impl Span {
+    /// Enters the span until the returned guard is dropped.
+    pub(crate) fn enter(&self) -> Entered<'_> {
+        #[cfg(feature = "tracing")]
+        return self.0.enter();
+        #[cfg(not(feature = "tracing"))]
+        return std::marker::PhantomData;
+    }
+
+    /// Records the value of a field declared when the span was created.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    /// * `value` - The value, a count or a size in bytes
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn record(&self, field: &'static str, value: u64) {
+        #[cfg(feature = "tracing")]
+        self.0.record(field, value);
+    }
+
+    /// Records the value of a field holding a name, e.g. the filter of a stream.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn record_str(&self, field: &'static str, value: &str) {
+        #[cfg(feature = "tracing")]
+        self.0.record(field, value);
+    }
+}
@@ -1,7 +1,5 @@
-use std::cmp::min;
-use crate::error::PDFError::XrefEntryNotFound;
+use crate::error::PDFError::PDFParseError0;
 use crate::error::Result;
-use crate::objects::XEntry;
 
 /// Maps a hexadecimal character to its corresponding numeric value.
 ///
@@ -16,8 +14,8 @@ use crate::objects::XEntry;
 macro_rules! hex_map {
     ($hex:ident,$(($val:literal, $char:literal)),+) => {
         match $hex {
-            $($char => $val,)+
-            _=> panic!("Invalid hex byte")
+            $($char => Some($val),)+
+            _=> None
         }
     };
 }
@@ -45,13 +43,13 @@ macro_rules! hex_convert {
         ///
         /// # Returns
         ///
-        /// The combined byte value
-        pub(crate) fn hex2byte(lsb: u8 ,msb: u8)-> u8 {
+        /// The combined byte value, or `None` if a character is not a hexadecimal digit
+        pub(crate) fn hex2byte(lsb: u8 ,msb: u8)-> Option<u8> {
            let lsb = char::from(lsb);
            let msb = char::from(msb);
-           let lv =  hex_map!(lsb, $(($val, $char)),+);
-           let mv =  hex_map!(msb, $(($val, $char)),+);
-           return lv | (mv<< 4);
+           let lv: u8 =  hex_map!(lsb, $(($val, $char)),+)?;
+           let mv: u8 =  hex_map!(msb, $(($val, $char)),+)?;
+           return Some(lv | (mv<< 4));
         }
     }
 }
@@ -91,15 +89,16 @@ hex_convert!(
 ///
 /// # Returns
 ///
-/// The parsed u64 value
-pub(crate) fn literal_to_u64(bytes: &[u8]) -> u64 {
-    let len = bytes.len();
-    let mut value: u64 = 0;
-    for i in 0..len {
-        let b = bytes[i] - 48;
-        value = (value * 10) + b as u64;
+/// The parsed u64 value, or `None` if the slice is empty, holds another byte or the value
+/// overflows
+pub(crate) fn literal_to_u64(bytes: &[u8]) -> Option<u64> {
+    if bytes.is_empty() {
+        return None;
     }
-    value
+    bytes.iter().try_fold(0u64, |value, b| {
+        let digit = (*b as char).to_digit(10)?;
+        value.checked_mul(10)?.checked_add(digit as u64)
+    })
 }
 
 /// Counts the number of leading line ending characters in a byte slice.
@@ -115,14 +114,7 @@ pub(crate) fn literal_to_u64(bytes: &[u8]) -> u64 {
 ///
 /// The count of leading line ending characters
 pub(crate) fn count_leading_line_endings(bytes: &[u8]) -> u64 {
-    let mut count = 0u64;
-    for i in 0..bytes.len() {
-        if !line_ending(bytes[i]) {
-            break;
-        }
-        count += 1;
-    }
-    count
+    bytes.iter().take_while(|b| line_ending(**b)).count() as u64
 }
 
 /// Checks if a byte represents a line ending character.
@@ -142,10 +134,18 @@ pub(crate) fn line_ending(b: u8) -> bool {
     b == b'\r' || b == b'\n'
 }
 
+/// Checks if a byte is a PDF white-space character: NUL, tab, line feed, form feed,
+/// carriage return or space.
+#[inline]
+pub(crate) fn is_white_space(b: u8) -> bool {
+    matches!(b, b'\0' | b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
 /// Converts a hexadecimal string representation to a vector of bytes.
 ///
 /// Takes a byte slice containing hexadecimal characters and converts pairs
-/// of characters to their corresponding byte values.
+/// of characters to their corresponding byte values. White-space is ignored and
+/// a missing last digit is taken as `0`.
 ///
 /// # Arguments
 ///
@@ -153,85 +153,77 @@ pub(crate) fn line_ending(b: u8) -> bool {
 ///
 /// # Returns
 ///
-/// A vector of bytes representing the parsed hexadecimal values
-pub(crate) fn hex2bytes(bytes: &[u8])-> Vec<u8>{
-    let len = bytes.len();
-    let mut buf = Vec::new();
-    if len == 0 {
-        return buf;
-    }
-    for i in (0..len).step_by(2) {
-        let msb = bytes[i];
-        let lsb = if i < len - 1 {
-            bytes[i + 1]
-        } else {
-            b'0'
-        };
-        let value = hex2byte(lsb, msb);
-        buf.push(value);
+/// A `Result` containing the parsed bytes, or an error if a character is not a hexadecimal digit
+pub(crate) fn hex2bytes(bytes: &[u8])-> Result<Vec<u8>>{
+    let digits = bytes.iter().filter(|b| !is_white_space(**b)).copied().collect::<Vec<u8>>();
+    let mut buf = Vec::with_capacity(digits.len().div_ceil(2));
+    for pair in digits.chunks(2) {
+        let msb = pair[0];
+        let lsb = pair.get(1).copied().unwrap_or(b'0');
+        match hex2byte(lsb, msb) {
+            Some(value) => buf.push(value),
+            None => {
+                let invalid = pair.iter().find(|it| !it.is_ascii_hexdigit()).copied().unwrap_or(msb);
+                return Err(PDFParseError0(format!("Invalid hexadecimal digit {:?}", char::from(invalid))));
+            }
+        }
     }
-    buf
+    Ok(buf)
 }
 
-/// Utility function to dump a byte slice in hexadecimal and output it to stdout.
-///
-/// This function formats a byte slice as a hexadecimal dump similar to tools like xxd or hexdump,
-/// showing both hexadecimal representation and ASCII representation of the bytes.
-///
-/// # Arguments
-///
-/// * `bytes` - A slice of bytes to dump in hexadecimal format
-pub(crate) fn hexdump(bytes: &[u8]) {
-    let len = bytes.len();
-    let groups = len / 16 + if len % 16 == 0 { 0 } else { 1 };
-    let mut hex = Vec::<String>::new();
-    let mut ascii = [' '; 16];
-    for group in 0..groups {
-        let offset = group * 16;
-        let bound = min(offset + 16, len);
-        for i in offset..bound {
-            hex.push(format!("{:02x}",bytes[i]));
-            let chr =  bytes[i] as char;
-            if chr.is_ascii_graphic() {
-                ascii[i - offset] = chr;
-            }else {
-                ascii[i - offset] = '.';
+/// Resolves the escape sequences of a literal string body, e.g. `\n`, `\(` or `\053`.
+///
+/// An escaped end-of-line continues the string on the next line and unescaped
+/// end-of-line markers are normalized to a single line feed. Octal escapes take up to
+/// three digits and keep the low byte of larger values like `\777`, a backslash before
+/// any other character is dropped.
+pub(crate) fn unescape_literal(bytes: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        i += 1;
+        if b == b'\r' {
+            buf.push(b'\n');
+            if bytes.get(i) == Some(&b'\n') {
+                i += 1;
             }
+            continue;
+        }
+        if b != b'\\' || i == bytes.len() {
+            buf.push(b);
+            continue;
         }
-        let len = hex.len();
-        if len < 16 {
-            hex.extend(vec!["  ".to_string(); 16 - len]);
+        let escaped = bytes[i];
+        i += 1;
+        match escaped {
+            b'n' => buf.push(b'\n'),
+            b'r' => buf.push(b'\r'),
+            b't' => buf.push(b'\t'),
+            b'b' => buf.push(0x08),
+            b'f' => buf.push(0x0C),
+            b'0'..=b'7' => {
+                let mut value = (escaped - b'0') as u32;
+                let mut digits = 1;
+                while digits < 3 && i < bytes.len() && (b'0'..=b'7').contains(&bytes[i]) {
+                    value = value * 8 + (bytes[i] - b'0') as u32;
+                    i += 1;
+                    digits += 1;
+                }
+                // The high-order overflow is ignored
+                buf.push(value as u8);
+            }
+            b'\r' => {
+                if bytes.get(i) == Some(&b'\n') {
+                    i += 1;
+                }
+            }
+            b'\n' => {}
+            // `\\`, `\(`, `\)` and unknown escapes stand for the character itself
+            escaped => buf.push(escaped),
         }
-        let (left, right) = hex.split_at(8);
-        println!("{:08x}  {}  {}  |{}|", offset, left.join(" "), right.join(" "), ascii.iter().collect::<String>());
-        hex.clear();
-        ascii.fill('.');
     }
-}
-
-/// Searches for an XRef entry that matches the given object reference.
-///
-/// This function iterates through the provided XRef entries to find one that
-/// matches both the object number and generation number of the given reference.
-///
-/// # Arguments
-///
-/// * `xrefs` - A slice of XRef entries to search through
-/// * `obj_ref` - A tuple containing the object number and generation number to search for
-///
-/// # Returns
-///
-/// * `Ok(&XEntry)` - A reference to the matching XRef entry if found
-/// * `Err(Error)` - An error if no matching entry is found, with a message indicating
-///                  the object number and generation number that could not be found
-///
-/// # Errors
-///
-/// Returns an XrefEntryNotFound error if no XRef entry matches the given object reference.
-pub(crate) fn xrefs_search(xrefs: &[XEntry], obj_ref: (u32, u16)) -> Result<&XEntry> {
-    xrefs.iter()
-        .find(|x| x.obj_num == obj_ref.0 && x.gen_num == obj_ref.1)
-        .ok_or_else(|| XrefEntryNotFound(obj_ref.0, obj_ref.1))
+    buf
 }
 
 #[cfg(test)]
@@ -245,10 +237,23 @@ mod tests {
     #[test]
     fn test_hex2bytes(){
         let hex = "012F3D4C".as_bytes();
-        let buf = hex2bytes(hex);
+        let buf = hex2bytes(hex).unwrap();
         assert_eq!(buf, [0x01, 0x2F, 0x3D, 0x4c]);
         let hex = "012F3D4".as_bytes();
         // Test if the last byte is not a hex digit
-        assert_eq!(hex2bytes(hex), [0x01, 0x2F, 0x3D, 0x40])
+        assert_eq!(hex2bytes(hex).unwrap(), [0x01, 0x2F, 0x3D, 0x40]);
+        // White-space is ignored, other characters are rejected
+        assert_eq!(hex2bytes(b"01 2F\n3D\x004").unwrap(), [0x01, 0x2F, 0x3D, 0x40]);
+        assert!(hex2bytes(b"0G").is_err());
+    }
+
+    /// Tests that only non-empty digit sequences within range are parsed.
+    #[test]
+    fn test_literal_to_u64() {
+        assert_eq!(literal_to_u64(b"0012345"), Some(12345));
+        assert_eq!(literal_to_u64(b"18446744073709551615"), Some(u64::MAX));
+        assert_eq!(literal_to_u64(b"18446744073709551616"), None);
+        assert_eq!(literal_to_u64(b" 12"), None);
+        assert_eq!(literal_to_u64(b""), None);
     }
 }
\ No newline at end of file
@@ -0,0 +1,581 @@
+use crate::catalog::NodeId;
+use crate::constants::{ANNOTS, FONT, MEDIA_BOX, PAGES, RESOURCES, VERSION};
+use crate::document::PDFDocument;
+use crate::duplicates::collect_duplicate_definitions;
+use crate::error::PDFError::StreamLengthMismatch;
+use crate::error::Result;
+use crate::known_keys::KeyContext;
+use crate::objects::{Dictionary, ObjRefTuple, PDFObject};
+use crate::vpdf::PDFVersion;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The category of a validation finding.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Category {
+    /// An xref entry points outside of the file.
+    XrefOffset,
+    /// The trailer `/Size` disagrees with the objects of the xref table.
+    TrailerSize,
+    /// A page tree node declares a `/Count` different from its leaf pages.
+    PageCount,
+    /// An object refers to an object missing from the xref table.
+    DanglingReference,
+    /// A stream declares a `/Length` that doesn't end at `endstream`.
+    StreamLength,
+    /// An outline item declares a `/Count` different from its visible descendants.
+    OutlineCount,
+    /// A required key is missing.
+    MissingKey,
+    /// An object can't be parsed.
+    UnreadableObject,
+    /// An object is defined more than once in the file.
+    DuplicateObject,
+    /// A dictionary has a key the specification doesn't define for its type, see
+    /// [`VerifyOptions::with_unknown_keys`].
+    UnknownKey,
+}
+
+/// The severity of a validation finding.
+#[derive(PartialEq, PartialOrd, Clone, Copy, Debug)]
+pub enum Severity {
+    /// Unusual but harmless.
+    Info,
+    /// Tolerated by most readers but not conforming.
+    Warning,
+    /// The document is broken at this location.
+    Error,
+}
+
+impl Category {
+    /// Returns the stable snake case code of the category, e.g. `dangling_reference`, used
+    /// by the JSON output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Category::XrefOffset => "xref_offset",
+            Category::TrailerSize => "trailer_size",
+            Category::PageCount => "page_count",
+            Category::DanglingReference => "dangling_reference",
+            Category::StreamLength => "stream_length",
+            Category::OutlineCount => "outline_count",
+            Category::MissingKey => "missing_key",
+            Category::UnreadableObject => "unreadable_object",
+            Category::DuplicateObject => "duplicate_object",
+            Category::UnknownKey => "unknown_key",
+        }
+    }
+}
+
+impl Severity {
+    /// Returns the stable lowercase code of the severity, e.g. `warning`, used by the JSON
+    /// output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// Options selecting the optional checks of [`PDFDocument::verify_with`].
+#[derive(Clone, Debug, Default)]
+pub struct VerifyOptions {
+    /// Whether keys undefined for the type of a dictionary are reported.
+    unknown_keys: bool,
+}
+
+impl VerifyOptions {
+    /// Creates the default options, running the structural checks only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether keys the specification doesn't define are reported.
+    ///
+    /// The keys of the catalog, the page tree nodes, the pages and their fonts and
+    /// annotations are compared with the keys defined up to the version of the document,
+    /// the header version or a newer catalog `/Version`. Each unknown key is reported once
+    /// per dictionary type as an informational finding with its number of occurrences and
+    /// the first object having it, e.g. keys of proprietary producers.
+    ///
+    /// # Arguments
+    ///
+    /// * `unknown_keys` - True to report unknown keys
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_unknown_keys(mut self, unknown_keys: bool) -> Self {
+        self.unknown_keys = unknown_keys;
+        self
+    }
+
+    /// Returns true if unknown keys are reported.
+    pub fn is_unknown_keys(&self) -> bool {
+        self.unknown_keys
+    }
+}
+
+/// A single issue found by [`PDFDocument::verify`].
+pub struct Finding {
+    category: Category,
+    severity: Severity,
+    message: String,
+    /// The object the finding is about, if any.
+    object: Option<ObjRefTuple>,
+    /// The byte offset the finding is about, if any.
+    offset: Option<u64>,
+}
+
+/// The result of a structural validation of a document.
+pub struct ValidationReport {
+    findings: Vec<Finding>,
+}
+
+impl Finding {
+    pub(crate) fn new(
+        category: Category,
+        severity: Severity,
+        message: String,
+        object: Option<ObjRefTuple>,
+        offset: Option<u64>,
+    ) -> Self {
+        Self { category, severity, message, object, offset }
+    }
+
+    /// Returns the category of the finding.
+    pub fn get_category(&self) -> Category {
+        self.category
+    }
+
+    /// Returns the severity of the finding.
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the human-readable description of the finding.
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the object the finding is about, if any.
+    pub fn get_object(&self) -> Option<ObjRefTuple> {
+        self.object
+    }
+
+    /// Returns the byte offset the finding is about, if any.
+    pub fn get_offset(&self) -> Option<u64> {
+        self.offset
+    }
+}
+
+impl ValidationReport {
+    /// Returns all findings in the order they were detected.
+    pub fn get_findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    /// Returns true if nothing was found.
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns the findings of the given category.
+    pub fn findings_of(&self, category: Category) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(move |it| it.category == category)
+    }
+
+    /// Returns true if any finding has at least the given severity.
+    pub fn has_severity(&self, severity: Severity) -> bool {
+        self.findings.iter().any(|it| it.severity >= severity)
+    }
+
+    /// Serializes the report as JSON in the schema described by [`crate::json`], for tools
+    /// gating on the findings. The `Display` output is meant for humans and may change.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        crate::json::report_to_json(self)
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:?}] {:?}: {}", self.severity, self.category, self.message)?;
+        if let Some((obj_num, gen_num)) = self.object {
+            write!(f, " (object {} {})", obj_num, gen_num)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " (offset {})", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for finding in &self.findings {
+            writeln!(f, "{}", finding)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every structural check over the document, and the optional checks selected by the
+/// options.
+///
+/// # Arguments
+///
+/// * `document` - A mutable reference to the PDF document, only used for reading
+/// * `options` - The optional checks to run
+///
+/// # Returns
+///
+/// A `Result` containing the validation report, or an error if the file can't be read at all
+pub(crate) fn verify_document(document: &mut PDFDocument, options: &VerifyOptions) -> Result<ValidationReport> {
+    let mut findings = Vec::new();
+    check_xref(document, &mut findings)?;
+    check_duplicates(document, &mut findings)?;
+    check_page_tree(document, &mut findings);
+    check_outline(document, &mut findings);
+    check_references(document, &mut findings);
+    if options.unknown_keys {
+        check_unknown_keys(document, &mut findings);
+    }
+    Ok(ValidationReport { findings })
+}
+
+/// Checks the xref entry offsets against the file size and the object headers found
+/// there, and the trailer `/Size` against the xref table.
+fn check_xref(document: &mut PDFDocument, findings: &mut Vec<Finding>) -> Result<()> {
+    let file_size = document.get_file_size()?;
+    // Compressed entries have no offset, their objects are checked while walking the references
+    let entries = document.get_xref_slice().iter().filter(|it| it.is_using() && !it.is_compressed()).cloned().collect::<Vec<_>>();
+    document.with_skipped_stream_data(|document| {
+        for entry in entries {
+            let obj_ref = (entry.get_obj_num(), entry.get_gen_num());
+            let offset = entry.get_value();
+            if offset >= file_size {
+                findings.push(Finding::new(
+                    Category::XrefOffset,
+                    Severity::Error,
+                    format!("Xref entry offset {} exceeds file size {}", offset, file_size),
+                    Some(obj_ref),
+                    Some(offset),
+                ));
+                continue;
+            }
+            // Objects which can't be parsed are reported while walking the references
+            let found = match document.parse_at_offset(offset) {
+                Ok((PDFObject::IndirectObject(obj_num, gen_num, _), _)) => (obj_num, gen_num),
+                _ => continue,
+            };
+            if found != obj_ref {
+                findings.push(Finding::new(
+                    Category::XrefOffset,
+                    Severity::Error,
+                    format!("Xref entry offset {} holds object {} {}", offset, found.0, found.1),
+                    Some(obj_ref),
+                    Some(offset),
+                ));
+            }
+        }
+    });
+    if let Some(size) = document.declared_size() {
+        let max_obj_num = document.actual_max_object().unwrap_or(0);
+        let expected = max_obj_num as u64 + 1;
+        if size != expected {
+            findings.push(Finding::new(
+                Category::TrailerSize,
+                Severity::Warning,
+                format!("Trailer /Size {} but the highest object number is {}", size, max_obj_num),
+                None,
+                None,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Reports the objects defined more than once, as information if the xref designates the
+/// last definition like an incremental update does and as a warning otherwise.
+///
+/// The scan for the definitions leaves neither warnings nor a smaller recovery budget.
+fn check_duplicates(document: &mut PDFDocument, findings: &mut Vec<Finding>) -> Result<()> {
+    for duplicate in document.with_restored_diagnostics(collect_duplicate_definitions)? {
+        let offsets = duplicate.get_offsets().iter().map(|it| it.to_string()).collect::<Vec<_>>().join(", ");
+        let (severity, message) = match duplicate.get_designated_offset() {
+            Some(offset) => {
+                let severity = if duplicate.is_last_designated() { Severity::Info } else { Severity::Warning };
+                (severity, format!("Object is defined at offsets {}, the xref designates {}", offsets, offset))
+            }
+            None => (Severity::Warning, format!("Object is defined at offsets {}, the xref designates none", offsets)),
+        };
+        findings.push(Finding::new(
+            Category::DuplicateObject,
+            severity,
+            message,
+            Some(duplicate.get_object()),
+            duplicate.get_designated_offset(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the `/Count` of every page tree node and the `/MediaBox` of every page, and notes
+/// a page tree without pages.
+fn check_page_tree(document: &PDFDocument, findings: &mut Vec<Finding>) {
+    let arena = document.get_page_tree();
+    let mut pending: Vec<NodeId> = arena.get_root_node().ok().map(|it| it.get_page_id()).into_iter().collect();
+    if let Ok(root) = arena.get_root_node()
+        && document.get_page_num() == 0
+    {
+        // Legal for documents carrying only attachments or an XFA form, but readers may balk
+        findings.push(Finding::new(
+            Category::PageCount,
+            Severity::Info,
+            "Page tree has no pages".to_string(),
+            Some(root.get_page_obj_ref()),
+            None,
+        ));
+    }
+    while let Some(node_id) = pending.pop() {
+        let node = match arena.node(node_id) {
+            Ok(node) if !node.is_unreadable() => node,
+            // Placeholders are reported while walking the references
+            _ => continue,
+        };
+        match node.get_kids() {
+            Some(kids) => {
+                let actual = arena.count_leaf_pages(node_id);
+                if node.get_count() != actual {
+                    findings.push(Finding::new(
+                        Category::PageCount,
+                        Severity::Error,
+                        format!("Page tree node declares /Count {} but has {} pages", node.get_count(), actual),
+                        Some(node.get_page_obj_ref()),
+                        None,
+                    ));
+                }
+                pending.extend(kids.iter().rev());
+            }
+            None => {
+                if arena.get_inherited_attr(node_id, MEDIA_BOX).is_none() {
+                    findings.push(Finding::new(
+                        Category::MissingKey,
+                        Severity::Error,
+                        "Page has no /MediaBox, neither own nor inherited".to_string(),
+                        Some(node.get_page_obj_ref()),
+                        None,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Checks the `/Count` of every outline item against the outline tree.
+fn check_outline(document: &PDFDocument, findings: &mut Vec<Finding>) {
+    let tree = match document.get_outline() {
+        Some(tree) => tree,
+        None => return,
+    };
+    for (node_id, node) in tree.count_mismatches() {
+        findings.push(Finding::new(
+            Category::OutlineCount,
+            Severity::Warning,
+            node.count_mismatch_message(),
+            Some(node_id.to_tuple()),
+            None,
+        ));
+    }
+}
+
+/// Walks every object reachable from the catalog, reporting unreadable objects,
+/// bad stream lengths and references to objects missing from the xref table.
+fn check_references(document: &mut PDFDocument, findings: &mut Vec<Finding>) {
+    let catalog = document.get_catalog_ref();
+    if catalog.is_none() {
+        findings.push(Finding::new(
+            Category::MissingKey,
+            Severity::Error,
+            "Trailer has no /Root".to_string(),
+            None,
+            None,
+        ));
+    }
+    let mut pending = catalog.into_iter().collect::<VecDeque<_>>();
+    let mut visited = pending.iter().copied().collect::<HashSet<_>>();
+    while let Some(obj_ref) = pending.pop_front() {
+        let object = match document.read_object_with_ref(obj_ref) {
+            Ok(Some(PDFObject::IndirectObject(_, _, object))) => object,
+            Ok(_) => continue,
+            Err(StreamLengthMismatch(length)) => {
+                findings.push(Finding::new(
+                    Category::StreamLength,
+                    Severity::Error,
+                    format!("Stream /Length {} is not followed by endstream", length),
+                    Some(obj_ref),
+                    None,
+                ));
+                continue;
+            }
+            Err(e) => {
+                findings.push(Finding::new(
+                    Category::UnreadableObject,
+                    Severity::Error,
+                    format!("Object can't be parsed: {}", e),
+                    Some(obj_ref),
+                    None,
+                ));
+                continue;
+            }
+        };
+        if Some(obj_ref) == catalog && object.as_dict().is_none_or(|dict| !dict.contain(PAGES)) {
+            findings.push(Finding::new(
+                Category::MissingKey,
+                Severity::Error,
+                "Catalog has no /Pages".to_string(),
+                Some(obj_ref),
+                None,
+            ));
+        }
+        let mut refs = Vec::new();
+        collect_refs(&object, &mut refs);
+        for target in refs {
+            let exists = document.get_xref_entry(target).is_some_and(|it| it.is_using());
+            if !exists {
+                findings.push(Finding::new(
+                    Category::DanglingReference,
+                    Severity::Warning,
+                    format!("Reference {} {} R points to no object", target.0, target.1),
+                    Some(obj_ref),
+                    None,
+                ));
+            } else if visited.insert(target) {
+                pending.push_back(target);
+            }
+        }
+    }
+}
+
+/// The occurrences of an unknown key, how many and the first object having it.
+type KeyOccurrences = BTreeMap<(KeyContext, String), (usize, ObjRefTuple)>;
+
+/// Reports the keys of the catalog, the page tree nodes, the pages and their fonts and
+/// annotations which the specification doesn't define up to the version of the document.
+///
+/// Objects which can't be read are skipped, they are reported while walking the references.
+fn check_unknown_keys(document: &mut PDFDocument, findings: &mut Vec<Finding>) {
+    let mut version = *document.get_version();
+    let catalog = document.get_catalog().get_attrs().clone();
+    if let Some(declared) = catalog.get_named_value(VERSION).and_then(|it| PDFVersion::from_str(it).ok()) {
+        version = version.max(declared);
+    }
+    let mut occurrences = KeyOccurrences::new();
+    let mut record = |context: KeyContext, dict: &Dictionary, object: ObjRefTuple| {
+        for (key, _) in dict.iter() {
+            if context.defined_since(key).is_none_or(|since| since > version) {
+                occurrences.entry((context, key.to_string())).or_insert((0, object)).0 += 1;
+            }
+        }
+    };
+    if let Some(catalog_ref) = document.get_catalog_ref() {
+        record(KeyContext::Catalog, &catalog, catalog_ref);
+    }
+
+    let arena = document.get_page_tree();
+    let mut pages = Vec::new();
+    let mut pending: Vec<NodeId> = arena.get_root_node().ok().map(|it| it.get_page_id()).into_iter().collect();
+    while let Some(node_id) = pending.pop() {
+        let Some(node) = arena.node(node_id).ok().filter(|it| !it.is_unreadable()) else {
+            continue;
+        };
+        let page_ref = node.get_page_obj_ref();
+        match node.get_kids() {
+            Some(kids) => {
+                record(KeyContext::Pages, node.get_attrs(), page_ref);
+                pending.extend(kids.iter().rev());
+            }
+            None => {
+                record(KeyContext::Page, node.get_attrs(), page_ref);
+                pages.push((page_ref, arena.get_effective_attrs(node_id).unwrap_or_default()));
+            }
+        }
+    }
+
+    // Fonts and annotations shared by several pages are counted once
+    let mut visited = HashSet::new();
+    for (page_ref, attrs) in pages {
+        let fonts = attrs
+            .get(RESOURCES)
+            .and_then(|it| resolve_dict(document, it.clone(), &mut visited))
+            .and_then(|(resources, _)| resources.get(FONT).cloned())
+            .and_then(|it| resolve_dict(document, it, &mut visited))
+            .map(|(fonts, _)| fonts.iter().map(|(_, font)| font.clone()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        for font in fonts {
+            if let Some((dict, object)) = resolve_dict(document, font, &mut visited) {
+                record(KeyContext::Font, &dict, object.unwrap_or(page_ref));
+            }
+        }
+        let annots = match attrs.get(ANNOTS).map(|it| document.resolve(it.clone())) {
+            Some(Ok(Some(PDFObject::Array(annots)))) => annots,
+            _ => Vec::new(),
+        };
+        for annot in annots {
+            if let Some((dict, object)) = resolve_dict(document, annot, &mut visited) {
+                record(KeyContext::Annot, &dict, object.unwrap_or(page_ref));
+            }
+        }
+    }
+
+    for ((context, key), (count, object)) in occurrences {
+        let reason = match context.defined_since(&key) {
+            Some(since) => format!("is defined since PDF {}, the document declares {}", since, version),
+            None => format!("is not defined by PDF {}", version),
+        };
+        let times = if count == 1 { "occurrence" } else { "occurrences" };
+        findings.push(Finding::new(
+            Category::UnknownKey,
+            Severity::Info,
+            format!("{} key /{} {} ({} {})", context, key, reason, count, times),
+            Some(object),
+            None,
+        ));
+    }
+}
+
+/// Resolves a dictionary, returning its object if it was referenced. A reference visited
+/// before or a value which isn't a dictionary yields `None`.
+fn resolve_dict(
+    document: &mut PDFDocument,
+    object: PDFObject,
+    visited: &mut HashSet<ObjRefTuple>,
+) -> Option<(Dictionary, Option<ObjRefTuple>)> {
+    let obj_ref = object.as_object_ref();
+    if let Some(obj_ref) = obj_ref
+        && !visited.insert(obj_ref)
+    {
+        return None;
+    }
+    match document.resolve(object) {
+        Ok(Some(PDFObject::Dict(dict))) => Some((dict, obj_ref)),
+        _ => None,
+    }
+}
+
+/// Collects the object references nested in an object.
+fn collect_refs(object: &PDFObject, refs: &mut Vec<ObjRefTuple>) {
+    match object {
+        PDFObject::ObjectRef(obj_num, gen_num) => refs.push((*obj_num, *gen_num)),
+        PDFObject::Array(elements) => elements.iter().for_each(|it| collect_refs(it, refs)),
+        PDFObject::Dict(dict) => dict.iter().for_each(|(_, it)| collect_refs(it, refs)),
+        PDFObject::Stream(stream) => {
+            stream.get_metadata().iter().for_each(|(_, it)| collect_refs(it, refs))
+        }
+        PDFObject::IndirectObject(_, _, object) => collect_refs(object, refs),
+        _ => {}
+    }
+}
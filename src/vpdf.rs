@@ -4,7 +4,8 @@ use crate::error::PDFError;
 
 macro_rules! pdf_version {
     ($(($name:ident,$version:literal)),+$(,)?) => {
-        #[derive(PartialEq,Debug)]
+        /// A PDF version, ordered from the oldest to the newest.
+        #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
         pub enum PDFVersion{
         $(
             $name,
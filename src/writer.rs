@@ -0,0 +1,679 @@
+use crate::constants::{COUNT, DECODE_PARMS, DEST, FILTER, FIRST, INFO, LAST, LENGTH, NEXT, OUTLINES, PARENT, PREV, ROOT, SIZE, TITLE, TYPE};
+use crate::document::PDFDescribe;
+use crate::error::PDFError;
+use crate::error::PDFError::PageNotFound;
+use crate::filter::{ASCII_FILTERS, SUPPORTED_FILTERS, decode_stream_xx_decode};
+use crate::objects::{ArrayBuilder, DictBuilder, Dictionary, ObjRefTuple, ObjectId, PDFNumber, PDFObject, PDFString, Stream};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Result, Write};
+
+/// A set of numbered objects which is written as a complete PDF file.
+///
+/// Object numbers are handed out in ascending order starting at 1 with generation 0, an
+/// object set under another generation keeps it.
+#[derive(Clone)]
+pub struct DocumentBuilder {
+    /// The generation and the object of each object number.
+    objects: BTreeMap<u32, (u16, PDFObject)>,
+    next_obj_num: u32,
+    root: Option<ObjRefTuple>,
+    /// The `/Outlines` dictionary linked from the catalog when writing.
+    outline: Option<ObjRefTuple>,
+    /// The document information dictionary written as `/Info` of the trailer.
+    info: Option<ObjRefTuple>,
+}
+
+/// Options controlling how objects are written, see [`DocumentBuilder::write_with`] and
+/// [`crate::document::PDFDocument::save`].
+#[derive(Clone, Debug, Default)]
+pub struct WriteOptions {
+    /// Whether a saved document keeps the object numbers and object order of its file.
+    preserve_numbering: bool,
+    /// Whether streams are re-encoded with `FlateDecode`, see [`WriteOptions::with_compress_streams`].
+    compress_streams: bool,
+}
+
+/// A bookmark of an outline built by [`OutlineBuilder`].
+#[derive(Clone, Debug)]
+pub struct OutlineItem {
+    title: String,
+    /// The index of the destination page in page order.
+    page: usize,
+    /// Whether the children are shown initially.
+    open: bool,
+    children: Vec<OutlineItem>,
+}
+
+/// Builds the bookmarks of a document, e.g.
+///
+/// ```
+/// use pdf_rs::writer::{OutlineBuilder, OutlineItem};
+///
+/// let outline = OutlineBuilder::new()
+///     .item(OutlineItem::new("Introduction", 0))
+///     .item(OutlineItem::new("Chapter 1", 1).child(OutlineItem::new("Section 1.1", 2)));
+/// assert_eq!(outline.get_items().len(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OutlineBuilder {
+    items: Vec<OutlineItem>,
+}
+
+impl DocumentBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        DocumentBuilder { objects: BTreeMap::new(), next_obj_num: 1, root: None, outline: None, info: None }
+    }
+
+    /// Creates an empty builder handing out object numbers from the given one on, e.g. for
+    /// the objects of an incremental update.
+    pub(crate) fn starting_at(next_obj_num: u32) -> Self {
+        DocumentBuilder { next_obj_num, ..Self::new() }
+    }
+
+    /// Reserves a fresh object number, the object is set later by [`DocumentBuilder::set`].
+    ///
+    /// A reserved number which is never set is written as a `null` object.
+    pub fn reserve(&mut self) -> ObjRefTuple {
+        let obj_num = self.next_obj_num;
+        self.next_obj_num += 1;
+        (obj_num, 0)
+    }
+
+    /// Adds an object under a fresh object number and returns its reference.
+    pub fn add(&mut self, object: impl Into<PDFObject>) -> ObjRefTuple {
+        let obj_ref = self.reserve();
+        self.set(obj_ref, object);
+        obj_ref
+    }
+
+    /// Sets the object of a reserved reference, replacing any previous object.
+    ///
+    /// The object is written with the generation of the reference.
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_ref` - A reference returned by [`DocumentBuilder::reserve`] or [`DocumentBuilder::add`]
+    /// * `object` - The object, without the `obj ... endobj` wrapper
+    pub fn set(&mut self, obj_ref: impl Into<ObjectId>, object: impl Into<PDFObject>) {
+        let (obj_num, gen_num) = obj_ref.into().to_tuple();
+        self.next_obj_num = self.next_obj_num.max(obj_num + 1);
+        self.objects.insert(obj_num, (gen_num, object.into()));
+    }
+
+    /// Returns the object with the given reference.
+    pub fn get(&self, obj_ref: impl Into<ObjectId>) -> Option<&PDFObject> {
+        let (obj_num, gen_num) = obj_ref.into().to_tuple();
+        match self.objects.get(&obj_num) {
+            Some((generation, object)) if *generation == gen_num => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the objects in ascending object number order.
+    pub fn iter(&self) -> impl Iterator<Item = (ObjRefTuple, &PDFObject)> {
+        self.objects.iter().map(|(obj_num, (gen_num, object))| ((*obj_num, *gen_num), object))
+    }
+
+    /// Returns the number of objects which have been set.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Returns true if no object has been set.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Sets the catalog written as `/Root` of the trailer.
+    pub fn set_root(&mut self, obj_ref: impl Into<ObjectId>) {
+        self.root = Some(obj_ref.into().to_tuple());
+    }
+
+    /// Sets the document information dictionary written as `/Info` of the trailer.
+    pub(crate) fn set_info_ref(&mut self, obj_ref: ObjRefTuple) {
+        self.info = Some(obj_ref);
+    }
+
+    /// Adds a document information dictionary, which is written as `/Info` of the trailer.
+    ///
+    /// # Arguments
+    ///
+    /// * `describe` - The information
+    ///
+    /// # Returns
+    ///
+    /// The reference of the information dictionary
+    pub fn set_info(&mut self, describe: &PDFDescribe) -> ObjRefTuple {
+        let mut dict = Dictionary::default();
+        describe.write_into(&mut dict);
+        let info = self.add(dict);
+        self.info = Some(info);
+        info
+    }
+
+    /// Adds the objects of an outline, which is written as `/Outlines` of the catalog.
+    ///
+    /// # Arguments
+    ///
+    /// * `outline` - The outline
+    /// * `pages` - The references of the pages in page order, the destinations of the items
+    ///   are indexes into it
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the reference of the `/Outlines` dictionary, or a `PageNotFound`
+    /// error if an item refers to a page out of range
+    pub fn set_outline(&mut self, outline: &OutlineBuilder, pages: &[ObjRefTuple]) -> crate::error::Result<ObjRefTuple> {
+        let root = self.reserve();
+        let (first, last) = write_outline_items(self, &outline.items, root, pages)?;
+        let mut dict = DictBuilder::new().name(TYPE, OUTLINES);
+        if let (Some(first), Some(last)) = (first, last) {
+            dict = dict.reference(FIRST, first).reference(LAST, last).entry(COUNT, visible_count(&outline.items));
+        }
+        self.set(root, dict.build());
+        self.outline = Some(root);
+        Ok(root)
+    }
+
+    /// Writes the objects as a PDF file with a classic cross-reference table.
+    ///
+    /// The trailer `/Size` is one greater than the highest reserved object number. The
+    /// outline set by [`DocumentBuilder::set_outline`] is linked from the catalog and the
+    /// information set by [`DocumentBuilder::set_info`] from the trailer.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The destination
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether writing succeeded
+    pub fn write(&self, out: &mut impl Write) -> Result<()> {
+        self.write_with(out, &WriteOptions::default())
+    }
+
+    /// Writes the objects like [`DocumentBuilder::write`] with the given options.
+    ///
+    /// The object numbers are those handed out by the builder, so only
+    /// [`WriteOptions::with_compress_streams`] applies. Streams which can't be decoded are
+    /// written unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The destination
+    /// * `options` - The options
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether writing succeeded
+    pub fn write_with(&self, out: &mut impl Write, options: &WriteOptions) -> Result<()> {
+        self.write_reporting(out, options, &mut |_, _| {})
+    }
+
+    /// Writes the objects like [`DocumentBuilder::write_with`], passing the reference and
+    /// the error of each stream which can't be decoded to `failed`.
+    pub(crate) fn write_reporting(
+        &self,
+        out: &mut impl Write,
+        options: &WriteOptions,
+        failed: &mut dyn FnMut(ObjRefTuple, PDFError),
+    ) -> Result<()> {
+        let mut objects = Vec::with_capacity(self.next_obj_num as usize);
+        for obj_num in 1..self.next_obj_num {
+            let (gen_num, mut object) = self.objects.get(&obj_num).cloned().unwrap_or((0, PDFObject::Null));
+            if let (PDFObject::Dict(catalog), Some(outline)) = (&mut object, self.outline)
+                && self.root == Some((obj_num, gen_num))
+            {
+                catalog.insert(OUTLINES, PDFObject::ObjectRef(outline.0, outline.1));
+            }
+            objects.push(((obj_num, gen_num), object));
+        }
+        let mut trailer = Dictionary::default();
+        if let Some((obj_num, gen_num)) = self.root {
+            trailer.insert(ROOT, PDFObject::ObjectRef(obj_num, gen_num));
+        }
+        if let Some((obj_num, gen_num)) = self.info {
+            trailer.insert(INFO, PDFObject::ObjectRef(obj_num, gen_num));
+        }
+        write_file("1.7", &objects, &HashMap::new(), trailer, options, failed, out)
+    }
+}
+
+impl Default for DocumentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WriteOptions {
+    /// Creates the default options, renumbering the objects of a saved document and
+    /// writing streams as they are.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether a saved document keeps the object numbers, generations and object
+    /// order of its file.
+    ///
+    /// Unchanged objects are then written at the same position of the object sequence, so
+    /// two saves differ only where objects changed. Otherwise the objects reachable from
+    /// the catalog are numbered anew and unreachable objects are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `preserve_numbering` - True to keep the numbering
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_preserve_numbering(mut self, preserve_numbering: bool) -> Self {
+        self.preserve_numbering = preserve_numbering;
+        self
+    }
+
+    /// Sets whether streams are re-encoded with `FlateDecode`.
+    ///
+    /// Streams whose filters can all be decoded, like `ASCIIHexDecode` and `ASCII85Decode`,
+    /// are decoded and compressed with `FlateDecode` alone. Image codecs like `DCTDecode` and
+    /// `JPXDecode` are kept, only ASCII filters in front of them are removed. Streams
+    /// encoded with `FlateDecode` alone or using decode parameters are written as they are,
+    /// as are streams which can't be decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `compress_streams` - True to compress the streams
+    ///
+    /// # Returns
+    ///
+    /// The updated options
+    pub fn with_compress_streams(mut self, compress_streams: bool) -> Self {
+        self.compress_streams = compress_streams;
+        self
+    }
+
+    /// Returns true if a saved document keeps the numbering of its file.
+    pub fn is_preserve_numbering(&self) -> bool {
+        self.preserve_numbering
+    }
+
+    /// Returns true if streams are re-encoded with `FlateDecode`.
+    pub fn is_compress_streams(&self) -> bool {
+        self.compress_streams
+    }
+}
+
+/// Writes a complete PDF file with a classic cross-reference table.
+///
+/// The objects are written in the given order, object numbers below the highest one which
+/// have no object become free entries with the generation given by `free`, or 0.
+///
+/// # Arguments
+///
+/// * `version` - The version of the header, e.g. `1.7`
+/// * `objects` - The objects with their references in writing order
+/// * `free` - The generations of the free entries
+/// * `trailer` - The trailer entries, `/Size` is added
+/// * `options` - The options
+/// * `failed` - Receives the streams which can't be decoded, they are written unchanged
+/// * `out` - The destination
+///
+/// # Returns
+///
+/// An `io::Result` indicating whether writing succeeded
+pub(crate) fn write_file(
+    version: &str,
+    objects: &[(ObjRefTuple, PDFObject)],
+    free: &HashMap<u32, u16>,
+    mut trailer: Dictionary,
+    options: &WriteOptions,
+    failed: &mut dyn FnMut(ObjRefTuple, PDFError),
+    out: &mut impl Write,
+) -> Result<()> {
+    let mut buf = format!("%PDF-{}\n", version).into_bytes();
+    buf.extend_from_slice(b"%\xE2\xE3\xCF\xD3\n");
+    let mut offsets = HashMap::with_capacity(objects.len());
+    for (obj_ref, object) in objects {
+        let (obj_num, gen_num) = *obj_ref;
+        offsets.insert(obj_num, (gen_num, buf.len()));
+        writeln!(buf, "{} {} obj", obj_num, gen_num)?;
+        let normalized = match object {
+            PDFObject::Stream(stream) if options.compress_streams => match normalize_stream(stream) {
+                Ok(normalized) => normalized,
+                Err(e) => {
+                    failed(*obj_ref, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        match normalized {
+            Some(stream) => write_object(&PDFObject::Stream(stream), &mut buf)?,
+            None => write_object(object, &mut buf)?,
+        }
+        buf.extend_from_slice(b"\nendobj\n");
+    }
+    let size = offsets.keys().chain(free.keys()).max().map_or(1, |it| it + 1);
+    // Free entries are linked in ascending order, starting at entry 0
+    let free_nums = (1..size).filter(|it| !offsets.contains_key(it)).collect::<Vec<_>>();
+    let xref_offset = buf.len();
+    write!(buf, "xref\n0 {}\n{:010} 65535 f\r\n", size, free_nums.first().copied().unwrap_or(0))?;
+    let mut next_free = free_nums.iter().skip(1);
+    for obj_num in 1..size {
+        match offsets.get(&obj_num) {
+            Some((gen_num, offset)) => write!(buf, "{:010} {:05} n\r\n", offset, gen_num)?,
+            None => {
+                let next = next_free.next().copied().unwrap_or(0);
+                write!(buf, "{:010} {:05} f\r\n", next, free.get(&obj_num).copied().unwrap_or(0))?
+            }
+        }
+    }
+    trailer.insert(SIZE, PDFObject::Number(PDFNumber::Unsigned(size as u64)));
+    buf.extend_from_slice(b"trailer\n");
+    write_object(&PDFObject::Dict(trailer), &mut buf)?;
+    write!(buf, "\nstartxref\n{}\n%%EOF\n", xref_offset)?;
+    out.write_all(&buf)
+}
+
+/// Re-encodes a stream with `FlateDecode`, see [`WriteOptions::with_compress_streams`].
+///
+/// # Returns
+///
+/// A `Result` containing the re-encoded stream, `None` if the stream is written as it is,
+/// or an error if a filter fails to decode the data
+fn normalize_stream(stream: &Stream) -> crate::error::Result<Option<Stream>> {
+    let filters = stream.filters();
+    let parms = stream.decode_parms();
+    if filters == ["FlateDecode"] {
+        return Ok(None);
+    }
+    let decodable = filters
+        .iter()
+        .zip(&parms)
+        .all(|(filter, parms)| parms.is_none() && SUPPORTED_FILTERS.contains(&filter.as_str()));
+    // Only the ASCII filters are removed in front of a filter which is kept
+    let strip = match decodable {
+        true => filters.len(),
+        false => filters.iter().take_while(|it| ASCII_FILTERS.contains(&it.as_str())).count(),
+    };
+    if !decodable && strip == 0 {
+        return Ok(None);
+    }
+    let mut data = stream.as_slice().to_vec();
+    for filter in &filters[..strip] {
+        data = decode_stream_xx_decode(filter, &data)?;
+    }
+    let kept = parms[strip..].iter().map(|it| it.cloned()).collect::<Vec<_>>();
+    let mut metadata = stream.get_metadata().clone();
+    metadata.remove(FILTER);
+    metadata.remove(DECODE_PARMS);
+    if decodable {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data)?;
+        data = encoder.finish()?;
+        metadata.insert(FILTER, PDFObject::Named("FlateDecode".into()));
+        return Ok(Some(Stream::new(metadata, data)));
+    }
+    let mut names = filters[strip..].iter().map(|it| PDFObject::Named(it.as_str().into())).collect::<Vec<_>>();
+    metadata.insert(FILTER, if names.len() == 1 { names.remove(0) } else { PDFObject::Array(names) });
+    if kept.iter().any(Option::is_some) {
+        let mut parms = kept.into_iter().map(|it| it.map_or(PDFObject::Null, PDFObject::Dict)).collect::<Vec<_>>();
+        metadata.insert(DECODE_PARMS, if parms.len() == 1 { parms.remove(0) } else { PDFObject::Array(parms) });
+    }
+    Ok(Some(Stream::new(metadata, data)))
+}
+
+impl OutlineItem {
+    /// Creates a closed item without children.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title shown in the bookmarks panel
+    /// * `page` - The index of the destination page in page order
+    pub fn new(title: impl Into<String>, page: usize) -> Self {
+        Self { title: title.into(), page, open: false, children: Vec::new() }
+    }
+
+    /// Sets whether the children are shown initially, written as a positive `/Count`.
+    pub fn with_open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Appends a child item.
+    pub fn child(mut self, child: OutlineItem) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Returns the title.
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the index of the destination page.
+    pub fn get_page(&self) -> usize {
+        self.page
+    }
+
+    /// Returns true if the children are shown initially.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Returns the child items.
+    pub fn get_children(&self) -> &[OutlineItem] {
+        &self.children
+    }
+}
+
+impl OutlineBuilder {
+    /// Creates an empty outline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a top-level item.
+    pub fn item(mut self, item: OutlineItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Returns the top-level items.
+    pub fn get_items(&self) -> &[OutlineItem] {
+        &self.items
+    }
+}
+
+/// Adds outline items as linked siblings under `parent`, their children recursively.
+///
+/// # Returns
+///
+/// A `Result` containing the references of the first and the last item
+fn write_outline_items(
+    builder: &mut DocumentBuilder,
+    items: &[OutlineItem],
+    parent: ObjRefTuple,
+    pages: &[ObjRefTuple],
+) -> crate::error::Result<(Option<ObjRefTuple>, Option<ObjRefTuple>)> {
+    let refs = items.iter().map(|_| builder.reserve()).collect::<Vec<_>>();
+    for (index, item) in items.iter().enumerate() {
+        let page = match pages.get(item.page) {
+            Some(page) => *page,
+            None => return Err(PageNotFound(format!("Page not found:{}", item.page))),
+        };
+        let dest = ArrayBuilder::new().reference(page).name("Fit").build();
+        let mut dict = DictBuilder::new()
+            .entry(TITLE, encode_text(&item.title))
+            .reference(PARENT, parent)
+            .entry(DEST, dest);
+        if index > 0 {
+            dict = dict.reference(PREV, refs[index - 1]);
+        }
+        if let Some(next) = refs.get(index + 1) {
+            dict = dict.reference(NEXT, *next);
+        }
+        if let (Some(first), Some(last)) = write_outline_items(builder, &item.children, refs[index], pages)? {
+            let count = visible_count(&item.children);
+            let count = if item.open { count } else { -count };
+            dict = dict.reference(FIRST, first).reference(LAST, last).entry(COUNT, count);
+        }
+        builder.set(refs[index], dict.build());
+    }
+    Ok((refs.first().copied(), refs.last().copied()))
+}
+
+/// Returns the number of items shown when their parent is open, the children of an
+/// open item are shown as well.
+fn visible_count(items: &[OutlineItem]) -> i64 {
+    let mut count = 0;
+    let mut pending = items.iter().collect::<Vec<_>>();
+    while let Some(item) = pending.pop() {
+        count += 1;
+        if item.open {
+            pending.extend(&item.children);
+        }
+    }
+    count
+}
+
+/// Encodes a text string, ASCII text as a literal string and anything else as UTF-16BE
+/// with a byte order mark.
+pub(crate) fn encode_text(text: &str) -> PDFString {
+    if text.is_ascii() {
+        return PDFString::literal(text);
+    }
+    let mut buf = vec![0xFE, 0xFF];
+    buf.extend(text.encode_utf16().flat_map(|it| it.to_be_bytes()));
+    PDFString::hexadecimal(buf)
+}
+
+/// Writes an object in PDF syntax, e.g. `<< /Type /Page /Parent 2 0 R >>`.
+///
+/// Dictionary keys are written in sorted order so the output is deterministic, and the
+/// `/Length` of a stream is written as the length of its data.
+///
+/// # Arguments
+///
+/// * `object` - The object to write
+/// * `out` - The destination
+///
+/// # Returns
+///
+/// An `io::Result` indicating whether writing succeeded
+pub fn write_object(object: &PDFObject, out: &mut impl Write) -> Result<()> {
+    match object {
+        PDFObject::Bool(value) => write!(out, "{}", value),
+        PDFObject::Number(number) => write_number(number, out),
+        PDFObject::Named(name) => write_name(name, out),
+        PDFObject::String(value) => write_string(value, out),
+        PDFObject::Array(elements) => {
+            out.write_all(b"[")?;
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    out.write_all(b" ")?;
+                }
+                write_object(element, out)?;
+            }
+            out.write_all(b"]")
+        }
+        PDFObject::Dict(dict) => write_dict(dict, None, out),
+        PDFObject::Null => out.write_all(b"null"),
+        PDFObject::ObjectRef(obj_num, gen_num) => write!(out, "{} {} R", obj_num, gen_num),
+        PDFObject::IndirectObject(obj_num, gen_num, value) => {
+            writeln!(out, "{} {} obj", obj_num, gen_num)?;
+            write_object(value, out)?;
+            out.write_all(b"\nendobj")
+        }
+        PDFObject::Stream(stream) => {
+            let data = stream.as_slice();
+            write_dict(stream.get_metadata(), Some(data.len()), out)?;
+            out.write_all(b"\nstream\n")?;
+            out.write_all(data)?;
+            out.write_all(b"\nendstream")
+        }
+    }
+}
+
+/// Returns an object written in PDF syntax, see [`write_object`].
+pub fn to_bytes(object: &PDFObject) -> Vec<u8> {
+    let mut buf = Vec::new();
+    // Writing into a vector can't fail
+    let _ = write_object(object, &mut buf);
+    buf
+}
+
+/// Writes a dictionary with sorted keys, `length` replaces the `/Length` of a stream.
+fn write_dict(dict: &Dictionary, length: Option<usize>, out: &mut impl Write) -> Result<()> {
+    let mut entries = dict.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    out.write_all(b"<<")?;
+    for (key, value) in entries {
+        if length.is_some() && key == LENGTH {
+            continue;
+        }
+        out.write_all(b" ")?;
+        write_name(key, out)?;
+        out.write_all(b" ")?;
+        write_object(value, out)?;
+    }
+    if let Some(length) = length {
+        write!(out, " /Length {}", length)?;
+    }
+    out.write_all(b" >>")
+}
+
+/// Writes a number, reals always get a decimal point so they are read back as reals.
+fn write_number(number: &PDFNumber, out: &mut impl Write) -> Result<()> {
+    match number {
+        PDFNumber::Signed(value) => write!(out, "{}", value),
+        PDFNumber::Unsigned(value) => write!(out, "{}", value),
+        PDFNumber::Real(value) if !value.is_finite() => out.write_all(b"0.0"),
+        PDFNumber::Real(value) => {
+            let text = value.to_string();
+            if text.contains('.') {
+                out.write_all(text.as_bytes())
+            } else {
+                write!(out, "{}.0", text)
+            }
+        }
+    }
+}
+
+/// Writes a name, escaping delimiters, white-space and `#` as `#xx`.
+fn write_name(name: &str, out: &mut impl Write) -> Result<()> {
+    out.write_all(b"/")?;
+    for b in name.bytes() {
+        let regular = (b'!'..=b'~').contains(&b) && !b"#()<>[]{}/%".contains(&b);
+        if regular {
+            out.write_all(&[b])?;
+        } else {
+            write!(out, "#{:02X}", b)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a string in its original form, escaping what can't appear in a literal string.
+fn write_string(value: &PDFString, out: &mut impl Write) -> Result<()> {
+    if value.is_hexadecimal() {
+        out.write_all(b"<")?;
+        for b in value.as_bytes() {
+            write!(out, "{:02X}", b)?;
+        }
+        return out.write_all(b">");
+    }
+    out.write_all(b"(")?;
+    for b in value.as_bytes() {
+        match b {
+            b'(' | b')' | b'\\' => out.write_all(&[b'\\', *b])?,
+            b'\n' => out.write_all(b"\\n")?,
+            b'\r' => out.write_all(b"\\r")?,
+            b' '..=b'~' => out.write_all(&[*b])?,
+            _ => write!(out, "\\{:03o}", b)?,
+        }
+    }
+    out.write_all(b")")
+}
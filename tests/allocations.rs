@@ -0,0 +1,55 @@
+use pdf_rs::ObjectReader;
+use pdf_rs::error::Result;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts the allocations of the whole test binary, which holds a single test.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_name_allocations() -> Result<()> {
+    // A soup of page-like objects whose names are common or short
+    let count = 1000;
+    let mut soup = Vec::new();
+    for i in 0..count {
+        soup.extend_from_slice(format!(
+            "{} 0 obj\n<< /Type /Page /Parent 3 0 R /MediaBox [0 0 612 792] /Rotate 0 \
+             /Resources << /Font << /F{} 5 0 R >> /ProcSet [/PDF /Text] >> \
+             /Contents {} 0 R /Group /Transparency >>\nendobj\n",
+            i + 10, i, i + 2000
+        ).as_bytes());
+    }
+    let mut reader = ObjectReader::from_bytes(&soup);
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let mut objects = 0;
+    while reader.next_object()?.is_some() {
+        objects += 1;
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    assert_eq!(objects, count);
+    // Allocating every name and delimiter took 83 allocations per object, what's left are
+    // the dictionaries and arrays themselves
+    assert!(allocations <= count * 10, "{} allocations for {} objects", allocations, count);
+    Ok(())
+}
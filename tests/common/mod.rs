@@ -0,0 +1,124 @@
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+/// Builds a classic PDF file whose object `i + 1` is `objects[i]`.
+///
+/// The catalog must be object 1, the cross-reference table and trailer are generated
+/// with the correct offsets.
+pub fn build_pdf(objects: &[String]) -> Vec<u8> {
+    build_pdf_with_trailer(objects, &format!("/Size {} /Root 1 0 R", objects.len() + 1))
+}
+
+/// Builds a PDF file like [`build_pdf`] with the given trailer dictionary entries.
+pub fn build_pdf_with_trailer(objects: &[String], trailer: &str) -> Vec<u8> {
+    let mut buf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(buf.len());
+        buf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+    }
+    let xref_offset = buf.len();
+    let size = objects.len() + 1;
+    buf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", size).as_bytes());
+    for offset in offsets {
+        buf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    buf.extend_from_slice(
+        format!("trailer\n<< {} >>\nstartxref\n{}\n%%EOF\n", trailer, xref_offset).as_bytes(),
+    );
+    buf
+}
+
+/// Appends an incremental update to a PDF file built by [`build_pdf`].
+///
+/// The update defines the given objects as `((obj_num, gen_num), object)`, consecutive
+/// object numbers share a cross-reference subsection. Its trailer gets the given dictionary
+/// entries followed by `/Prev` pointing at the previous cross-reference table.
+pub fn append_update(mut buf: Vec<u8>, objects: &[((u32, u16), String)], trailer: &str) -> Vec<u8> {
+    let text = String::from_utf8_lossy(&buf).into_owned();
+    let start = text.rfind("startxref").unwrap() + "startxref".len();
+    let prev = text[start..].split_whitespace().next().unwrap().to_string();
+    let mut entries = Vec::with_capacity(objects.len());
+    for ((obj_num, gen_num), object) in objects {
+        entries.push((*obj_num, *gen_num, buf.len()));
+        buf.extend_from_slice(format!("{} {} obj\n{}\nendobj\n", obj_num, gen_num, object).as_bytes());
+    }
+    entries.sort();
+    let xref_offset = buf.len();
+    buf.extend_from_slice(b"xref\n");
+    let mut index = 0;
+    while index < entries.len() {
+        let len = entries[index..]
+            .iter()
+            .enumerate()
+            .take_while(|(i, entry)| entry.0 == entries[index].0 + *i as u32)
+            .count();
+        buf.extend_from_slice(format!("{} {}\n", entries[index].0, len).as_bytes());
+        for (_, gen_num, offset) in &entries[index..index + len] {
+            buf.extend_from_slice(format!("{:010} {:05} n \n", offset, gen_num).as_bytes());
+        }
+        index += len;
+    }
+    buf.extend_from_slice(
+        format!("trailer\n<< {} /Prev {} >>\nstartxref\n{}\n%%EOF\n", trailer, prev, xref_offset)
+            .as_bytes(),
+    );
+    buf
+}
+
+/// Returns the objects of a document with a catalog, object 1, and a page tree root, object 2,
+/// whose pages follow from object 3 on, followed by `objects`.
+///
+/// Each page dictionary gets the given entries after `/Type /Page /Parent 2 0 R`.
+pub fn catalog_with_pages(pages: &[&str], objects: Vec<String>) -> Vec<String> {
+    let kids = (0..pages.len()).map(|page| format!("{} 0 R", 3 + page)).collect::<Vec<_>>();
+    let mut all = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+    ];
+    for entries in pages {
+        match entries.is_empty() {
+            true => all.push("<< /Type /Page /Parent 2 0 R >>".to_string()),
+            false => all.push(format!("<< /Type /Page /Parent 2 0 R {} >>", entries)),
+        }
+    }
+    all.extend(objects);
+    all
+}
+
+/// Returns the objects of a document whose page tree root, object 2, has `nodes`
+/// intermediate nodes of `pages` pages each, the root declaring `/Count root_count`.
+pub fn page_tree_objects(nodes: usize, pages: usize, root_count: usize) -> Vec<String> {
+    let first_page = 3 + nodes;
+    let mut objects = vec!["<< /Type /Catalog /Pages 2 0 R >>".to_string()];
+    let kids = (0..nodes).map(|node| format!("{} 0 R", 3 + node)).collect::<Vec<_>>().join(" ");
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, root_count));
+    for node in 0..nodes {
+        let kids = (0..pages).map(|page| format!("{} 0 R", first_page + node * pages + page)).collect::<Vec<_>>();
+        objects.push(format!("<< /Type /Pages /Parent 2 0 R /Kids [{}] /Count {} >>", kids.join(" "), pages));
+    }
+    for node in 0..nodes {
+        for _ in 0..pages {
+            objects.push(format!("<< /Type /Page /Parent {} 0 R >>", 3 + node));
+        }
+    }
+    objects
+}
+
+/// Builds a PDF file via [`build_pdf`] and writes it into the temporary directory.
+pub fn write_pdf(name: &str, objects: &[String]) -> PathBuf {
+    write_bytes(name, &build_pdf(objects))
+}
+
+/// Writes the given PDF bytes into the temporary directory.
+pub fn write_bytes(name: &str, bytes: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pdf-rs-{}-{}.pdf", std::process::id(), name));
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+/// Formats a stream object with the given extra dictionary entries and data.
+pub fn stream(entries: &str, data: &str) -> String {
+    format!("<< {} /Length {} >>\nstream\n{}\nendstream", entries, data.len(), data)
+}
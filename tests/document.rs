@@ -1,7 +1,33 @@
+use std::collections::HashMap;
+use std::ops::ControlFlow;
 use std::path::PathBuf;
-use pdf_rs::document::PDFDocument;
-use pdf_rs::error::Result;
-use pdf_rs::helper::extract_page_text;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use pdf_rs::catalog::MarkInfo;
+use pdf_rs::date::Date;
+use pdf_rs::diff::DiffKind;
+use pdf_rs::encoding::{Encoding, PreDefinedEncoding};
+use pdf_rs::labels::LabelStyle;
+use pdf_rs::name::Name;
+use pdf_rs::names;
+use pdf_rs::document::{PDFDescribe, PDFDocument, Trapped, XrefKind};
+use pdf_rs::error::{PDFError, Result};
+use pdf_rs::features::Feature;
+use pdf_rs::font::{Confidence, TextDecoding};
+use pdf_rs::operators::HistogramOptions;
+use pdf_rs::objects::{ArrayBuilder, DictBuilder, ObjectId, PDFObject, PDFString, Rect, Stream, XEntryKind};
+use pdf_rs::options::{ParseOptions, Phase, Resource, WarningCode};
+use pdf_rs::path::{PathSeg, parse_path};
+use pdf_rs::page::{Page, PageTransform};
+use pdf_rs::page_count::CountResult;
+use pdf_rs::partial::Facet;
+use pdf_rs::text::{CoordinateSpace, InvisibleText, TextExtractionOptions};
+use pdf_rs::import::ObjectImporter;
+use pdf_rs::report::PageStatus;
+use pdf_rs::sequence::{FileSequence, MemorySequence, Sequence};
+use pdf_rs::verify::{Category, Severity, VerifyOptions};
+use pdf_rs::writer::{self, DocumentBuilder, OutlineBuilder, OutlineItem, WriteOptions};
+mod common;
 
 #[test]
 fn document() -> Result<()> {
@@ -11,7 +37,7 @@ fn document() -> Result<()> {
     assert_eq!(document.get_page_num(), 230);
     match document.read_object(0)?{
         Some(obj) => assert!(obj.is_indirect_object()),
-        _ => assert!(false),
+        _ => unreachable!(),
     }
     Ok(())
 }
@@ -28,10 +54,10 @@ fn test_stream_read() -> Result<()> {
                     assert!(obj.is_stream());
                     assert!(obj_num == 1354 && gen_num == 0)
                 }
-                _ => assert!(false),
+                _ => unreachable!(),
             }
         }
-        _ => assert!(false),
+        _ => unreachable!(),
     }
     Ok(())
 }
@@ -42,7 +68,3354 @@ fn test_page_tree() -> Result<()> {
     let page_ids = document.get_page_ids();
     assert_eq!(page_ids.len(), document.get_page_num());
     for page_id in page_ids {
-        extract_page_text(&mut document, page_id)?;
+        Page::new(page_id).text(&mut document)?;
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_extract_page_text() -> Result<()> {
+    let encoding = "<< /Type /Encoding /BaseEncoding /WinAnsiEncoding /Differences [65 /Euro /uni00E9] >>";
+    let path = common::write_pdf("extract-page-text", &common::catalog_with_pages(
+        &["/Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R"],
+        vec![
+            format!("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding {} >>", encoding),
+            common::stream("", "BT /F1 12 Tf 72 600 Td (Low) Tj 0 100 Td (ABC) Tj ET"),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    let page = Page::new(document.get_page_ids()[0]);
+    // The codes of A and B are mapped through the /Differences of the encoding
+    assert_eq!(page.text(&mut document)?, "Low\n€éC");
+    // The layout mode arranges the lines from top to bottom
+    let options = TextExtractionOptions::new().with_layout(true);
+    assert_eq!(page.text_with(&mut document, &options)?, "€éC\nLow\n");
+
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    let text = Page::new(document.get_page_ids()[1]).text(&mut document)?;
+    assert!(text.contains("Library of Congress Cataloging-in-Publication Data"));
+    Ok(())
+}
+
+#[test]
+fn test_pages_referencing() -> Result<()> {
+    let resources = "/Resources << /Font << /F1 5 0 R >> /XObject << /Im1 6 0 R >> >>";
+    let path = common::write_pdf("pages-referencing", &[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 7 0 R] /Count 2 >>".to_string(),
+        format!("<< /Type /Page /Parent 2 0 R {} /Contents 4 0 R >>", resources),
+        common::stream("", "BT /F1 12 Tf 72 712 Td (Hello) Tj ET q 10 0 0 10 0 0 cm /Im1 Do Q"),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        common::stream("/Type /XObject /Subtype /Image /Width 1 /Height 1 /ColorSpace /DeviceGray /BitsPerComponent 8", "A"),
+        format!("<< /Type /Page /Parent 2 0 R {} /Contents 8 0 R >>", resources),
+        common::stream("", "q 20 0 0 20 0 0 cm /Im1 Do Q"),
+    ]);
+    let mut document = PDFDocument::open(path)?;
+    assert_eq!(document.pages_referencing((6, 0))?, vec![0, 1]);
+    assert_eq!(document.pages_referencing((5, 0))?, vec![0]);
+    assert!(document.pages_referencing((4, 0))?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_verify_clean_document() -> Result<()> {
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    let report = document.verify()?;
+    assert!(report.get_findings().is_empty(), "{}", report);
+    Ok(())
+}
+
+#[test]
+fn test_verify_damaged_document() -> Result<()> {
+    let bytes = common::build_pdf_with_trailer(&[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 3 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 5 0 R /Annots [9 0 R] >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+        "<< /Length 3 >>\nstream\nBT ET\nendstream".to_string(),
+    ], "/Size 10 /Root 1 0 R");
+    let path = common::write_bytes("verify-damaged", &bytes);
+    let mut document = PDFDocument::open(path)?;
+    let report = document.verify()?;
+    let objects = |category| {
+        report.findings_of(category).map(|it| it.get_object()).collect::<Vec<_>>()
+    };
+    assert_eq!(objects(Category::TrailerSize), vec![None]);
+    assert_eq!(objects(Category::PageCount), vec![Some((2, 0))]);
+    assert_eq!(objects(Category::MissingKey), vec![Some((4, 0))]);
+    assert_eq!(objects(Category::DanglingReference), vec![Some((3, 0))]);
+    assert_eq!(objects(Category::StreamLength), vec![Some((5, 0))]);
+    assert!(objects(Category::XrefOffset).is_empty());
+    assert!(objects(Category::UnreadableObject).is_empty());
+    Ok(())
+}
+
+/// Opens a one page document whose content stream (object 4) declares the given length.
+fn open_with_stream_length(name: &str, data: &str, length: usize, options: ParseOptions) -> Result<PDFDocument> {
+    let path = common::write_pdf(name, &common::catalog_with_pages(&["/MediaBox [0 0 612 792] /Contents 4 0 R"], vec![
+        format!("<< /Length {} >>\nstream\n{}\nendstream", length, data),
+    ]));
+    PDFDocument::open_with_options(path, options)
+}
+
+fn read_stream_data(document: &mut PDFDocument, obj_num: u32) -> Result<Vec<u8>> {
+    match document.read_object_with_ref((obj_num, 0))? {
+        Some(PDFObject::IndirectObject(_, _, object)) => match *object {
+            PDFObject::Stream(stream) => Ok(stream.as_slice().to_vec()),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_stream_length_recovery() -> Result<()> {
+    let data = "BT /F1 12 Tf 72 712 Td (Hello, lenient parser) Tj ET";
+    for (name, length) in [("stream-short", data.len() - 10), ("stream-long", data.len() + 10)] {
+        let mut document = open_with_stream_length(name, data, length, ParseOptions::lenient())?;
+        assert_eq!(read_stream_data(&mut document, 4)?, data.as_bytes());
+        assert_eq!(document.get_warnings().len(), 1);
+        let mut document = open_with_stream_length(name, data, length, ParseOptions::strict())?;
+        assert!(matches!(document.read_object_with_ref((4, 0)), Err(PDFError::StreamLengthMismatch(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_stream_length_wins_over_endstream_in_data() -> Result<()> {
+    let data = "BT (endstream) Tj ET";
+    let mut document = open_with_stream_length("stream-keyword", data, data.len(), ParseOptions::lenient())?;
+    assert_eq!(read_stream_data(&mut document, 4)?, data.as_bytes());
+    assert!(document.get_warnings().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_resync_after_corrupt_object() -> Result<()> {
+    let page = "<< /Type /Page /Parent 2 0 R >>".to_string();
+    let path = common::write_pdf("corrupt-object", &[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >>".to_string(),
+        page.clone(),
+        "<< /Type /Page /Parent 2 0 R ) >>".to_string(),
+        page,
+    ]);
+    assert!(PDFDocument::open(path.clone()).is_err());
+    let mut document = PDFDocument::open_with_options(path, ParseOptions::lenient())?;
+    assert_eq!(document.get_warnings().len(), 1);
+    assert!(document.is_unreadable((4, 0)));
+    let page_ids = document.get_page_ids();
+    assert_eq!(page_ids.len(), 3);
+    let unreadable = page_ids
+        .iter()
+        .map(|id| document.get_page_node(*id).unwrap().is_unreadable())
+        .collect::<Vec<_>>();
+    assert_eq!(unreadable, vec![false, true, false]);
+    let scanned = document.scan_objects()?.into_iter().map(|(obj_ref, _)| obj_ref).collect::<Vec<_>>();
+    assert_eq!(scanned, vec![(1, 0), (2, 0), (3, 0), (5, 0)]);
+    assert_eq!(document.get_warnings().len(), 2);
+
+    // The scan resumes after the header of an unreadable object, whose `12 0 obj` isn't
+    // read again as `2 0 obj`
+    let mut objects = vec!["null".to_string(); 12];
+    objects[0] = "<< /Type /Catalog /Pages 2 0 R >>".to_string();
+    objects[1] = "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string();
+    objects[2] = "<< /Type /Page /Parent 2 0 R >>".to_string();
+    objects[11] = "<< /Type /Page /Parent 2 0 R ) >>".to_string();
+    let mut document = PDFDocument::open(common::write_pdf("corrupt-object-12", &objects))?;
+    let scanned = document.scan_objects()?;
+    assert_eq!(scanned.len(), 11);
+    assert!(scanned.iter().all(|(obj_ref, _)| obj_ref.0 <= 11));
+    assert_eq!(document.get_warnings().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_newest_trailer_root_wins() -> Result<()> {
+    let base = common::build_pdf(&common::catalog_with_pages(&[""], vec![
+        "<< /Type /Page /Parent 5 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>".to_string(),
+    ]));
+    let bytes = common::append_update(
+        base,
+        &[((6, 0), "<< /Type /Catalog /Pages 5 0 R >>".to_string())],
+        "/Size 7 /Root 6 0 R",
+    );
+    let path = common::write_bytes("newest-root", &bytes);
+    let document = PDFDocument::open(path)?;
+    assert_eq!(document.get_page_num(), 2);
+    assert!(document.get_encrypt_ref().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_newest_trailer_entries_win() -> Result<()> {
+    let base = common::build_pdf_with_trailer(
+        &common::catalog_with_pages(&[""], vec![
+            "<< /Producer (base) >>".to_string(),
+            "<< /Filter /Standard /V 1 /R 2 >>".to_string(),
+        ]),
+        "/Size 6 /Root 1 0 R /Info 4 0 R /Encrypt 5 0 R /ID [<01> <02>]",
+    );
+    // The update replaces /Info and /ID, /Encrypt is only defined by the older trailer
+    let bytes = common::append_update(
+        base,
+        &[((6, 0), "<< /Producer (update) >>".to_string())],
+        "/Size 7 /Root 1 0 R /Info 6 0 R /ID [<01> <03>]",
+    );
+    let path = common::write_bytes("newest-trailer-entries", &bytes);
+    let document = PDFDocument::open_with_options(path, ParseOptions::lenient())?;
+    assert_eq!(document.get_describe().and_then(|it| it.get_producer()), Some("update"));
+    assert_eq!(document.get_encrypt_ref(), Some((5, 0)));
+    let id = document.get_id().unwrap().iter().map(|it| it.as_string().unwrap().as_bytes()).collect::<Vec<_>>();
+    assert_eq!(id, [[0x01], [0x03]]);
+    Ok(())
+}
+
+#[test]
+fn test_reused_object_slot() -> Result<()> {
+    let base = common::build_pdf(&common::catalog_with_pages(&["/Rotate 0"], vec![]));
+    let bytes = common::append_update(
+        base,
+        &[
+            ((2, 0), "<< /Type /Pages /Kids [3 1 R] /Count 1 >>".to_string()),
+            ((3, 1), "<< /Type /Page /Parent 2 0 R /Rotate 90 >>".to_string()),
+        ],
+        "/Size 4 /Root 1 0 R",
+    );
+    let path = common::write_bytes("reused-slot", &bytes);
+    let mut document = PDFDocument::open(path)?;
+    let page_id = document.get_page_ids()[0];
+    assert_eq!(document.get_page_node(page_id).unwrap().get_page_obj_ref(), (3, 1));
+    match document.read_object_with_ref((3, 1))? {
+        Some(PDFObject::IndirectObject(3, 1, _)) => {}
+        _ => unreachable!(),
+    }
+    match document.read_object_with_ref((3, 0)) {
+        Err(PDFError::GenerationMismatch { requested, found }) => {
+            assert_eq!(requested, (3, 0));
+            assert_eq!(found, (3, 1));
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_freed_object_slot() -> Result<()> {
+    let base = common::build_pdf(&common::catalog_with_pages(&["/Annots [4 0 R]"], vec![
+        "<< /Type /Annot /Subtype /Text /Rect [0 0 10 10] >>".to_string(),
+    ]));
+    // Object 4 is freed with generation 1, leaving a dangling reference on the page
+    let text = String::from_utf8_lossy(&base).into_owned();
+    let offset = text.find("\n4 0 obj").unwrap() + 1;
+    let bytes = text.replace(&format!("{:010} 00000 n", offset), "0000000000 00001 f").into_bytes();
+    let mut document = PDFDocument::from_bytes(bytes)?;
+    assert!(document.read_object_with_ref((4, 0))?.is_none());
+    assert!(document.read_object_with_ref((4, 1))?.is_none());
+    assert!(document.read_object_with_ref((9, 0))?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_object_revisions() -> Result<()> {
+    let base = common::build_pdf(&common::catalog_with_pages(&["/Contents 4 0 R"], vec![
+        common::stream("", "BT ET"),
+        "<< /Producer (base) >>".to_string(),
+    ]));
+    let first = common::append_update(
+        base,
+        &[
+            ((5, 0), "<< /Producer (first) >>".to_string()),
+            ((6, 0), "<< /Type /Annot /Subtype /Text /Rect [0 0 10 10] >>".to_string()),
+        ],
+        "/Size 7 /Root 1 0 R /Info 5 0 R",
+    );
+    let page = "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /Annots [6 0 R] >>".to_string();
+    let mut bytes = common::append_update(first, &[((3, 0), page)], "/Size 7 /Root 1 0 R /Info 5 0 R");
+    let mut document = PDFDocument::from_bytes(bytes.clone())?;
+    assert_eq!(document.xref_sections(), 3);
+    assert_eq!(document.object_revision(ObjectId::new(3, 0)), Some(2));
+    assert_eq!(document.object_revision((5, 0)), Some(1));
+    assert_eq!(document.object_revision((6, 0)), Some(1));
+    for untouched in [(1, 0), (2, 0), (4, 0)] {
+        assert_eq!(document.object_revision(untouched), Some(0));
+    }
+    assert_eq!(document.object_revision((5, 1)), None);
+    assert_eq!(document.object_revision((9, 0)), None);
+    assert_eq!(document.get_xref_entry((3, 0)).map(|it| it.get_revision()), Some(2));
+
+    let changes = document.revision_changes();
+    assert_eq!(changes.iter().map(|it| it.get_revision()).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(changes[0].get_added(), [(1, 0), (2, 0), (3, 0), (4, 0), (5, 0)]);
+    assert!(changes[0].get_changed().is_empty());
+    assert_eq!(changes[1].get_added(), [(6, 0)]);
+    assert_eq!(changes[1].get_changed(), [(5, 0)]);
+    assert_eq!(changes[2].get_changed(), [(3, 0)]);
+    assert!(changes[2].get_added().is_empty() && changes[2].get_deleted().is_empty());
+    // Overrides don't rewrite the history
+    document.override_xref_entry((4, 0), XEntryKind::Free);
+    assert_eq!(document.object_revision((4, 0)), Some(0));
+
+    // A third update lists the contents and the Info where they already are and frees the
+    // annotation
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    let contents = text.find("4 0 obj").unwrap();
+    let info = text.rfind("5 0 obj").unwrap();
+    let prev = text[text.rfind("startxref").unwrap() + 9..].split_whitespace().next().unwrap().to_string();
+    let xref = bytes.len();
+    bytes.extend_from_slice(format!(
+        "xref\n4 3\n{:010} 00000 n \n{:010} 00000 n \n0000000000 00001 f \ntrailer\n<< /Size 7 /Root 1 0 R /Prev {} >>\nstartxref\n{}\n%%EOF\n",
+        contents, info, prev, xref
+    ).as_bytes());
+    let document = PDFDocument::from_bytes(bytes)?;
+    let changes = document.revision_changes();
+    assert_eq!(changes.len(), 4);
+    assert_eq!(changes[3].get_deleted(), [(6, 0)]);
+    assert!(changes[3].get_added().is_empty() && changes[3].get_changed().is_empty());
+    assert_eq!(document.object_revision((4, 0)), Some(0));
+    assert_eq!(document.object_revision((5, 0)), Some(1));
+    assert_eq!(document.object_revision((6, 0)), None);
+    assert_eq!(document.get_xref_entry((6, 1)).map(|it| it.get_revision()), Some(3));
+    Ok(())
+}
+
+#[test]
+fn test_read_entry() -> Result<()> {
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    for index in 0..50 {
+        if let Some((entry, object)) = document.read_entry(index)? {
+            let (obj_num, gen_num, _) = object.as_indirect_object().unwrap();
+            assert_eq!((entry.get_obj_num(), entry.get_gen_num()), (obj_num, gen_num));
+        }
+    }
+    assert!(document.get_warnings().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_read_entry_with_shifted_offset() -> Result<()> {
+    let mut bytes = common::build_pdf(&common::catalog_with_pages(&[""], vec![
+        "<< /Producer (test) >>".to_string(),
+    ]));
+    // Point the entry of object 4 to object 3
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    let object3 = format!("{:010} 00000 n ", text.find("3 0 obj").unwrap());
+    let object4 = format!("{:010} 00000 n ", text.find("4 0 obj").unwrap());
+    let at = text.rfind(&object4).unwrap();
+    bytes.splice(at..at + object4.len(), object3.bytes());
+    let path = common::write_bytes("shifted-offset", &bytes);
+    let mut document = PDFDocument::open(path)?;
+    let index = document.find_xref_index(|entry| entry.get_obj_num() == 4).unwrap();
+    let (entry, object) = document.read_entry(index)?.unwrap();
+    assert_eq!(entry.get_obj_num(), 4);
+    assert!(matches!(object, PDFObject::IndirectObject(3, 0, _)));
+    assert_eq!(document.get_warnings().len(), 1);
+    Ok(())
+}
+
+fn open_with_catalog(name: &str, catalog: &str) -> Result<PDFDocument> {
+    let path = common::write_pdf(name, &[
+        catalog.to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+        "<< /Marked true /UserProperties true >>".to_string(),
+    ]);
+    PDFDocument::open(path)
+}
+
+#[test]
+fn test_catalog_lang_and_mark_info() -> Result<()> {
+    let document = open_with_catalog(
+        "catalog-tagged",
+        "<< /Type /Catalog /Pages 2 0 R /Lang (en-US) /MarkInfo << /Marked true /Suspects true >> >>",
+    )?;
+    let catalog = document.get_catalog();
+    assert_eq!(catalog.lang(), Some("en-US"));
+    let mark_info = catalog.mark_info();
+    assert!(mark_info.is_marked() && mark_info.has_suspects() && !mark_info.has_user_properties());
+
+    let document = open_with_catalog("catalog-indirect-mark-info", "<< /Type /Catalog /Pages 2 0 R /MarkInfo 4 0 R >>")?;
+    let mark_info = document.get_catalog().mark_info();
+    assert!(mark_info.is_marked() && !mark_info.has_suspects() && mark_info.has_user_properties());
+
+    let document = open_with_catalog("catalog-untagged", "<< /Type /Catalog /Pages 2 0 R >>")?;
+    assert_eq!(document.get_catalog().lang(), None);
+    assert_eq!(document.get_catalog().mark_info(), MarkInfo::default());
+
+    // A damaged /MarkInfo is read as untagged with a warning
+    let path = common::write_pdf("catalog-damaged-mark-info", &[
+        "<< /Type /Catalog /Pages 2 0 R /MarkInfo 4 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+        "<< /Marked true ) >>".to_string(),
+    ]);
+    let document = PDFDocument::open(path)?;
+    assert_eq!(document.get_catalog().mark_info(), MarkInfo::default());
+    let warnings = document.get_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].get_message().starts_with("Mark information is unreadable"));
+    Ok(())
+}
+
+#[test]
+fn test_stats() -> Result<()> {
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    let stats = document.stats();
+    assert_eq!(stats.get_pages(), 230);
+    assert!(stats.get_dictionaries() > 0 && stats.get_streams() > 0);
+    assert!(stats.get_fonts() > 0 && stats.get_stream_bytes() > 0);
+    assert!(stats.get_filters().get("FlateDecode").is_some_and(|count| *count > 0));
+    assert!(stats.get_in_use_entries() > 0);
+
+    let path = common::write_pdf("stats", &common::catalog_with_pages(&["/Contents 4 0 R"], vec![
+        common::stream("", "BT /F1 12 Tf ET"),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        common::stream("/Type /XObject /Subtype /Image /Filter /ASCIIHexDecode", "41>"),
+        "42".to_string(),
+        "[1 2 3]".to_string(),
+        "<< /Type /Page ) >>".to_string(),
+    ]));
+    let mut document = PDFDocument::open(path)?;
+    let stats = document.stats();
+    assert_eq!(stats.get_dictionaries(), 4);
+    assert_eq!(stats.get_streams(), 2);
+    assert_eq!(stats.get_numbers(), 1);
+    assert_eq!(stats.get_arrays(), 1);
+    assert_eq!(stats.get_unreadable(), 1);
+    assert_eq!(stats.get_fonts(), 1);
+    assert_eq!(stats.get_images(), 1);
+    assert_eq!(stats.get_stream_bytes(), 18);
+    assert_eq!(stats.get_filters().get("ASCIIHexDecode"), Some(&1));
+    assert_eq!(stats.get_in_use_entries(), 9);
+    assert_eq!(stats.get_free_entries(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_stream_declared_length_in() -> Result<()> {
+    let path = common::write_pdf("declared-length", &common::catalog_with_pages(&[""], vec![
+        "42".to_string(),
+    ]));
+    let mut document = PDFDocument::open(path)?;
+    let indirect = Stream::new(DictBuilder::new().reference("Length", (4, 0)).build(), Vec::new());
+    assert_eq!(indirect.declared_length(), None);
+    assert_eq!(indirect.declared_length_in(&mut document)?, Some(42));
+    let direct = Stream::new(DictBuilder::new().entry("Length", 7).build(), Vec::new());
+    assert_eq!(direct.declared_length_in(&mut document)?, Some(7));
+    Ok(())
+}
+
+#[test]
+fn test_object_streams() -> Result<()> {
+    // The 1.6 fixture uses cross-reference streams, so the object stream is built here
+    let path = common::write_pdf("object-streams", &common::catalog_with_pages(&[""], vec![
+        common::stream("/Type /ObjStm /N 2 /First 9", "5 0 6 11 << /A 1 >> << /B 2 >>"),
+    ]));
+    let mut document = PDFDocument::open(path)?;
+    let streams = document.object_streams()?;
+    assert_eq!(streams.len(), 1);
+    let stream = &streams[0];
+    assert_eq!(stream.get_obj_ref(), (4, 0));
+    assert_eq!(stream.get_n(), 2);
+    assert_eq!(stream.get_first(), 9);
+    assert_eq!(stream.get_entries(), &[(5, 0), (6, 11)]);
+    assert_eq!(stream.index_of(6), Some(1));
+    assert_eq!(stream.index_of(7), None);
+    // Cached after the first call
+    assert_eq!(document.object_streams()?.len(), 1);
+
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    assert!(document.object_streams()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_object_stream_bad_offsets() -> Result<()> {
+    // Object 6 points beyond the data and object 8 goes back before object 7
+    let build = |entries: &str| common::catalog_with_pages(&[""], vec![
+        common::stream(entries, "5 0 6 200 7 11 8 5 << /A 1 >> << /C 3 >>"),
+    ]);
+    let mut document = PDFDocument::open(common::write_pdf("object-stream-bad-offsets", &build("/Type /ObjStm /N 4 /First 19")))?;
+    let streams = document.object_streams()?;
+    assert_eq!(streams[0].get_entries(), &[(5, 0), (6, 200), (7, 11), (8, 5)]);
+    assert_eq!(streams[0].get_skipped(), &[1, 3]);
+    assert_eq!(streams[0].index_of(7), Some(2));
+    let objects = document.object_stream_objects((4, 0))?;
+    assert_eq!(objects.iter().map(|it| it.0).collect::<Vec<_>>(), [5, 7]);
+    assert!(objects[0].1 == pdf_rs::parse_object(b"<< /A 1 >>")?);
+    assert!(objects[1].1 == pdf_rs::parse_object(b"<< /C 3 >>")?);
+    let warnings = document.get_warnings().iter().map(|it| it.get_message()).collect::<Vec<_>>();
+    assert!(warnings.contains(&"Object stream 4 0 entry 1 (object 6) has invalid offset 200, skipped"));
+    assert!(warnings.contains(&"Object stream 4 0 entry 3 (object 8) has invalid offset 5, skipped"));
+    assert!(document.object_stream_objects((3, 0)).is_err());
+
+    // /N has to match the number of pairs
+    let mut document = PDFDocument::open(common::write_pdf("object-stream-bad-n", &build("/Type /ObjStm /N 3 /First 19")))?;
+    match document.object_streams() {
+        Err(PDFError::PDFParseError0(e)) => assert_eq!(e, "Object stream 4 0 declares /N 3 but its header holds 4 pairs"),
+        _ => panic!("expected an /N error"),
+    }
+    Ok(())
+}
+
+/// Collects the references nested in an object.
+fn collect_refs(object: &PDFObject, refs: &mut Vec<(u32, u16)>) {
+    match object {
+        PDFObject::ObjectRef(obj_num, gen_num) => refs.push((*obj_num, *gen_num)),
+        PDFObject::Array(elements) => elements.iter().for_each(|it| collect_refs(it, refs)),
+        PDFObject::Dict(dict) => dict.iter().for_each(|(_, it)| collect_refs(it, refs)),
+        PDFObject::Stream(stream) => stream.get_metadata().iter().for_each(|(_, it)| collect_refs(it, refs)),
+        _ => {}
+    }
+}
+
+#[test]
+fn test_import_page_subtree() -> Result<()> {
+    let path = common::write_pdf("import-source", &common::catalog_with_pages(
+        &["/Resources << /Font << /F1 5 0 R >> >> /Contents 6 0 R", "/Annots [9 0 R]"],
+        vec![
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            common::stream("", "BT /F1 12 Tf 72 712 Td (Hello) Tj ET"),
+        ],
+    ));
+    let mut src = PDFDocument::open(path)?;
+    let mut dest = DocumentBuilder::new();
+    let catalog = dest.reserve();
+    let (pages, mapping) = ObjectImporter::import(&mut dest, &mut src, (2, 0))?;
+    // The pages, the fonts and the content, but not the catalog, plus the missing annotation
+    assert_eq!(mapping.len(), 6);
+    assert!(!mapping.contains_key(&(1, 0)));
+    assert_eq!(mapping[&(2, 0)], pages);
+    assert!(dest.get(mapping[&(9, 0)]) == Some(&PDFObject::Null));
+    for (_, object) in dest.iter() {
+        let mut refs = Vec::new();
+        collect_refs(object, &mut refs);
+        assert!(refs.iter().all(|it| dest.get(*it).is_some()), "dangling reference");
+    }
+
+    dest.set(catalog, DictBuilder::new().name("Type", "Catalog").reference("Pages", pages).build());
+    dest.set_root(catalog);
+    let mut buf = Vec::new();
+    dest.write(&mut buf)?;
+    let mut document = PDFDocument::open(common::write_bytes("import-dest", &buf))?;
+    assert_eq!(document.get_page_num(), 2);
+    let page_id = document.get_page_ids()[0];
+    assert_eq!(Page::new(page_id).text(&mut document)?, "Hello");
+
+    // A single page is copied without the page tree above it and without /Parent
+    let mut dest = DocumentBuilder::default();
+    let (page, mapping) = ObjectImporter::import(&mut dest, &mut src, (3, 0))?;
+    assert_eq!(page, (1, 0));
+    let mut copied = mapping.keys().copied().collect::<Vec<_>>();
+    copied.sort();
+    assert_eq!(copied, [(3, 0), (5, 0), (6, 0)]);
+    let copy = dest.get(page).and_then(|it| it.as_dict()).unwrap();
+    assert!(copy.get("Parent").is_none() && copy.get("Contents").is_some());
+
+    // An object keeps the generation it is set under
+    dest.set((7, 2), PDFObject::Null);
+    assert!(dest.get((7, 2)).is_some() && dest.get((7, 0)).is_none());
+    assert_eq!(dest.iter().last().map(|(obj_ref, _)| obj_ref), Some((7, 2)));
+    let mut buf = Vec::new();
+    dest.write(&mut buf)?;
+    assert!(String::from_utf8_lossy(&buf).contains("\n7 2 obj\n"));
+    Ok(())
+}
+
+#[test]
+fn test_trailer_prev_and_size_checks() -> Result<()> {
+    let objects = common::catalog_with_pages(&[""], vec![]);
+    let bytes = common::build_pdf_with_trailer(&objects, "/Size 4 /Root 1 0 R /Prev 123456789");
+    let path = common::write_bytes("prev-out-of-range", &bytes);
+    let message = format!("trailer /Prev 123456789 exceeds file size {}", bytes.len());
+    match PDFDocument::open(path.clone()) {
+        Err(PDFError::PDFParseError0(e)) => assert_eq!(e, message),
+        _ => panic!("expected an out of range /Prev error"),
+    }
+    let document = PDFDocument::open_with_options(path, ParseOptions::lenient())?;
+    assert_eq!(document.get_page_num(), 1);
+    assert!(document.get_warnings().iter().any(|it| it.get_message() == message));
+
+    let path = common::write_bytes("prev-header", &common::build_pdf_with_trailer(&objects, "/Size 4 /Root 1 0 R /Prev 0"));
+    match PDFDocument::open(path) {
+        Err(PDFError::PDFParseError0(e)) => assert_eq!(e, "trailer /Prev 0 doesn't point at an xref table"),
+        _ => panic!("expected a /Prev error"),
+    }
+
+    let path = common::write_bytes("undersized-size", &common::build_pdf_with_trailer(&objects, "/Size 2 /Root 1 0 R"));
+    let document = PDFDocument::open(path)?;
+    assert_eq!(document.get_page_num(), 1);
+    let warnings = document.get_warnings();
+    let size_warnings = warnings.iter().filter(|it| it.get_message().starts_with("trailer /Size")).collect::<Vec<_>>();
+    assert_eq!(size_warnings.len(), 1);
+    assert_eq!(size_warnings[0].get_message(), "trailer /Size 2 doesn't match the highest object number 3");
+    Ok(())
+}
+
+#[test]
+fn test_progress_callback() -> Result<()> {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = calls.clone();
+    let options = ParseOptions::strict().with_progress(move |phase, done, total| {
+        recorded.lock().unwrap().push((phase, done, total));
+        ControlFlow::Continue(())
+    });
+    let document = PDFDocument::open_with_options(PathBuf::from("document/pdfreference1.0.pdf"), options)?;
+    let calls = calls.lock().unwrap();
+    let mut phases = calls.iter().map(|it| it.0).collect::<Vec<_>>();
+    phases.dedup();
+    let mut expected = vec![Phase::Header, Phase::Xref, Phase::Catalog, Phase::PageTree];
+    if document.get_outline().is_some() {
+        expected.push(Phase::Outline);
+    }
+    assert_eq!(phases, expected);
+    let pages = calls.iter().filter(|it| it.0 == Phase::PageTree).collect::<Vec<_>>();
+    assert_eq!(pages.iter().map(|it| it.1).collect::<Vec<_>>(), [64, 128, 192, 230]);
+    assert!(pages.iter().all(|it| it.2 == 230));
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = calls.clone();
+    let options = ParseOptions::lenient().with_progress(move |phase, _, _| {
+        recorded.lock().unwrap().push(phase);
+        match phase {
+            Phase::PageTree => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        }
+    });
+    let result = PDFDocument::open_with_options(PathBuf::from("document/pdfreference1.0.pdf"), options);
+    assert!(matches!(result, Err(PDFError::Cancelled)));
+    // Nothing is reported after the callback asked to stop
+    assert_eq!(calls.lock().unwrap().last(), Some(&Phase::PageTree));
+    assert_eq!(calls.lock().unwrap().iter().filter(|it| **it == Phase::PageTree).count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_recovery_budget() -> Result<()> {
+    // Every stream declares a wrong length, so reading it scans the data for endstream
+    let data = "x".repeat(10_000);
+    let mut objects = common::catalog_with_pages(&[""], vec![]);
+    for _ in 0..20 {
+        objects.push(format!("<< /Length 10 >>\nstream\n{}\nendstream", data));
+    }
+    let path = common::write_pdf("recovery-budget", &objects);
+    let options = ParseOptions::lenient().with_max_recovery_scan(50_000);
+    let mut document = PDFDocument::open_with_options(path, options)?;
+    let mut recovered = 0;
+    for obj_num in 4..24 {
+        match read_stream_data(&mut document, obj_num) {
+            Ok(buf) => {
+                assert_eq!(buf, data.as_bytes());
+                recovered += 1;
+            }
+            Err(e) => assert!(matches!(e, PDFError::StreamLengthMismatch(10))),
+        }
+    }
+    assert_eq!(recovered, 4);
+    assert!(document.get_recovery_scanned() <= 50_000);
+    let message = "Recovery budget of 50000 bytes is exhausted";
+    assert!(document.get_warnings().iter().any(|it| it.get_message() == message));
+    // The object scan shares the exhausted budget
+    assert!(document.scan_objects()?.is_empty());
+    assert!(document.get_recovery_scanned() <= 50_000);
+    Ok(())
+}
+
+#[test]
+fn test_xref_kind() -> Result<()> {
+    let document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    assert_eq!(document.xref_kind(), XrefKind::Classic);
+    assert_eq!(document.xref_sections(), 2);
+    assert!(!document.uses_object_streams());
+
+    // The 1.6 fixture uses cross-reference streams only
+    let mut document = PDFDocument::open(PathBuf::from("document/PDF32000_2008.pdf"))?;
+    assert_eq!(document.xref_kind(), XrefKind::Stream);
+    assert!(document.get_page_num() > 0);
+    assert!(document.required_features().contains(&Feature::XrefStream));
+
+    // startxref points at a cross-reference stream listing objects 1 to 5 in rows of type 1
+    let objects = common::catalog_with_pages(&["/Contents 4 0 R"], vec![common::stream("", "BT (Streamed) Tj ET")]);
+    let mut bytes = b"%PDF-1.5\n".to_vec();
+    let mut hex = String::new();
+    for (index, object) in objects.iter().enumerate() {
+        hex.push_str(&format!("01{:08X}00", bytes.len()));
+        bytes.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+    }
+    let xref_offset = bytes.len();
+    hex.push_str(&format!("01{:08X}00>", xref_offset));
+    let xref_stream = common::stream("/Type /XRef /Size 6 /W [1 4 1] /Index [1 5] /Root 1 0 R /Filter /ASCIIHexDecode", &hex);
+    bytes.extend_from_slice(format!("5 0 obj\n{}\nendobj\nstartxref\n{}\n%%EOF\n", xref_stream, xref_offset).as_bytes());
+    let mut document = PDFDocument::open(common::write_bytes("xref-stream", &bytes))?;
+    assert_eq!(document.xref_kind(), XrefKind::Stream);
+    assert_eq!(document.xref_sections(), 1);
+    assert!(document.get_warnings().is_empty());
+    assert_eq!(document.get_page_num(), 1);
+    assert_eq!(Page::new(document.get_page_ids()[0]).text(&mut document)?, "Streamed");
+
+    let base = common::build_pdf(&common::catalog_with_pages(&[""], vec![]));
+    let bytes = common::append_update(
+        base,
+        &[((3, 0), "<< /Type /Page /Parent 2 0 R /Rotate 90 >>".to_string())],
+        "/Size 4 /Root 1 0 R /XRefStm 0",
+    );
+    let document = PDFDocument::open(common::write_bytes("hybrid-xref", &bytes))?;
+    assert_eq!(document.xref_kind(), XrefKind::Hybrid);
+    assert_eq!(document.xref_sections(), 2);
+    // The /XRefStm doesn't point at a stream, so no object is compressed
+    assert!(!document.uses_object_streams());
+    Ok(())
+}
+
+#[test]
+fn test_hybrid_object_streams() -> Result<()> {
+    // The page tree, objects 2 to 4, is packed into object stream 5, which only the
+    // cross-reference stream 6 lists
+    let packed = [
+        "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /MediaBox [0 0 200 300] >>",
+        "<< /Type /Page /Parent 2 0 R >>",
+        "<< /Type /Page /Parent 2 0 R /Rotate 90 /Contents 7 0 R >>",
+    ];
+    let (mut header, mut body) = (String::new(), String::new());
+    for (index, object) in packed.iter().enumerate() {
+        header.push_str(&format!("{} {} ", index + 2, body.len()));
+        body.push_str(object);
+        body.push('\n');
+    }
+    let object_stream = common::stream(&format!("/Type /ObjStm /N 3 /First {}", header.len()), &(header.clone() + &body));
+    // Rows of type 2 with the object stream number and the index
+    let hex = (0..3u8).map(|index| format!("020005{:02X}", index)).collect::<String>() + ">";
+    let xref_stream = common::stream("/Type /XRef /Size 8 /W [1 2 1] /Index [2 3] /Filter /ASCIIHexDecode", &hex);
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "null".to_string(),
+        "null".to_string(),
+        "null".to_string(),
+        object_stream,
+        xref_stream,
+        common::stream("", "BT (Packed) Tj ET"),
+    ];
+    let draft = String::from_utf8_lossy(&common::build_pdf(&objects)).into_owned();
+    let xref_stm = draft.find("\n6 0 obj").unwrap() + 1;
+    let bytes = common::build_pdf_with_trailer(&objects, &format!("/Size 8 /Root 1 0 R /XRefStm {}", xref_stm));
+    // The xref table lists the packed objects as free, like for readers predating object streams
+    let mut text = String::from_utf8_lossy(&bytes).into_owned();
+    for obj_num in 2..=4 {
+        let offset = text.find(&format!("\n{} 0 obj", obj_num)).unwrap() + 1;
+        text = text.replace(&format!("{:010} 00000 n", offset), "0000000000 00000 f");
+    }
+    let mut document = PDFDocument::open(common::write_bytes("hybrid-object-streams", text.as_bytes()))?;
+    assert_eq!(document.xref_kind(), XrefKind::Hybrid);
+    assert!(document.uses_object_streams());
+    assert!(document.get_warnings().is_empty());
+    let entry = document.get_xref_slice().iter().find(|it| it.get_obj_num() == 4).unwrap().clone();
+    assert_eq!((entry.get_object_stream(), entry.get_value()), (Some(5), 2));
+
+    assert_eq!(document.get_page_num(), 2);
+    let pages = document.get_page_ids();
+    // The MediaBox is inherited from the page tree root
+    assert!(Page::new(pages[0]).transform(&document).is_ok());
+    let rotate = document.get_page_node(pages[1]).and_then(|it| it.get_attr("Rotate")).and_then(|it| it.as_number()).map(|it| it.as_f64());
+    assert_eq!(rotate, Some(90.0));
+    assert_eq!(Page::new(pages[1]).text(&mut document)?, "Packed");
+    match document.read_object_with_ref((3, 0))? {
+        Some(PDFObject::IndirectObject(3, 0, object)) => assert!(object.as_dict().is_some()),
+        _ => panic!("object 3 must be read from its object stream"),
+    }
+    assert!(document.required_features().iter().all(|it| it.is_supported()));
+    let mut dump = String::new();
+    document.dump_xref(&mut dump).unwrap();
+    assert!(dump.contains("0000000001 00000 n % in object stream 5"));
+
+    // Preserving the numbering writes the packed objects on their own, once
+    let mut saved = Vec::new();
+    document.save(&mut saved, &WriteOptions::new().with_preserve_numbering(true))?;
+    let text = String::from_utf8_lossy(&saved);
+    assert!(!text.contains("/ObjStm") && !text.contains("/XRef"));
+    assert_eq!(text.matches("\n3 0 obj\n").count(), 1);
+    let mut document = PDFDocument::from_bytes(saved)?;
+    assert!(!document.uses_object_streams());
+    assert!(document.get_warnings().is_empty());
+    assert_eq!(document.get_page_num(), 2);
+    assert_eq!(Page::new(document.get_page_ids()[1]).text(&mut document)?, "Packed");
+    for obj_num in [5, 6] {
+        assert!(document.read_object_with_ref((obj_num, 0))?.is_none());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_open_partial() -> Result<()> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R /Outlines [".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /Rotate 90 >>".to_string(),
+        "<< /Title (Partial) >>".to_string(),
+    ];
+    // The catalog is broken, so opening fails as a whole
+    let bytes = common::build_pdf_with_trailer(&objects, "/Size 5 /Root 1 0 R /Info 4 0 R");
+    let path = common::write_bytes("partial-catalog", &bytes);
+    assert!(PDFDocument::open(path.clone()).is_err());
+    let (mut partial, errors) = PDFDocument::open_partial(path);
+    assert_eq!(partial.get_unavailable(), [Facet::Catalog]);
+    assert!(partial.is_available(Facet::Xref) && !partial.is_complete());
+    assert_eq!(errors.len(), 1);
+    let document = partial.get_document_mut();
+    assert_eq!(document.get_describe().and_then(|it| it.get_title()), Some("Partial"));
+    assert_eq!(document.get_page_num(), 0);
+    match document.read_object_with_ref((3, 0))? {
+        Some(PDFObject::IndirectObject(3, 0, page)) => assert!(page.as_dict().is_some_and(|it| it.contain("Rotate"))),
+        _ => panic!("object 3 must be readable"),
+    }
+
+    // Without an xref table the objects are found by scanning, the trailer is lost
+    let mut text = String::from_utf8_lossy(&bytes).into_owned();
+    let start = text.rfind("startxref\n").unwrap() + "startxref\n".len();
+    text.replace_range(start.., "999999\n%%EOF\n");
+    let (mut partial, errors) = PDFDocument::open_partial(common::write_bytes("partial-xref", text.as_bytes()));
+    assert_eq!(partial.get_unavailable(), [Facet::Xref, Facet::Catalog]);
+    assert_eq!(errors.len(), 2);
+    let document = partial.get_document_mut();
+    // The broken catalog isn't found
+    let found = document.get_xref_slice().iter().map(|it| it.get_obj_num()).collect::<Vec<_>>();
+    assert_eq!(found, [2, 3, 4]);
+    assert!(matches!(document.read_object_with_ref((4, 0))?, Some(PDFObject::IndirectObject(4, 0, _))));
+    // Without a /Root neither save writes a trailer pointing at a made up catalog
+    document.set_describe(PDFDescribe::new().with_title("Rootless"));
+    let mut out = Vec::new();
+    assert!(matches!(document.save_incremental(&mut out), Err(PDFError::ObjectAttrMiss(_))));
+    assert!(matches!(document.save(&mut out, &WriteOptions::default()), Err(PDFError::ObjectAttrMiss(_))));
+    assert!(out.is_empty());
+    let report = document.verify()?;
+    assert!(report.findings_of(Category::MissingKey).any(|it| it.get_message() == "Trailer has no /Root"));
+
+    // A /Root which isn't a dictionary can't get an outline
+    let (mut partial, _) = PDFDocument::open_partial(common::write_pdf("partial-root", &["(not a catalog)".to_string()]));
+    let document = partial.get_document_mut();
+    document.set_outline(OutlineBuilder::new());
+    let mut out = Vec::new();
+    assert!(matches!(document.save_incremental(&mut out), Err(PDFError::ObjectAttrMiss(_))));
+    assert!(out.is_empty());
+
+    // A file which can't be opened leaves nothing available
+    let (partial, errors) = PDFDocument::open_partial(PathBuf::from("document/missing.pdf"));
+    assert!(matches!(errors[0], PDFError::IOError(_)));
+    assert!(!partial.is_available(Facet::Header) && partial.get_document().get_xref_slice().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_page_tree_topology() -> Result<()> {
+    let document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    let tree = document.get_page_tree();
+    let root = tree.get_root_node()?;
+    assert_eq!(root.get_page_id(), tree.get_root_id());
+    assert!(tree.parent_of(root.get_page_id())?.is_none());
+    let leaves = tree.leaf_ids()?;
+    assert_eq!(leaves, document.get_page_ids());
+    assert_eq!(leaves.len(), 230);
+    for child in tree.children_of(root.get_page_id())? {
+        assert_eq!(child.get_parent_id(), Some(root.get_page_id()));
+    }
+
+    // Climbing from the first page reaches the root, whose first descendants lead back to it
+    let page = Page::new(leaves[0]);
+    assert!(tree.children_of(leaves[0])?.is_empty());
+    let mut ancestors = Vec::new();
+    let mut node = page.parent(&document)?;
+    while let Some(parent) = node {
+        ancestors.push(parent.get_page_id());
+        node = tree.parent_of(parent.get_page_id())?;
+    }
+    assert_eq!(ancestors.last(), Some(&root.get_page_id()));
+    let mut first = root.get_page_id();
+    for ancestor in ancestors.iter().rev().skip(1) {
+        assert_eq!(tree.children_of(first)?[0].get_page_id(), *ancestor);
+        first = *ancestor;
+    }
+    assert_eq!(tree.children_of(first)?[0].get_page_id(), page.node(&document)?.get_page_id());
+
+    let missing = ObjectId::from((99999, 0));
+    assert!(matches!(tree.node(missing), Err(PDFError::MissingNode(id)) if id == missing));
+    assert!(matches!(Page::new(missing).parent(&document), Err(PDFError::MissingNode(_))));
+
+    // A /Pages reference to a missing object leaves a dangling root
+    let objects = ["<< /Type /Catalog /Pages 9 0 R >>".to_string()];
+    let (partial, _) = PDFDocument::open_partial(common::write_pdf("dangling-root", &objects));
+    let tree = partial.get_document().get_page_tree();
+    assert!(matches!(tree.get_root_node(), Err(PDFError::MissingNode(_))));
+    assert!(matches!(tree.leaf_ids(), Err(PDFError::MissingNode(_))));
+    assert!(partial.get_document().get_page_ids().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_indexed_xref_lookup() -> Result<()> {
+    let pages = 3000;
+    let kids = (0..pages).map(|i| format!("{} 0 R", i + 3)).collect::<Vec<_>>().join(" ");
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages),
+    ];
+    objects.extend((0..pages).map(|_| "<< /Type /Page /Parent 2 0 R >>".to_string()));
+    let bytes = common::build_pdf(&objects);
+    // The update reuses the slot of the last page with a new generation
+    let kids = kids.replace(&format!("{} 0 R", pages + 2), &format!("{} 1 R", pages + 2));
+    let bytes = common::append_update(
+        bytes,
+        &[
+            ((2, 0), format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages)),
+            ((3, 0), "<< /Type /Page /Parent 2 0 R /Rotate 90 >>".to_string()),
+        ],
+        &format!("/Size {} /Root 1 0 R", pages + 3),
+    );
+    let bytes = common::append_update(
+        bytes,
+        &[((pages + 2, 1), "<< /Type /Page /Parent 2 0 R /Rotate 180 >>".to_string())],
+        &format!("/Size {} /Root 1 0 R", pages + 3),
+    );
+    let mut document = PDFDocument::open(common::write_bytes("indexed-xref", &bytes))?;
+    assert_eq!(document.get_page_num(), pages as usize);
+    assert_eq!(document.get_xref_slice().len(), pages as usize + 3);
+    let rotate = |document: &PDFDocument, index: usize| {
+        document.get_page_node(document.get_page_ids()[index]).and_then(|it| it.get_attrs().get_u64_num("Rotate"))
+    };
+    assert_eq!(rotate(&document, 0), Some(90));
+    assert_eq!(rotate(&document, 1), None);
+    assert_eq!(rotate(&document, pages as usize - 1), Some(180));
+    assert!(document.get_xref_entry((pages + 2, 1)).is_some_and(|it| it.is_using()));
+    assert!(document.get_xref_entry((pages + 2, 0)).is_none());
+    assert!(document.get_xref_entry((pages + 3, 0)).is_none());
+    assert!(matches!(document.read_object_with_ref((pages + 2, 0)), Err(PDFError::GenerationMismatch { .. })));
+    Ok(())
+}
+
+#[test]
+fn test_get_path() -> Result<()> {
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    let catalog = PDFObject::Dict(document.get_catalog().get_attrs().clone());
+    // The first page sits below two intermediate page tree nodes
+    let media_box = document.get_path(&catalog, "Pages.Kids[0].Kids[0].Kids[0].MediaBox")?.unwrap();
+    assert_eq!(writer::to_bytes(&media_box), b"[0 0 612 792]");
+    let segs = parse_path("Pages.Count")?;
+    assert_eq!(segs, [PathSeg::Key("Pages".to_string()), PathSeg::Key("Count".to_string())]);
+    assert!(document.get_path_segs(&catalog, &segs)? == Some(PDFObject::from(230)));
+    assert!(document.get_path(&catalog, "Pages.Missing")?.is_none());
+    assert!(document.get_path(&catalog, "Pages.Kids[100000]")?.is_none());
+
+    match document.get_path(&catalog, "Pages.Count.Kids") {
+        Err(PDFError::PathSegmentMismatch { segment, found }) => {
+            assert_eq!(segment, "Pages.Count.Kids");
+            assert_eq!(found, "number");
+        }
+        _ => panic!("expected a segment mismatch"),
+    }
+    assert!(matches!(document.get_path(&catalog, "Pages.Kids[0]x"), Err(PDFError::InvalidPath(_))));
+    assert!(matches!(document.get_path(&catalog, "Pages..Kids"), Err(PDFError::InvalidPath(_))));
+    Ok(())
+}
+
+#[test]
+fn test_dump_xref() -> Result<()> {
+    let base = common::build_pdf(&common::catalog_with_pages(&[""], vec![]));
+    let bytes = common::append_update(base, &[((5, 0), "(x)".to_string()), ((6, 0), "(y)".to_string())], "/Size 7 /Root 1 0 R");
+    // Point object 6 past the end of the file
+    let text = String::from_utf8(bytes).unwrap().replace("0000000324 00000 n", "0000099999 00000 n");
+    let document = PDFDocument::open(common::write_bytes("dump-xref", text.as_bytes()))?;
+    let mut dump = String::new();
+    document.dump_xref(&mut dump).unwrap();
+    assert_eq!(dump, "\
+xref
+0 4
+0000000000 65535 f
+0000000009 00000 n
+0000000058 00000 n
+0000000115 00000 n
+5 2
+0000000305 00000 n
+0000099999 00000 n % offset beyond end of file
+");
+    let entry = document.get_xref_slice().iter().find(|it| it.get_obj_num() == 1).unwrap();
+    assert_eq!(entry.to_string(), "0000000009 00000 n");
+    assert!(format!("{:?}", entry).starts_with("XEntry {"));
+    Ok(())
+}
+
+#[test]
+fn test_page_content_bytes() -> Result<()> {
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    let page_id = document.get_page_ids()[0];
+    let content = document.page_content_bytes(page_id)?;
+    assert!(content.starts_with(b"1 1 1 rg\n/GS0 gs\n"));
+    let operations = document.page_content_operations(page_id)?;
+    let operators = operations.iter().take(4).map(|it| it.get_operator()).collect::<Vec<_>>();
+    assert_eq!(operators, ["rg", "gs", "re", "f"]);
+    assert!(operations[1].get_operands()[0] == PDFObject::Named("GS0".into()));
+    assert!(operations.iter().any(|it| it.get_operator() == "Tj" || it.get_operator() == "TJ"));
+
+    let path = common::write_pdf("no-contents", &common::catalog_with_pages(&[""], vec![]));
+    let mut document = PDFDocument::open(path)?;
+    let page_id = document.get_page_ids()[0];
+    assert!(document.page_content_bytes(page_id)?.is_empty());
+    assert!(document.page_content_operations(page_id)?.is_empty());
+    Ok(())
+}
+
+/// Opens a one page document with the given `/Contents`, objects 4 and 5 are content
+/// streams, object 6 an array of both and object 7 a dictionary.
+fn open_with_contents(name: &str, contents: &str) -> Result<PDFDocument> {
+    let path = common::write_pdf(name, &common::catalog_with_pages(&[&format!("/Contents {}", contents)], vec![
+        common::stream("", "BT"),
+        common::stream("", "ET"),
+        "[4 0 R 5 0 R]".to_string(),
+        "<< /Type /Font >>".to_string(),
+    ]));
+    PDFDocument::open(path)
+}
+
+#[test]
+fn test_nested_contents() -> Result<()> {
+    let mut document = open_with_contents("contents-indirect-array", "6 0 R")?;
+    let page_id = document.get_page_ids()[0];
+    assert_eq!(document.page_content_bytes(page_id)?, b"BT\nET\n");
+
+    let mut document = open_with_contents("contents-null", "[5 0 R null 4 0 R 9 0 R]")?;
+    let page_id = document.get_page_ids()[0];
+    assert_eq!(document.page_content_bytes(page_id)?, b"ET\nBT\n");
+    let warnings = document.get_warnings();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].get_message(), "Page 3 0 /Contents element 1 is null, skipped");
+
+    let mut document = open_with_contents("contents-not-stream", "[4 0 R 7 0 R]")?;
+    let page_id = document.get_page_ids()[0];
+    match document.page_content_bytes(page_id) {
+        Err(e @ PDFError::ContentNotStream { .. }) => {
+            assert_eq!(e.to_string(), "Page (3, 0) /Contents element 1 (object 7 0) is a dictionary instead of a stream");
+            let PDFError::ContentNotStream { page, index, object, found } = e else { unreachable!() };
+            assert_eq!(page, (3, 0));
+            assert_eq!(index, 1);
+            assert_eq!(object, Some((7, 0)));
+            assert_eq!(found, "dictionary");
+        }
+        _ => panic!("expected ContentNotStream"),
+    }
+
+    // A direct element has no object, the value of /Contents is element 0
+    let mut document = open_with_contents("contents-direct-not-stream", "[4 0 R 12]")?;
+    let page_id = document.get_page_ids()[0];
+    assert!(matches!(
+        document.page_content_bytes(page_id),
+        Err(PDFError::ContentNotStream { index: 1, object: None, found: "number", .. })
+    ));
+    let mut document = open_with_contents("contents-dictionary", "7 0 R")?;
+    let page_id = document.get_page_ids()[0];
+    assert!(matches!(
+        document.page_content_bytes(page_id),
+        Err(PDFError::ContentNotStream { index: 0, object: Some((7, 0)), found: "dictionary", .. })
+    ));
+
+    // An indirect object wrapping another indirect object holding the array
+    let path = common::write_pdf("contents-double-wrapped", &common::catalog_with_pages(&["/Contents 6 0 R"], vec![
+        common::stream("", "BT"),
+        common::stream("", "ET"),
+        "6 0 obj\n[4 0 R 5 0 R]\nendobj".to_string(),
+    ]));
+    let mut document = PDFDocument::open(path)?;
+    let page_id = document.get_page_ids()[0];
+    assert_eq!(document.page_content_bytes(page_id)?, b"BT\nET\n");
+
+    // Contents resolving to null are empty
+    let mut document = open_with_contents("contents-missing", "9 0 R")?;
+    let page_id = document.get_page_ids()[0];
+    assert!(document.page_content_bytes(page_id)?.is_empty());
+    let warnings = document.get_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].get_message(), "Page 3 0 /Contents is null, skipped");
+    Ok(())
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_page_text_matches_helper() -> Result<()> {
+    let path = common::write_pdf("page-text-helper", &common::catalog_with_pages(
+        &["/Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R"],
+        vec![
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            common::stream("", "BT /F1 12 Tf 72 712 Td (Hello) Tj 0 -20 Td (World) Tj 200 0 Td (again) Tj ET"),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    let page_id = document.get_page_ids()[0];
+    let page = Page::new(page_id);
+    let layout = TextExtractionOptions::new().with_layout(true);
+    let expected = "Hello\nWorld again";
+    assert_eq!(page.text(&mut document)?, expected);
+    assert_eq!(pdf_rs::helper::extract_page_text(&mut document, page_id)?.as_deref(), Some(expected));
+    // `again` starts 200 points right of `World`, in column 33 with columns half the font size wide
+    let expected = format!("Hello\nWorld{}again\n", " ".repeat(28));
+    assert_eq!(page.text_with(&mut document, &layout)?, expected);
+    let text = pdf_rs::helper::extract_page_text_with(&mut document, page_id, &layout)?;
+    assert_eq!(text.as_deref(), Some(expected.as_str()));
+    Ok(())
+}
+
+#[test]
+fn test_coordinate_spaces() -> Result<()> {
+    let path = common::write_pdf("media-box-offset", &[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 /MediaBox [20 20 620 812] >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        common::stream("", "BT /F1 12 Tf 320 416 Td (Hello) Tj ET"),
+    ]);
+    let mut document = PDFDocument::open(path)?;
+    let page = Page::new(document.get_page_ids()[0]);
+    let transform = page.transform(&document)?;
+    assert_eq!(transform, PageTransform::new([620.0, 812.0, 20.0, 20.0]));
+    assert_eq!(transform.get_media_box(), [20.0, 20.0, 620.0, 812.0]);
+
+    let point = (320.0, 416.0);
+    assert_eq!(transform.convert(point, CoordinateSpace::User), point);
+    assert_eq!(transform.to_media_relative(point), (300.0, 396.0));
+    assert_eq!(transform.to_normalized(point), (0.5, 0.5));
+    assert_eq!(transform.from_media_relative((300.0, 396.0)), point);
+    assert_eq!(transform.from_normalized((0.5, 0.5)), point);
+
+    for (space, expected) in [
+        (CoordinateSpace::User, (320.0, 416.0)),
+        (CoordinateSpace::MediaRelative, (300.0, 396.0)),
+        (CoordinateSpace::Normalized, (0.5, 0.5)),
+    ] {
+        let options = TextExtractionOptions::new().with_coordinate_space(space);
+        let fragments = page.fragments_with(&mut document, &options)?;
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].get_text(), "Hello");
+        assert_eq!((fragments[0].get_x(), fragments[0].get_y()), expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_text_under_annotation() -> Result<()> {
+    // Without /Widths every glyph advances 6 units at 12 points
+    let content = "BT /F1 12 Tf 72 712 Td (The quick brown fox jumps) Tj 0 -20 Td [(over the la) -0 (zy dog)] TJ ET";
+    let path = common::write_pdf("annotations", &common::catalog_with_pages(
+        &["/Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R /Annots 6 0 R"],
+        vec![
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            common::stream("", content),
+            "[7 0 R 8 0 R 9 0 R << /Subtype /Text >>]".to_string(),
+            // Acrobat order: upper left, upper right, lower left, lower right
+            "<< /Subtype /Highlight /Rect [0 0 612 792] /QuadPoints [132 722 186 722 132 709 186 709] >>".to_string(),
+            "<< /Subtype /Square /Rect [120 688 152 700] >>".to_string(),
+            // Specification order, spanning the end of the first and the start of the second line
+            "<< /Subtype /Highlight /Rect [0 0 1 1] /QuadPoints [186 709 222 709 222 722 186 722 72 689 96 689 72 702 96 702] >>"
+                .to_string(),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    assert_eq!(document.text_under_annotation(0, 0)?, "brown fox");
+    assert_eq!(document.text_under_annotation(0, 1)?, "lazy");
+    assert_eq!(document.text_under_annotation(0, 2)?, "jumps over");
+    assert!(matches!(document.text_under_annotation(0, 3), Err(PDFError::ObjectAttrMiss(_))));
+    assert!(matches!(document.text_under_annotation(0, 4), Err(PDFError::ObjectAttrMiss(_))));
+    assert!(matches!(document.text_under_annotation(1, 0), Err(PDFError::PageNotFound(_))));
+    Ok(())
+}
+
+#[test]
+fn test_declared_size() -> Result<()> {
+    let objects = common::catalog_with_pages(&[""], vec![]);
+    let is_size_warning = |message: &str| message.starts_with("trailer /Size");
+    for (size, warning) in [
+        (4, None),
+        (2, Some("trailer /Size 2 doesn't match the highest object number 3")),
+        (9, Some("trailer /Size 9 doesn't match the highest object number 3")),
+    ] {
+        let trailer = format!("/Size {} /Root 1 0 R", size);
+        let path = common::write_bytes(&format!("declared-size-{}", size), &common::build_pdf_with_trailer(&objects, &trailer));
+        let document = PDFDocument::open(path)?;
+        assert_eq!(document.declared_size(), Some(size));
+        assert_eq!(document.actual_max_object(), Some(3));
+        let warnings = document.get_warnings().iter().map(|it| it.get_message()).collect::<Vec<_>>();
+        match warning {
+            Some(warning) => assert!(warnings.contains(&warning)),
+            None => assert!(!warnings.iter().any(|it| is_size_warning(it))),
+        }
+    }
+
+    // Reserved but unset numbers still count towards the written /Size
+    let mut builder = DocumentBuilder::new();
+    let catalog = builder.reserve();
+    builder.reserve();
+    let pages = builder.reserve();
+    let page = builder.add(DictBuilder::new().name("Type", "Page").reference("Parent", pages).build());
+    let kids = ArrayBuilder::new().reference(page).build();
+    builder.set(pages, DictBuilder::new().name("Type", "Pages").entry("Kids", kids).entry("Count", 1).build());
+    builder.set(catalog, DictBuilder::new().name("Type", "Catalog").reference("Pages", pages).build());
+    builder.set_root(catalog);
+    let mut buf = Vec::new();
+    builder.write(&mut buf)?;
+    let document = PDFDocument::open(common::write_bytes("declared-size-writer", &buf))?;
+    assert_eq!(document.declared_size(), Some(5));
+    assert_eq!(document.actual_max_object(), Some(4));
+    assert!(!document.get_warnings().iter().any(|it| is_size_warning(it.get_message())));
+    Ok(())
+}
+
+/// Opens a one page document with the given objects starting at object 4, the first
+/// `fonts` of them are the fonts of the page.
+fn open_with_fonts(name: &str, fonts: usize, others: &[String]) -> Result<PDFDocument> {
+    let resources = (0..fonts).map(|i| format!("/F{} {} 0 R", i, i + 4)).collect::<Vec<_>>().join(" ");
+    let mut objects = common::catalog_with_pages(&[&format!("/Resources << /Font << {} >> >>", resources)], vec![]);
+    objects.extend_from_slice(others);
+    PDFDocument::open(common::write_pdf(name, &objects))
+}
+
+#[test]
+fn test_font_preflight() -> Result<()> {
+    // An embedded subset and a composite font embedding through its descendant
+    let mut document = open_with_fonts("fonts-embedded", 2, &[
+        "<< /Type /Font /Subtype /Type1 /BaseFont /ABCDEF+Minion /FontDescriptor 6 0 R >>".to_string(),
+        "<< /Type /Font /Subtype /Type0 /BaseFont /Gothic /DescendantFonts [7 0 R] >>".to_string(),
+        "<< /Type /FontDescriptor /FontFile3 8 0 R >>".to_string(),
+        "<< /Type /Font /Subtype /CIDFontType2 /FontDescriptor << /FontFile2 8 0 R >> >>".to_string(),
+        common::stream("", "font program"),
+    ])?;
+    let preflight = document.font_preflight()?;
+    let fonts = preflight.get_fonts();
+    assert_eq!(fonts.len(), 2);
+    assert_eq!(fonts[0].get_base_font(), Some("ABCDEF+Minion"));
+    assert!(fonts[0].is_embedded() && fonts[0].is_subset() && !fonts[0].is_standard_14());
+    assert_eq!(fonts[1].get_subtype(), Some("Type0"));
+    assert!(fonts[1].is_embedded() && !fonts[1].is_subset());
+    assert!(preflight.all_fonts_embedded());
+
+    // A standard 14 font passes without being embedded
+    let mut document = open_with_fonts("fonts-standard", 1, &[
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ])?;
+    let preflight = document.font_preflight()?;
+    let helvetica = &preflight.get_fonts()[0];
+    assert!(!helvetica.is_embedded() && helvetica.is_standard_14() && helvetica.is_acceptable());
+    assert!(preflight.all_fonts_embedded());
+
+    // Neither embedded nor exempt, also a composite font without a font program in its descendant
+    let mut document = open_with_fonts("fonts-missing", 2, &[
+        "<< /Type /Font /Subtype /TrueType /BaseFont /Arial-Black /FontDescriptor << /Flags 32 >> >>".to_string(),
+        "<< /Type /Font /Subtype /Type0 /BaseFont /Helvetica /DescendantFonts [6 0 R] >>".to_string(),
+        "<< /Type /Font /Subtype /CIDFontType0 >>".to_string(),
+    ])?;
+    let preflight = document.font_preflight()?;
+    assert!(!preflight.all_fonts_embedded());
+    let missing = preflight.missing().map(|it| it.get_obj_ref()).collect::<Vec<_>>();
+    assert_eq!(missing, [Some((4, 0)), Some((5, 0))]);
+
+    // Fonts of a form XObject, of an annotation appearance and inline, an unreadable font
+    // and one with a malformed font descriptor
+    let path = common::write_pdf("fonts-nested", &common::catalog_with_pages(
+        &["/Resources << /Font << /F0 4 0 R /F1 << /Type /Font /Subtype /TrueType /BaseFont /Inline >> /F2 5 0 R >> \
+           /XObject << /X0 6 0 R >> >> /Annots [8 0 R]"],
+        vec![
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            "42".to_string(),
+            common::stream("/Type /XObject /Subtype /Form /BBox [0 0 10 10] /Resources << /Font << /F0 7 0 R >> >>", ""),
+            "<< /Type /Font /Subtype /TrueType /BaseFont /FormFont /FontDescriptor [1 2] >>".to_string(),
+            "<< /Type /Annot /Subtype /Widget /Rect [0 0 10 10] /AP << /N << /On 9 0 R /Off 9 0 R >> >> >>".to_string(),
+            common::stream("/BBox [0 0 10 10] /Resources << /Font << /F0 10 0 R >> >>", ""),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /ZapfDingbats >>".to_string(),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    let preflight = document.font_preflight()?;
+    let fonts = preflight.get_fonts().iter().map(|it| (it.get_obj_ref(), it.get_base_font())).collect::<Vec<_>>();
+    assert_eq!(fonts, [
+        (Some((4, 0)), Some("Helvetica")),
+        (Some((5, 0)), None),
+        (Some((7, 0)), Some("FormFont")),
+        (Some((10, 0)), Some("ZapfDingbats")),
+        (None, Some("Inline")),
+    ]);
+    let missing = preflight.missing().map(|it| it.get_obj_ref()).collect::<Vec<_>>();
+    assert_eq!(missing, [Some((5, 0)), Some((7, 0)), None]);
+    Ok(())
+}
+
+#[test]
+fn test_fallback_text_decoding() -> Result<()> {
+    // Neither /Encoding nor /ToUnicode: a symbolic font, a nonsymbolic font, a font with an
+    // encoding, a font without flags and a Type1 font whose standard default lacks the euro
+    let truetype = |descriptor: &str| format!("<< /Type /Font /Subtype /TrueType /BaseFont /Custom {} >>", descriptor);
+    let path = common::write_pdf("fallback-decoding", &common::catalog_with_pages(
+        &["/Resources << /Font << /F0 5 0 R /F1 6 0 R /F2 7 0 R /F3 8 0 R /F4 9 0 R >> >> /Contents 4 0 R"],
+        vec![
+            common::stream("", "BT /F0 12 Tf <48656C6C6F20E974E9> Tj /F1 12 Tf 0 -20 Td <93486994> Tj \
+                               /F2 12 Tf 0 -20 Td (Hi) Tj /F3 12 Tf 0 -20 Td <93486994> Tj \
+                               /F4 12 Tf 0 -20 Td <3580> Tj ET"),
+            truetype("/FontDescriptor << /Flags 4 >>"),
+            truetype("/FontDescriptor << /Flags 32 >>"),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+            truetype(""),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Custom >>".to_string(),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    let page = Page::new(document.get_page_ids()[0]);
+    let fragments = page.fragments_with(&mut document, &TextExtractionOptions::new())?;
+    let decoded = fragments.iter().map(|it| (it.get_text(), it.get_confidence())).collect::<Vec<_>>();
+    assert_eq!(decoded, [
+        ("Hello été", Confidence::Low),
+        ("\u{201C}Hi\u{201D}", Confidence::Medium),
+        ("Hi", Confidence::High),
+        ("\u{201C}Hi\u{201D}", Confidence::Low),
+        ("5\u{20AC}", Confidence::Low),
+    ]);
+
+    let preflight = document.font_preflight()?;
+    let decodings = preflight.get_fonts().iter().map(|it| it.get_decoding()).collect::<Vec<_>>();
+    assert_eq!(decodings, [
+        TextDecoding::Latin1,
+        TextDecoding::Builtin,
+        TextDecoding::Encoding,
+        TextDecoding::Cp1252,
+        TextDecoding::Builtin,
+    ]);
+    assert_eq!(TextDecoding::Latin1.confidence(), Confidence::Low);
+    Ok(())
+}
+
+#[test]
+fn test_operator_histogram() -> Result<()> {
+    // A text and vector page painting a form twice, then a scanned page
+    let path = common::write_pdf("operator-histogram", &common::catalog_with_pages(
+        &["/Resources << /XObject << /Fm1 5 0 R >> >> /Contents 6 0 R", "/Resources << /XObject << /Im0 7 0 R >> >> /Contents 8 0 R"],
+        vec![
+            common::stream("/Type /XObject /Subtype /Form /BBox [0 0 100 100]", "BT (C) Tj ET"),
+            common::stream("", "q 1 0 0 1 0 0 cm BT /F1 12 Tf (A) Tj (B) Tj ET 0 0 10 10 re f 5 5 10 10 re f /Fm1 Do /Fm1 Do Q"),
+            common::stream("/Type /XObject /Subtype /Image /Width 1 /Height 1 /BitsPerComponent 8 /ColorSpace /DeviceGray", "x"),
+            common::stream("", "q 612 0 0 792 0 0 cm /Im0 Do Q"),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    let histogram = document.page_operator_histogram(0)?;
+    let count = |histogram: &HashMap<String, u32>, operator: &str| histogram.get(operator).copied().unwrap_or(0);
+    for (operator, expected) in [("Tj", 4), ("BT", 3), ("ET", 3), ("re", 2), ("f", 2), ("Do", 2), ("Tf", 1), ("cm", 1)] {
+        assert_eq!(count(&histogram, operator), expected, "{}", operator);
+    }
+    assert_eq!(histogram.values().sum::<u32>(), 20);
+
+    let separate = HistogramOptions::new().with_merge_forms(false);
+    let histogram = document.page_operator_histogram_with(0, &separate)?;
+    assert_eq!((count(&histogram, "Tj"), count(&histogram, "Form/Tj"), count(&histogram, "Form/BT")), (2, 2, 2));
+
+    // The scanned page paints a single image and shows no text
+    let scanned = document.page_operator_histogram(1)?;
+    assert_eq!((count(&scanned, "Do"), count(&scanned, "cm")), (1, 1));
+    assert_eq!(scanned.values().sum::<u32>(), 4);
+    assert!(!scanned.contains_key("Tj"));
+
+    let total = document.operator_histogram(&HistogramOptions::new())?;
+    assert_eq!((count(&total, "Do"), count(&total, "q"), count(&total, "Tj")), (3, 2, 4));
+    assert!(matches!(document.page_operator_histogram(2), Err(PDFError::PageNotFound(_))));
+    Ok(())
+}
+
+#[test]
+fn test_image_inventory() -> Result<()> {
+    let image = |color_space: &str, data: &str| {
+        let entries = format!("/Type /XObject /Subtype /Image /Width 2 /Height 1 /ColorSpace {} /BitsPerComponent 8", color_space);
+        common::stream(&entries, data)
+    };
+    let xobjects = "/Resources << /XObject << /Im1 6 0 R /Im2 7 0 R /Im3 8 0 R >> >>";
+    let path = common::write_pdf("image-inventory", &common::catalog_with_pages(
+        &[&format!("{} /Contents 5 0 R", xobjects), &format!("{} /Contents 9 0 R", xobjects)],
+        vec![
+            common::stream("", "/Im1 Do"),
+            // Painted on both pages
+            image("/DeviceGray", "AB"),
+            // Two separate objects with the same data
+            image("[/Indexed /DeviceRGB 1 <000000FFFFFF>]", "CD"),
+            image("/DeviceGray", "CD"),
+            common::stream("", "/Im1 Do /Im2 Do /Im3 Do"),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    let mut inventory = document.image_inventory()?;
+    let images = inventory.get_images();
+    assert_eq!(images.iter().map(|it| it.get_obj_ref()).collect::<Vec<_>>(), [(6, 0), (7, 0), (8, 0)]);
+    assert_eq!(images[0].get_pages(), &[0, 1]);
+    assert_eq!(images[1].get_pages(), &[1]);
+    assert_eq!((images[0].get_width(), images[0].get_height()), (Some(2), Some(1)));
+    assert_eq!(images[0].get_color_space(), Some("DeviceGray"));
+    assert_eq!(images[1].get_color_space(), Some("Indexed"));
+    assert_eq!(images[0].get_declared_length(), Some(2));
+    assert!(images[0].get_filters().is_empty());
+    // Hashes are only computed on request
+    assert!(images.iter().all(|it| it.get_hash().is_none()));
+    assert_eq!(inventory.duplicates(&mut document)?, vec![vec![(7, 0), (8, 0)]]);
+    let images = inventory.get_images();
+    assert_eq!(images[1].get_hash(), images[2].get_hash());
+    assert_ne!(images[0].get_hash(), images[1].get_hash());
+    Ok(())
+}
+
+#[test]
+fn test_stream_map() -> Result<()> {
+    let path = PathBuf::from("document/pdfreference1.0.pdf");
+    let file_size = std::fs::metadata(&path)?.len();
+    let mut document = PDFDocument::open(path)?;
+    let map = document.stream_map();
+    let ranges = map.get_ranges();
+    assert!(ranges.len() >= 2);
+    assert!(ranges[0].1.end <= ranges[1].1.start);
+    assert!(ranges.iter().all(|it| it.1.start < it.1.end && it.1.end <= file_size));
+    assert!(map.get_overlaps().is_empty());
+    assert_eq!(map.gaps().len(), ranges.len() - 1);
+
+    // Eagerly read streams report the same range
+    let (obj_ref, range) = ranges[0].clone();
+    let stream = document.read_object_with_ref(obj_ref)?.and_then(|it| it.into_indirect()).and_then(|it| it.2.into_stream());
+    assert_eq!(stream.unwrap().raw_range(), Some(range));
+
+    let bytes = common::build_pdf(&common::catalog_with_pages(&["/Contents 4 0 R"], vec![
+        common::stream("", "BT ET"),
+    ]));
+    let start = bytes.windows(7).position(|it| it == b"stream\n").unwrap() as u64 + 7;
+    let mut document = PDFDocument::open(common::write_bytes("stream-map", &bytes))?;
+    assert_eq!(document.stream_map().get_ranges(), &[((4, 0), start..start + 5)]);
+    assert_eq!(&bytes[start as usize..start as usize + 5], b"BT ET");
+    assert!(Stream::new(DictBuilder::new().build(), Vec::new()).raw_range().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_page_tree_without_type() -> Result<()> {
+    // An intermediate node without /Type and pages identified by /Contents, /MediaBox or nothing
+    let path = common::write_pdf("page-tree-untyped", &[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 7 0 R] /Count 4 >>".to_string(),
+        "<< /Parent 2 0 R /Kids [4 0 R 5 0 R 6 0 R] /Count 3 >>".to_string(),
+        "<< /Parent 3 0 R /Contents 8 0 R >>".to_string(),
+        "<< /Parent 3 0 R /MediaBox [0 0 612 792] >>".to_string(),
+        "<< /Parent 3 0 R >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+        common::stream("", "BT ET"),
+    ]);
+    let document = PDFDocument::open(path)?;
+    assert_eq!(document.get_page_num(), 4);
+    let pages = document.get_page_ids().into_iter().map(|it| document.get_page_node(it).unwrap().get_page_obj_ref()).collect::<Vec<_>>();
+    assert_eq!(pages, [(4, 0), (5, 0), (6, 0), (7, 0)]);
+    assert!(document.get_warnings().is_empty());
+
+    // /Type contradicting the structure, the structure wins
+    let path = common::write_pdf("page-tree-conflict", &[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 5 0 R] /Count 2 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /Kids [4 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 3 0 R >>".to_string(),
+        "<< /Type /Pages /Parent 2 0 R /Contents 6 0 R >>".to_string(),
+        common::stream("", "BT ET"),
+    ]);
+    let document = PDFDocument::open(path)?;
+    assert_eq!(document.get_page_num(), 2);
+    let warnings = document.get_warnings().iter().map(|it| it.get_message()).collect::<Vec<_>>();
+    assert_eq!(warnings, [
+        "Page tree node 3 0 has /Type /Page but /Kids, treated as /Pages",
+        "Page tree node 5 0 has /Type /Pages but /Contents and no /Kids, treated as /Page",
+    ]);
+    Ok(())
+}
+
+#[test]
+fn test_page_tree_count() -> Result<()> {
+    let open = |name: &str, count: &str, options: ParseOptions| {
+        let path = common::write_pdf(name, &[
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!("<< /Type /Pages /Kids [3 0 R 6 0 R] {} >>", count),
+            format!("<< /Type /Pages /Parent 2 0 R /Kids [4 0 R 5 0 R] {} >>", count),
+            "<< /Type /Page /Parent 3 0 R >>".to_string(),
+            "<< /Type /Page /Parent 3 0 R >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R >>".to_string(),
+        ]);
+        PDFDocument::open_with_options(path, options)
+    };
+    let document = open("page-count-missing", "", ParseOptions::lenient())?;
+    assert_eq!(document.get_page_num(), 3);
+    assert_eq!(document.get_page_ids().len(), 3);
+    assert_eq!(document.get_warnings()[0].get_message(), "Page tree node 3 0 has no valid /Count, computed 2");
+    assert!(open("page-count-missing-strict", "", ParseOptions::default()).is_err());
+
+    let document = open("page-count-real", "/Count 2.0", ParseOptions::lenient())?;
+    assert_eq!(document.get_page_num(), 3);
+    assert_eq!(document.get_page_ids().len(), 3);
+
+    // Off by two, the computed count is stored
+    let document = open("page-count-wrong", "/Count 5", ParseOptions::lenient())?;
+    assert_eq!(document.get_page_num(), 3);
+    assert_eq!(document.get_page_ids().len(), 3);
+    assert_eq!(document.get_page_tree().get_root_node().unwrap().get_count(), 3);
+    let warnings = document.get_warnings().iter().map(|it| it.get_message()).collect::<Vec<_>>();
+    assert_eq!(warnings, [
+        "Page tree node 3 0 declares /Count 5 but has 2 pages",
+        "Page tree node 2 0 declares /Count 5 but has 3 pages",
+    ]);
+    Ok(())
+}
+
+#[test]
+fn test_page_tree_cycle() -> Result<()> {
+    // Node 3 lists its parent 2 as a kid again
+    let path = common::write_pdf("page-tree-cycle", &[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>".to_string(),
+        "<< /Type /Pages /Parent 2 0 R /Kids [2 0 R 5 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+        "<< /Type /Page /Parent 3 0 R >>".to_string(),
+    ]);
+    match PDFDocument::open(path.clone()) {
+        Err(PDFError::CircularReference { object }) => assert_eq!(object, ObjectId::new(2, 0)),
+        _ => panic!("expected a circular reference"),
+    }
+    let document = PDFDocument::open_with_options(path, ParseOptions::lenient())?;
+    assert_eq!(document.get_page_num(), 2);
+    let pages = document.get_page_ids().iter().map(|it| it.to_tuple()).collect::<Vec<_>>();
+    assert_eq!(pages, [(5, 0), (4, 0)]);
+    let warnings = document.get_warnings().iter().map(|it| it.get_message()).collect::<Vec<_>>();
+    assert_eq!(warnings, ["Page tree node 2 0 is listed more than once, ignored"]);
+    Ok(())
+}
+
+#[test]
+fn test_from_bytes() -> Result<()> {
+    fn assert_send<T: Send>(_: &T) {}
+    let document = PDFDocument::from_bytes(std::fs::read("document/pdfreference1.0.pdf")?)?;
+    assert_send(&document);
+    assert_eq!(document.get_page_num(), 230);
+    // The document can move to another thread
+    let pages = std::thread::spawn(move || document.get_page_num()).join().unwrap();
+    assert_eq!(pages, 230);
+    Ok(())
+}
+
+#[test]
+fn test_sequence_parity() -> Result<()> {
+    // Opens a file through a file and a memory sequence, summarizing what was read
+    let summaries = |path: PathBuf| -> Vec<String> {
+        let bytes = std::fs::read(&path).unwrap();
+        let file = PDFDocument::new_with_options(FileSequence::new(std::fs::File::open(&path).unwrap()), ParseOptions::lenient());
+        let memory = PDFDocument::new_with_options(MemorySequence::from(bytes), ParseOptions::lenient());
+        [file, memory]
+            .into_iter()
+            .map(|document| match document {
+                Ok(mut document) => {
+                    let text = match document.get_page_ids().first() {
+                        Some(page_id) => Page::new(*page_id).text(&mut document).unwrap_or_default(),
+                        None => String::new(),
+                    };
+                    format!(
+                        "{} pages, {} xref entries, {} warnings, {:?}",
+                        document.get_page_num(),
+                        document.get_xref_slice().len(),
+                        document.get_warnings().len(),
+                        text
+                    )
+                }
+                Err(e) => format!("error: {}", e),
+            })
+            .collect()
+    };
+    let crlf = common::build_pdf(&common::catalog_with_pages(&[""], vec![]));
+    let crlf = String::from_utf8(crlf).unwrap().replace('\n', "\r\n");
+    for path in [
+        PathBuf::from("document/pdfreference1.0.pdf"),
+        PathBuf::from("document/PDF32000_2008.pdf"),
+        common::write_bytes("parity-crlf", crlf.as_bytes()),
+        common::write_bytes("parity-garbage", b"%PDF-1.4\nnot a document"),
+    ] {
+        let summaries = summaries(path.clone());
+        assert_eq!(summaries[0], summaries[1], "{}", path.display());
+    }
+
+    let mut sequence = MemorySequence::new(b"%PDF-1.4\r\n\r\n1 0 obj".to_vec());
+    assert_eq!(sequence.read_line_str()?, "%PDF-1.4");
+    assert!(matches!(sequence.read_line(), Err(PDFError::EOFError)));
+    assert_eq!(sequence.seek(19)?, 19);
+    assert!(matches!(sequence.seek(20), Err(PDFError::SeekExceedError)));
+    assert_eq!(sequence.into_inner().len(), 19);
+    Ok(())
+}
+
+#[test]
+fn test_resource_limits() -> Result<()> {
+    let path = PathBuf::from("document/pdfreference1.0.pdf");
+    let exceeded = |result: Result<PDFDocument>| match result {
+        Err(PDFError::ResourceLimitExceeded { which, limit }) => Some((which, limit)),
+        _ => None,
+    };
+    let options = ParseOptions::default().with_max_objects(10);
+    assert_eq!(exceeded(PDFDocument::open_with_options(path.clone(), options)), Some((Resource::Objects, 10)));
+    // Lenient mode doesn't replace the pages beyond the limit by placeholders
+    let options = ParseOptions::lenient().with_max_objects(10);
+    assert_eq!(exceeded(PDFDocument::open_with_options(path.clone(), options)), Some((Resource::Objects, 10)));
+    let options = ParseOptions::default().with_time_budget(Duration::ZERO);
+    assert_eq!(exceeded(PDFDocument::open_with_options(path.clone(), options)), Some((Resource::Time, 0)));
+
+    let options = ParseOptions::default()
+        .with_max_objects(1_000_000)
+        .with_max_total_decoded_bytes(1 << 30)
+        .with_time_budget(Duration::from_secs(3600));
+    let mut document = PDFDocument::open_with_options(path.clone(), options)?;
+    assert_eq!(document.get_page_num(), 230);
+    assert!(!Page::new(document.get_page_ids()[1]).text(&mut document)?.is_empty());
+
+    // The decoded bytes of all pages count together
+    let options = ParseOptions::default().with_max_total_decoded_bytes(4096);
+    let mut document = PDFDocument::open_with_options(path, options)?;
+    let report = document.extract_text_report(&TextExtractionOptions::default());
+    let last = report.get_pages().last().unwrap();
+    assert!(last.get_index() < 229);
+    match last.get_status() {
+        PageStatus::Failed(PDFError::ResourceLimitExceeded { which, limit }) => assert_eq!((*which, *limit), (Resource::DecodedBytes, 4096)),
+        _ => panic!("the limit must stop the extraction"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_content_parse_error_location() -> Result<()> {
+    let content = "BT /F1 12 Tf 72 700 Td (Hello) Tj 1.2.3 0 Td (World) Tj ET";
+    let open = |name: &str, options: ParseOptions| {
+        let path = common::write_pdf(name, &common::catalog_with_pages(&["", "/Contents 5 0 R"], vec![
+            common::stream("", content),
+        ]));
+        PDFDocument::open_with_options(path, options)
+    };
+    let offset = content.find("1.2.3").unwrap() as u64;
+
+    let mut document = open("content-error", ParseOptions::strict())?;
+    let page = Page::new(document.get_page_ids()[1]);
+    match page.text(&mut document) {
+        Err(PDFError::ContentParseError { page, operation, offset: at, message }) => {
+            assert_eq!((page, operation, at), (Some(1), 4, offset));
+            assert_eq!(message, "Multiple dot was found in real number.");
+        }
+        _ => panic!("expected a content parse error"),
+    }
+    let error = page.content_operations(&mut document).err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        format!("Page 2, operation #5 near byte {}: Multiple dot was found in real number.", offset)
+    );
+
+    // Lenient mode skips the operation up to its operator and keeps the rest
+    let mut document = open("content-error-lenient", ParseOptions::lenient())?;
+    let page = Page::new(document.get_page_ids()[1]);
+    let operators = page.content_operations(&mut document)?.iter().map(|it| it.get_operator().to_string()).collect::<Vec<_>>();
+    assert_eq!(operators, ["BT", "Tf", "Td", "Tj", "Tj", "ET"]);
+    let text = page.text(&mut document)?;
+    assert!(text.contains("Hello") && text.contains("World"));
+    assert_eq!(
+        document.get_warnings()[0].get_message(),
+        format!("Page 2, operation #5 near byte {}: Multiple dot was found in real number. (skipped)", offset)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_unbalanced_content_operators() -> Result<()> {
+    let content = "q Q Q BT /F1 12 Tf 72 700 Td (Hello) Tj BT 72 680 Td (World) Tj ET BT 72 660 Td (Again) Tj";
+    let path = common::write_pdf("content-unbalanced", &common::catalog_with_pages(&["/Contents 4 0 R"], vec![
+        common::stream("", content),
+    ]));
+    let mut document = PDFDocument::open(path)?;
+    let page = Page::new(document.get_page_ids()[0]);
+    let text = page.text(&mut document)?;
+    assert!(text.contains("Hello") && text.contains("World") && text.contains("Again"));
+
+    let warnings = document.get_warnings();
+    let codes = warnings.iter().map(|it| it.get_code()).collect::<Vec<_>>();
+    assert_eq!(codes, [
+        WarningCode::GraphicsStackUnderflow,
+        WarningCode::NestedTextObject,
+        WarningCode::UnterminatedTextObject,
+    ]);
+    assert_eq!(
+        warnings[0].get_message(),
+        format!("Page 1, operation #3 near byte {}: Q without a matching q, ignored", content.find("Q Q").unwrap() + 2)
+    );
+    let last = content.rfind("BT").unwrap();
+    assert_eq!(
+        warnings[2].get_message(),
+        format!("Page 1, operation #12 near byte {}: text object isn't closed by ET, closed at the end of the content", last)
+    );
+
+    // The unbalanced Q keeps the scaling instead of resetting to the identity
+    let path = common::write_pdf("content-unbalanced-ctm", &common::catalog_with_pages(&["/Contents 4 0 R"], vec![
+        common::stream("", "2 0 0 2 0 0 cm Q BT /F1 6 Tf 36 350 Td (Scaled) Tj ET"),
+    ]));
+    let mut document = PDFDocument::open(path)?;
+    let page = Page::new(document.get_page_ids()[0]);
+    let fragments = page.fragments_with(&mut document, &TextExtractionOptions::new())?;
+    assert_eq!(fragments.len(), 1);
+    assert_eq!((fragments[0].get_x(), fragments[0].get_y(), fragments[0].get_font_size()), (72.0, 700.0, 12.0));
+    Ok(())
+}
+
+#[test]
+fn test_form_xobject_content() -> Result<()> {
+    let font = "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string();
+    let path = common::write_pdf("form-xobject", &common::catalog_with_pages(
+        &["/Resources << /XObject << /Fm1 4 0 R >> >> /Contents 5 0 R"],
+        vec![
+            common::stream(
+                "/Type /XObject /Subtype /Form /BBox [0 0 200 200] /Matrix [1 0 0 1 100 50] \
+                 /Resources << /Font << /F1 6 0 R >> /XObject << /Im1 7 0 R >> >>",
+                "BT /F1 10 Tf 10 20 Td (Stamped) Tj ET /Im1 Do",
+            ),
+            common::stream("", "q 1 0 0 1 5 5 cm /Fm1 Do Q"),
+            font,
+            common::stream("/Type /XObject /Subtype /Image /Width 1 /Height 1 /BitsPerComponent 8 /ColorSpace /DeviceGray", "x"),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    let page = Page::new(document.get_page_ids()[0]);
+    assert_eq!(page.text(&mut document)?, "Stamped");
+    let fragments = page.fragments_with(&mut document, &TextExtractionOptions::new())?;
+    assert_eq!((fragments[0].get_x(), fragments[0].get_y()), (115.0, 75.0));
+    assert_eq!(fragments[0].get_font_size(), 10.0);
+    // The image painted by the form is used by the page
+    assert_eq!(document.pages_referencing((7, 0))?, [0]);
+    assert_eq!(document.pages_referencing((6, 0))?, [0]);
+
+    // A form painting itself errors instead of looping, lenient mode skips it
+    let open = |name: &str, options: ParseOptions| {
+        let path = common::write_pdf(name, &common::catalog_with_pages(
+            &["/Resources << /XObject << /Fm1 4 0 R >> >> /Contents 5 0 R"],
+            vec![
+                common::stream(
+                    "/Type /XObject /Subtype /Form /BBox [0 0 200 200] /Resources << /XObject << /Fm1 4 0 R >> >>",
+                    "BT 10 20 Td (Loop) Tj ET /Fm1 Do",
+                ),
+                common::stream("", "/Fm1 Do"),
+            ],
+        ));
+        PDFDocument::open_with_options(path, options)
+    };
+    let mut document = open("form-xobject-cycle", ParseOptions::strict())?;
+    let page = Page::new(document.get_page_ids()[0]);
+    assert!(matches!(page.text(&mut document), Err(PDFError::FormXObjectCycle((4, 0)))));
+
+    let mut document = open("form-xobject-cycle-lenient", ParseOptions::lenient())?;
+    let page = Page::new(document.get_page_ids()[0]);
+    assert_eq!(page.text(&mut document)?, "Loop");
+    assert_eq!(document.get_warnings()[0].get_code(), WarningCode::FormRecursion);
+
+    let options = ParseOptions::strict().with_max_form_depth(0);
+    let mut document = open("form-xobject-depth", options)?;
+    let page = Page::new(document.get_page_ids()[0]);
+    assert!(matches!(page.text(&mut document), Err(PDFError::FormXObjectTooDeep(0))));
+    Ok(())
+}
+
+#[test]
+fn test_annotation_appearance_text() -> Result<()> {
+    // A flattened form: the widgets have no /V, the values only live in their appearances
+    let resources = "/Resources << /Font << /Helv 9 0 R >> >>";
+    let path = common::write_pdf("appearance-text", &common::catalog_with_pages(
+        &["/Annots [4 0 R 5 0 R << /Subtype /Text >>]"],
+        vec![
+            "<< /Subtype /Widget /FT /Tx /T (name) /Rect [100 700 250 720] /AP << /N 6 0 R >> >>".to_string(),
+            "<< /Subtype /Widget /FT /Btn /T (agree) /Rect [100 650 112 662] /AS /Yes /AP << /N << /Yes 7 0 R /Off 8 0 R >> >> >>"
+                .to_string(),
+            // The label above the box is drawn outside the /BBox and is dropped
+            common::stream(
+                &format!("/Type /XObject /Subtype /Form /BBox [0 0 150 20] {}", resources),
+                "/Tx BMC q BT /Helv 10 Tf 2 6 Td (Jane Doe) Tj ET Q EMC BT /Helv 8 Tf 2 30 Td (Name:) Tj ET",
+            ),
+            common::stream(&format!("/Type /XObject /Subtype /Form /BBox [0 0 12 12] {}", resources), "BT /Helv 10 Tf 2 2 Td (X) Tj ET"),
+            common::stream("/Type /XObject /Subtype /Form /BBox [0 0 12 12]", ""),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    assert_eq!(document.annotation_appearance_text(0, 0)?.as_deref(), Some("Jane Doe"));
+    assert_eq!(document.annotation_appearance_text(0, 1)?.as_deref(), Some("X"));
+    assert_eq!(document.annotation_appearance_text(0, 2)?, None);
+    assert!(matches!(document.annotation_appearance_text(0, 3), Err(PDFError::ObjectAttrMiss(_))));
+    Ok(())
+}
+
+#[test]
+fn test_outline_round_trip() -> Result<()> {
+    let mut builder = DocumentBuilder::new();
+    let catalog = builder.reserve();
+    let pages = builder.reserve();
+    let page_refs = (0..3)
+        .map(|_| builder.add(DictBuilder::new().name("Type", "Page").reference("Parent", pages).build()))
+        .collect::<Vec<_>>();
+    let kids = page_refs.iter().fold(ArrayBuilder::new(), |kids, it| kids.reference(*it)).build();
+    builder.set(pages, DictBuilder::new().name("Type", "Pages").entry("Kids", kids).entry("Count", 3).build());
+    builder.set(catalog, DictBuilder::new().name("Type", "Catalog").reference("Pages", pages).build());
+    builder.set_root(catalog);
+    let outline = OutlineBuilder::new()
+        .item(OutlineItem::new("Introduction", 0))
+        .item(
+            OutlineItem::new("Chapitre 1 – Données", 1)
+                .with_open(true)
+                .child(OutlineItem::new("Section 1.1", 1))
+                .child(OutlineItem::new("Section 1.2", 2).child(OutlineItem::new("Detail", 2))),
+        )
+        .item(OutlineItem::new("Appendix", 2).child(OutlineItem::new("Notes", 2)));
+    builder.set_outline(&outline, &page_refs)?;
+    let mut buf = Vec::new();
+    builder.write(&mut buf)?;
+
+    let document = PDFDocument::open(common::write_bytes("outline-round-trip", &buf))?;
+    let page_of = |node: &pdf_rs::catalog::OutlineNode| {
+        let page_ref = node.get_dest().and_then(|it| it.as_array()).and_then(|it| it.first()).and_then(|it| it.as_object_ref());
+        document.get_page_ids().iter().position(|it| Some(document.get_page_node(*it).unwrap().get_page_obj_ref()) == page_ref)
+    };
+    let tree = document.get_outline().expect("outline");
+    let children = |first: Option<ObjectId>| {
+        let mut nodes = Vec::new();
+        let mut next = first;
+        while let Some(node_id) = next {
+            let node = tree.get_node(node_id).unwrap();
+            nodes.push((node_id, node));
+            next = node.get_next_id();
+        }
+        nodes
+    };
+    let root = tree.get_root_node().unwrap();
+    assert_eq!(root.get_count(), 5);
+    let top = children(root.get_first_id());
+    let titles = top.iter().map(|it| it.1.get_title().unwrap()).collect::<Vec<_>>();
+    assert_eq!(titles, ["Introduction", "Chapitre 1 – Données", "Appendix"]);
+    assert_eq!(top.iter().map(|it| page_of(it.1)).collect::<Vec<_>>(), [Some(0), Some(1), Some(2)]);
+    assert_eq!(top.iter().map(|it| it.1.get_count()).collect::<Vec<_>>(), [0, 2, -1]);
+
+    let chapter = children(top[1].1.get_first_id());
+    let titles = chapter.iter().map(|it| it.1.get_title().unwrap()).collect::<Vec<_>>();
+    assert_eq!(titles, ["Section 1.1", "Section 1.2"]);
+    assert_eq!(chapter[1].1.get_count(), -1);
+    assert_eq!(page_of(chapter[1].1), Some(2));
+    let detail = children(chapter[1].1.get_first_id());
+    assert_eq!(detail[0].1.get_title(), Some("Detail"));
+    assert_eq!(top[1].1.get_last_id(), Some(chapter[1].0));
+
+    // Destinations must exist
+    let missing = OutlineBuilder::new().item(OutlineItem::new("Missing", 3));
+    assert!(matches!(DocumentBuilder::new().set_outline(&missing, &page_refs), Err(PDFError::PageNotFound(_))));
+    Ok(())
+}
+
+#[test]
+fn test_save_incremental_info() -> Result<()> {
+    let stamp = format!("pdf-rs {}", env!("CARGO_PKG_VERSION"));
+    let created = Date::from_millisecond(1_700_000_000_000, 0);
+    assert_eq!(created.to_string(), "D:20231114221320Z");
+    assert_eq!(Date::from_millisecond(1_700_000_000_000, 2).to_string(), "D:20231115001320+02'00'");
+
+    let objects = common::catalog_with_pages(&[""], vec![
+        "<< /Title (Old) /Author (Someone) /Custom (kept) >>".to_string(),
+    ]);
+    let original = common::build_pdf_with_trailer(&objects, "/Size 5 /Root 1 0 R /Info 4 0 R");
+    let mut document = PDFDocument::open(common::write_bytes("info-original", &original))?;
+    let describe = document.get_describe().unwrap().clone();
+    document.set_describe(describe.with_title("Überblick – 概要").with_creation_date(created));
+    let mut buf = Vec::new();
+    document.save_incremental(&mut buf)?;
+    assert!(buf.len() > original.len() && buf.starts_with(&original));
+
+    let mut document = PDFDocument::open(common::write_bytes("info-updated", &buf))?;
+    assert_eq!(document.xref_sections(), 2);
+    let describe = document.get_describe().unwrap();
+    assert_eq!(describe.get_title(), Some("Überblick – 概要"));
+    assert_eq!(describe.get_author(), Some("Someone"));
+    assert_eq!(describe.get_producer(), Some(stamp.as_str()));
+    assert_eq!(describe.get_creation_date(), Some(&created));
+    let info = document.read_object_with_ref((4, 0))?.unwrap();
+    assert!(document.get_path(&info, "Custom")?.is_some());
+
+    // Bytes before the header are kept, the appended offsets are relative to the header
+    let junk = [b"junk before the header\n".as_slice(), &original].concat();
+    for (name, mut document) in [
+        ("info-junk", PDFDocument::open(common::write_bytes("info-junk", &junk))?),
+        ("info-junk-memory", PDFDocument::from_bytes(junk.clone())?),
+    ] {
+        let mut buf = Vec::new();
+        document.save_incremental(&mut buf)?;
+        assert_eq!(buf, junk, "{}", name);
+        document.set_describe(PDFDescribe::new().with_title("Junk"));
+        let mut buf = Vec::new();
+        document.save_incremental(&mut buf)?;
+        assert!(buf.starts_with(&junk), "{}", name);
+        let document = PDFDocument::from_bytes(buf)?;
+        assert_eq!(document.get_header_offset(), 23);
+        assert_eq!(document.xref_sections(), 2);
+        assert_eq!(document.get_describe().unwrap().get_title(), Some("Junk"));
+        assert_eq!(document.get_describe().unwrap().get_author(), Some("Someone"));
+    }
+
+    // Without /Info a new dictionary is appended, the stamp can be turned off
+    let original = common::build_pdf_with_trailer(&objects[..3], "/Size 4 /Root 1 0 R");
+    let mut document = PDFDocument::open(common::write_bytes("info-missing", &original))?;
+    let mut buf = Vec::new();
+    document.save_incremental(&mut buf)?;
+    assert_eq!(buf, original);
+    document.set_describe(PDFDescribe::new().with_subject("Tests").with_producer("Tool").with_producer_stamp(false));
+    let mut buf = Vec::new();
+    document.save_incremental(&mut buf)?;
+    let document = PDFDocument::open(common::write_bytes("info-added", &buf))?;
+    let describe = document.get_describe().unwrap();
+    assert_eq!((describe.get_subject(), describe.get_producer()), (Some("Tests"), Some("Tool")));
+    assert_eq!(document.declared_size(), Some(5));
+
+    // An implausible /Size is ignored instead of numbering the new objects after it
+    let original = common::build_pdf_with_trailer(&objects[..3], "/Size 99999999999 /Root 1 0 R");
+    let mut document = PDFDocument::from_bytes(original)?;
+    document.set_describe(PDFDescribe::new().with_subject("Tests"));
+    let mut buf = Vec::new();
+    document.save_incremental(&mut buf)?;
+    let message = document.get_warnings().last().unwrap().get_message();
+    assert_eq!(message, "trailer /Size 99999999999 is implausible, ignored");
+    let document = PDFDocument::from_bytes(buf)?;
+    assert_eq!(document.get_describe().unwrap().get_subject(), Some("Tests"));
+    assert_eq!((document.declared_size(), document.actual_max_object()), (Some(5), Some(4)));
+
+    // The full writer
+    let mut builder = DocumentBuilder::new();
+    let catalog = builder.reserve();
+    let pages = builder.add(DictBuilder::new().name("Type", "Pages").entry("Kids", ArrayBuilder::new().build()).entry("Count", 0).build());
+    builder.set(catalog, DictBuilder::new().name("Type", "Catalog").reference("Pages", pages).build());
+    builder.set_root(catalog);
+    builder.set_info(&PDFDescribe::new().with_keywords("ünïcödé"));
+    let mut buf = Vec::new();
+    builder.write(&mut buf)?;
+    let document = PDFDocument::open(common::write_bytes("info-writer", &buf))?;
+    assert_eq!(document.get_describe().unwrap().get_keywords(), Some("ünïcödé"));
+    assert_eq!(document.get_describe().unwrap().get_producer(), Some(stamp.as_str()));
+    Ok(())
+}
+
+#[test]
+fn test_unused_resources() -> Result<()> {
+    let font = "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string();
+    let image = common::stream("/Type /XObject /Subtype /Image /Width 1 /Height 1 /BitsPerComponent 8 /ColorSpace /DeviceGray", "x");
+    let path = common::write_pdf("unused-resources", &[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 9 0 R] /Count 2 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /Resources << /Font << /F1 4 0 R /F2 5 0 R >> \
+         /XObject << /Fm1 6 0 R /Im1 7 0 R /Im2 8 0 R >> >> /Contents 10 0 R >>".to_string(),
+        font.clone(),
+        font,
+        // The form has no resources of its own, so it paints the image of the page
+        common::stream("/Type /XObject /Subtype /Form /BBox [0 0 10 10]", "/Im1 Do"),
+        image.clone(),
+        image,
+        // Inline fonts are declared like referenced ones
+        "<< /Type /Page /Parent 2 0 R /Resources << /Font << /F1 4 0 R \
+         /F3 << /Type /Font /Subtype /Type1 /BaseFont /Courier >> \
+         /F4 << /Type /Font /Subtype /Type1 /BaseFont /Symbol >> >> >> /Contents 11 0 R >>".to_string(),
+        common::stream("", "BT /F1 12 Tf (Used) Tj ET /Fm1 Do"),
+        common::stream("", "BT /F1 12 Tf (Used) Tj /F3 12 Tf (Inline) Tj ET"),
+    ]);
+    let mut document = PDFDocument::open(path)?;
+    let unused = document.unused_resources(0)?;
+    assert_eq!(unused.get_fonts(), ["F2"]);
+    assert_eq!(unused.get_xobjects(), ["Im2"]);
+    let unused = document.unused_resources(1)?;
+    assert_eq!(unused.get_fonts(), ["F4"]);
+    assert!(unused.get_xobjects().is_empty());
+    assert!(matches!(document.unused_resources(2), Err(PDFError::PageNotFound(_))));
+
+    let report = document.resource_usage()?;
+    assert_eq!(report.get_pages().len(), 2);
+    assert_eq!((report.unused_fonts(), report.unused_xobjects()), (2, 1));
+    Ok(())
+}
+
+#[test]
+fn test_outline_count_reconciliation() -> Result<()> {
+    // An open chapter holding a closed section with two children
+    let mut builder = DocumentBuilder::new();
+    let catalog = builder.reserve();
+    let pages = builder.reserve();
+    let page = builder.add(DictBuilder::new().name("Type", "Page").reference("Parent", pages).build());
+    let kids = ArrayBuilder::new().reference(page).build();
+    builder.set(pages, DictBuilder::new().name("Type", "Pages").entry("Kids", kids).entry("Count", 1).build());
+    builder.set(catalog, DictBuilder::new().name("Type", "Catalog").reference("Pages", pages).build());
+    builder.set_root(catalog);
+    let section = OutlineItem::new("Section", 0).child(OutlineItem::new("A", 0)).child(OutlineItem::new("B", 0));
+    let outline = OutlineBuilder::new().item(OutlineItem::new("Chapter", 0).with_open(true).child(section));
+    builder.set_outline(&outline, &[page])?;
+    let mut buf = Vec::new();
+    builder.write(&mut buf)?;
+    let options = ParseOptions::strict().with_check_outline_counts(true);
+    let mut document = PDFDocument::open_with_options(common::write_bytes("outline-counts", &buf), options)?;
+    assert!(document.get_warnings().is_empty());
+    let tree = document.get_outline().expect("outline");
+    let root = tree.get_root_node().unwrap();
+    let chapter = tree.get_node(root.get_first_id().unwrap()).unwrap();
+    let section = tree.get_node(chapter.get_first_id().unwrap()).unwrap();
+    assert_eq!((root.get_count(), root.get_computed_count()), (2, 2));
+    assert_eq!((chapter.get_count(), chapter.get_computed_count()), (1, 1));
+    assert_eq!((section.get_count(), section.get_computed_count()), (-2, -2));
+    assert!(tree.count_mismatches().is_empty());
+    assert_eq!(document.verify()?.findings_of(Category::OutlineCount).count(), 0);
+
+    // Every declared count is zero, so the item with children is closed
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R /Outlines 4 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+        "<< /Type /Outlines /First 5 0 R /Last 6 0 R /Count 0 >>".to_string(),
+        "<< /Title (Parent) /Parent 4 0 R /Next 6 0 R /First 7 0 R /Last 8 0 R /Count 0 >>".to_string(),
+        "<< /Title (Sibling) /Parent 4 0 R /Prev 5 0 R /Count 0 >>".to_string(),
+        "<< /Title (Child 1) /Parent 5 0 R /Next 8 0 R /Count 0 >>".to_string(),
+        "<< /Title (Child 2) /Parent 5 0 R /Prev 7 0 R /Count 0 >>".to_string(),
+    ];
+    let path = common::write_pdf("outline-zero-counts", &objects);
+    let mut document = PDFDocument::open(path.clone())?;
+    assert!(document.get_warnings().is_empty());
+    let tree = document.get_outline().expect("outline");
+    let mismatches = tree.count_mismatches().iter().map(|it| it.1.get_computed_count()).collect::<Vec<_>>();
+    assert_eq!(mismatches, [2, -2]);
+    let report = document.verify()?;
+    let findings = report.findings_of(Category::OutlineCount).collect::<Vec<_>>();
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[1].get_message(), "Outline item \"Parent\" declares /Count 0 but -2 is computed");
+    assert_eq!(findings[1].get_object(), Some((5, 0)));
+
+    let document = PDFDocument::open_with_options(path, ParseOptions::strict().with_check_outline_counts(true))?;
+    let warnings = document.get_warnings();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].get_code(), WarningCode::OutlineCount);
+    assert_eq!(warnings[0].get_message(), "Outline root declares /Count 0 but 2 is computed");
+    Ok(())
+}
+
+#[test]
+fn test_damaged_info() -> Result<()> {
+    let objects = common::catalog_with_pages(&[""], vec![
+        "[(Not) (a) (dictionary)]".to_string(),
+    ]);
+    let open = |name: &str, info: &str| {
+        let trailer = format!("/Size 5 /Root 1 0 R /Info {}", info);
+        PDFDocument::open(common::write_bytes(name, &common::build_pdf_with_trailer(&objects, &trailer)))
+    };
+    // A dangling /Info doesn't prevent opening, even in strict mode
+    let document = open("dangling-info", "9 0 R")?;
+    assert!(document.get_describe().is_none());
+    assert_eq!(document.get_page_num(), 1);
+    assert_eq!(document.get_warnings().len(), 1);
+    assert_eq!(document.get_warnings()[0].get_message(), "Info object 9 0 is missing from the xref table, ignored");
+
+    let document = open("array-info", "4 0 R")?;
+    assert!(document.get_describe().is_none());
+    assert_eq!(document.get_warnings()[0].get_message(), "Info object 4 0 is not a dictionary, ignored");
+    Ok(())
+}
+
+#[test]
+fn test_parse_at_offset() -> Result<()> {
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    // The linearization dictionary follows the header at byte 16
+    let (object, len) = document.parse_at_offset(16)?;
+    match object.as_indirect_object() {
+        Some((obj_num, gen_num, object)) => {
+            assert_eq!((obj_num, gen_num), (1046, 0));
+            assert!(object.as_dict().is_some_and(|dict| dict.contain("Linearized")));
+        }
+        None => unreachable!(),
+    }
+    assert_eq!(len, 93);
+    // The binary comment line before the object is skipped
+    let (object, len) = document.parse_at_offset(9)?;
+    assert!(object.is_indirect_object());
+    assert_eq!(len, 100);
+
+    // A wrong offset points into the dictionary, one past the end is outside the file
+    assert!(document.parse_at_offset(99).is_err());
+    let size = std::fs::metadata("document/pdfreference1.0.pdf")?.len();
+    assert!(matches!(
+        document.parse_at_offset(size),
+        Err(PDFError::OffsetOutOfRange { offset, size: found }) if offset == size && found == size
+    ));
+
+    // verify() spot-checks the object found at every xref offset
+    let objects = common::catalog_with_pages(&["/MediaBox [0 0 10 10]"], vec![
+        "(four)".to_string(),
+        "(five)".to_string(),
+    ]);
+    let text = String::from_utf8(common::build_pdf(&objects)).unwrap();
+    let mut lines = text.lines().collect::<Vec<_>>();
+    let first = lines.iter().position(|it| it.starts_with("0000000000")).unwrap();
+    // Swap the entries of objects 4 and 5
+    lines.swap(first + 4, first + 5);
+    let swapped = lines.join("\n") + "\n";
+    let mut document = PDFDocument::open(common::write_bytes("swapped-offsets", swapped.as_bytes()))?;
+    let report = document.verify()?;
+    let findings = report.findings_of(Category::XrefOffset).collect::<Vec<_>>();
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[0].get_object(), Some((4, 0)));
+    assert!(findings[0].get_message().ends_with("holds object 5 0"));
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_definitions() -> Result<()> {
+    let objects = common::catalog_with_pages(&["/Rotate 0"], vec![
+        "<< /Title (Original) >>".to_string(),
+    ]);
+    let bytes = common::build_pdf_with_trailer(&objects, "/Size 5 /Root 1 0 R /Info 4 0 R");
+    // The update redefines the Info dictionary like any incremental update
+    let mut bytes = common::append_update(bytes, &[((4, 0), "<< /Title (Updated) >>".to_string())], "/Size 5 /Root 1 0 R /Info 4 0 R");
+    // A second page object no xref points at, in front of the trailing startxref
+    let shadow = b"3 0 obj\n<< /Type /Page /Parent 2 0 R /Rotate 90 >>\nendobj\n";
+    let at = bytes.windows(9).rposition(|it| it == b"startxref").unwrap();
+    let start = at - "trailer\n".len();
+    let start = bytes[..start].windows(4).rposition(|it| it == b"xref").unwrap();
+    bytes.splice(start..start, shadow.iter().copied());
+    let text = String::from_utf8(bytes).unwrap();
+    let offset = text.rfind("startxref\n").unwrap() + "startxref\n".len();
+    let old = text[offset..].split_whitespace().next().unwrap().to_string();
+    let new = (old.parse::<usize>().unwrap() + shadow.len()).to_string();
+    let text = format!("{}{}\n%%EOF\n", &text[..offset], new);
+    let shadow_offset = text.find("3 0 obj\n<< /Type /Page /Parent 2 0 R /Rotate 90").unwrap() as u64;
+
+    let mut document = PDFDocument::open(common::write_bytes("duplicates", text.as_bytes()))?;
+    // Reads follow the xref
+    assert_eq!(document.get_describe().and_then(|it| it.get_title()), Some("Updated"));
+    let page = document.get_page_node(document.get_page_ids()[0]).unwrap();
+    assert_eq!(page.get_attrs().get_u64_num("Rotate"), Some(0));
+
+    let duplicates = document.duplicate_definitions()?;
+    assert_eq!(duplicates.iter().map(|it| it.get_object()).collect::<Vec<_>>(), [(3, 0), (4, 0)]);
+    let page = &duplicates[0];
+    assert_eq!(page.get_offsets().len(), 2);
+    assert_eq!(page.get_shadowed_offsets(), [shadow_offset]);
+    assert!(page.get_designated_offset().is_some() && !page.is_last_designated());
+    assert!(duplicates[1].is_last_designated());
+
+    let report = document.verify()?;
+    let findings = report.findings_of(Category::DuplicateObject).collect::<Vec<_>>();
+    assert_eq!(findings.iter().map(|it| it.get_severity()).collect::<Vec<_>>(), [Severity::Warning, Severity::Info]);
+    assert!(findings[0].get_message().contains(&shadow_offset.to_string()));
+    // The scan of verify() leaves the diagnostics of the document alone
+    let unreadable = text.replacen("4 0 obj\n<< /Title (Original) >>", "4 0 obj\n<< /Title (Original) ]>", 1);
+    let options = ParseOptions::lenient();
+    let mut document = PDFDocument::open_with_options(common::write_bytes("duplicates-lenient", unreadable.as_bytes()), options)?;
+    let (warnings, scanned) = (document.get_warnings().len(), document.get_recovery_scanned());
+    document.verify()?;
+    assert_eq!((document.get_warnings().len(), document.get_recovery_scanned()), (warnings, scanned));
+    assert!(document.scan_objects().is_ok());
+    assert!(document.get_warnings().len() > warnings);
+
+    // Without an xref the last definition is used and the duplicate is a warning
+    let offset = text.rfind("startxref\n").unwrap() + "startxref\n".len();
+    let broken = format!("{}999999\n%%EOF\n", &text[..offset]);
+    let (partial, _) = PDFDocument::open_partial(common::write_bytes("duplicates-scan", broken.as_bytes()));
+    let document = partial.get_document();
+    let warning = document.get_warnings().iter().find(|it| it.get_message().starts_with("Object 3 0 is defined"));
+    assert!(warning.is_some_and(|it| it.get_message().ends_with(&format!("and {}, the later one is used", shadow_offset))));
+    assert_eq!(document.get_xref_entry((3, 0)).map(|it| it.get_value()), Some(shadow_offset));
+    Ok(())
+}
+
+#[test]
+fn test_object_id() -> Result<()> {
+    let id: ObjectId = "12 0 R".parse()?;
+    assert_eq!((id.get_number(), id.get_generation()), (12, 0));
+    assert_eq!(id.to_string().parse::<ObjectId>()?, id);
+    assert_eq!(" 7  2 ".parse::<ObjectId>()?, ObjectId::new(7, 2));
+    for text in ["12 R", "12 0 obj", "12 0 R R", "-1 0 R", ""] {
+        assert!(matches!(text.parse::<ObjectId>(), Err(PDFError::InvalidObjectId(_))), "{}", text);
+    }
+    assert_eq!(ObjectId::from((3, 1)), (3, 1));
+    assert!(ObjectId::new(2, 5) < ObjectId::new(3, 0));
+
+    // Page handles are the identifiers of the page objects, tuples are still accepted
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    let page_id = document.get_page_ids()[0];
+    assert_eq!(page_id, document.get_page_node(page_id).unwrap().get_page_obj_ref());
+    let by_id = document.read_object_with_ref(page_id)?.unwrap();
+    let by_tuple = document.read_object_with_ref(page_id.to_tuple())?.unwrap();
+    assert!(by_id == by_tuple);
+    Ok(())
+}
+
+#[test]
+fn test_extract_text_report() -> Result<()> {
+    let content = |text: &str| common::stream("", &format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text));
+    let page = |contents: u32| {
+        format!("<< /Type /Page /Parent 2 0 R /Resources << /Font << /F1 9 0 R >> >> /Contents {} 0 R >>", contents)
+    };
+    let path = common::write_pdf("text-report", &[
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >>".to_string(),
+        page(6),
+        // The second page is corrupted
+        "<< /Type /Page /Parent 2 0 R /Contents 7 0 R /Resources << /Font [ >> >>".to_string(),
+        page(8),
+        content("First"),
+        content("Lost"),
+        content("Third"),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ]);
+    let mut document = PDFDocument::open_with_options(path, ParseOptions::lenient())?;
+    let report = document.extract_text_report(&TextExtractionOptions::new());
+    let statuses = report.get_pages().iter().map(|it| it.get_status().is_extracted()).collect::<Vec<_>>();
+    assert_eq!(statuses, [true, false, true]);
+    assert!(matches!(report.get_pages()[0].get_status(), PageStatus::Ok));
+    assert!(matches!(report.get_pages()[1].get_status(), PageStatus::Failed(_)));
+    assert_eq!((report.ok_pages(), report.warning_pages(), report.failed_pages()), (2, 0, 1));
+    assert_eq!(report.get_text(), "First\x0c\x0cThird");
+    assert_eq!(report.total_chars(), 10);
+    assert_eq!(report.total_elapsed(), report.get_pages().iter().map(|it| it.get_elapsed()).sum());
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_features() -> Result<()> {
+    let objects = common::catalog_with_pages(&["/Contents 5 0 R"], vec![
+        "<< /Filter /Standard /V 5 /R 6 /CF << /StdCF << /CFM /AESV3 >> >> /StmF /StdCF /StrF /StdCF >>".to_string(),
+        common::stream("/Filter [/ASCIIHexDecode /JBIG2Decode]", "42540A4554>"),
+    ]);
+    let encrypted = common::build_pdf_with_trailer(&objects, "/Size 6 /Root 1 0 R /Encrypt 4 0 R");
+    let path = common::write_bytes("unsupported-encryption", &encrypted);
+    let aes = Feature::Encryption("AESV3".to_string());
+    // Strict mode opens the document but refuses to decode the encrypted stream data
+    let mut document = PDFDocument::open(path.clone())?;
+    assert_eq!(document.get_encryption(), Some(&aes));
+    assert_eq!(document.get_page_num(), 1);
+    match Page::new(document.get_page_ids()[0]).text(&mut document) {
+        Err(PDFError::Unsupported { feature, .. }) => assert_eq!(feature, aes),
+        _ => panic!("the content of an encrypted document can't be decoded"),
+    }
+
+    let mut document = PDFDocument::open_with_options(path, ParseOptions::lenient())?;
+    assert_eq!(document.get_encryption(), Some(&aes));
+    assert_eq!(document.get_warnings()[0].get_code(), WarningCode::Unsupported);
+    let jbig2 = Feature::Filter("JBIG2Decode".to_string());
+    let features = document.required_features();
+    assert_eq!(features, vec![aes, Feature::Filter("ASCIIHexDecode".to_string()), jbig2.clone()]);
+    assert_eq!(features.iter().map(|it| it.is_supported()).collect::<Vec<_>>(), [false, true, false]);
+    match Page::new(document.get_page_ids()[0]).text(&mut document) {
+        Err(PDFError::Unsupported { feature, .. }) => assert_eq!(feature, jbig2),
+        _ => panic!("JBIG2Decode must be unsupported"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_save_preserves_numbering() -> Result<()> {
+    // The body of the newest definition of an object
+    let body = |bytes: &[u8], obj_num: u32| {
+        let text = String::from_utf8_lossy(bytes);
+        let start = text.rfind(&format!("\n{} 0 obj\n", obj_num)).unwrap() + format!("\n{} 0 obj\n", obj_num).len();
+        let end = start + text[start..].find("\nendobj").unwrap();
+        text[start..end].to_string()
+    };
+    let position = |bytes: &[u8], obj_num: u32| String::from_utf8_lossy(bytes).rfind(&format!("\n{} 0 obj\n", obj_num));
+    let base = common::build_pdf(&[
+        "<< /Pages 2 0 R /Type /Catalog >>".to_string(),
+        "<< /Count 1 /Kids [3 0 R] /Type /Pages >>".to_string(),
+        "<< /Contents 4 0 R /Parent 2 0 R /Type /Page >>".to_string(),
+        "<< /Length 5 >>\nstream\nBT ET\nendstream".to_string(),
+        "(removed)".to_string(),
+    ]);
+    // Object 5 is freed with generation 1 in place, keeping the offsets valid
+    let text = String::from_utf8_lossy(&base).into_owned();
+    let offset = text.find("\n5 0 obj").unwrap() + 1;
+    let base = text.replace(&format!("{:010} 00000 n", offset), "0000000000 00001 f").into_bytes();
+    // Objects 1 and 2 are redefined after the others
+    let original = common::append_update(
+        base,
+        &[
+            ((1, 0), "<< /Lang (en) /Pages 2 0 R /Type /Catalog >>".to_string()),
+            ((2, 0), "<< /Count 1 /Kids [3 0 R] /Rotate 90 /Type /Pages >>".to_string()),
+        ],
+        "/Size 6 /Root 1 0 R",
+    );
+    let path = common::write_bytes("save-preserve", &original);
+    let mut document = PDFDocument::open(path)?;
+    let options = WriteOptions::new().with_preserve_numbering(true);
+    let mut saved = Vec::new();
+    document.save(&mut saved, &options)?;
+    for obj_num in [1, 2, 3, 4] {
+        assert_eq!(body(&saved, obj_num), body(&original, obj_num), "object {}", obj_num);
+    }
+    let order = [3, 4, 1, 2].map(|it| position(&saved, it).unwrap());
+    assert!(order.is_sorted());
+    assert!(position(&saved, 5).is_none());
+
+    // Saving the saved document again produces the same bytes
+    let mut resaved_document = PDFDocument::from_bytes(saved.clone())?;
+    let free = resaved_document.get_xref_slice().iter().find(|it| it.get_obj_num() == 5).unwrap();
+    assert!(free.is_freed() && free.get_gen_num() == 1);
+    assert_eq!(resaved_document.get_page_num(), 1);
+    let mut resaved = Vec::new();
+    resaved_document.save(&mut resaved, &options)?;
+    assert_eq!(resaved, saved);
+
+    // Renumbering closes the gap, compression keeps the content
+    let content = document.page_content_bytes(document.get_page_ids()[0])?;
+    let mut renumbered = Vec::new();
+    document.save(&mut renumbered, &WriteOptions::new().with_compress_streams(true))?;
+    assert!(String::from_utf8_lossy(&renumbered).contains("/Filter /FlateDecode"));
+    let mut document = PDFDocument::from_bytes(renumbered)?;
+    assert_eq!(document.get_xref_slice().iter().filter(|it| !it.is_freed()).count(), 4);
+    assert_eq!(document.page_content_bytes(document.get_page_ids()[0])?, content);
+    Ok(())
+}
+
+#[test]
+fn test_save_recompresses_streams() -> Result<()> {
+    let hex = |bytes: &[u8]| bytes.iter().map(|it| format!("{:02X}", it)).collect::<String>() + ">";
+    let content = (0..100)
+        .map(|line| format!("BT /F1 12 Tf 72 {} Td (Line {} of the quick brown fox) Tj ET\n", 700 - line * 6, line))
+        .collect::<String>();
+    let path = common::write_pdf("save-recompress", &common::catalog_with_pages(
+        &["/Resources << /Font << /F1 4 0 R >> /XObject << /Im1 6 0 R >> >> /Contents 5 0 R"],
+        vec![
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            common::stream("/Filter /ASCIIHexDecode", &hex(content.as_bytes())),
+            common::stream(
+                "/Subtype /Image /Width 1 /Height 1 /BitsPerComponent 8 /Filter [/ASCIIHexDecode /DCTDecode]",
+                &hex(&[0xFF, 0xD8, 0xFF, 0xD9]),
+            ),
+            // `{` is outside the ASCII85 alphabet
+            common::stream("/Filter /ASCII85Decode", "87c{~>"),
+        ],
+    ));
+    let original = std::fs::read(&path)?;
+    let mut document = PDFDocument::open(path)?;
+    let text = document.pages_text(&TextExtractionOptions::new())?;
+    let mut saved = Vec::new();
+    let options = WriteOptions::new().with_preserve_numbering(true).with_compress_streams(true);
+    document.save(&mut saved, &options)?;
+    assert!(saved.len() * 4 < original.len(), "{} of {} bytes", saved.len(), original.len());
+    let warning = document.get_warnings().last().unwrap().get_message().to_string();
+    assert!(warning.starts_with("Stream 7 0 can't be decoded, written unchanged"), "{}", warning);
+
+    let mut document = PDFDocument::from_bytes(saved)?;
+    assert_eq!(document.pages_text(&TextExtractionOptions::new())?, text);
+    let mut stream = |obj_num: u32| match document.read_object_with_ref((obj_num, 0)) {
+        Ok(Some(PDFObject::IndirectObject(_, _, object))) => object.as_stream().cloned().unwrap(),
+        _ => panic!("object {} must be a stream", obj_num),
+    };
+    assert_eq!(stream(5).filters(), ["FlateDecode"]);
+    let image = stream(6);
+    assert_eq!((image.filters(), image.as_slice()), (vec!["DCTDecode".to_string()], &[0xFF, 0xD8, 0xFF, 0xD9][..]));
+    let broken = stream(7);
+    assert_eq!((broken.filters(), broken.as_slice()), (vec!["ASCII85Decode".to_string()], &b"87c{~>"[..]));
+    Ok(())
+}
+
+#[test]
+fn test_circular_references() -> Result<()> {
+    let open = |name: &str, contents: &str, extra: &[String]| {
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R /Loop 6 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R >>".to_string(),
+            contents.to_string(),
+        ];
+        objects.extend_from_slice(extra);
+        PDFDocument::open(common::write_pdf(name, &objects))
+    };
+    let circular = |result: Result<Vec<u8>>| match result {
+        Err(PDFError::CircularReference { object }) => Some(object),
+        _ => None,
+    };
+    // An indirect /Length is resolved
+    let mut document = open("indirect-length", "<< /Length 5 0 R >>\nstream\nBT ET\nendstream", &["5".to_string()])?;
+    assert!(document.page_content_bytes(document.get_page_ids()[0])?.starts_with(b"BT ET"));
+
+    let mut document = open("self-length", "<< /Length 4 0 R >>\nstream\nBT ET\nendstream", &[])?;
+    let page_id = document.get_page_ids()[0];
+    assert_eq!(circular(document.page_content_bytes(page_id)), Some(ObjectId::new(4, 0)));
+
+    // The /Length of object 4 is object 5, a stream whose /Length is object 4
+    let other = "<< /Length 4 0 R >>\nstream\nxx\nendstream".to_string();
+    let mut document = open("cyclic-length", "<< /Length 5 0 R >>\nstream\nBT ET\nendstream", &[other])?;
+    let page_id = document.get_page_ids()[0];
+    assert_eq!(circular(document.page_content_bytes(page_id)), Some(ObjectId::new(4, 0)));
+
+    // Objects 6 and 7 are references to each other
+    let extra = ["5".to_string(), "7 0 R".to_string(), "6 0 R".to_string()];
+    let mut document = open("cyclic-refs", "<< /Length 5 0 R >>\nstream\nBT ET\nendstream", &extra)?;
+    let catalog = PDFObject::Dict(document.get_catalog().get_attrs().clone());
+    match document.get_path(&catalog, "Loop") {
+        Err(PDFError::CircularReference { object }) => assert_eq!(object, ObjectId::new(6, 0)),
+        _ => panic!("the references must be reported as circular"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_diff() -> Result<()> {
+    let path = PathBuf::from("document/pdfreference1.0.pdf");
+    let mut a = PDFDocument::open(path.clone())?;
+    let mut b = PDFDocument::open(path.clone())?;
+    let report = pdf_rs::diff(&mut a, &mut b);
+    assert!(report.is_empty(), "{}", report);
+
+    let describe = b.get_describe().unwrap().clone();
+    b.set_describe(describe.with_title("Changed").with_producer_stamp(false));
+    let mut buf = Vec::new();
+    b.save_incremental(&mut buf)?;
+    let mut b = PDFDocument::from_bytes(buf)?;
+    let report = pdf_rs::diff(&mut a, &mut b);
+    assert_eq!(report.get_differences().len(), 1, "{}", report);
+    let difference = report.find("Info/Title").unwrap();
+    assert_eq!(difference.get_kind(), DiffKind::Changed);
+    assert_eq!(difference.get_after(), Some("Changed"));
+    assert!(report.to_string().starts_with("~ Info/Title: "));
+
+    // Objects are paired by path, the renumbered page and content compare equal
+    let a_objects = common::catalog_with_pages(&["/MediaBox [0 0 612 792] /Contents 4 0 R"], vec![
+        common::stream("", "BT ET"),
+    ]);
+    let b_objects = [
+        "<< /Type /Catalog /Pages 2 0 R /Lang (en) >>".to_string(),
+        "<< /Type /Pages /Kids [4 0 R 5 0 R] /Count 2 >>".to_string(),
+        common::stream("", "BT ET"),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Contents 3 0 R >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+    ];
+    let mut a = PDFDocument::from_bytes(common::build_pdf(&a_objects))?;
+    let mut b = PDFDocument::from_bytes(common::build_pdf(&b_objects))?;
+    let report = pdf_rs::diff(&mut a, &mut b);
+    let paths = report.get_differences().iter().map(|it| (it.get_kind(), it.get_path())).collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            (DiffKind::Changed, "PageCount"),
+            (DiffKind::Added, "Root/Lang"),
+            (DiffKind::Changed, "Root/Pages/Count"),
+            (DiffKind::Added, "Root/Pages/Kids[1]"),
+            (DiffKind::Changed, "Root/Pages/Kids[0]/MediaBox[2]"),
+            (DiffKind::Changed, "Root/Pages/Kids[0]/MediaBox[3]"),
+        ]
+    );
+    assert_eq!(report.find("Root/Pages/Kids[1]").unwrap().get_after(), Some("5 0 R"));
+    Ok(())
+}
+
+#[test]
+fn test_ordered_page_access() -> Result<()> {
+    // The pages are defined out of order below two levels of intermediate nodes:
+    // root [A [p1 B [p2 p3]] p4], pages tagged by their /Rotate
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [4 0 R 3 0 R] /Count 4 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /Rotate 270 >>".to_string(),
+        "<< /Type /Pages /Parent 2 0 R /Kids [7 0 R 5 0 R] /Count 3 >>".to_string(),
+        "<< /Type /Pages /Parent 4 0 R /Kids [8 0 R 6 0 R] /Count 2 >>".to_string(),
+        "<< /Type /Page /Parent 5 0 R /Rotate 180 >>".to_string(),
+        "<< /Type /Page /Parent 4 0 R /Rotate 0 >>".to_string(),
+        "<< /Type /Page /Parent 5 0 R /Rotate 90 >>".to_string(),
+    ];
+    let document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let rotates = document.pages().map(|it| it.get_attrs().get_u64_num("Rotate")).collect::<Vec<_>>();
+    assert_eq!(rotates, [Some(0), Some(90), Some(180), Some(270)]);
+    for (index, page) in document.pages().enumerate() {
+        assert_eq!(page.get_index(), index);
+        assert_eq!(document.get_page(index), Some(page.get_page()));
+        assert_eq!(page.get_page_id(), document.get_page_ids()[index]);
+    }
+    assert_eq!(document.get_page(1).map(|it| it.get_page_id()), Some(ObjectId::from((8, 0))));
+    assert_eq!(document.get_page(4), None);
+    assert_eq!(document.get_page(usize::MAX), None);
+
+    let document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    assert_eq!(document.pages().count(), 230);
+    assert_eq!(document.get_page(229).map(|it| it.get_page_id()), document.get_page_ids().last().copied());
+    Ok(())
+}
+
+#[test]
+fn test_custom_info_entries() -> Result<()> {
+    let objects = common::catalog_with_pages(&[""], vec![
+        "<< /Title (Old) /Producer (Original) /CreationDate (yesterday) /Trapped /False /SourceModified (D:20240101) \
+         /Company.Tag [1 2] /Extra 5 0 R >>".to_string(),
+        "(referenced)".to_string(),
+    ]);
+    let original = common::build_pdf_with_trailer(&objects, "/Size 6 /Root 1 0 R /Info 4 0 R");
+    let mut document = PDFDocument::from_bytes(original)?;
+    let describe = document.get_describe().unwrap().clone();
+    assert_eq!(describe.get_creation_date(), None);
+
+    // Without staged changes a renumbering save copies the information unchanged
+    let mut buf = Vec::new();
+    document.save(&mut buf, &WriteOptions::new())?;
+    assert!(String::from_utf8_lossy(&buf).contains("/CreationDate (yesterday)"));
+    let mut saved = PDFDocument::from_bytes(buf)?;
+    let unchanged = saved.get_describe().unwrap().clone();
+    assert_eq!(unchanged.get_producer(), Some("Original"));
+    let (_, extra) = unchanged.get_custom().find(|(key, _)| *key == "Extra").unwrap();
+    let referenced = saved.read_object_with_ref(extra.as_object_ref().unwrap())?;
+    assert!(matches!(referenced, Some(PDFObject::IndirectObject(_, _, value)) if *value == PDFObject::String(PDFString::literal("referenced"))));
+    assert_eq!(describe.get_trapped(), Some(Trapped::False));
+    let mut custom = describe.get_custom().map(|(key, value)| (key.to_string(), writer::to_bytes(value))).collect::<Vec<_>>();
+    custom.sort();
+    assert_eq!(
+        custom,
+        [
+            ("Company.Tag".to_string(), b"[1 2]".to_vec()),
+            ("Extra".to_string(), b"5 0 R".to_vec()),
+            ("SourceModified".to_string(), b"(D:20240101)".to_vec()),
+        ]
+    );
+
+    // Only the title is edited, the custom entries and /Trapped survive the update
+    document.set_describe(describe.with_title("New").with_producer_stamp(false));
+    let mut buf = Vec::new();
+    document.save_incremental(&mut buf)?;
+    let mut document = PDFDocument::from_bytes(buf)?;
+    let describe = document.get_describe().unwrap().clone();
+    assert_eq!(describe.get_title(), Some("New"));
+    assert_eq!(describe.get_trapped(), Some(Trapped::False));
+    assert_eq!(describe.get_custom().count(), 3);
+    let info = document.read_object_with_ref((4, 0))?.unwrap();
+    assert!(document.get_path(&info, "Extra")? == Some(PDFObject::String(PDFString::literal("referenced"))));
+
+    // A renumbering save writes the changes into the copied dictionary, the referenced
+    // entry and the unparsable date survive
+    document.set_describe(describe.with_trapped(Trapped::True).with_custom("Title", PDFString::literal("ignored")).with_custom("Added", 7u64));
+    let mut buf = Vec::new();
+    document.save(&mut buf, &WriteOptions::new())?;
+    assert!(String::from_utf8_lossy(&buf).contains("/CreationDate (yesterday)"));
+    let mut document = PDFDocument::from_bytes(buf)?;
+    let describe = document.get_describe().unwrap().clone();
+    assert_eq!((describe.get_title(), describe.get_trapped()), (Some("New"), Some(Trapped::True)));
+    let mut keys = describe.get_custom().map(|(key, _)| key).collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, ["Added", "Company.Tag", "Extra", "SourceModified"]);
+    let (_, extra) = describe.get_custom().find(|(key, _)| *key == "Extra").unwrap();
+    let referenced = document.read_object_with_ref(extra.as_object_ref().unwrap())?;
+    assert!(matches!(referenced, Some(PDFObject::IndirectObject(_, _, value)) if *value == PDFObject::String(PDFString::literal("referenced"))));
+    Ok(())
+}
+
+#[test]
+fn test_page_boxes() -> Result<()> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /MediaBox 5 0 R /Rotate -90 /Resources 6 0 R >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /CropBox [700 -10 10 400] >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Rotate 7 0 R /Resources << >> >>".to_string(),
+        "[0 0 612 8 0 R]".to_string(),
+        "<< /Font << /F1 9 0 R >> >>".to_string(),
+        "450".to_string(),
+        "792".to_string(),
+    ];
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let first = document.get_page(0).unwrap();
+    let media_box = first.media_box(&mut document)?;
+    assert_eq!(media_box, Rect::new(0.0, 0.0, 612.0, 792.0));
+    assert_eq!((media_box.get_width(), media_box.get_height()), (612.0, 792.0));
+    // The CropBox is ordered and clipped to the MediaBox
+    assert_eq!(first.crop_box(&mut document)?.to_array(), [10.0, 0.0, 612.0, 400.0]);
+    assert_eq!(first.rotate(&mut document)?, 270);
+    assert!(first.resources(&mut document)?.is_some_and(|it| it.contain("Font")));
+
+    let second = document.get_page(1).unwrap();
+    assert_eq!(second.media_box(&mut document)?.to_array(), [0.0, 0.0, 595.0, 842.0]);
+    assert_eq!(second.crop_box(&mut document)?, second.media_box(&mut document)?);
+    assert_eq!(second.rotate(&mut document)?, 90);
+    assert!(second.resources(&mut document)?.is_some_and(|it| !it.contain("Font")));
+
+    assert!(PDFObject::Array(vec![PDFObject::from(1u64); 3]).as_rect().is_none());
+    assert_eq!(Rect::new(0.0, 0.0, 10.0, 10.0).intersect(&Rect::new(20.0, 20.0, 30.0, 30.0)), None);
+    assert!(matches!(Page::new(ObjectId::from((99, 0))).rotate(&mut document), Err(PDFError::PageNotFound(_))));
+    Ok(())
+}
+
+#[test]
+fn test_inherited_page_attributes() -> Result<()> {
+    // The resources and MediaBox are only defined on the root, the intermediate node
+    // overrides the rotation and defines /Contents which isn't inheritable
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 /MediaBox [0 0 612 792] /Rotate 180 /Resources << /Font << /F1 6 0 R >> >> >>".to_string(),
+        "<< /Type /Pages /Parent 2 0 R /Kids [4 0 R] /Count 1 /Rotate 90 /Contents 5 0 R >>".to_string(),
+        "<< /Type /Page /Parent 3 0 R /Contents 5 0 R >>".to_string(),
+        common::stream("", "BT /F1 12 Tf 72 712 Td (Inherited) Tj ET"),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let page_id = document.get_page_ids()[0];
+    let tree = document.get_page_tree();
+    assert!(tree.get_inherited_attr(page_id, "MediaBox").is_some_and(|it| it.as_rect().is_some()));
+    assert_eq!(tree.get_inherited_attr(page_id, "Rotate").and_then(|it| it.as_number()).map(|it| it.as_f64()), Some(90.0));
+    assert!(tree.get_inherited_attr(page_id, "Count").is_none());
+    assert!(tree.get_inherited_attr(ObjectId::from((3, 0)), "Count").is_some());
+    let attrs = tree.get_effective_attrs(page_id).unwrap();
+    let mut keys = attrs.iter().map(|(key, _)| key.to_string()).collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, ["Contents", "MediaBox", "Parent", "Resources", "Rotate", "Type"]);
+    assert!(tree.get_effective_attrs(ObjectId::from((9, 0))).is_none());
+
+    let page = document.get_page(0).unwrap();
+    assert_eq!(page.media_box(&mut document)?.to_array(), [0.0, 0.0, 612.0, 792.0]);
+    assert_eq!(page.rotate(&mut document)?, 90);
+    assert_eq!(page.text(&mut document)?, "Inherited");
+    assert!(document.unused_resources(0)?.get_fonts().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_text_state_spacing() -> Result<()> {
+    // Helvetica without /Widths measures every glyph 500, i.e. 5 units at size 10
+    let document_with = |content: &str| {
+        let objects = common::catalog_with_pages(&["/Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R"], vec![
+            common::stream("", content),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ]);
+        PDFDocument::from_bytes(common::build_pdf(&objects))
+    };
+    let fragments = |document: &mut PDFDocument| -> Result<Vec<(String, f64, f64, f64)>> {
+        let page = document.get_page(0).unwrap();
+        let fragments = page.fragments_with(document, &TextExtractionOptions::new())?;
+        Ok(fragments.iter().map(|it| (it.get_text().to_string(), it.get_x(), it.get_y(), it.get_width())).collect())
+    };
+
+    // The word spacing only widens the space, the character spacing every glyph
+    let mut document = document_with("BT /F1 10 Tf 100 700 Td 40 Tw 1 Tc (a b) Tj (c) Tj ET")?;
+    let measured = fragments(&mut document)?;
+    assert_eq!(measured[0], ("a b".to_string(), 100.0, 700.0, 58.0));
+    assert_eq!(measured[1], ("c".to_string(), 158.0, 700.0, 6.0));
+    // A text object continuing where the widened words end continues the last word, one
+    // starting further away begins a new word
+    for (x, text) in [(135, "a bc"), (140, "a b c")] {
+        let content = format!("BT /F1 10 Tf 100 700 Td 20 Tw (a b) Tj ET BT /F1 10 Tf {} 700 Td (c) Tj ET", x);
+        let mut document = document_with(&content)?;
+        let page = document.get_page(0).unwrap();
+        assert_eq!(page.text(&mut document)?, text);
+    }
+
+    // Tz 50 halves the advance, also of TJ adjustments, and is restored by Q
+    let mut document = document_with("BT /F1 10 Tf 100 700 Td q 50 Tz (ab) Tj [(c) -1000 (d)] TJ Q (e) Tj ET")?;
+    let measured = fragments(&mut document)?;
+    let positions = measured.iter().map(|(text, x, _, width)| (text.as_str(), *x, *width)).collect::<Vec<_>>();
+    assert_eq!(positions, [("ab", 100.0, 5.0), ("c", 105.0, 2.5), ("d", 112.5, 2.5), ("e", 115.0, 5.0)]);
+
+    // A superscript is raised without leaving the line, the rise doesn't advance
+    let mut document = document_with("BT /F1 10 Tf 100 700 Td (E=mc) Tj 6 Ts (2) Tj 0 Ts ( holds) Tj ET")?;
+    let measured = fragments(&mut document)?;
+    assert_eq!((measured[1].1, measured[1].2), (120.0, 706.0));
+    assert_eq!((measured[2].1, measured[2].2), (125.0, 700.0));
+    let page = document.get_page(0).unwrap();
+    assert_eq!(page.text(&mut document)?, "E=mc2 holds");
+    let layout = page.text_with(&mut document, &TextExtractionOptions::new().with_layout(true))?;
+    assert_eq!(layout.lines().count(), 1);
+
+    // The " operator sets both spacings
+    let mut document = document_with("BT /F1 10 Tf 12 TL 100 700 Td 10 2 (a b) \" ET")?;
+    assert_eq!(fragments(&mut document)?[0], ("a b".to_string(), 100.0, 688.0, 31.0));
+    Ok(())
+}
+
+#[test]
+fn test_right_to_left_text() -> Result<()> {
+    // The font maps a to d onto ש ל ו ם, the lines show שלום from left to right, i.e. in
+    // visual order starting with the final mem, the last one in two fragments
+    let content = "BT /F1 10 Tf 100 700 Td (dcba) Tj 0 -20 Td (Hello dcba) Tj 0 -20 Td (42 dcba) Tj 0 -20 Td (dc) Tj (ba) Tj ET";
+    let objects = common::catalog_with_pages(&["/Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R"], vec![
+        common::stream("", content),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Hebrew \
+         /Encoding << /Differences [97 /uni05E9 /uni05DC /uni05D5 /uni05DD] >> >>".to_string(),
+    ]);
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let page = document.get_page(0).unwrap();
+    assert_eq!(page.text(&mut document)?, "םולש\nHello םולש\n42 םולש\nםולש");
+
+    // A Hebrew word in a Latin line is reversed in place, in a Hebrew line the number
+    // keeps its order and follows the word
+    let bidi = TextExtractionOptions::new().with_bidi(true);
+    assert!(bidi.is_bidi() && !TextExtractionOptions::new().is_bidi());
+    assert_eq!(page.text_with(&mut document, &bidi)?, "שלום\nHello שלום\nשלום 42\nשלום");
+    let layout = page.text_with(&mut document, &bidi.clone().with_layout(true))?;
+    assert_eq!(layout, "שלום\nHello שלום\nשלום 42\nשלום\n");
+    // The fragments are in logical order too, those of a right-to-left line reversed
+    let fragments = page.fragments_with(&mut document, &bidi)?;
+    let texts = fragments.iter().map(|it| it.get_text()).collect::<Vec<_>>();
+    assert_eq!(texts, ["שלום", "Hello שלום", "שלום 42", "של", "ום"]);
+    assert!(fragments[3].get_x() >= fragments[4].get_x());
+    let plain = page.fragments_with(&mut document, &TextExtractionOptions::new())?;
+    assert_eq!(plain.iter().map(|it| it.get_text()).collect::<Vec<_>>(), ["םולש", "Hello םולש", "42 םולש", "םו", "לש"]);
+    Ok(())
+}
+
+#[test]
+fn test_invisible_text() -> Result<()> {
+    // An OCR line in mode 3 between two visible lines, Q restores the rendering mode
+    let content = "BT /F1 10 Tf 100 700 Td (Visible) Tj ET \
+                   q BT /F1 10 Tf 3 Tr 100 680 Td (OCR layer) Tj ET Q \
+                   BT /F1 10 Tf 100 660 Td (Shown) Tj ET";
+    let objects = common::catalog_with_pages(&["/Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R"], vec![
+        common::stream("", content),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ]);
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let page = document.get_page(0).unwrap();
+    let flags = |document: &mut PDFDocument, options: &TextExtractionOptions| -> Result<Vec<(u8, bool)>> {
+        let fragments = page.fragments_with(document, options)?;
+        Ok(fragments.iter().map(|it| (it.get_rendering_mode(), it.is_invisible())).collect())
+    };
+
+    // Included by default, the mode is reported without flagging the fragment
+    let include = TextExtractionOptions::new();
+    assert_eq!(include.get_invisible_text(), InvisibleText::Include);
+    assert_eq!(page.text_with(&mut document, &include)?, "Visible\nOCR layer\nShown");
+    assert_eq!(flags(&mut document, &include)?, [(0, false), (3, false), (0, false)]);
+
+    let exclude = TextExtractionOptions::new().with_invisible_text(InvisibleText::Exclude);
+    assert_eq!(page.text_with(&mut document, &exclude)?, "Visible\nShown");
+    assert_eq!(flags(&mut document, &exclude)?, [(0, false), (0, false)]);
+
+    let flag = TextExtractionOptions::new().with_invisible_text(InvisibleText::Flag);
+    assert_eq!(page.text_with(&mut document, &flag)?, "Visible\nOCR layer\nShown");
+    assert_eq!(flags(&mut document, &flag)?, [(0, false), (3, true), (0, false)]);
+    Ok(())
+}
+
+#[test]
+fn test_page_labels() -> Result<()> {
+    // Front matter i to iii, body 1 to 3, then appendix pages A-8 to A-9 and a cover
+    // labelled by its prefix only; the ranges are split over two leaves
+    let kids = (0..9).map(|i| format!("{} 0 R", i + 6)).collect::<Vec<_>>().join(" ");
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R /PageLabels 3 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count 9 >>", kids),
+        "<< /Kids [4 0 R 5 0 R] >>".to_string(),
+        "<< /Limits [0 3] /Nums [0 << /S /r >> 3 15 0 R] >>".to_string(),
+        "<< /Limits [6 8] /Nums [6 << /S /D /P (A-) /St 8 >> 8 << /P (Cover) >>] >>".to_string(),
+    ];
+    objects.extend((0..9).map(|_| "<< /Type /Page /Parent 2 0 R >>".to_string()));
+    objects.push("<< /S /D >>".to_string());
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let labels = (0..9).map(|index| document.page_label(index).unwrap()).collect::<Vec<_>>();
+    assert_eq!(labels, ["i", "ii", "iii", "1", "2", "3", "A-8", "A-9", "Cover"]);
+    assert_eq!(document.page_label(9), None);
+    let ranges = document.page_labels()?;
+    assert_eq!(ranges.len(), 4);
+    assert_eq!((ranges[2].get_start(), ranges[2].get_prefix(), ranges[2].get_first_number()), (6, "A-", 8));
+    assert_eq!(ranges[3].get_style(), None);
+
+    assert_eq!(LabelStyle::UpperRoman.format(1994), "MCMXCIV");
+    assert_eq!(LabelStyle::LowerAlpha.format(28), "bb");
+    assert_eq!(LabelStyle::UpperAlpha.format(53), "AAA");
+    assert_eq!(LabelStyle::Decimal.format(0), "");
+
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    assert_eq!(document.page_label(0).as_deref(), Some("1"));
+    assert_eq!(document.page_label(10).as_deref(), Some("11"));
+
+    let objects = ["<< /Type /Catalog /Pages 2 0 R >>", "<< /Type /Pages /Kids [3 0 R] /Count 1 >>", "<< /Type /Page /Parent 2 0 R >>"].map(String::from);
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    assert!(document.page_labels()?.is_empty());
+    assert_eq!(document.page_label(0), None);
+    Ok(())
+}
+
+#[test]
+fn test_outline_title_encodings() -> Result<()> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R /Outlines 4 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R >>",
+        "<< /Type /Outlines /First 5 0 R /Last 10 0 R /Count 6 >>",
+        // PDFDocEncoding, 0x80 is a bullet and 0xE9 is é, in a literal and a hexadecimal string
+        r"<< /Title (\200 Caf\351) /Parent 4 0 R /Next 6 0 R >>",
+        "<< /Title <8020436166E9> /Parent 4 0 R /Prev 5 0 R /Next 7 0 R >>",
+        // UTF-16BE in a literal and a hexadecimal string
+        r"<< /Title (\376\377\000\304\000p\000f\000e\000l) /Parent 4 0 R /Prev 6 0 R /Next 8 0 R >>",
+        "<< /Title <FEFF00C4007000660065006C> /Parent 4 0 R /Prev 7 0 R /Next 9 0 R >>",
+        // A character outside the BMP
+        "<< /Title <FEFF698289810020D834DD1E> /Parent 4 0 R /Prev 8 0 R /Next 10 0 R >>",
+        "<< /Title 11 0 R /Parent 4 0 R /Prev 9 0 R >>",
+        "<FEFF03A903BC03AD03B303B1>",
+    ]
+    .map(String::from);
+    let document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let tree = document.get_outline().expect("outline");
+    let mut titles = Vec::new();
+    let mut next = tree.get_root_node().and_then(|it| it.get_first_id());
+    while let Some(node_id) = next {
+        let node = tree.get_node(node_id).unwrap();
+        titles.push(node.get_title().map(str::to_string));
+        next = node.get_next_id();
+    }
+    let expected = ["\u{2022} Café", "\u{2022} Café", "Äpfel", "Äpfel", "概要 \u{1D11E}", "Ωμέγα"].map(|it| Some(it.to_string()));
+    assert_eq!(titles, expected);
+    Ok(())
+}
+
+#[test]
+fn test_xref_override() -> Result<()> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R >>",
+        "<< /Note (repaired) >>",
+    ]
+    .map(String::from);
+    let mut buf = common::build_pdf(&objects);
+    let text = String::from_utf8_lossy(&buf).into_owned();
+    let offset = text.find("4 0 obj").unwrap() as u64;
+    // The xref points object 4 beyond the end of the file
+    let line = text.rfind(&format!("{:010} 00000 n", offset)).unwrap();
+    buf[line..line + 10].copy_from_slice(b"0000099999");
+    let mut document = PDFDocument::from_bytes(buf)?;
+    assert!(document.read_object_with_ref((4, 0)).is_err());
+
+    document.override_xref_entry((4, 0), XEntryKind::Offset(offset));
+    assert_eq!(document.get_xref_entry((4, 0)).map(|it| it.get_value()), Some(offset));
+    assert_eq!(document.get_xref_overrides().count(), 1);
+    let object = document.read_object_with_ref((4, 0))?.and_then(PDFObject::into_indirect).map(|it| it.2);
+    let note = object.as_ref().and_then(|it| it.as_dict()).and_then(|it| it.get("Note"));
+    assert!(note == Some(&PDFObject::String(PDFString::literal("repaired"))));
+
+    document.override_xref_entry((4, 0), XEntryKind::Free);
+    assert!(document.read_object_with_ref((4, 0))?.is_none());
+    // Only an override of the same generation is removed
+    assert!(!document.remove_xref_override((4, 1)));
+    assert!(document.remove_xref_override((4, 0)));
+    assert_eq!(document.get_xref_entry((4, 0)).map(|it| it.get_value()), Some(99999));
+    assert!(document.read_object_with_ref((4, 0)).is_err());
+    assert!(document.get_xref_overrides().next().is_none());
+
+    // A compressed entry keeps the generation, an unlisted object number is added in order
+    document.override_xref_entry((4, 2), XEntryKind::Compressed { object_stream: 9, index: 1 });
+    assert_eq!(document.get_xref_entry((4, 2)).map(|it| it.get_object_stream()), Some(Some(9)));
+    document.override_xref_entry((7, 0), XEntryKind::Offset(offset));
+    let numbers = document.get_xref_slice().iter().map(|it| it.get_obj_num()).collect::<Vec<_>>();
+    assert!(numbers.is_sorted() && numbers.contains(&7));
+    assert!(document.remove_xref_override((4, 2)));
+    Ok(())
+}
+
+#[test]
+fn test_outline_children() -> Result<()> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R /Outlines 4 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R >>",
+        "<< /Type /Outlines /First 5 0 R /Last 7 0 R /Count 5 >>",
+        "<< /Title (A) /Parent 4 0 R /Next 6 0 R /First 8 0 R /Last 9 0 R /Count 2 >>",
+        "<< /Title (B) /Parent 4 0 R /Prev 5 0 R /Next 7 0 R >>",
+        // The chain of siblings loops back to the first one
+        "<< /Title (C) /Parent 4 0 R /Prev 6 0 R /Next 5 0 R >>",
+        "<< /Title (A.1) /Parent 5 0 R /Next 9 0 R >>",
+        // The child points back at its parent
+        "<< /Title (A.2) /Parent 5 0 R /Prev 8 0 R /First 5 0 R >>",
+    ]
+    .map(String::from);
+    let document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let tree = document.get_outline().expect("outline");
+    let titles = |ids: &[ObjectId]| ids.iter().map(|it| tree.get_node(*it).unwrap().get_title().unwrap().to_string()).collect::<Vec<_>>();
+    assert_eq!(titles(tree.root_items()), ["A", "B", "C"]);
+    let first = tree.get_node(tree.root_items()[0]).unwrap();
+    assert_eq!(titles(first.children()), ["A.1", "A.2"]);
+    assert!(first.children().iter().all(|it| tree.get_node(*it).unwrap().children().is_empty()));
+    assert!(tree.count_mismatches().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_names_constants() -> Result<()> {
+    let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+    let catalog = document.get_catalog().get_attrs().clone();
+    assert!(catalog.named_value_was(names::TYPE, "Catalog"));
+    let pages = catalog.get(names::PAGES).and_then(|it| it.as_object_ref()).expect("pages");
+    // The constants, a `String` and a `Name` find the same entry
+    assert!(catalog.get(String::from("Pages")) == catalog.get(names::PAGES));
+    assert!(catalog.get(Name::new("Pages")) == catalog.get(names::PAGES));
+    assert!(!catalog.contain(names::MEDIA_BOX));
+
+    let pages = document.read_object_with_ref(pages)?.and_then(PDFObject::into_indirect).expect("pages").2;
+    let pages = pages.as_dict().expect("pages dictionary");
+    assert!(pages.named_value_was(names::TYPE, "Pages"));
+    assert_eq!(pages.get_u64_num(names::COUNT), Some(document.get_page_num() as u64));
+    assert!(pages.get_array_value(names::KIDS).is_some_and(|it| !it.is_empty()));
+    Ok(())
+}
+
+#[test]
+fn test_name_tree() -> Result<()> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R /Names << /Dests 4 0 R /JavaScript 9 0 R >> >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R >>",
+        // The last kid points back at the root
+        "<< /Kids [5 0 R 6 0 R 7 0 R 4 0 R] >>",
+        "<< /Limits [(apple) (banana)] /Names [(apple) 8 0 R (banana) [3 0 R /Fit]] >>",
+        "<< /Limits [(cherry) (kiwi)] /Names [(cherry) [3 0 R /XYZ 0 0 0] (kiwi) 8 0 R] >>",
+        // Malformed limits don't hide the entries, which aren't sorted either
+        "<< /Limits [(zz)] /Names [(zebra) 8 0 R (lemon) 8 0 R] >>",
+        "[3 0 R /FitH 792]",
+        "<< /Names [(init) << /S /JavaScript /JS (app.alert\\(1\\)) >>] >>",
+    ]
+    .map(String::from);
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let dests = document.get_name_tree("Dests")?.expect("dests");
+    assert!(dests.lookup(&mut document, b"apple")? == Some(PDFObject::ObjectRef(8, 0)));
+    let banana = dests.lookup(&mut document, b"banana")?.expect("banana");
+    assert_eq!(banana.as_array().map(|it| it.len()), Some(2));
+    assert!(dests.lookup(&mut document, b"zebra")? == Some(PDFObject::ObjectRef(8, 0)));
+    assert!(dests.lookup(&mut document, b"lemon")?.is_some());
+    // Misses, inside and outside the limits of a kid
+    assert!(dests.lookup(&mut document, b"date")?.is_none());
+    assert!(dests.lookup(&mut document, b"aardvark")?.is_none());
+    assert!(dests.lookup(&mut document, b"")?.is_none());
+
+    let keys = dests.iter(&mut document).map(|it| it.map(|(key, _)| String::from_utf8(key).unwrap())).collect::<Result<Vec<_>>>()?;
+    assert_eq!(keys, ["apple", "banana", "cherry", "kiwi", "zebra", "lemon"]);
+
+    // A flat tree keeps its entries in the root
+    let scripts = document.get_name_tree("JavaScript")?.expect("scripts");
+    let script = scripts.lookup(&mut document, b"init")?.expect("init");
+    assert!(script.as_dict().is_some_and(|it| it.named_value_was("S", "JavaScript")));
+    assert_eq!(scripts.iter(&mut document).count(), 1);
+    assert!(document.get_name_tree("EmbeddedFiles")?.is_none());
+
+    let mut document = open_with_contents("name-tree-none", "4 0 R")?;
+    assert!(document.get_name_tree("Dests")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_number_tree() -> Result<()> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R /StructTreeRoot 4 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R /StructParents 12 >>",
+        "<< /Type /StructTreeRoot /ParentTree 5 0 R >>",
+        // The last kid points back at the root
+        "<< /Kids [6 0 R 7 0 R 8 0 R 5 0 R] >>",
+        "<< /Limits [0 10] /Nums [0 9 0 R 10 [9 0 R 9 0 R]] >>",
+        // Overlaps the limits of the previous kid
+        "<< /Limits [5 20] /Nums [7 10 0 R 12 11 0 R 15 99 0 R] >>",
+        "<< /Limits [30 40] /Nums [] >>",
+        "<< /Type /StructElem /S /P >>",
+        "<< /Type /StructElem /S /Figure >>",
+        "10 0 R",
+    ]
+    .map(String::from);
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects))?;
+    let tree = document.get_parent_tree()?.expect("parent tree");
+    let first = tree.lookup(&mut document, 0)?.expect("0");
+    assert!(first.as_dict().is_some_and(|it| it.named_value_was("S", "P")));
+    assert_eq!(tree.lookup(&mut document, 10)?.and_then(|it| it.into_array()).map(|it| it.len()), Some(2));
+    // Found in the second kid although the limits of the first one include it
+    let figure = tree.lookup(&mut document, 7)?.expect("7");
+    assert!(figure.as_dict().is_some_and(|it| it.named_value_was("S", "Figure")));
+    // The value is resolved through two references
+    let paragraph = tree.lookup(&mut document, 12)?.expect("12");
+    assert!(paragraph.as_dict().is_some_and(|it| it.named_value_was("S", "Figure")));
+    // A value referencing a missing object is absent
+    assert!(tree.lookup(&mut document, 15)?.is_none());
+    assert!(tree.lookup(&mut document, 5)?.is_none());
+    assert!(tree.lookup(&mut document, 35)?.is_none());
+    assert!(tree.lookup(&mut document, -1)?.is_none());
+
+    let mut keys = |range| tree.range_iter(&mut document, range).map(|it| it.map(|(key, _)| key)).collect::<Result<Vec<_>>>();
+    assert_eq!(keys(i64::MIN..i64::MAX)?, [0, 10, 7, 12]);
+    assert_eq!(keys(7..12)?, [10, 7]);
+    assert_eq!(keys(30..41)?, Vec::<i64>::new());
+
+    let mut document = open_with_contents("number-tree-none", "4 0 R")?;
+    assert!(document.get_parent_tree()?.is_none());
+    Ok(())
+}
+
+/// A sequence in memory counting its seeks, the tokenizer seeks once per object read.
+struct SeekCountingSequence {
+    inner: MemorySequence,
+    seeks: Arc<Mutex<usize>>,
+}
+
+impl Sequence for SeekCountingSequence {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.inner.read(buf)
+    }
+
+    fn read_line(&mut self) -> Result<Vec<u8>> {
+        self.inner.read_line()
+    }
+
+    fn read_line_str(&mut self) -> Result<String> {
+        self.inner.read_line_str()
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<u64> {
+        *self.seeks.lock().unwrap() += 1;
+        self.inner.seek(pos)
+    }
+
+    fn size(&self) -> Result<u64> {
+        self.inner.size()
+    }
+}
+
+#[test]
+fn test_count_pages_up_to() -> Result<()> {
+    // Opens the document, returning it with the counter of the seeks after opening
+    let open = |objects: &[String]| -> Result<(PDFDocument, Arc<Mutex<usize>>)> {
+        let seeks = Arc::new(Mutex::new(0));
+        let inner = MemorySequence::new(common::build_pdf(objects));
+        let document = PDFDocument::new(SeekCountingSequence { inner, seeks: seeks.clone() })?;
+        *seeks.lock().unwrap() = 0;
+        Ok((document, seeks))
+    };
+    let objects = common::page_tree_objects(10, 100, 1000);
+    let (mut document, seeks) = open(&objects)?;
+    assert_eq!(document.count_pages_up_to(50)?, CountResult::AtLeast(50));
+    // The root and its kids, which agree with the root /Count
+    assert_eq!(std::mem::take(&mut *seeks.lock().unwrap()), 11);
+    assert_eq!(document.count_pages_up_to(1000)?, CountResult::AtLeast(1000));
+    assert_eq!(std::mem::take(&mut *seeks.lock().unwrap()), 11);
+    assert_eq!(document.count_pages_up_to(1001)?, CountResult::Exact(1000));
+    assert_eq!(std::mem::take(&mut *seeks.lock().unwrap()), 11);
+    assert_eq!(document.count_pages_up_to(0)?, CountResult::AtLeast(0));
+
+    // The kids of a flat tree are sampled only up to the limit
+    let mut objects = vec!["<< /Type /Catalog /Pages 2 0 R >>".to_string()];
+    let kids = (3..1003).map(|page| format!("{} 0 R", page)).collect::<Vec<_>>();
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count 1000 >>", kids.join(" ")));
+    objects.extend((0..1000).map(|_| "<< /Type /Page /Parent 2 0 R >>".to_string()));
+    let (mut document, seeks) = open(&objects)?;
+    assert_eq!(document.count_pages_up_to(50)?, CountResult::AtLeast(50));
+    assert_eq!(std::mem::take(&mut *seeks.lock().unwrap()), 51);
+    assert_eq!(document.count_pages_up_to(1001)?, CountResult::Exact(1000));
+
+    // A root /Count the kids disagree with is walked instead of trusted
+    let objects = common::page_tree_objects(10, 100, 5000);
+    let mut document = PDFDocument::from_bytes_with_options(common::build_pdf(&objects), ParseOptions::lenient())?;
+    let count = document.count_pages_up_to(50)?;
+    assert_eq!((count.get_pages(), count.is_exact()), (50, false));
+    assert_eq!(document.count_pages_up_to(5000)?, CountResult::Exact(1000));
+
+    let mut document = open_with_contents("count-pages", "4 0 R")?;
+    assert_eq!(document.count_pages_up_to(10)?, CountResult::Exact(document.get_page_num()));
+    Ok(())
+}
+
+#[test]
+fn test_verify_unknown_keys() -> Result<()> {
+    let objects = |catalog: &str| {
+        [
+            format!("<< /Type /Catalog /Pages 2 0 R /PageMode /UseNone {} >>", catalog),
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /MediaBox [0 0 612 792] /Resources << /Font << /F1 5 0 R >> >> >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /ACME_Stamp (approved) /Rotate 90 /Contents 6 0 R /Annots [7 0 R] >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /UserUnit 2 /Annots [7 0 R << /Subtype /Text /Rect [0 0 1 1] /Open true >>] >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding /XFontHint 3 >>".to_string(),
+            common::stream("", "BT ET"),
+            "<< /Type /Annot /Subtype /Link /Rect [0 0 10 10] /Border [0 0 0] /Dest [3 0 R /Fit] >>".to_string(),
+        ]
+    };
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects("")))?;
+    assert_eq!(document.verify()?.findings_of(Category::UnknownKey).count(), 0);
+    let report = document.verify_with(&VerifyOptions::new().with_unknown_keys(true))?;
+    let findings = report.findings_of(Category::UnknownKey).collect::<Vec<_>>();
+    let messages = findings.iter().map(|it| it.get_message()).collect::<Vec<_>>();
+    assert_eq!(messages, [
+        "Page key /ACME_Stamp is not defined by PDF 1.4 (1 occurrence)",
+        "Page key /UserUnit is defined since PDF 1.6, the document declares 1.4 (1 occurrence)",
+        "Font key /XFontHint is not defined by PDF 1.4 (1 occurrence)",
+    ]);
+    assert!(findings.iter().all(|it| it.get_severity() == Severity::Info));
+    assert_eq!(findings.iter().map(|it| it.get_object()).collect::<Vec<_>>(), [Some((3, 0)), Some((4, 0)), Some((5, 0))]);
+
+    // A newer catalog /Version admits the key of the newer version
+    let mut document = PDFDocument::from_bytes(common::build_pdf(&objects("/Version /1.7 /ACME_Stamp 1")))?;
+    let report = document.verify_with(&VerifyOptions::new().with_unknown_keys(true))?;
+    let findings = report.findings_of(Category::UnknownKey).map(|it| it.get_message().to_string()).collect::<Vec<_>>();
+    assert_eq!(findings, [
+        "Catalog key /ACME_Stamp is not defined by PDF 1.7 (1 occurrence)",
+        "Page key /ACME_Stamp is not defined by PDF 1.7 (1 occurrence)",
+        "Font key /XFontHint is not defined by PDF 1.7 (1 occurrence)",
+    ]);
+    Ok(())
+}
+
+#[test]
+fn test_zero_pages() -> Result<()> {
+    let objects = |pages: &str| {
+        [
+            "<< /Type /Catalog /Pages 2 0 R /Names << /EmbeddedFiles 3 0 R >> >>".to_string(),
+            pages.to_string(),
+            "<< /Names [(data.csv) 4 0 R] >>".to_string(),
+            "<< /Type /Filespec /F (data.csv) >>".to_string(),
+        ]
+    };
+    for pages in ["<< /Type /Pages /Kids [] /Count 0 >>", "<< /Type /Pages /Count 0 >>"] {
+        for options in [ParseOptions::default(), ParseOptions::lenient()] {
+            let bytes = common::build_pdf(&objects(pages));
+            let mut document = PDFDocument::from_bytes_with_options(bytes, options)?;
+            assert_eq!(document.get_page_num(), 0);
+            assert!(document.get_page_ids().is_empty());
+            assert_eq!(document.pages().count(), 0);
+            assert!(document.get_page(0).is_none());
+            assert!(document.get_page_tree().get_root_node().is_ok());
+            assert_eq!(document.count_pages_up_to(10)?, CountResult::Exact(0));
+
+            assert!(document.pages_text(&TextExtractionOptions::new())?.is_empty());
+            let report = document.extract_text_report(&TextExtractionOptions::new());
+            assert_eq!(report.get_text(), "");
+            assert!(report.get_pages().is_empty());
+            assert_eq!(document.page_label(0), None);
+            assert!(document.operator_histogram(&HistogramOptions::new())?.is_empty());
+            assert!(document.resource_usage()?.get_pages().is_empty());
+            assert!(document.image_inventory()?.get_images().is_empty());
+            assert!(document.font_preflight()?.get_fonts().is_empty());
+            assert_eq!(document.stats().get_pages(), 0);
+
+            let report = document.verify()?;
+            assert!(report.get_findings().iter().all(|it| it.get_severity() == Severity::Info));
+            let findings = report.findings_of(Category::PageCount).collect::<Vec<_>>();
+            assert_eq!(findings.len(), 1);
+            assert_eq!(findings[0].get_message(), "Page tree has no pages");
+            assert_eq!(findings[0].get_object(), Some((2, 0)));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_comments() -> Result<()> {
+    let objects = [
+        "% The catalog\n<< /Type /Catalog % comment inside a dictionary\n/Pages 2 0 R%no space before\n>>",
+        "<< /Type /Pages /Kids [3 0 R % first page\n4 0 R] /Count 2 >>",
+        "<< /Type /Page /Parent 2 0 R >>",
+        "<< /Type /Page /Parent 2 0 R /Contents 6 0 R >>",
+        "<< /Title (100% sure) /Producer%(not a string)\n(hand) >>",
+        "<< /Length 22 >>\nstream\n% not a comment\nBT ET\nendstream",
+    ];
+    let mut buf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n% A comment after the header\n".to_vec();
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        buf.extend_from_slice(b"% Between objects\n");
+        offsets.push(buf.len());
+        buf.extend_from_slice(format!("{} 0 obj % header\n{}\nendobj\n", index + 1, object).as_bytes());
+    }
+    let xref_offset = buf.len();
+    buf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        buf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    buf.extend_from_slice(b"% Before the trailer\ntrailer % the trailer\n<< /Size 7 /Root 1 0 R %root\n/Info 5 0 R >>\n");
+    buf.extend_from_slice(format!("startxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+
+    let mut document = PDFDocument::from_bytes(buf)?;
+    assert!(document.get_warnings().is_empty());
+    assert_eq!(document.get_page_num(), 2);
+    let describe = document.get_describe().unwrap();
+    assert_eq!(describe.get_title(), Some("100% sure"));
+    assert_eq!(describe.get_producer(), Some("hand"));
+    // Stream data is never a comment
+    match document.read_object_with_ref((6, 0))? {
+        Some(PDFObject::IndirectObject(6, 0, object)) => {
+            assert_eq!(object.as_stream().unwrap().as_slice(), b"% not a comment\nBT ET\n");
+        }
+        _ => unreachable!(),
+    }
+    assert!(document.pages_text(&TextExtractionOptions::new())?.iter().all(|it| it.is_empty()));
+    Ok(())
+}
+
+#[test]
+fn test_custom_encoding() -> Result<()> {
+    let win_ansi = Encoding::predefined("WinAnsiEncoding").unwrap();
+    assert_eq!(win_ansi, PreDefinedEncoding::WinAnsi.get_encoding());
+    assert_eq!(win_ansi.lookup(0x80), Some('€'));
+    assert_eq!(win_ansi.lookup(b'A'), Some('A'));
+    assert_eq!(PreDefinedEncoding::Standard.get_encoding().lookup(0x80), None);
+    assert_eq!(PreDefinedEncoding::PDFDoc.get_encoding().lookup(0xA0), Some('€'));
+    assert!(Encoding::predefined("IdentityEncoding").is_none());
+    assert_eq!(PreDefinedEncoding::from_name("MacRomanEncoding").map(|it| it.get_name()), Some("MacRomanEncoding"));
+
+    let mut encoding = win_ansi.clone();
+    let differences = pdf_rs::parse_object(b"[65 /Euro /uni00E9]")?.into_array().unwrap();
+    encoding.apply_differences(&differences);
+    assert_eq!((encoding.lookup(b'A'), encoding.lookup(b'B'), encoding.lookup(b'C')), (Some('€'), Some('é'), Some('C')));
+
+    // A Type3 font naming its glyphs freely, which no encoding table knows
+    let path = common::write_pdf("custom-encoding", &common::catalog_with_pages(
+        &["/Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R"],
+        vec![
+            "<< /Type /Font /Subtype /Type3 /FontBBox [0 0 1 1] /FontMatrix [0.001 0 0 0.001 0 0] \
+                /Encoding << /Differences [1 /g1 /g2 /g3] >> /CharProcs << >> >>".to_string(),
+            common::stream("", "BT /F1 12 Tf (\\001\\002\\003\\002) Tj ET"),
+        ],
+    ));
+    let mut document = PDFDocument::open(path)?;
+    let page = Page::new(document.get_page_ids()[0]);
+    assert_eq!(page.text(&mut document)?, "");
+
+    document.set_font_encoding((4, 0), Encoding::from_pairs([(1, 'p'), (2, 'd'), (3, 'f')]));
+    assert_eq!(document.get_font_encoding((4, 0)).and_then(|it| it.lookup(1)), Some('p'));
+    assert_eq!(page.text(&mut document)?, "pdfd");
+    assert!(document.remove_font_encoding((4, 0)).is_some());
+    assert!(document.get_font_encoding((4, 0)).is_none());
+    assert_eq!(page.text(&mut document)?, "");
+    Ok(())
+}
+
+#[test]
+fn test_leading_garbage() -> Result<()> {
+    let objects = common::catalog_with_pages(&["/Rotate 90"], vec![
+        "<< /Title (Prefixed) >>".to_string(),
+    ]);
+    let original = common::build_pdf_with_trailer(&objects, "/Size 5 /Root 1 0 R /Info 4 0 R");
+    let junk = b"HTTP/1.1 200 OK\r\nContent-Type: application/pdf\r\n\r\n";
+    let bytes = [junk.as_slice(), &original].concat();
+    let path = common::write_bytes("leading-garbage", &bytes);
+    for mut document in [PDFDocument::open(path.clone())?, PDFDocument::from_bytes(bytes.clone())?] {
+        assert_eq!(document.get_header_offset(), junk.len() as u64);
+        assert_eq!(document.get_version().to_string(), "1.4");
+        assert_eq!(document.get_page_num(), 1);
+        assert_eq!(document.get_describe().and_then(|it| it.get_title()), Some("Prefixed"));
+        assert!(matches!(document.read_object_with_ref((3, 0))?, Some(PDFObject::IndirectObject(3, 0, _))));
+        let messages = document.get_warnings().iter().map(|it| it.get_message()).collect::<Vec<_>>();
+        assert_eq!(messages, [format!("{} bytes before the %PDF- header are ignored", junk.len())]);
+        // Saving keeps the bytes before the header
+        let mut buf = Vec::new();
+        document.save_incremental(&mut buf)?;
+        assert_eq!(buf, bytes);
+    }
+    assert_eq!(PDFDocument::from_bytes(original)?.get_header_offset(), 0);
+
+    // Objects are found by scanning relative to the header too
+    let mut text = String::from_utf8_lossy(&bytes).into_owned();
+    let start = text.rfind("startxref\n").unwrap() + "startxref\n".len();
+    text.replace_range(start.., "999999\n%%EOF\n");
+    let (mut partial, _) = PDFDocument::new_partial(MemorySequence::new(text.into_bytes()), ParseOptions::lenient());
+    assert!(partial.is_available(Facet::Header));
+    let document = partial.get_document_mut();
+    assert!(matches!(document.read_object_with_ref((3, 0))?, Some(PDFObject::IndirectObject(3, 0, _))));
+
+    // The header must start within the first 1024 bytes
+    let bytes = [vec![b' '; 1025], common::build_pdf(&objects)].concat();
+    assert!(matches!(PDFDocument::from_bytes(bytes), Err(PDFError::InvalidPDFDocument)));
+    Ok(())
+}
+
+#[test]
+fn test_incremental_outline() -> Result<()> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R /PageLayout /SinglePage >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R >>".to_string(),
+    ];
+    let original = common::build_pdf_with_trailer(&objects, "/Size 5 /Root 1 0 R");
+    let mut document = PDFDocument::open(common::write_bytes("outline-original", &original))?;
+    assert!(document.get_outline().is_none());
+    let outline = OutlineBuilder::new()
+        .item(OutlineItem::new("Cover", 0))
+        .item(OutlineItem::new("Contents", 1));
+    document.set_outline(outline);
+    let mut buf = Vec::new();
+    document.save_incremental(&mut buf)?;
+    assert!(buf.len() > original.len() && buf.starts_with(&original));
+
+    let document = PDFDocument::open(common::write_bytes("outline-updated", &buf))?;
+    assert_eq!(document.xref_sections(), 2);
+    let changes = &document.revision_changes()[1];
+    assert_eq!(changes.get_changed(), [(1, 0)]);
+    assert_eq!(changes.get_added(), [(5, 0), (6, 0), (7, 0)]);
+    assert_eq!(document.object_revision((3, 0)), Some(0));
+    assert!(document.get_catalog().get_attrs().contain("PageLayout"));
+    let tree = document.get_outline().expect("outline");
+    let root = tree.get_root_node().unwrap();
+    assert_eq!(root.get_count(), 2);
+    let first = tree.get_node(root.get_first_id().unwrap()).unwrap();
+    let last = tree.get_node(first.get_next_id().unwrap()).unwrap();
+    assert_eq!((first.get_title(), last.get_title()), (Some("Cover"), Some("Contents")));
+    let page_ref = |node: &pdf_rs::catalog::OutlineNode| {
+        node.get_dest().and_then(|it| it.as_array()).and_then(|it| it.first()).and_then(|it| it.as_object_ref())
+    };
+    assert_eq!((page_ref(first), page_ref(last)), (Some((3, 0)), Some((4, 0))));
+
+    // An item pointing past the last page is rejected before anything is appended
+    let mut document = PDFDocument::open(common::write_bytes("outline-original", &original))?;
+    document.set_outline(OutlineBuilder::new().item(OutlineItem::new("Missing", 2)));
+    let mut buf = Vec::new();
+    assert!(matches!(document.save_incremental(&mut buf), Err(PDFError::PageNotFound(_))));
+    assert!(buf.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_startxref_search() -> Result<()> {
+    let objects = common::catalog_with_pages(&[""], vec![]);
+    let original = common::build_pdf(&objects);
+    // Data appended after %%EOF pushes startxref out of the last kilobyte, a mention of the
+    // keyword without an offset is skipped
+    let tail = format!("% {}\n% startxref\n", "x".repeat(200 * 1024));
+    let bytes = [original.clone(), tail.into_bytes()].concat();
+    assert_eq!(PDFDocument::from_bytes(bytes.clone())?.get_page_num(), 1);
+    let options = ParseOptions::default().with_max_startxref_scan(64 * 1024);
+    assert!(matches!(PDFDocument::from_bytes_with_options(bytes, options), Err(PDFError::InvalidPDFDocument)));
+
+    // The keyword straddles the boundary of the first window
+    let keyword = original.windows(9).rposition(|it| it == b"startxref").unwrap();
+    let padding = 1024 + 4 - (original.len() - keyword);
+    let bytes = [original.clone(), vec![b'\n'; padding]].concat();
+    assert_eq!(bytes.len() - 1024, keyword + 4);
+    assert_eq!(PDFDocument::from_bytes(bytes)?.get_page_num(), 1);
+
+    // Without an offset after the keyword the file can't be opened
+    let mut bytes = original;
+    bytes.truncate(keyword + 9);
+    bytes.extend_from_slice(b"\n%%EOF\n");
+    assert!(matches!(PDFDocument::from_bytes(bytes), Err(PDFError::InvalidPDFDocument)));
+    Ok(())
+}
+
+#[test]
+fn test_xref_subsections() -> Result<()> {
+    let objects = common::catalog_with_pages(&[""], vec![
+        "<< /Title (Old) >>".to_string(),
+        "<< /Producer (Old) >>".to_string(),
+    ]);
+    let original = common::build_pdf_with_trailer(&objects, "/Size 6 /Root 1 0 R /Info 4 0 R");
+    // Objects 1, 3 to 4 and 6 make three subsections
+    let updated = common::append_update(original, &[
+        ((1, 0), "<< /Type /Catalog /Pages 2 0 R /PageLayout /OneColumn >>".to_string()),
+        ((3, 0), "<< /Type /Page /Parent 2 0 R /Rotate 90 >>".to_string()),
+        ((4, 0), "<< /Title (New) >>".to_string()),
+        ((6, 0), "<< /Type /Annot /Subtype /Text /Rect [0 0 1 1] >>".to_string()),
+    ], "/Size 7 /Root 1 0 R /Info 4 0 R");
+    assert!(String::from_utf8_lossy(&updated).contains("xref\n1 1\n"));
+    let mut document = PDFDocument::from_bytes(updated)?;
+    assert_eq!(document.xref_sections(), 2);
+    assert_eq!(document.revision_changes()[1].get_changed(), [(1, 0), (3, 0), (4, 0)]);
+    assert_eq!(document.revision_changes()[1].get_added(), [(6, 0)]);
+    assert!(document.get_catalog().get_attrs().contain("PageLayout"));
+    assert_eq!(document.get_describe().and_then(|it| it.get_title()), Some("New"));
+    assert_eq!(document.object_revision((5, 0)), Some(0));
+    assert!(matches!(document.read_object_with_ref((6, 0))?, Some(PDFObject::IndirectObject(6, 0, _))));
+
+    // An incremental update changing the catalog, the information and the outline at once
+    let mut buf = Vec::new();
+    let describe = document.get_describe().unwrap().clone().with_title("Newer");
+    document.set_describe(describe);
+    document.set_outline(OutlineBuilder::new().item(OutlineItem::new("Only page", 0)));
+    document.save_incremental(&mut buf)?;
+    let document = PDFDocument::from_bytes(buf)?;
+    assert_eq!(document.xref_sections(), 3);
+    assert_eq!(document.revision_changes()[2].get_changed(), [(1, 0), (4, 0)]);
+    assert_eq!(document.revision_changes()[2].get_added(), [(7, 0), (8, 0)]);
+    assert_eq!(document.get_describe().and_then(|it| it.get_title()), Some("Newer"));
+    let tree = document.get_outline().expect("outline");
+    let first = tree.get_node(tree.get_root_node().unwrap().get_first_id().unwrap()).unwrap();
+    assert_eq!(first.get_title(), Some("Only page"));
+    Ok(())
+}
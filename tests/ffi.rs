@@ -0,0 +1,58 @@
+use pdf_rs::document::PDFDocument;
+use pdf_rs::error::Result;
+use pdf_rs::ffi::{PDF_ERR_NULL_ARGUMENT, PDF_ERR_PAGE_NOT_FOUND};
+use pdf_rs::page::Page;
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+/// The opaque document handle as C sees it.
+#[repr(C)]
+struct PdfDoc {
+    _private: [u8; 0],
+}
+
+// Called through the C ABI like a C application would
+unsafe extern "C" {
+    fn pdf_open(path: *const c_char) -> *mut PdfDoc;
+    fn pdf_page_count(doc: *const PdfDoc) -> i64;
+    fn pdf_extract_text(doc: *mut PdfDoc, page: usize, out_buf: *mut c_char, out_len: usize) -> i64;
+    fn pdf_last_error_message() -> *const c_char;
+    fn pdf_close(doc: *mut PdfDoc);
+}
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(pdf_last_error_message()) }.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_ffi_text() -> Result<()> {
+    let path = "document/pdfreference1.0.pdf";
+    let mut document = PDFDocument::open(path.into())?;
+    let expected = Page::new(document.get_page_ids()[1]).text(&mut document)?;
+
+    let c_path = CString::new(path).unwrap();
+    let doc = unsafe { pdf_open(c_path.as_ptr()) };
+    assert!(!doc.is_null());
+    assert_eq!(unsafe { pdf_page_count(doc) }, 230);
+
+    // Query the length, then extract into a buffer with room for the NUL
+    let len = unsafe { pdf_extract_text(doc, 1, ptr::null_mut(), 0) };
+    assert_eq!(len as usize, expected.len());
+    let mut small = vec![1 as c_char; len as usize];
+    assert_eq!(unsafe { pdf_extract_text(doc, 1, small.as_mut_ptr(), small.len()) }, len);
+    assert!(small.iter().all(|it| *it == 1));
+    let mut buf = vec![1 as c_char; len as usize + 1];
+    assert_eq!(unsafe { pdf_extract_text(doc, 1, buf.as_mut_ptr(), buf.len()) }, len);
+    let text = unsafe { CStr::from_ptr(buf.as_ptr()) }.to_str().unwrap();
+    assert_eq!(text, expected);
+
+    assert_eq!(unsafe { pdf_extract_text(doc, 230, ptr::null_mut(), 0) }, PDF_ERR_PAGE_NOT_FOUND);
+    assert_eq!(last_error(), "Page not found:230");
+    unsafe { pdf_close(doc) };
+
+    assert_eq!(unsafe { pdf_page_count(ptr::null()) }, PDF_ERR_NULL_ARGUMENT);
+    let missing = CString::new("document/missing.pdf").unwrap();
+    assert!(unsafe { pdf_open(missing.as_ptr()) }.is_null());
+    assert!(last_error().starts_with("IO error:"));
+    Ok(())
+}
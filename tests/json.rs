@@ -0,0 +1,66 @@
+mod common;
+
+use pdf_rs::document::PDFDocument;
+use pdf_rs::error::Result;
+use pdf_rs::json::SCHEMA_VERSION;
+use serde_json::Value;
+
+/// A document whose trailer `/Size` is too large and whose page refers to a missing object.
+fn open_broken() -> Result<PDFDocument> {
+    let objects = common::catalog_with_pages(&["/MediaBox [0 0 612 792] /Contents 4 0 R /Thumb 9 0 R"], vec![
+        common::stream("/Filter /FlateDecode", "xyz"),
+    ]);
+    PDFDocument::from_bytes(common::build_pdf_with_trailer(&objects, "/Size 9 /Root 1 0 R"))
+}
+
+#[test]
+fn test_report_json() -> Result<()> {
+    let mut document = open_broken()?;
+    let json = document.verify()?.to_json();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"schema_version":1,"findings":["#,
+            r#"{"code":"trailer_size","severity":"warning","message":"Trailer /Size 9 but the highest object number is 4","object":null,"offset":null},"#,
+            r#"{"code":"dangling_reference","severity":"warning","message":"Reference 9 0 R points to no object","object":{"number":3,"generation":0},"offset":null}"#,
+            "]}"
+        )
+    );
+
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["schema_version"].as_u64(), Some(SCHEMA_VERSION as u64));
+    let findings = value["findings"].as_array().expect("findings");
+    assert_eq!(findings.len(), 2);
+    for finding in findings {
+        assert!(finding["code"].is_string());
+        assert!(finding["severity"].is_string());
+        assert!(finding["message"].is_string());
+        assert!(finding["object"].is_null() || finding["object"]["number"].is_u64() && finding["object"]["generation"].is_u64());
+        assert!(finding["offset"].is_null() || finding["offset"].is_u64());
+    }
+
+    // A clean document has an empty list
+    let mut document = PDFDocument::open("document/pdfreference1.0.pdf".into())?;
+    let value: Value = serde_json::from_str(&document.verify()?.to_json()).unwrap();
+    assert_eq!(value["findings"].as_array().map(|it| it.len()), Some(0));
+    Ok(())
+}
+
+#[test]
+fn test_stats_json() -> Result<()> {
+    let mut document = open_broken()?;
+    let json = document.stats().to_json();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"schema_version":1,"dictionaries":3,"streams":1,"arrays":0,"numbers":0,"strings":0,"names":0,"booleans":0,"nulls":0,"#,
+            r#""unreadable":0,"filters":{"FlateDecode":1},"stream_bytes":3,"pages":1,"fonts":0,"images":0,"in_use_entries":4,"free_entries":1}"#
+        )
+    );
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["schema_version"].as_u64(), Some(SCHEMA_VERSION as u64));
+    let object = value.as_object().expect("object");
+    assert!(object.iter().filter(|(key, _)| *key != "filters").all(|(_, value)| value.is_u64()));
+    assert!(value["filters"].as_object().is_some_and(|it| it.values().all(Value::is_u64)));
+    Ok(())
+}
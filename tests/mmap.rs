@@ -0,0 +1,31 @@
+use pdf_rs::document::PDFDocument;
+use pdf_rs::error::{PDFError, Result};
+use pdf_rs::options::ParseOptions;
+use pdf_rs::page::Page;
+use pdf_rs::sequence::{FileSequence, MmapSequence, Sequence};
+use std::fs::File;
+use std::path::PathBuf;
+
+#[test]
+fn test_mmap_sequence() -> Result<()> {
+    let path = PathBuf::from("document/pdfreference1.0.pdf");
+    let mut file = PDFDocument::new(FileSequence::new(File::open(&path)?))?;
+    // SAFETY: the fixture isn't modified by the tests
+    let mut mapped = unsafe { PDFDocument::open_mmap_with_options(path.clone(), ParseOptions::default())? };
+    assert_eq!(mapped.get_page_num(), file.get_page_num());
+    assert_eq!(mapped.get_xref_slice().len(), file.get_xref_slice().len());
+    // Stream data is copied straight from the mapping, it must equal what the file yields
+    for index in 0..file.get_xref_slice().len() {
+        assert!(mapped.read_object(index)? == file.read_object(index)?, "object at index {}", index);
+    }
+    let page_id = file.get_page_ids()[1];
+    assert_eq!(Page::new(page_id).text(&mut mapped)?, Page::new(page_id).text(&mut file)?);
+
+    let mut sequence = unsafe { MmapSequence::open(&path)? };
+    let size = sequence.size()?;
+    assert_eq!(sequence.read_line_str()?, "%PDF-1.3");
+    assert_eq!(sequence.seek(size)?, size);
+    assert!(matches!(sequence.seek(size + 1), Err(PDFError::SeekExceedError)));
+    assert!(unsafe { MmapSequence::open("document/missing.pdf") }.is_err());
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use pdf_rs::document::PDFDocument;
+use pdf_rs::error::Result;
+use pdf_rs::text::TextExtractionOptions;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_async_parse_off_executor() -> Result<()> {
+    // The runtime has a single thread, the timer only ticks if the parsing runs elsewhere
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let timer = tokio::spawn({
+        let ticks = ticks.clone();
+        async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                ticks.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    });
+    let document = PDFDocument::open_async("document/pdfreference1.0.pdf".into()).await?;
+    let (document, report) = document.verify_async().await?;
+    assert!(report.get_findings().is_empty());
+    let (document, text) = document.pages_text_async(TextExtractionOptions::default()).await?;
+    assert_eq!(text.len(), document.get_page_num());
+    assert!(text[1].contains("Library of Congress"));
+    assert!(ticks.load(Ordering::Relaxed) > 0);
+    timer.abort();
+
+    let bytes = std::fs::read("document/pdfreference1.0.pdf")?;
+    let document = PDFDocument::from_bytes_async(bytes).await?;
+    assert_eq!(document.get_page_num(), 230);
+    assert!(PDFDocument::from_bytes_async(b"not a pdf".to_vec()).await.is_err());
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use pdf_rs::error::Result;
+use std::path::PathBuf;
+
+#[path = "../examples/pdfinfo.rs"]
+#[allow(dead_code)]
+mod pdfinfo;
+
+#[test]
+fn test_pdfinfo() -> Result<()> {
+    let mut out = Vec::new();
+    pdfinfo::run(PathBuf::from("document/pdfreference1.0.pdf"), true, &mut out)?;
+    let text = String::from_utf8(out)?;
+    let lines = text.lines().collect::<Vec<_>>();
+    for expected in [
+        "PDF version:    1.3",
+        "Pages:          230",
+        "Producer:       Acrobat Distiller 4.0 for Windows",
+        "Encrypted:      no",
+        "Page 1 size:    612 x 792 pts",
+        "Outline:",
+        "  CHAPTER 1",
+        "Fonts:",
+        "  Helvetica-Bold",
+    ] {
+        assert!(lines.contains(&expected), "missing line '{}' in:\n{}", expected, text);
+    }
+    let mut xref = lines.into_iter().skip_while(|it| *it != "xref");
+    assert_eq!(xref.next(), Some("xref"));
+    assert!(xref.next().is_some_and(|it| it.starts_with("0 ")));
+    assert_eq!(xref.next(), Some("0000000000 65535 f"));
+    Ok(())
+}
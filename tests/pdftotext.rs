@@ -0,0 +1,57 @@
+use pdf_rs::error::Result;
+
+#[path = "../examples/pdftotext.rs"]
+#[allow(dead_code)]
+mod pdftotext;
+
+/// Runs the example with the given arguments and returns its stdout and stderr.
+fn run(args: &[&str]) -> Result<(String, String)> {
+    let args = pdftotext::parse_args(args.iter().map(|it| it.to_string())).unwrap();
+    let mut out = Vec::new();
+    let mut err = Vec::new();
+    pdftotext::run(&args, &mut out, &mut err)?;
+    Ok((String::from_utf8(out)?, String::from_utf8(err)?))
+}
+
+#[test]
+fn test_pdftotext_page_range() -> Result<()> {
+    let (text, err) = run(&["--pages", "2,4-5", "document/pdfreference1.0.pdf"])?;
+    assert!(err.is_empty(), "unexpected errors: {}", err);
+    let pages = text.split('\x0c').filter(|it| !it.is_empty()).collect::<Vec<_>>();
+    assert_eq!(pages.len(), 3);
+    assert!(pages[0].contains("Library of Congress Cataloging-in-Publication Data"));
+    assert!(pages[1].contains("3.7 Relationships among coordinate systems 18"));
+    assert!(pages[2].contains("7.10 Type 3 font operators 112"));
+    // Pages 1 and 3 are not selected
+    assert!(!text.contains("Tim Bienz and Richard Cohn"));
+    assert!(!text.contains("1.1 About this book"));
+    Ok(())
+}
+
+#[test]
+fn test_pdftotext_layout() -> Result<()> {
+    let (text, _) = run(&["--layout", "--pages", "4", "document/pdfreference1.0.pdf"])?;
+    let line = text.lines().find(|it| it.contains("Relationships among coordinate systems")).unwrap();
+    // The section number and the title are placed in separate columns
+    assert!(line.trim_start().starts_with("3.7  Relationships"), "unexpected line '{}'", line);
+    Ok(())
+}
+
+#[test]
+fn test_pdftotext_args() {
+    assert!(pdftotext::parse_args(["--pages".to_string()]).is_none());
+    assert!(pdftotext::parse_args(["--unknown".to_string(), "a.pdf".to_string()]).is_none());
+    assert!(pdftotext::parse_args(Vec::new()).is_none());
+}
+
+#[test]
+fn test_parse_page_ranges() -> Result<()> {
+    use pdf_rs::helper::parse_page_ranges;
+    assert_eq!(parse_page_ranges("2-5,8", 10)?, vec![1, 2, 3, 4, 7]);
+    assert_eq!(parse_page_ranges("9-", 10)?, vec![8, 9]);
+    assert_eq!(parse_page_ranges("-2", 10)?, vec![0, 1]);
+    for invalid in ["0", "3-2", "11", "a", "1,,2"] {
+        assert!(parse_page_ranges(invalid, 10).is_err(), "accepted '{}'", invalid);
+    }
+    Ok(())
+}
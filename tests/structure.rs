@@ -1,5 +1,8 @@
 use std::str::FromStr;
-use pdf_rs::error::Result;
+use pdf_rs::error::{PDFError, Result};
+use pdf_rs::objects::{ArrayBuilder, DictBuilder, PDFObject, PDFString, Stream};
+use pdf_rs::writer;
+use pdf_rs::{ObjectReader, parse_object};
 use pdf_rs::vpdf::PDFVersion;
 mod common;
 
@@ -16,3 +19,284 @@ fn vpdf_test() -> Result<()> {
     assert_eq!(PDFVersion::V2_0, PDFVersion::from_str("2.0")?);
     Ok(())
 }
+
+#[test]
+fn test_parse_object() -> Result<()> {
+    let object = parse_object(b"<< /Type /Page /Kids [3 0 R] /Title (a \\(b\\)) >>")?;
+    let dict = object.as_dict().unwrap();
+    assert_eq!(dict.get_named_value("Type"), Some("Page"));
+    assert_eq!(dict.get_array_value("Kids").unwrap()[0].as_object_ref(), Some((3, 0)));
+    assert_eq!(parse_object(b"12 0 obj\n42\nendobj")?.as_indirect_object().map(|it| (it.0, it.1)), Some((12, 0)));
+    assert!(parse_object(b"42")?.is_number());
+    // Names starting like a number are names
+    let object = parse_object(b"<< /Version /1.7 /A /-2x /B /.5 >>")?;
+    let dict = object.as_dict().unwrap();
+    let names = ["Version", "A", "B"].map(|key| dict.get_named_value(key).map(|it| it.to_string()));
+    assert_eq!(names, [Some("1.7".to_string()), Some("-2x".to_string()), Some(".5".to_string())]);
+    // Exactly one object is parsed
+    assert!(parse_object(b"(a) (b)").is_err());
+    assert!(parse_object(b"").is_err());
+    // Deep but bounded nesting is fine
+    let nested = format!("{}1{}", "[<</A ".repeat(128), ">>]".repeat(128));
+    assert!(parse_object(nested.as_bytes())?.is_array());
+    Ok(())
+}
+
+#[test]
+fn test_literal_strings() -> Result<()> {
+    let bytes = |input: &[u8]| -> Result<Vec<u8>> {
+        let object = parse_object(input)?;
+        Ok(object.as_string().map(|it| it.as_bytes().to_vec()).unwrap_or_default())
+    };
+    assert_eq!(bytes(b"()")?, b"");
+    // Escaped parentheses don't close the string
+    assert_eq!(bytes(b"(a\\(b\\)c)")?, b"a(b)c");
+    assert_eq!(bytes(b"(a\\(b)")?, b"a(b");
+    // Balanced parentheses need no escape, at any depth
+    assert_eq!(bytes(b"(a(b)c)")?, b"a(b)c");
+    assert_eq!(bytes(b"(a(b(c))d)")?, b"a(b(c))d");
+    assert_eq!(bytes(b"(\\n\\r\\t\\b\\f\\(\\)\\\\)")?, b"\n\r\t\x08\x0C()\\");
+    // Unknown escapes drop the backslash
+    assert_eq!(bytes(b"(\\q)")?, b"q");
+    // Octal escapes take one to three digits, overflow keeps the low byte
+    assert_eq!(bytes(b"(\\101\\53\\7)")?, b"A+\x07");
+    assert_eq!(bytes(b"(\\1234)")?, b"S4");
+    assert_eq!(bytes(b"(\\08)")?, b"\x008");
+    assert_eq!(bytes(b"(\\777\\400)")?, b"\xFF\x00");
+    // An escaped end-of-line continues the line, a bare one becomes a line feed
+    assert_eq!(bytes(b"(ab\\\ncd\\\r\nef\\\rgh)")?, b"abcdefgh");
+    assert_eq!(bytes(b"(a\r\nb\rc\nd)")?, b"a\nb\nc\nd");
+    // A string ending in an escaped backslash is closed, an escaped parenthesis isn't
+    assert_eq!(bytes(b"(a\\\\)")?, b"a\\");
+    assert!(parse_object(b"(a\\)").is_err());
+    assert!(parse_object(b"(a(b)").is_err());
+    assert!(parse_object(b"(a\\").is_err());
+
+    // Every byte survives writing and parsing again
+    let all = (0..=255u8).collect::<Vec<_>>();
+    let object = PDFObject::String(PDFString::literal(all.clone()));
+    assert_eq!(bytes(&writer::to_bytes(&object))?, all);
+    Ok(())
+}
+
+#[test]
+fn test_hex_strings() -> Result<()> {
+    let bytes = |input: &[u8]| -> Result<Vec<u8>> {
+        let object = parse_object(input)?;
+        Ok(object.as_string().map(|it| it.as_bytes().to_vec()).unwrap_or_default())
+    };
+    assert_eq!(bytes(b"<>")?, b"");
+    assert_eq!(bytes(b"<4142cD>")?, b"AB\xCD");
+    // White-space, NUL included, is ignored and an odd last digit is padded with 0
+    assert_eq!(bytes(b"<4 1\r\n4\t2\x0C4\x003>")?, b"ABC");
+    assert_eq!(bytes(b"<417>")?, b"Ap");
+    assert_eq!(bytes(b"< 7 >")?, b"p");
+    // Hex strings have no escapes
+    let error = parse_object(b"<41\\42>").err().map(|it| it.to_string());
+    assert_eq!(error.as_deref(), Some("Hexadecimal string at offset 1: Invalid hexadecimal digit '\\\\'"));
+    assert!(parse_object(b"<4G>").is_err());
+    assert!(parse_object(b"<41").is_err());
+    assert!(parse_object(b"<41)>").is_err());
+
+    // `<` starts a hex string and `<<` a dictionary wherever they appear
+    let object = parse_object(b"<</A<41>/B<>/C<</D<42>>>/E[<43><44>]>>")?;
+    let dict = object.as_dict().unwrap();
+    let string = |key: &str| dict.get(key).and_then(|it| it.as_string()).map(|it| it.as_bytes().to_vec());
+    assert_eq!(string("A").as_deref(), Some(&b"A"[..]));
+    assert_eq!(string("B").as_deref(), Some(&b""[..]));
+    let inner = dict.get("C").and_then(|it| it.as_dict()).unwrap();
+    assert!(inner.get("D") == Some(&PDFObject::String(PDFString::hexadecimal(b"B".to_vec()))));
+    assert_eq!(dict.get_array_value("E").map(|it| it.len()), Some(2));
+    assert!(parse_object(b"[<41><<>>]")?.as_array().is_some_and(|it| it[1].as_dict().is_some()));
+    Ok(())
+}
+
+#[test]
+fn test_parse_object_regressions() -> Result<()> {
+    // Inputs which used to panic or overflow the stack must fail cleanly
+    for entry in std::fs::read_dir("fuzz/regressions/parse_object")? {
+        let path = entry?.path();
+        let bytes = std::fs::read(&path)?;
+        assert!(parse_object(&bytes).is_err(), "{} was accepted", path.display());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_object_ref_lookahead() -> Result<()> {
+    let numbers = |values: &[u64]| values.iter().fold(ArrayBuilder::new(), |it, value| it.push(*value)).build();
+    assert!(parse_object(b"[1 2 3]")? == numbers(&[1, 2, 3]));
+    assert!(parse_object(b"[1 2 3 4 5]")? == numbers(&[1, 2, 3, 4, 5]));
+    assert!(parse_object(b"[1 2 R]")? == ArrayBuilder::new().reference((1, 2)).build());
+    // A plain number before a reference must not swallow its object number
+    let mixed = ArrayBuilder::new().push(0u64).reference((1006, 0)).push(5u64).build();
+    assert!(parse_object(b"[0 1006 0 R 5]")? == mixed);
+    let mixed = ArrayBuilder::new().push(1u64).push(2u64).reference((3, 4)).build();
+    assert!(parse_object(b"[1 2 3 4 R]")? == mixed);
+    let (obj_num, gen_num, value) = parse_object(b"7 0 obj\n[7 0]\nendobj")?.into_indirect().unwrap();
+    assert_eq!((obj_num, gen_num), (7, 0));
+    assert!(value == numbers(&[7, 0]));
+    let dict = DictBuilder::new().entry("A", 5u64).entry("B", 6u64).reference("Next", (7, 0)).build();
+    assert!(parse_object(b"<< /A 5 /B 6 /Next 7 0 R >>")? == PDFObject::Dict(dict));
+    Ok(())
+}
+
+#[test]
+fn test_object_reader() -> Result<()> {
+    let mut reader = ObjectReader::from_bytes(b"<< /Type /Font /Subtype /Type1 >><</Type /XObject /Length 3 0 R>>\n");
+    let first = reader.next_object()?.and_then(PDFObject::into_dict).unwrap();
+    assert_eq!(first.get_named_value("Subtype"), Some("Type1"));
+    let second = reader.next_object()?.and_then(PDFObject::into_dict).unwrap();
+    assert_eq!(second.get_named_value("Type"), Some("XObject"));
+    assert_eq!(second.get("Length").and_then(|it| it.as_object_ref()), Some((3, 0)));
+    assert!(reader.next_object()?.is_none());
+    // A trailing number is not mistaken for the start of a reference
+    let mut reader = ObjectReader::from_bytes(b"12 0 13 45");
+    let mut numbers = Vec::new();
+    while let Some(object) = reader.next_object()? {
+        numbers.push(object.as_number().map(|it| it.as_f64()));
+    }
+    assert_eq!(numbers, [Some(12.0), Some(0.0), Some(13.0), Some(45.0)]);
+    assert!(ObjectReader::from_bytes(b"<< /A").next_object().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_token_too_long() -> Result<()> {
+    let blob = vec![b'a'; 10 * 1024 * 1024];
+    let start = std::time::Instant::now();
+    match parse_object(&blob) {
+        Err(PDFError::TokenTooLong { limit, at_offset }) => {
+            assert_eq!(limit, 4 * 1024 * 1024);
+            assert_eq!(at_offset, 0);
+        }
+        _ => panic!("expected TokenTooLong"),
+    }
+    // The offset is the one of the token start, the `/` of a name
+    let name = [b"  /".as_slice(), &blob].concat();
+    assert!(matches!(parse_object(&name), Err(PDFError::TokenTooLong { at_offset: 2, .. })));
+    assert!(start.elapsed().as_secs() < 10);
+    // Strings have their own, larger limit
+    let string = [b"(".as_slice(), &blob, b")"].concat();
+    assert_eq!(parse_object(&string)?.as_string().map(|it| it.as_bytes().len()), Some(blob.len()));
+    Ok(())
+}
+
+#[test]
+fn test_object_equality() -> Result<()> {
+    // Dictionaries compare regardless of the key order, arrays element-wise
+    let a = parse_object(b"<< /A [1 (x) << /B 2 0 R >>] /C /Name >>")?;
+    let b = parse_object(b"<< /C /Name /A [1 <78> << /B 2 0 R >>] >>")?;
+    assert!(a == b);
+    assert!(a != parse_object(b"<< /C /Name /A [1 (x) << /B 2 1 R >>] >>")?);
+    assert!(a != parse_object(b"<< /C /Name /A [(x) 1 << /B 2 0 R >>] >>")?);
+    assert!(a != parse_object(b"<< /C /Name >>")?);
+    // Numbers compare by variant and value
+    assert!(parse_object(b"1")? == parse_object(b"1")?);
+    assert!(parse_object(b"1")? != parse_object(b"1.0")?);
+    assert!(parse_object(b"-1")? != parse_object(b"1")?);
+    // Streams compare by dictionary and raw data
+    let stream = parse_object(b"<< /Length 3 >>\nstream\nabc\nendstream")?;
+    assert!(stream == parse_object(b"<< /Length 3 >>\nstream\nabc\nendstream")?);
+    assert!(stream != parse_object(b"<< /Length 3 >>\nstream\nabd\nendstream")?);
+    assert!(parse_object(b"3 0 obj 5 endobj")? != parse_object(b"4 0 obj 5 endobj")?);
+    Ok(())
+}
+
+#[test]
+fn test_into_accessors() -> Result<()> {
+    let (obj_num, gen_num, value) = parse_object(b"7 0 obj\n<< /Type /Page >>\nendobj")?.into_indirect().unwrap();
+    assert_eq!((obj_num, gen_num), (7, 0));
+    let dict = value.into_dict().unwrap();
+    assert_eq!(dict.get_named_value("Type"), Some("Page"));
+    let elements = parse_object(b"[/A (b)]")?.into_array().unwrap();
+    assert_eq!(elements.len(), 2);
+    let bytes = elements.into_iter().nth(1).and_then(PDFObject::into_string_bytes);
+    assert_eq!(bytes.as_deref(), Some(b"b".as_slice()));
+    let stream = PDFObject::from(Stream::new(DictBuilder::new().build(), b"data".to_vec()));
+    assert_eq!(stream.into_stream().map(|it| it.as_slice().to_vec()), Some(b"data".to_vec()));
+    // The wrong kind yields None
+    assert!(parse_object(b"42")?.into_dict().is_none());
+    assert!(parse_object(b"<< >>")?.into_array().is_none());
+    assert!(parse_object(b"/Name")?.into_string_bytes().is_none());
+    assert!(parse_object(b"null")?.into_indirect().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_object_clone() -> Result<()> {
+    let original = parse_object(b"<< /Kids [<< /A 1 >> 2 0 R] /S << /Length 3 >>\nstream\nabc\nendstream >>")?;
+    let copy = original.clone();
+    assert!(copy == original);
+    // Changing the clone leaves the original untouched
+    let mut copy = copy.into_dict().unwrap();
+    copy.remove("Kids");
+    let copy = PDFObject::Dict(copy);
+    assert!(copy != original);
+    assert!(original.as_dict().unwrap().contain("Kids"));
+    let stream = original.as_dict().unwrap().get("S").unwrap().clone();
+    assert_eq!(stream.as_stream().unwrap().as_slice(), b"abc");
+    Ok(())
+}
+
+#[test]
+fn test_build_and_write_objects() -> Result<()> {
+    let resources = DictBuilder::new()
+        .entry("Font", DictBuilder::new().reference("F1", (5, 0)).build())
+        .entry("ProcSet", ArrayBuilder::new().name("PDF").name("Text").build())
+        .build();
+    let page = DictBuilder::new()
+        .name("Type", "Page")
+        .reference("Parent", (2, 0))
+        .entry("Resources", resources)
+        .entry("Rotate", -90)
+        .entry("UserUnit", 1.0)
+        .entry("Hidden", false)
+        .string("Title", "Name (with \\ escapes)\n")
+        .entry("Kids", ArrayBuilder::new().push(PDFObject::Null).push(PDFString::hexadecimal(vec![0, 255])).build())
+        .build();
+    let mut dict = page.clone();
+    assert!(dict.insert("Rotate", 90) == Some(PDFObject::from(-90)));
+    assert!(dict.remove("Hidden").is_some());
+    *dict.entry("Count").or_insert(PDFObject::from(0)) = PDFObject::from(3);
+    assert_eq!(dict.len(), page.len());
+    assert!(dict.get("Count").unwrap().as_number().is_some());
+
+    let object = PDFObject::Dict(page);
+    let bytes = writer::to_bytes(&object);
+    assert!(parse_object(&bytes)? == object, "{}", String::from_utf8_lossy(&bytes));
+    // The output is deterministic
+    assert_eq!(bytes, writer::to_bytes(&object.clone()));
+    Ok(())
+}
+
+#[test]
+fn test_stream_helpers() -> Result<()> {
+    let stream = |dict: DictBuilder| Stream::new(dict.build(), b"data".to_vec());
+    let image = stream(DictBuilder::new().name("Type", "XObject").name("Subtype", "Image").entry("Length", 4));
+    assert!(image.is_image() && !image.is_form_xobject());
+    assert_eq!(image.declared_length(), Some(4));
+    let form = stream(DictBuilder::new().name("Subtype", "Form").reference("Length", (9, 0)));
+    assert!(form.is_form_xobject() && !form.is_image());
+    assert_eq!(form.declared_length(), None);
+    assert!(stream(DictBuilder::new().name("Type", "Metadata")).is_metadata());
+    assert!(stream(DictBuilder::new().name("Type", "ObjStm")).is_object_stream());
+    let xref = stream(DictBuilder::new().name("Type", "XRef"));
+    assert!(xref.is_xref_stream() && !xref.is_object_stream() && !xref.is_metadata());
+
+    // A single filter and its parameters are normalized to vectors
+    let parms = DictBuilder::new().entry("Predictor", 12).build();
+    let single = stream(DictBuilder::new().name("Filter", "FlateDecode").entry("DecodeParms", parms.clone()));
+    assert_eq!(single.filters(), vec!["FlateDecode".to_string()]);
+    assert!(single.decode_parms() == vec![Some(&parms)]);
+    let filters = ArrayBuilder::new().name("ASCII85Decode").name("FlateDecode").build();
+    let chained = stream(DictBuilder::new().entry("Filter", filters.clone()));
+    assert_eq!(chained.filters().len(), 2);
+    assert!(chained.decode_parms() == vec![None, None]);
+    let chained_parms = ArrayBuilder::new().push(PDFObject::Null).push(parms.clone()).build();
+    let chained = stream(DictBuilder::new().entry("Filter", filters).entry("DecodeParms", chained_parms));
+    assert!(chained.decode_parms() == vec![None, Some(&parms)]);
+    assert!(chained.dict() == chained.get_metadata());
+    Ok(())
+}
+
@@ -0,0 +1,119 @@
+mod common;
+
+use pdf_rs::document::PDFDocument;
+use pdf_rs::error::Result;
+use pdf_rs::options::ParseOptions;
+use pdf_rs::page::Page;
+use pdf_rs::page_count::CountResult;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// A span seen by the subscriber with the fields recorded so far.
+#[derive(Clone, Default)]
+struct SpanData {
+    name: &'static str,
+    fields: HashMap<&'static str, String>,
+}
+
+impl Visit for SpanData {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.fields.insert(field.name(), format!("{:?}", value));
+    }
+}
+
+/// A subscriber collecting every span in creation order, the ID of a span is its
+/// position plus one.
+#[derive(Clone, Default)]
+struct CollectingSubscriber {
+    spans: Arc<Mutex<Vec<SpanData>>>,
+}
+
+impl CollectingSubscriber {
+    fn spans(&self, name: &str) -> Vec<SpanData> {
+        self.spans.lock().unwrap().iter().filter(|it| it.name == name).cloned().collect()
+    }
+}
+
+impl Subscriber for CollectingSubscriber {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut span = SpanData { name: attrs.metadata().name(), fields: HashMap::new() };
+        attrs.record(&mut span);
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(span);
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, id: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        values.record(&mut spans[id.into_u64() as usize - 1]);
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn test_parse_spans() -> Result<()> {
+    let subscriber = CollectingSubscriber::default();
+    let result = tracing::subscriber::with_default(subscriber.clone(), || -> Result<String> {
+        let mut document = PDFDocument::open(PathBuf::from("document/pdfreference1.0.pdf"))?;
+        Page::new(document.get_page_ids()[0]).text(&mut document)
+    });
+    assert!(!result?.is_empty());
+
+    for name in ["pdf.header", "pdf.xref", "pdf.xref_section", "pdf.catalog", "pdf.page_tree", "pdf.outline"] {
+        assert!(!subscriber.spans(name).is_empty(), "{}", name);
+    }
+    // The first page xref of the linearized file, objects 1046 to 4962, and the main
+    // xref table of objects 0 to 1045
+    let sections = subscriber.spans("pdf.xref_section");
+    let entries = sections.iter().map(|it| it.fields["entries"].clone()).collect::<Vec<_>>();
+    assert_eq!(entries, ["3917", "1046"]);
+    assert_eq!(subscriber.spans("pdf.xref")[0].fields["sections"], "2");
+    assert_eq!(subscriber.spans("pdf.header")[0].fields["header_offset"], "0");
+    assert_eq!(subscriber.spans("pdf.page_tree")[0].fields["pages"], "230");
+
+    let decodes = subscriber.spans("pdf.stream_decode");
+    assert!(decodes.iter().any(|it| it.fields["filter"] == "\"FlateDecode\""));
+    let page = &subscriber.spans("pdf.page_text")[0];
+    assert!(page.fields["chars"].parse::<u64>().unwrap() > 0);
+    Ok(())
+}
+
+#[test]
+fn test_count_pages_span() -> Result<()> {
+    let subscriber = CollectingSubscriber::default();
+    let counts = tracing::subscriber::with_default(subscriber.clone(), || -> Result<Vec<CountResult>> {
+        // The root /Count of 5000 disagrees with the 1,000 pages below it, so pages are walked
+        let bytes = common::build_pdf(&common::page_tree_objects(10, 100, 5000));
+        let mut document = PDFDocument::from_bytes_with_options(bytes, ParseOptions::lenient())?;
+        let walked = document.count_pages_up_to(50)?;
+        let bytes = common::build_pdf(&common::page_tree_objects(10, 100, 1000));
+        let mut document = PDFDocument::from_bytes(bytes)?;
+        Ok(vec![walked, document.count_pages_up_to(50)?])
+    });
+    assert_eq!(counts?, [CountResult::AtLeast(50), CountResult::AtLeast(50)]);
+
+    let spans = subscriber.spans("pdf.count_pages");
+    let nodes = spans.iter().map(|it| it.fields["nodes"].parse::<u64>().unwrap()).collect::<Vec<_>>();
+    // The root, its 10 kids and the first 50 pages of the first kid
+    assert_eq!(nodes[0], 61);
+    // The root and its kids agree, nothing below them is read
+    assert_eq!(nodes[1], 11);
+    assert!(spans.iter().all(|it| it.fields["pages"] == "50" && it.fields["limit"] == "50"));
+    Ok(())
+}